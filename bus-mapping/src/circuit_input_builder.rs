@@ -21,11 +21,11 @@ use crate::{
 };
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
 pub use block::{Block, BlockContext};
-pub use call::{Call, CallContext, CallKind};
+pub use call::{Call, CallContext, CallKind, ControlFrame, FunctionFrame, SuspendedFrame};
 use core::fmt::Debug;
 use eth_types::{
     self,
-    evm_types::OpcodeId,
+    evm_types::{MemoryAddress, OpcodeId, WasmFuelSchedule},
     geth_types,
     sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
     Address, GethExecStep, GethExecTrace, ToBigEndian, ToWord, Word, H256, U256,
@@ -87,6 +87,9 @@ pub struct CircuitsParams {
     /// calculated, so the same circuit will not be able to prove different
     /// witnesses.
     pub max_keccak_rows: usize,
+    /// Fuel charged for WASM instructions. The trace must charge the same gas
+    /// and the EVM circuit loads it into its fixed fuel table.
+    pub wasm_fuel_schedule: WasmFuelSchedule,
 }
 
 impl Default for CircuitsParams {
@@ -106,6 +109,7 @@ impl Default for CircuitsParams {
             max_evm_rows: 0,
             max_keccak_rows: 0,
             max_rlp_rows: 1000,
+            wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
         }
     }
 }
@@ -791,22 +795,16 @@ pub fn keccak_inputs_tx_circuit(
     Ok(inputs)
 }
 
-/// Retrieve the init_code from memory for {CREATE, CREATE2}
-pub fn get_create_init_code<'a>(
-    call_ctx: &'a CallContext,
-    step: &GethExecStep,
-) -> Result<&'a [u8], Error> {
-    let offset = step.stack.nth_last(1)?.low_u64() as usize;
-    let length = step.stack.nth_last(2)?.as_usize();
-
-    let mem_len = call_ctx.memory.0.len();
-    if offset >= mem_len {
-        return Ok(&[]);
-    }
-
-    let offset_end = offset.checked_add(length).unwrap_or(mem_len);
+/// Retrieve the init_code from memory for {CREATE, CREATE2}, the salt pointer
+/// of CREATE2 sits between the result pointer and the code length
+pub fn get_create_init_code(step: &GethExecStep) -> Result<Vec<u8>, Error> {
+    let salt_args = usize::from(step.op == OpcodeId::CREATE2);
+    let length = step.stack.nth_last(1 + salt_args)?.as_usize();
+    let offset = step.stack.nth_last(2 + salt_args)?.as_usize();
 
-    Ok(&call_ctx.memory.0[offset..offset_end])
+    Ok(step
+        .global_memory
+        .read_chunk(MemoryAddress(offset), MemoryAddress(length)))
 }
 
 /// Retrieve the memory offset and length of call.
@@ -4,6 +4,7 @@ use eth_types::{
     evm_types::{Memory, OpcodeId},
     Address, Hash, Word,
 };
+use std::collections::HashSet;
 
 /// Type of a *CALL*/CREATE* Function.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -127,6 +128,69 @@ impl Call {
     }
 }
 
+/// Structured control frame opened by `block`, `loop` or `if`. WASM validation
+/// fixes the operand stack height at every instruction, so a frame is fully
+/// described by where it starts and the params and results of its blocktype.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ControlFrame {
+    /// Whether a branch to this frame jumps back to its start
+    pub is_loop: bool,
+    /// Program counter of the instruction that opened the frame
+    pub pc: u64,
+    /// Program counter of the `end` closing the frame
+    pub end_pc: u64,
+    /// Number of params of the blocktype
+    pub num_params: usize,
+    /// Number of results of the blocktype
+    pub num_results: usize,
+    /// Stack size at the frame entry with the params, without the `if`
+    /// condition
+    pub stack_size: usize,
+}
+
+impl ControlFrame {
+    /// Number of values a branch to this frame carries, a loop is re-entered
+    /// with its params
+    pub fn arity(&self) -> usize {
+        if self.is_loop {
+            self.num_params
+        } else {
+            self.num_results
+        }
+    }
+
+    /// Stack pointer under the params of the frame, the frame is left with its
+    /// results right above it
+    pub fn stack_pointer(&self) -> usize {
+        1024 - self.stack_size + self.num_params
+    }
+}
+
+/// WASM function running in a [`Call`], the entry function when no `call`
+/// instruction was executed yet. Its params sit on the stack from
+/// `locals_base` upwards, its declared locals live in their own rw target.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FunctionFrame {
+    /// Function index plus one, zero for the entry function
+    pub function_id: u64,
+    /// Stack height under the first param
+    pub locals_base: usize,
+    /// Rw counter of the step that entered the function minus the call id,
+    /// zero for the entry function
+    pub locals_frame: usize,
+}
+
+/// Caller of a running WASM function, restored when the function returns.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SuspendedFrame {
+    /// Function frame of the caller
+    pub function_frame: FunctionFrame,
+    /// Program counter of the instruction following the call
+    pub return_pc: u64,
+    /// Control frames the caller had open at the call
+    pub control_frames: Vec<ControlFrame>,
+}
+
 /// Context of a [`Call`].
 #[derive(Debug, Default)]
 pub struct CallContext {
@@ -143,6 +207,19 @@ pub struct CallContext {
     pub memory: Memory,
     /// return data buffer
     pub return_data: Vec<u8>,
+    /// Number of pages added to the linear memory by `memory.grow`
+    pub grown_memory_pages: u64,
+    /// Label stack of the structured control frames entered so far
+    pub control_frames: Vec<ControlFrame>,
+    /// WASM function frame currently running
+    pub function_frame: FunctionFrame,
+    /// Callers of the running function frame, the innermost last
+    pub suspended_frames: Vec<SuspendedFrame>,
+    /// Value of the `FunctionDepth` call context field, the number of nested
+    /// function frames the call depth limit is checked against
+    pub function_depth: u64,
+    /// Indices of the data segments dropped by `data.drop`
+    pub dropped_data: HashSet<u32>,
 }
 
 impl CallContext {
@@ -3,7 +3,7 @@
 use std::marker::PhantomData;
 
 use crate::{
-    circuit_input_builder::CallContext, error::ExecError, exec_trace::OperationRef,
+    circuit_input_builder::{CallContext, ControlFrame, FunctionFrame}, error::ExecError, exec_trace::OperationRef,
     operation::RWCounter, precompile::PrecompileCalls,
 };
 use eth_types::{
@@ -57,6 +57,12 @@ pub struct ExecStep {
     pub max_stack_height: u32,
     ///
     pub num_locals: u32,
+    /// Control frame opened, closed or targeted by the step
+    pub control_frame: Option<ControlFrame>,
+    /// Function frame left by a return to its caller
+    pub function_frame: Option<FunctionFrame>,
+    /// Immediate arguments of the instruction
+    pub params: Vec<u64>,
 }
 
 impl ExecStep {
@@ -87,6 +93,9 @@ impl ExecStep {
             function_index: 0,
             max_stack_height: 0,
             num_locals: 0,
+            control_frame: None,
+            function_frame: None,
+            params: step.params.clone(),
         }
     }
 
@@ -125,6 +134,9 @@ impl Default for ExecStep {
             function_index: 0,
             num_locals: 0,
             max_stack_height: 0,
+            control_frame: None,
+            function_frame: None,
+            params: Vec::new(),
         }
     }
 }
@@ -202,6 +214,9 @@ pub enum CopyDataType {
     RlcAcc,
     /// When the source of the copy is a call to a precompiled contract.
     Precompile(PrecompileCalls),
+    /// When the source for the copy event is a constant byte repeated over the
+    /// whole range, as for WASM `memory.fill`. The source ID holds the byte.
+    Constant,
 }
 impl CopyDataType {
     /// Get variants that represent a precompile call.
@@ -209,7 +224,7 @@ impl CopyDataType {
         PrecompileCalls::iter().map(Self::Precompile).collect()
     }
 }
-const NUM_COPY_DATA_TYPES: usize = 15usize;
+const NUM_COPY_DATA_TYPES: usize = 16usize;
 pub struct CopyDataTypeIter {
     idx: usize,
     back_idx: usize,
@@ -233,6 +248,7 @@ impl CopyDataTypeIter {
             12usize => Some(CopyDataType::Precompile(PrecompileCalls::Bn128Mul)),
             13usize => Some(CopyDataType::Precompile(PrecompileCalls::Bn128Pairing)),
             14usize => Some(CopyDataType::Precompile(PrecompileCalls::Blake2F)),
+            15usize => Some(CopyDataType::Constant),
             _ => None,
         }
     }
@@ -300,6 +316,7 @@ impl From<CopyDataType> for usize {
             CopyDataType::TxLog => 4,
             CopyDataType::RlcAcc => 5,
             CopyDataType::Precompile(prec_call) => 5 + usize::from(prec_call),
+            CopyDataType::Constant => 15,
         }
     }
 }
@@ -314,6 +331,7 @@ impl From<&CopyDataType> for u64 {
             CopyDataType::TxLog => 4,
             CopyDataType::RlcAcc => 5,
             CopyDataType::Precompile(prec_call) => 5 + u64::from(*prec_call),
+            CopyDataType::Constant => 15,
         }
     }
 }
@@ -372,6 +390,10 @@ pub struct CopyEvent {
     pub rw_counter_start: RWCounter,
     /// Represents the list of (bytes, is_code) copied during this copy event
     pub bytes: Vec<(u8, bool)>,
+    /// Whether the bytes are copied from the highest address down, as a WASM
+    /// `memory.copy` into an overlapping higher range needs. `bytes` stay in
+    /// address order.
+    pub is_reverse: bool,
 }
 
 impl CopyEvent {
@@ -390,10 +412,24 @@ impl CopyEvent {
         self.rw_counter_increase(self.bytes.len() * 2)
     }
 
+    /// Offset from the source and destination start addresses of the byte
+    /// copied at step index
+    pub fn addr_offset(&self, step_index: usize) -> u64 {
+        let offset = u64::try_from(step_index).unwrap() / 2;
+        if self.is_reverse {
+            u64::try_from(self.bytes.len()).unwrap() - 1 - offset
+        } else {
+            offset
+        }
+    }
+
     // increase in rw counter from the start of the copy event to step index
     fn rw_counter_increase(&self, step_index: usize) -> u64 {
         let source_rw_increase = match self.src_type {
-            CopyDataType::Bytecode | CopyDataType::TxCalldata | CopyDataType::Precompile(_) => 0,
+            CopyDataType::Bytecode
+            | CopyDataType::TxCalldata
+            | CopyDataType::Precompile(_)
+            | CopyDataType::Constant => 0,
             CopyDataType::Memory => std::cmp::min(
                 u64::try_from(step_index + 1).unwrap() / 2,
                 self.src_addr_end
@@ -405,7 +441,7 @@ impl CopyEvent {
         let destination_rw_increase = match self.dst_type {
             CopyDataType::RlcAcc | CopyDataType::Bytecode | CopyDataType::Precompile(_) => 0,
             CopyDataType::TxLog | CopyDataType::Memory => u64::try_from(step_index).unwrap() / 2,
-            CopyDataType::TxCalldata | CopyDataType::Padding => {
+            CopyDataType::TxCalldata | CopyDataType::Padding | CopyDataType::Constant => {
                 unreachable!()
             }
         };
@@ -22,12 +22,15 @@ use crate::{
     state_db::{CodeDB, StateDB},
     Error,
 };
+use eth_types::bytecode::{
+    wasm_data_segment, wasm_indirect_call_target, wasm_memory_limits, WASM_PAGE_SIZE,
+};
 use eth_types::{evm_types::{
-    Gas, GasCost, MemoryAddress, OpcodeId, StackAddress,
-}, Address, Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H256, U256, StackWord, ToU256, ToAddress};
+    Gas, GasCost, MemoryAddress, OpcodeId, StackAddress, MAX_WASM_CALL_DEPTH,
+}, Address, Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H256, U256, StackWord, ToU256};
 use ethers_core::utils::{get_contract_address, get_create2_address, keccak256};
 use std::cmp::max;
-use crate::operation::GlobalOp;
+use crate::operation::{ControlFrameOp, DataDropOp, GlobalOp, LocalOp};
 
 /// Reference to the internal state of the CircuitInputBuilder in a particular
 /// [`ExecStep`].
@@ -293,29 +296,96 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
-    ///
+    /// Push a write of a declared local of the running WASM function.
     pub fn local_write(
+        &mut self,
+        step: &mut ExecStep,
+        local_index: u32,
+        value: StackWord,
+    ) -> Result<(), Error> {
+        let frame_id = self.call()?.call_id + self.call_ctx()?.function_frame.locals_frame;
+        self.push_op(step, RW::WRITE, LocalOp::new(frame_id, local_index, value));
+        Ok(())
+    }
+
+    /// Push a read of a declared local of the running WASM function.
+    pub fn local_read(
+        &mut self,
+        step: &mut ExecStep,
+        local_index: u32,
+        value: StackWord,
+    ) -> Result<(), Error> {
+        let frame_id = self.call()?.call_id + self.call_ctx()?.function_frame.locals_frame;
+        self.push_op(step, RW::READ, LocalOp::new(frame_id, local_index, value));
+        Ok(())
+    }
+
+    /// Push a write of the stack pointer under the params of the control frame
+    /// opened by the instruction at `pc` in the running WASM function.
+    pub fn control_frame_write(
+        &mut self,
+        step: &mut ExecStep,
+        pc: u64,
+        stack_pointer: usize,
+    ) -> Result<(), Error> {
+        let frame_id = self.call()?.call_id + self.call_ctx()?.function_frame.locals_frame;
+        self.push_op(step, RW::WRITE, ControlFrameOp::new(frame_id, pc, stack_pointer));
+        Ok(())
+    }
+
+    /// Push a read of the stack pointer under the params of the control frame
+    /// opened by the instruction at `pc` in the running WASM function.
+    pub fn control_frame_read(
+        &mut self,
+        step: &mut ExecStep,
+        pc: u64,
+        stack_pointer: usize,
+    ) -> Result<(), Error> {
+        let frame_id = self.call()?.call_id + self.call_ctx()?.function_frame.locals_frame;
+        self.push_op(step, RW::READ, ControlFrameOp::new(frame_id, pc, stack_pointer));
+        Ok(())
+    }
+
+    /// Push a read of the dropped flag of a data segment of the running WASM
+    /// module and return the flag.
+    pub fn data_drop_read(&mut self, step: &mut ExecStep, data_index: u32) -> Result<bool, Error> {
+        let call_id = self.call()?.call_id;
+        let is_dropped = self.call_ctx()?.dropped_data.contains(&data_index);
+        self.push_op(step, RW::READ, DataDropOp::new(call_id, data_index, is_dropped));
+        Ok(is_dropped)
+    }
+
+    /// Push a write dropping a data segment of the running WASM module.
+    pub fn data_drop_write(&mut self, step: &mut ExecStep, data_index: u32) -> Result<(), Error> {
+        let call_id = self.call()?.call_id;
+        self.call_ctx_mut()?.dropped_data.insert(data_index);
+        self.push_op(step, RW::WRITE, DataDropOp::new(call_id, data_index, true));
+        Ok(())
+    }
+
+    /// Push a write of a param of the running WASM function, which is the stack
+    /// slot at `address`.
+    pub fn param_write(
         &mut self,
         step: &mut ExecStep,
         address: StackAddress,
         local_index: usize,
         value: StackWord,
     ) -> Result<(), Error> {
-        // println!("set local, value={}, addr={}, local_index={}", value, address.0, local_index);
         let call_id = self.call()?.call_id;
         self.push_op(step, RW::WRITE, StackOp::new_with_local_index(call_id, address, value, local_index));
         Ok(())
     }
 
-    ///
-    pub fn local_read(
+    /// Push a read of a param of the running WASM function, which is the stack
+    /// slot at `address`.
+    pub fn param_read(
         &mut self,
         step: &mut ExecStep,
         address: StackAddress,
         local_index: usize,
         value: StackWord,
     ) -> Result<(), Error> {
-        // println!("get local, value={}, addr={}, local_index={}", value, address.0, local_index);
         let call_id = self.call()?.call_id;
         self.push_op(step, RW::READ, StackOp::new_with_local_index(call_id, address, value, local_index));
         Ok(())
@@ -846,11 +916,11 @@ impl<'a> CircuitInputStateRef<'a> {
     }
 
     /// Return the contract address of a CREATE2 step.  This is calculated
-    /// deterministically from the arguments in the stack.
+    /// deterministically from the init code and the salt in linear memory.
     pub(crate) fn create2_address(&self, step: &GethExecStep) -> Result<Address, Error> {
-        let salt = step.stack.nth_last(3)?;
-        let call_ctx = self.call_ctx()?;
-        let init_code = get_create_init_code(call_ctx, step)?.to_vec();
+        let salt_offset = step.stack.nth_last(1)?;
+        let salt = step.global_memory.read_u256(salt_offset)?;
+        let init_code = get_create_init_code(step)?;
         let address =
             get_create2_address(self.call()?.address, salt.to_be_bytes().to_vec(), init_code);
         log::trace!(
@@ -888,7 +958,6 @@ impl<'a> CircuitInputStateRef<'a> {
             .unwrap();
         let kind = CallKind::try_from(step.op)?;
         let caller = self.call()?;
-        let caller_ctx = self.call_ctx()?;
 
         let (caller_address, address, value) = match kind {
             CallKind::Call => {
@@ -899,18 +968,18 @@ impl<'a> CircuitInputStateRef<'a> {
                 (caller.address, address, value)
             }
             CallKind::CallCode => {
-                let value_offset = step.stack.nth_last(7)?;
+                let value_offset = step.stack.nth_last(5)?;
                 let value = step.global_memory.read_u256(value_offset)?;
                 (caller.address, caller.address, value)
             },
             CallKind::DelegateCall => (caller.caller_address, caller.address, caller.value),
             CallKind::StaticCall => {
-                let address_offset = step.stack.nth_last(6)?;
+                let address_offset = step.stack.nth_last(5)?;
                 let address = step.global_memory.read_address(address_offset)?;
                 (caller.address, address, Word::zero())
             },
             CallKind::Create => {
-                let value_offset = step.stack.nth_last(2)?;
+                let value_offset = step.stack.nth_last(3)?;
                 let value = step.global_memory.read_u256(value_offset)?;
                 (caller.address, self.create_address()?, value)
             },
@@ -923,16 +992,21 @@ impl<'a> CircuitInputStateRef<'a> {
 
         let (code_source, code_hash) = match kind {
             CallKind::Create | CallKind::Create2 => {
-                let init_code = get_create_init_code(caller_ctx, step)?.to_vec();
+                let init_code = get_create_init_code(step)?;
                 let code_hash = self.code_db.insert(init_code);
                 (CodeSource::Memory, code_hash)
             }
             _ => {
+                // CALLCODE has the value pointer between the call data and the
+                // callee pointers, DELEGATECALL has none
                 let code_address = match kind {
-                    CallKind::CallCode | CallKind::DelegateCall => {
+                    CallKind::CallCode => {
                         let address_offset = step.stack.nth_last(6)?;
-                        let address = step.global_memory.read_address(address_offset)?;
-                        address
+                        step.global_memory.read_address(address_offset)?
+                    }
+                    CallKind::DelegateCall => {
+                        let address_offset = step.stack.nth_last(5)?;
+                        step.global_memory.read_address(address_offset)?
                     }
                     _ => address,
                 };
@@ -1374,10 +1448,98 @@ impl<'a> CircuitInputStateRef<'a> {
             return Ok(Some(ExecError::InvalidOpcode));
         }
 
+        // the depth is the `FunctionDepth` counter the circuit reads, the trap
+        // is proven against the value the calls and returns wrote
+        if matches!(step.op, OpcodeId::Call | OpcodeId::CallIndirect)
+            && self.call_ctx()?.function_depth >= MAX_WASM_CALL_DEPTH
+        {
+            return Ok(Some(ExecError::CallStackExhausted));
+        }
+
+        if step.op == OpcodeId::Unreachable {
+            return Ok(Some(ExecError::Unreachable));
+        }
+
+        if matches!(
+            step.op,
+            OpcodeId::I32DivS
+                | OpcodeId::I32DivU
+                | OpcodeId::I32RemS
+                | OpcodeId::I32RemU
+                | OpcodeId::I64DivS
+                | OpcodeId::I64DivU
+                | OpcodeId::I64RemS
+                | OpcodeId::I64RemU
+        ) {
+            let rhs = step.stack.nth_last(0)?.as_u64();
+            let lhs = step.stack.nth_last(1)?.as_u64();
+            if rhs == 0 {
+                return Ok(Some(ExecError::IntegerDivideByZero));
+            }
+            // the remainder of the same operands is zero and does not trap
+            let is_overflow = match step.op {
+                OpcodeId::I32DivS => lhs == i32::MIN as u32 as u64 && rhs == u32::MAX as u64,
+                OpcodeId::I64DivS => lhs == i64::MIN as u64 && rhs == u64::MAX,
+                _ => false,
+            };
+            if is_overflow {
+                return Ok(Some(ExecError::IntegerOverflow));
+            }
+        }
+
+        if let Some(len) = step.op.wasm_memory_access_len() {
+            let code = self.code(self.call()?.code_hash)?;
+            let (initial_pages, _) = wasm_memory_limits(&code).unwrap_or_default();
+            let pages = initial_pages + self.call_ctx()?.grown_memory_pages;
+            let address = step.stack.nth_last(step.op.is_wasm_store() as usize)?.as_u64();
+            if address + step.params[0] + len > pages * WASM_PAGE_SIZE {
+                return Ok(Some(ExecError::MemoryOutOfBounds));
+            }
+        }
+
+        // bulk memory instructions trap before writing anything when either
+        // range ends past its memory or data segment, a dropped segment is empty
+        if matches!(
+            step.op,
+            OpcodeId::MemoryCopy | OpcodeId::MemoryFill | OpcodeId::MemoryInit
+        ) {
+            let code = self.code(self.call()?.code_hash)?;
+            let (initial_pages, _) = wasm_memory_limits(&code).unwrap_or_default();
+            let memory_size =
+                (initial_pages + self.call_ctx()?.grown_memory_pages) * WASM_PAGE_SIZE;
+            let length = step.stack.nth_last(0)?.as_u64();
+            let src = step.stack.nth_last(1)?.as_u64();
+            let dst = step.stack.nth_last(2)?.as_u64();
+            let src_size = match step.op {
+                OpcodeId::MemoryCopy => Some(memory_size),
+                OpcodeId::MemoryInit => {
+                    let data_index = step.params[0];
+                    let (segment_start, segment_end) = wasm_data_segment(&code, data_index)
+                        .ok_or(Error::InternalError("memory.init of an unknown data segment"))?;
+                    let is_dropped = self.call_ctx()?.dropped_data.contains(&(data_index as u32));
+                    Some(if is_dropped { 0 } else { segment_end - segment_start })
+                }
+                _ => None,
+            };
+            if dst + length > memory_size || src_size.map_or(false, |size| src + length > size) {
+                return Ok(Some(ExecError::MemoryOutOfBounds));
+            }
+        }
+
         if let Some(error) = &step.error {
             return Ok(Some(get_step_reported_error(&step.op, error)));
         }
 
+        // an error reported by the tracer, like running out of gas, takes
+        // precedence over resolving the call through the table
+        if step.op == OpcodeId::CallIndirect {
+            let code = self.code(self.call()?.code_hash)?;
+            let elem_index = step.stack.last()?.as_u64();
+            if wasm_indirect_call_target(&code, step.params[0], elem_index).is_none() {
+                return Ok(Some(ExecError::InvalidIndirectCall));
+            }
+        }
+
         let call = self.call()?;
 
         if matches!(next_step, None) {
@@ -1402,7 +1564,13 @@ impl<'a> CircuitInputStateRef<'a> {
         let next_result = next_step
             .map(|s| {
                 let result_offset = step.stack.last().unwrap_or_else(|_| StackWord::zero());
-                StackWord::from(s.global_memory.read_u8(result_offset).unwrap_or_default())
+                // CREATE and CREATE2 report the created address instead of a status byte
+                if matches!(step.op, OpcodeId::CREATE | OpcodeId::CREATE2) {
+                    let address = s.global_memory.read_address(result_offset).unwrap_or_default();
+                    StackWord::from(!address.is_zero() as u64)
+                } else {
+                    StackWord::from(s.global_memory.read_u8(result_offset).unwrap_or_default())
+                }
             })
             .unwrap_or_else(StackWord::zero);
 
@@ -1413,8 +1581,12 @@ impl<'a> CircuitInputStateRef<'a> {
                 let value_offset = step.stack.nth_last(5)?;
                 step.global_memory.read_u256(value_offset)?
             }
-            OpcodeId::CREATE | OpcodeId::CREATE2 => {
-                let value_offset = step.stack.nth_last(2)?;
+            OpcodeId::CREATE => {
+                let value_offset = step.stack.nth_last(3)?;
+                step.global_memory.read_u256(value_offset)?
+            }
+            OpcodeId::CREATE2 => {
+                let value_offset = step.stack.nth_last(4)?;
                 step.global_memory.read_u256(value_offset)?
             }
             _ => Word::zero(),
@@ -1456,8 +1628,8 @@ impl<'a> CircuitInputStateRef<'a> {
             } else {
                 // Return from a {CREATE, CREATE2} with a failure, via RETURN
                 if call.is_create() {
-                    let offset = step.stack.nth_last(0)?;
-                    let length = step.stack.nth_last(1)?;
+                    let length = step.stack.nth_last(0)?;
+                    let offset = step.stack.nth_last(1)?;
                     if length > StackWord::from(0x6000u64) {
                         return Ok(Some(ExecError::MaxCodeSizeExceeded));
                     } else if length > StackWord::zero()
@@ -1583,7 +1755,11 @@ impl<'a> CircuitInputStateRef<'a> {
                 step.op,
                 OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL
             ) {
-                let code_address = step.stack.nth_last(1)?.to_address();
+                let callee_offset = step.stack.nth_last(match step.op {
+                    OpcodeId::CALL | OpcodeId::CALLCODE => 6,
+                    _ => 5,
+                })?;
+                let code_address = step.global_memory.read_address(callee_offset)?;
                 if is_precompiled(&code_address) {
                     // Log the precompile address and gas left. Since this failure is mainly caused
                     // by out of gas.
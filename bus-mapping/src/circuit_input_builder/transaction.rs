@@ -1,12 +1,12 @@
 //! Transaction & TransactionContext utility module.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
 use eth_types::{
-    evm_types::{gas_utils::tx_data_gas_cost, Memory},
+    evm_types::{gas_utils::tx_data_gas_cost, Memory, OpcodeId},
     geth_types,
     geth_types::{get_rlp_unsigned, TxType},
-    Address, GethExecTrace, Signature, Word, H256,
+    Address, GethExecStep, GethExecTrace, Signature, Word, H256,
 };
 use ethers_core::utils::get_contract_address;
 
@@ -16,7 +16,7 @@ use crate::{
     Error,
 };
 
-use super::{call::ReversionGroup, Call, CallContext, CallKind, CodeSource, ExecStep};
+use super::{call::ReversionGroup, Call, CallContext, CallKind, CodeSource, ExecStep, FunctionFrame};
 
 /// Precision of transaction L1 fee
 pub const TX_L1_FEE_PRECISION: u64 = 1_000_000_000;
@@ -44,6 +44,18 @@ pub struct TransactionContext {
     pub(crate) reversion_groups: Vec<ReversionGroup>,
 }
 
+/// Read the result a call step wrote at its result pointer, which is the
+/// created address for CREATE/CREATE2 and a status byte otherwise.
+fn call_result_is_success(call_step: &GethExecStep, next_step: &GethExecStep) -> Result<bool, Error> {
+    let result_offset = call_step.stack.last()?;
+    Ok(match call_step.op {
+        OpcodeId::CREATE | OpcodeId::CREATE2 => {
+            !next_step.global_memory.read_address(result_offset)?.is_zero()
+        }
+        _ => next_step.global_memory.read_u8(result_offset)? != 0u8,
+    })
+}
+
 impl TransactionContext {
     /// Create a new Self.
     pub fn new(
@@ -64,13 +76,14 @@ impl TransactionContext {
                     // Emerge from call
                     } else if geth_step.depth - 1 == geth_next_step.depth {
                         let last_call_index = call_indices.pop().unwrap();
-                        let result_offset = geth_trace.struct_logs[last_call_index].stack.last()?;
-                        let is_success = geth_next_step.global_memory.read_u8(result_offset)? != 0u8;
+                        let is_success = call_result_is_success(
+                            &geth_trace.struct_logs[last_call_index],
+                            geth_next_step,
+                        )?;
                         call_is_success_map.insert(last_call_index, is_success);
                     // Callee with empty code
                     } else if CallKind::try_from(geth_step.op).is_ok() {
-                        let result_offset = geth_step.stack.last()?;
-                        let is_success = geth_next_step.global_memory.read_u8(result_offset)? != 0u8;
+                        let is_success = call_result_is_success(geth_step, geth_next_step)?;
                         call_is_success_map.insert(index, is_success);
                     }
                 }
@@ -176,6 +189,12 @@ impl TransactionContext {
             call_data,
             memory: Memory::default(),
             return_data: vec![],
+            grown_memory_pages: 0,
+            control_frames: vec![],
+            function_frame: FunctionFrame::default(),
+            suspended_frames: vec![],
+            function_depth: 0,
+            dropped_data: HashSet::new(),
         });
     }
 
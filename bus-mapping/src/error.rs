@@ -173,6 +173,20 @@ pub enum ExecError {
     PrecompileFailed,
     /// For CREATE, CREATE2
     NonceUintOverflow(NonceUintOverflowError),
+    /// For CALL_INDIRECT with a table slot out of bounds, a null reference or
+    /// a signature mismatch
+    InvalidIndirectCall,
+    /// For CALL and CALL_INDIRECT entering a function frame beyond the maximum
+    /// call depth
+    CallStackExhausted,
+    /// For UNREACHABLE, which always traps
+    Unreachable,
+    /// For the WASM integer divisions and remainders with a zero divisor
+    IntegerDivideByZero,
+    /// For the WASM signed divisions of the minimal integer by -1
+    IntegerOverflow,
+    /// For the WASM loads and stores beyond the current memory size
+    MemoryOutOfBounds,
 }
 
 // TODO: Move to impl block.
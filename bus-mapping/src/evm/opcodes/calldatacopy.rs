@@ -143,6 +143,7 @@ fn gen_copy_event(
         log_id: None,
         rw_counter_start,
         bytes: copy_steps,
+        is_reverse: false,
     })
 }
 
@@ -378,6 +378,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             log_id: None,
                             rw_counter_start,
                             bytes,
+                            is_reverse: false,
                         },
                     );
                 }
@@ -407,6 +408,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             log_id: None,
                             rw_counter_start,
                             bytes,
+                            is_reverse: false,
                         },
                     );
                 }
@@ -441,6 +443,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                             log_id: None,
                             rw_counter_start,
                             bytes,
+                            is_reverse: false,
                         },
                     );
                 }
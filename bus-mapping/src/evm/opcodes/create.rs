@@ -358,6 +358,7 @@ fn handle_copy(
             dst_addr: 0,
             log_id: None,
             bytes,
+            is_reverse: false,
         },
     );
 
@@ -78,6 +78,7 @@ impl Opcode for Sha3 {
                 log_id: None,
                 rw_counter_start,
                 bytes: steps,
+                is_reverse: false,
             },
         );
 
@@ -17,6 +17,9 @@ impl fmt::Debug for OperationRef {
                 Target::Memory => "Memory",
                 Target::Stack => "Stack",
                 Target::Global=> "Global",
+                Target::Local => "Local",
+                Target::DataDrop => "DataDrop",
+                Target::ControlFrame => "ControlFrame",
                 Target::Storage => "Storage",
                 Target::TxAccessListAccount => "TxAccessListAccount",
                 Target::TxAccessListAccountStorage => "TxAccessListAccountStorage",
@@ -92,6 +92,14 @@ pub enum Target {
     Stack,
     /// Means that target of the operation is the Global.
     Global,
+    /// Means that target of the operation is a Local of a WASM function frame.
+    Local,
+    /// Means that target of the operation is the dropped flag of a WASM data
+    /// segment.
+    DataDrop,
+    /// Means that target of the operation is a control frame of a WASM
+    /// function frame.
+    ControlFrame,
     /// Means the target of the operation is the Storage.
     Storage,
     /// Means the target of the operation is the TxAccessListAccount.
@@ -369,6 +377,238 @@ impl Ord for GlobalOp {
     }
 }
 
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) of a declared local
+/// of the WASM function frame `frame_id`. Parameters are not part of it, they
+/// stay in the stack slots the caller pushed them to.
+#[derive(Clone, PartialEq, Eq)]
+pub struct LocalOp {
+    /// Frame ID, the rw counter of the step that entered the function
+    pub frame_id: usize,
+    /// Local index
+    pub local_index: u32,
+    /// Value
+    pub value: StackWord,
+}
+
+impl Debug for LocalOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LocalOp { ")?;
+        f.write_fmt(format_args!(
+            "frame_id: {:?}, index: {:?}, val: 0x{:x}",
+            self.frame_id, self.local_index, self.value
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl LocalOp {
+    /// Create a new instance of a `LocalOp` from it's components.
+    pub const fn new(frame_id: usize, local_index: u32, value: StackWord) -> LocalOp {
+        LocalOp {
+            frame_id,
+            local_index,
+            value,
+        }
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::Local
+    }
+
+    /// Returns the frame id associated to this Operation.
+    pub const fn frame_id(&self) -> usize {
+        self.frame_id
+    }
+
+    /// Returns the local index associated to this Operation.
+    pub const fn address(&self) -> u32 {
+        self.local_index
+    }
+
+    /// Returns the [`Word`] read or written by this operation.
+    pub const fn value(&self) -> &StackWord {
+        &self.value
+    }
+}
+
+impl Op for LocalOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::Local(self)
+    }
+
+    fn reverse(&self) -> Self {
+        unreachable!("LocalOp can't be reverted")
+    }
+}
+
+impl PartialOrd for LocalOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LocalOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.frame_id, &self.local_index).cmp(&(&other.frame_id, &other.local_index))
+    }
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) of the flag telling
+/// whether `data.drop` dropped a data segment of the WASM module running in
+/// the call `call_id`. A segment which was never dropped reads as not dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DataDropOp {
+    /// Call ID
+    pub call_id: usize,
+    /// Data index
+    pub data_index: u32,
+    /// Whether the segment was dropped
+    pub is_dropped: bool,
+}
+
+impl Debug for DataDropOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DataDropOp { ")?;
+        f.write_fmt(format_args!(
+            "call_id: {:?}, index: {:?}, is_dropped: {:?}",
+            self.call_id, self.data_index, self.is_dropped
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl DataDropOp {
+    /// Create a new instance of a `DataDropOp` from it's components.
+    pub const fn new(call_id: usize, data_index: u32, is_dropped: bool) -> DataDropOp {
+        DataDropOp {
+            call_id,
+            data_index,
+            is_dropped,
+        }
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::DataDrop
+    }
+
+    /// Returns the call id associated to this Operation.
+    pub const fn call_id(&self) -> usize {
+        self.call_id
+    }
+
+    /// Returns the data index associated to this Operation.
+    pub const fn address(&self) -> u32 {
+        self.data_index
+    }
+
+    /// Returns whether the segment was dropped.
+    pub const fn is_dropped(&self) -> bool {
+        self.is_dropped
+    }
+}
+
+impl Op for DataDropOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::DataDrop(self)
+    }
+
+    fn reverse(&self) -> Self {
+        unreachable!("DataDropOp can't be reverted")
+    }
+}
+
+impl PartialOrd for DataDropOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DataDropOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.call_id, &self.data_index).cmp(&(&other.call_id, &other.data_index))
+    }
+}
+
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) of the control frame
+/// opened by the `block`, `loop` or `if` at `pc` in the WASM function frame
+/// `frame_id`. It holds the stack pointer under the params of the frame, a
+/// frame is written each time its opener runs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ControlFrameOp {
+    /// Frame ID, the rw counter of the step that entered the function
+    pub frame_id: usize,
+    /// Program counter of the instruction opening the frame
+    pub pc: u64,
+    /// Stack pointer under the params of the frame
+    pub stack_pointer: usize,
+}
+
+impl Debug for ControlFrameOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ControlFrameOp { ")?;
+        f.write_fmt(format_args!(
+            "frame_id: {:?}, pc: {:?}, stack_pointer: {:?}",
+            self.frame_id, self.pc, self.stack_pointer
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl ControlFrameOp {
+    /// Create a new instance of a `ControlFrameOp` from it's components.
+    pub const fn new(frame_id: usize, pc: u64, stack_pointer: usize) -> ControlFrameOp {
+        ControlFrameOp {
+            frame_id,
+            pc,
+            stack_pointer,
+        }
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::ControlFrame
+    }
+
+    /// Returns the frame id associated to this Operation.
+    pub const fn frame_id(&self) -> usize {
+        self.frame_id
+    }
+
+    /// Returns the program counter of the opener associated to this Operation.
+    pub const fn address(&self) -> u64 {
+        self.pc
+    }
+
+    /// Returns the stack pointer read or written by this operation.
+    pub const fn stack_pointer(&self) -> usize {
+        self.stack_pointer
+    }
+}
+
+impl Op for ControlFrameOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::ControlFrame(self)
+    }
+
+    fn reverse(&self) -> Self {
+        unreachable!("ControlFrameOp can't be reverted")
+    }
+}
+
+impl PartialOrd for ControlFrameOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ControlFrameOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.frame_id, &self.pc).cmp(&(&other.frame_id, &other.pc))
+    }
+}
+
 /// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the storage
 /// implied by an specific
 /// [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
@@ -759,6 +999,14 @@ pub enum CallContextField {
     MemorySize,
     /// ReversibleWriteCounter
     ReversibleWriteCounter,
+    /// GrownMemoryPages
+    GrownMemoryPages,
+    /// LocalsBase
+    LocalsBase,
+    /// LocalsFrame
+    LocalsFrame,
+    /// FunctionDepth
+    FunctionDepth,
 }
 
 /// Represents an CallContext read/write operation.
@@ -1009,6 +1257,12 @@ pub enum OpEnum {
     Stack(StackOp),
     /// Global
     Global(GlobalOp),
+    /// Local
+    Local(LocalOp),
+    /// DataDrop
+    DataDrop(DataDropOp),
+    /// ControlFrame
+    ControlFrame(ControlFrameOp),
     /// Memory
     Memory(MemoryOp),
     /// Storage
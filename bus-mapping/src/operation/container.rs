@@ -5,7 +5,7 @@ use super::{
 };
 use crate::exec_trace::OperationRef;
 use itertools::Itertools;
-use crate::operation::GlobalOp;
+use crate::operation::{ControlFrameOp, DataDropOp, GlobalOp, LocalOp};
 
 /// The `OperationContainer` is meant to store all of the [`Operation`]s that an
 /// [`ExecStep`](crate::circuit_input_builder::ExecStep) performs during its
@@ -29,6 +29,12 @@ pub struct OperationContainer {
     pub stack: Vec<Operation<StackOp>>,
     /// Operations of GlobalOp
     pub globals: Vec<Operation<GlobalOp>>,
+    /// Operations of LocalOp
+    pub locals: Vec<Operation<LocalOp>>,
+    /// Operations of DataDropOp
+    pub data_drops: Vec<Operation<DataDropOp>>,
+    /// Operations of ControlFrameOp
+    pub control_frames: Vec<Operation<ControlFrameOp>>,
     /// Operations of StorageOp
     pub storage: Vec<Operation<StorageOp>>,
     /// Operations of TxAccessListAccountOp
@@ -63,6 +69,9 @@ impl OperationContainer {
             memory: Vec::new(),
             stack: Vec::new(),
             globals: Vec::new(),
+            locals: Vec::new(),
+            data_drops: Vec::new(),
+            control_frames: Vec::new(),
             storage: Vec::new(),
             tx_access_list_account: Vec::new(),
             tx_access_list_account_storage: Vec::new(),
@@ -110,6 +119,18 @@ impl OperationContainer {
                 self.globals.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Global, self.globals.len() - 1))
             },
+            OpEnum::Local(op) => {
+                self.locals.push(Operation::new(rwc, rw, op));
+                OperationRef::from((Target::Local, self.locals.len() - 1))
+            },
+            OpEnum::DataDrop(op) => {
+                self.data_drops.push(Operation::new(rwc, rw, op));
+                OperationRef::from((Target::DataDrop, self.data_drops.len() - 1))
+            },
+            OpEnum::ControlFrame(op) => {
+                self.control_frames.push(Operation::new(rwc, rw, op));
+                OperationRef::from((Target::ControlFrame, self.control_frames.len() - 1))
+            },
             OpEnum::Storage(op) => {
                 self.storage.push(if reversible {
                     Operation::new_reversible(rwc, rw, op)
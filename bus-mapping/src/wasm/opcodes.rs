@@ -13,13 +13,17 @@ use callop::CallOpcode;
 use callvalue::Callvalue;
 use codecopy::Codecopy;
 use codesize::Codesize;
+use error_call_stack_exhausted::ErrorCallStackExhausted;
+use error_invalid_indirect_call::ErrorInvalidIndirectCall;
 use error_invalid_jump::InvalidJump;
+use error_memory_out_of_bounds::ErrorMemoryOutOfBounds;
 use error_oog_call::OOGCall;
+use error_oog_create::ErrorOOGCreate;
 use error_oog_log::ErrorOOGLog;
 use error_oog_sload_sstore::OOGSloadSstore;
 use error_return_data_outofbound::ErrorReturnDataOutOfBound;
 use error_write_protection::ErrorWriteProtection;
-use eth_types::{evm_types::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED}, evm_unimplemented, GethExecStep, GethExecTrace, StackWord, ToAddress, ToWord, Word};
+use eth_types::{evm_types::{GasCost, WasmFuelSchedule, MAX_REFUND_QUOTIENT_OF_GAS_USED}, evm_unimplemented, GethExecStep, GethExecTrace, StackWord, ToWord, Word};
 use eth_types::evm_types::MemoryAddress;
 use extcodecopy::Extcodecopy;
 use extcodesize::Extcodesize;
@@ -33,10 +37,16 @@ use selfbalance::Selfbalance;
 use stackonlyop::StackOnlyOpcode;
 use stacktomemoryop::{StackToMemoryOpcode, STACK_TO_MEMORY_TYPE_U256, STACK_TO_MEMORY_TYPE_U64};
 use stop::Stop;
+use wasm_block::WasmBlockOpcode;
+use wasm_bulk_memory::WasmBulkMemoryOpcode;
 use wasm_break::WasmBreakOpcode;
 use wasm_call::WasmCallOpcode;
 use wasm_global::WasmGlobalOpcode;
+use wasm_load::WasmLoadOpcode;
 use wasm_local::WasmLocalOpcode;
+use wasm_memory_grow::WasmMemoryGrowOpcode;
+use wasm_return::WasmReturnOpcode;
+use wasm_store::WasmStoreOpcode;
 
 use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
@@ -44,17 +54,18 @@ use crate::{
     Error,
     evm::OpcodeId,
     operation::{
-        AccountField, AccountOp, CallContextField, RW, TxAccessListAccountOp, TxReceiptField,
+        AccountField, AccountOp, CallContextField, RW, TxReceiptField,
         TxRefundOp,
     },
 };
 use crate::error::{ContractAddressCollisionError, DepthError, InsufficientBalanceError, NonceUintOverflowError};
-use crate::evm::opcodes::create::Create;
 use crate::evm::opcodes::extcodehash::Extcodehash;
 use crate::precompile::is_precompiled;
 use crate::state_db::CodeDB;
 use crate::util::CHECK_MEM_STRICT;
+use crate::wasm::opcodes::create::Create;
 use crate::wasm::opcodes::error_codestore::ErrorCodeStore;
+use crate::wasm::opcodes::error_contract_address_collision::ContractAddressCollision;
 use crate::wasm::opcodes::error_invalid_creation_code::ErrorCreationCode;
 use crate::wasm::opcodes::error_oog_account_access::ErrorOOGAccountAccess;
 use crate::wasm::opcodes::error_oog_dynamic_memory::OOGDynamicMemory;
@@ -98,12 +109,16 @@ mod stackonlyop;
 mod stacktomemoryop;
 mod stop;
 
+mod error_call_stack_exhausted;
 mod error_codestore;
 mod error_contract_address_collision;
 mod error_invalid_creation_code;
+mod error_invalid_indirect_call;
 mod error_invalid_jump;
+mod error_memory_out_of_bounds;
 mod error_oog_account_access;
 mod error_oog_call;
+mod error_oog_create;
 mod error_oog_dynamic_memory;
 mod error_oog_log;
 mod error_oog_memory_copy;
@@ -114,13 +129,21 @@ mod error_write_protection;
 
 #[cfg(test)]
 mod memory_expansion_test;
+#[cfg(test)]
+mod wasm_fuel_test;
 #[cfg(feature = "test")]
 pub use callop::tests::PrecompileCallArgs;
 
 mod wasm_call;
 mod wasm_global;
+mod wasm_load;
 mod wasm_local;
+mod wasm_memory_grow;
+mod wasm_store;
 mod wasm_break;
+mod wasm_block;
+mod wasm_bulk_memory;
+mod wasm_return;
 
 /// Generic opcode trait which defines the logic of the
 /// [`Operation`](crate::operation::Operation) that should be generated for one
@@ -158,13 +181,14 @@ type FnGenAssociatedOps = fn(
 fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
     match opcode_id {
         // WASM opcodes
-        OpcodeId::Unreachable => Stop::gen_associated_ops,
-        // OpcodeId::Nop => Dummy::gen_associated_ops,
-        // OpcodeId::Block => Dummy::gen_associated_ops,
-        // OpcodeId::Loop => Dummy::gen_associated_ops,
-        // OpcodeId::If => Dummy::gen_associated_ops,
-        // OpcodeId::Else => Dummy::gen_associated_ops,
-        OpcodeId::End => Stop::gen_associated_ops,
+        // `unreachable` always traps, see `ExecError::Unreachable`
+        // WASM control opcodes, `end` of the function body is handled as STOP
+        OpcodeId::Nop |
+        OpcodeId::Block |
+        OpcodeId::Loop |
+        OpcodeId::If |
+        OpcodeId::Else |
+        OpcodeId::End => WasmBlockOpcode::gen_associated_ops,
         // OpcodeId::Br => Dummy::gen_associated_ops,
         // OpcodeId::BrIf => Dummy::gen_associated_ops,
         // OpcodeId::BrTable => Dummy::gen_associated_ops,
@@ -201,8 +225,6 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         // OpcodeId::I64Store8 => Dummy::gen_associated_ops,
         // OpcodeId::I64Store16 => Dummy::gen_associated_ops,
         // OpcodeId::I64Store32 => Dummy::gen_associated_ops,
-        // OpcodeId::CurrentMemory => Dummy::gen_associated_ops,
-        // OpcodeId::GrowMemory => Dummy::gen_associated_ops,
         OpcodeId::I32Const |
         OpcodeId::I64Const => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         // WASM binary opcodes
@@ -264,7 +286,7 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::I64Rotl |
         OpcodeId::I64Rotr => StackOnlyOpcode::<2, 1>::gen_associated_ops,
 
-        // WASM load store like opcodes (like unary).
+        // WASM load opcodes
         OpcodeId::I32Load |
         OpcodeId::I32Load8S |
         OpcodeId::I32Load8U |
@@ -276,12 +298,17 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::I64Load16S |
         OpcodeId::I64Load16U |
         OpcodeId::I64Load32S |
-        OpcodeId::I64Load32U => StackOnlyOpcode::<1, 1>::gen_associated_ops,
+        OpcodeId::I64Load32U => WasmLoadOpcode::gen_associated_ops,
 
         // WASM unary opcodes
         OpcodeId::I64ExtendUI32 |
         OpcodeId::I64ExtendSI32 |
         OpcodeId::I32WrapI64 |
+        OpcodeId::I32Extend8S |
+        OpcodeId::I32Extend16S |
+        OpcodeId::I64Extend8S |
+        OpcodeId::I64Extend16S |
+        OpcodeId::I64Extend32S |
         OpcodeId::I32Ctz |
         OpcodeId::I64Ctz |
         OpcodeId::I32Clz |
@@ -308,14 +335,22 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         // WASM select like opcodes.
         OpcodeId::Select => StackOnlyOpcode::<3, 1>::gen_associated_ops,
 
-        // WASM store like ops.
+        // WASM store opcodes
         OpcodeId::I32Store |
         OpcodeId::I32Store8 |
         OpcodeId::I32Store16 |
         OpcodeId::I64Store |
         OpcodeId::I64Store8 |
         OpcodeId::I64Store16 |
-        OpcodeId::I64Store32 => StackOnlyOpcode::<2, 0>::gen_associated_ops,
+        OpcodeId::I64Store32 => WasmStoreOpcode::gen_associated_ops,
+
+        // WASM memory opcodes
+        OpcodeId::CurrentMemory |
+        OpcodeId::GrowMemory => WasmMemoryGrowOpcode::gen_associated_ops,
+        OpcodeId::MemoryCopy |
+        OpcodeId::MemoryFill |
+        OpcodeId::MemoryInit |
+        OpcodeId::DataDrop => WasmBulkMemoryOpcode::gen_associated_ops,
 
         // WASM test opcodes
         OpcodeId::I32Eqz | OpcodeId::I64Eqz => StackOnlyOpcode::<1, 1>::gen_associated_ops,
@@ -364,18 +399,9 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::CALL | OpcodeId::CALLCODE => CallOpcode::<true>::gen_associated_ops,
         OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => CallOpcode::<false>::gen_associated_ops,
         OpcodeId::RETURN | OpcodeId::REVERT => ReturnRevert::gen_associated_ops,
-        OpcodeId::SELFDESTRUCT => {
-            evm_unimplemented!("Using dummy gen_selfdestruct_ops for opcode SELFDESTRUCT");
-            DummySelfDestruct::gen_associated_ops
-        }
-        // OpcodeId::CREATE => {
-        //     evm_unimplemented!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-        //     DummyCreate::<false>::gen_associated_ops
-        // }
-        // OpcodeId::CREATE2 => {
-        //     evm_unimplemented!("Using dummy gen_create_ops for opcode {:?}", opcode_id);
-        //     DummyCreate::<true>::gen_associated_ops
-        // }
+        OpcodeId::SELFDESTRUCT => SelfDestructForbidden::gen_associated_ops,
+        OpcodeId::CREATE => Create::<false>::gen_associated_ops,
+        OpcodeId::CREATE2 => Create::<true>::gen_associated_ops,
         _ => {
             evm_unimplemented!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
             Dummy::gen_associated_ops
@@ -405,9 +431,11 @@ fn fn_gen_error_state_associated_ops(
         ExecError::OutOfGas(OogError::Constant) => {
             Some(StackOnlyOpcode::<0, 0, true>::gen_associated_ops)
         }
-        ExecError::OutOfGas(OogError::Create) => {
-            Some(StackOnlyOpcode::<4, 0, true>::gen_associated_ops)
-        }
+        ExecError::OutOfGas(OogError::Create) => match geth_step.op {
+            OpcodeId::CREATE => Some(ErrorOOGCreate::<false>::gen_associated_ops),
+            OpcodeId::CREATE2 => Some(ErrorOOGCreate::<true>::gen_associated_ops),
+            op => unreachable!("OOG Create cannot occur in {op}"),
+        },
         ExecError::OutOfGas(OogError::Log) => Some(ErrorOOGLog::gen_associated_ops),
         ExecError::OutOfGas(OogError::DynamicMemoryExpansion) => {
             Some(OOGDynamicMemory::gen_associated_ops)
@@ -420,7 +448,7 @@ fn fn_gen_error_state_associated_ops(
         }
         ExecError::OutOfGas(OogError::MemoryCopy) => Some(OOGMemoryCopy::gen_associated_ops),
         ExecError::OutOfGas(OogError::Sha3) => {
-            Some(StackOnlyOpcode::<2, 0, true>::gen_associated_ops)
+            Some(StackOnlyOpcode::<3, 0, true>::gen_associated_ops)
         }
         ExecError::OutOfGas(OogError::SloadSstore) => Some(OOGSloadSstore::gen_associated_ops),
         ExecError::OutOfGas(OogError::AccountAccess) => {
@@ -447,10 +475,10 @@ fn fn_gen_error_state_associated_ops(
         ExecError::ReturnDataOutOfBounds => Some(ErrorReturnDataOutOfBound::gen_associated_ops),
         // create & create2 can encounter contract address collision.
         ExecError::ContractAddressCollision(ContractAddressCollisionError::Create) => {
-            Some(Create::<false>::gen_associated_ops)
+            Some(ContractAddressCollision::<false>::gen_associated_ops)
         }
         ExecError::ContractAddressCollision(ContractAddressCollisionError::Create2) => {
-            Some(Create::<true>::gen_associated_ops)
+            Some(ContractAddressCollision::<true>::gen_associated_ops)
         }
         // create & create2 can encounter nonce uint overflow.
         ExecError::NonceUintOverflow(NonceUintOverflowError::Create) => {
@@ -460,6 +488,13 @@ fn fn_gen_error_state_associated_ops(
             Some(Create::<true>::gen_associated_ops)
         }
         ExecError::InvalidCreationCode => Some(ErrorCreationCode::gen_associated_ops),
+        ExecError::InvalidIndirectCall => Some(ErrorInvalidIndirectCall::gen_associated_ops),
+        ExecError::CallStackExhausted => Some(ErrorCallStackExhausted::gen_associated_ops),
+        ExecError::Unreachable => Some(StackOnlyOpcode::<0, 0, true>::gen_associated_ops),
+        ExecError::IntegerDivideByZero | ExecError::IntegerOverflow => {
+            Some(StackOnlyOpcode::<2, 0, true>::gen_associated_ops)
+        }
+        ExecError::MemoryOutOfBounds => Some(ErrorMemoryOutOfBounds::gen_associated_ops),
         // more future errors place here
         _ => {
             evm_unimplemented!("TODO: error state {:?} not implemented", error);
@@ -561,6 +596,11 @@ pub fn gen_associated_ops(
         }
     }
     // if no errors, continue as normal
+    check_wasm_fuel(
+        &state.block.circuits_params.wasm_fuel_schedule,
+        geth_step,
+        next_step,
+    )?;
     let fn_gen_associated_ops = fn_gen_associated_ops(opcode_id);
     let res = fn_gen_associated_ops(state, geth_steps)?;
     // copy global memory dump into call context
@@ -575,6 +615,44 @@ pub fn gen_associated_ops(
     Ok(res)
 }
 
+/// Recomputes the fuel of a WASM instruction from the [`WasmFuelSchedule`] of
+/// the circuits params and rejects a trace step charging a different amount of
+/// gas.
+fn check_wasm_fuel(
+    schedule: &WasmFuelSchedule,
+    geth_step: &GethExecStep,
+    next_step: Option<&GethExecStep>,
+) -> Result<(), Error> {
+    let mut fuel = match schedule.instruction_fuel(geth_step.op) {
+        Some(fuel) => fuel,
+        None => return Ok(()),
+    };
+    if geth_step.op == OpcodeId::GrowMemory {
+        let next_step = next_step.ok_or(Error::InvalidGethExecStep(
+            "memory.grow without a next step",
+            Box::new(geth_step.clone()),
+        ))?;
+        // a failed `memory.grow` pushes -1 and grows nothing
+        if next_step.stack.nth_last(0)? != StackWord::from(0xffffffffu64) {
+            fuel += schedule.memory_grow_fuel(geth_step.stack.nth_last(0)?.as_u64());
+        }
+    }
+    if geth_step.gas_cost.0 != fuel {
+        log::error!(
+            "{:?} at pc {:?} charges {} gas, the fuel schedule charges {}",
+            geth_step.op,
+            geth_step.pc,
+            geth_step.gas_cost.0,
+            fuel,
+        );
+        return Err(Error::InvalidGethExecStep(
+            "wasm instruction gas diverges from the fuel schedule",
+            Box::new(geth_step.clone()),
+        ));
+    }
+    Ok(())
+}
+
 pub fn gen_begin_tx_ops(
     state: &mut CircuitInputStateRef,
     geth_trace: &GethExecTrace,
@@ -666,7 +744,11 @@ pub fn gen_begin_tx_ops(
             && !callee_account.code_hash.eq(&CodeDB::empty_code_hash()))
             || !callee_account.nonce.is_zero())
     {
-        unimplemented!("deployment collision");
+        // the creation transaction can't deploy over an account which already
+        // holds code or a nonce
+        return Err(Error::ExecutionError(ExecError::ContractAddressCollision(
+            ContractAddressCollisionError::Create,
+        )));
     }
     let (callee_code_hash, is_empty_code_hash) = match (state.tx.is_create(), callee_exists) {
         (true, _) => (call.code_hash.to_word(), false),
@@ -977,100 +1059,18 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
     Ok(exec_step)
 }
 
+/// Wasm contracts can't import `_evm_selfdestruct` (the wasm circuit rejects such modules), so
+/// a SELFDESTRUCT step in a wasm trace is never provable.
 #[derive(Debug, Copy, Clone)]
-struct DummySelfDestruct;
+struct SelfDestructForbidden;
 
-impl Opcode for DummySelfDestruct {
+impl Opcode for SelfDestructForbidden {
     fn gen_associated_ops(
-        state: &mut CircuitInputStateRef,
-        geth_steps: &[GethExecStep],
+        _state: &mut CircuitInputStateRef,
+        _geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
-        dummy_gen_selfdestruct_ops(state, geth_steps)
+        Err(Error::InternalError(
+            "SELFDESTRUCT is not available to wasm contracts",
+        ))
     }
 }
-fn dummy_gen_selfdestruct_ops(
-    state: &mut CircuitInputStateRef,
-    geth_steps: &[GethExecStep],
-) -> Result<Vec<ExecStep>, Error> {
-    let geth_step = &geth_steps[0];
-    let mut exec_step = state.new_step(geth_step)?;
-    let sender = state.call()?.address;
-    let receiver = geth_step.stack.last()?.to_address();
-
-    let is_warm = state.sdb.check_account_in_access_list(&receiver);
-    state.push_op_reversible(
-        &mut exec_step,
-        TxAccessListAccountOp {
-            tx_id: state.tx_ctx.id(),
-            address: receiver,
-            is_warm: true,
-            is_warm_prev: is_warm,
-        },
-    )?;
-
-    let (found, receiver_account) = state.sdb.get_account(&receiver);
-    if !found {
-        return Err(Error::AccountNotFound(receiver));
-    }
-    let receiver_account = &receiver_account.clone();
-    let (found, sender_account) = state.sdb.get_account(&sender);
-    if !found {
-        return Err(Error::AccountNotFound(sender));
-    }
-    let sender_account = &sender_account.clone();
-    let value = sender_account.balance;
-    log::trace!(
-        "self destruct, sender {:?} receiver {:?} value {:?}",
-        sender,
-        receiver,
-        value
-    );
-    // NOTE: In this dummy implementation we assume that the receiver already
-    // exists.
-
-    state.push_op_reversible(
-        &mut exec_step,
-        AccountOp {
-            address: sender,
-            field: AccountField::Balance,
-            value: Word::zero(),
-            value_prev: value,
-        },
-    )?;
-    state.push_op_reversible(
-        &mut exec_step,
-        AccountOp {
-            address: sender,
-            field: AccountField::Nonce,
-            value: Word::zero(),
-            value_prev: sender_account.nonce,
-        },
-    )?;
-    state.push_op_reversible(
-        &mut exec_step,
-        AccountOp {
-            address: sender,
-            field: AccountField::CodeHash,
-            value: Word::zero(),
-            value_prev: sender_account.code_hash.to_word(),
-        },
-    )?;
-    if receiver != sender {
-        state.push_op_reversible(
-            &mut exec_step,
-            AccountOp {
-                address: receiver,
-                field: AccountField::Balance,
-                value: receiver_account.balance + value,
-                value_prev: receiver_account.balance,
-            },
-        )?;
-    }
-
-    if state.call()?.is_persistent {
-        state.sdb.destruct_account(sender);
-    }
-
-    state.handle_return(&mut exec_step, geth_steps, false)?;
-    Ok(vec![exec_step])
-}
@@ -14,7 +14,7 @@ use eth_types::{
         gas_utils::{eip150_gas, memory_expansion_gas_cost},
         Gas, GasCost, OpcodeId, MemoryAddress,
     },
-    GethExecStep, ToWord, Word, StackWord, ToBigEndian,
+    GethExecStep, ToWord, Word, ToBigEndian,
 };
 use std::cmp::min;
 
@@ -35,10 +35,10 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
         let mut exec_step = state.new_step(geth_step)?;
 
         let status_offset = geth_step.stack.nth_last(0)?.low_u64() as usize;
-        let ret_offset = geth_step.stack.nth_last(1)?.low_u64() as usize;
-        let ret_length = geth_step.stack.nth_last(2)?.as_usize();
-        let args_offset = geth_step.stack.nth_last(3)?.low_u64() as usize;
-        let args_length = geth_step.stack.nth_last(4)?.as_usize();
+        let ret_length = geth_step.stack.nth_last(1)?.as_usize();
+        let ret_offset = geth_step.stack.nth_last(2)?.low_u64() as usize;
+        let args_length = geth_step.stack.nth_last(3)?.as_usize();
+        let args_offset = geth_step.stack.nth_last(4)?.low_u64() as usize;
 
         // we need to keep the memory until parse_call complete
         state.call_expand_memory(args_offset, args_length, ret_offset, ret_length)?;
@@ -87,37 +87,39 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
             state.call_context_read(&mut exec_step, current_call.call_id, field, value);
         }
 
-        let mut stack_offset = 0;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset + 0), StackWord::from(status_offset as u64))?;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset + 1), StackWord::from(ret_length as u64))?;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset + 2), StackWord::from(ret_offset as u64))?;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset + 3), StackWord::from(args_length as u64))?;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset + 4), StackWord::from(args_offset as u64))?;
-        stack_offset += 5;
+        // status, return data and call data pointers are on top of the stack
+        for i in 0..5 {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+        let mut stack_offset = 5;
 
-        // if we have value then register value ops
+        // the value is read from linear memory as a 32-byte big-endian word
         if WITH_VALUE {
             let value_offset = geth_step.stack.nth_last(stack_offset)?;
             state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset), value_offset)?;
             let value_address = MemoryAddress::try_from(value_offset)?;
             let value = geth_step.global_memory.read_u256(value_offset)?.to_be_bytes();
-            state.memory_write_n(&mut exec_step, value_address, &value)?;
+            state.memory_read_n(&mut exec_step, value_address, &value)?;
             stack_offset += 1;
         };
 
-        // register callee ops
+        // the callee is read from linear memory as a 20-byte address
         {
             let callee_offset = geth_step.stack.nth_last(stack_offset)?;
             state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset), callee_offset)?;
             let callee_address = MemoryAddress::try_from(callee_offset)?;
             let callee = geth_step.global_memory.read_address(callee_offset)?.to_fixed_bytes();
-            state.memory_write_n(&mut exec_step, callee_address, &callee)?;
+            state.memory_read_n(&mut exec_step, callee_address, &callee)?;
             stack_offset += 1;
         }
 
         // read gas from stack
-        let gas = geth_step.stack.nth_last(stack_offset)?;
-        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset), gas)?;
+        let gas_specified = geth_step.stack.nth_last(stack_offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset), gas_specified)?;
 
         // register status offset ops
         state.memory_write(&mut exec_step, MemoryAddress(status_offset), call.is_success as u8)?;
@@ -231,7 +233,6 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
         } else {
             0
         } + memory_expansion_gas_cost;
-        let gas_specified = geth_step.stack.last()?;
         debug_assert!(
             geth_step.gas.0 >= gas_cost,
             "gas {:?} gas_cost {:?} memory_expansion_gas_cost {:?}",
@@ -250,7 +251,7 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
             // panic with full info
             let info1 = format!("callee_gas_left {} gas_specified {} gas_cost {} is_warm {} has_value {} current_memory_word_size {} next_memory_word_size {}, memory_expansion_gas_cost {}",
                     callee_gas_left, gas_specified, gas_cost, is_warm, has_value, curr_memory_word_size, next_memory_word_size, memory_expansion_gas_cost);
-            let info2 = format!("args gas:{:?} addr:{:?} value:{:?} cd_pos:{:?} cd_len:{:?} rd_pos:{:?} rd_len:{:?}",
+            let info2 = format!("args status:{:?} rd_len:{:?} rd_pos:{:?} cd_len:{:?} cd_pos:{:?} value/addr:{:?} addr/gas:{:?}",
                         geth_step.stack.nth_last(0),
                         geth_step.stack.nth_last(1),
                         geth_step.stack.nth_last(2),
@@ -344,7 +345,7 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
                     ),
                     (
                         CallContextField::StackPointer,
-                        (geth_step.stack.stack_pointer().0 + if WITH_VALUE { 8 } else { 7 } - 1).into(),
+                        (geth_step.stack.stack_pointer().0 + if WITH_VALUE { 8 } else { 7 }).into(),
                     ),
                     (
                         CallContextField::GasLeft,
@@ -399,6 +400,7 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
                             log_id: None,
                             rw_counter_start,
                             bytes,
+                            is_reverse: false,
                         },
                     );
                 }
@@ -428,6 +430,7 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
                             log_id: None,
                             rw_counter_start,
                             bytes,
+                            is_reverse: false,
                         },
                     );
                 }
@@ -462,6 +465,7 @@ impl<const WITH_VALUE: bool> Opcode for CallOpcode<WITH_VALUE> {
                             log_id: None,
                             rw_counter_start,
                             bytes,
+                            is_reverse: false,
                         },
                     );
                 }
@@ -8,9 +8,18 @@ use crate::{
     state_db::CodeDB,
     Error,
 };
-use eth_types::{Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H160, H256};
+use eth_types::{
+    evm_types::MemoryAddress, Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H160, H256,
+};
 use ethers_core::utils::{get_create2_address, keccak256, rlp};
 
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the `OpcodeId::CREATE` and `OpcodeId::CREATE2`.
+/// The `_evm_create*` host functions take pointers into linear memory:
+/// - CREATE: value_ptr, code_offset, code_length, result_ptr
+/// - CREATE2: value_ptr, code_offset, code_length, salt_ptr, result_ptr
+///
+/// The created address, or zero on failure, is written to `result_ptr`.
 #[derive(Debug, Copy, Clone)]
 pub struct Create<const IS_CREATE2: bool>;
 
@@ -22,17 +31,19 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
 
-        // Get low Uint64 of offset.
-        let offset = geth_step.stack.nth_last(1)?.low_u64() as usize;
-        let length = geth_step.stack.nth_last(2)?.as_usize();
+        let salt_args = usize::from(IS_CREATE2);
+        let n_pop = 4 + salt_args;
+        let result_offset = geth_step.stack.nth_last(0)?;
+        let length = geth_step.stack.nth_last(1 + salt_args)?.as_usize();
+        let offset = geth_step.stack.nth_last(2 + salt_args)?.as_usize();
+        let value_offset = geth_step.stack.nth_last(3 + salt_args)?;
 
-        if length != 0 {
-            state
-                .call_ctx_mut()?
-                .memory
-                .extend_at_least(offset + length);
-        }
-        let next_memory_word_size = state.call_ctx()?.memory_word_size();
+        let curr_memory_word_size = (exec_step.memory_size as u64) / 0x10000;
+        let next_memory_word_size = if length != 0 {
+            curr_memory_word_size.max((offset + length + 0xffff) as u64 / 0x10000)
+        } else {
+            curr_memory_word_size
+        };
 
         let callee = state.parse_call(geth_step)?;
 
@@ -43,7 +54,6 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             Word::from(state.call()?.is_static as u8),
         );
 
-        let n_pop = if IS_CREATE2 { 4 } else { 3 };
         for i in 0..n_pop {
             state.stack_read(
                 &mut exec_step,
@@ -52,6 +62,26 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             )?;
         }
 
+        // value and salt are 32-byte big-endian words in linear memory
+        let value = geth_step.global_memory.read_u256(value_offset)?;
+        state.memory_read_n(
+            &mut exec_step,
+            MemoryAddress::try_from(value_offset)?,
+            &value.to_be_bytes(),
+        )?;
+        let salt = if IS_CREATE2 {
+            let salt_offset = geth_step.stack.nth_last(1)?;
+            let salt = geth_step.global_memory.read_u256(salt_offset)?;
+            state.memory_read_n(
+                &mut exec_step,
+                MemoryAddress::try_from(salt_offset)?,
+                &salt.to_be_bytes(),
+            )?;
+            salt
+        } else {
+            Word::zero()
+        };
+
         let address = if IS_CREATE2 {
             state.create2_address(&geth_steps[0])?
         } else {
@@ -64,18 +94,18 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             state.sdb.get_account_mut(&address).1.storage.clear();
         }
 
-        // state.stack_write(
-        //     &mut exec_step,
-        //     geth_step.stack.nth_last_filled(n_pop - 1),
-        //     if callee.is_success {
-        //         address.to_word()
-        //     } else {
-        //         Word::zero()
-        //     },
-        // )?;
+        state.memory_write_n(
+            &mut exec_step,
+            MemoryAddress::try_from(result_offset)?,
+            &if callee.is_success {
+                address.to_fixed_bytes()
+            } else {
+                [0u8; 20]
+            },
+        )?;
 
         let (initialization_code, keccak_code_hash, code_hash) = if length > 0 {
-            handle_copy(state, &mut exec_step, state.call()?.call_id, offset, length)?
+            handle_copy(state, &mut exec_step, geth_step, state.call()?.call_id, offset, length)?
         } else {
             (vec![], H256(keccak256([])), CodeDB::empty_code_hash())
         };
@@ -141,7 +171,6 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             )?;
         }
 
-        // TODO: look into when this can be pushed. Could it be done in parse call?
         state.push_call(callee.clone());
 
         for (field, value) in [
@@ -157,25 +186,15 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             state.call_context_write(&mut exec_step, callee.call_id, field, value);
         }
 
-        // if address created before, nonce is not zero
-
-        // this could be good place for checking callee_exists = true, since above
-        // operation happens in evm create() method before checking
-        // ErrContractAddressCollision
+        // An account that already holds code or a nonce can't be created
+        // again, for CREATE this happens when the address was funded or
+        // deployed to before the caller's nonce reached it.
         let code_hash_previous = if callee_exists {
             if is_precheck_ok {
-                // CREATE2 may cause address collision error. And for a tricky
-                // case of CREATE, it could also cause this error. e.g. the `to`
-                // field of transaction is set to the calculated contract
-                // address (reference testool case
-                // `RevertDepthCreateAddressCollision_d0_g0_v0` for details).
-                exec_step.error = Some(ExecError::ContractAddressCollision(match geth_step.op {
-                    OpcodeId::CREATE => ContractAddressCollisionError::Create,
-                    OpcodeId::CREATE2 => ContractAddressCollisionError::Create2,
-                    op => unreachable!(
-                        "Contract address collision error is unexpected for opcode: {:?}",
-                        op
-                    ),
+                exec_step.error = Some(ExecError::ContractAddressCollision(if IS_CREATE2 {
+                    ContractAddressCollisionError::Create2
+                } else {
+                    ContractAddressCollisionError::Create
                 }));
             }
             callee_account.code_hash
@@ -222,7 +241,7 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             ),
             (
                 CallContextField::StackPointer,
-                geth_step.stack.nth_last_filled(n_pop - 1).0.into(),
+                (geth_step.stack.stack_pointer().0 + n_pop).into(),
             ),
             (CallContextField::GasLeft, caller_gas_left.into()),
             (CallContextField::MemorySize, next_memory_word_size.into()),
@@ -274,7 +293,6 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
         }
 
         let keccak_input = if IS_CREATE2 {
-            let salt = geth_step.stack.nth_last(3)?;
             assert_eq!(
                 address,
                 get_create2_address(
@@ -322,11 +340,14 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
 fn handle_copy(
     state: &mut CircuitInputStateRef,
     step: &mut ExecStep,
-    callee_id: usize,
+    geth_step: &GethExecStep,
+    caller_id: usize,
     offset: usize,
     length: usize,
 ) -> Result<(Vec<u8>, H256, H256), Error> {
-    let initialization_bytes = state.call_ctx()?.memory.0[offset..offset + length].to_vec();
+    let initialization_bytes = geth_step
+        .global_memory
+        .read_chunk(MemoryAddress(offset), MemoryAddress(length));
     let keccak_code_hash = H256(keccak256(&initialization_bytes));
     let code_hash = CodeDB::hash(&initialization_bytes);
     let bytes: Vec<_> = Bytecode::from(initialization_bytes.clone())
@@ -337,11 +358,10 @@ fn handle_copy(
 
     let rw_counter_start = state.block_ctx.rwc;
     for (i, (byte, _)) in bytes.iter().enumerate() {
-        // this could be a memory read, if this happens before we push the new call?
         state.push_op(
             step,
             RW::READ,
-            MemoryOp::new(callee_id, (offset + i).into(), *byte),
+            MemoryOp::new(caller_id, (offset + i).into(), *byte),
         );
     }
 
@@ -350,7 +370,7 @@ fn handle_copy(
         CopyEvent {
             rw_counter_start,
             src_type: CopyDataType::Memory,
-            src_id: NumberOrHash::Number(callee_id),
+            src_id: NumberOrHash::Number(caller_id),
             src_addr: offset.try_into().unwrap(),
             src_addr_end: (offset + length).try_into().unwrap(),
             dst_type: CopyDataType::Bytecode,
@@ -358,6 +378,7 @@ fn handle_copy(
             dst_addr: 0,
             log_id: None,
             bytes,
+            is_reverse: false,
         },
     );
 
@@ -367,39 +388,33 @@ fn handle_copy(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{circuit_input_builder::ExecState, mock::BlockData, operation::RW};
-    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, word};
+    use crate::{circuit_input_builder::ExecState, mock::BlockData};
+    use eth_types::{bytecode::WasmBinaryBytecode, bytecode_internal, geth_types::GethData, U256};
     use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
     #[test]
     fn test_create_address_collision_error() {
-        let code = bytecode! {
-            PUSH21(word!("6B6020600060003760206000F3600052600C6014F3"))
-            PUSH1(0)
-            MSTORE
-
-            PUSH1 (0xef) // salt
-            PUSH1 (0x15) // size
-            PUSH1 (0xB) // offset
-            PUSH1 (0)   // value
-            CREATE2
-
-            PUSH1 (0xef) // salt
-            PUSH1 (0x15) // size
-            PUSH1 (0xB) // offset
-            PUSH1 (0)   // value
-            CREATE2
-
-            PUSH1 (0x20)   // retLength
-            PUSH1 (0x20)   // retOffset
-            PUSH1 (0x20)   // argsLength
-            PUSH1 (0x00)      // argsOffset
-            PUSH1 (0x00)      // value
-            DUP6           // addr from above CREATE2
-            PUSH2 (0xFFFF) // gas
-            CALL
-            STOP
-        };
+        // init code returning an empty module
+        let deployed = Bytecode::default().wasm_binary();
+        let mut init_code = Bytecode::default();
+        let deployed_offset = init_code.fill_default_global_data(deployed.clone());
+        bytecode_internal!(init_code,
+            I32Const[deployed_offset]
+            I32Const[deployed.len()]
+            RETURN
+        );
+        let init_code = init_code.wasm_binary();
+
+        let mut code = Bytecode::default();
+        let init_code_offset = code.fill_default_global_data(init_code.clone());
+        for _ in 0..2 {
+            code.emit_evm_create(
+                U256::zero(),
+                init_code_offset as u64,
+                init_code.len() as u64,
+                Some(U256::from(0xef)),
+            );
+        }
 
         // Get the execution steps from the external tracer
         let block: GethData = TestContext::<2, 1>::new(
@@ -434,8 +449,21 @@ mod tests {
             ))
         );
 
+        // result pointer, salt pointer, code length, code offset and value pointer
         let container = builder.block.container.clone();
-        let operation = &container.stack[step.bus_mapping_instance[1].as_usize()];
-        assert_eq!(operation.rw(), RW::READ);
+        for i in 1..=5 {
+            let operation = &container.stack[step.bus_mapping_instance[i].as_usize()];
+            assert_eq!(operation.rw(), RW::READ);
+        }
+        // the collision reports a zero address
+        let result_writes = step
+            .bus_mapping_instance
+            .iter()
+            .filter(|op_ref| op_ref.target() == crate::operation::Target::Memory)
+            .map(|op_ref| &container.memory[op_ref.as_usize()])
+            .filter(|op| op.rw() == RW::WRITE)
+            .collect::<Vec<_>>();
+        assert_eq!(result_writes.len(), 20);
+        assert!(result_writes.iter().all(|op| op.op().value() == 0));
     }
 }
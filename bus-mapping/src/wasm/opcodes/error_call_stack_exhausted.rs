@@ -0,0 +1,108 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::{Opcode, OpcodeId},
+    operation::CallContextField,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// `call` or `call_indirect` while the maximum number of function frames is
+/// already nested. `call_indirect` pops its table slot before the frame is
+/// pushed.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorCallStackExhausted;
+
+impl Opcode for ErrorCallStackExhausted {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = state.get_step_err(geth_step, geth_steps.get(1)).unwrap();
+
+        if geth_step.op == OpcodeId::CallIndirect {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.last_filled(),
+                geth_step.stack.last()?,
+            )?;
+        }
+
+        let call_id = state.call()?.call_id;
+        let depth = state.call_ctx()?.function_depth;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::FunctionDepth,
+            depth.into(),
+        );
+
+        // `IsSuccess` call context operation is added in handle_return
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod call_stack_exhausted_tests {
+    use eth_types::{bytecode, evm_types::MAX_WASM_CALL_DEPTH, geth_types::GethData, Word};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, RW},
+    };
+
+    #[test]
+    fn call_at_the_maximum_depth_traps() {
+        let mut code = bytecode! {
+            Call[0]
+        };
+        code.new_function(vec![], vec![], bytecode! { Call[0] }, vec![]);
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.error.is_some())
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        assert_eq!(step.error, Some(ExecError::CallStackExhausted));
+        // `call` pops nothing, the depth is read before the root call fails
+        assert_eq!(
+            [0, 1]
+                .map(|idx| &builder.block.container.call_context
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::FunctionDepth,
+                        value: MAX_WASM_CALL_DEPTH.into(),
+                    }
+                ),
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::IsSuccess,
+                        value: Word::zero(),
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -1,12 +1,14 @@
-use super::Opcode;
+use super::{create::Create, Opcode};
 use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
-    operation::{AccountField, AccountOp},
+    error::{ContractAddressCollisionError, ExecError},
     Error,
 };
-use eth_types::{GethExecStep, StackWord};
+use eth_types::GethExecStep;
 
-/// ..
+/// The collision is proven by the CREATE gadget itself, which reads the code
+/// hash of the derived address, so this only checks that the step indeed
+/// collided.
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct ContractAddressCollision<const IS_CREATE2: bool>;
 
@@ -15,55 +17,15 @@ impl<const IS_CREATE2: bool> Opcode for ContractAddressCollision<IS_CREATE2> {
         state: &mut CircuitInputStateRef,
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
-        let geth_step = &geth_steps[0];
-        let mut exec_step = state.new_step(geth_step)?;
-
-        let offset = geth_step.stack.nth_last(1)?.as_usize();
-        let length = geth_step.stack.nth_last(2)?.as_usize();
-
-        if length != 0 {
-            state
-                .call_ctx_mut()?
-                .memory
-                .extend_at_least(offset + length);
-        }
-
-        let n_pop = if IS_CREATE2 { 4 } else { 3 };
-        for i in 0..n_pop {
-            state.stack_read(
-                &mut exec_step,
-                geth_step.stack.nth_last_filled(i),
-                geth_step.stack.nth_last(i)?,
-            )?;
-        }
-
-        let _address = if IS_CREATE2 {
-            state.create2_address(&geth_steps[0])?
+        let exec_steps = Create::<IS_CREATE2>::gen_associated_ops(state, geth_steps)?;
+        let expected = ExecError::ContractAddressCollision(if IS_CREATE2 {
+            ContractAddressCollisionError::Create2
         } else {
-            state.create_address()?
-        };
-        // TODO: assert address is collision
-
-        state.stack_write(
-            &mut exec_step,
-            geth_step.stack.nth_last_filled(n_pop - 1),
-            StackWord::zero(),
-        )?;
-
-        let caller = state.call()?.clone();
-
-        // Increase caller's nonce
-        let caller_nonce = state.sdb.get_nonce(&caller.address);
-        state.push_op_reversible(
-            &mut exec_step,
-            AccountOp {
-                address: caller.address,
-                field: AccountField::Nonce,
-                value: (caller_nonce + 1).into(),
-                value_prev: caller_nonce.into(),
-            },
-        )?;
-
-        Ok(vec![exec_step])
+            ContractAddressCollisionError::Create
+        });
+        if exec_steps[0].error.as_ref() != Some(&expected) {
+            return Err(Error::InternalError("contract address collision expected"));
+        }
+        Ok(exec_steps)
     }
 }
@@ -0,0 +1,115 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::{Opcode, OpcodeId},
+    Error,
+};
+use eth_types::GethExecStep;
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorInvalidIndirectCall;
+
+impl Opcode for ErrorInvalidIndirectCall {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let next_step = if geth_steps.len() > 1 {
+            Some(&geth_steps[1])
+        } else {
+            None
+        };
+        exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
+        assert_eq!(geth_step.op, OpcodeId::CallIndirect);
+
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            geth_step.stack.last()?,
+        )?;
+
+        // `IsSuccess` call context operation is added in handle_return
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod invalid_indirect_call_tests {
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        StackWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, StackOp, RW},
+    };
+
+    #[test]
+    fn call_indirect_past_the_table_traps() {
+        let mut code = bytecode! {
+            I32Const[2]
+            CallIndirect[0]
+        };
+        code.new_function(vec![], vec![], bytecode! { Return }, vec![]);
+        code.with_function_table(vec![0, 0]);
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::CallIndirect))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        assert_eq!(step.error, Some(ExecError::InvalidIndirectCall));
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.stack[step.bus_mapping_instance[0].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &StackOp::new(call_id, StackAddress::from(1023), StackWord::from(2u64)),
+            )
+        );
+        // the root call fails right away, so it only reads its failure ops
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.call_context[step.bus_mapping_instance[1].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id,
+                    field: CallContextField::IsSuccess,
+                    value: Word::zero(),
+                }
+            )
+        );
+        assert_eq!(
+            builder.block.container.call_context[step.bus_mapping_instance[2].as_usize()]
+                .op()
+                .field,
+            CallContextField::RwCounterEndOfReversion
+        );
+    }
+}
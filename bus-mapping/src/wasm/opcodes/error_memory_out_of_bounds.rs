@@ -0,0 +1,180 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::Opcode,
+    operation::CallContextField,
+    Error,
+};
+use eth_types::{evm_types::OpcodeId, GethExecStep};
+
+/// A load or store beyond the current memory size, or a bulk memory
+/// instruction whose destination or source range ends past its memory or data
+/// segment. Stores pop the value on top of the address and bulk memory
+/// instructions pop three operands, all of them are read to keep the stack
+/// consistent. `memory.init` also reads whether its segment was dropped.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorMemoryOutOfBounds;
+
+impl Opcode for ErrorMemoryOutOfBounds {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = state.get_step_err(geth_step, geth_steps.get(1)).unwrap();
+
+        let n_pops = match geth_step.op {
+            OpcodeId::MemoryCopy | OpcodeId::MemoryFill | OpcodeId::MemoryInit => 3,
+            op if op.is_wasm_store() => 2,
+            _ => 1,
+        };
+        for i in 0..n_pops {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        let call_id = state.call()?.call_id;
+        let grown_pages = state.call_ctx()?.grown_memory_pages;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::GrownMemoryPages,
+            grown_pages.into(),
+        );
+        if geth_step.op == OpcodeId::MemoryInit {
+            state.data_drop_read(&mut exec_step, geth_step.params[0] as u32)?;
+        }
+
+        // `IsSuccess` call context operation is added in handle_return
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod memory_out_of_bounds_tests {
+    use eth_types::{
+        bytecode,
+        bytecode::WASM_PAGE_SIZE,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Bytecode, StackWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::{CircuitInputBuilder, ExecState, ExecStep},
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, StackOp, RW},
+    };
+
+    fn trapping_step(code: Bytecode, opcode: OpcodeId) -> (CircuitInputBuilder, ExecStep) {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(opcode))
+            .unwrap()
+            .clone();
+        assert_eq!(step.error, Some(ExecError::MemoryOutOfBounds));
+        (builder, step)
+    }
+
+    /// Asserts the operands popped by the trapping step, then the read of the
+    /// grown memory pages and the failure of the root call right after them.
+    fn assert_trap_ops(builder: &CircuitInputBuilder, step: &ExecStep, pops: &[(usize, u64)]) {
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+        for (idx, &(address, value)) in pops.iter().enumerate() {
+            let operation =
+                &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()];
+            assert_eq!(
+                (operation.rw(), operation.op()),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(address), StackWord::from(value)),
+                )
+            );
+        }
+        assert_eq!(
+            [pops.len(), pops.len() + 1]
+                .map(|idx| &builder.block.container.call_context
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::GrownMemoryPages,
+                        value: Word::zero(),
+                    }
+                ),
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::IsSuccess,
+                        value: Word::zero(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn memory_fill_past_the_memory_end_traps() {
+        let code = bytecode! {
+            I32Const[WASM_PAGE_SIZE - 1]
+            I32Const[0]
+            I32Const[2]
+            MemoryFill
+        };
+        let (builder, step) = trapping_step(code, OpcodeId::MemoryFill);
+
+        assert_trap_ops(
+            &builder,
+            &step,
+            &[(1023, 2), (1022, 0), (1021, WASM_PAGE_SIZE - 1)],
+        );
+    }
+
+    #[test]
+    fn load_past_the_memory_end_traps() {
+        let code = bytecode! {
+            I32Const[WASM_PAGE_SIZE - 2]
+            .write_memarg(OpcodeId::I32Load, 0, 2, 0)
+            Drop
+        };
+        let (builder, step) = trapping_step(code, OpcodeId::I32Load);
+
+        assert_trap_ops(&builder, &step, &[(1023, WASM_PAGE_SIZE - 2)]);
+    }
+
+    #[test]
+    fn store_past_the_memory_end_pops_the_value_and_the_address() {
+        let code = bytecode! {
+            I32Const[WASM_PAGE_SIZE - 1]
+            I64Const[0x1122]
+            .write_memarg(OpcodeId::I64Store16, 0, 1, 0)
+        };
+        let (builder, step) = trapping_step(code, OpcodeId::I64Store16);
+
+        assert_trap_ops(
+            &builder,
+            &step,
+            &[(1023, 0x1122), (1022, WASM_PAGE_SIZE - 1)],
+        );
+    }
+}
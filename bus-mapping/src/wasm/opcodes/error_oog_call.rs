@@ -4,7 +4,10 @@ use crate::{
     operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
     Error,
 };
-use eth_types::{evm_types::OpcodeId, GethExecStep, ToAddress, ToWord, Word};
+use eth_types::{
+    evm_types::{MemoryAddress, OpcodeId},
+    GethExecStep, ToBigEndian, ToWord, Word,
+};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the out of gas errors for [`OpcodeId::CALL`],
@@ -19,9 +22,9 @@ impl Opcode for OOGCall {
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
-        let stack_input_num = match geth_step.op {
-            OpcodeId::CALL | OpcodeId::CALLCODE => 7,
-            OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => 6,
+        let with_value = match geth_step.op {
+            OpcodeId::CALL | OpcodeId::CALLCODE => true,
+            OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => false,
             op => unreachable!("{op} should not happen in OOGCall"),
         };
 
@@ -34,7 +37,6 @@ impl Opcode for OOGCall {
         exec_step.error = state.get_step_err(geth_step, next_step).unwrap();
 
         let tx_id = state.tx_ctx.id();
-        let call_address = geth_step.stack.nth_last(1)?.to_address();
 
         let current_call = state.call()?.clone();
         for (field, value) in [
@@ -47,19 +49,36 @@ impl Opcode for OOGCall {
             state.call_context_read(&mut exec_step, current_call.call_id, field, value);
         }
 
-        for i in 0..stack_input_num {
+        // the arguments are laid out as in `CallOpcode`, the status pointer is
+        // not written since the call is never made
+        for i in 0..5 {
             state.stack_read(
                 &mut exec_step,
                 geth_step.stack.nth_last_filled(i),
                 geth_step.stack.nth_last(i)?,
             )?;
         }
-
-        state.stack_write(
+        let mut stack_offset = 5;
+        if with_value {
+            let value_offset = geth_step.stack.nth_last(stack_offset)?;
+            state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset), value_offset)?;
+            let value = geth_step.global_memory.read_u256(value_offset)?.to_be_bytes();
+            state.memory_read_n(&mut exec_step, MemoryAddress::try_from(value_offset)?, &value)?;
+            stack_offset += 1;
+        }
+        let callee_offset = geth_step.stack.nth_last(stack_offset)?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(stack_offset), callee_offset)?;
+        let call_address = geth_step.global_memory.read_address(callee_offset)?;
+        state.memory_read_n(
+            &mut exec_step,
+            MemoryAddress::try_from(callee_offset)?,
+            &call_address.to_fixed_bytes(),
+        )?;
+        stack_offset += 1;
+        state.stack_read(
             &mut exec_step,
-            geth_step.stack.nth_last_filled(stack_input_num - 1),
-            // Must fail.
-            (0_u64).into(),
+            geth_step.stack.nth_last_filled(stack_offset),
+            geth_step.stack.nth_last(stack_offset)?,
         )?;
 
         let (_, callee_account) = state.sdb.get_account(&call_address);
@@ -0,0 +1,165 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::Opcode,
+    operation::CallContextField,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// CREATE or CREATE2 running out of gas. The grown memory pages are read so the
+/// memory expansion is paid from the actual page count of the caller.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ErrorOOGCreate<const IS_CREATE2: bool>;
+
+impl<const IS_CREATE2: bool> Opcode for ErrorOOGCreate<IS_CREATE2> {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = state.get_step_err(geth_step, geth_steps.get(1)).unwrap();
+
+        let n_pop = 4 + usize::from(IS_CREATE2);
+        for i in 0..n_pop {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        let call_id = state.call()?.call_id;
+        let grown_pages = state.call_ctx()?.grown_memory_pages;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::GrownMemoryPages,
+            grown_pages.into(),
+        );
+
+        // `IsSuccess` call context operation is added in handle_return
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod oog_create_tests {
+    use eth_types::{
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        Bytecode, StackWord, Word, U256,
+    };
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::ExecState,
+        error::{ExecError, OogError},
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, StackOp, RW},
+    };
+
+    fn test_oog_create(salt: Option<U256>) {
+        let opcode = if salt.is_some() {
+            OpcodeId::CREATE2
+        } else {
+            OpcodeId::CREATE
+        };
+        let mut code = Bytecode::default();
+        code.emit_evm_create(U256::zero(), 0, 0x100, salt);
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(21_100u64));
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(opcode))
+            .unwrap();
+        let geth_step = block.geth_traces[0]
+            .struct_logs
+            .iter()
+            .find(|step| step.op == opcode)
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+        let n_pop = 4 + usize::from(salt.is_some());
+
+        assert_eq!(step.error, Some(ExecError::OutOfGas(OogError::Create)));
+        // the length and the offset of the init code sit under the salt offset of
+        // CREATE2 and the result offset
+        assert_eq!(
+            geth_step.stack.nth_last(n_pop - 3).unwrap(),
+            StackWord::from(0x100u64)
+        );
+        assert_eq!(
+            geth_step.stack.nth_last(n_pop - 2).unwrap(),
+            StackWord::zero()
+        );
+        for idx in 0..n_pop {
+            let operation =
+                &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()];
+            assert_eq!(
+                (operation.rw(), operation.op()),
+                (
+                    RW::READ,
+                    &StackOp::new(
+                        call_id,
+                        StackAddress::from(1023 - idx),
+                        geth_step.stack.nth_last(idx).unwrap()
+                    ),
+                )
+            );
+        }
+        assert_eq!(
+            [n_pop, n_pop + 1]
+                .map(|idx| &builder.block.container.call_context
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::GrownMemoryPages,
+                        value: Word::zero(),
+                    }
+                ),
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::IsSuccess,
+                        value: Word::zero(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn oog_create_reads_its_operands_and_the_grown_pages() {
+        test_oog_create(None);
+    }
+
+    #[test]
+    fn oog_create2_reads_its_operands_and_the_grown_pages() {
+        test_oog_create(Some(U256::from(0x123456)));
+    }
+}
@@ -15,39 +15,17 @@ impl Opcode for OOGDynamicMemory {
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
-        debug_assert!(
-            [OpcodeId::CREATE, OpcodeId::RETURN, OpcodeId::REVERT].contains(&geth_step.op)
-        );
+        debug_assert!([OpcodeId::RETURN, OpcodeId::REVERT].contains(&geth_step.op));
 
         let mut exec_step = state.new_step(geth_step)?;
         exec_step.error = Some(ExecError::OutOfGas(OogError::DynamicMemoryExpansion));
 
-        if geth_step.op == OpcodeId::CREATE {
+        // length and offset of the returned range
+        for i in 0..2 {
             state.stack_read(
                 &mut exec_step,
-                geth_step.stack.last_filled(),
-                geth_step.stack.last()?,
-            )?;
-            state.stack_read(
-                &mut exec_step,
-                geth_step.stack.nth_last_filled(1),
-                geth_step.stack.nth_last(1)?,
-            )?;
-            state.stack_read(
-                &mut exec_step,
-                geth_step.stack.nth_last_filled(2),
-                geth_step.stack.nth_last(2)?,
-            )?;
-        } else {
-            state.stack_read(
-                &mut exec_step,
-                geth_step.stack.last_filled(),
-                geth_step.stack.last()?,
-            )?;
-            state.stack_read(
-                &mut exec_step,
-                geth_step.stack.nth_last_filled(1),
-                geth_step.stack.nth_last(1)?,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
             )?;
         }
 
@@ -5,7 +5,10 @@ use crate::{
     operation::{CallContextField, TxAccessListAccountOp, RW},
     Error,
 };
-use eth_types::{evm_types::OpcodeId, GethExecStep, ToAddress};
+use eth_types::{
+    evm_types::{MemoryAddress, OpcodeId},
+    GethExecStep,
+};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the
@@ -32,6 +35,17 @@ impl Opcode for OOGMemoryCopy {
 
         let is_extcodecopy = geth_step.op == OpcodeId::EXTCODECOPY;
 
+        // Each of CALLDATACOPY, CODECOPY and RETURNDATACOPY has 3 stack read values.
+        // But EXTCODECOPY has 4. It has an extra stack pop for the external address pointer.
+        let stack_read_num = if is_extcodecopy { 4 } else { 3 };
+        for i in 0..stack_read_num {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
         // According to EIP-2929, EXTCODECOPY constant gas cost is different for cold
         // and warm accounts.
         if is_extcodecopy {
@@ -42,7 +56,8 @@ impl Opcode for OOGMemoryCopy {
                 state.tx_ctx.id().into(),
             );
 
-            let external_address = geth_step.stack.last()?.to_address();
+            let external_address_offset = geth_step.stack.nth_last(3)?;
+            let external_address = geth_step.global_memory.read_address(external_address_offset)?;
             let is_warm = state.sdb.check_account_in_access_list(&external_address);
             state.push_op(
                 &mut exec_step,
@@ -54,16 +69,12 @@ impl Opcode for OOGMemoryCopy {
                     is_warm_prev: is_warm,
                 },
             );
-        }
 
-        // Each of CALLDATACOPY, CODECOPY and RETURNDATACOPY has 3 stack read values.
-        // But EXTCODECOPY has 4. It has an extra stack pop for external address.
-        let stack_read_num = if is_extcodecopy { 4 } else { 3 };
-        for i in 0..stack_read_num {
-            state.stack_read(
+            // The external address is read from the linear memory.
+            state.memory_read_n(
                 &mut exec_step,
-                geth_step.stack.nth_last_filled(i),
-                geth_step.stack.nth_last(i)?,
+                MemoryAddress::from(external_address_offset.as_u64()),
+                &external_address.to_fixed_bytes(),
             )?;
         }
 
@@ -161,6 +161,7 @@ fn gen_copy_event<const N_LOGS: usize>(
         log_id: Some(state.tx_ctx.log_id as u64 + 1),
         rw_counter_start,
         bytes: steps,
+        is_reverse: false,
     })
 }
 
@@ -1,7 +1,6 @@
 use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
-use crate::operation::CallContextField;
 use crate::Error;
-use eth_types::{GethExecStep, U256};
+use eth_types::GethExecStep;
 use eth_types::evm_types::MemoryAddress;
 use crate::evm::Opcode;
 
@@ -20,13 +19,6 @@ impl Opcode for Number {
         let mut exec_step = state.new_step(step)?;
         let value = &second_step.memory[0].0;
 
-        state.call_context_read(
-            &mut exec_step,
-            state.call()?.call_id,
-            CallContextField::Value,
-            U256::from_big_endian(value),
-        );
-
         // Read dest offset as the last stack element
         let dest_offset = step.stack.nth_last(0)?;
         state.stack_read(&mut exec_step, step.stack.nth_last_filled(0), dest_offset)?;
@@ -89,7 +81,7 @@ mod number_tests {
             .find(|step| step.exec_state == ExecState::Op(OpcodeId::NUMBER))
             .unwrap();
 
-        let op_number = &builder.block.container.stack[step.bus_mapping_instance[1].as_usize()];
+        let op_number = &builder.block.container.stack[step.bus_mapping_instance[0].as_usize()];
         assert_eq!(
             (op_number.rw(), op_number.op()),
             (
@@ -102,7 +94,7 @@ mod number_tests {
             assert_eq!(
                 {
                     let operation =
-                        &builder.block.container.memory[step.bus_mapping_instance[2 + idx].as_usize()];
+                        &builder.block.container.memory[step.bus_mapping_instance[1 + idx].as_usize()];
                     (operation.rw(), operation.op())
                 },
                 (
@@ -214,6 +214,7 @@ fn handle_copy(
             dst_addr: destination.offset.try_into().unwrap(),
             log_id: None,
             bytes,
+            is_reverse: false,
         },
     );
 
@@ -264,6 +265,7 @@ fn handle_create(
             dst_addr: 0,
             log_id: None,
             bytes,
+            is_reverse: false,
         },
     );
 
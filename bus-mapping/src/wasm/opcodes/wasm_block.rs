@@ -0,0 +1,220 @@
+use eth_types::bytecode::{wasm_blocks, WasmBlock};
+use eth_types::evm_types::OpcodeId;
+use eth_types::GethExecStep;
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ControlFrame, ExecStep};
+use crate::Error;
+use crate::operation::CallContextField;
+
+use super::{Opcode, Stop, WasmReturnOpcode};
+
+/// Returns the frame opened by the `block`, `loop` or `if` at the program
+/// counter of the step.
+pub(crate) fn opened_block(
+    state: &CircuitInputStateRef,
+    step: &GethExecStep,
+) -> Result<WasmBlock, Error> {
+    let code = state.code(state.call()?.code_hash)?;
+    wasm_blocks(&code)
+        .and_then(|blocks| blocks.into_iter().find(|block| block.pc == step.pc.0 as u64))
+        .ok_or(Error::InvalidGethExecStep(
+            "not supported blocktype",
+            Box::new(step.clone()),
+        ))
+}
+
+/// Pushes a read of the locals frame of the running function, which keys its
+/// control frames together with the call id.
+pub(crate) fn locals_frame_read(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+) -> Result<(), Error> {
+    let call_id = state.call()?.call_id;
+    let locals_frame = state.call_ctx()?.function_frame.locals_frame;
+    state.call_context_read(
+        exec_step,
+        call_id,
+        CallContextField::LocalsFrame,
+        locals_frame.into(),
+    );
+    Ok(())
+}
+
+/// Handles structured control instructions and keeps the label stack of the
+/// call context in sync. An opener writes the stack pointer under the params of
+/// its frame, `else` and `end` read it back. An `end` with no open frame ends
+/// the function body, which returns to the caller of a called function.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmBlockOpcode;
+
+impl Opcode for WasmBlockOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+
+        if current_step.op == OpcodeId::End && state.call_ctx()?.control_frames.is_empty() {
+            if !state.call_ctx()?.suspended_frames.is_empty() {
+                return WasmReturnOpcode::gen_associated_ops(state, geth_steps);
+            }
+            return Stop::gen_associated_ops(state, geth_steps);
+        }
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        let control_frame = match current_step.op {
+            OpcodeId::Nop => None,
+            OpcodeId::Block | OpcodeId::Loop | OpcodeId::If => {
+                let mut stack_size = current_step.stack.0.len();
+                if current_step.op == OpcodeId::If {
+                    let cond = current_step.stack.last()?;
+                    state.stack_read(&mut exec_step, current_step.stack.last_filled(), cond)?;
+                    stack_size -= 1;
+                }
+                let block = opened_block(state, current_step)?;
+                let frame = ControlFrame {
+                    is_loop: current_step.op == OpcodeId::Loop,
+                    pc: block.pc,
+                    end_pc: block.end_pc,
+                    num_params: block.num_params as usize,
+                    num_results: block.num_results as usize,
+                    stack_size,
+                };
+                locals_frame_read(state, &mut exec_step)?;
+                state.control_frame_write(&mut exec_step, frame.pc, frame.stack_pointer())?;
+                state.call_ctx_mut()?.control_frames.push(frame);
+                Some(frame)
+            }
+            // `else` is only reached from the end of the `then` arm, it jumps to the
+            // `end` of the frame which pops it
+            OpcodeId::Else | OpcodeId::End => {
+                let control_frames = &mut state.call_ctx_mut()?.control_frames;
+                let frame = if current_step.op == OpcodeId::End {
+                    control_frames.pop()
+                } else {
+                    control_frames.last().copied()
+                }
+                .ok_or(Error::InvalidGethExecStep(
+                    "else without an open frame",
+                    Box::new(current_step.clone()),
+                ))?;
+                locals_frame_read(state, &mut exec_step)?;
+                state.control_frame_read(&mut exec_step, frame.pc, frame.stack_pointer())?;
+                Some(frame)
+            }
+            _ => unreachable!("not supported opcode: {:?}", current_step.op),
+        };
+        exec_step.control_frame = control_frame;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_block_tests {
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        StackWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, ControlFrameOp, StackOp, RW},
+    };
+
+    #[test]
+    fn wasm_if_and_end_write_and_read_back_the_control_frame() {
+        let code = bytecode! {
+            I32Const[7]
+            I32Const[1]
+            If
+                Nop
+            End
+            Drop
+        };
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let if_step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::If))
+            .unwrap();
+        let end_step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::End))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+        let locals_frame_read = (
+            RW::READ,
+            &CallContextOp {
+                call_id,
+                field: CallContextField::LocalsFrame,
+                value: Word::zero(),
+            },
+        );
+        // the frame keeps the `7` under it once the condition is popped
+        let control_frame = ControlFrameOp::new(call_id, if_step.pc.0 as u64, 1023);
+
+        // 1 stack read, 1 call context read and 1 control frame write.
+        assert_eq!(if_step.bus_mapping_instance.len(), 3);
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.stack[if_step.bus_mapping_instance[0].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &StackOp::new(call_id, StackAddress::from(1022), StackWord::from(1u64)),
+            )
+        );
+        assert_eq!(
+            {
+                let operation = &builder.block.container.call_context
+                    [if_step.bus_mapping_instance[1].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            locals_frame_read
+        );
+        assert_eq!(
+            {
+                let operation = &builder.block.container.control_frames
+                    [if_step.bus_mapping_instance[2].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (RW::WRITE, &control_frame)
+        );
+
+        // 1 call context read and 1 control frame read.
+        assert_eq!(end_step.bus_mapping_instance.len(), 2);
+        assert_eq!(
+            {
+                let operation = &builder.block.container.call_context
+                    [end_step.bus_mapping_instance[0].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            locals_frame_read
+        );
+        assert_eq!(
+            {
+                let operation = &builder.block.container.control_frames
+                    [end_step.bus_mapping_instance[1].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (RW::READ, &control_frame)
+        );
+    }
+}
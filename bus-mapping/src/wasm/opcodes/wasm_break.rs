@@ -1,12 +1,18 @@
 use eth_types::{GethExecStep};
-use eth_types::evm_types::OpcodeId;
+use eth_types::evm_types::{OpcodeId, StackAddress, MAX_WASM_ARITY};
 
 use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
 use crate::Error;
+use crate::operation::CallContextField;
 
 use super::Opcode;
+use super::wasm_block::locals_frame_read;
+use super::wasm_return::gen_return_ops;
 
-///
+/// `return`, `br` and `br_if`. A taken branch reads the stack pointer under the
+/// params of the target frame and moves the values the frame carries right
+/// above it. A branch to the label of the function body returns, from a
+/// called function to its caller, from the entry function out of the call.
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct WasmBreakOpcode;
 
@@ -16,21 +22,110 @@ impl Opcode for WasmBreakOpcode {
         geth_steps: &[GethExecStep],
     ) -> Result<Vec<ExecStep>, Error> {
         let current_step = &geth_steps[0];
-        let _next_step = &geth_steps[1];
+        let next_step = &geth_steps[1];
 
-        let exec_step = state.new_step(current_step)?;
-
-        match current_step.op {
-            OpcodeId::Return => {
+        let (label, n_pops) = match current_step.op {
+            OpcodeId::Return => (None, 0),
+            OpcodeId::Br => (Some(current_step.params[0] as usize), 0),
+            OpcodeId::BrIf => (Some(current_step.params[0] as usize), 1),
+            // the wasm circuit does not decode the label vector of br_table
+            OpcodeId::BrTable => {
+                return Err(Error::InvalidGethExecStep(
+                    "br_table is not supported",
+                    Box::new(current_step.clone()),
+                ))
             }
-            OpcodeId::Br => {
+            _ => unreachable!("not supported opcode: {:?}", current_step.op)
+        };
+        let num_frames = state.call_ctx()?.control_frames.len();
+        let depth = match label {
+            Some(label) if label > num_frames => {
+                return Err(Error::InvalidGethExecStep(
+                    "branch label out of the open frames",
+                    Box::new(current_step.clone()),
+                ))
             }
-            OpcodeId::BrIf => {
+            // the label of the function body
+            Some(label) if label == num_frames => None,
+            Some(label) => Some(num_frames - 1 - label),
+            None => None,
+        };
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        let is_taken = if current_step.op == OpcodeId::BrIf {
+            let cond = current_step.stack.last()?;
+            state.stack_read(&mut exec_step, current_step.stack.last_filled(), cond)?;
+            !cond.is_zero()
+        } else {
+            true
+        };
+        if !is_taken {
+            return Ok(vec![exec_step]);
+        }
+
+        let depth = match depth {
+            Some(depth) => depth,
+            None if !state.call_ctx()?.suspended_frames.is_empty() => {
+                gen_return_ops(state, &mut exec_step, current_step, n_pops)?;
+                return Ok(vec![exec_step]);
             }
-            OpcodeId::BrTable => {
+            // a return from the entry function ends the call
+            None => {
+                let call_id = state.call()?.call_id;
+                state.call_context_read(
+                    &mut exec_step,
+                    call_id,
+                    CallContextField::InternalFunctionId,
+                    0.into(),
+                );
+                return Ok(vec![exec_step]);
             }
-            _ => unreachable!("not supported opcode: {:?}", current_step.op)
         };
+        let target = state.call_ctx()?.control_frames[depth];
+        let arity = target.arity();
+        if arity as u64 > MAX_WASM_ARITY {
+            return Err(Error::InvalidGethExecStep(
+                "branch carries more values than supported",
+                Box::new(current_step.clone()),
+            ));
+        }
+
+        // the values carried by the branch are moved right above the frame entry,
+        // everything pushed inside the frame is dropped. All of them are read
+        // before the first write, the ranges may overlap.
+        locals_frame_read(state, &mut exec_step)?;
+        state.control_frame_read(&mut exec_step, target.pc, target.stack_pointer())?;
+        let mut values = Vec::with_capacity(arity);
+        for i in 0..arity {
+            let value = current_step.stack.nth_last(n_pops + i)?;
+            state.stack_read(
+                &mut exec_step,
+                current_step.stack.nth_last_filled(n_pops + i),
+                value,
+            )?;
+            values.push(value);
+        }
+        for (i, value) in values.into_iter().enumerate() {
+            state.stack_write(
+                &mut exec_step,
+                StackAddress::from(target.stack_pointer() - arity + i),
+                value,
+            )?;
+        }
+
+        // a branch to a loop which lands on the loop header enters it again, a branch
+        // to a block which lands on its `end` leaves popping the frame to the `end`
+        let keeps_target = if target.is_loop {
+            next_step.pc.0 as u64 != target.pc
+        } else {
+            next_step.op == OpcodeId::End
+        };
+        state
+            .call_ctx_mut()?
+            .control_frames
+            .truncate(depth + keeps_target as usize);
+        exec_step.control_frame = Some(target);
 
         Ok(vec![exec_step])
     }
@@ -0,0 +1,233 @@
+use eth_types::bytecode::wasm_data_segment;
+use eth_types::evm_types::{MemoryAddress, OpcodeId};
+use eth_types::GethExecStep;
+
+use crate::circuit_input_builder::{CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash};
+use crate::operation::CallContextField;
+use crate::Error;
+
+use super::Opcode;
+
+/// Handles `memory.copy`, `memory.fill` and `memory.init`, all of them pop the
+/// destination, the source (or the fill value) and the length, read the grown
+/// memory pages to bound the ranges, and produce a copy event into memory.
+/// `memory.init` reads whether its segment was dropped, `data.drop` only
+/// writes that flag. Out of bounds ranges are handled as a trap.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmBulkMemoryOpcode;
+
+impl Opcode for WasmBulkMemoryOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+        let next_step = &geth_steps[1];
+
+        let mut exec_step = state.new_step(current_step)?;
+        if current_step.op == OpcodeId::DataDrop {
+            state.data_drop_write(&mut exec_step, current_step.params[0] as u32)?;
+            return Ok(vec![exec_step]);
+        }
+
+        let length = current_step.stack.nth_last(0)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), length)?;
+        let src = current_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(1), src)?;
+        let dst = current_step.stack.nth_last(2)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(2), dst)?;
+
+        let call_id = state.call()?.call_id;
+        let grown_pages = state.call_ctx()?.grown_memory_pages;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::GrownMemoryPages,
+            grown_pages.into(),
+        );
+        if current_step.op == OpcodeId::MemoryInit {
+            state.data_drop_read(&mut exec_step, current_step.params[0] as u32)?;
+        }
+
+        let (length, src, dst_addr) = (length.as_u64(), src.as_u64(), dst.as_u64());
+        if length > 0 {
+            let rw_counter_start = state.block_ctx.rwc;
+            let (src_type, src_id, src_addr, src_addr_end, bytes) = match current_step.op {
+                OpcodeId::MemoryCopy => {
+                    let bytes = next_step
+                        .global_memory
+                        .read_chunk(MemoryAddress(dst_addr as usize), MemoryAddress(length as usize));
+                    (
+                        CopyDataType::Memory,
+                        NumberOrHash::Number(call_id),
+                        src,
+                        src + length,
+                        bytes,
+                    )
+                }
+                OpcodeId::MemoryFill => {
+                    let value = src as u8;
+                    (
+                        CopyDataType::Constant,
+                        NumberOrHash::Number(value as usize),
+                        0,
+                        length,
+                        vec![value; length as usize],
+                    )
+                }
+                OpcodeId::MemoryInit => {
+                    let code_hash = state.call()?.code_hash;
+                    let code = state.code(code_hash)?;
+                    let data_index = current_step.params[0];
+                    let (segment_start, segment_end) = wasm_data_segment(&code, data_index)
+                        .ok_or(Error::InternalError("memory.init of an unknown data segment"))?;
+                    let src_addr = segment_start + src;
+                    let bytes = code[src_addr as usize..(src_addr + length) as usize].to_vec();
+                    (
+                        CopyDataType::Bytecode,
+                        NumberOrHash::Hash(code_hash),
+                        src_addr,
+                        segment_end,
+                        bytes,
+                    )
+                }
+                _ => unreachable!("not supported opcode: {:?}", current_step.op),
+            };
+
+            // reads and writes are interleaved in the copy circuit, so a copy
+            // into an overlapping higher range goes from the highest address down
+            // not to read bytes it has already overwritten
+            let is_reverse = src_type == CopyDataType::Memory && dst_addr > src;
+            let mut copy_steps = bytes.iter().enumerate().collect::<Vec<_>>();
+            if is_reverse {
+                copy_steps.reverse();
+            }
+            for (i, byte) in copy_steps {
+                if src_type == CopyDataType::Memory {
+                    state.memory_read(&mut exec_step, MemoryAddress(src as usize + i), *byte)?;
+                }
+                state.memory_write(&mut exec_step, MemoryAddress(dst_addr as usize + i), *byte)?;
+            }
+            let copy_event = CopyEvent {
+                src_type,
+                src_id,
+                src_addr,
+                src_addr_end,
+                dst_type: CopyDataType::Memory,
+                dst_id: NumberOrHash::Number(call_id),
+                dst_addr,
+                log_id: None,
+                rw_counter_start,
+                bytes: bytes.into_iter().map(|byte| (byte, false)).collect(),
+                is_reverse,
+            };
+            state.push_copy(&mut exec_step, copy_event);
+        }
+
+        let call_ctx = state.call_ctx_mut()?;
+        call_ctx.memory = next_step.global_memory.clone();
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_bulk_memory_tests {
+    use eth_types::{
+        bytecode,
+        evm_types::{MemoryAddress, OpcodeId, StackAddress},
+        geth_types::GethData,
+        StackWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::{CopyDataType, ExecState},
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, MemoryOp, StackOp, RW},
+    };
+
+    #[test]
+    fn wasm_memory_fill_writes_the_low_byte_of_the_value() {
+        let code = bytecode! {
+            I32Const[3]
+            I32Const[0x1ab]
+            I32Const[2]
+            MemoryFill
+        };
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::MemoryFill))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        // 3 stack reads, 1 call context read and 2 memory writes.
+        assert_eq!(step.bus_mapping_instance.len(), 6);
+        assert_eq!(
+            [0, 1, 2]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), StackWord::from(2u64)),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1022), StackWord::from(0x1abu64)),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1021), StackWord::from(3u64)),
+                ),
+            ]
+        );
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.call_context[step.bus_mapping_instance[3].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id,
+                    field: CallContextField::GrownMemoryPages,
+                    value: Word::zero(),
+                }
+            )
+        );
+        assert_eq!(
+            [4, 5]
+                .map(|idx| &builder.block.container.memory
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op().clone())),
+            [
+                (
+                    RW::WRITE,
+                    MemoryOp::new(call_id, MemoryAddress::from(3u32), 0xab)
+                ),
+                (
+                    RW::WRITE,
+                    MemoryOp::new(call_id, MemoryAddress::from(4u32), 0xab)
+                ),
+            ]
+        );
+
+        let copy_event = &builder.block.copy_events[0];
+        assert_eq!(copy_event.src_type, CopyDataType::Constant);
+        assert_eq!(copy_event.dst_addr, 3);
+        assert_eq!(copy_event.bytes, vec![(0xab, false); 2]);
+    }
+}
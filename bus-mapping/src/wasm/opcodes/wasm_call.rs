@@ -1,13 +1,15 @@
-    use eth_types::{GethExecStep, ToU256, ToWord};
+use eth_types::GethExecStep;
+use eth_types::bytecode::{wasm_function_locals, wasm_indirect_call_target};
 use eth_types::evm_types::OpcodeId;
 
-use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep, FunctionFrame, SuspendedFrame};
 use crate::Error;
 use crate::operation::CallContextField;
 
 use super::Opcode;
 
-///
+/// `call` and `call_indirect` push a function frame, the caller frame is
+/// suspended until the callee returns.
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct WasmCallOpcode;
 
@@ -21,28 +23,80 @@ impl Opcode for WasmCallOpcode {
 
         let mut exec_step = state.new_step(current_step)?;
 
-        match current_step.op {
-            OpcodeId::Call => {
-                let call_index = current_step.params[0];
-                let pc = next_step.pc;
-                state.call_context_write(
-                    &mut exec_step,
-                    state.call()?.call_id,
-                    CallContextField::InternalFunctionId,
-                    call_index.to_word(),
-                );
-                state.call_context_write(
-                    &mut exec_step,
-                    state.call()?.call_id,
-                    CallContextField::ProgramCounter,
-                    pc.0.to_u256(),
-                );
-            }
+        let function_index = match current_step.op {
+            OpcodeId::Call => current_step.params[0],
             OpcodeId::CallIndirect => {
-
+                // traps are handled as `ErrorInvalidIndirectCall`, so the call is resolved
+                let elem_index = current_step.stack.last()?;
+                state.stack_read(&mut exec_step, current_step.stack.last_filled(), elem_index)?;
+                let code = state.code(state.call()?.code_hash)?;
+                wasm_indirect_call_target(&code, current_step.params[0], elem_index.as_u64())
+                    .ok_or_else(|| {
+                        Error::InvalidGethExecStep(
+                            "indirect call does not resolve to a function",
+                            Box::new(current_step.clone()),
+                        )
+                    })?
             }
             _ => unreachable!("not supported opcode: {:?}", current_step.op)
         };
+        let code = state.code(state.call()?.code_hash)?;
+        let (_, num_locals) = wasm_function_locals(&code)
+            .and_then(|locals| locals.get(function_index as usize).copied())
+            .expect("called function must have local declarations");
+
+        // the caller frame is saved in call context rows keyed by the callee
+        // frame, which the return reads back
+        let call_id = state.call()?.call_id;
+        let caller = state.call_ctx()?.function_frame;
+        let depth = state.call_ctx()?.function_depth;
+        for (field, value) in [
+            (CallContextField::InternalFunctionId, caller.function_id.into()),
+            (CallContextField::LocalsBase, caller.locals_base.into()),
+            (CallContextField::LocalsFrame, caller.locals_frame.into()),
+            (CallContextField::FunctionDepth, depth.into()),
+        ] {
+            state.call_context_read(&mut exec_step, call_id, field, value);
+        }
+        let frame_id = exec_step.rwc.0;
+        let return_pc = current_step.pc.0 as u64 + 1;
+        for (field, value) in [
+            (CallContextField::InternalFunctionId, caller.function_id.into()),
+            (CallContextField::LocalsBase, caller.locals_base.into()),
+            (CallContextField::LocalsFrame, caller.locals_frame.into()),
+            (CallContextField::ProgramCounter, return_pc.into()),
+        ] {
+            state.call_context_write(&mut exec_step, frame_id, field, value);
+        }
+
+        // the callee starts with its params and its zeroed declared locals on
+        // top of the stack
+        let frame = FunctionFrame {
+            function_id: function_index + 1,
+            locals_base: next_step.stack.0.len() - num_locals as usize,
+            locals_frame: frame_id - call_id,
+        };
+        let pc = next_step.pc;
+        for (field, value) in [
+            (CallContextField::InternalFunctionId, frame.function_id.into()),
+            (CallContextField::ProgramCounter, pc.0.into()),
+            (CallContextField::LocalsBase, frame.locals_base.into()),
+            (CallContextField::LocalsFrame, frame.locals_frame.into()),
+            (CallContextField::FunctionDepth, (depth + 1).into()),
+        ] {
+            state.call_context_write(&mut exec_step, call_id, field, value);
+        }
+
+        // the callee opens no control frame of the caller
+        let call_ctx = state.call_ctx_mut()?;
+        let control_frames = std::mem::take(&mut call_ctx.control_frames);
+        call_ctx.suspended_frames.push(SuspendedFrame {
+            function_frame: caller,
+            return_pc,
+            control_frames,
+        });
+        call_ctx.function_frame = frame;
+        call_ctx.function_depth = depth + 1;
 
         Ok(vec![exec_step])
     }
@@ -0,0 +1,98 @@
+use super::check_wasm_fuel;
+use crate::{circuit_input_builder::CircuitsParams, mock::BlockData};
+use eth_types::{
+    bytecode,
+    evm_types::{OpcodeId, WasmFuelSchedule},
+    geth_types::GethData,
+    Bytecode,
+};
+use mock::TestContext;
+
+/// Runs an instruction of every fuel class, a successful and a failed
+/// `memory.grow` and a call.
+fn code_with_every_fuel_class() -> Bytecode {
+    let mut code = bytecode! {
+        I32Const[7]
+        I32Const[2]
+        I32DivU
+        I32Const[3]
+        I32Add
+        Drop
+        I32Const[0]
+        I32Const[1]
+        .write_memarg(OpcodeId::I32Store, 0, 0, 0)
+        I32Const[0]
+        .write_memarg(OpcodeId::I32Load, 0, 0, 0)
+        Drop
+        Block
+            I32Const[1]
+            BrIf[0]
+        End
+        I32Const[1]
+        GrowMemory
+        Drop
+        I32Const[0x10000]
+        GrowMemory
+        Drop
+        Call[0]
+    };
+    code.new_function(
+        vec![],
+        vec![],
+        bytecode! {
+            Return
+        },
+        vec![],
+    );
+    code
+}
+
+#[test]
+fn traced_wasm_gas_matches_the_default_schedule() {
+    let block: GethData =
+        TestContext::<2, 1>::simple_ctx_with_bytecode(code_with_every_fuel_class())
+            .unwrap()
+            .into();
+    let steps = &block.geth_traces[0].struct_logs;
+
+    let schedule = WasmFuelSchedule::DEFAULT;
+    for (idx, step) in steps.iter().enumerate() {
+        check_wasm_fuel(&schedule, step, steps.get(idx + 1)).unwrap();
+    }
+    for opcode in [
+        OpcodeId::I32DivU,
+        OpcodeId::I32Add,
+        OpcodeId::I32Store,
+        OpcodeId::I32Load,
+        OpcodeId::BrIf,
+        OpcodeId::GrowMemory,
+        OpcodeId::Call,
+    ] {
+        assert!(
+            steps.iter().any(|step| step.op == opcode),
+            "{:?} not traced",
+            opcode
+        );
+    }
+}
+
+#[test]
+fn traced_wasm_gas_diverging_from_the_schedule_is_rejected() {
+    let block: GethData =
+        TestContext::<2, 1>::simple_ctx_with_bytecode(code_with_every_fuel_class())
+            .unwrap()
+            .into();
+    let circuits_params = CircuitsParams {
+        wasm_fuel_schedule: WasmFuelSchedule {
+            base: WasmFuelSchedule::DEFAULT.base + 1,
+            ..WasmFuelSchedule::DEFAULT
+        },
+        ..Default::default()
+    };
+
+    let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), circuits_params)
+        .new_circuit_input_builder();
+    assert!(builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .is_err());
+}
@@ -0,0 +1,153 @@
+use eth_types::evm_types::{MemoryAddress, OpcodeId};
+use eth_types::GethExecStep;
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::operation::CallContextField;
+use crate::Error;
+
+use super::Opcode;
+
+///
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmLoadOpcode;
+
+impl Opcode for WasmLoadOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+        let next_step = &geth_steps[1];
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        let n_bytes = match current_step.op {
+            OpcodeId::I32Load8S | OpcodeId::I32Load8U |
+            OpcodeId::I64Load8S | OpcodeId::I64Load8U => 1,
+            OpcodeId::I32Load16S | OpcodeId::I32Load16U |
+            OpcodeId::I64Load16S | OpcodeId::I64Load16U => 2,
+            OpcodeId::I32Load | OpcodeId::I64Load32S | OpcodeId::I64Load32U => 4,
+            OpcodeId::I64Load => 8,
+            _ => unreachable!("not supported opcode: {:?}", current_step.op)
+        };
+        let memarg_offset = current_step.params[0];
+
+        let raw_address = current_step.stack.nth_last(0)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), raw_address)?;
+
+        // the memory size the accessed range is checked against
+        let call_id = state.call()?.call_id;
+        let grown_pages = state.call_ctx()?.grown_memory_pages;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::GrownMemoryPages,
+            grown_pages.into(),
+        );
+
+        // the low bytes of the loaded value are exactly the bytes read from memory,
+        // sign or zero extension only touches the high ones
+        let value = next_step.stack.nth_last(0)?;
+        let address = MemoryAddress((raw_address.as_u64() + memarg_offset) as usize);
+        for (i, byte) in value.as_u64().to_le_bytes().iter().take(n_bytes).enumerate() {
+            state.memory_read(&mut exec_step, address.map(|a| a + i), *byte)?;
+        }
+
+        state.stack_write(&mut exec_step, next_step.stack.nth_last_filled(0), value)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_load_tests {
+    use eth_types::{
+        bytecode_internal,
+        evm_types::{MemoryAddress, OpcodeId, StackAddress},
+        geth_types::GethData,
+        Bytecode, StackWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, MemoryOp, StackOp, RW},
+    };
+
+    #[test]
+    fn wasm_load_reads_the_bytes_past_the_memarg_offset() {
+        let mut code = Bytecode::default();
+        let dest = code.fill_default_global_data(vec![0x11, 0x22, 0x33, 0x44]);
+        bytecode_internal! {code,
+            I32Const[dest]
+            .write_memarg(OpcodeId::I32Load16U, 1, 0, 0)
+            Drop
+        }
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::I32Load16U))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        // 1 stack read, 1 call context read, 2 memory reads and 1 stack write.
+        assert_eq!(step.bus_mapping_instance.len(), 5);
+        assert_eq!(
+            [0, 4]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), StackWord::from(dest)),
+                ),
+                (
+                    RW::WRITE,
+                    &StackOp::new(call_id, StackAddress::from(1023), StackWord::from(0x3322u64)),
+                ),
+            ]
+        );
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.call_context[step.bus_mapping_instance[1].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id,
+                    field: CallContextField::GrownMemoryPages,
+                    value: Word::zero(),
+                }
+            )
+        );
+        assert_eq!(
+            [2, 3]
+                .map(|idx| &builder.block.container.memory
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op().clone())),
+            [
+                (
+                    RW::READ,
+                    MemoryOp::new(call_id, MemoryAddress::from(dest + 1), 0x22)
+                ),
+                (
+                    RW::READ,
+                    MemoryOp::new(call_id, MemoryAddress::from(dest + 2), 0x33)
+                ),
+            ]
+        );
+    }
+}
@@ -1,12 +1,17 @@
-use eth_types::GethExecStep;
-use eth_types::evm_types::OpcodeId;
+use eth_types::{GethExecStep, StackWord};
+use eth_types::bytecode::{wasm_entry_function, wasm_function_locals};
+use eth_types::evm_types::{OpcodeId, StackAddress};
 
 use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
 use crate::Error;
+use crate::operation::CallContextField;
 
 use super::Opcode;
 
-///
+/// `local.get`, `local.set` and `local.tee`. The trace addresses a local by its
+/// depth in the stack, which gives its index once the frame base is known.
+/// Params are the stack slots the caller pushed, declared locals are read and
+/// written in their own rw target.
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct WasmLocalOpcode;
 
@@ -20,24 +25,63 @@ impl Opcode for WasmLocalOpcode {
 
         let mut exec_step = state.new_step(current_step)?;
 
-        let local_index = current_step.params[0] as usize;
-        let local_offset = local_index - 1;
+        let call_id = state.call()?.call_id;
+        let frame = state.call_ctx()?.function_frame;
+        for (field, value) in [
+            (CallContextField::InternalFunctionId, frame.function_id.into()),
+            (CallContextField::LocalsBase, frame.locals_base.into()),
+            (CallContextField::LocalsFrame, frame.locals_frame.into()),
+        ] {
+            state.call_context_read(&mut exec_step, call_id, field, value);
+        }
+
+        let code = state.code(state.call()?.code_hash)?;
+        let function_index = match frame.function_id {
+            0 => wasm_entry_function(&code),
+            function_id => Some(function_id - 1),
+        };
+        let (num_params, num_locals) = function_index
+            .and_then(|index| wasm_function_locals(&code)?.get(index as usize).copied())
+            .ok_or(Error::InvalidGethExecStep(
+                "running function has no local declarations",
+                Box::new(current_step.clone()),
+            ))?;
+
+        // the local sits in the stack the access is made to, which is the
+        // stack after the pop for `local.set` and `local.tee`
+        let depth = current_step.params[0] as usize;
+        let stack = if current_step.op == OpcodeId::GetLocal {
+            &current_step.stack
+        } else {
+            &next_step.stack
+        };
+        let local_index = stack
+            .0
+            .len()
+            .checked_sub(depth + frame.locals_base)
+            .filter(|index| (*index as u64) < num_locals)
+            .ok_or(Error::InvalidGethExecStep(
+                "local index out of the declared locals",
+                Box::new(current_step.clone()),
+            ))?;
+        let is_param = (local_index as u64) < num_params;
+        let address = stack.nth_last_filled(depth - 1);
 
         match current_step.op {
             OpcodeId::SetLocal => {
                 let value = current_step.stack.nth_last(0)?;
                 state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), value)?;
-                state.local_write(&mut exec_step, next_step.stack.nth_last_filled(local_offset), local_offset, value)?;
+                local_access(state, &mut exec_step, true, is_param, address, local_index, value)?;
             }
             OpcodeId::GetLocal => {
-                let value = current_step.stack.nth_last(local_offset)?;
-                state.local_read(&mut exec_step, current_step.stack.nth_last_filled(local_offset), local_offset, value)?;
+                let value = current_step.stack.nth_last(depth - 1)?;
+                local_access(state, &mut exec_step, false, is_param, address, local_index, value)?;
                 state.stack_write(&mut exec_step, next_step.stack.nth_last_filled(0), value)?;
             }
             OpcodeId::TeeLocal => {
                 let value = current_step.stack.nth_last(0)?;
                 state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), value)?;
-                state.local_write(&mut exec_step, next_step.stack.nth_last_filled(local_offset), local_offset, value)?;
+                local_access(state, &mut exec_step, true, is_param, address, local_index, value)?;
                 state.stack_write(&mut exec_step, next_step.stack.nth_last_filled(0), value)?;
             }
             _ => unreachable!("not supported opcode: {:?}", current_step.op)
@@ -46,3 +90,20 @@ impl Opcode for WasmLocalOpcode {
         Ok(vec![exec_step])
     }
 }
+
+fn local_access(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    is_write: bool,
+    is_param: bool,
+    address: StackAddress,
+    local_index: usize,
+    value: StackWord,
+) -> Result<(), Error> {
+    match (is_write, is_param) {
+        (true, true) => state.param_write(exec_step, address, local_index, value),
+        (false, true) => state.param_read(exec_step, address, local_index, value),
+        (true, false) => state.local_write(exec_step, local_index as u32, value),
+        (false, false) => state.local_read(exec_step, local_index as u32, value),
+    }
+}
@@ -0,0 +1,214 @@
+use eth_types::bytecode::wasm_memory_limits;
+use eth_types::evm_types::OpcodeId;
+use eth_types::{GethExecStep, StackWord};
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::operation::CallContextField;
+use crate::Error;
+
+use super::Opcode;
+
+/// Handles `memory.size` and `memory.grow`. The call context keeps the number of
+/// pages grown on top of the initial memory, so it starts at zero for every call.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmMemoryGrowOpcode;
+
+impl Opcode for WasmMemoryGrowOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+        let next_step = &geth_steps[1];
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        let call = state.call()?.clone();
+        let code = state.code(call.code_hash)?;
+        let (initial_pages, max_pages) = wasm_memory_limits(&code).unwrap_or_default();
+
+        let delta = if current_step.op == OpcodeId::GrowMemory {
+            let delta = current_step.stack.nth_last(0)?;
+            state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), delta)?;
+            delta.as_u64()
+        } else {
+            0
+        };
+
+        let grown_pages = state.call_ctx()?.grown_memory_pages;
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::GrownMemoryPages,
+            grown_pages.into(),
+        );
+
+        let old_pages = initial_pages + grown_pages;
+        let result = if old_pages + delta > max_pages {
+            // growing beyond the declared maximum fails with -1 and leaves memory untouched
+            0xffffffff
+        } else {
+            if current_step.op == OpcodeId::GrowMemory {
+                state.call_context_write(
+                    &mut exec_step,
+                    call.call_id,
+                    CallContextField::GrownMemoryPages,
+                    (grown_pages + delta).into(),
+                );
+                state.call_ctx_mut()?.grown_memory_pages = grown_pages + delta;
+            }
+            old_pages
+        };
+        debug_assert_eq!(
+            StackWord::from(result),
+            next_step.stack.nth_last(0)?,
+            "memory page count diverges from the trace"
+        );
+
+        state.stack_write(&mut exec_step, next_step.stack.nth_last_filled(0), result.into())?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_memory_grow_tests {
+    use eth_types::{
+        bytecode,
+        evm_types::{OpcodeId, StackAddress},
+        geth_types::GethData,
+        StackWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::{CircuitInputBuilder, ExecState, ExecStep},
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, StackOp, RW},
+    };
+
+    /// Grows the single initial page by one page up to the declared maximum of two
+    /// pages, then tries to grow past it.
+    fn grow_memory_twice() -> CircuitInputBuilder {
+        let mut code = bytecode! {
+            I32Const[1]
+            GrowMemory
+            Drop
+            I32Const[1]
+            GrowMemory
+            Drop
+        };
+        code.with_max_memory_pages(2);
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder
+    }
+
+    fn grow_memory_steps(builder: &CircuitInputBuilder) -> Vec<&ExecStep> {
+        builder.block.txs()[0]
+            .steps()
+            .iter()
+            .filter(|step| step.exec_state == ExecState::Op(OpcodeId::GrowMemory))
+            .collect()
+    }
+
+    #[test]
+    fn wasm_memory_grow_within_the_maximum_bumps_the_grown_pages() {
+        let builder = grow_memory_twice();
+        let step = grow_memory_steps(&builder)[0];
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        // 1 stack read, 1 call context read, 1 call context write and 1 stack write.
+        assert_eq!(step.bus_mapping_instance.len(), 4);
+        assert_eq!(
+            [0, 3]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), StackWord::from(1u64)),
+                ),
+                (
+                    RW::WRITE,
+                    &StackOp::new(call_id, StackAddress::from(1023), StackWord::from(1u64)),
+                ),
+            ]
+        );
+        assert_eq!(
+            [1, 2]
+                .map(|idx| &builder.block.container.call_context
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::GrownMemoryPages,
+                        value: Word::zero(),
+                    }
+                ),
+                (
+                    RW::WRITE,
+                    &CallContextOp {
+                        call_id,
+                        field: CallContextField::GrownMemoryPages,
+                        value: Word::one(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn wasm_memory_grow_past_the_maximum_pushes_minus_one() {
+        let builder = grow_memory_twice();
+        let step = grow_memory_steps(&builder)[1];
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        // 1 stack read, 1 call context read and 1 stack write, the grown pages are kept.
+        assert_eq!(step.bus_mapping_instance.len(), 3);
+        assert_eq!(
+            [0, 2]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), StackWord::from(1u64)),
+                ),
+                (
+                    RW::WRITE,
+                    &StackOp::new(
+                        call_id,
+                        StackAddress::from(1023),
+                        StackWord::from(0xffffffffu64)
+                    ),
+                ),
+            ]
+        );
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.call_context[step.bus_mapping_instance[1].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id,
+                    field: CallContextField::GrownMemoryPages,
+                    value: Word::one(),
+                }
+            )
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use eth_types::GethExecStep;
+use eth_types::bytecode::{wasm_function_types, wasm_type_arities};
+use eth_types::evm_types::{StackAddress, MAX_WASM_ARITY};
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::Error;
+use crate::operation::CallContextField;
+
+use super::Opcode;
+
+/// `return`, or the `end` of the function body, leaving a function called by
+/// `call` or `call_indirect`. The results are moved right above the caller
+/// stack and the suspended caller frame is restored. At most
+/// [`MAX_WASM_ARITY`] results are supported.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmReturnOpcode;
+
+impl Opcode for WasmReturnOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+
+        let mut exec_step = state.new_step(current_step)?;
+        gen_return_ops(state, &mut exec_step, current_step, 0)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+/// Leaves the running function to its caller. A `br` or `br_if` to the label
+/// of the function body returns as well, `n_pops` is the number of operands
+/// the instruction already popped above the results.
+pub(crate) fn gen_return_ops(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    current_step: &GethExecStep,
+    n_pops: usize,
+) -> Result<(), Error> {
+    let call_id = state.call()?.call_id;
+    let callee = state.call_ctx()?.function_frame;
+    let depth = state.call_ctx()?.function_depth;
+    for (field, value) in [
+        (CallContextField::InternalFunctionId, callee.function_id.into()),
+        (CallContextField::LocalsBase, callee.locals_base.into()),
+        (CallContextField::LocalsFrame, callee.locals_frame.into()),
+        (CallContextField::FunctionDepth, depth.into()),
+    ] {
+        state.call_context_read(exec_step, call_id, field, value);
+    }
+
+    let code = state.code(state.call()?.code_hash)?;
+    let num_results = wasm_function_types(&code)
+        .and_then(|types| types.get(callee.function_id as usize - 1).copied())
+        .and_then(|type_index| wasm_type_arities(&code)?.get(type_index as usize).copied())
+        .map(|(_, num_results)| num_results as usize)
+        .ok_or(Error::InvalidGethExecStep(
+            "returning function has no type",
+            Box::new(current_step.clone()),
+        ))?;
+    if num_results as u64 > MAX_WASM_ARITY {
+        return Err(Error::InvalidGethExecStep(
+            "returning function has more results than supported",
+            Box::new(current_step.clone()),
+        ));
+    }
+    // all the results are read before the first write, the ranges may overlap
+    let mut values = Vec::with_capacity(num_results);
+    for i in 0..num_results {
+        let value = current_step.stack.nth_last(n_pops + i)?;
+        state.stack_read(exec_step, current_step.stack.nth_last_filled(n_pops + i), value)?;
+        values.push(value);
+    }
+    for (i, value) in values.into_iter().enumerate() {
+        state.stack_write(
+            exec_step,
+            StackAddress::from(1024 - callee.locals_base - num_results + i),
+            value,
+        )?;
+    }
+
+    let caller = state
+        .call_ctx_mut()?
+        .suspended_frames
+        .pop()
+        .expect("returning function must have a caller");
+    let frame_id = call_id + callee.locals_frame;
+    for (field, value) in [
+        (CallContextField::InternalFunctionId, caller.function_frame.function_id.into()),
+        (CallContextField::LocalsBase, caller.function_frame.locals_base.into()),
+        (CallContextField::LocalsFrame, caller.function_frame.locals_frame.into()),
+        (CallContextField::ProgramCounter, caller.return_pc.into()),
+    ] {
+        state.call_context_read(exec_step, frame_id, field, value);
+    }
+    for (field, value) in [
+        (CallContextField::InternalFunctionId, caller.function_frame.function_id.into()),
+        (CallContextField::LocalsBase, caller.function_frame.locals_base.into()),
+        (CallContextField::LocalsFrame, caller.function_frame.locals_frame.into()),
+        (CallContextField::FunctionDepth, (depth - 1).into()),
+    ] {
+        state.call_context_write(exec_step, call_id, field, value);
+    }
+
+    let call_ctx = state.call_ctx_mut()?;
+    call_ctx.function_depth = depth - 1;
+    call_ctx.function_frame = caller.function_frame;
+    call_ctx.control_frames = caller.control_frames;
+    exec_step.function_frame = Some(callee);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod wasm_return_tests {
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, RW},
+    };
+
+    #[test]
+    fn wasm_end_of_a_called_function_restores_the_caller_frame() {
+        let mut code = bytecode! {
+            Call[0]
+        };
+        code.new_function(vec![], vec![], bytecode! { Nop }, vec![]);
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let call_step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::Call))
+            .unwrap();
+        let step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::End))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+        let callee = step.function_frame.unwrap();
+        let frame_id = call_id + callee.locals_frame;
+        assert_ne!(frame_id, call_id);
+
+        let call_context_ops = |rw: RW, call_id: usize, fields: [(CallContextField, Word); 4]| {
+            fields.map(|(field, value)| {
+                (
+                    rw,
+                    CallContextOp {
+                        call_id,
+                        field,
+                        value,
+                    },
+                )
+            })
+        };
+        // the callee frame and depth, the caller frame saved by the call and the
+        // caller frame restored, no results to move.
+        assert_eq!(step.bus_mapping_instance.len(), 12);
+        assert_eq!(
+            step.bus_mapping_instance
+                .iter()
+                .map(|op_ref| {
+                    let operation = &builder.block.container.call_context[op_ref.as_usize()];
+                    (operation.rw(), operation.op().clone())
+                })
+                .collect::<Vec<_>>(),
+            [
+                call_context_ops(
+                    RW::READ,
+                    call_id,
+                    [
+                        (
+                            CallContextField::InternalFunctionId,
+                            callee.function_id.into()
+                        ),
+                        (CallContextField::LocalsBase, callee.locals_base.into()),
+                        (CallContextField::LocalsFrame, callee.locals_frame.into()),
+                        (CallContextField::FunctionDepth, Word::one()),
+                    ]
+                ),
+                call_context_ops(
+                    RW::READ,
+                    frame_id,
+                    [
+                        (CallContextField::InternalFunctionId, Word::zero()),
+                        (CallContextField::LocalsBase, Word::zero()),
+                        (CallContextField::LocalsFrame, Word::zero()),
+                        (
+                            CallContextField::ProgramCounter,
+                            (call_step.pc.0 + 1).into()
+                        ),
+                    ]
+                ),
+                call_context_ops(
+                    RW::WRITE,
+                    call_id,
+                    [
+                        (CallContextField::InternalFunctionId, Word::zero()),
+                        (CallContextField::LocalsBase, Word::zero()),
+                        (CallContextField::LocalsFrame, Word::zero()),
+                        (CallContextField::FunctionDepth, Word::zero()),
+                    ]
+                ),
+            ]
+            .concat()
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use eth_types::evm_types::{MemoryAddress, OpcodeId};
+use eth_types::GethExecStep;
+
+use crate::circuit_input_builder::{CircuitInputStateRef, ExecStep};
+use crate::operation::CallContextField;
+use crate::Error;
+
+use super::Opcode;
+
+///
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WasmStoreOpcode;
+
+impl Opcode for WasmStoreOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let current_step = &geth_steps[0];
+        let next_step = &geth_steps[1];
+
+        let mut exec_step = state.new_step(current_step)?;
+
+        let n_bytes = match current_step.op {
+            OpcodeId::I32Store8 | OpcodeId::I64Store8 => 1,
+            OpcodeId::I32Store16 | OpcodeId::I64Store16 => 2,
+            OpcodeId::I32Store | OpcodeId::I64Store32 => 4,
+            OpcodeId::I64Store => 8,
+            _ => unreachable!("not supported opcode: {:?}", current_step.op)
+        };
+        let memarg_offset = current_step.params[0];
+
+        let value = current_step.stack.nth_last(0)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(0), value)?;
+        let raw_address = current_step.stack.nth_last(1)?;
+        state.stack_read(&mut exec_step, current_step.stack.nth_last_filled(1), raw_address)?;
+
+        // the memory size the accessed range is checked against
+        let call_id = state.call()?.call_id;
+        let grown_pages = state.call_ctx()?.grown_memory_pages;
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::GrownMemoryPages,
+            grown_pages.into(),
+        );
+
+        let address = MemoryAddress((raw_address.as_u64() + memarg_offset) as usize);
+        for (i, byte) in value.as_u64().to_le_bytes().iter().take(n_bytes).enumerate() {
+            state.memory_write(&mut exec_step, address.map(|a| a + i), *byte)?;
+        }
+        let call_ctx = state.call_ctx_mut()?;
+        call_ctx.memory = next_step.global_memory.clone();
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod wasm_store_tests {
+    use eth_types::{
+        bytecode_internal,
+        evm_types::{MemoryAddress, OpcodeId, StackAddress},
+        geth_types::GethData,
+        Bytecode, StackWord, Word,
+    };
+    use mock::TestContext;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, MemoryOp, StackOp, RW},
+    };
+
+    #[test]
+    fn wasm_store_writes_the_low_bytes_past_the_memarg_offset() {
+        let mut code = Bytecode::default();
+        let dest = code.fill_default_global_data(vec![0; 4]);
+        bytecode_internal! {code,
+            I32Const[dest]
+            I32Const[0x1234]
+            .write_memarg(OpcodeId::I32Store16, 2, 0, 0)
+        }
+
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::I32Store16))
+            .unwrap();
+        let call_id = builder.block.txs()[0].calls()[0].call_id;
+
+        // 2 stack reads, 1 call context read and 2 memory writes.
+        assert_eq!(step.bus_mapping_instance.len(), 5);
+        assert_eq!(
+            [0, 1]
+                .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op())),
+            [
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1022), StackWord::from(0x1234u64)),
+                ),
+                (
+                    RW::READ,
+                    &StackOp::new(call_id, StackAddress::from(1023), StackWord::from(dest)),
+                ),
+            ]
+        );
+        assert_eq!(
+            {
+                let operation =
+                    &builder.block.container.call_context[step.bus_mapping_instance[2].as_usize()];
+                (operation.rw(), operation.op())
+            },
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id,
+                    field: CallContextField::GrownMemoryPages,
+                    value: Word::zero(),
+                }
+            )
+        );
+        assert_eq!(
+            [3, 4]
+                .map(|idx| &builder.block.container.memory
+                    [step.bus_mapping_instance[idx].as_usize()])
+                .map(|op| (op.rw(), op.op().clone())),
+            [
+                (
+                    RW::WRITE,
+                    MemoryOp::new(call_id, MemoryAddress::from(dest + 2), 0x34)
+                ),
+                (
+                    RW::WRITE,
+                    MemoryOp::new(call_id, MemoryAddress::from(dest + 3), 0x12)
+                ),
+            ]
+        );
+    }
+}
@@ -4,7 +4,7 @@
 mod tests {
     use ark_std::{end_timer, start_timer};
     use bus_mapping::circuit_input_builder::CircuitsParams;
-    use eth_types::{address, bytecode, geth_types::GethData, Word};
+    use eth_types::{address, bytecode, evm_types::WasmFuelSchedule, geth_types::GethData, Word};
     use ethers_signers::{LocalWallet, Signer};
     use halo2_proofs::{
         halo2curves::bn256::{Bn256, Fr, G1Affine},
@@ -94,6 +94,7 @@ mod tests {
             max_inner_blocks: MAX_INNER_BLOCKS,
             max_keccak_rows: 0,
             max_rlp_rows: 256,
+            wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
         };
         let (_, circuit, instance, _) =
             SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, 0x100>::build(
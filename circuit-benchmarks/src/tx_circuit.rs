@@ -5,6 +5,7 @@ mod tests {
     use ark_std::{end_timer, start_timer};
     use bus_mapping::circuit_input_builder::{BuilderClient, CircuitsParams};
     use env_logger::Env;
+    use eth_types::evm_types::WasmFuelSchedule;
     use halo2_proofs::{
         halo2curves::bn256::{Bn256, Fr, G1Affine},
         plonk::{create_proof, keygen_pk, keygen_vk, verify_proof},
@@ -55,6 +56,7 @@ mod tests {
             max_exp_steps: 100_000,
             max_evm_rows: 4_000_000,
             max_rlp_rows: 4_000_000,
+            wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
         };
         let cli = BuilderClient::new(cli, params).await.unwrap();
         let (builder, _) = cli.gen_inputs(block_num).await.unwrap();
@@ -1,13 +1,13 @@
 //! EVM byte code generator
 
-use crate::{evm_types::OpcodeId, Bytes, ToWord, Word, Address, U256, ToLittleEndian};
+use crate::{evm_types::{opcode_ids::WASM_PREFIX_FC, OpcodeId}, Bytes, ToWord, Word, Address, U256, ToBigEndian};
 use std::{collections::HashMap, str::FromStr};
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
-use wasm_encoder::{CodeSection, ConstExpr, DataSection, Encode, Function, FunctionSection, GlobalSection, GlobalType, Instruction, MemArg, TypeSection, ValType};
+use wasm_encoder::{BlockType, CodeSection, ConstExpr, DataCountSection, DataSection, ElementSection, Elements, Encode, Function, FunctionSection, GlobalSection, GlobalType, Instruction, MemArg, TableSection, TableType, TypeSection, ValType};
 use wasm_encoder::BlockType::Empty;
 
 /// Error type for Bytecode related failures
@@ -41,6 +41,59 @@ impl Ord for SectionDescriptor {
     }
 }
 
+/// Module every host function is imported from
+pub const WASM_HOST_MODULE: &str = "env";
+
+/// Host functions a wasm contract can import, with the opcode each one stands for and the number
+/// of i32 params it takes. None of them returns a value.
+pub const WASM_HOST_FUNCTIONS: &[(OpcodeId, &str, usize)] = &[
+    (OpcodeId::STOP, "_evm_stop", 0),
+    (OpcodeId::RETURN, "_evm_return", 2),
+    (OpcodeId::SHA3, "_evm_keccak256", 3),
+    (OpcodeId::ADDRESS, "_evm_address", 1),
+    (OpcodeId::BALANCE, "_evm_balance", 2),
+    (OpcodeId::ORIGIN, "_evm_origin", 1),
+    (OpcodeId::CALLER, "_evm_caller", 1),
+    (OpcodeId::CALLVALUE, "_evm_callvalue", 1),
+    (OpcodeId::CALLDATALOAD, "_evm_calldataload", 2),
+    (OpcodeId::CALLDATASIZE, "_evm_calldatasize", 1),
+    (OpcodeId::CALLDATACOPY, "_evm_calldatacopy", 3),
+    (OpcodeId::CODESIZE, "_evm_codesize", 1),
+    (OpcodeId::CODECOPY, "_evm_codecopy", 3),
+    (OpcodeId::GASPRICE, "_evm_gasprice", 1),
+    (OpcodeId::EXTCODESIZE, "_evm_extcodesize", 2),
+    (OpcodeId::EXTCODECOPY, "_evm_extcodecopy", 4),
+    (OpcodeId::EXTCODEHASH, "_evm_extcodehash", 2),
+    (OpcodeId::RETURNDATASIZE, "_evm_returndatasize", 1),
+    (OpcodeId::RETURNDATACOPY, "_evm_returndatacopy", 3),
+    (OpcodeId::BLOCKHASH, "_evm_blockhash", 2),
+    (OpcodeId::COINBASE, "_evm_coinbase", 1),
+    (OpcodeId::TIMESTAMP, "_evm_timestamp", 1),
+    (OpcodeId::NUMBER, "_evm_number", 1),
+    (OpcodeId::DIFFICULTY, "_evm_difficulty", 1),
+    (OpcodeId::GASLIMIT, "_evm_gaslimit", 1),
+    (OpcodeId::CHAINID, "_evm_chainid", 1),
+    (OpcodeId::BASEFEE, "_evm_basefee", 1),
+    (OpcodeId::SLOAD, "_evm_sload", 2),
+    (OpcodeId::SSTORE, "_evm_sstore", 2),
+    (OpcodeId::GAS, "_evm_gas", 1),
+    (OpcodeId::PC, "_evm_pc", 1),
+    (OpcodeId::MSIZE, "_evm_msize", 1),
+    (OpcodeId::LOG0, "_evm_log0", 2),
+    (OpcodeId::LOG1, "_evm_log1", 3),
+    (OpcodeId::LOG2, "_evm_log2", 4),
+    (OpcodeId::LOG3, "_evm_log3", 5),
+    (OpcodeId::LOG4, "_evm_log4", 6),
+    (OpcodeId::CREATE, "_evm_create", 4),
+    (OpcodeId::CALL, "_evm_call", 8),
+    (OpcodeId::CALLCODE, "_evm_callcode", 8),
+    (OpcodeId::DELEGATECALL, "_evm_delegatecall", 7),
+    (OpcodeId::CREATE2, "_evm_create2", 5),
+    (OpcodeId::STATICCALL, "_evm_staticcall", 7),
+    (OpcodeId::REVERT, "_evm_revert", 2),
+    (OpcodeId::SELFBALANCE, "_evm_selfbalance", 1),
+];
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct EvmCall {
     fn_name: &'static str,
@@ -117,6 +170,9 @@ pub struct Bytecode {
     evm_table: HashMap<EvmCall, usize>,
     num_opcodes: usize,
     markers: HashMap<String, usize>,
+    max_memory_pages: Option<u32>,
+    table_elements: Vec<u32>,
+    passive_data: Vec<Vec<u8>>,
 }
 
 impl From<Bytecode> for Bytes {
@@ -157,13 +213,22 @@ impl WasmBinaryBytecode for Bytecode {
             .map(|(k, v)| (v, k))
             .collect::<BTreeMap<_, _>>();
         for (_, evm_call) in ordered_evm_table {
-            imports.import("env", evm_call.fn_name, EntityType::Function(evm_call.type_index));
+            imports.import(WASM_HOST_MODULE, evm_call.fn_name, EntityType::Function(evm_call.type_index));
+        }
+        // Create table section
+        let mut tables = TableSection::new();
+        if self.table_elements.len() > 0 {
+            tables.table(TableType {
+                element_type: ValType::FuncRef,
+                minimum: self.table_elements.len() as u32,
+                maximum: Some(self.table_elements.len() as u32),
+            });
         }
         // Create memory section
         let mut memories = MemorySection::new();
         memories.memory(MemoryType {
             minimum: 1,
-            maximum: None,
+            maximum: self.max_memory_pages.map(|pages| pages as u64),
             memory64: false,
             shared: false,
         });
@@ -183,6 +248,9 @@ impl WasmBinaryBytecode for Bytecode {
         module.section(&self.types);
         module.section(&imports);
         module.section(&functions);
+        if self.table_elements.len() > 0 {
+            module.section(&tables);
+        }
         module.section(&memories);
         if self.variables.len() > 0 {
             let mut global_section = GlobalSection::new();
@@ -197,7 +265,31 @@ impl WasmBinaryBytecode for Bytecode {
             module.section(&global_section);
         }
         module.section(&exports);
+        if self.table_elements.len() > 0 {
+            let mut elements = ElementSection::new();
+            elements.active(
+                None,
+                &ConstExpr::i32_const(0),
+                ValType::FuncRef,
+                Elements::Functions(&self.table_elements),
+            );
+            module.section(&elements);
+        }
+        // passive segments are referenced from the code, so their count goes first
+        if self.passive_data.len() > 0 {
+            let count = self.passive_data.len()
+                + self.section_descriptors.len()
+                + (self.global_data.1.len() > 0) as usize;
+            module.section(&DataCountSection { count: count as u32 });
+        }
         module.section(&codes);
+        if self.passive_data.len() > 0 {
+            let mut data_section = DataSection::new();
+            for data in &self.passive_data {
+                data_section.passive(data.clone());
+            }
+            module.section(&data_section);
+        }
         // if we have global data section then put it into final binary
         let mut sections = self.section_descriptors.clone();
         sections.sort();
@@ -221,6 +313,556 @@ impl WasmBinaryBytecode for Bytecode {
     }
 }
 
+/// Size of a WASM linear memory page in bytes.
+pub const WASM_PAGE_SIZE: u64 = 0x10000;
+/// Number of pages a 32-bit linear memory can grow to when no maximum is declared.
+pub const WASM_MAX_PAGES: u64 = 0x10000;
+
+fn read_leb128(code: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *code.get(*pos)?;
+        *pos += 1;
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(result);
+        }
+        shift += 7;
+        if shift >= 64 {
+            return None;
+        }
+    }
+}
+
+/// Returns the body of the section with the given id of a WASM binary.
+fn wasm_section(code: &[u8], id: u8) -> Option<&[u8]> {
+    let (start, end) = wasm_section_range(code, id)?;
+    code.get(start..end)
+}
+
+/// Returns the byte range of the body of the section with the given id inside
+/// a WASM binary.
+fn wasm_section_range(code: &[u8], id: u8) -> Option<(usize, usize)> {
+    // skip magic prefix and version
+    let mut pos = 8;
+    while pos < code.len() {
+        let section_id = code[pos];
+        pos += 1;
+        let section_len = read_leb128(code, &mut pos)? as usize;
+        if section_id == id {
+            return (pos + section_len <= code.len()).then_some((pos, pos + section_len));
+        }
+        pos += section_len;
+    }
+    None
+}
+
+fn skip_limits(code: &[u8], pos: &mut usize) -> Option<()> {
+    let flags = *code.get(*pos)?;
+    *pos += 1;
+    read_leb128(code, pos)?;
+    if flags & 1 != 0 {
+        read_leb128(code, pos)?;
+    }
+    Some(())
+}
+
+/// Reads a constant expression which is either an `i32.const` offset, a
+/// `ref.func` or a `ref.null`, the latter gives `None`.
+fn read_const_expr(code: &[u8], pos: &mut usize) -> Option<Option<u64>> {
+    let opcode = *code.get(*pos)?;
+    *pos += 1;
+    let value = match opcode {
+        // i32.const and ref.func, offsets are non-negative so the signed
+        // encoding reads the same
+        0x41 | 0xd2 => Some(read_leb128(code, pos)?),
+        // ref.null followed by its reference type
+        0xd0 => {
+            *pos += 1;
+            None
+        }
+        _ => return None,
+    };
+    if *code.get(*pos)? != 0x0b {
+        return None;
+    }
+    *pos += 1;
+    Some(value)
+}
+
+/// Returns the initial and the maximum page count of the memory declared in the
+/// memory section of a WASM binary, or `None` if the binary declares no memory.
+pub fn wasm_memory_limits(code: &[u8]) -> Option<(u64, u64)> {
+    const MEMORY_SECTION_ID: u8 = 5;
+    let section = wasm_section(code, MEMORY_SECTION_ID)?;
+    let mut pos = 0;
+    if read_leb128(section, &mut pos)? == 0 {
+        return None;
+    }
+    let flags = *section.get(pos)?;
+    pos += 1;
+    let initial_pages = read_leb128(section, &mut pos)?;
+    let max_pages = if flags & 1 != 0 {
+        read_leb128(section, &mut pos)?
+    } else {
+        WASM_MAX_PAGES
+    };
+    Some((initial_pages, max_pages))
+}
+
+/// Returns the position of the initial page count of the memory declared in the
+/// memory section of a WASM binary inside the binary and the count itself.
+pub fn wasm_initial_memory_pages(code: &[u8]) -> Option<(usize, u64)> {
+    const MEMORY_SECTION_ID: u8 = 5;
+    let (mut pos, _) = wasm_section_range(code, MEMORY_SECTION_ID)?;
+    if read_leb128(code, &mut pos)? == 0 {
+        return None;
+    }
+    // the limits flags
+    pos += 1;
+    let start = pos;
+    Some((start, read_leb128(code, &mut pos)?))
+}
+
+/// Returns the type index of every function of a WASM binary in the function
+/// index space, imported functions come first.
+pub fn wasm_function_types(code: &[u8]) -> Option<Vec<u64>> {
+    const IMPORT_SECTION_ID: u8 = 2;
+    const FUNCTION_SECTION_ID: u8 = 3;
+    let mut function_types = vec![];
+    if let Some(section) = wasm_section(code, IMPORT_SECTION_ID) {
+        let mut pos = 0;
+        for _ in 0..read_leb128(section, &mut pos)? {
+            // module and field names
+            for _ in 0..2 {
+                let len = read_leb128(section, &mut pos)? as usize;
+                pos += len;
+            }
+            let kind = *section.get(pos)?;
+            pos += 1;
+            match kind {
+                0x00 => function_types.push(read_leb128(section, &mut pos)?),
+                0x01 => {
+                    pos += 1;
+                    skip_limits(section, &mut pos)?;
+                }
+                0x02 => skip_limits(section, &mut pos)?,
+                0x03 => pos += 2,
+                _ => return None,
+            }
+        }
+    }
+    if let Some(section) = wasm_section(code, FUNCTION_SECTION_ID) {
+        let mut pos = 0;
+        for _ in 0..read_leb128(section, &mut pos)? {
+            function_types.push(read_leb128(section, &mut pos)?);
+        }
+    }
+    Some(function_types)
+}
+
+/// Returns the number of functions a WASM binary imports, they have no body
+/// and come first in the function index space.
+pub fn wasm_imported_function_count(code: &[u8]) -> Option<usize> {
+    const FUNCTION_SECTION_ID: u8 = 3;
+    let num_declared = match wasm_section(code, FUNCTION_SECTION_ID) {
+        Some(section) => read_leb128(section, &mut 0)? as usize,
+        None => 0,
+    };
+    wasm_function_types(code)?.len().checked_sub(num_declared)
+}
+
+/// Returns the number of params and results of every function type declared
+/// in the type section of a WASM binary.
+pub fn wasm_type_arities(code: &[u8]) -> Option<Vec<(u64, u64)>> {
+    const TYPE_SECTION_ID: u8 = 1;
+    let section = match wasm_section(code, TYPE_SECTION_ID) {
+        Some(section) => section,
+        None => return Some(vec![]),
+    };
+    let mut pos = 0;
+    let mut arities = vec![];
+    for _ in 0..read_leb128(section, &mut pos)? {
+        // function types are the only form
+        if *section.get(pos)? != 0x60 {
+            return None;
+        }
+        pos += 1;
+        // value types are encoded in a single byte
+        let params = read_leb128(section, &mut pos)?;
+        pos += params as usize;
+        let results = read_leb128(section, &mut pos)?;
+        pos += results as usize;
+        arities.push((params, results));
+    }
+    Some(arities)
+}
+
+/// Returns the number of params and the number of locals, params included, of
+/// every function of a WASM binary in the function index space. The locals of
+/// a defined function are the repetition counts of its local declarations.
+pub fn wasm_function_locals(code: &[u8]) -> Option<Vec<(u64, u64)>> {
+    const CODE_SECTION_ID: u8 = 10;
+    let arities = wasm_type_arities(code)?;
+    let mut locals = wasm_function_types(code)?
+        .into_iter()
+        .map(|type_index| arities.get(type_index as usize).map(|&(params, _)| (params, params)))
+        .collect::<Option<Vec<_>>>()?;
+    let section = match wasm_section(code, CODE_SECTION_ID) {
+        Some(section) => section,
+        None => return Some(locals),
+    };
+    let mut pos = 0;
+    let num_bodies = read_leb128(section, &mut pos)? as usize;
+    // imported functions have no body and come first
+    let first_defined = locals.len().checked_sub(num_bodies)?;
+    for (_, num_locals) in locals[first_defined..].iter_mut() {
+        let body_len = read_leb128(section, &mut pos)? as usize;
+        let body_end = pos + body_len;
+        for _ in 0..read_leb128(section, &mut pos)? {
+            *num_locals += read_leb128(section, &mut pos)?;
+            // the value type
+            pos += 1;
+        }
+        pos = body_end;
+    }
+    Some(locals)
+}
+
+/// Returns the index of the function exported as `main`, which runs first
+/// when a WASM contract is called.
+pub fn wasm_entry_function(code: &[u8]) -> Option<u64> {
+    const EXPORT_SECTION_ID: u8 = 7;
+    let section = wasm_section(code, EXPORT_SECTION_ID)?;
+    let mut pos = 0;
+    for _ in 0..read_leb128(section, &mut pos)? {
+        let name_len = read_leb128(section, &mut pos)? as usize;
+        let name = section.get(pos..pos + name_len)?;
+        pos += name_len;
+        let kind = *section.get(pos)?;
+        pos += 1;
+        let index = read_leb128(section, &mut pos)?;
+        if kind == 0x00 && name == b"main" {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// Structured control frame of a WASM binary, opened by a `block`, `loop` or
+/// `if` and closed by its `end`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WasmBlock {
+    /// Position of the blocktype inside the binary
+    pub blocktype_pos: usize,
+    /// Program counter of the instruction opening the frame
+    pub pc: u64,
+    /// Program counter of the `end` closing the frame
+    pub end_pc: u64,
+    /// Program counter of the `else` of an `if`, 0 without one
+    pub else_pc: u64,
+    /// Number of params of the blocktype
+    pub num_params: u64,
+    /// Number of results of the blocktype
+    pub num_results: u64,
+}
+
+#[derive(Default)]
+struct WasmCode {
+    instructions: Vec<(usize, u64, u64)>,
+    blocks: Vec<WasmBlock>,
+    branches: Vec<(usize, u64, u64)>,
+    bodies: Vec<u64>,
+}
+
+/// Returns the position inside a WASM binary, the opcode and the bound immediate
+/// of every instruction of its code section, the index of an instruction in
+/// this order is its program counter. A prefixed opcode is packed with its
+/// sub-opcode like `OpcodeId::as_u64`. The bound immediate is the offset of a
+/// memarg, the memory index of `memory.size`/`memory.grow` and the first
+/// immediate of a variable, a control or a prefixed instruction. It is the
+/// number of params plus the number of results times 2^16 for a `block`, `loop`
+/// or `if`, the program counter of the opener plus one for an `else` and for
+/// an `end` closing a frame, and 0 otherwise.
+pub fn wasm_instructions(code: &[u8]) -> Option<Vec<(usize, u64, u64)>> {
+    Some(decode_code_section(code)?.instructions)
+}
+
+/// Returns the structured control frames of the code section of a WASM binary
+/// in the order of their openers.
+pub fn wasm_blocks(code: &[u8]) -> Option<Vec<WasmBlock>> {
+    Some(decode_code_section(code)?.blocks)
+}
+
+/// Returns the position of the label of every `br` and `br_if` of the code
+/// section of a WASM binary, its program counter and the program counter plus
+/// one of the `block`, `loop` or `if` it targets, 0 for the function body.
+pub fn wasm_branches(code: &[u8]) -> Option<Vec<(usize, u64, u64)>> {
+    Some(decode_code_section(code)?.branches)
+}
+
+/// Returns the program counter of the first instruction of every function body
+/// of the code section of a WASM binary, in the order of the defined functions.
+pub fn wasm_function_bodies(code: &[u8]) -> Option<Vec<u64>> {
+    Some(decode_code_section(code)?.bodies)
+}
+
+fn decode_code_section(code: &[u8]) -> Option<WasmCode> {
+    const CODE_SECTION_ID: u8 = 10;
+    let (mut pos, _) = match wasm_section_range(code, CODE_SECTION_ID) {
+        Some(range) => range,
+        None => return Some(WasmCode::default()),
+    };
+    let arities = wasm_type_arities(code)?;
+    let mut instructions = vec![];
+    let mut blocks: Vec<WasmBlock> = vec![];
+    let mut branches = vec![];
+    let mut bodies = vec![];
+    for _ in 0..read_leb128(code, &mut pos)? {
+        // blocks open in the function body, the innermost last
+        let mut frames: Vec<usize> = vec![];
+        let body_len = read_leb128(code, &mut pos)? as usize;
+        let body_end = pos + body_len;
+        for _ in 0..read_leb128(code, &mut pos)? {
+            read_leb128(code, &mut pos)?;
+            // the value type
+            pos += 1;
+        }
+        bodies.push(instructions.len() as u64);
+        while pos < body_end {
+            let start = pos;
+            let opcode = *code.get(pos)?;
+            pos += 1;
+            let (opcode, arg) = match opcode {
+                // blocktype, a value type or a non-negative s33 type index
+                0x02..=0x04 => {
+                    let blocktype_pos = pos;
+                    let blocktype = read_leb128(code, &mut pos)?;
+                    let (num_params, num_results) = match blocktype {
+                        _ if pos - blocktype_pos > 1 || blocktype < 0x40 => {
+                            *arities.get(blocktype as usize)?
+                        }
+                        0x40 => (0, 0),
+                        0x6f | 0x70 | 0x7b..=0x7f => (0, 1),
+                        _ => return None,
+                    };
+                    frames.push(blocks.len());
+                    blocks.push(WasmBlock {
+                        blocktype_pos,
+                        pc: instructions.len() as u64,
+                        end_pc: 0,
+                        else_pc: 0,
+                        num_params,
+                        num_results,
+                    });
+                    (opcode as u64, num_params + (num_results << 16))
+                }
+                0x05 => {
+                    let frame = blocks.get_mut(*frames.last()?)?;
+                    frame.else_pc = instructions.len() as u64;
+                    (opcode as u64, frame.pc + 1)
+                }
+                // an `end` with no open frame ends the function body
+                0x0b => match frames.pop() {
+                    Some(index) => {
+                        blocks[index].end_pc = instructions.len() as u64;
+                        (opcode as u64, blocks[index].pc + 1)
+                    }
+                    None => (opcode as u64, 0),
+                },
+                // br and br_if, the label of the function body is past the open frames
+                0x0c | 0x0d => {
+                    let label_pos = pos;
+                    let label = read_leb128(code, &mut pos)? as usize;
+                    let target = match label.cmp(&frames.len()) {
+                        Ordering::Less => blocks[frames[frames.len() - 1 - label]].pc + 1,
+                        Ordering::Equal => 0,
+                        Ordering::Greater => return None,
+                    };
+                    branches.push((label_pos, instructions.len() as u64, target));
+                    (opcode as u64, label as u64)
+                }
+                // call, variables and table.get/table.set
+                0x10 | 0x20..=0x26 => (opcode as u64, read_leb128(code, &mut pos)?),
+                // br_table, the labels follow their count and end with the default one
+                0x0e => {
+                    let count = read_leb128(code, &mut pos)?;
+                    for _ in 0..=count {
+                        read_leb128(code, &mut pos)?;
+                    }
+                    (opcode as u64, count)
+                }
+                // call_indirect, the type index then the table index
+                0x11 => {
+                    let type_index = read_leb128(code, &mut pos)?;
+                    read_leb128(code, &mut pos)?;
+                    (opcode as u64, type_index)
+                }
+                // typed select, a vector of value types
+                0x1c => {
+                    pos += read_leb128(code, &mut pos)? as usize;
+                    (opcode as u64, 0)
+                }
+                // memarg, the alignment then the offset
+                0x28..=0x3e => {
+                    read_leb128(code, &mut pos)?;
+                    (opcode as u64, read_leb128(code, &mut pos)?)
+                }
+                // memory.size, memory.grow and ref.func
+                0x3f | 0x40 | 0xd2 => (opcode as u64, read_leb128(code, &mut pos)?),
+                // signed constants have the same length as unsigned ones
+                0x41 | 0x42 => {
+                    read_leb128(code, &mut pos)?;
+                    (opcode as u64, 0)
+                }
+                0x43 => {
+                    pos += 4;
+                    (opcode as u64, 0)
+                }
+                0x44 => {
+                    pos += 8;
+                    (opcode as u64, 0)
+                }
+                // ref.null and its reference type
+                0xd0 => {
+                    pos += 1;
+                    (opcode as u64, 0)
+                }
+                0xfc => {
+                    let sub_opcode = read_leb128(code, &mut pos)?;
+                    let args_count = match sub_opcode {
+                        8 | 10 | 12 | 14 => 2,
+                        9 | 11 | 13 | 15..=17 => 1,
+                        _ => 0,
+                    };
+                    let mut arg = 0;
+                    for i in 0..args_count {
+                        let value = read_leb128(code, &mut pos)?;
+                        if i == 0 {
+                            arg = value;
+                        }
+                    }
+                    ((opcode as u64) << 32 | sub_opcode, arg)
+                }
+                _ => (opcode as u64, 0),
+            };
+            instructions.push((start, opcode, arg));
+        }
+        pos = body_end;
+    }
+    Some(WasmCode {
+        instructions,
+        blocks,
+        branches,
+        bodies,
+    })
+}
+
+/// Returns the initial content of the first table declared in the table section
+/// of a WASM binary filled by its active element segments. A slot holds the
+/// function index it references or `None` for a null reference.
+pub fn wasm_table_elements(code: &[u8]) -> Option<Vec<Option<u64>>> {
+    const TABLE_SECTION_ID: u8 = 4;
+    const ELEMENT_SECTION_ID: u8 = 9;
+    let mut elements = match wasm_section(code, TABLE_SECTION_ID) {
+        Some(section) => {
+            let mut pos = 0;
+            if read_leb128(section, &mut pos)? == 0 {
+                return Some(vec![]);
+            }
+            // skip the reference type and the limits flags
+            pos += 2;
+            vec![None; read_leb128(section, &mut pos)? as usize]
+        }
+        None => return Some(vec![]),
+    };
+    let section = match wasm_section(code, ELEMENT_SECTION_ID) {
+        Some(section) => section,
+        None => return Some(elements),
+    };
+    let mut pos = 0;
+    for _ in 0..read_leb128(section, &mut pos)? {
+        let flags = read_leb128(section, &mut pos)?;
+        let is_active = flags & 1 == 0;
+        let table_index = if flags & 2 != 0 && is_active {
+            read_leb128(section, &mut pos)?
+        } else {
+            0
+        };
+        let offset = if is_active {
+            read_const_expr(section, &mut pos)??
+        } else {
+            0
+        };
+        // the element kind or the reference type, absent in the legacy encodings
+        if flags & 3 != 0 {
+            pos += 1;
+        }
+        let mut items = vec![];
+        for _ in 0..read_leb128(section, &mut pos)? {
+            items.push(if flags & 4 != 0 {
+                read_const_expr(section, &mut pos)?
+            } else {
+                Some(read_leb128(section, &mut pos)?)
+            });
+        }
+        // passive and declarative segments are not part of the initial table
+        if !is_active || table_index != 0 {
+            continue;
+        }
+        for (i, item) in items.into_iter().enumerate() {
+            if let Some(slot) = elements.get_mut(offset as usize + i) {
+                *slot = item;
+            }
+        }
+    }
+    Some(elements)
+}
+
+/// Returns the byte range of the content of the data segment with the given
+/// index inside a WASM binary, `memory.init` copies from there.
+pub fn wasm_data_segment(code: &[u8], data_index: u64) -> Option<(u64, u64)> {
+    const DATA_SECTION_ID: u8 = 11;
+    let (section_start, section_end) = wasm_section_range(code, DATA_SECTION_ID)?;
+    let section = &code[section_start..section_end];
+    let mut pos = 0;
+    let count = read_leb128(section, &mut pos)?;
+    if data_index >= count {
+        return None;
+    }
+    for i in 0..=data_index {
+        let flags = read_leb128(section, &mut pos)?;
+        match flags {
+            0 => {
+                read_const_expr(section, &mut pos)?;
+            }
+            1 => {}
+            2 => {
+                read_leb128(section, &mut pos)?;
+                read_const_expr(section, &mut pos)?;
+            }
+            _ => return None,
+        }
+        let len = read_leb128(section, &mut pos)? as usize;
+        if i == data_index {
+            let start = (section_start + pos) as u64;
+            return (pos + len <= section.len()).then_some((start, start + len as u64));
+        }
+        pos += len;
+    }
+    None
+}
+
+/// Returns the function a `call_indirect` of the given type reaches through the
+/// given table slot, or `None` if the call traps on a slot out of bounds, a null
+/// reference or a signature mismatch.
+pub fn wasm_indirect_call_target(code: &[u8], type_index: u64, elem_index: u64) -> Option<u64> {
+    let function_index = (*wasm_table_elements(code)?.get(elem_index as usize)?)?;
+    let function_type = *wasm_function_types(code)?.get(function_index as usize)?;
+    (function_type == type_index).then_some(function_index)
+}
+
 impl Default for Bytecode {
     fn default() -> Self {
         let mut res = Self {
@@ -236,6 +878,9 @@ impl Default for Bytecode {
             evm_table: Default::default(),
             num_opcodes: 0,
             markers: Default::default(),
+            max_memory_pages: None,
+            table_elements: vec![],
+            passive_data: vec![],
         };
         res.ensure_function_type(vec![], vec![]);
         res
@@ -266,6 +911,23 @@ impl Bytecode {
         current_offset as u32
     }
 
+    pub fn with_max_memory_pages(&mut self, pages: u32) -> &mut Self {
+        self.max_memory_pages = Some(pages);
+        self
+    }
+
+    /// Declares a table initialized with the given function indices, used by `call_indirect`.
+    pub fn with_function_table(&mut self, elements: Vec<u32>) -> &mut Self {
+        self.table_elements = elements;
+        self
+    }
+
+    /// Declares a passive data segment and returns its index for `memory.init`.
+    pub fn with_passive_data(&mut self, data: Vec<u8>) -> u32 {
+        self.passive_data.push(data);
+        self.passive_data.len() as u32 - 1
+    }
+
     pub fn with_main_locals(&mut self, locals: Vec<(u32, ValType)>) -> &mut Self {
         self.main_locals.extend(&locals);
         self
@@ -347,54 +1009,10 @@ impl Bytecode {
     }
 
     pub fn evm_call(&mut self, op: OpcodeId) -> &mut Self {
-        let (fn_name, args_num) = match op {
-            OpcodeId::STOP => ("_evm_stop", 0),
-            OpcodeId::RETURN => ("_evm_return", 2),
-            OpcodeId::SHA3 => ("_evm_keccak256", 3),
-            OpcodeId::ADDRESS => ("_evm_address", 1),
-            OpcodeId::BALANCE => ("_evm_balance", 2),
-            OpcodeId::ORIGIN => ("_evm_origin", 1),
-            OpcodeId::CALLER => ("_evm_caller", 1),
-            OpcodeId::CALLVALUE => ("_evm_callvalue", 1),
-            OpcodeId::CALLDATALOAD => ("_evm_calldataload", 2),
-            OpcodeId::CALLDATASIZE => ("_evm_calldatasize", 1),
-            OpcodeId::CALLDATACOPY => ("_evm_calldatacopy", 3),
-            OpcodeId::CODESIZE => ("_evm_codesize", 1),
-            OpcodeId::CODECOPY => ("_evm_codecopy", 3),
-            OpcodeId::GASPRICE => ("_evm_gasprice", 1),
-            OpcodeId::EXTCODESIZE => ("_evm_extcodesize", 2),
-            OpcodeId::EXTCODECOPY => ("_evm_extcodecopy", 4),
-            OpcodeId::EXTCODEHASH => ("_evm_extcodehash", 2),
-            OpcodeId::RETURNDATASIZE => ("_evm_returndatasize", 1),
-            OpcodeId::RETURNDATACOPY => ("_evm_returndatacopy", 3),
-            OpcodeId::BLOCKHASH => ("_evm_blockhash", 2),
-            OpcodeId::COINBASE => ("_evm_coinbase", 1),
-            OpcodeId::TIMESTAMP => ("_evm_timestamp", 1),
-            OpcodeId::NUMBER => ("_evm_number", 1),
-            OpcodeId::DIFFICULTY => ("_evm_difficulty", 1),
-            OpcodeId::GASLIMIT => ("_evm_gaslimit", 1),
-            OpcodeId::CHAINID => ("_evm_chainid", 1),
-            OpcodeId::BASEFEE => ("_evm_basefee", 1),
-            OpcodeId::SLOAD => ("_evm_sload", 2),
-            OpcodeId::SSTORE => ("_evm_sstore", 2),
-            OpcodeId::GAS => ("_evm_gas", 1),
-            OpcodeId::PC => ("_evm_pc", 1),
-            OpcodeId::MSIZE => ("_evm_msize", 1),
-            OpcodeId::LOG0 => ("_evm_log0", 2),
-            OpcodeId::LOG1 => ("_evm_log1", 3),
-            OpcodeId::LOG2 => ("_evm_log2", 4),
-            OpcodeId::LOG3 => ("_evm_log3", 5),
-            OpcodeId::LOG4 => ("_evm_log4", 6),
-            OpcodeId::CREATE => ("_evm_create", 3),
-            OpcodeId::CALL => ("_evm_call", 8),
-            OpcodeId::CALLCODE => ("_evm_callcode", 8),
-            OpcodeId::DELEGATECALL => ("_evm_delegatecall", 7),
-            OpcodeId::CREATE2 => ("_evm_create2", 5),
-            OpcodeId::STATICCALL => ("_evm_staticcall", 7),
-            OpcodeId::REVERT => ("_evm_revert", 2),
-            OpcodeId::SELFBALANCE => ("_evm_selfbalance", 1),
-            _ => unreachable!("not supported EVM opcode: {op}")
-        };
+        let &(_, fn_name, args_num) = WASM_HOST_FUNCTIONS
+            .iter()
+            .find(|(opcode, ..)| *opcode == op)
+            .unwrap_or_else(|| unreachable!("not supported EVM opcode: {op}"));
 
         let type_index = self.ensure_function_type(vec![ValType::I32; args_num], vec![]);
 
@@ -417,6 +1035,22 @@ impl Bytecode {
         if op.is_evm_call() {
             return self.evm_call(op);
         }
+        match op {
+            OpcodeId::MemoryCopy | OpcodeId::TableCopy => return self.write_prefixed(op, &[0, 0]),
+            OpcodeId::MemoryFill
+            | OpcodeId::TableGrow
+            | OpcodeId::TableSize
+            | OpcodeId::TableFill => return self.write_prefixed(op, &[0]),
+            OpcodeId::I32TruncSatSF32
+            | OpcodeId::I32TruncSatUF32
+            | OpcodeId::I32TruncSatSF64
+            | OpcodeId::I32TruncSatUF64
+            | OpcodeId::I64TruncSatSF32
+            | OpcodeId::I64TruncSatUF32
+            | OpcodeId::I64TruncSatSF64
+            | OpcodeId::I64TruncSatUF64 => return self.write_prefixed(op, &[]),
+            _ => {}
+        }
         let op = match op {
             // WASM opcode mapping
             OpcodeId::Nop => Instruction::Nop,
@@ -471,8 +1105,8 @@ impl Bytecode {
             OpcodeId::I64Store32 => Instruction::I64Store32,
 */
 
-            //OpcodeId::GrowMemory => Instruction::MemoryGrow,
-            //OpcodeId::CurrentMemory => Instruction::MemorySize,
+            OpcodeId::GrowMemory => Instruction::MemoryGrow(0),
+            OpcodeId::CurrentMemory => Instruction::MemorySize(0),
 
             OpcodeId::I32Add => Instruction::I32Add,
             OpcodeId::I64Add => Instruction::I64Add,
@@ -499,12 +1133,19 @@ impl Bytecode {
             OpcodeId::I32WrapI64 => Instruction::I32WrapI64,
             OpcodeId::I64ExtendSI32 => Instruction::I64ExtendI32S,
             OpcodeId::I64ExtendUI32 => Instruction::I64ExtendI32U,
+            OpcodeId::I32Extend8S => Instruction::I32Extend8S,
+            OpcodeId::I32Extend16S => Instruction::I32Extend16S,
+            OpcodeId::I64Extend8S => Instruction::I64Extend8S,
+            OpcodeId::I64Extend16S => Instruction::I64Extend16S,
+            OpcodeId::I64Extend32S => Instruction::I64Extend32S,
             OpcodeId::End => Instruction::End,
             OpcodeId::Unreachable => Instruction::Unreachable,
             OpcodeId::Drop => Instruction::Drop,
             OpcodeId::Return => Instruction::Return,
             OpcodeId::Block => Instruction::Block(Empty),
             OpcodeId::Loop => Instruction::Loop(Empty),
+            OpcodeId::If => Instruction::If(Empty),
+            OpcodeId::Else => Instruction::Else,
             OpcodeId::Select => Instruction::Select,
             _ => {
                 unreachable!("not supported opcode: {:?} ({})", op, op.as_u8())
@@ -535,6 +1176,37 @@ impl Bytecode {
         self
     }
 
+    /// Writes a `block`, `loop` or `if` taking `params` and leaving `results`,
+    /// a blocktype with params or several results refers to a function type.
+    pub fn write_block(
+        &mut self,
+        op: OpcodeId,
+        params: Vec<ValType>,
+        results: Vec<ValType>,
+    ) -> &mut Self {
+        let blocktype = match (params.as_slice(), results.as_slice()) {
+            ([], []) => Empty,
+            ([], [result]) => BlockType::Result(*result),
+            _ => BlockType::FunctionType(self.ensure_function_type(params, results)),
+        };
+        let op = match op {
+            OpcodeId::Block => Instruction::Block(blocktype),
+            OpcodeId::Loop => Instruction::Loop(blocktype),
+            OpcodeId::If => Instruction::If(blocktype),
+            _ => unreachable!("not a block opcode: {:?} ({})", op, op.as_u8()),
+        };
+        let mut buf: Vec<u8> = vec![];
+        op.encode(&mut buf);
+        for (i, b) in buf.iter().enumerate() {
+            if i == 0 {
+                self.write_op_internal(*b);
+            } else {
+                self.write(*b, false);
+            }
+        }
+        self
+    }
+
     pub fn write_memarg(&mut self, op: OpcodeId, offset: u64, align: u32, memory_index: u32) -> &mut Self {
         let mem_arg = MemArg { offset, align, memory_index };
         let op = match op {
@@ -587,8 +1259,13 @@ impl Bytecode {
             OpcodeId::SetLocal => Instruction::LocalSet(val as u32),
             OpcodeId::TeeLocal => Instruction::LocalTee(val as u32),
             OpcodeId::Call => Instruction::Call(val as u32),
+            OpcodeId::CallIndirect => Instruction::CallIndirect { ty: val as u32, table: 0 },
             OpcodeId::Br => Instruction::Br(val as u32),
             OpcodeId::BrIf => Instruction::BrIf(val as u32),
+            OpcodeId::MemoryInit | OpcodeId::TableInit => {
+                return self.write_prefixed(op, &[val as u32, 0])
+            }
+            OpcodeId::DataDrop | OpcodeId::ElemDrop => return self.write_prefixed(op, &[val as u32]),
             _ => {
                 unreachable!("not supported opcode: {:?} ({})", op, op.as_u8())
             }
@@ -605,6 +1282,25 @@ impl Bytecode {
         self
     }
 
+    /// Writes a prefixed instruction: the prefix byte, the LEB128 sub-opcode and the
+    /// LEB128 immediates (memory, table, data or element indices).
+    pub fn write_prefixed(&mut self, op: OpcodeId, immediates: &[u32]) -> &mut Self {
+        let (prefix, sub_opcode) = match (op.prefix(), op.sub_opcode()) {
+            (Some(prefix), Some(sub_opcode)) => (prefix, sub_opcode),
+            _ => unreachable!("not a prefixed opcode: {:?} ({})", op, op.as_u8()),
+        };
+        let mut buf: Vec<u8> = vec![];
+        sub_opcode.encode(&mut buf);
+        for immediate in immediates {
+            immediate.encode(&mut buf);
+        }
+        self.write_op_internal(prefix);
+        for b in buf {
+            self.write(b, false);
+        }
+        self
+    }
+
     fn write_op_internal(&mut self, op: u8) -> &mut Self {
         self.num_opcodes += 1;
         self.write(op, true)
@@ -687,7 +1383,7 @@ impl Bytecode {
     ) -> &mut Self {
         if opcode == OpcodeId::CALL || opcode == OpcodeId::CALLCODE {
             let address_offset = self.fill_default_global_data(address.to_fixed_bytes().to_vec());
-            let value_offset = self.fill_default_global_data(value.to_le_bytes().to_vec());
+            let value_offset = self.fill_default_global_data(value.to_be_bytes().to_vec());
             let status_offset = self.alloc_default_global_data(1);
             crate::bytecode_internal!(self,
                 I32Const[gas]
@@ -715,6 +1411,37 @@ impl Bytecode {
         self.write_op(opcode)
     }
 
+    /// Create a contract from the init code in linear memory, with CREATE2
+    /// when a salt is given
+    pub fn emit_evm_create(
+        &mut self,
+        value: U256,
+        code_offset: u64,
+        code_length: u64,
+        salt: Option<U256>,
+    ) -> &mut Self {
+        let value_offset = self.fill_default_global_data(value.to_be_bytes().to_vec());
+        crate::bytecode_internal!(self,
+            I32Const[value_offset as u64]
+            I32Const[code_offset]
+            I32Const[code_length]
+        );
+        let opcode = if let Some(salt) = salt {
+            let salt_offset = self.fill_default_global_data(salt.to_be_bytes().to_vec());
+            crate::bytecode_internal!(self,
+                I32Const[salt_offset as u64]
+            );
+            OpcodeId::CREATE2
+        } else {
+            OpcodeId::CREATE
+        };
+        let result_offset = self.alloc_default_global_data(20);
+        crate::bytecode_internal!(self,
+            I32Const[result_offset as u64]
+        );
+        self.write_op(opcode)
+    }
+
     /// Generate the diassembly
     pub fn disasm(&self) -> String {
         let mut asm = String::new();
@@ -827,6 +1554,14 @@ impl<'a> Iterator for BytecodeIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.0.next().map(|byte| {
+            if byte.is_code && byte.value == WASM_PREFIX_FC {
+                let sub_opcode = self.read_leb128();
+                // skip the immediates, they are never marked as code
+                while self.0.as_slice().first().map_or(false, |b| !b.is_code) {
+                    self.0.next();
+                }
+                return OpcodeWithData::Opcode(OpcodeId::from_prefixed(byte.value, sub_opcode));
+            }
             let op = OpcodeId::from(byte.value);
             let n = op.data_len();
             if n > 0 {
@@ -845,6 +1580,21 @@ impl<'a> Iterator for BytecodeIterator<'a> {
     }
 }
 
+impl<'a> BytecodeIterator<'a> {
+    fn read_leb128(&mut self) -> u32 {
+        let mut result = 0u32;
+        let mut shift = 0;
+        for byte in self.0.by_ref() {
+            result |= ((byte.value & 0x7f) as u32) << shift;
+            shift += 7;
+            if byte.value & 0x80 == 0 || shift >= 32 {
+                break;
+            }
+        }
+        result
+    }
+}
+
 impl From<Vec<u8>> for Bytecode {
     fn from(input: Vec<u8>) -> Self {
         Bytecode::from_raw_unchecked(input)
@@ -1127,6 +1877,132 @@ mod tests {
         println!("{}", hex::encode(wasm_binary));
     }
 
+    #[test]
+    fn test_wasm_memory_limits() {
+        let mut bytecode = bytecode! {
+            I32Const[1]
+            GrowMemory
+            Drop
+        };
+        assert_eq!(wasm_memory_limits(&bytecode.wasm_binary()), Some((1, WASM_MAX_PAGES)));
+        bytecode.with_max_memory_pages(3);
+        assert_eq!(wasm_memory_limits(&bytecode.wasm_binary()), Some((1, 3)));
+    }
+
+    #[test]
+    fn test_wasm_function_locals() {
+        let mut bytecode = bytecode! {
+            I32Const[100]
+            I32Const[20]
+            Call[0]
+            Drop
+        };
+        bytecode.new_function(vec![ValType::I32; 2], vec![ValType::I32], bytecode! {
+            GetLocal[0]
+            GetLocal[1]
+            I32Add
+        }, vec![(1, ValType::I32), (2, ValType::I64)]);
+        bytecode.with_main_locals(vec![(3, ValType::I32)]);
+        let wasm_binary = bytecode.wasm_binary();
+        assert_eq!(wasm_function_locals(&wasm_binary), Some(vec![(2, 5), (0, 3)]));
+        assert_eq!(wasm_entry_function(&wasm_binary), Some(1));
+    }
+
+    #[test]
+    fn test_wasm_function_table() {
+        let mut bytecode = bytecode! {
+            I32Const[1]
+            CallIndirect[0]
+        };
+        bytecode.new_function(vec![], vec![], bytecode! {}, vec![]);
+        bytecode.new_function(vec![ValType::I32], vec![], bytecode! {}, vec![]);
+        bytecode.with_function_table(vec![1, 0]);
+        let wasm_binary = bytecode.wasm_binary();
+        assert_eq!(wasm_function_types(&wasm_binary), Some(vec![0, 1, 0]));
+        assert_eq!(wasm_table_elements(&wasm_binary), Some(vec![Some(1), Some(0)]));
+        assert_eq!(wasm_indirect_call_target(&wasm_binary, 0, 1), Some(0));
+        assert_eq!(wasm_indirect_call_target(&wasm_binary, 0, 0), None);
+        assert_eq!(wasm_indirect_call_target(&wasm_binary, 1, 2), None);
+    }
+
+    #[test]
+    fn test_wasm_data_segment() {
+        let mut bytecode = bytecode! {
+            I32Const[0]
+            I32Const[0]
+            I32Const[3]
+            MemoryInit[0]
+        };
+        bytecode.fill_default_global_data(vec![0xff; 4]);
+        let data_index = bytecode.with_passive_data(vec![1, 2, 3]);
+        let wasm_binary = bytecode.wasm_binary();
+        let (start, end) = wasm_data_segment(&wasm_binary, data_index as u64).unwrap();
+        assert_eq!(&wasm_binary[start as usize..end as usize], &[1, 2, 3]);
+        assert_eq!(wasm_data_segment(&wasm_binary, 2), None);
+    }
+
+    #[test]
+    fn test_wasm_blocks() {
+        let bytecode = bytecode! {
+            .write_block(OpcodeId::Block, vec![], vec![ValType::I32, ValType::I32])
+                I32Const[1]
+                If
+                    I32Const[2]
+                    I32Const[3]
+                    Br[1]
+                Else
+                End
+                I32Const[4]
+                I32Const[5]
+            End
+            Drop
+            Drop
+            Br[0]
+        };
+        let wasm_binary = bytecode.wasm_binary();
+        let blocks = wasm_blocks(&wasm_binary)
+            .unwrap()
+            .into_iter()
+            .map(|block| (block.pc, block.end_pc, block.else_pc, block.num_params, block.num_results))
+            .collect::<Vec<_>>();
+        assert_eq!(blocks, vec![(0, 10, 0, 0, 2), (2, 7, 6, 0, 0)]);
+        let args = wasm_instructions(&wasm_binary)
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, arg)| arg)
+            .collect::<Vec<_>>();
+        assert_eq!(args, vec![2 << 16, 0, 0, 0, 0, 1, 3, 3, 0, 0, 1, 0, 0, 0, 0]);
+        let branches = wasm_branches(&wasm_binary)
+            .unwrap()
+            .into_iter()
+            .map(|(_, pc, target)| (pc, target))
+            .collect::<Vec<_>>();
+        assert_eq!(branches, vec![(5, 1), (13, 0)]);
+    }
+
+    #[test]
+    fn test_prefixed_opcodes() {
+        let bytecode = bytecode! {
+            MemoryCopy
+            MemoryFill
+            DataDrop[1]
+            I32TruncSatSF32
+        };
+        assert_eq!(
+            bytecode.code(),
+            vec![0xfc, 0x0a, 0x00, 0x00, 0xfc, 0x0b, 0x00, 0xfc, 0x09, 0x01, 0xfc, 0x00],
+        );
+        assert_eq!(
+            bytecode.iter().map(|op| op.opcode()).collect::<Vec<_>>(),
+            vec![
+                OpcodeId::MemoryCopy,
+                OpcodeId::MemoryFill,
+                OpcodeId::DataDrop,
+                OpcodeId::I32TruncSatSF32,
+            ],
+        );
+    }
+
     #[cfg(feature = "shanghai")]
     #[test]
     fn test_asm_disasm_for_shanghai() {
@@ -4,12 +4,14 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 pub mod block_utils;
+pub mod fuel;
 pub mod gas_utils;
 pub mod memory;
 pub mod opcode_ids;
 pub mod stack;
 pub mod storage;
 
+pub use fuel::WasmFuelSchedule;
 pub use memory::{Memory, MemoryAddress};
 pub use opcode_ids::OpcodeId;
 pub use stack::{Stack, StackAddress};
@@ -63,6 +65,11 @@ impl fmt::Debug for Gas {
 
 /// Maximum bytecode size to permit for a contract.
 pub const MAX_CODE_SIZE: u64 = 24576;
+/// Maximum number of WASM function frames a call can nest.
+pub const MAX_WASM_CALL_DEPTH: u64 = 1024;
+/// Maximum number of values a WASM branch or return carries, the params of a
+/// loop, the results of a block or the results of a function.
+pub const MAX_WASM_ARITY: u64 = 4;
 /// This constant ((2^32 - 1) * 32) is the highest number that can be used without overflowing the
 /// square operation of gas calculation.
 /// <https://github.com/ethereum/go-ethereum/blob/e6b6a8b738069ad0579f6798ee59fde93ed13b43/core/vm/gas_table.go#L38>
@@ -0,0 +1,296 @@
+//! Fuel schedule charged for WASM instructions
+
+use super::OpcodeId;
+
+/// Per-instruction fuel charged for WASM code, grouped by instruction class,
+/// plus the dynamic charges of `memory.grow` and of entering a call frame.
+/// Gas left of a WASM step decreases by exactly this amount, so the circuit and
+/// the bus-mapping both derive it from the schedule of the block's
+/// `CircuitsParams` instead of the trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WasmFuelSchedule {
+    /// `unreachable`, `nop`, `drop`, `select`, constants, locals, globals and
+    /// `memory.size`
+    pub base: u64,
+    /// Structured control flow and branches
+    pub control: u64,
+    /// Integer and float arithmetic, bitwise, comparison and conversions
+    pub arithmetic: u64,
+    /// Integer divisions and remainders
+    pub division: u64,
+    /// Loads and stores
+    pub memory_access: u64,
+    /// Bulk memory and table instructions
+    pub bulk_memory: u64,
+    /// `call` and `call_indirect`
+    pub call: u64,
+    /// Charged on top of `call` for every call frame pushed
+    pub call_frame: u64,
+    /// `memory.grow` itself, no matter how many pages it requests
+    pub memory_grow: u64,
+    /// Charged per page actually grown by `memory.grow`
+    pub memory_grow_per_page: u64,
+}
+
+impl Default for WasmFuelSchedule {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl WasmFuelSchedule {
+    /// Schedule of the gas injector linked by `geth-utils`, which charges one
+    /// unit per instruction and nothing for the frames and pages it allocates,
+    /// as the `gasCost` of the steps it traces shows.
+    pub const DEFAULT: Self = Self {
+        base: 1,
+        control: 1,
+        arithmetic: 1,
+        division: 1,
+        memory_access: 1,
+        bulk_memory: 1,
+        call: 1,
+        call_frame: 0,
+        memory_grow: 1,
+        memory_grow_per_page: 0,
+    };
+
+    /// Returns the static fuel charged for a WASM instruction, or `None` when
+    /// the opcode is not a WASM instruction.
+    pub const fn instruction_fuel(&self, opcode: OpcodeId) -> Option<u64> {
+        let fuel = match opcode {
+            OpcodeId::Unreachable
+            | OpcodeId::Nop
+            | OpcodeId::Drop
+            | OpcodeId::Select
+            | OpcodeId::GetLocal
+            | OpcodeId::SetLocal
+            | OpcodeId::TeeLocal
+            | OpcodeId::GetGlobal
+            | OpcodeId::SetGlobal
+            | OpcodeId::CurrentMemory
+            | OpcodeId::I32Const
+            | OpcodeId::I64Const
+            | OpcodeId::F32Const
+            | OpcodeId::F64Const => self.base,
+            OpcodeId::Block
+            | OpcodeId::Loop
+            | OpcodeId::If
+            | OpcodeId::Else
+            | OpcodeId::End
+            | OpcodeId::Br
+            | OpcodeId::BrIf
+            | OpcodeId::BrTable
+            | OpcodeId::Return => self.control,
+            OpcodeId::Call | OpcodeId::CallIndirect => self.call + self.call_frame,
+            OpcodeId::I32Load
+            | OpcodeId::I64Load
+            | OpcodeId::F32Load
+            | OpcodeId::F64Load
+            | OpcodeId::I32Load8S
+            | OpcodeId::I32Load8U
+            | OpcodeId::I32Load16S
+            | OpcodeId::I32Load16U
+            | OpcodeId::I64Load8S
+            | OpcodeId::I64Load8U
+            | OpcodeId::I64Load16S
+            | OpcodeId::I64Load16U
+            | OpcodeId::I64Load32S
+            | OpcodeId::I64Load32U
+            | OpcodeId::I32Store
+            | OpcodeId::I64Store
+            | OpcodeId::F32Store
+            | OpcodeId::F64Store
+            | OpcodeId::I32Store8
+            | OpcodeId::I32Store16
+            | OpcodeId::I64Store8
+            | OpcodeId::I64Store16
+            | OpcodeId::I64Store32 => self.memory_access,
+            OpcodeId::GrowMemory => self.memory_grow,
+            OpcodeId::I32DivS
+            | OpcodeId::I32DivU
+            | OpcodeId::I32RemS
+            | OpcodeId::I32RemU
+            | OpcodeId::I64DivS
+            | OpcodeId::I64DivU
+            | OpcodeId::I64RemS
+            | OpcodeId::I64RemU => self.division,
+            OpcodeId::MemoryInit
+            | OpcodeId::DataDrop
+            | OpcodeId::MemoryCopy
+            | OpcodeId::MemoryFill
+            | OpcodeId::TableInit
+            | OpcodeId::ElemDrop
+            | OpcodeId::TableCopy
+            | OpcodeId::TableGrow
+            | OpcodeId::TableSize
+            | OpcodeId::TableFill => self.bulk_memory,
+            OpcodeId::I32Eqz
+            | OpcodeId::I32Eq
+            | OpcodeId::I32Ne
+            | OpcodeId::I32LtS
+            | OpcodeId::I32LtU
+            | OpcodeId::I32GtS
+            | OpcodeId::I32GtU
+            | OpcodeId::I32LeS
+            | OpcodeId::I32LeU
+            | OpcodeId::I32GeS
+            | OpcodeId::I32GeU
+            | OpcodeId::I64Eqz
+            | OpcodeId::I64Eq
+            | OpcodeId::I64Ne
+            | OpcodeId::I64LtS
+            | OpcodeId::I64LtU
+            | OpcodeId::I64GtS
+            | OpcodeId::I64GtU
+            | OpcodeId::I64LeS
+            | OpcodeId::I64LeU
+            | OpcodeId::I64GeS
+            | OpcodeId::I64GeU
+            | OpcodeId::F32Eq
+            | OpcodeId::F32Ne
+            | OpcodeId::F32Lt
+            | OpcodeId::F32Gt
+            | OpcodeId::F32Le
+            | OpcodeId::F32Ge
+            | OpcodeId::F64Eq
+            | OpcodeId::F64Ne
+            | OpcodeId::F64Lt
+            | OpcodeId::F64Gt
+            | OpcodeId::F64Le
+            | OpcodeId::F64Ge
+            | OpcodeId::I32Clz
+            | OpcodeId::I32Ctz
+            | OpcodeId::I32Popcnt
+            | OpcodeId::I32Add
+            | OpcodeId::I32Sub
+            | OpcodeId::I32Mul
+            | OpcodeId::I32And
+            | OpcodeId::I32Or
+            | OpcodeId::I32Xor
+            | OpcodeId::I32Shl
+            | OpcodeId::I32ShrS
+            | OpcodeId::I32ShrU
+            | OpcodeId::I32Rotl
+            | OpcodeId::I32Rotr
+            | OpcodeId::I64Clz
+            | OpcodeId::I64Ctz
+            | OpcodeId::I64Popcnt
+            | OpcodeId::I64Add
+            | OpcodeId::I64Sub
+            | OpcodeId::I64Mul
+            | OpcodeId::I64And
+            | OpcodeId::I64Or
+            | OpcodeId::I64Xor
+            | OpcodeId::I64Shl
+            | OpcodeId::I64ShrS
+            | OpcodeId::I64ShrU
+            | OpcodeId::I64Rotl
+            | OpcodeId::I64Rotr
+            | OpcodeId::F32Abs
+            | OpcodeId::F32Neg
+            | OpcodeId::F32Ceil
+            | OpcodeId::F32Floor
+            | OpcodeId::F32Trunc
+            | OpcodeId::F32Nearest
+            | OpcodeId::F32Sqrt
+            | OpcodeId::F32Add
+            | OpcodeId::F32Sub
+            | OpcodeId::F32Mul
+            | OpcodeId::F32Div
+            | OpcodeId::F32Min
+            | OpcodeId::F32Max
+            | OpcodeId::F32Copysign
+            | OpcodeId::F64Abs
+            | OpcodeId::F64Neg
+            | OpcodeId::F64Ceil
+            | OpcodeId::F64Floor
+            | OpcodeId::F64Trunc
+            | OpcodeId::F64Nearest
+            | OpcodeId::F64Sqrt
+            | OpcodeId::F64Add
+            | OpcodeId::F64Sub
+            | OpcodeId::F64Mul
+            | OpcodeId::F64Div
+            | OpcodeId::F64Min
+            | OpcodeId::F64Max
+            | OpcodeId::F64Copysign
+            | OpcodeId::I32WrapI64
+            | OpcodeId::I32TruncSF32
+            | OpcodeId::I32TruncUF32
+            | OpcodeId::I32TruncSF64
+            | OpcodeId::I32TruncUF64
+            | OpcodeId::I64ExtendSI32
+            | OpcodeId::I64ExtendUI32
+            | OpcodeId::I64TruncSF32
+            | OpcodeId::I64TruncUF32
+            | OpcodeId::I64TruncSF64
+            | OpcodeId::I64TruncUF64
+            | OpcodeId::F32ConvertSI32
+            | OpcodeId::F32ConvertUI32
+            | OpcodeId::F32ConvertSI64
+            | OpcodeId::F32ConvertUI64
+            | OpcodeId::F32DemoteF64
+            | OpcodeId::F64ConvertSI32
+            | OpcodeId::F64ConvertUI32
+            | OpcodeId::F64ConvertSI64
+            | OpcodeId::F64ConvertUI64
+            | OpcodeId::F64PromoteF32
+            | OpcodeId::I32ReinterpretF32
+            | OpcodeId::I64ReinterpretF64
+            | OpcodeId::F32ReinterpretI32
+            | OpcodeId::F64ReinterpretI64
+            | OpcodeId::I32Extend8S
+            | OpcodeId::I32Extend16S
+            | OpcodeId::I64Extend8S
+            | OpcodeId::I64Extend16S
+            | OpcodeId::I64Extend32S
+            | OpcodeId::I32TruncSatSF32
+            | OpcodeId::I32TruncSatUF32
+            | OpcodeId::I32TruncSatSF64
+            | OpcodeId::I32TruncSatUF64
+            | OpcodeId::I64TruncSatSF32
+            | OpcodeId::I64TruncSatUF32
+            | OpcodeId::I64TruncSatSF64
+            | OpcodeId::I64TruncSatUF64 => self.arithmetic,
+            _ => return None,
+        };
+        Some(fuel)
+    }
+
+    /// Returns the dynamic fuel of `memory.grow` growing the memory by `pages`.
+    /// A failed `memory.grow` grows nothing and pays only its static fuel.
+    pub const fn memory_grow_fuel(&self, pages: u64) -> u64 {
+        pages * self.memory_grow_per_page
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn every_wasm_instruction_has_fuel() {
+        let schedule = WasmFuelSchedule::DEFAULT;
+        for opcode in OpcodeId::iter() {
+            let is_wasm = (OpcodeId::Unreachable..=OpcodeId::TableFill).contains(&opcode);
+            assert_eq!(
+                schedule.instruction_fuel(opcode).is_some(),
+                is_wasm,
+                "{:?}",
+                opcode
+            );
+        }
+    }
+
+    #[test]
+    fn call_pays_for_the_new_frame() {
+        let schedule = WasmFuelSchedule::DEFAULT;
+        assert_eq!(
+            schedule.instruction_fuel(OpcodeId::Call),
+            Some(schedule.call + schedule.call_frame)
+        );
+        assert_eq!(schedule.memory_grow_fuel(0), 0);
+    }
+}
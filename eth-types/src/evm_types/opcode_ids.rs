@@ -8,7 +8,11 @@ use std::{fmt, matches, str::FromStr};
 use itertools::Itertools;
 use strum_macros::EnumIter;
 
-/// Opcode enum. One-to-one corresponding to an `u8` value.
+/// Prefix byte of the WASM instructions encoded as the prefix followed by a LEB128 sub-opcode.
+pub const WASM_PREFIX_FC: u8 = 0xfc;
+
+/// Opcode enum. One-to-one corresponding to an `u8` value, except the prefixed WASM instructions
+/// that are identified by a prefix byte and a sub-opcode.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Hash, EnumIter, PartialOrd, Ord)]
 pub enum OpcodeId {
     CALLDATALOAD,
@@ -236,6 +240,30 @@ pub enum OpcodeId {
     I64ReinterpretF64,
     F32ReinterpretI32,
     F64ReinterpretI64,
+    I32Extend8S,
+    I32Extend16S,
+    I64Extend8S,
+    I64Extend16S,
+    I64Extend32S,
+    // WASM 0xFC-prefixed opcode family
+    I32TruncSatSF32,
+    I32TruncSatUF32,
+    I32TruncSatSF64,
+    I32TruncSatUF64,
+    I64TruncSatSF32,
+    I64TruncSatUF32,
+    I64TruncSatSF64,
+    I64TruncSatUF64,
+    MemoryInit,
+    DataDrop,
+    MemoryCopy,
+    MemoryFill,
+    TableInit,
+    ElemDrop,
+    TableCopy,
+    TableGrow,
+    TableSize,
+    TableFill,
 
     // TODO: "remove these opcodes in the future they don't work"
     STOP,
@@ -612,7 +640,31 @@ impl OpcodeId {
             OpcodeId::I64ReinterpretF64 => 0xbd,
             OpcodeId::F32ReinterpretI32 => 0xbe,
             OpcodeId::F64ReinterpretI64 => 0xbf,
-            // EVM opcode family
+            OpcodeId::I32Extend8S => 0xc0,
+            OpcodeId::I32Extend16S => 0xc1,
+            OpcodeId::I64Extend8S => 0xc2,
+            OpcodeId::I64Extend16S => 0xc3,
+            OpcodeId::I64Extend32S => 0xc4,
+            OpcodeId::I32TruncSatSF32
+                | OpcodeId::I32TruncSatUF32
+                | OpcodeId::I32TruncSatSF64
+                | OpcodeId::I32TruncSatUF64
+                | OpcodeId::I64TruncSatSF32
+                | OpcodeId::I64TruncSatUF32
+                | OpcodeId::I64TruncSatSF64
+                | OpcodeId::I64TruncSatUF64
+                | OpcodeId::MemoryInit
+                | OpcodeId::DataDrop
+                | OpcodeId::MemoryCopy
+                | OpcodeId::MemoryFill
+                | OpcodeId::TableInit
+                | OpcodeId::ElemDrop
+                | OpcodeId::TableCopy
+                | OpcodeId::TableGrow
+                | OpcodeId::TableSize
+                | OpcodeId::TableFill => WASM_PREFIX_FC,
+            // EVM opcode family, these ids are never decoded from wasm bytes so sharing
+            // 0xc1..=0xc4 with the sign-extension opcodes is fine
             OpcodeId::CALLDATALOAD => 0xc1,
             OpcodeId::CALLDATASIZE => 0xc2,
             OpcodeId::CALLDATACOPY => 0xc3,
@@ -662,9 +714,74 @@ impl OpcodeId {
         }
     }
 
-    /// Returns the `OpcodeId` as a `u64`.
+    /// Returns the `OpcodeId` as a `u64`. Prefixed instructions are packed as
+    /// `prefix << 32 | sub_opcode`, so they don't collide with each other.
     pub const fn as_u64(&self) -> u64 {
-        self.as_u8() as u64
+        match self.sub_opcode() {
+            Some(sub_opcode) => (self.as_u8() as u64) << 32 | sub_opcode as u64,
+            None => self.as_u8() as u64,
+        }
+    }
+
+    /// Returns the prefix byte if the `OpcodeId` is a prefixed WASM instruction.
+    pub const fn prefix(&self) -> Option<u8> {
+        match self.sub_opcode() {
+            Some(_) => Some(WASM_PREFIX_FC),
+            None => None,
+        }
+    }
+
+    /// Returns the sub-opcode following the prefix byte of a prefixed WASM instruction.
+    pub const fn sub_opcode(&self) -> Option<u32> {
+        match self {
+            OpcodeId::I32TruncSatSF32 => Some(0x00),
+            OpcodeId::I32TruncSatUF32 => Some(0x01),
+            OpcodeId::I32TruncSatSF64 => Some(0x02),
+            OpcodeId::I32TruncSatUF64 => Some(0x03),
+            OpcodeId::I64TruncSatSF32 => Some(0x04),
+            OpcodeId::I64TruncSatUF32 => Some(0x05),
+            OpcodeId::I64TruncSatSF64 => Some(0x06),
+            OpcodeId::I64TruncSatUF64 => Some(0x07),
+            OpcodeId::MemoryInit => Some(0x08),
+            OpcodeId::DataDrop => Some(0x09),
+            OpcodeId::MemoryCopy => Some(0x0a),
+            OpcodeId::MemoryFill => Some(0x0b),
+            OpcodeId::TableInit => Some(0x0c),
+            OpcodeId::ElemDrop => Some(0x0d),
+            OpcodeId::TableCopy => Some(0x0e),
+            OpcodeId::TableGrow => Some(0x0f),
+            OpcodeId::TableSize => Some(0x10),
+            OpcodeId::TableFill => Some(0x11),
+            _ => None,
+        }
+    }
+
+    /// Returns the prefixed WASM instruction for the given prefix and sub-opcode.
+    pub fn from_prefixed(prefix: u8, sub_opcode: u32) -> Self {
+        if prefix != WASM_PREFIX_FC {
+            return OpcodeId::INVALID(prefix);
+        }
+        match sub_opcode {
+            0x00 => OpcodeId::I32TruncSatSF32,
+            0x01 => OpcodeId::I32TruncSatUF32,
+            0x02 => OpcodeId::I32TruncSatSF64,
+            0x03 => OpcodeId::I32TruncSatUF64,
+            0x04 => OpcodeId::I64TruncSatSF32,
+            0x05 => OpcodeId::I64TruncSatUF32,
+            0x06 => OpcodeId::I64TruncSatSF64,
+            0x07 => OpcodeId::I64TruncSatUF64,
+            0x08 => OpcodeId::MemoryInit,
+            0x09 => OpcodeId::DataDrop,
+            0x0a => OpcodeId::MemoryCopy,
+            0x0b => OpcodeId::MemoryFill,
+            0x0c => OpcodeId::TableInit,
+            0x0d => OpcodeId::ElemDrop,
+            0x0e => OpcodeId::TableCopy,
+            0x0f => OpcodeId::TableGrow,
+            0x10 => OpcodeId::TableSize,
+            0x11 => OpcodeId::TableFill,
+            _ => OpcodeId::INVALID(prefix),
+        }
     }
 
     /// Returns the constant gas cost of `OpcodeId`
@@ -831,6 +948,45 @@ impl OpcodeId {
         )
     }
 
+    /// Returns the number of bytes a WASM load or store accesses in memory
+    pub const fn wasm_memory_access_len(&self) -> Option<u64> {
+        match self {
+            OpcodeId::I32Load8S
+            | OpcodeId::I32Load8U
+            | OpcodeId::I64Load8S
+            | OpcodeId::I64Load8U
+            | OpcodeId::I32Store8
+            | OpcodeId::I64Store8 => Some(1),
+            OpcodeId::I32Load16S
+            | OpcodeId::I32Load16U
+            | OpcodeId::I64Load16S
+            | OpcodeId::I64Load16U
+            | OpcodeId::I32Store16
+            | OpcodeId::I64Store16 => Some(2),
+            OpcodeId::I32Load
+            | OpcodeId::I64Load32S
+            | OpcodeId::I64Load32U
+            | OpcodeId::I32Store
+            | OpcodeId::I64Store32 => Some(4),
+            OpcodeId::I64Load | OpcodeId::I64Store => Some(8),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the `OpcodeId` is a WASM store
+    pub const fn is_wasm_store(&self) -> bool {
+        matches!(
+            self,
+            OpcodeId::I32Store
+                | OpcodeId::I64Store
+                | OpcodeId::I32Store8
+                | OpcodeId::I32Store16
+                | OpcodeId::I64Store8
+                | OpcodeId::I64Store16
+                | OpcodeId::I64Store32
+        )
+    }
+
     /// Returns PUSHn opcode from parameter n.
     pub fn push_n(n: u8) -> Result<Self, Error> {
         let op = OpcodeId::from(OpcodeId::PUSH0.as_u8().checked_add(n).unwrap_or_default());
@@ -1077,11 +1233,12 @@ impl From<u8> for OpcodeId {
             0xbd => OpcodeId::I64ReinterpretF64,
             0xbe => OpcodeId::F32ReinterpretI32,
             0xbf => OpcodeId::F64ReinterpretI64,
+            0xc0 => OpcodeId::I32Extend8S,
+            0xc1 => OpcodeId::I32Extend16S,
+            0xc2 => OpcodeId::I64Extend8S,
+            0xc3 => OpcodeId::I64Extend16S,
+            0xc4 => OpcodeId::I64Extend32S,
             // EVM opcode family
-            0xc1 => OpcodeId::CALLDATALOAD,
-            0xc2 => OpcodeId::CALLDATASIZE,
-            0xc3 => OpcodeId::CALLDATACOPY,
-            0xc4 => OpcodeId::CODESIZE,
             0xc5 => OpcodeId::CODECOPY,
             0xc6 => OpcodeId::PC,
             0xc7 => OpcodeId::MSIZE,
@@ -1311,6 +1468,29 @@ impl FromStr for OpcodeId {
             "i64_reinterpret_f64" => OpcodeId::I64ReinterpretF64,
             "f32_reinterpret_i32" => OpcodeId::F32ReinterpretI32,
             "f64_reinterpret_i64" => OpcodeId::F64ReinterpretI64,
+            "i32_extend8_s" => OpcodeId::I32Extend8S,
+            "i32_extend16_s" => OpcodeId::I32Extend16S,
+            "i64_extend8_s" => OpcodeId::I64Extend8S,
+            "i64_extend16_s" => OpcodeId::I64Extend16S,
+            "i64_extend32_s" => OpcodeId::I64Extend32S,
+            "i32_trunc_sat_s_f32" => OpcodeId::I32TruncSatSF32,
+            "i32_trunc_sat_u_f32" => OpcodeId::I32TruncSatUF32,
+            "i32_trunc_sat_s_f64" => OpcodeId::I32TruncSatSF64,
+            "i32_trunc_sat_u_f64" => OpcodeId::I32TruncSatUF64,
+            "i64_trunc_sat_s_f32" => OpcodeId::I64TruncSatSF32,
+            "i64_trunc_sat_u_f32" => OpcodeId::I64TruncSatUF32,
+            "i64_trunc_sat_s_f64" => OpcodeId::I64TruncSatSF64,
+            "i64_trunc_sat_u_f64" => OpcodeId::I64TruncSatUF64,
+            "memory_init" => OpcodeId::MemoryInit,
+            "data_drop" => OpcodeId::DataDrop,
+            "memory_copy" => OpcodeId::MemoryCopy,
+            "memory_fill" => OpcodeId::MemoryFill,
+            "table_init" => OpcodeId::TableInit,
+            "elem_drop" => OpcodeId::ElemDrop,
+            "table_copy" => OpcodeId::TableCopy,
+            "table_grow" => OpcodeId::TableGrow,
+            "table_size" => OpcodeId::TableSize,
+            "table_fill" => OpcodeId::TableFill,
             // special WASM opcodes
             "evm_stop" => OpcodeId::STOP,
             "evm_return" => OpcodeId::RETURN,
@@ -1439,4 +1619,24 @@ mod opcode_ids_tests {
         assert_eq!(OpcodeId::LOG2.data_len(), 0);
         assert_eq!(OpcodeId::CALLCODE.data_len(), 0);
     }
+
+    #[test]
+    fn prefixed() {
+        assert_eq!(OpcodeId::MemoryCopy.prefix(), Some(WASM_PREFIX_FC));
+        assert_eq!(OpcodeId::MemoryCopy.sub_opcode(), Some(10));
+        assert_eq!(OpcodeId::MemoryCopy.as_u8(), WASM_PREFIX_FC);
+        assert_ne!(OpcodeId::MemoryCopy.as_u64(), OpcodeId::MemoryFill.as_u64());
+        assert_eq!(OpcodeId::I32Add.prefix(), None);
+        assert_eq!(OpcodeId::from_prefixed(WASM_PREFIX_FC, 11), OpcodeId::MemoryFill);
+        assert_eq!(OpcodeId::from_prefixed(WASM_PREFIX_FC, 0x80), OpcodeId::INVALID(WASM_PREFIX_FC));
+        assert_eq!(OpcodeId::from_str("i32_trunc_sat_s_f32").unwrap(), OpcodeId::I32TruncSatSF32);
+    }
+
+    #[test]
+    fn sign_extension() {
+        assert_eq!(OpcodeId::from(0xc1), OpcodeId::I32Extend16S);
+        assert_eq!(OpcodeId::I32Extend16S.as_u8(), 0xc1);
+        assert_eq!(OpcodeId::CALLDATALOAD.as_u8(), 0xc1);
+        assert_eq!(OpcodeId::CODESIZE.as_u8(), 0xc4);
+    }
 }
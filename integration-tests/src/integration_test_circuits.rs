@@ -3,7 +3,7 @@ use bus_mapping::{
     circuit_input_builder::{BuilderClient, CircuitInputBuilder, CircuitsParams},
     mock::BlockData,
 };
-use eth_types::geth_types::GethData;
+use eth_types::{evm_types::WasmFuelSchedule, geth_types::GethData};
 use halo2_proofs::{
     dev::{CellValue, MockProver},
     halo2curves::bn256::{Bn256, Fr, G1Affine},
@@ -76,6 +76,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_exp_steps: MAX_EXP_STEPS,
     max_keccak_rows: MAX_KECCAK_ROWS,
     max_rlp_rows: MAX_RLP_ROWS,
+    wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
 };
 
 const EVM_CIRCUIT_DEGREE: u32 = 18;
@@ -3,6 +3,7 @@
 use bus_mapping::circuit_input_builder::{
     build_state_code_db, get_state_accesses, BuilderClient, CircuitsParams,
 };
+use eth_types::evm_types::WasmFuelSchedule;
 use integration_tests::{get_client, log_init, GenDataOutput};
 use lazy_static::lazy_static;
 use log::trace;
@@ -27,6 +28,7 @@ async fn test_circuit_input_builder_block(block_num: u64) {
             max_exp_steps: 1000,
             max_keccak_rows: 0,
             max_rlp_rows: 4200,
+            wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
         },
     )
     .await
@@ -2,6 +2,7 @@ use bus_mapping::{
     circuit_input_builder::{keccak_inputs, BuilderClient, CircuitsParams},
     Error::JSONRpcError,
 };
+use eth_types::evm_types::WasmFuelSchedule;
 use halo2_proofs::{
     circuit::Value,
     dev::{MockProver, VerifyFailure},
@@ -33,6 +34,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_exp_steps: 1000,
     max_evm_rows: 0,
     max_rlp_rows: 33000,
+    wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
 };
 
 #[tokio::test]
@@ -56,6 +58,7 @@ async fn test_mock_prove_tx() {
         max_exp_steps: 5000,
         max_evm_rows: 0,
         max_rlp_rows: 42000,
+        wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
     };
 
     let cli = BuilderClient::new(cli, params).await.unwrap();
@@ -123,6 +126,7 @@ async fn test_circuit_all_block() {
             max_exp_steps: 100_000,
             max_evm_rows: 0,
             max_rlp_rows: 2_070_000,
+            wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
         };
         let cli = BuilderClient::new(cli, params).await.unwrap();
         let builder = cli.gen_inputs(block_num).await;
@@ -4,7 +4,10 @@ use bus_mapping::{
     circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
     mock::BlockData,
 };
-use eth_types::{geth_types, geth_types::TxType, Address, Bytes, GethExecTrace, U256, U64};
+use eth_types::{
+    evm_types::WasmFuelSchedule, geth_types, geth_types::TxType, Address, Bytes, GethExecTrace,
+    U256, U64,
+};
 use ethers_core::{
     k256::ecdsa::SigningKey,
     types::{transaction::eip2718::TypedTransaction, TransactionRequest},
@@ -332,6 +335,7 @@ pub fn run_test(
             max_keccak_rows: 0,
             max_inner_blocks: 64,
             max_rlp_rows: 6000,
+            wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
         };
         let block_data = BlockData::new_from_geth_data_with_params(geth_data, circuits_params);
 
@@ -360,6 +364,7 @@ pub fn run_test(
             max_keccak_rows: 0,
             max_inner_blocks: 64,
             max_rlp_rows: 512,
+            wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
         };
         let (k, circuit, instance, _builder) =
             SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, 64, 0x100>::build(geth_data, circuits_params)
@@ -141,6 +141,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
         let rlc_acc = copy_table.rlc_acc;
         let rw_counter = copy_table.rw_counter;
         let rwc_inc_left = copy_table.rwc_inc_left;
+        let is_reverse = copy_table.is_reverse;
         let tag = copy_table.tag;
 
         // annotate table columns
@@ -224,6 +225,10 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                 "is_last is boolean",
                 meta.query_advice(is_last, Rotation::cur()),
             );
+            cb.require_boolean(
+                "is_reverse is boolean",
+                meta.query_advice(is_reverse, Rotation::cur()),
+            );
             cb.require_zero(
                 "is_first == 0 when q_step == 0",
                 and::expr([
@@ -259,8 +264,14 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                         tag.value(Rotation(2))(meta),
                     );
                     cb.require_equal(
-                        "rows[0].addr + 1 == rows[2].addr",
-                        meta.query_advice(addr, Rotation::cur()) + 1.expr(),
+                        "rows[0].is_reverse == rows[2].is_reverse",
+                        meta.query_advice(is_reverse, Rotation::cur()),
+                        meta.query_advice(is_reverse, Rotation(2)),
+                    );
+                    cb.require_equal(
+                        "rows[0].addr + 1 == rows[2].addr, or - 1 when is_reverse",
+                        meta.query_advice(addr, Rotation::cur()) + 1.expr()
+                            - 2.expr() * meta.query_advice(is_reverse, Rotation::cur()),
                         meta.query_advice(addr, Rotation(2)),
                     );
                     cb.require_equal(
@@ -397,6 +408,11 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                 meta.query_advice(value, Rotation::cur()),
                 meta.query_advice(value, Rotation::next()),
             );
+            cb.require_equal(
+                "is_reverse is same for read-write rows",
+                meta.query_advice(is_reverse, Rotation::cur()),
+                meta.query_advice(is_reverse, Rotation::next()),
+            );
             cb.require_equal(
                 "value_acc is same for read-write rows",
                 meta.query_advice(value_acc, Rotation::cur()),
@@ -432,6 +448,19 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                 "is_pad == 0 for write row",
                 meta.query_advice(is_pad, Rotation::next()),
             );
+            cb.condition(
+                and::expr([
+                    tag.value_equals(CopyDataType::Constant, Rotation::cur())(meta),
+                    not::expr(meta.query_advice(is_pad, Rotation::cur())),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "value == id when reading from a Constant source",
+                        meta.query_advice(value, Rotation::cur()),
+                        meta.query_advice(id, Rotation::cur()),
+                    );
+                },
+            );
 
             cb.gate(and::expr([
                 meta.query_fixed(q_enable, Rotation::cur()),
@@ -624,7 +653,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                 lt_chip.assign(
                     region,
                     *offset,
-                    F::from(copy_event.src_addr + u64::try_from(step_idx).unwrap() / 2u64),
+                    F::from(copy_event.src_addr + copy_event.addr_offset(step_idx)),
                     F::from(copy_event.src_addr_end),
                 )?;
             }
@@ -843,6 +872,13 @@ impl<F: Field> CopyCircuitConfig<F> {
             *offset,
             || Value::known(F::zero()),
         )?;
+        // is_reverse
+        region.assign_advice(
+            || format!("assign is_reverse {}", *offset),
+            self.copy_table.is_reverse,
+            *offset,
+            || Value::known(F::zero()),
+        )?;
         // tag
         tag_chip.assign(region, *offset, &CopyDataType::Padding)?;
         // Assign LT gadget
@@ -30,11 +30,13 @@ use crate::{
     evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
     table::{
         BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, LookupTable, RwTable, TxTable,
+        WasmFunctionTable,
     },
     util::{SubCircuit, SubCircuitConfig},
+    wasm_circuit::{bytecode::bytecode_table::WasmBytecodeTable, circuit::WasmCircuit},
 };
 use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use eth_types::{evm_types::WasmFuelSchedule, Field};
 use execution::ExecutionConfig;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
@@ -46,6 +48,7 @@ use witness::Block;
 pub struct EvmCircuitConfig<F> {
     fixed_table: [Column<Fixed>; 4],
     byte_table: [Column<Fixed>; 1],
+    wasm_function_table: WasmFunctionTable,
     pub(crate) execution: Box<ExecutionConfig<F>>,
     // External tables
     tx_table: TxTable,
@@ -55,6 +58,7 @@ pub struct EvmCircuitConfig<F> {
     copy_table: CopyTable,
     keccak_table: KeccakTable,
     exp_table: ExpTable,
+    wasm_bytecode_table: WasmBytecodeTable,
 }
 
 /// Circuit configuration arguments
@@ -75,6 +79,10 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub keccak_table: KeccakTable,
     /// ExpTable
     pub exp_table: ExpTable,
+    /// WasmFunctionTable
+    pub wasm_function_table: WasmFunctionTable,
+    /// WasmBytecodeTable
+    pub wasm_bytecode_table: WasmBytecodeTable,
 }
 
 /// Circuit exported cells after synthesis, used for subcircuit
@@ -100,6 +108,8 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            wasm_function_table,
+            wasm_bytecode_table,
         }: Self::ConfigArgs,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
@@ -116,6 +126,9 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &copy_table,
             &keccak_table,
             &exp_table,
+            &wasm_function_table,
+            &wasm_bytecode_table,
+            &wasm_bytecode_table.module_table(),
         ));
 
         meta.annotate_lookup_any_column(byte_table[0], || "byte_range");
@@ -129,10 +142,13 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
         copy_table.annotate_columns(meta);
         keccak_table.annotate_columns(meta);
         exp_table.annotate_columns(meta);
+        wasm_function_table.annotate_columns(meta);
+        wasm_bytecode_table.annotate_columns(meta);
 
         Self {
             fixed_table,
             byte_table,
+            wasm_function_table,
             execution,
             tx_table,
             rw_table,
@@ -141,6 +157,7 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            wasm_bytecode_table,
         }
     }
 }
@@ -151,12 +168,17 @@ impl<F: Field> EvmCircuitConfig<F> {
         &self,
         layouter: &mut impl Layouter<F>,
         fixed_table_tags: Vec<FixedTableTag>,
+        wasm_fuel_schedule: WasmFuelSchedule,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "fixed table",
             |mut region| {
                 for (offset, row) in std::iter::once([F::zero(); 4])
-                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build()))
+                    .chain(
+                        fixed_table_tags
+                            .iter()
+                            .flat_map(|tag| tag.build(wasm_fuel_schedule)),
+                    )
                     .enumerate()
                 {
                     for (column, value) in self.fixed_table.iter().zip_eq(row) {
@@ -280,7 +302,10 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
             Self::get_num_rows_required_no_padding(block);
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(block)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| {
+                tag.build::<F>(block.circuits_params.wasm_fuel_schedule)
+                    .count()
+            })
             .sum();
         (
             num_rows_required_for_execution_steps,
@@ -303,8 +328,15 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
     ) -> Result<(), Error> {
         let block = self.block.as_ref().unwrap();
 
-        config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
+        config.load_fixed_table(
+            layouter,
+            self.fixed_table_tags.clone(),
+            block.circuits_params.wasm_fuel_schedule,
+        )?;
         config.load_byte_table(layouter)?;
+        config
+            .wasm_function_table
+            .load(layouter, block.bytecodes.values(), challenges)?;
         let export = config.execution.assign_block(layouter, block, challenges)?;
         self.exports.borrow_mut().replace(export);
         Ok(())
@@ -417,6 +449,8 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
         let copy_table = CopyTable::construct(meta, q_copy_table);
         let keccak_table = KeccakTable::construct(meta);
         let exp_table = ExpTable::construct(meta);
+        let wasm_function_table = WasmFunctionTable::construct(meta);
+        let wasm_bytecode_table = WasmBytecodeTable::construct(meta, true);
         (
             EvmCircuitConfig::new(
                 meta,
@@ -429,6 +463,8 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     copy_table,
                     keccak_table,
                     exp_table,
+                    wasm_function_table,
+                    wasm_bytecode_table,
                 },
             ),
             challenges,
@@ -473,6 +509,9 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             .keccak_table
             .dev_load(&mut layouter, &block.sha3_inputs, &challenges)?;
         config.exp_table.dev_load(&mut layouter, block)?;
+        config
+            .wasm_bytecode_table
+            .dev_load(&mut layouter, &WasmCircuit::<F>::new_from_block(block).wbs)?;
 
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
@@ -654,7 +693,11 @@ mod evm_circuit_stats {
             keccak_table,
             LOOKUP_CONFIG[6].1,
             exp_table,
-            LOOKUP_CONFIG[7].1
+            LOOKUP_CONFIG[7].1,
+            wasm_function_table,
+            LOOKUP_CONFIG[8].1,
+            wasm_bytecode_table,
+            LOOKUP_CONFIG[9].1
         );
     }
 
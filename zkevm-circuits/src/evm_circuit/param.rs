@@ -37,7 +37,10 @@ pub(crate) const EVM_LOOKUP_COLS: usize = FIXED_TABLE_LOOKUPS
     + BLOCK_TABLE_LOOKUPS
     + COPY_TABLE_LOOKUPS
     + KECCAK_TABLE_LOOKUPS
-    + EXP_TABLE_LOOKUPS;
+    + EXP_TABLE_LOOKUPS
+    + WASM_FUNCTION_TABLE_LOOKUPS
+    + WASM_BYTECODE_TABLE_LOOKUPS
+    + WASM_MODULE_TABLE_LOOKUPS;
 
 /// Lookups done per row.
 pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
@@ -49,6 +52,9 @@ pub(crate) const LOOKUP_CONFIG: &[(Table, usize)] = &[
     (Table::Copy, COPY_TABLE_LOOKUPS),
     (Table::Keccak, KECCAK_TABLE_LOOKUPS),
     (Table::Exp, EXP_TABLE_LOOKUPS),
+    (Table::WasmFunction, WASM_FUNCTION_TABLE_LOOKUPS),
+    (Table::WasmBytecode, WASM_BYTECODE_TABLE_LOOKUPS),
+    (Table::WasmModule, WASM_MODULE_TABLE_LOOKUPS),
 ];
 
 /// Fixed Table lookups done in EVMCircuit
@@ -75,6 +81,15 @@ pub const KECCAK_TABLE_LOOKUPS: usize = 1;
 /// Exp Table lookups done in EVMCircuit
 pub const EXP_TABLE_LOOKUPS: usize = 1;
 
+/// Wasm Function Table lookups done in EVMCircuit
+pub const WASM_FUNCTION_TABLE_LOOKUPS: usize = 3;
+
+/// Wasm Bytecode Table lookups done in EVMCircuit
+pub const WASM_BYTECODE_TABLE_LOOKUPS: usize = 1;
+
+/// Wasm Module Table lookups done in EVMCircuit
+pub const WASM_MODULE_TABLE_LOOKUPS: usize = 3;
+
 /// Maximum number of bytes that an integer can fit in field without wrapping
 /// around.
 pub(crate) const MAX_N_BYTES_INTEGER: usize = 31;
@@ -45,7 +45,9 @@ pub enum ExecutionState {
     EndBlock,
     // WASM opcode cases
     WASM_BIN,
+    WASM_BLOCK,
     WASM_BREAK,
+    WASM_BULK_MEMORY,
     WASM_CALL,
     WASM_CONST,
     WASM_CONVERSION,
@@ -54,7 +56,9 @@ pub enum ExecutionState {
     WASM_GLOBAL,
     WASM_LOAD,
     WASM_LOCAL,
+    WASM_MEMORY_GROW,
     WASM_REL,
+    WASM_RETURN,
     WASM_SELECT,
     WASM_STORE,
     WASM_TEST,
@@ -123,6 +127,12 @@ pub enum ExecutionState {
     ErrorWriteProtection,
     ErrorInvalidCreationCode,
     ErrorInvalidJump,
+    ErrorInvalidIndirectCall,
+    // WASM traps
+    ErrorCallStackExhausted,
+    ErrorUnreachable,
+    ErrorIntegerDivision, // divide by zero, signed overflow
+    ErrorMemoryOutOfBounds,
     ErrorReturnDataOutOfBound,
     ErrorPrecompileFailed,
     ErrorOutOfGasConstant,
@@ -211,6 +221,11 @@ impl ExecutionState {
                 | Self::ErrorWriteProtection
                 | Self::ErrorInvalidCreationCode
                 | Self::ErrorInvalidJump
+                | Self::ErrorInvalidIndirectCall
+                | Self::ErrorCallStackExhausted
+                | Self::ErrorUnreachable
+                | Self::ErrorIntegerDivision
+                | Self::ErrorMemoryOutOfBounds
                 | Self::ErrorReturnDataOutOfBound
                 | Self::ErrorOutOfGasConstant
                 | Self::ErrorOutOfGasStaticMemoryExpansion
@@ -263,11 +278,18 @@ impl ExecutionState {
                 OpcodeId::I32RemU,
                 OpcodeId::I64RemU,
             ],
+            Self::WASM_BLOCK => vec![
+                OpcodeId::Nop,
+                OpcodeId::Block,
+                OpcodeId::Loop,
+                OpcodeId::If,
+                OpcodeId::Else,
+                OpcodeId::End,
+            ],
             Self::WASM_BREAK => vec![
                 OpcodeId::Return,
                 OpcodeId::Br,
                 OpcodeId::BrIf,
-                OpcodeId::BrTable,
             ],
             Self::WASM_CONST => vec![
                 OpcodeId::I32Const,
@@ -302,6 +324,11 @@ impl ExecutionState {
                 OpcodeId::I32WrapI64,
                 OpcodeId::I64ExtendUI32,
                 OpcodeId::I64ExtendSI32,
+                OpcodeId::I32Extend8S,
+                OpcodeId::I32Extend16S,
+                OpcodeId::I64Extend8S,
+                OpcodeId::I64Extend16S,
+                OpcodeId::I64Extend32S,
             ],
             Self::WASM_GLOBAL => vec![
                 OpcodeId::GetGlobal,
@@ -312,7 +339,41 @@ impl ExecutionState {
                 OpcodeId::SetLocal,
                 OpcodeId::TeeLocal,
             ],
+            Self::WASM_LOAD => vec![
+                OpcodeId::I32Load,
+                OpcodeId::I64Load,
+                OpcodeId::I32Load8S,
+                OpcodeId::I32Load8U,
+                OpcodeId::I32Load16S,
+                OpcodeId::I32Load16U,
+                OpcodeId::I64Load8S,
+                OpcodeId::I64Load8U,
+                OpcodeId::I64Load16S,
+                OpcodeId::I64Load16U,
+                OpcodeId::I64Load32S,
+                OpcodeId::I64Load32U,
+            ],
+            Self::WASM_STORE => vec![
+                OpcodeId::I32Store,
+                OpcodeId::I64Store,
+                OpcodeId::I32Store8,
+                OpcodeId::I32Store16,
+                OpcodeId::I64Store8,
+                OpcodeId::I64Store16,
+                OpcodeId::I64Store32,
+            ],
+            Self::WASM_MEMORY_GROW => vec![
+                OpcodeId::CurrentMemory,
+                OpcodeId::GrowMemory,
+            ],
+            Self::WASM_BULK_MEMORY => vec![
+                OpcodeId::MemoryCopy,
+                OpcodeId::MemoryFill,
+                OpcodeId::MemoryInit,
+                OpcodeId::DataDrop,
+            ],
             Self::WASM_END => vec![OpcodeId::End],
+            Self::WASM_RETURN => vec![OpcodeId::Return, OpcodeId::End, OpcodeId::Br, OpcodeId::BrIf],
             // EVM opcodes
             Self::STOP => vec![OpcodeId::STOP],
             Self::MUL_DIV_MOD => vec![OpcodeId::MUL, OpcodeId::DIV, OpcodeId::MOD],
@@ -382,6 +443,28 @@ impl ExecutionState {
             Self::RETURN_REVERT => vec![OpcodeId::RETURN, OpcodeId::REVERT],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
             Self::ErrorInvalidOpcode => OpcodeId::invalid_opcodes(),
+            Self::ErrorUnreachable => vec![OpcodeId::Unreachable],
+            Self::ErrorCallStackExhausted => vec![OpcodeId::Call, OpcodeId::CallIndirect],
+            Self::ErrorIntegerDivision => vec![
+                OpcodeId::I32DivS,
+                OpcodeId::I64DivS,
+                OpcodeId::I32DivU,
+                OpcodeId::I64DivU,
+                OpcodeId::I32RemS,
+                OpcodeId::I64RemS,
+                OpcodeId::I32RemU,
+                OpcodeId::I64RemU,
+            ],
+            Self::ErrorMemoryOutOfBounds => OpcodeId::valid_opcodes()
+                .into_iter()
+                .filter(|op| {
+                    op.wasm_memory_access_len().is_some()
+                        || matches!(
+                            op,
+                            OpcodeId::MemoryCopy | OpcodeId::MemoryFill | OpcodeId::MemoryInit
+                        )
+                })
+                .collect(),
             _ => vec![],
         }
             .into_iter()
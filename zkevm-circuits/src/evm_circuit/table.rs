@@ -4,7 +4,7 @@ use crate::{
     impl_expr,
 };
 use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
-use eth_types::Field;
+use eth_types::{evm_types::WasmFuelSchedule, Field};
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
 use strum::IntoEnumIterator;
@@ -36,11 +36,16 @@ pub enum FixedTableTag {
     Clz,
     ClzFilter,
     CzOut,
+    WasmFuel,
 }
 impl_expr!(FixedTableTag);
 
 impl FixedTableTag {
-    pub fn build<F: Field>(&self) -> Box<dyn Iterator<Item = [F; 4]>> {
+    /// Builds the rows of the tag, `WasmFuel` ones from the given fuel schedule.
+    pub fn build<F: Field>(
+        &self,
+        wasm_fuel_schedule: WasmFuelSchedule,
+    ) -> Box<dyn Iterator<Item = [F; 4]>> {
         let tag = F::from(*self as u64);
         match self {
             Self::Zero => Box::new((0..1).map(move |_| [tag, F::zero(), F::zero(), F::zero()])),
@@ -197,6 +202,22 @@ impl FixedTableTag {
                      out
                 })])
             })),
+            // Only `memory.grow` is charged per page on top of its static fuel.
+            Self::WasmFuel => Box::new(OpcodeId::iter().filter_map(move |opcode| {
+                let fuel_per_page = if opcode == OpcodeId::GrowMemory {
+                    wasm_fuel_schedule.memory_grow_per_page
+                } else {
+                    0
+                };
+                wasm_fuel_schedule.instruction_fuel(opcode).map(|fuel| {
+                    [
+                        tag,
+                        F::from(opcode.as_u64()),
+                        F::from(fuel),
+                        F::from(fuel_per_page),
+                    ]
+                })
+            })),
         }
     }
 }
@@ -211,6 +232,9 @@ pub(crate) enum Table {
     Copy,
     Keccak,
     Exp,
+    WasmFunction,
+    WasmBytecode,
+    WasmModule,
 }
 
 #[derive(Clone, Debug)]
@@ -341,6 +365,8 @@ pub(crate) enum Lookup<F> {
         /// The RW counter that is incremented by the time all bytes have been
         /// copied specific to this copy event.
         rwc_inc: Expression<F>,
+        /// Whether the bytes are copied from the highest address down.
+        is_reverse: Expression<F>,
     },
     /// Lookup to keccak table.
     KeccakTable {
@@ -360,6 +386,43 @@ pub(crate) enum Lookup<F> {
         exponent_lo_hi: [Expression<F>; 2],
         exponentiation_lo_hi: [Expression<F>; 2],
     },
+    /// Lookup to wasm function table, which contains the function table and
+    /// the function signatures of every wasm module.
+    WasmFunctionTable {
+        /// Hash to specify which module to read.
+        hash: Expression<F>,
+        /// Tag to specify whether its the table size, a table slot or a
+        /// function type.
+        tag: Expression<F>,
+        /// Index of the table slot or of the function.
+        index: Expression<F>,
+        /// Value corresponding to the tag.
+        value: Expression<F>,
+    },
+    /// Lookup to wasm bytecode table, which keeps the verdict of the wasm
+    /// circuit at the last byte of every bytecode.
+    WasmBytecodeTable {
+        /// Hash to specify which module to check.
+        hash: Expression<F>,
+        /// Whether the row is the last byte of the module.
+        is_last: Expression<F>,
+        /// Error code of the module, 0 if it was validated.
+        error_code: Expression<F>,
+    },
+    /// Lookup to the module view of the wasm bytecode table, which contains
+    /// what the wasm circuit decoded from the sections of every module.
+    WasmModuleTable {
+        /// Hash to specify which module to read.
+        hash: Expression<F>,
+        /// Tag to specify what is decoded, see `WasmModuleTag`.
+        tag: Expression<F>,
+        /// Index of the decoded value.
+        index: Expression<F>,
+        /// Value corresponding to the tag.
+        value: Expression<F>,
+        /// Second value of the tags which have one.
+        arg: Expression<F>,
+    },
     /// Conditional lookup enabled by the first element.
     Conditional(Expression<F>, Box<Lookup<F>>),
 }
@@ -379,6 +442,9 @@ impl<F: Field> Lookup<F> {
             Self::CopyTable { .. } => Table::Copy,
             Self::KeccakTable { .. } => Table::Keccak,
             Self::ExpTable { .. } => Table::Exp,
+            Self::WasmFunctionTable { .. } => Table::WasmFunction,
+            Self::WasmBytecodeTable { .. } => Table::WasmBytecode,
+            Self::WasmModuleTable { .. } => Table::WasmModule,
             Self::Conditional(_, lookup) => lookup.table(),
         }
     }
@@ -455,6 +521,7 @@ impl<F: Field> Lookup<F> {
                 rlc_acc,
                 rw_counter,
                 rwc_inc,
+                is_reverse,
             } => vec![
                 1.expr(),
                 is_first.clone(),
@@ -469,6 +536,7 @@ impl<F: Field> Lookup<F> {
                 rlc_acc.clone(),
                 rw_counter.clone(),
                 rwc_inc.clone(),
+                is_reverse.clone(),
             ],
             Self::KeccakTable {
                 input_rlc,
@@ -501,6 +569,36 @@ impl<F: Field> Lookup<F> {
                 exponentiation_lo_hi[0].clone(),
                 exponentiation_lo_hi[1].clone(),
             ],
+            Self::WasmFunctionTable {
+                hash,
+                tag,
+                index,
+                value,
+            } => vec![
+                1.expr(), // q_enable
+                hash.clone(),
+                tag.clone(),
+                index.clone(),
+                value.clone(),
+            ],
+            Self::WasmBytecodeTable {
+                hash,
+                is_last,
+                error_code,
+            } => vec![hash.clone(), is_last.clone(), error_code.clone()],
+            Self::WasmModuleTable {
+                hash,
+                tag,
+                index,
+                value,
+                arg,
+            } => vec![
+                hash.clone(),
+                tag.clone(),
+                index.clone(),
+                value.clone(),
+                arg.clone(),
+            ],
             Self::Conditional(condition, lookup) => lookup
                 .input_exprs()
                 .into_iter()
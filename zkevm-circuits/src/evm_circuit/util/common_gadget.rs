@@ -23,7 +23,10 @@ use crate::{
     util::Expr,
     witness::{Block, Call, ExecStep},
 };
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256, U64};
+use eth_types::{
+    evm_types::{GasCost, OpcodeId},
+    Field, ToLittleEndian, ToScalar, U256, U64,
+};
 use gadgets::util::{select, sum};
 use halo2_proofs::{
     circuit::Value,
@@ -92,6 +95,61 @@ impl<F: Field> SameContextGadget<F> {
     }
 }
 
+/// Looks up the fuel charged for a WASM instruction in the fixed fuel table,
+/// so gadgets handling several opcodes charge each one its own fuel. The table
+/// is built from the fuel schedule of the block's circuits params.
+#[derive(Clone, Debug)]
+pub(crate) struct WasmFuelGadget<F> {
+    fuel: Cell<F>,
+    fuel_per_page: Cell<F>,
+}
+
+impl<F: Field> WasmFuelGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, opcode: Expression<F>) -> Self {
+        let fuel = cb.query_cell();
+        let fuel_per_page = cb.query_cell();
+        cb.wasm_fuel_lookup(opcode, fuel.expr(), fuel_per_page.expr());
+
+        Self {
+            fuel,
+            fuel_per_page,
+        }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        self.fuel.expr()
+    }
+
+    /// Fuel charged per page grown, zero for every opcode but `memory.grow`.
+    pub(crate) fn per_page_expr(&self) -> Expression<F> {
+        self.fuel_per_page.expr()
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        opcode: OpcodeId,
+    ) -> Result<u64, Error> {
+        let schedule = block.circuits_params.wasm_fuel_schedule;
+        let fuel = schedule
+            .instruction_fuel(opcode)
+            .expect("only WASM instructions are charged fuel");
+        let fuel_per_page = if opcode == OpcodeId::GrowMemory {
+            schedule.memory_grow_per_page
+        } else {
+            0
+        };
+        self.fuel
+            .assign(region, offset, Value::known(F::from(fuel)))?;
+        self.fuel_per_page
+            .assign(region, offset, Value::known(F::from(fuel_per_page)))?;
+
+        Ok(fuel)
+    }
+}
+
 /// Construction of step state transition that restores caller's state.
 #[derive(Clone, Debug)]
 pub(crate) struct RestoreContextGadget<F> {
@@ -654,9 +712,8 @@ pub(crate) struct CommonCallGadget<F, MemAddrGadget, const IS_SUCCESS_CALL: bool
     pub is_success: Cell<F>,
 
     pub gas: Cell<F>,
-    pub gas_is_u64: IsZeroGadget<F>,
     pub callee_address_offset: Cell<F>,
-    pub callee_address: Word<F>,
+    pub callee_address: RandomLinearCombination<F, N_BYTES_ACCOUNT_ADDRESS>,
     pub value_offset: Cell<F>,
     pub value: Word<F>,
     pub cd_address: MemAddrGadget,
@@ -711,28 +768,27 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
 
         cb.stack_pop(status_offset.expr());
         cb.stack_pop(rd_address.length());
-        cb.stack_pop(rd_address.address());
+        cb.stack_pop(rd_address.offset_rlc());
         cb.stack_pop(cd_address.length());
-        cb.stack_pop(cd_address.address());
+        cb.stack_pop(cd_address.offset_rlc());
 
-        // `CALL` and `CALLCODE` opcodes have an additional stack pop `value`.
+        // `CALL` and `CALLCODE` opcodes have an additional `value` pointer, the
+        // value itself is a big-endian word in linear memory.
         cb.condition(is_call + is_callcode, |cb| {
             cb.stack_pop(value_offset.expr());
-            // cb.memory_rlc_lookup(0.expr(), &value_offset, &value);
+            cb.memory_rlc_lookup(0.expr(), &value_offset, &value);
         });
 
         cb.stack_pop(callee_address_offset.expr());
-        // cb.memory_rlc_lookup(0.expr(), &callee_address_offset, &callee_address);
+        cb.memory_rlc_lookup(0.expr(), &callee_address_offset, &callee_address);
         cb.stack_pop(gas.expr());
 
-        cb.memory_lookup(1.expr(), status_offset.expr(), if IS_SUCCESS_CALL {
-            is_success.expr()
-        } else {
-            0.expr()
-        }, None);
+        // Only a call that is actually made reports its status, the error
+        // gadgets halt before writing it.
+        if IS_SUCCESS_CALL {
+            cb.memory_lookup(1.expr(), status_offset.expr(), is_success.expr(), None);
+        }
 
-        // Recomposition of random linear combination to integer
-        let gas_is_u64 = IsZeroGadget::construct(cb, gas.expr());
         let memory_expansion =
             MemoryExpansionGadget::construct(cb, [cd_address.address(), rd_address.address()]);
 
@@ -746,7 +802,7 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
 
         let phase2_callee_code_hash = cb.query_cell_with_type(CellType::StoragePhase2);
         cb.account_read(
-            from_bytes::expr(&callee_address.cells[..N_BYTES_ACCOUNT_ADDRESS]),
+            from_bytes::expr(&callee_address.cells),
             AccountFieldTag::CodeHash,
             phase2_callee_code_hash.expr(),
         );
@@ -757,7 +813,6 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
         Self {
             is_success,
             gas,
-            gas_is_u64,
             callee_address_offset,
             callee_address,
             value_offset,
@@ -778,7 +833,7 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
     }
 
     pub fn callee_address_expr(&self) -> Expression<F> {
-        from_bytes::expr(&self.callee_address.cells[..N_BYTES_ACCOUNT_ADDRESS])
+        from_bytes::expr(&self.callee_address.cells)
     }
 
     pub fn gas_expr(&self) -> Expression<F> {
@@ -818,31 +873,36 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
         rd_offset: U64,
         rd_length: U64,
         phase2_callee_code_hash: Value<F>,
+        curr_memory_word_size: u64,
     ) -> Result<u64, Error> {
         self.callee_address_offset.assign(region, offset, Value::known(F::from(callee_address_offset.as_u64())))?;
         self.value_offset.assign(region, offset, Value::known(F::from(value_offset.as_u64())))?;
         self.status_offset.assign(region, offset, Value::known(F::from(status_offset.as_u64())))?;
 
         self.gas.assign(region, offset, Value::known(F::from(gas.as_u64())))?;
-        self.callee_address
-            .assign(region, offset, Some(callee_address.to_le_bytes()))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Some(callee_address.to_le_bytes()[..N_BYTES_ACCOUNT_ADDRESS].try_into().unwrap()),
+        )?;
         self.value
             .assign(region, offset, Some(value.to_le_bytes()))?;
         if IS_SUCCESS_CALL {
             self.is_success
                 .assign(region, offset, Value::known(F::from(is_success.low_u64())))?;
         }
-        self.gas_is_u64.assign(
-            region,
-            offset,
-            sum::value(&gas.to_le_bytes()[N_BYTES_GAS..]),
-        )?;
         let cd_address = self
             .cd_address
             .assign(region, offset, cd_offset, cd_length)?;
         let rd_address = self
             .rd_address
             .assign(region, offset, rd_offset, rd_length)?;
+        let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            curr_memory_word_size,
+            [cd_address, rd_address],
+        )?;
 
         self.value_is_zero
             .assign(region, offset, sum::value(&value.to_le_bytes()))?;
@@ -856,7 +916,7 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
         )?;
         self.callee_not_exists
             .assign_value(region, offset, phase2_callee_code_hash)?;
-        Ok(0)
+        Ok(memory_expansion_gas_cost)
     }
 
     pub(crate) fn cal_gas_cost_for_assignment(
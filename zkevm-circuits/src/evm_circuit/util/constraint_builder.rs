@@ -7,9 +7,10 @@ use crate::{
     },
     table::{
         AccountFieldTag, BytecodeFieldTag, CallContextFieldTag, RwTableTag, TxContextFieldTag,
-        TxLogFieldTag, TxReceiptFieldTag,
+        TxLogFieldTag, TxReceiptFieldTag, WasmFunctionTableTag,
     },
     util::{build_tx_log_expression, Challenges, Expr},
+    wasm_circuit::types::WasmModuleTag,
 };
 use bus_mapping::{
     state_db::EMPTY_CODE_HASH_LE,
@@ -635,6 +636,23 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // WASM fuel
+
+    pub(crate) fn wasm_fuel_lookup(
+        &mut self,
+        opcode: Expression<F>,
+        fuel: Expression<F>,
+        fuel_per_page: Expression<F>,
+    ) {
+        self.add_lookup(
+            "wasm fuel",
+            Lookup::Fixed {
+                tag: FixedTableTag::WasmFuel.expr(),
+                values: [opcode, fuel, fuel_per_page],
+            },
+        );
+    }
+
     // Opcode
 
     pub(crate) fn opcode_lookup(&mut self, opcode: Expression<F>, is_code: Expression<F>) {
@@ -700,6 +718,59 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    pub(crate) fn wasm_function_table_lookup(
+        &mut self,
+        code_hash: Expression<F>,
+        tag: WasmFunctionTableTag,
+        index: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Wasm function table lookup",
+            Lookup::WasmFunctionTable {
+                hash: code_hash,
+                tag: tag.expr(),
+                index,
+                value,
+            },
+        );
+    }
+
+    /// Requires the code behind `code_hash` to be a module the wasm circuit
+    /// validated, i.e. its last byte was assigned the `Ok` error code.
+    pub(crate) fn wasm_module_validated_lookup(&mut self, code_hash: Expression<F>) {
+        self.add_lookup(
+            "Wasm module validated lookup",
+            Lookup::WasmBytecodeTable {
+                hash: code_hash,
+                is_last: 1.expr(),
+                error_code: 0.expr(),
+            },
+        );
+    }
+
+    /// Looks up what the wasm circuit decoded from the module behind
+    /// `code_hash`, see `WasmModuleTag`.
+    pub(crate) fn wasm_module_lookup(
+        &mut self,
+        code_hash: Expression<F>,
+        tag: WasmModuleTag,
+        index: Expression<F>,
+        value: Expression<F>,
+        arg: Expression<F>,
+    ) {
+        self.add_lookup(
+            "Wasm module lookup",
+            Lookup::WasmModuleTable {
+                hash: code_hash,
+                tag: tag.expr(),
+                index,
+                value,
+                arg,
+            },
+        );
+    }
+
     // Tx context
 
     pub(crate) fn tx_context(
@@ -1216,6 +1287,83 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // Local
+
+    pub(crate) fn local_lookup(
+        &mut self,
+        is_write: Expression<F>,
+        frame_id: Expression<F>,
+        index: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "Local lookup",
+            is_write,
+            RwTableTag::Local,
+            RwValues::new(
+                frame_id,
+                index,
+                0.expr(),
+                0.expr(),
+                value,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    // DataDrop
+
+    pub(crate) fn data_drop_lookup(
+        &mut self,
+        is_write: Expression<F>,
+        data_index: Expression<F>,
+        is_dropped: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "DataDrop lookup",
+            is_write,
+            RwTableTag::DataDrop,
+            RwValues::new(
+                self.curr.state.call_id.expr(),
+                data_index,
+                0.expr(),
+                0.expr(),
+                is_dropped,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    // ControlFrame
+
+    pub(crate) fn control_frame_lookup(
+        &mut self,
+        is_write: Expression<F>,
+        frame_id: Expression<F>,
+        pc: Expression<F>,
+        stack_pointer: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "ControlFrame lookup",
+            is_write,
+            RwTableTag::ControlFrame,
+            RwValues::new(
+                frame_id,
+                pc,
+                0.expr(),
+                0.expr(),
+                stack_pointer,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
     // Stack
 
     pub(crate) fn stack_pop(&mut self, value: Expression<F>) {
@@ -1409,6 +1557,38 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         length: Expression<F>,
         rlc_acc: Expression<F>,
         rwc_inc: Expression<F>,
+    ) {
+        self.copy_table_lookup_with_direction(
+            src_id,
+            src_tag,
+            dst_id,
+            dst_tag,
+            src_addr,
+            src_addr_end,
+            dst_addr,
+            length,
+            rlc_acc,
+            rwc_inc,
+            0.expr(),
+        );
+    }
+
+    /// Copy lookup whose source and destination addresses are the first
+    /// copied ones, the highest of the ranges when `is_reverse`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn copy_table_lookup_with_direction(
+        &mut self,
+        src_id: Expression<F>,
+        src_tag: Expression<F>,
+        dst_id: Expression<F>,
+        dst_tag: Expression<F>,
+        src_addr: Expression<F>,
+        src_addr_end: Expression<F>,
+        dst_addr: Expression<F>,
+        length: Expression<F>,
+        rlc_acc: Expression<F>,
+        rwc_inc: Expression<F>,
+        is_reverse: Expression<F>,
     ) {
         self.add_lookup(
             "copy lookup",
@@ -1425,6 +1605,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
                 rlc_acc,
                 rw_counter: self.curr.state.rw_counter.expr() + self.rw_counter_offset(),
                 rwc_inc: rwc_inc.clone(),
+                is_reverse,
             },
         );
         self.rw_counter_offset = self.rw_counter_offset.clone() + self.condition_expr() * rwc_inc;
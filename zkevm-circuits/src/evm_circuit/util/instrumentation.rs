@@ -103,6 +103,15 @@ impl Instrument {
                     CellType::Lookup(Table::Exp) => {
                         report.exp_table = data_entry;
                     }
+                    CellType::Lookup(Table::WasmFunction) => {
+                        report.wasm_function_table = data_entry;
+                    }
+                    CellType::Lookup(Table::WasmBytecode) => {
+                        report.wasm_bytecode_table = data_entry;
+                    }
+                    CellType::Lookup(Table::WasmModule) => {
+                        report.wasm_module_table = data_entry;
+                    }
                 }
             }
             report_collection.push(report);
@@ -129,6 +138,9 @@ pub(crate) struct ExecStateReport {
     pub(crate) copy_table: StateReportRow,
     pub(crate) keccak_table: StateReportRow,
     pub(crate) exp_table: StateReportRow,
+    pub(crate) wasm_function_table: StateReportRow,
+    pub(crate) wasm_bytecode_table: StateReportRow,
+    pub(crate) wasm_module_table: StateReportRow,
 }
 
 impl From<ExecutionState> for ExecStateReport {
@@ -327,6 +327,11 @@ impl<F: Field, const IS_CREATE2: bool> ContractCreateGadget<F, IS_CREATE2> {
         )
     }
 
+    /// Salt bytes in little-endian order.
+    pub(crate) fn salt(&self) -> &[Cell<F>; N_BYTES_WORD] {
+        &self.salt
+    }
+
     /// Salt EVM word RLC.
     pub(crate) fn salt_word_rlc(&self, cb: &EVMConstraintBuilder<F>) -> Expression<F> {
         cb.word_rlc::<N_BYTES_WORD>(
@@ -512,6 +512,17 @@ impl<F: Field, const N: usize, const N_BYTES_MEMORY_WORD_SIZE: usize>
     pub(crate) fn construct(
         cb: &mut EVMConstraintBuilder<F>,
         addresses: [Expression<F>; N],
+    ) -> Self {
+        let curr_memory_word_size = cb.curr.state.memory_word_size.expr();
+        Self::construct_with_memory_word_size(cb, curr_memory_word_size, addresses)
+    }
+
+    /// Same as `construct`, but expands the memory from the given size instead
+    /// of the memory size of the step state.
+    pub(crate) fn construct_with_memory_word_size(
+        cb: &mut EVMConstraintBuilder<F>,
+        curr_memory_word_size: Expression<F>,
+        addresses: [Expression<F>; N],
     ) -> Self {
         // Calculate the memory size of the memory access
         // `address_memory_word_size < 256**MAX_MEMORY_SIZE_IN_BYTES`
@@ -521,7 +532,6 @@ impl<F: Field, const N: usize, const N_BYTES_MEMORY_WORD_SIZE: usize>
         // The memory size needs to be updated if this memory access
         // requires expanding the memory.
         // `next_memory_word_size < 256**MAX_MEMORY_SIZE_IN_BYTES`
-        let curr_memory_word_size = cb.curr.state.memory_word_size.expr();
         let mut next_memory_word_size = curr_memory_word_size.clone();
         let max_memory_word_sizes = array_init(|idx| {
             let max_memory_word_size = MinMaxGadget::construct(
@@ -51,14 +51,21 @@ mod error_oog_dynamic_memory;
 mod error_oog_memory_copy;
 mod error_oog_sha3;
 mod error_precompile_failed;
+mod precompiles;
 
 mod common_begin_tx;
 mod common_block_ctx;
+#[allow(dead_code)]
 mod common_dummy;
+mod common_forbidden;
 mod common_end_block;
 mod common_end_tx;
+mod error_call_stack_exhausted;
+mod error_integer_division;
+mod error_invalid_indirect_call;
 mod error_invalid_jump;
 mod error_invalid_opcode;
+mod error_memory_out_of_bounds;
 mod error_oog_call;
 mod error_oog_constant;
 mod error_oog_exp;
@@ -67,6 +74,7 @@ mod error_oog_sload_sstore;
 mod error_oog_static_memory;
 mod error_return_data_oo_bound;
 mod error_stack;
+mod error_unreachable;
 mod error_write_protection;
 mod evm_address;
 mod evm_balance;
@@ -80,6 +88,7 @@ mod evm_callvalue;
 mod evm_chainid;
 mod evm_codecopy;
 mod evm_codesize;
+mod evm_create;
 mod evm_extcodecopy;
 mod evm_extcodehash;
 mod evm_extcodesize;
@@ -98,36 +107,49 @@ mod evm_sload;
 mod evm_sstore;
 mod evm_stop;
 mod wasm_bin;
+mod wasm_block;
 mod wasm_break;
+mod wasm_bulk_memory;
 mod wasm_call;
 mod wasm_const;
 mod wasm_conversion;
 mod wasm_drop;
 mod wasm_end;
 mod wasm_global;
-// mod wasm_load;
+mod wasm_load;
 mod wasm_local;
+mod wasm_memory_grow;
 mod wasm_rel;
+mod wasm_return;
 mod wasm_select;
-// mod wasm_store;
+mod wasm_store;
 mod wasm_test;
 mod wasm_unary;
 
 use common_begin_tx::CommonBeginTxGadget;
-// use common_block_ctx::CommonBlockCtxGadget;
-use common_dummy::CommonDummyGadget;
+use common_block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget};
+use common_forbidden::CommonForbiddenGadget;
 use common_end_block::CommonEndBlockGadget;
 use common_end_tx::CommonEndTxGadget;
+use error_integer_division::ErrorIntegerDivisionGadget;
+use error_call_stack_exhausted::ErrorCallStackExhaustedGadget;
+use error_invalid_indirect_call::ErrorInvalidIndirectCallGadget;
 use error_invalid_jump::ErrorInvalidJumpGadget;
 use error_invalid_opcode::ErrorInvalidOpcodeGadget;
+use error_memory_out_of_bounds::ErrorMemoryOutOfBoundsGadget;
 use error_oog_call::ErrorOOGCallGadget;
 use error_oog_constant::ErrorOOGConstantGadget;
+use error_oog_create2::ErrorOOGCreateGadget;
+use error_oog_dynamic_memory::ErrorOOGDynamicMemoryGadget;
 use error_oog_exp::ErrorOOGExpGadget;
 use error_oog_log::ErrorOOGLogGadget;
+use error_oog_memory_copy::ErrorOOGMemoryCopyGadget;
+use error_oog_sha3::ErrorOOGSha3Gadget;
 use error_oog_sload_sstore::ErrorOOGSloadSstoreGadget;
-// use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
+use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
 use error_return_data_oo_bound::ErrorReturnDataOutOfBoundGadget;
 use error_stack::ErrorStackGadget;
+use error_unreachable::ErrorUnreachableGadget;
 use error_write_protection::ErrorWriteProtectionGadget;
 use evm_address::EvmAddressGadget;
 use evm_balance::EvmBalanceGadget;
@@ -141,6 +163,7 @@ use evm_callvalue::EvmCallValueGadget;
 use evm_chainid::EvmChainIdGadget;
 use evm_codecopy::EvmCodeCopyGadget;
 use evm_codesize::EvmCodeSizeGadget;
+use evm_create::CreateGadget;
 use evm_extcodecopy::EvmExtCodeCopyGadget;
 use evm_extcodehash::EvmExtCodeHashGadget;
 use evm_extcodesize::EvmExtCodeSizeGadget;
@@ -159,18 +182,22 @@ use evm_sload::EvmSloadGadget;
 use evm_sstore::EvmSstoreGadget;
 use evm_stop::EvmStopGadget;
 use wasm_bin::WasmBinGadget;
+use wasm_block::WasmBlockGadget;
 use wasm_break::WasmBreakGadget;
+use wasm_bulk_memory::WasmBulkMemoryGadget;
 use wasm_call::WasmCallGadget;
 use wasm_const::WasmConstGadget;
 use wasm_conversion::WasmConversionGadget;
 use wasm_drop::WasmDropGadget;
 use wasm_end::WasmEndGadget;
 use wasm_global::WasmGlobalGadget;
-// use wasm_load::WasmLoadGadget;
+use wasm_load::WasmLoadGadget;
 use wasm_local::WasmLocalGadget;
+use wasm_memory_grow::WasmMemoryGrowGadget;
 use wasm_rel::WasmRelGadget;
+use wasm_return::WasmReturnGadget;
 use wasm_select::WasmSelectGadget;
-// use wasm_store::WasmStoreGadget;
+use wasm_store::WasmStoreGadget;
 use wasm_test::WasmTestGadget;
 use wasm_unary::WasmUnaryGadget;
 use crate::evm_circuit::EvmCircuitExports;
@@ -178,6 +205,7 @@ use crate::evm_circuit::wasm::end_inner_block::EndInnerBlockGadget;
 use crate::evm_circuit::wasm::error_code_store::ErrorCodeStoreGadget;
 use crate::evm_circuit::wasm::error_invalid_creation_code::ErrorInvalidCreationCodeGadget;
 use crate::evm_circuit::wasm::error_precompile_failed::ErrorPrecompileFailedGadget;
+use crate::evm_circuit::wasm::precompiles::IdentityGadget;
 use crate::evm_circuit::wasm::error_oog_account_access::ErrorOOGAccountAccessGadget;
 use crate::table::{RwTableTag, TxReceiptFieldTag};
 
@@ -231,7 +259,9 @@ pub(crate) struct ExecutionConfig<F> {
 
     // Common Gadgets
     common_begin_tx: Box<CommonBeginTxGadget<F>>,
-    // common_block_ctx: Box<CommonBlockCtxGadget<F>>,
+    block_ctx_u64_gadget: Box<BlockCtxU64Gadget<F>>,
+    block_ctx_u160_gadget: Box<BlockCtxU160Gadget<F>>,
+    block_ctx_u256_gadget: Box<BlockCtxU256Gadget<F>>,
     // common_dummy: Box<CommonDummyGadget<F>>,
     common_end_block: Box<CommonEndBlockGadget<F>>,
     common_end_inner_block: Box<EndInnerBlockGadget<F>>,
@@ -241,25 +271,33 @@ pub(crate) struct ExecutionConfig<F> {
     error_oog_call: Box<ErrorOOGCallGadget<F>>,
     error_oog_constant: Box<ErrorOOGConstantGadget<F>>,
     error_oog_exp: Box<ErrorOOGExpGadget<F>>,
-    error_oog_memory_copy: Box<CommonDummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasMemoryCopy }>>,
+    error_oog_memory_copy: Box<ErrorOOGMemoryCopyGadget<F>>,
     error_oog_sload_sstore: Box<ErrorOOGSloadSstoreGadget<F>>,
-    error_oog_static_memory_gadget: Box<CommonDummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasStaticMemoryExpansion }>>,
+    error_oog_static_memory_gadget: Box<ErrorOOGStaticMemoryGadget<F>>,
     error_stack: Box<ErrorStackGadget<F>>,
     error_write_protection: Box<ErrorWriteProtectionGadget<F>>,
-    error_oog_dynamic_memory_gadget: Box<CommonDummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasDynamicMemoryExpansion }>>,
+    error_oog_dynamic_memory_gadget: Box<ErrorOOGDynamicMemoryGadget<F>>,
     error_oog_log: Box<ErrorOOGLogGadget<F>>,
     error_oog_account_access: Box<ErrorOOGAccountAccessGadget<F>>,
-    error_oog_sha3: Box<CommonDummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSHA3 }>>,
-    error_oog_create2: Box<CommonDummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasCREATE }>>,
+    error_oog_sha3: Box<ErrorOOGSha3Gadget<F>>,
+    error_oog_create2: Box<ErrorOOGCreateGadget<F>>,
     error_code_store: Box<ErrorCodeStoreGadget<F>>,
-    #[cfg(not(feature = "scroll"))]
-    error_oog_self_destruct: Box<CommonDummyGadget<F, 0, 0, { ExecutionState::ErrorOutOfGasSELFDESTRUCT }>>,
+    error_oog_self_destruct:
+        Box<CommonForbiddenGadget<F, { ExecutionState::ErrorOutOfGasSELFDESTRUCT }>>,
     error_invalid_jump: Box<ErrorInvalidJumpGadget<F>>,
+    error_invalid_indirect_call: Box<ErrorInvalidIndirectCallGadget<F>>,
+    error_call_stack_exhausted: Box<ErrorCallStackExhaustedGadget<F>>,
+    error_unreachable: Box<ErrorUnreachableGadget<F>>,
+    error_integer_division: Box<ErrorIntegerDivisionGadget<F>>,
+    error_memory_out_of_bounds: Box<ErrorMemoryOutOfBoundsGadget<F>>,
     error_invalid_opcode: Box<ErrorInvalidOpcodeGadget<F>>,
     error_invalid_creation_code: Box<ErrorInvalidCreationCodeGadget<F>>,
     error_precompile_failed: Box<ErrorPrecompileFailedGadget<F>>,
     error_return_data_out_of_bound: Box<ErrorReturnDataOutOfBoundGadget<F>>,
 
+    // precompile calls
+    precompile_identity_gadget: Box<IdentityGadget<F>>,
+
     // EVM Gadgets
     evm_address: Box<EvmAddressGadget<F>>,
     evm_balance: Box<EvmBalanceGadget<F>>,
@@ -268,11 +306,13 @@ pub(crate) struct ExecutionConfig<F> {
     evm_calldataload: Box<EvmCallDataLoadGadget<F>>,
     evm_calldatasize: Box<EvmCallDataSizeGadget<F>>,
     evm_caller: Box<EvmCallerGadget<F>>,
-    evm_callop: Box<CommonDummyGadget<F, 0, 0, { ExecutionState::CALL_OP }>>,
+    evm_callop: Box<EvmCallOpGadget<F>>,
     evm_callvalue: Box<EvmCallValueGadget<F>>,
     evm_chainid: Box<EvmChainIdGadget<F>>,
     evm_codecopy: Box<EvmCodeCopyGadget<F>>,
     evm_codesize: Box<EvmCodeSizeGadget<F>>,
+    evm_create: Box<CreateGadget<F, false, { ExecutionState::CREATE }>>,
+    evm_create2: Box<CreateGadget<F, true, { ExecutionState::CREATE2 }>>,
     evm_extcodecopy: Box<EvmExtCodeCopyGadget<F>>,
     evm_extcodehash: Box<EvmExtCodeHashGadget<F>>,
     evm_extcodesize: Box<EvmExtCodeSizeGadget<F>>,
@@ -287,24 +327,29 @@ pub(crate) struct ExecutionConfig<F> {
     evm_returndatacopy: Box<EvmReturnDataCopyGadget<F>>,
     evm_returndatasize: Box<EvmReturnDataSizeGadget<F>>,
     evm_selfbalance: Box<EvmSelfBalanceGadget<F>>,
+    evm_selfdestruct: Box<CommonForbiddenGadget<F, { ExecutionState::SELFDESTRUCT }>>,
     evm_sload: Box<EvmSloadGadget<F>>,
     evm_sstore: Box<EvmSstoreGadget<F>>,
     evm_stop: Box<EvmStopGadget<F>>,
 
     // WASM Gadgets
     wasm_bin: Box<WasmBinGadget<F>>,
+    wasm_block: Box<WasmBlockGadget<F>>,
     wasm_break: Box<WasmBreakGadget<F>>,
+    wasm_bulk_memory: Box<WasmBulkMemoryGadget<F>>,
     wasm_call: Box<WasmCallGadget<F>>,
     wasm_const: Box<WasmConstGadget<F>>,
     wasm_conversion: Box<WasmConversionGadget<F>>,
     wasm_drop: Box<WasmDropGadget<F>>,
     wasm_end: Box<WasmEndGadget<F>>,
     wasm_global: Box<WasmGlobalGadget<F>>,
-    // wasm_load: Box<WasmLoadGadget<F>>,
+    wasm_load: Box<WasmLoadGadget<F>>,
     wasm_local: Box<WasmLocalGadget<F>>,
+    wasm_memory_grow: Box<WasmMemoryGrowGadget<F>>,
     wasm_rel: Box<WasmRelGadget<F>>,
+    wasm_return: Box<WasmReturnGadget<F>>,
     wasm_select: Box<WasmSelectGadget<F>>,
-    // wasm_store: Box<WasmStoreGadget<F>>,
+    wasm_store: Box<WasmStoreGadget<F>>,
     wasm_test: Box<WasmTestGadget<F>>,
     wasm_unary: Box<WasmUnaryGadget<F>>,
 }
@@ -324,6 +369,9 @@ impl<F: Field> ExecutionConfig<F> {
         copy_table: &dyn LookupTable<F>,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        wasm_function_table: &dyn LookupTable<F>,
+        wasm_bytecode_table: &dyn LookupTable<F>,
+        wasm_module_table: &dyn LookupTable<F>,
     ) -> Self {
         let mut instrument = Instrument::default();
         let q_usable = meta.complex_selector();
@@ -484,7 +532,9 @@ impl<F: Field> ExecutionConfig<F> {
             advices,
 
             common_begin_tx: configure_gadget!(),
-            // common_block_ctx: configure_gadget!(),
+            block_ctx_u64_gadget: configure_gadget!(),
+            block_ctx_u160_gadget: configure_gadget!(),
+            block_ctx_u256_gadget: configure_gadget!(),
             // common_dummy: configure_gadget!(),
             common_end_block: configure_gadget!(),
             common_end_inner_block: configure_gadget!(),
@@ -501,15 +551,21 @@ impl<F: Field> ExecutionConfig<F> {
             error_oog_sha3: configure_gadget!(),
             error_oog_exp: configure_gadget!(),
             error_oog_create2: configure_gadget!(),
-            #[cfg(not(feature = "scroll"))]
             error_oog_self_destruct: configure_gadget!(),
             error_code_store: configure_gadget!(),
             error_invalid_jump: configure_gadget!(),
+            error_invalid_indirect_call: configure_gadget!(),
+            error_call_stack_exhausted: configure_gadget!(),
+            error_unreachable: configure_gadget!(),
+            error_integer_division: configure_gadget!(),
+            error_memory_out_of_bounds: configure_gadget!(),
             error_invalid_opcode: configure_gadget!(),
             error_write_protection: configure_gadget!(),
             error_invalid_creation_code: configure_gadget!(),
             error_return_data_out_of_bound: configure_gadget!(),
             error_precompile_failed: configure_gadget!(),
+            // precompile calls
+            precompile_identity_gadget: configure_gadget!(),
             evm_address: configure_gadget!(),
             evm_balance: configure_gadget!(),
             evm_blockhash: configure_gadget!(),
@@ -522,6 +578,8 @@ impl<F: Field> ExecutionConfig<F> {
             evm_chainid: configure_gadget!(),
             evm_codecopy: configure_gadget!(),
             evm_codesize: configure_gadget!(),
+            evm_create: configure_gadget!(),
+            evm_create2: configure_gadget!(),
             evm_extcodecopy: configure_gadget!(),
             evm_extcodehash: configure_gadget!(),
             evm_extcodesize: configure_gadget!(),
@@ -536,22 +594,27 @@ impl<F: Field> ExecutionConfig<F> {
             evm_returndatacopy: configure_gadget!(),
             evm_returndatasize: configure_gadget!(),
             evm_selfbalance: configure_gadget!(),
+            evm_selfdestruct: configure_gadget!(),
             evm_sload: configure_gadget!(),
             evm_sstore: configure_gadget!(),
             evm_stop: configure_gadget!(),
             wasm_bin: configure_gadget!(),
+            wasm_block: configure_gadget!(),
             wasm_break: configure_gadget!(),
+            wasm_bulk_memory: configure_gadget!(),
             wasm_call: configure_gadget!(),
             wasm_const: configure_gadget!(),
             wasm_conversion: configure_gadget!(),
             wasm_drop: configure_gadget!(),
             wasm_end: configure_gadget!(),
             wasm_global: configure_gadget!(),
-            // wasm_load: configure_gadget!(),
+            wasm_load: configure_gadget!(),
             wasm_local: configure_gadget!(),
+            wasm_memory_grow: configure_gadget!(),
             wasm_rel: configure_gadget!(),
+            wasm_return: configure_gadget!(),
             wasm_select: configure_gadget!(),
-            // wasm_store: configure_gadget!(),
+            wasm_store: configure_gadget!(),
             wasm_test: configure_gadget!(),
             wasm_unary: configure_gadget!(),
 
@@ -573,6 +636,9 @@ impl<F: Field> ExecutionConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            wasm_function_table,
+            wasm_bytecode_table,
+            wasm_module_table,
             &challenges,
             &cell_manager,
         );
@@ -828,6 +894,9 @@ impl<F: Field> ExecutionConfig<F> {
         copy_table: &dyn LookupTable<F>,
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
+        wasm_function_table: &dyn LookupTable<F>,
+        wasm_bytecode_table: &dyn LookupTable<F>,
+        wasm_module_table: &dyn LookupTable<F>,
         challenges: &Challenges<Expression<F>>,
         cell_manager: &CellManager<F>,
     ) {
@@ -844,6 +913,9 @@ impl<F: Field> ExecutionConfig<F> {
                         Table::Copy => copy_table,
                         Table::Keccak => keccak_table,
                         Table::Exp => exp_table,
+                        Table::WasmFunction => wasm_function_table,
+                        Table::WasmBytecode => wasm_bytecode_table,
+                        Table::WasmModule => wasm_module_table,
                     }
                     .table_exprs(meta);
                     vec![(
@@ -1289,17 +1361,23 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::EndTx => assign_exec_step!(self.common_end_tx),
             // WASM opcodes
             ExecutionState::WASM_BIN => assign_exec_step!(self.wasm_bin),
+            ExecutionState::WASM_BLOCK => assign_exec_step!(self.wasm_block),
             ExecutionState::WASM_TEST => assign_exec_step!(self.wasm_test),
             ExecutionState::WASM_CONST => assign_exec_step!(self.wasm_const),
             ExecutionState::WASM_DROP => assign_exec_step!(self.wasm_drop),
             ExecutionState::WASM_GLOBAL => assign_exec_step!(self.wasm_global),
             ExecutionState::WASM_LOCAL => assign_exec_step!(self.wasm_local),
+            ExecutionState::WASM_LOAD => assign_exec_step!(self.wasm_load),
+            ExecutionState::WASM_STORE => assign_exec_step!(self.wasm_store),
+            ExecutionState::WASM_MEMORY_GROW => assign_exec_step!(self.wasm_memory_grow),
+            ExecutionState::WASM_BULK_MEMORY => assign_exec_step!(self.wasm_bulk_memory),
             ExecutionState::WASM_UNARY => assign_exec_step!(self.wasm_unary),
             ExecutionState::WASM_CONVERSION => assign_exec_step!(self.wasm_conversion),
             ExecutionState::WASM_REL => assign_exec_step!(self.wasm_rel),
             ExecutionState::WASM_END => assign_exec_step!(self.wasm_end),
             ExecutionState::WASM_BREAK => assign_exec_step!(self.wasm_break),
             ExecutionState::WASM_CALL => assign_exec_step!(self.wasm_call),
+            ExecutionState::WASM_RETURN => assign_exec_step!(self.wasm_return),
             // opcode
             ExecutionState::SHA3 => assign_exec_step!(self.evm_keccak256),
             ExecutionState::ADDRESS => assign_exec_step!(self.evm_address),
@@ -1327,16 +1405,16 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::RETURN_REVERT => assign_exec_step!(self.evm_return_revert),
             // ExecutionState::RETURNDATASIZE => assign_exec_step!(self.returndatasize_gadget),
             // ExecutionState::RETURNDATACOPY => assign_exec_step!(self.returndatacopy_gadget),
-            // ExecutionState::BLOCKCTXU64 => assign_exec_step!(self.block_ctx_u64_gadget),
-            // ExecutionState::BLOCKCTXU160 => assign_exec_step!(self.block_ctx_u160_gadget),
-            // ExecutionState::BLOCKCTXU256 => assign_exec_step!(self.block_ctx_u256_gadget),
+            ExecutionState::BLOCKCTXU64 => assign_exec_step!(self.block_ctx_u64_gadget),
+            ExecutionState::BLOCKCTXU160 => assign_exec_step!(self.block_ctx_u160_gadget),
+            ExecutionState::BLOCKCTXU256 => assign_exec_step!(self.block_ctx_u256_gadget),
             ExecutionState::BLOCKHASH => assign_exec_step!(self.evm_blockhash),
             ExecutionState::SELFBALANCE => assign_exec_step!(self.evm_selfbalance),
             // dummy gadgets
             ExecutionState::EXTCODECOPY => assign_exec_step!(self.evm_extcodecopy),
-            // ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
-            // ExecutionState::CREATE2 => assign_exec_step!(self.create2_gadget),
-            // ExecutionState::SELFDESTRUCT => assign_exec_step!(self.selfdestruct_gadget),
+            ExecutionState::CREATE => assign_exec_step!(self.evm_create),
+            ExecutionState::CREATE2 => assign_exec_step!(self.evm_create2),
+            ExecutionState::SELFDESTRUCT => assign_exec_step!(self.evm_selfdestruct),
             // end of dummy gadgets
             // ExecutionState::SHA3 => assign_exec_step!(self.sha3_gadget),
             // ExecutionState::SHL_SHR => assign_exec_step!(self.shl_shr_gadget),
@@ -1380,7 +1458,6 @@ impl<F: Field> ExecutionConfig<F> {
                 assign_exec_step!(self.error_oog_create2)
             }
             ExecutionState::ErrorOutOfGasSELFDESTRUCT => {
-                #[cfg(not(feature = "scroll"))]
                 assign_exec_step!(self.error_oog_self_destruct)
             }
             ExecutionState::ErrorCodeStore => {
@@ -1392,6 +1469,21 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorInvalidJump => {
                 assign_exec_step!(self.error_invalid_jump)
             }
+            ExecutionState::ErrorInvalidIndirectCall => {
+                assign_exec_step!(self.error_invalid_indirect_call)
+            }
+            ExecutionState::ErrorCallStackExhausted => {
+                assign_exec_step!(self.error_call_stack_exhausted)
+            }
+            ExecutionState::ErrorUnreachable => {
+                assign_exec_step!(self.error_unreachable)
+            }
+            ExecutionState::ErrorIntegerDivision => {
+                assign_exec_step!(self.error_integer_division)
+            }
+            ExecutionState::ErrorMemoryOutOfBounds => {
+                assign_exec_step!(self.error_memory_out_of_bounds)
+            }
             ExecutionState::ErrorInvalidOpcode => {
                 assign_exec_step!(self.error_invalid_opcode)
             }
@@ -1407,6 +1499,10 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorPrecompileFailed => {
                 assign_exec_step!(self.error_precompile_failed)
             }
+            // precompile calls
+            ExecutionState::PrecompileIdentity => {
+                assign_exec_step!(self.precompile_identity_gadget)
+            }
 
             _ => evm_unimplemented!("unimplemented ExecutionState: {:?}", step.execution_state),
         }
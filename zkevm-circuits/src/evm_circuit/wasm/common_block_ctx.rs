@@ -6,7 +6,7 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
-            from_bytes, CachedRegion, RandomLinearCombination,
+            from_bytes, CachedRegion, Cell, RandomLinearCombination,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -14,21 +14,25 @@ use crate::{
     util::Expr,
 };
 use bus_mapping::evm::OpcodeId;
-use eth_types::{Field, ToLittleEndian};
-use halo2_proofs::plonk::Error;
+use eth_types::Field;
+use halo2_proofs::{circuit::Value, plonk::Error};
 
+/// Block context host functions write the field of the current inner block
+/// to linear memory at the destination pointer, `N_BYTES` wide and
+/// big-endian.
 #[derive(Clone, Debug)]
 pub(crate) struct CommonBlockCtxGadget<F, const N_BYTES: usize> {
     same_context: SameContextGadget<F>,
+    dest_offset: Cell<F>,
     value: RandomLinearCombination<F, N_BYTES>,
 }
 
 impl<F: Field, const N_BYTES: usize> CommonBlockCtxGadget<F, N_BYTES> {
     fn construct(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let value = cb.query_word_rlc();
+        let dest_offset = cb.query_cell();
 
-        // Push the const generic parameter N_BYTES value to the stack
-        cb.stack_push(value.expr());
+        cb.stack_pop(dest_offset.expr());
 
         // Get op's FieldTag
         let opcode = cb.query_cell();
@@ -43,12 +47,13 @@ impl<F: Field, const N_BYTES: usize> CommonBlockCtxGadget<F, N_BYTES> {
             from_bytes::expr(&value.cells)
         };
         cb.block_lookup(blockctx_tag, cb.curr.state.block_number.expr(), value_expr);
+        cb.memory_rlc_lookup(1.expr(), &dest_offset, &value);
 
         // State transition
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(1.expr()),
+            rw_counter: Delta((1 + N_BYTES).expr()),
             program_counter: Delta(1.expr()),
-            stack_pointer: Delta((-1).expr()),
+            stack_pointer: Delta(1.expr()),
             gas_left: Delta(-OpcodeId::TIMESTAMP.constant_gas_cost().expr()),
             ..Default::default()
         };
@@ -56,9 +61,33 @@ impl<F: Field, const N_BYTES: usize> CommonBlockCtxGadget<F, N_BYTES> {
 
         Self {
             same_context,
+            dest_offset,
             value,
         }
     }
+
+    fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let dest_offset = block.rws[step.rw_indices[0]].stack_value();
+        self.dest_offset
+            .assign(region, offset, Value::known(F::from(dest_offset.as_u64())))?;
+
+        // memory holds the value big-endian, the cells are little-endian
+        let mut value = [0u8; N_BYTES];
+        for (i, byte) in value.iter_mut().rev().enumerate() {
+            *byte = block.rws[step.rw_indices[1 + i]].memory_value();
+        }
+        self.value.assign(region, offset, Some(value))?;
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -86,19 +115,7 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU64Gadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        self.value_u64
-            .same_context
-            .assign_exec_step(region, offset, step)?;
-
-        let value = block.rws[step.rw_indices[0]].stack_value();
-
-        self.value_u64.value.assign(
-            region,
-            offset,
-            Some(u64::try_from(value).unwrap().to_le_bytes()),
-        )?;
-
-        Ok(())
+        self.value_u64.assign(region, offset, block, step)
     }
 }
 
@@ -127,23 +144,7 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU160Gadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        self.value_u160
-            .same_context
-            .assign_exec_step(region, offset, step)?;
-
-        let value = block.rws[step.rw_indices[0]].stack_value();
-
-        self.value_u160.value.assign(
-            region,
-            offset,
-            Some(
-                value.to_le_bytes()[..N_BYTES_ACCOUNT_ADDRESS]
-                    .try_into()
-                    .unwrap(),
-            ),
-        )?;
-
-        Ok(())
+        self.value_u160.assign(region, offset, block, step)
     }
 }
 
@@ -172,17 +173,7 @@ impl<F: Field> ExecutionGadget<F> for BlockCtxU256Gadget<F> {
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        self.value_u256
-            .same_context
-            .assign_exec_step(region, offset, step)?;
-
-        let value = block.rws[step.rw_indices[0]].stack_value();
-
-        self.value_u256
-            .value
-            .assign(region, offset, Some(value.to_le_bytes()))?;
-
-        Ok(())
+        self.value_u256.assign(region, offset, block, step)
     }
 }
 
@@ -202,10 +193,11 @@ mod test {
     #[test]
     fn blockcxt_u64_gadget_test() {
         let bytecode = bytecode! {
+            I32Const[0x00]
             TIMESTAMP
-            POP
+            I32Const[0x10]
             NUMBER
-            POP
+            I32Const[0x20]
             GASLIMIT
             STOP
         };
@@ -214,6 +206,7 @@ mod test {
     #[test]
     fn blockcxt_u160_gadget_test() {
         let bytecode = bytecode! {
+            I32Const[0x7f]
             COINBASE
             STOP
         };
@@ -223,8 +216,9 @@ mod test {
     #[test]
     fn blockcxt_u256_gadget_test() {
         let bytecode = bytecode! {
+            I32Const[0x00]
             DIFFICULTY
-            POP
+            I32Const[0x20]
             BASEFEE
             STOP
         };
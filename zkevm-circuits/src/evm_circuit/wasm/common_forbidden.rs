@@ -0,0 +1,51 @@
+use std::marker::PhantomData;
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            CachedRegion,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use eth_types::Field;
+use halo2_proofs::plonk::Error;
+
+/// Gadget for the execution states no wasm trace can reach, such as the ones of SELFDESTRUCT
+/// which the host import allow-list rejects. Its constraint can't be satisfied, so the state
+/// can't be selected by a prover either.
+#[derive(Clone, Debug)]
+pub(crate) struct CommonForbiddenGadget<F, const S: ExecutionState> {
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field, const S: ExecutionState> ExecutionGadget<F> for CommonForbiddenGadget<F, S> {
+    const NAME: &'static str = "FORBIDDEN";
+
+    const EXECUTION_STATE: ExecutionState = S;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        cb.require_zero("execution state is forbidden", 1.expr());
+
+        Self {
+            _marker: PhantomData,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        _: &mut CachedRegion<'_, '_, F>,
+        _: usize,
+        _: &Block<F>,
+        _: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        log::error!("forbidden execution state {:?} in {:?}", S, step);
+        Err(Error::Synthesis)
+    }
+}
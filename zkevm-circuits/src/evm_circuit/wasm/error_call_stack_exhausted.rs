@@ -0,0 +1,119 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget, constraint_builder::ConstrainBuilderCommon,
+        CachedRegion, Cell,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use crate::table::CallContextFieldTag;
+use eth_types::{
+    evm_types::{OpcodeId, MAX_WASM_CALL_DEPTH},
+    Field,
+};
+use gadgets::util::Expr;
+use halo2_proofs::{circuit::Value, plonk::Error};
+use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+
+/// `call` and `call_indirect` trap when `MAX_WASM_CALL_DEPTH` function frames
+/// are already nested. A successful call never goes beyond it, so the depth
+/// is exactly the maximum here.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorCallStackExhaustedGadget<F> {
+    opcode: Cell<F>,
+    is_call_indirect: Cell<F>,
+    elem_index: Cell<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorCallStackExhaustedGadget<F> {
+    const NAME: &'static str = "ErrorCallStackExhausted";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorCallStackExhausted;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let is_call_indirect = cb.query_bool();
+        cb.require_equal(
+            "ErrorCallStackExhausted only happens in CALL or CALL_INDIRECT",
+            opcode.expr(),
+            OpcodeId::Call.expr() + is_call_indirect.expr(),
+        );
+
+        let elem_index = cb.query_cell();
+        cb.condition(is_call_indirect.expr(), |cb| {
+            cb.stack_pop(elem_index.expr());
+        });
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::FunctionDepth,
+            MAX_WASM_CALL_DEPTH.expr(),
+        );
+
+        let common_error_gadget =
+            CommonErrorGadget::construct(cb, opcode.expr(), 3.expr() + is_call_indirect.expr());
+
+        Self {
+            opcode,
+            is_call_indirect,
+            elem_index,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let is_call_indirect = opcode == OpcodeId::CallIndirect;
+        self.is_call_indirect
+            .assign(region, offset, Value::known(F::from(is_call_indirect as u64)))?;
+        let elem_index = if is_call_indirect {
+            block.rws[step.rw_indices[0]].stack_value().as_u64()
+        } else {
+            0
+        };
+        self.elem_index
+            .assign(region, offset, Value::known(F::from(elem_index)))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3 + is_call_indirect as usize)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eth_types::{bytecode, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_unbounded_recursion() {
+        let mut code = bytecode! {
+            Call[0]
+        };
+        code.new_function(vec![], vec![], bytecode! {
+            Call[0]
+        }, vec![]);
+        run_test(code);
+    }
+}
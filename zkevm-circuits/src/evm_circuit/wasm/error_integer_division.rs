@@ -0,0 +1,187 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    table::{FixedTableTag, Lookup},
+    util::{
+        common_gadget::CommonErrorGadget,
+        constraint_builder::ConstrainBuilderCommon,
+        math_gadget::{IsEqualGadget, IsZeroGadget},
+        CachedRegion, Cell,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use eth_types::{evm_types::OpcodeId, Field};
+use gadgets::util::Expr;
+use halo2_proofs::{circuit::Value, plonk::Error};
+use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+
+/// The integer divisions and remainders trap on a zero divisor, the signed
+/// divisions also trap when the quotient of the minimal integer by -1
+/// overflows.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorIntegerDivisionGadget<F> {
+    opcode: Cell<F>,
+    lhs: Cell<F>,
+    rhs: Cell<F>,
+    is_rhs_zero: IsZeroGadget<F>,
+    is_i32_div_s: IsEqualGadget<F>,
+    is_i64_div_s: IsEqualGadget<F>,
+    is_i32_min: IsEqualGadget<F>,
+    is_i64_min: IsEqualGadget<F>,
+    is_i32_minus_one: IsEqualGadget<F>,
+    is_i64_minus_one: IsEqualGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorIntegerDivisionGadget<F> {
+    const NAME: &'static str = "ErrorIntegerDivision";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorIntegerDivision;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let lhs = cb.query_cell();
+        let rhs = cb.query_cell();
+
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    Self::EXECUTION_STATE.as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
+
+        cb.stack_pop(rhs.expr());
+        cb.stack_pop(lhs.expr());
+
+        let is_rhs_zero = IsZeroGadget::construct(cb, rhs.expr());
+        let is_i32_div_s = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::I32DivS.expr());
+        let is_i64_div_s = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::I64DivS.expr());
+        let is_i32_min = IsEqualGadget::construct(cb, lhs.expr(), (i32::MIN as u32 as u64).expr());
+        let is_i64_min = IsEqualGadget::construct(cb, lhs.expr(), (i64::MIN as u64).expr());
+        let is_i32_minus_one = IsEqualGadget::construct(cb, rhs.expr(), (u32::MAX as u64).expr());
+        let is_i64_minus_one = IsEqualGadget::construct(cb, rhs.expr(), u64::MAX.expr());
+
+        // a zero divisor is never -1, so at most one of the cases holds
+        cb.require_equal(
+            "divisor is zero or the signed quotient overflows",
+            is_rhs_zero.expr()
+                + is_i32_div_s.expr() * is_i32_min.expr() * is_i32_minus_one.expr()
+                + is_i64_div_s.expr() * is_i64_min.expr() * is_i64_minus_one.expr(),
+            1.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 4.expr());
+
+        Self {
+            opcode,
+            lhs,
+            rhs,
+            is_rhs_zero,
+            is_i32_div_s,
+            is_i64_div_s,
+            is_i32_min,
+            is_i64_min,
+            is_i32_minus_one,
+            is_i64_minus_one,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = F::from(step.opcode.unwrap().as_u64());
+        self.opcode.assign(region, offset, Value::known(opcode))?;
+
+        let [rhs, lhs] = [0, 1].map(|i| block.rws[step.rw_indices[i]].stack_value().as_u64());
+        self.lhs.assign(region, offset, Value::known(F::from(lhs)))?;
+        self.rhs.assign(region, offset, Value::known(F::from(rhs)))?;
+
+        self.is_rhs_zero.assign(region, offset, F::from(rhs))?;
+        self.is_i32_div_s
+            .assign(region, offset, opcode, F::from(OpcodeId::I32DivS.as_u64()))?;
+        self.is_i64_div_s
+            .assign(region, offset, opcode, F::from(OpcodeId::I64DivS.as_u64()))?;
+        self.is_i32_min
+            .assign(region, offset, F::from(lhs), F::from(i32::MIN as u32 as u64))?;
+        self.is_i64_min
+            .assign(region, offset, F::from(lhs), F::from(i64::MIN as u64))?;
+        self.is_i32_minus_one
+            .assign(region, offset, F::from(rhs), F::from(u32::MAX as u64))?;
+        self.is_i64_minus_one
+            .assign(region, offset, F::from(rhs), F::from(u64::MAX))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 4)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_divide_by_zero() {
+        for op in [
+            OpcodeId::I32DivS,
+            OpcodeId::I32DivU,
+            OpcodeId::I32RemS,
+            OpcodeId::I32RemU,
+        ] {
+            let mut code = bytecode! {
+                I32Const[7]
+                I32Const[0]
+            };
+            code.write_op(op);
+            run_test(code);
+        }
+        for op in [
+            OpcodeId::I64DivS,
+            OpcodeId::I64DivU,
+            OpcodeId::I64RemS,
+            OpcodeId::I64RemU,
+        ] {
+            let mut code = bytecode! {
+                I64Const[7]
+                I64Const[0]
+            };
+            code.write_op(op);
+            run_test(code);
+        }
+    }
+
+    #[test]
+    fn test_signed_division_overflow() {
+        run_test(bytecode! {
+            I32Const[i32::MIN]
+            I32Const[-1]
+            I32DivS
+        });
+        run_test(bytecode! {
+            I64Const[i64::MIN]
+            I64Const[-1]
+            I64DivS
+        });
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstrainBuilderCommon,
+            math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::WasmFunctionTableTag,
+    util::Expr,
+    wasm_circuit::types::WasmModuleTag,
+};
+use eth_types::{
+    bytecode::{wasm_function_types, wasm_table_elements},
+    evm_types::OpcodeId,
+    Field,
+};
+
+use halo2_proofs::{circuit::Value, plonk::Error};
+use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+
+/// Table slots and table sizes are i32 values.
+const N_BYTES_TABLE_INDEX: usize = 4;
+
+/// `call_indirect` traps when the table slot is out of bounds, holds a null
+/// reference or a function whose type differs from the expected one.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorInvalidIndirectCallGadget<F> {
+    opcode: Cell<F>,
+    type_index: Cell<F>,
+    elem_index: Cell<F>,
+    table_size: Cell<F>,
+    element: Cell<F>,
+    callee_type: Cell<F>,
+    is_in_bounds: LtGadget<F, N_BYTES_TABLE_INDEX>,
+    is_null: IsZeroGadget<F>,
+    is_type_match: IsEqualGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorInvalidIndirectCallGadget<F> {
+    const NAME: &'static str = "ErrorInvalidIndirectCall";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorInvalidIndirectCall;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let type_index = cb.query_cell();
+        let elem_index = cb.query_cell();
+        let table_size = cb.query_cell();
+        let element = cb.query_cell();
+        let callee_type = cb.query_cell();
+
+        cb.require_equal(
+            "ErrorInvalidIndirectCall only happens in CALL_INDIRECT",
+            opcode.expr(),
+            OpcodeId::CallIndirect.expr(),
+        );
+        // the expected type is the immediate of the instruction
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            cb.curr.state.program_counter.expr(),
+            opcode.expr(),
+            type_index.expr(),
+        );
+
+        cb.stack_pop(elem_index.expr());
+
+        cb.wasm_function_table_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmFunctionTableTag::TableSize,
+            0.expr(),
+            table_size.expr(),
+        );
+        let is_in_bounds = LtGadget::construct(cb, elem_index.expr(), table_size.expr());
+
+        cb.condition(is_in_bounds.expr(), |cb| {
+            cb.wasm_function_table_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmFunctionTableTag::Element,
+                elem_index.expr(),
+                element.expr(),
+            );
+        });
+        let is_null = IsZeroGadget::construct(cb, element.expr());
+
+        let is_callable = is_in_bounds.expr() * (1.expr() - is_null.expr());
+        cb.condition(is_callable.clone(), |cb| {
+            cb.wasm_function_table_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmFunctionTableTag::FunctionType,
+                element.expr() - 1.expr(),
+                callee_type.expr(),
+            );
+        });
+        let is_type_match = IsEqualGadget::construct(cb, callee_type.expr(), type_index.expr());
+
+        cb.require_zero(
+            "slot is out of bounds, null or of another type",
+            is_callable * is_type_match.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 3.expr());
+
+        Self {
+            opcode,
+            type_index,
+            elem_index,
+            table_size,
+            element,
+            callee_type,
+            is_in_bounds,
+            is_null,
+            is_type_match,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let type_index = step.params[0];
+        let elem_index = block.rws[step.rw_indices[0]].stack_value().as_u64();
+
+        let code = &block
+            .bytecodes
+            .get(&call.code_hash)
+            .expect("could not find current environment's bytecode")
+            .bytes;
+        let elements = wasm_table_elements(code).unwrap_or_default();
+        let function_types = wasm_function_types(code).unwrap_or_default();
+
+        let table_size = elements.len() as u64;
+        let element = elements
+            .get(elem_index as usize)
+            .copied()
+            .flatten()
+            .map_or(0, |function_index| function_index + 1);
+        let callee_type = if element > 0 {
+            function_types[element as usize - 1]
+        } else {
+            0
+        };
+
+        self.type_index
+            .assign(region, offset, Value::known(F::from(type_index)))?;
+        self.elem_index
+            .assign(region, offset, Value::known(F::from(elem_index)))?;
+        self.table_size
+            .assign(region, offset, Value::known(F::from(table_size)))?;
+        self.element
+            .assign(region, offset, Value::known(F::from(element)))?;
+        self.callee_type
+            .assign(region, offset, Value::known(F::from(callee_type)))?;
+
+        self.is_in_bounds
+            .assign(region, offset, F::from(elem_index), F::from(table_size))?;
+        self.is_null.assign(region, offset, F::from(element))?;
+        self.is_type_match
+            .assign(region, offset, F::from(callee_type), F::from(type_index))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use wasm_encoder::ValType;
+
+    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    fn code_with_table(elem_index: u64) -> Bytecode {
+        let mut code = bytecode! {
+            I32Const[elem_index]
+            CallIndirect[0]
+        };
+        code.new_function(vec![], vec![], bytecode! {
+            Return
+        }, vec![]);
+        code.new_function(vec![ValType::I32], vec![], bytecode! {
+            Return
+        }, vec![]);
+        code.with_function_table(vec![1, 0]);
+        code
+    }
+
+    #[test]
+    fn test_call_indirect_out_of_bounds() {
+        run_test(code_with_table(2));
+    }
+
+    #[test]
+    fn test_call_indirect_type_mismatch() {
+        run_test(code_with_table(0));
+    }
+
+    #[test]
+    fn test_call_indirect_type_other_than_the_immediate_must_fail() {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code_with_table(0)).unwrap(),
+        )
+        .block_modifier(Box::new(|block| {
+            // still another type than the one of the slot, so the trap alone holds
+            let step = block.txs[0]
+                .steps
+                .iter_mut()
+                .find(|step| step.opcode == Some(OpcodeId::CallIndirect))
+                .unwrap();
+            step.params[0] += 2;
+        }))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        .run();
+    }
+}
@@ -0,0 +1,455 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::CommonErrorGadget,
+            constraint_builder::ConstrainBuilderCommon,
+            math_gadget::LtGadget,
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+    wasm_circuit::types::WasmModuleTag,
+};
+use eth_types::{
+    bytecode::{wasm_data_segment, wasm_memory_limits, WASM_PAGE_SIZE},
+    evm_types::OpcodeId,
+    Field,
+};
+
+use halo2_proofs::{circuit::Value, plonk::Error};
+use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+
+/// The address and the memarg offset are decomposed into four bytes each, so
+/// their sum plus the access width always fits five bytes, and so does the
+/// memory size. The same holds for the bulk memory operands and their length.
+const N_BYTES_MEMORY_END: usize = 5;
+
+/// Loads and stores trap when the accessed range ends beyond the current memory
+/// size, the initial pages of the memory section plus the pages grown so far.
+/// Bulk memory instructions trap when the destination range does, or when the
+/// source range ends beyond the memory for `memory.copy` or beyond the data
+/// segment for `memory.init`, a dropped segment being empty. For them the
+/// address is the destination and the immediate is the data index.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorMemoryOutOfBoundsGadget<F> {
+    opcode: Cell<F>,
+    is_store: Cell<F>,
+    widths: [Cell<F>; 4],
+    is_copy: Cell<F>,
+    is_fill: Cell<F>,
+    is_init: Cell<F>,
+    value: Cell<F>,
+    address: Cell<F>,
+    address_bytes: [Cell<F>; 4],
+    memarg_offset: Cell<F>,
+    memarg_offset_bytes: [Cell<F>; 4],
+    length_bytes: [Cell<F>; 4],
+    src_bytes: [Cell<F>; 4],
+    segment_start: Cell<F>,
+    segment_end: Cell<F>,
+    is_dropped: Cell<F>,
+    initial_pages: Cell<F>,
+    grown_pages: Cell<F>,
+    is_out_of_bounds: LtGadget<F, N_BYTES_MEMORY_END>,
+    is_src_out_of_bounds: LtGadget<F, N_BYTES_MEMORY_END>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+const WIDTHS: [u64; 4] = [1, 2, 4, 8];
+
+impl<F: Field> ExecutionGadget<F> for ErrorMemoryOutOfBoundsGadget<F> {
+    const NAME: &'static str = "ErrorMemoryOutOfBounds";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorMemoryOutOfBounds;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let is_store = cb.query_bool();
+        let widths = [(); 4].map(|_| cb.query_bool());
+        let is_copy = cb.query_bool();
+        let is_fill = cb.query_bool();
+        let is_init = cb.query_bool();
+        let value = cb.query_cell();
+        let address = cb.query_cell();
+        let address_bytes = cb.query_bytes();
+        let memarg_offset = cb.query_cell();
+        let memarg_offset_bytes = cb.query_bytes();
+        let initial_pages = cb.query_cell();
+        let grown_pages = cb.query_cell();
+        let length_bytes = cb.query_bytes();
+        let src_bytes = cb.query_bytes();
+        let segment_start = cb.query_cell();
+        let segment_end = cb.query_cell();
+        let is_dropped = cb.query_bool();
+
+        let is_bulk = is_copy.expr() + is_fill.expr() + is_init.expr();
+        cb.require_equal(
+            "ErrorMemoryOutOfBounds: exactly one width or bulk memory instruction is selected",
+            widths.iter().fold(is_bulk.clone(), |acc, width| acc + width.expr()),
+            1.expr(),
+        );
+        cb.require_zero(
+            "ErrorMemoryOutOfBounds: bulk memory instructions are not stores",
+            is_bulk.clone() * is_store.expr(),
+        );
+        cb.condition(is_bulk.clone(), |cb| {
+            cb.require_equal(
+                "ErrorMemoryOutOfBounds: opcode matches the bulk memory instruction",
+                opcode.expr(),
+                is_copy.expr() * OpcodeId::MemoryCopy.expr()
+                    + is_fill.expr() * OpcodeId::MemoryFill.expr()
+                    + is_init.expr() * OpcodeId::MemoryInit.expr(),
+            );
+        });
+        for (width, len) in widths.iter().zip(WIDTHS) {
+            for is_wasm_store in [false, true] {
+                let condition = if is_wasm_store {
+                    is_store.expr()
+                } else {
+                    1.expr() - is_store.expr()
+                };
+                cb.condition(width.expr() * condition, |cb| {
+                    cb.require_in_set(
+                        "ErrorMemoryOutOfBounds: opcode matches width and access kind",
+                        opcode.expr(),
+                        OpcodeId::valid_opcodes()
+                            .into_iter()
+                            .filter(|op| {
+                                op.wasm_memory_access_len() == Some(len)
+                                    && op.is_wasm_store() == is_wasm_store
+                            })
+                            .map(|op| op.expr())
+                            .collect(),
+                    );
+                });
+            }
+        }
+
+        let [length, src] = [&length_bytes, &src_bytes]
+            .map(|bytes| bytes.iter().rev().fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr()));
+        cb.condition(is_store.expr(), |cb| {
+            cb.stack_pop(value.expr());
+        });
+        cb.condition(is_bulk.clone(), |cb| {
+            cb.stack_pop(length.clone());
+            cb.stack_pop(src.clone());
+        });
+        cb.stack_pop(address.expr());
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::GrownMemoryPages,
+            grown_pages.expr(),
+        );
+
+        // the memarg offset is the immediate of the instruction at the program
+        // counter and the initial pages are the minimum of the memory limits
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            cb.curr.state.program_counter.expr(),
+            opcode.expr(),
+            memarg_offset.expr(),
+        );
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::MemoryPages,
+            0.expr(),
+            initial_pages.expr(),
+            0.expr(),
+        );
+        cb.condition(is_init.expr(), |cb| {
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::DataSegment,
+                memarg_offset.expr(),
+                segment_start.expr(),
+                segment_end.expr(),
+            );
+            cb.data_drop_lookup(false.expr(), memarg_offset.expr(), is_dropped.expr());
+        });
+        for (name, cell, bytes) in [
+            ("ErrorMemoryOutOfBounds: address fits four bytes", &address, &address_bytes),
+            (
+                "ErrorMemoryOutOfBounds: memarg offset fits four bytes",
+                &memarg_offset,
+                &memarg_offset_bytes,
+            ),
+        ] {
+            cb.require_equal(
+                name,
+                cell.expr(),
+                bytes
+                    .iter()
+                    .rev()
+                    .fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr()),
+            );
+        }
+
+        let len = widths
+            .iter()
+            .zip(WIDTHS)
+            .fold(0.expr(), |acc, (width, len)| acc + width.expr() * len.expr());
+        let memory_size = (initial_pages.expr() + grown_pages.expr()) * WASM_PAGE_SIZE.expr();
+        let is_out_of_bounds = LtGadget::construct(
+            cb,
+            memory_size.clone(),
+            address.expr() + (1.expr() - is_bulk.clone()) * memarg_offset.expr() + len + length.clone(),
+        );
+        let src_size = is_copy.expr() * memory_size
+            + is_init.expr() * (segment_end.expr() - segment_start.expr()) * (1.expr() - is_dropped.expr());
+        let is_src_out_of_bounds = LtGadget::construct(cb, src_size, src + length);
+        cb.require_zero(
+            "accessed range or bulk memory source range ends beyond its bound",
+            (1.expr() - is_out_of_bounds.expr())
+                * (1.expr() - (is_copy.expr() + is_init.expr()) * is_src_out_of_bounds.expr()),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(
+            cb,
+            opcode.expr(),
+            4.expr() + is_store.expr() + 2.expr() * is_bulk + is_init.expr(),
+        );
+
+        Self {
+            opcode,
+            is_store,
+            widths,
+            is_copy,
+            is_fill,
+            is_init,
+            value,
+            address,
+            address_bytes,
+            memarg_offset,
+            memarg_offset_bytes,
+            length_bytes,
+            src_bytes,
+            segment_start,
+            segment_end,
+            is_dropped,
+            initial_pages,
+            grown_pages,
+            is_out_of_bounds,
+            is_src_out_of_bounds,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let is_store = opcode.is_wasm_store();
+        let is_init = opcode == OpcodeId::MemoryInit;
+        let is_bulk = matches!(opcode, OpcodeId::MemoryCopy | OpcodeId::MemoryFill) || is_init;
+        let len = opcode.wasm_memory_access_len().unwrap_or_default();
+        self.is_store
+            .assign(region, offset, Value::known(F::from(is_store as u64)))?;
+        for (width, width_len) in self.widths.iter().zip(WIDTHS) {
+            width.assign(region, offset, Value::known(F::from((len == width_len) as u64)))?;
+        }
+        for (cell, op) in [
+            (&self.is_copy, OpcodeId::MemoryCopy),
+            (&self.is_fill, OpcodeId::MemoryFill),
+            (&self.is_init, OpcodeId::MemoryInit),
+        ] {
+            cell.assign(region, offset, Value::known(F::from((opcode == op) as u64)))?;
+        }
+
+        let mut rws = step.rw_indices.iter().map(|idx| &block.rws[*idx]);
+        let value = if is_store {
+            rws.next().unwrap().stack_value().as_u64()
+        } else {
+            0
+        };
+        let (length, src) = if is_bulk {
+            let length = rws.next().unwrap().stack_value().as_u64();
+            (length, rws.next().unwrap().stack_value().as_u64())
+        } else {
+            (0, 0)
+        };
+        let address = rws.next().unwrap().stack_value().as_u64();
+        let grown_pages = rws.next().unwrap().call_context_value().as_u64();
+        let is_dropped = is_init && rws.next().unwrap().data_drop_value();
+        let memarg_offset = step.params[0];
+
+        let code = &block
+            .bytecodes
+            .get(&call.code_hash)
+            .expect("could not find current environment's bytecode")
+            .bytes;
+        let (initial_pages, _) = wasm_memory_limits(code).unwrap_or_default();
+        let (segment_start, segment_end) = if is_init {
+            wasm_data_segment(code, memarg_offset).unwrap_or_default()
+        } else {
+            (0, 0)
+        };
+
+        self.value
+            .assign(region, offset, Value::known(F::from(value)))?;
+        for (cell, bytes, val) in [
+            (&self.address, &self.address_bytes, address),
+            (&self.memarg_offset, &self.memarg_offset_bytes, memarg_offset),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(val)))?;
+            for (byte_cell, byte) in bytes.iter().zip((val as u32).to_le_bytes()) {
+                byte_cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+            }
+        }
+        for (bytes, val) in [(&self.length_bytes, length), (&self.src_bytes, src)] {
+            for (cell, byte) in bytes.iter().zip((val as u32).to_le_bytes()) {
+                cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+            }
+        }
+        self.segment_start
+            .assign(region, offset, Value::known(F::from(segment_start)))?;
+        self.segment_end
+            .assign(region, offset, Value::known(F::from(segment_end)))?;
+        self.is_dropped
+            .assign(region, offset, Value::known(F::from(is_dropped as u64)))?;
+        self.initial_pages
+            .assign(region, offset, Value::known(F::from(initial_pages)))?;
+        self.grown_pages
+            .assign(region, offset, Value::known(F::from(grown_pages)))?;
+
+        let memory_size = (initial_pages + grown_pages) * WASM_PAGE_SIZE;
+        let end = if is_bulk { address + length } else { address + memarg_offset + len };
+        self.is_out_of_bounds
+            .assign(region, offset, F::from(memory_size), F::from(end))?;
+        let src_size = match opcode {
+            OpcodeId::MemoryCopy => memory_size,
+            OpcodeId::MemoryInit if !is_dropped => segment_end - segment_start,
+            _ => 0,
+        };
+        self.is_src_out_of_bounds
+            .assign(region, offset, F::from(src_size), F::from(src + length))?;
+
+        let rw_offset = 4 + is_store as usize + 2 * is_bulk as usize + is_init as usize;
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, rw_offset)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eth_types::{bytecode, bytecode::WASM_PAGE_SIZE, evm_types::OpcodeId, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_load_out_of_bounds() {
+        run_test(bytecode! {
+            I32Const[WASM_PAGE_SIZE - 2]
+            .write_memarg(OpcodeId::I32Load, 0, 2, 0)
+            Drop
+        });
+        run_test(bytecode! {
+            I32Const[0]
+            .write_memarg(OpcodeId::I64Load8U, WASM_PAGE_SIZE, 0, 0)
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_store_out_of_bounds() {
+        run_test(bytecode! {
+            I32Const[WASM_PAGE_SIZE - 1]
+            I64Const[0x1122]
+            .write_memarg(OpcodeId::I64Store16, 0, 1, 0)
+        });
+    }
+
+    #[test]
+    fn test_load_out_of_bounds_at_address_space_end() {
+        run_test(bytecode! {
+            I32Const[-1]
+            .write_memarg(OpcodeId::I64Load, 0xffff_ffff, 3, 0)
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_load_out_of_bounds_after_memory_grow() {
+        run_test(bytecode! {
+            I32Const[1]
+            GrowMemory
+            Drop
+            I32Const[2 * WASM_PAGE_SIZE - 4]
+            .write_memarg(OpcodeId::I64Load, 0, 3, 0)
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_memory_copy_out_of_bounds() {
+        run_test(bytecode! {
+            I32Const[WASM_PAGE_SIZE - 2]
+            I32Const[0]
+            I32Const[3]
+            MemoryCopy
+        });
+        run_test(bytecode! {
+            I32Const[0]
+            I32Const[WASM_PAGE_SIZE - 2]
+            I32Const[3]
+            MemoryCopy
+        });
+    }
+
+    #[test]
+    fn test_memory_fill_out_of_bounds() {
+        run_test(bytecode! {
+            I32Const[-1]
+            I32Const[0]
+            I32Const[-1]
+            MemoryFill
+        });
+    }
+
+    #[test]
+    fn test_memory_init_past_segment_end() {
+        let mut code = bytecode! {
+            I32Const[0]
+            I32Const[1]
+            I32Const[3]
+            MemoryInit[0]
+        };
+        code.with_passive_data(vec![7, 8, 9]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_memory_init_after_data_drop() {
+        let mut code = bytecode! {
+            DataDrop[0]
+            I32Const[0]
+            I32Const[0]
+            I32Const[1]
+            MemoryInit[0]
+        };
+        code.with_passive_data(vec![7, 8, 9]);
+        run_test(code);
+    }
+}
@@ -1,14 +1,14 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::N_BYTES_GAS,
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_WORD},
         step::ExecutionState,
         util::{
             common_gadget::{CommonCallGadget, CommonErrorGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{IsZeroGadget, LtGadget},
-            memory_gadget::MemoryExpandedAddressGadget,
-            or, CachedRegion, Cell,
+            memory_gadget::MemoryAddress64Gadget,
+            CachedRegion, Cell,
         },
     },
     table::CallContextFieldTag,
@@ -16,7 +16,7 @@ use crate::{
     witness::{Block, Call, ExecStep, Transaction},
 };
 use bus_mapping::evm::OpcodeId;
-use eth_types::{Field, StackWord, ToU256, U64};
+use eth_types::{Field, U256, U64};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget to implement the corresponding out of gas errors for
@@ -32,7 +32,7 @@ pub(crate) struct ErrorOOGCallGadget<F> {
     tx_id: Cell<F>,
     is_static: Cell<F>,
     is_warm: Cell<F>,
-    call: CommonCallGadget<F, MemoryExpandedAddressGadget<F>, false>,
+    call: CommonCallGadget<F, MemoryAddress64Gadget<F>, false>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
     common_error_gadget: CommonErrorGadget<F>,
 }
@@ -55,7 +55,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
         let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
 
-        let call_gadget: CommonCallGadget<F, MemoryExpandedAddressGadget<F>, false> =
+        let call_gadget: CommonCallGadget<F, MemoryAddress64Gadget<F>, false> =
             CommonCallGadget::construct(
                 cb,
                 is_call.expr(),
@@ -85,22 +85,20 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         // Check if the amount of gas available is less than the amount of gas required
         let insufficient_gas = LtGadget::construct(cb, cb.curr.state.gas_left.expr(), gas_cost);
 
+        // Linear memory offsets are i32 values, so they can't overflow and only
+        // the gas left is checked.
         cb.require_equal(
-            "Either Memory address is overflow or gas left is less than cost",
-            or::expr([
-                call_gadget.cd_address.overflow(),
-                call_gadget.rd_address.overflow(),
-                insufficient_gas.expr(),
-            ]),
+            "Gas left is less than cost",
+            insufficient_gas.expr(),
             1.expr(),
         );
 
-        // Both CALL and CALLCODE opcodes have an extra stack pop `value` relative to
-        // DELEGATECALL and STATICCALL.
+        // Both CALL and CALLCODE opcodes have an extra `value` pointer and 32
+        // memory reads relative to DELEGATECALL and STATICCALL.
         let common_error_gadget = CommonErrorGadget::construct(
             cb,
             opcode.expr(),
-            13.expr() + is_call.expr() + is_callcode.expr(),
+            33.expr() + 33.expr() * (is_call.expr() + is_callcode.expr()),
         );
 
         Self {
@@ -132,48 +130,53 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             usize::from([OpcodeId::CALL, OpcodeId::CALLCODE].contains(&opcode));
         let [tx_id, is_static] =
             [step.rw_indices[0], step.rw_indices[1]].map(|idx| block.rws[idx].call_context_value());
-        let stack_index = 2;
-        let [gas, callee_address] = [
-            step.rw_indices[stack_index],
-            step.rw_indices[stack_index + 1],
-        ]
-            .map(|idx| block.rws[idx].stack_value());
-        let value = if is_call_or_callcode == 1 {
-            block.rws[step.rw_indices[stack_index + 2]].stack_value()
+        let read_memory = |index: usize, n: usize| {
+            let bytes = step.rw_indices[index..index + n]
+                .iter()
+                .map(|&idx| block.rws[idx].memory_value())
+                .collect::<Vec<u8>>();
+            U256::from_big_endian(&bytes)
+        };
+        let [status_offset, rd_length, rd_offset, cd_length, cd_offset] =
+            [2, 3, 4, 5, 6].map(|i| block.rws[step.rw_indices[i]].stack_value());
+        let mut rw_index = 7;
+        let (value_offset, value) = if is_call_or_callcode == 1 {
+            let value_offset = block.rws[step.rw_indices[rw_index]].stack_value();
+            let value = read_memory(rw_index + 1, N_BYTES_WORD);
+            rw_index += 1 + N_BYTES_WORD;
+            (value_offset, value)
         } else {
-            StackWord::zero()
+            (U64::zero(), U256::zero())
         };
-        let [cd_offset, cd_length, rd_offset, rd_length] = [
-            step.rw_indices[stack_index + is_call_or_callcode + 2],
-            step.rw_indices[stack_index + is_call_or_callcode + 3],
-            step.rw_indices[stack_index + is_call_or_callcode + 4],
-            step.rw_indices[stack_index + is_call_or_callcode + 5],
-        ]
-            .map(|idx| block.rws[idx].stack_value());
-
-        let callee_code_hash = block.rws[step.rw_indices[9 + is_call_or_callcode]]
+        let callee_address_offset = block.rws[step.rw_indices[rw_index]].stack_value();
+        let callee_address = read_memory(rw_index + 1, N_BYTES_ACCOUNT_ADDRESS);
+        rw_index += 1 + N_BYTES_ACCOUNT_ADDRESS;
+        let gas = block.rws[step.rw_indices[rw_index]].stack_value();
+
+        let callee_code_hash = block.rws[step.rw_indices[rw_index + 1]]
             .account_value_pair()
             .0;
         let callee_exists = !callee_code_hash.is_zero();
 
         let (is_warm, is_warm_prev) =
-            block.rws[step.rw_indices[10 + is_call_or_callcode]].tx_access_list_value_pair();
+            block.rws[step.rw_indices[rw_index + 2]].tx_access_list_value_pair();
 
         let memory_expansion_gas_cost = self.call.assign(
             region,
             offset,
             gas,
+            callee_address_offset,
+            callee_address,
+            value_offset,
+            value,
+            status_offset,
             U64::zero(),
-            callee_address.to_u256(),
-            U64::zero(),
-            value.to_u256(),
-            U64::zero(),
-            U64::from(0),
             cd_offset,
             cd_length,
             rd_offset,
             rd_length,
-            region.word_rlc(callee_code_hash),
+            region.code_hash(callee_code_hash),
+            step.memory_word_size(),
         )?;
 
         self.opcode
@@ -225,15 +228,13 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             Value::known(F::from(gas_cost)),
         )?;
 
-        // Both CALL and CALLCODE opcodes have an extra stack pop `value` relative to
-        // DELEGATECALL and STATICCALL.
         self.common_error_gadget.assign(
             region,
             offset,
             block,
             call,
             step,
-            13 + is_call_or_callcode,
+            33 + 33 * is_call_or_callcode,
         )?;
         Ok(())
     }
@@ -1,87 +1,148 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64, N_BYTES_WORD},
         step::ExecutionState,
         util::{
             common_gadget::CommonErrorGadget,
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
-            math_gadget::LtGadget,
+            math_gadget::{ConstantDivisionGadget, LtGadget},
             memory_gadget::{
-                CommonMemoryAddressGadget, MemoryExpandedAddressGadget, MemoryExpansionGadget,
-                MemoryWordSizeGadget,
+                CommonMemoryAddressGadget, MemoryAddress64Gadget, MemoryExpansionGadget,
             },
-            or, CachedRegion, Cell, Word,
+            CachedRegion, Cell,
         },
     },
+    table::CallContextFieldTag,
+    wasm_circuit::types::WasmModuleTag,
     witness::{Block, Call, ExecStep, Transaction},
 };
-use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use eth_types::{
+    bytecode::wasm_memory_limits,
+    evm_types::{GasCost, OpcodeId, CREATE2_GAS_PER_CODE_WORD, CREATE_GAS_PER_CODE_WORD},
+    Field, U64,
+};
 use gadgets::util::Expr;
 use halo2_proofs::{circuit::Value, plonk::Error};
 
+/// Gadget for CREATE and CREATE2 running out of gas, the init code range is
+/// popped from the stack together with the value, salt and result pointers.
+/// The memory is expanded in pages from the initial pages of the memory section
+/// plus the pages grown so far, like the loads and stores do.
 #[derive(Clone, Debug)]
-pub(crate) struct ErrorOOGCreate2Gadget<F> {
+pub(crate) struct ErrorOOGCreateGadget<F> {
     opcode: Cell<F>,
-    value: Word<F>,
-    salt: Word<F>,
-    minimum_word_size: MemoryWordSizeGadget<F>,
-    memory_address: MemoryExpandedAddressGadget<F>,
+    is_create2: Cell<F>,
+    result_offset: Cell<F>,
+    result_offset_bytes: [Cell<F>; 4],
+    salt_offset: Cell<F>,
+    value_offset: Cell<F>,
+    initial_pages: Cell<F>,
+    grown_pages: Cell<F>,
+    memory_address: MemoryAddress64Gadget<F>,
+    init_code_word_size: ConstantDivisionGadget<F, N_BYTES_U64>,
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
     common_error_gadget: CommonErrorGadget<F>,
 }
 
-impl<F: Field> ExecutionGadget<F> for ErrorOOGCreate2Gadget<F> {
-    const NAME: &'static str = "ErrorOutOfGasCREATE2";
+impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasCREATE";
 
     const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCREATE;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
+        let is_create2 = cb.query_bool();
 
         cb.require_equal(
-            "ErrorOutOfGasCREATE2 opcode must be CREATE2",
+            "ErrorOutOfGasCREATE opcode must be CREATE or CREATE2",
             opcode.expr(),
-            OpcodeId::CREATE2.expr(),
+            OpcodeId::CREATE.expr()
+                + is_create2.expr() * (OpcodeId::CREATE2.expr() - OpcodeId::CREATE.expr()),
         );
 
-        let value = cb.query_word_rlc();
-        let salt = cb.query_word_rlc();
-
-        let memory_address = MemoryExpandedAddressGadget::construct_self(cb);
+        let result_offset = cb.query_cell();
+        let result_offset_bytes = cb.query_bytes();
+        let salt_offset = cb.query_cell();
+        let value_offset = cb.query_cell();
+        let memory_offset = cb.query_cell_phase2();
+        let memory_length = cb.query_cell();
+
+        cb.stack_pop(result_offset.expr());
+        cb.condition(is_create2.expr(), |cb| {
+            cb.stack_pop(salt_offset.expr());
+        });
+        cb.stack_pop(memory_length.expr());
+        cb.stack_pop(memory_offset.expr());
+        cb.stack_pop(value_offset.expr());
 
-        cb.stack_pop(value.expr());
-        cb.stack_pop(memory_address.offset_rlc());
-        cb.stack_pop(memory_address.length_rlc());
-        cb.stack_pop(salt.expr());
+        cb.require_equal(
+            "result offset fits four bytes",
+            result_offset.expr(),
+            result_offset_bytes
+                .iter()
+                .rev()
+                .fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr()),
+        );
 
-        let minimum_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
+        let initial_pages = cb.query_cell();
+        let grown_pages = cb.query_cell();
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::GrownMemoryPages,
+            grown_pages.expr(),
+        );
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::MemoryPages,
+            0.expr(),
+            initial_pages.expr(),
+            0.expr(),
+        );
 
-        let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
+        let memory_address = MemoryAddress64Gadget::construct(cb, memory_offset, memory_length);
+        let init_code_word_size = ConstantDivisionGadget::construct(
+            cb,
+            memory_address.length() + (N_BYTES_WORD - 1).expr(),
+            N_BYTES_WORD as u64,
+        );
+        let memory_expansion = MemoryExpansionGadget::construct_with_memory_word_size(
+            cb,
+            initial_pages.expr() + grown_pages.expr(),
+            [memory_address.address()],
+        );
 
+        let gas_per_code_word = CREATE_GAS_PER_CODE_WORD.expr()
+            + is_create2.expr() * (CREATE2_GAS_PER_CODE_WORD - CREATE_GAS_PER_CODE_WORD).expr();
         let insufficient_gas = LtGadget::construct(
             cb,
             cb.curr.state.gas_left.expr(),
-            OpcodeId::CREATE2.constant_gas_cost().expr()
+            GasCost::CREATE.expr()
                 + memory_expansion.gas_cost()
-                + 6.expr() * minimum_word_size.expr(),
+                + gas_per_code_word * init_code_word_size.quotient(),
         );
-
         cb.require_equal(
-            "Memory address is overflow or gas left is less than cost",
-            or::expr([memory_address.overflow(), insufficient_gas.expr()]),
+            "gas left is less than gas required",
+            insufficient_gas.expr(),
             1.expr(),
         );
 
-        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 6.expr());
+        let common_error_gadget =
+            CommonErrorGadget::construct(cb, opcode.expr(), 7.expr() + is_create2.expr());
 
         Self {
             opcode,
-            value,
-            salt,
-            minimum_word_size,
+            is_create2,
+            result_offset,
+            result_offset_bytes,
+            salt_offset,
+            value_offset,
+            initial_pages,
+            grown_pages,
             memory_address,
+            init_code_word_size,
             memory_expansion,
             insufficient_gas,
             common_error_gadget,
@@ -98,50 +159,86 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreate2Gadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         let opcode = step.opcode.unwrap();
+        let is_create2 = opcode == OpcodeId::CREATE2;
 
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.is_create2
+            .assign(region, offset, Value::known(F::from(is_create2 as u64)))?;
+
+        let n_pop = 4 + usize::from(is_create2);
+        let stack_values = (0..n_pop)
+            .map(|i| block.rws[step.rw_indices[i]].stack_value())
+            .collect::<Vec<_>>();
+        let salt_offset = if is_create2 { stack_values[1] } else { U64::zero() };
+        let [result_offset, memory_length, memory_offset, value_offset] = [
+            stack_values[0],
+            stack_values[n_pop - 3],
+            stack_values[n_pop - 2],
+            stack_values[n_pop - 1],
+        ];
 
-        self.value.assign(
-            region,
-            offset,
-            Some(block.rws[step.rw_indices[0]].stack_value().to_le_bytes()),
-        )?;
+        for (cell, value) in [
+            (&self.result_offset, result_offset),
+            (&self.salt_offset, salt_offset),
+            (&self.value_offset, value_offset),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(value.as_u64())))?;
+        }
+        for (cell, byte) in self
+            .result_offset_bytes
+            .iter()
+            .zip((result_offset.as_u64() as u32).to_le_bytes())
+        {
+            cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+        }
 
-        let memory_offset = block.rws[step.rw_indices[1]].stack_value();
-        let memory_length = block.rws[step.rw_indices[2]].stack_value();
+        let grown_pages = block.rws[step.rw_indices[n_pop]]
+            .call_context_value()
+            .as_u64();
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let (initial_pages, _) = wasm_memory_limits(code).unwrap_or_default();
+        self.initial_pages
+            .assign(region, offset, Value::known(F::from(initial_pages)))?;
+        self.grown_pages
+            .assign(region, offset, Value::known(F::from(grown_pages)))?;
 
         let memory_address =
             self.memory_address
                 .assign(region, offset, memory_offset, memory_length)?;
-
-        self.salt.assign(
-            region,
-            offset,
-            Some(block.rws[step.rw_indices[3]].stack_value().to_le_bytes()),
-        )?;
-
-        let minimum_word_size = self.minimum_word_size.assign(
+        let (init_code_word_size, _) = self.init_code_word_size.assign(
             region,
             offset,
-            MemoryExpandedAddressGadget::<F>::length_value(memory_offset, memory_length),
+            memory_length.as_u64() as u128 + (N_BYTES_WORD as u128 - 1),
         )?;
         let memory_expansion_gas = self
             .memory_expansion
-            .assign(region, offset, step.memory_word_size(), [memory_address])?
+            .assign(
+                region,
+                offset,
+                initial_pages + grown_pages,
+                [memory_address],
+            )?
             .1;
 
-        let constant_gas_cost = opcode.constant_gas_cost().0;
-
+        let gas_per_code_word = if is_create2 {
+            CREATE2_GAS_PER_CODE_WORD
+        } else {
+            CREATE_GAS_PER_CODE_WORD
+        };
         self.insufficient_gas.assign(
             region,
             offset,
             F::from(step.gas_left),
-            F::from(6 * minimum_word_size + memory_expansion_gas + constant_gas_cost),
+            F::from(
+                GasCost::CREATE.as_u64()
+                    + memory_expansion_gas
+                    + gas_per_code_word * init_code_word_size as u64,
+            ),
         )?;
 
         self.common_error_gadget
-            .assign(region, offset, block, call, step, 6)?;
+            .assign(region, offset, block, call, step, 7 + usize::from(is_create2))?;
 
         Ok(())
     }
@@ -149,166 +246,65 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreate2Gadget<F> {
 
 #[cfg(test)]
 mod tests {
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, word, Bytecode, ToWord, Word, U256};
-    use mock::{
-        eth,
-        test_ctx::{helpers::account_0_code_account_1_no_code, LoggerConfig},
-        TestContext, MOCK_ACCOUNTS, MOCK_BLOCK_GAS_LIMIT,
-    };
-
-    struct TestCase {
-        bytecode: Bytecode,
-        gas: Word,
-    }
+    use crate::{evm_circuit::step::ExecutionState, test_util::CircuitTestBuilder, witness::Rw};
+    use eth_types::{Bytecode, Word, U256};
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
-    #[test]
-    fn test_oog_create2_simple() {
-        let cases = [
-            TestCase {
-                bytecode: bytecode! {
-                    PUSH8(0x0) // salt
-                    PUSH8(0xFF) // size
-                    PUSH32(word!("0xffffffff")) // offset
-                    PUSH8(0x0) // value
-                    CREATE2
-                    STOP
-                },
-                gas: word!("0xFFFF"),
-            },
-            TestCase {
-                bytecode: bytecode! {
-                    PUSH1(2) // salt
-                    PUSH1(4) // size
-                    PUSH1(0x0) // offset
-                    PUSH1(0x0) // value
-                    CREATE2
-                },
-                gas: word!("0x7D0F"),
-            },
-        ];
-
-        for case in cases.iter() {
-            test_root(case);
-            test_internal(case);
-        }
-    }
-
-    #[test]
-    fn test_oog_create2_max_expanded_address() {
-        // 0xffffffff1 + 0xffffffff0 = 0x1fffffffe1
-        // > MAX_EXPANDED_MEMORY_ADDRESS (0x1fffffffe0)
-        let case = TestCase {
-            bytecode: bytecode! {
-                PUSH8(0) // salt
-                PUSH32(0xffffffff0_u64) // size
-                PUSH32(0xffffffff1_u64) // offset
-                PUSH8(0) // value
-                CREATE2
-                STOP
-            },
-            gas: MOCK_BLOCK_GAS_LIMIT.into(),
-        };
-
-        test_root(&case);
-        test_internal(&case);
-    }
-
-    #[test]
-    fn test_oog_create2_max_u64_address() {
-        let case = TestCase {
-            bytecode: bytecode! {
-                PUSH8(0) // salt
-                PUSH32(u64::MAX) // size
-                PUSH32(u64::MAX) // offset
-                PUSH8(0) // value
-                CREATE2
-                STOP
-            },
-            gas: MOCK_BLOCK_GAS_LIMIT.into(),
-        };
-
-        test_root(&case);
-        test_internal(&case);
-    }
-
-    #[test]
-    fn test_oog_create2_max_word_address() {
-        let case = TestCase {
-            bytecode: bytecode! {
-                PUSH8(0) // salt
-                PUSH32(U256::MAX) // size
-                PUSH32(U256::MAX) // offset
-                PUSH8(0) // value
-                CREATE2
-                STOP
-            },
-            gas: MOCK_BLOCK_GAS_LIMIT.into(),
-        };
-
-        test_root(&case);
-        test_internal(&case);
-    }
-
-    fn test_root(case: &TestCase) {
-        let ctx = TestContext::<2, 1>::new_with_logger_config(
+    fn test_ctx(code: Bytecode, gas: u64) -> TestContext<2, 1> {
+        TestContext::<2, 1>::new(
             None,
-            account_0_code_account_1_no_code(case.bytecode.clone()),
+            account_0_code_account_1_no_code(code),
             |mut txs, accs| {
                 txs[0]
                     .from(accs[1].address)
                     .to(accs[0].address)
-                    .gas(case.gas);
+                    .gas(Word::from(gas));
             },
             |block, _tx| block,
-            LoggerConfig {
-                enable_memory: true,
-                ..Default::default()
-            },
         )
-        .unwrap();
-
-        log::debug!("{:?}", ctx.geth_traces[0]);
+        .unwrap()
+    }
 
-        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    fn test_root(code: Bytecode, gas: u64) {
+        CircuitTestBuilder::new_from_test_ctx(test_ctx(code, gas)).run();
     }
 
-    fn test_internal(case: &TestCase) {
-        let code_a = bytecode! {
-            PUSH1(0x00) // retLength
-            PUSH1(0x00) // retOffset
-            PUSH32(0x00) // argsLength
-            PUSH32(0x00) // argsOffset
-            PUSH1(0x00) // value
-            PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
-            PUSH32(case.gas) // gas
-            CALL
-            STOP
-        };
+    #[test]
+    fn test_oog_create() {
+        let mut code = Bytecode::default();
+        code.emit_evm_create(U256::zero(), 0, 0x100, None);
+        test_root(code, 21_100);
+    }
 
-        let ctx = TestContext::<3, 1>::new_with_logger_config(
-            None,
-            |accs| {
-                accs[0].address(MOCK_ACCOUNTS[0]).code(code_a);
-                accs[1]
-                    .address(MOCK_ACCOUNTS[1])
-                    .code(case.bytecode.clone());
-                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(1));
-            },
-            |mut txs, accs| {
-                txs[0]
-                    .from(accs[2].address)
-                    .to(accs[0].address)
-                    .gas(word!("0xFFFFF"));
-            },
-            |block, _tx| block,
-            LoggerConfig {
-                enable_memory: true,
-                ..Default::default()
-            },
-        )
-        .unwrap();
+    #[test]
+    fn test_oog_create2() {
+        let mut code = Bytecode::default();
+        code.emit_evm_create(U256::zero(), 0, 0x100, Some(U256::from(0x123456)));
+        test_root(code, 21_100);
+    }
 
-        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    #[test]
+    fn test_oog_create_result_offset_wider_than_four_bytes_must_fail() {
+        let mut code = Bytecode::default();
+        code.emit_evm_create(U256::zero(), 0, 0x100, None);
+        CircuitTestBuilder::new_from_test_ctx(test_ctx(code, 21_100))
+            .block_modifier(Box::new(|block| {
+                // the result pointer is the first popped operand
+                let step = block.txs[0]
+                    .steps
+                    .iter()
+                    .find(|step| step.execution_state == ExecutionState::ErrorOutOfGasCREATE)
+                    .unwrap();
+                let (tag, index) = step.rw_indices[0];
+                if let Rw::Stack { value, .. } = &mut block.rws.0.get_mut(&tag).unwrap()[index] {
+                    *value = *value + (1u64 << 32);
+                }
+            }))
+            .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+                assert!(prover
+                    .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                    .is_err())
+            }))
+            .run();
     }
 }
@@ -7,19 +7,19 @@ use crate::{
             common_gadget::CommonErrorGadget,
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             from_bytes,
-            math_gadget::{IsZeroGadget, LtGadget},
+            math_gadget::{IsEqualGadget, LtGadget},
             memory_gadget::{
-                CommonMemoryAddressGadget, MemoryCopierGasGadget, MemoryExpandedAddressGadget,
+                CommonMemoryAddressGadget, MemoryAddress64Gadget, MemoryCopierGasGadget,
                 MemoryExpansionGadget,
             },
-            or, select, CachedRegion, Cell, Word,
+            select, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     table::CallContextFieldTag,
     util::Expr,
 };
-use eth_types::{evm_types::{GasCost, OpcodeId}, Field, StackWord, ToLittleEndian};
+use eth_types::{evm_types::{GasCost, OpcodeId}, Field, ToLittleEndian, ToWord};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget to implement the corresponding out of gas errors for
@@ -31,16 +31,18 @@ pub(crate) struct ErrorOOGMemoryCopyGadget<F> {
     /// Check if `EXTCODECOPY` external address is warm
     is_warm: Cell<F>,
     tx_id: Cell<F>,
-    /// Extra stack pop for `EXTCODECOPY`
+    /// Extra stack pop for `EXTCODECOPY`, the address itself is read from memory
+    external_address_offset: Cell<F>,
     external_address: Word<F>,
     /// Source offset
-    src_offset: Word<F>,
+    src_offset: Cell<F>,
     /// Destination offset and size to copy
-    dst_memory_addr: MemoryExpandedAddressGadget<F>,
+    dst_memory_addr: MemoryAddress64Gadget<F>,
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
-    is_extcodecopy: IsZeroGadget<F>,
+    is_extcodecopy: IsEqualGadget<F>,
+    is_returndatacopy: IsEqualGadget<F>,
     common_error_gadget: CommonErrorGadget<F>,
 }
 
@@ -62,15 +64,38 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             ],
         );
 
-        let src_offset = cb.query_word_rlc();
+        let is_extcodecopy =
+            IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::EXTCODECOPY.expr());
+        let is_returndatacopy =
+            IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::RETURNDATACOPY.expr());
+
+        let dst_offset = cb.query_cell_phase2();
+        let src_offset = cb.query_cell();
+        let length = cb.query_cell();
+
+        // RETURNDATACOPY takes the destination on top of the stack, the other
+        // copies take the length on top.
+        cb.stack_pop(select::expr(
+            is_returndatacopy.expr(),
+            dst_offset.expr(),
+            length.expr(),
+        ));
+        cb.stack_pop(src_offset.expr());
+        cb.stack_pop(select::expr(
+            is_returndatacopy.expr(),
+            length.expr(),
+            dst_offset.expr(),
+        ));
+
+        let external_address_offset = cb.query_cell();
         let external_address = cb.query_word_rlc();
         let is_warm = cb.query_bool();
         let tx_id = cb.query_cell();
 
-        let is_extcodecopy =
-            IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::EXTCODECOPY.expr());
-
         cb.condition(is_extcodecopy.expr(), |cb| {
+            // EXTCODECOPY has an extra stack pop for the external address pointer.
+            cb.stack_pop(external_address_offset.expr());
+
             cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, tx_id.expr());
 
             // Check if EXTCODECOPY external address is warm.
@@ -80,18 +105,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
                 is_warm.expr(),
             );
 
-            // EXTCODECOPY has an extra stack pop for external address.
-            cb.stack_pop(external_address.expr());
+            cb.memory_address_lookup(0.expr(), &external_address_offset, &external_address);
         });
 
-        let dst_memory_addr = MemoryExpandedAddressGadget::construct_self(cb);
-
-        cb.stack_pop(dst_memory_addr.offset_rlc());
-        cb.stack_pop(src_offset.expr());
-        cb.stack_pop(dst_memory_addr.length_rlc());
-
-        //TODO: Add constrait for memory check
-
+        let dst_memory_addr = MemoryAddress64Gadget::construct(cb, dst_offset, length);
         let memory_expansion = MemoryExpansionGadget::construct(cb, [dst_memory_addr.address()]);
         let memory_copier_gas = MemoryCopierGasGadget::construct(
             cb,
@@ -117,25 +134,26 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             cb.curr.state.gas_left.expr(),
             constant_gas_cost + memory_copier_gas.gas_cost(),
         );
-
         cb.require_equal(
-            "Memory address is overflow or gas left is less than cost",
-            or::expr([dst_memory_addr.overflow(), insufficient_gas.expr()]),
+            "gas left is less than gas required",
+            insufficient_gas.expr(),
             1.expr(),
         );
 
         let common_error_gadget = CommonErrorGadget::construct(
             cb,
             opcode.expr(),
-            // EXTCODECOPY has extra 1 call context lookup (tx_id), 1 account access list
-            // read (is_warm), and 1 stack pop (external_address).
-            5.expr() + 3.expr() * is_extcodecopy.expr(),
+            // EXTCODECOPY has extra 1 stack pop (external address pointer), 1 call context
+            // lookup (tx_id), 1 account access list read (is_warm) and 20 memory reads of
+            // the external address.
+            5.expr() + 23.expr() * is_extcodecopy.expr(),
         );
 
         Self {
             opcode,
             is_warm,
             tx_id,
+            external_address_offset,
             external_address,
             src_offset,
             dst_memory_addr,
@@ -143,6 +161,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             memory_copier_gas,
             insufficient_gas,
             is_extcodecopy,
+            is_returndatacopy,
             common_error_gadget,
         }
     }
@@ -158,6 +177,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
     ) -> Result<(), Error> {
         let opcode = step.opcode.unwrap();
         let is_extcodecopy = opcode == OpcodeId::EXTCODECOPY;
+        let is_returndatacopy = opcode == OpcodeId::RETURNDATACOPY;
 
         log::debug!(
             "ErrorOutOfGasMemoryCopy: opcode = {}, gas_left = {}, gas_cost = {}",
@@ -166,29 +186,54 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             step.gas_cost,
         );
 
-        let (is_warm, external_address) = if is_extcodecopy {
+        let [top, src_offset, bottom] =
+            [0, 1, 2].map(|idx| block.rws[step.rw_indices[idx]].stack_value());
+        let (dst_offset, copy_size) = if is_returndatacopy {
+            (top, bottom)
+        } else {
+            (bottom, top)
+        };
+
+        let (external_address_offset, is_warm, external_address) = if is_extcodecopy {
             (
-                block.rws[step.rw_indices[1]].tx_access_list_value_pair().0,
-                block.rws[step.rw_indices[2]].stack_value(),
+                block.rws[step.rw_indices[3]].stack_value().as_u64(),
+                block.rws[step.rw_indices[5]].tx_access_list_value_pair().0,
+                block.rws[step.rw_indices[5]]
+                    .address()
+                    .ok_or(Error::Synthesis)?
+                    .to_word(),
             )
         } else {
-            (false, StackWord::zero())
+            (0, false, Default::default())
         };
 
-        let rw_offset = if is_extcodecopy { 3 } else { 0 };
-        let [dst_offset, src_offset, copy_size] = [rw_offset, rw_offset + 1, rw_offset + 2]
-            .map(|idx| block.rws[step.rw_indices[idx]].stack_value());
-
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.is_extcodecopy.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::EXTCODECOPY.as_u64()),
+        )?;
+        self.is_returndatacopy.assign(
+            region,
+            offset,
+            F::from(opcode.as_u64()),
+            F::from(OpcodeId::RETURNDATACOPY.as_u64()),
+        )?;
         self.is_warm
             .assign(region, offset, Value::known(F::from(u64::from(is_warm))))?;
         self.tx_id
             .assign(region, offset, Value::known(F::from(transaction.id as u64)))?;
+        self.external_address_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(external_address_offset)),
+        )?;
         self.external_address
             .assign(region, offset, Some(external_address.to_le_bytes()))?;
         self.src_offset
-            .assign(region, offset, Some(src_offset.to_le_bytes()))?;
+            .assign(region, offset, Value::known(F::from(src_offset.as_u64())))?;
         let memory_addr = self
             .dst_memory_addr
             .assign(region, offset, dst_offset, copy_size)?;
@@ -198,7 +243,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
         let memory_copier_gas = self.memory_copier_gas.assign(
             region,
             offset,
-            MemoryExpandedAddressGadget::<F>::length_value(dst_offset, copy_size),
+            copy_size.as_u64(),
             memory_expansion_cost,
         )?;
         let constant_gas_cost = if is_extcodecopy {
@@ -208,18 +253,13 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
                 GasCost::COLD_ACCOUNT_ACCESS
             }
         } else {
-            GasCost::FASTEST
+            OpcodeId::CALLDATACOPY.constant_gas_cost()
         };
-        self.insufficient_gas.assign_value(
-            region,
-            offset,
-            Value::known(F::from(step.gas_left)),
-            Value::known(F::from(memory_copier_gas + constant_gas_cost.0)),
-        )?;
-        self.is_extcodecopy.assign(
+        self.insufficient_gas.assign(
             region,
             offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::EXTCODECOPY.as_u64()),
+            F::from(step.gas_left),
+            F::from(memory_copier_gas + constant_gas_cost.0),
         )?;
         self.common_error_gadget.assign(
             region,
@@ -227,9 +267,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
             block,
             call,
             step,
-            // EXTCODECOPY has extra 1 call context lookup (tx_id), 1 account access list
-            // read (is_warm), and 1 stack pop (external_address).
-            5 + if is_extcodecopy { 3 } else { 0 },
+            5 + if is_extcodecopy { 23 } else { 0 },
         )?;
 
         Ok(())
@@ -238,183 +276,39 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryCopyGadget<F> {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::{
-        evm_circuit::test::{rand_bytes, rand_word},
-        test_util::CircuitTestBuilder,
-    };
+    use crate::test_util::CircuitTestBuilder;
     use bus_mapping::circuit_input_builder::CircuitsParams;
-    use eth_types::{bytecode, evm_types::gas_utils::memory_copier_gas_cost, Bytecode, ToWord, U256, bytecode_internal};
-    use itertools::Itertools;
+    use eth_types::{
+        bytecode::WASM_PAGE_SIZE,
+        bytecode_internal,
+        evm_types::{GasCost, OpcodeId},
+        Bytecode, Word,
+    };
     use mock::{
-        eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
+        test_ctx::helpers::account_0_code_account_1_no_code, TestContext, MOCK_ACCOUNTS,
         MOCK_BLOCK_GAS_LIMIT,
     };
-    use eth_types::ToBigEndian;
-
-    const TESTING_COMMON_OPCODES: &[OpcodeId] = &[
-        OpcodeId::CALLDATACOPY,
-        OpcodeId::CODECOPY,
-        // OpcodeId::RETURNDATACOPY,
-    ];
-
-    const TESTING_DST_OFFSET_COPY_SIZE_PAIRS: &[(u64, u64)] =
-        &[(0x20, 0), (0x40, 20), (0x2000, 0x200)];
 
-    #[test]
-    fn test_oog_memory_copy_for_common_opcodes() {
-        for (opcode, (dst_offset, copy_size)) in TESTING_COMMON_OPCODES
-            .iter()
-            .cartesian_product(TESTING_DST_OFFSET_COPY_SIZE_PAIRS.iter())
-        {
-            let testing_data =
-                TestingData::new_for_common_opcode(*opcode, *dst_offset, *copy_size, None);
-
-            test_root(&testing_data);
-            // test_internal(&testing_data);
-        }
-    }
+    /// Copies 64 pages, so the dynamic cost can't be covered by the gas left after the
+    /// constant cost.
+    const COPY_SIZE: u64 = 64 * WASM_PAGE_SIZE;
 
-    #[test]
-    fn test_oog_memory_copy_for_extcodecopy() {
-        for (is_warm, (dst_offset, copy_size)) in [false, true]
-            .iter()
-            .cartesian_product(TESTING_DST_OFFSET_COPY_SIZE_PAIRS.iter())
-        {
-            let testing_data =
-                TestingData::new_for_extcodecopy(*is_warm, *dst_offset, *copy_size, None);
-
-            test_root(&testing_data);
-            // test_internal(&testing_data);
-        }
-    }
-
-    #[test]
-    fn test_oog_memory_copy_max_expanded_address() {
-        // 0xffffffff1 + 0xffffffff0 = 0x1fffffffe1
-        // > MAX_EXPANDED_MEMORY_ADDRESS (0x1fffffffe0)
-        test_for_edge_memory_size(0xffffffff1, 0xffffffff0);
+    /// Gas to run `n_pushes` constants and pay the constant cost of the host call.
+    fn gas_before_dynamic_cost(n_pushes: u64, constant_gas_cost: GasCost) -> u64 {
+        GasCost::TX.0 + OpcodeId::I32Const.constant_gas_cost().0 * n_pushes + constant_gas_cost.0
     }
 
-    #[test]
-    fn test_oog_memory_copy_max_u64_address() {
-        test_for_edge_memory_size(u64::MAX, u64::MAX);
-    }
-
-    struct TestingData {
-        bytecode: Bytecode,
-        gas_cost: u64,
-    }
-
-    impl TestingData {
-        pub fn new_for_common_opcode(
-            opcode: OpcodeId,
-            dst_offset: u64,
-            copy_size: u64,
-            gas_cost: Option<u64>,
-        ) -> Self {
-            let mut bytecode = Bytecode::default();
-            let memory_offset = bytecode.fill_default_global_data( rand_word().to_be_bytes().to_vec());
-            bytecode_internal! {bytecode,
-                I32Const[copy_size]
-                I32Const[memory_offset]
-                I32Const[dst_offset]
-                .write_op(opcode)
-            };
-
-            let gas_cost = gas_cost.unwrap_or_else(|| {
-                let memory_word_size = (dst_offset + copy_size + 31) / 32;
-
-                OpcodeId::I32Const.constant_gas_cost().0 * 3
-                    + opcode.constant_gas_cost().0
-                    + memory_copier_gas_cost(0, memory_word_size, copy_size, GasCost::COPY.as_u64())
-            });
-
-            Self { bytecode, gas_cost }
-        }
-
-        pub fn new_for_extcodecopy(
-            is_warm: bool,
-            dst_offset: u64,
-            copy_size: u64,
-            gas_cost: Option<u64>,
-        ) -> Self {
-            let external_address = MOCK_ACCOUNTS[4];
-
-            let mut bytecode = Bytecode::default();
-            let memory_offset = bytecode.fill_default_global_data( rand_word().to_be_bytes().to_vec());
-            let address_offset = bytecode.fill_default_global_data( external_address.to_fixed_bytes().to_vec());
-            bytecode_internal! {bytecode,
-                I32Const[copy_size]
-                I32Const[memory_offset]
-                I32Const[dst_offset]
-                I32Const[address_offset]
-                EXTCODECOPY
-            };
-
-            if is_warm {
-                bytecode_internal! {bytecode,
-                    I32Const[copy_size]
-                    I32Const[memory_offset]
-                    I32Const[dst_offset]
-                    I32Const[address_offset]
-                    EXTCODECOPY
-                };
-            }
-
-            let gas_cost = gas_cost.unwrap_or_else(|| {
-                let memory_word_size = (dst_offset + copy_size + 31) / 32;
-
-                let gas_cost = OpcodeId::PUSH32.constant_gas_cost().0 * 4
-                    + GasCost::COLD_ACCOUNT_ACCESS.0
-                    + memory_copier_gas_cost(
-                        0,
-                        memory_word_size,
-                        copy_size,
-                        GasCost::COPY.as_u64(),
-                    );
-
-                if is_warm {
-                    gas_cost
-                        + OpcodeId::PUSH32.constant_gas_cost().0 * 4
-                        + GasCost::WARM_ACCESS.0
-                        + memory_copier_gas_cost(
-                            memory_word_size,
-                            memory_word_size,
-                            copy_size,
-                            GasCost::COPY.as_u64(),
-                        )
-                } else {
-                    gas_cost
-                }
-            });
-
-            Self { bytecode, gas_cost }
-        }
-    }
-
-    fn test_root(testing_data: &TestingData) {
-        let gas_cost = GasCost::TX
-            .0
-            // Decrease expected gas cost (by 1) to trigger out of gas error.
-            .checked_add(testing_data.gas_cost - 1)
-            .unwrap_or(MOCK_BLOCK_GAS_LIMIT);
-        let gas_cost = if gas_cost > MOCK_BLOCK_GAS_LIMIT {
-            MOCK_BLOCK_GAS_LIMIT
-        } else {
-            gas_cost
-        };
-
+    fn test_root(code: Bytecode, gas: u64) {
         let ctx = TestContext::<2, 1>::new(
             None,
-            account_0_code_account_1_no_code(testing_data.bytecode.clone()),
+            account_0_code_account_1_no_code(code),
             |mut txs, accs| {
                 txs[0]
                     .from(accs[1].address)
                     .to(accs[0].address)
-                    .gas(gas_cost.into());
+                    .gas(Word::from(gas));
             },
-            |block, _tx| block.number(0xcafe_u64),
+            |block, _tx| block,
         )
         .unwrap();
 
@@ -426,77 +320,86 @@ mod tests {
             .run();
     }
 
-    fn test_internal(testing_data: &TestingData) {
-        let (addr_a, addr_b) = (MOCK_ACCOUNTS[0], MOCK_ACCOUNTS[1]);
-
-        // code B gets called by code A, so the call is an internal call.
-        let code_b = testing_data.bytecode.clone();
-        let gas_cost_b = testing_data.gas_cost;
-
-        // Code A calls code B.
-        let code_a = bytecode! {
-            // populate memory in A's context.
-            PUSH8(U256::from_big_endian(&rand_bytes(8)))
-            PUSH1(0x00) // offset
-            MSTORE
-            // call ADDR_B.
-            PUSH1(0x00) // retLength
-            PUSH1(0x00) // retOffset
-            PUSH32(0x00) // argsLength
-            PUSH32(0x20) // argsOffset
-            PUSH1(0x00) // value
-            PUSH32(addr_b.to_word()) // addr
-            // Decrease expected gas cost (by 1) to trigger out of gas error.
-            PUSH32(gas_cost_b - 1) // gas
-            CALL
-            STOP
-        };
+    #[test]
+    fn test_oog_memory_copy_for_common_opcodes() {
+        for opcode in [OpcodeId::CALLDATACOPY, OpcodeId::CODECOPY] {
+            let mut code = Bytecode::default();
+            bytecode_internal! {code,
+                I32Const[0x100] // dst_offset
+                I32Const[0] // src_offset
+                I32Const[COPY_SIZE]
+            };
+            code.evm_call(opcode);
+            test_root(code, gas_before_dynamic_cost(3, opcode.constant_gas_cost()));
+        }
+    }
 
-        let ctx = TestContext::<3, 1>::new(
-            None,
-            |accs| {
-                accs[0].address(addr_b).code(code_b);
-                accs[1].address(addr_a).code(code_a);
-                accs[2].address(MOCK_ACCOUNTS[2]).balance(eth(10));
-            },
-            |mut txs, accs| {
-                txs[0].from(accs[2].address).to(accs[1].address);
-            },
-            |block, _tx| block,
-        )
-        .unwrap();
+    #[test]
+    fn test_oog_memory_copy_for_returndatacopy() {
+        let mut code = Bytecode::default();
+        bytecode_internal! {code,
+            I32Const[COPY_SIZE]
+            I32Const[0] // src_offset
+            I32Const[0x100] // dst_offset
+        };
+        code.evm_call(OpcodeId::RETURNDATACOPY);
+        test_root(
+            code,
+            gas_before_dynamic_cost(3, OpcodeId::RETURNDATACOPY.constant_gas_cost()),
+        );
+    }
 
-        CircuitTestBuilder::new_from_test_ctx(ctx)
-            .params(CircuitsParams {
-                max_copy_rows: 1750,
-                ..Default::default()
-            })
-            .run();
+    #[test]
+    fn test_oog_memory_copy_for_extcodecopy() {
+        let mut code = Bytecode::default();
+        let address_offset =
+            code.fill_default_global_data(MOCK_ACCOUNTS[4].to_fixed_bytes().to_vec());
+        bytecode_internal! {code,
+            I32Const[address_offset]
+            I32Const[0x100] // dst_offset
+            I32Const[0] // code_offset
+            I32Const[COPY_SIZE]
+        };
+        code.evm_call(OpcodeId::EXTCODECOPY);
+        test_root(code, gas_before_dynamic_cost(4, GasCost::COLD_ACCOUNT_ACCESS));
     }
 
-    fn test_for_edge_memory_size(dst_offset: u64, copy_size: u64) {
-        TESTING_COMMON_OPCODES.iter().for_each(|opcode| {
-            let testing_data = TestingData::new_for_common_opcode(
-                *opcode,
-                dst_offset,
-                copy_size,
-                Some(MOCK_BLOCK_GAS_LIMIT),
-            );
+    #[test]
+    fn test_oog_memory_copy_max_expanded_address() {
+        // 0xfffffff1 + 0xfffffff0 = 0x1ffffffe1
+        // > 65536 pages, the whole wasm32 address space
+        test_for_edge_memory_size(0xfffffff1, 0xfffffff0);
+    }
 
-            test_root(&testing_data);
-            test_internal(&testing_data);
-        });
+    #[test]
+    fn test_oog_memory_copy_max_u64_address() {
+        // The operands are i32, so `u32::MAX` is the widest offset and size a
+        // wasm contract can pass.
+        test_for_edge_memory_size(u32::MAX as u64, u32::MAX as u64);
+    }
 
-        [false, true].into_iter().for_each(|is_warm| {
-            let testing_data = TestingData::new_for_extcodecopy(
-                is_warm,
-                dst_offset,
-                copy_size,
-                Some(MOCK_BLOCK_GAS_LIMIT),
-            );
+    fn test_for_edge_memory_size(dst_offset: u64, copy_size: u64) {
+        for opcode in [OpcodeId::CALLDATACOPY, OpcodeId::CODECOPY] {
+            let mut code = Bytecode::default();
+            bytecode_internal! {code,
+                I32Const[dst_offset]
+                I32Const[0] // src_offset
+                I32Const[copy_size]
+            };
+            code.evm_call(opcode);
+            test_root(code, MOCK_BLOCK_GAS_LIMIT);
+        }
 
-            test_root(&testing_data);
-            test_internal(&testing_data);
-        });
+        let mut code = Bytecode::default();
+        let address_offset =
+            code.fill_default_global_data(MOCK_ACCOUNTS[4].to_fixed_bytes().to_vec());
+        bytecode_internal! {code,
+            I32Const[address_offset]
+            I32Const[dst_offset]
+            I32Const[0] // code_offset
+            I32Const[copy_size]
+        };
+        code.evm_call(OpcodeId::EXTCODECOPY);
+        test_root(code, MOCK_BLOCK_GAS_LIMIT);
     }
 }
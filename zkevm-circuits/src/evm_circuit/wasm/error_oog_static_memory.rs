@@ -4,35 +4,31 @@ use crate::{
         param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
         util::{
-            math_gadget::{IsEqualGadget, IsZeroGadget, RangeCheckGadget},
-            memory_gadget::{address_high, address_low, MemoryExpansionGadget},
-            CachedRegion, Cell, Word,
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::{IsEqualGadget, LtGadget},
+            memory_gadget::MemoryExpansionGadget,
+            CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
-use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
-use halo2_proofs::plonk::Error;
-use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+use eth_types::{evm_types::OpcodeId, Field};
+use halo2_proofs::{circuit::Value, plonk::Error};
 
+/// Gadget for MLOAD, MSTORE and MSTORE8 running out of gas while expanding
+/// the memory pages up to the end of the accessed word (or byte). Wasm loads
+/// and stores never expand the memory, they trap out of bounds instead, so
+/// only traces reporting these opcodes reach this state.
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorOOGStaticMemoryGadget<F> {
     opcode: Cell<F>,
-    address: Word<F>,
-    address_in_range: IsZeroGadget<F>,
-    // Allow memory size to expand to 5 bytes, because memory address could be
-    // at most 2^40 - 1, after constant division by 32, the memory word size
-    // then could be at most 2^35 - 1.
-    // So generic N_BYTES_MEMORY_WORD_SIZE for MemoryExpansionGadget needs to
-    // be larger by 1 than normal usage (to be 5), to be able to contain
-    // number up to 2^35 - 1.
-    memory_expansion: MemoryExpansionGadget<F, 1, { N_BYTES_MEMORY_WORD_SIZE + 1 }>,
-    // Even memory size at most could be 2^35 - 1, the qudratic part of memory
-    // expansion gas cost could be at most 2^61 - 2^27, due to the constant
-    // division by 512, which still fits in 8 bytes.
-    insufficient_gas: RangeCheckGadget<F, N_BYTES_GAS>,
+    address: Cell<F>,
     is_mstore8: IsEqualGadget<F>,
+    memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
+    insufficient_gas: LtGadget<F, N_BYTES_GAS>,
+    common_error_gadget: CommonErrorGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
@@ -40,49 +36,50 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
 
     const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasStaticMemoryExpansion;
 
-    // Support other OOG due to pure memory including CREATE, RETURN and REVERT
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
+        cb.require_in_set(
+            "ErrorOutOfGasStaticMemoryExpansion opcode must be MLOAD, MSTORE or MSTORE8",
+            opcode.expr(),
+            vec![
+                OpcodeId::MLOAD.expr(),
+                OpcodeId::MSTORE.expr(),
+                OpcodeId::MSTORE8.expr(),
+            ],
+        );
 
-        // Query address by a full word
-        let address = cb.query_word_rlc();
+        let address = cb.query_cell();
+        cb.stack_pop(address.expr());
 
         // Check if this is an MSTORE8
         let is_mstore8 = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::MSTORE8.expr());
-        let is_not_mstore8 = 1.expr() - is_mstore8.expr();
 
         // Get the next memory size and the gas cost for this memory access
         let memory_expansion = MemoryExpansionGadget::construct(
             cb,
-            [address_low::expr(&address) + 1.expr() + (is_not_mstore8 * 31.expr())],
+            [address.expr() + 1.expr() + (1.expr() - is_mstore8.expr()) * 31.expr()],
         );
 
-        // Check if the memory address is too large
-        let address_in_range = IsZeroGadget::construct(cb, address_high::expr(&address));
-        // Check if the amount of gas available is less than the amount of gas
-        // required
-        let insufficient_gas = cb.condition(address_in_range.expr(), |cb| {
-            RangeCheckGadget::construct(
-                cb,
-                OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost()
-                    - cb.curr.state.gas_left.expr(),
-            )
-        });
-
-        // Pop the address from the stack
-        // We still have to do this to verify the correctness of `address`
-        cb.stack_pop(address.expr());
+        let insufficient_gas = LtGadget::construct(
+            cb,
+            cb.curr.state.gas_left.expr(),
+            OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost(),
+        );
+        cb.require_equal(
+            "gas left is less than gas required",
+            insufficient_gas.expr(),
+            1.expr(),
+        );
 
-        // TODO: Use ContextSwitchGadget to switch call context to caller's and
-        // consume all gas_left.
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 3.expr());
 
         Self {
             opcode,
             address,
-            address_in_range,
+            is_mstore8,
             memory_expansion,
             insufficient_gas,
-            is_mstore8,
+            common_error_gadget,
         }
     }
 
@@ -92,17 +89,17 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
 
-        // Inputs/Outputs
-        let address = block.rws[step.rw_indices[0]].stack_value();
+        let address = block.rws[step.rw_indices[0]].stack_value().as_u64();
         self.address
-            .assign(region, offset, Some(address.to_le_bytes()))?;
+            .assign(region, offset, Value::known(F::from(address)))?;
 
-        // Check if this is an MSTORE8
         let is_mstore8 = self.is_mstore8.assign(
             region,
             offset,
@@ -110,27 +107,93 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
             F::from(OpcodeId::MSTORE8.as_u64()),
         )?;
 
-        // Address in range check
-        self.address_in_range.assign(
+        let (_, memory_expansion_gas) = self.memory_expansion.assign(
             region,
             offset,
-            address_high::value::<F>(address.to_le_bytes()),
+            step.memory_word_size(),
+            [address + if is_mstore8 == F::one() { 1 } else { 32 }],
         )?;
 
-        // Memory expansion
-        self.memory_expansion.assign(
+        self.insufficient_gas.assign(
             region,
             offset,
-            step.memory_word_size(),
-            [address_low::value(address.to_le_bytes())
-                + if is_mstore8 == F::one() { 1 } else { 32 }],
+            F::from(step.gas_left),
+            F::from(OpcodeId::MLOAD.constant_gas_cost().0 + memory_expansion_gas),
         )?;
 
-        // Gas insufficient check
-        // Get `gas_available` variable here once it's available
-        self.insufficient_gas
-            .assign(region, offset, F::from(step.gas_cost - step.gas_left))?;
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 3)?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::{
+        circuit_input_builder::ExecState,
+        error::{ExecError, OogError},
+        mock::BlockData,
+    };
+    use eth_types::{
+        bytecode::WASM_PAGE_SIZE,
+        bytecode_internal,
+        evm_types::{GasCost, OpcodeId},
+        geth_types::GethData,
+        Bytecode, Word,
+    };
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    /// Stores the loaded word in the last 32 bytes of the first page, with one gas
+    /// less than the constant cost, which CALLDATALOAD shares with MLOAD.
+    fn calldataload_bytecode() -> Bytecode {
+        let mut code = Bytecode::default();
+        bytecode_internal! {code,
+            I32Const[WASM_PAGE_SIZE - 32] // dest_offset
+            I32Const[0] // offset
+        };
+        code.evm_call(OpcodeId::CALLDATALOAD);
+        code
+    }
+
+    fn test_ctx(code: Bytecode) -> TestContext<2, 1> {
+        let gas = GasCost::TX.0
+            + OpcodeId::I32Const.constant_gas_cost().0 * 2
+            + OpcodeId::CALLDATALOAD.constant_gas_cost().0
+            - 1;
+        TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(gas));
+            },
+            |block, _tx| block,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_oog_static_memory_is_not_reported_for_host_calls() {
+        // Wasm has no MLOAD, MSTORE or MSTORE8 host function, and the host calls
+        // storing a fixed size word never expand the memory, so running out of gas
+        // on them is a constant gas error.
+        let block: GethData = test_ctx(calldataload_bytecode()).into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::CALLDATALOAD))
+            .unwrap();
+        assert_eq!(step.error, Some(ExecError::OutOfGas(OogError::Constant)));
+
+        CircuitTestBuilder::new_from_test_ctx(test_ctx(calldataload_bytecode())).run();
+    }
+}
@@ -0,0 +1,90 @@
+use crate::evm_circuit::{
+    execution::ExecutionGadget,
+    step::ExecutionState,
+    util::{
+        common_gadget::CommonErrorGadget, constraint_builder::ConstrainBuilderCommon,
+        CachedRegion, Cell,
+    },
+    witness::{Block, Call, ExecStep, Transaction},
+};
+use eth_types::{evm_types::OpcodeId, Field};
+use gadgets::util::Expr;
+use halo2_proofs::{circuit::Value, plonk::Error};
+use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+
+/// `unreachable` traps unconditionally.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorUnreachableGadget<F> {
+    opcode: Cell<F>,
+    common_error_gadget: CommonErrorGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for ErrorUnreachableGadget<F> {
+    const NAME: &'static str = "ErrorUnreachable";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorUnreachable;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "ErrorUnreachable only happens in UNREACHABLE",
+            opcode.expr(),
+            OpcodeId::Unreachable.expr(),
+        );
+
+        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 2.expr());
+
+        Self {
+            opcode,
+            common_error_gadget,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, 2)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eth_types::{bytecode, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_unreachable() {
+        run_test(bytecode! {
+            Unreachable
+        });
+    }
+
+    #[test]
+    fn test_unreachable_with_stack() {
+        run_test(bytecode! {
+            I32Const[1]
+            I64Const[2]
+            Unreachable
+        });
+    }
+}
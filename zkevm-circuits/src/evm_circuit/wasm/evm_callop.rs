@@ -1,8 +1,9 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_U64},
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_U64, N_BYTES_WORD},
         step::ExecutionState,
+        table::{FixedTableTag, Lookup},
         util::{
             and,
             common_gadget::{CommonCallGadget, TransferGadget},
@@ -13,23 +14,28 @@ use crate::{
             math_gadget::{
                 ConstantDivisionGadget, IsZeroGadget, LtGadget, LtWordGadget, MinMaxGadget,
             },
-            memory_gadget::{CommonMemoryAddressGadget, MemoryAddressGadget},
-            not, or, select, CachedRegion, Cell, Word,
+            memory_gadget::{CommonMemoryAddressGadget, MemoryAddress64Gadget},
+            not, or,
+            precompile_gadget::PrecompileGadget,
+            rlc, select, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     table::{AccountFieldTag, CallContextFieldTag},
     util::Expr,
 };
-use bus_mapping::{evm::OpcodeId, precompile::is_precompiled};
-use eth_types::{evm_types::GAS_STIPEND_CALL_WITH_VALUE, Field, StackWord, ToAddress, ToLittleEndian, ToScalar, ToU256, U256, U64};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId, precompile::is_precompiled};
+use eth_types::{evm_types::GAS_STIPEND_CALL_WITH_VALUE, Field, ToAddress, ToLittleEndian, ToScalar, U256, U64};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget for call related opcodes. It supports `OpcodeId::CALL`,
 /// `OpcodeId::CALLCODE`, `OpcodeId::DELEGATECALL` and `OpcodeId::STATICCALL`.
 /// both for successful and failure(insufficient balance error) cases.
+///
+/// The `_evm_call*` host functions take pointers into linear memory, the
+/// callee address and the transferred value are read from there and the call
+/// status is written back to the status pointer.
 #[derive(Clone, Debug)]
-
 pub(crate) struct EvmCallOpGadget<F> {
     opcode: Cell<F>,
     is_call: IsZeroGadget<F>,
@@ -42,7 +48,7 @@ pub(crate) struct EvmCallOpGadget<F> {
     current_caller_address: Cell<F>,
     is_static: Cell<F>,
     depth: Cell<F>,
-    call: CommonCallGadget<F, MemoryAddressGadget<F>, true>,
+    call: CommonCallGadget<F, MemoryAddress64Gadget<F>, true>,
     current_value: Word<F>,
     is_warm: Cell<F>,
     is_warm_prev: Cell<F>,
@@ -55,13 +61,18 @@ pub(crate) struct EvmCallOpGadget<F> {
     is_depth_ok: LtGadget<F, N_BYTES_U64>,
     one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
     capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
-    is_code_address_zero: IsZeroGadget<F>,
-    is_precompile_lt: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
     // FIXME: free cells, only used in empty codehash (empty account and precompiles)
     step_gas_cost: Cell<F>,
-    // used only in precompiled contracts
-    return_data_len: Cell<F>,
+    // to handle precompile calls
+    is_code_address_zero: IsZeroGadget<F>,
+    is_precompile_lt: LtGadget<F, N_BYTES_ACCOUNT_ADDRESS>,
+    precompile_gadget: PrecompileGadget<F>,
+    precompile_return_length: Cell<F>,
+    precompile_return_length_zero: IsZeroGadget<F>,
     return_data_copy_size: MinMaxGadget<F, N_BYTES_GAS>,
+    input_bytes_rlc: Cell<F>,  // input bytes to precompile call.
+    output_bytes_rlc: Cell<F>, // output bytes from precompile call.
+    return_bytes_rlc: Cell<F>, // bytes returned to caller from precompile call.
 }
 
 impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
@@ -71,7 +82,17 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
-        cb.opcode_lookup(opcode.expr(), 1.expr());
+        cb.add_lookup(
+            "Responsible opcode lookup",
+            Lookup::Fixed {
+                tag: FixedTableTag::ResponsibleOpcode.expr(),
+                values: [
+                    Self::EXECUTION_STATE.as_u64().expr(),
+                    opcode.expr(),
+                    0.expr(),
+                ],
+            },
+        );
         let is_call = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALL.expr());
         let is_callcode = IsZeroGadget::construct(cb, opcode.expr() - OpcodeId::CALLCODE.expr());
         let is_delegatecall =
@@ -98,7 +119,7 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
             )
         });
 
-        let call_gadget: CommonCallGadget<F, MemoryAddressGadget<F>, true> =
+        let call_gadget: CommonCallGadget<F, MemoryAddress64Gadget<F>, true> =
             CommonCallGadget::construct(
                 cb,
                 is_call.expr(),
@@ -127,6 +148,7 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
         // Add callee to access list
         let is_warm = cb.query_bool();
         let is_warm_prev = cb.query_bool();
+        cb.require_true("callee is warm after the call", is_warm.expr());
         cb.account_access_list_write(
             tx_id.expr(),
             call_gadget.callee_address_expr(),
@@ -173,27 +195,44 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
             not::expr(is_insufficient_balance.expr()),
         ]);
 
-        // stack write is zero when is_insufficient_balance is true
+        // the status written back to linear memory is zero when the precheck fails
         cb.condition(not::expr(is_precheck_ok.expr()), |cb| {
             cb.require_zero(
-                "stack write result is zero when is_insufficient_balance is true",
+                "call status is zero when is_insufficient_balance is true",
                 call_gadget.is_success.expr(),
             );
         });
 
+        // whether the call is to a precompiled contract.
+        // precompile contracts are stored from address 0x01 to 0x09.
         let is_code_address_zero = IsZeroGadget::construct(cb, call_gadget.callee_address_expr());
         let is_precompile_lt =
-            LtGadget::construct(cb, call_gadget.callee_address_expr(), 0xA.expr());
-        let is_precompile = and::expr(&[
+            LtGadget::construct(cb, call_gadget.callee_address_expr(), 0x0A.expr());
+        let is_precompile = and::expr([
             not::expr(is_code_address_zero.expr()),
             is_precompile_lt.expr(),
         ]);
+        let precompile_return_length = cb.query_cell();
+        let precompile_return_length_zero =
+            IsZeroGadget::construct(cb, precompile_return_length.expr());
+        let return_data_copy_size = MinMaxGadget::construct(
+            cb,
+            precompile_return_length.expr(),
+            call_gadget.rd_address.length(),
+        );
+        // The caller's call data is always read, the result is only written to
+        // the callee's and the caller's memory when the call succeeds.
+        let precompile_memory_rws = call_gadget.cd_address.has_length()
+            * (call_gadget.cd_address.length()
+                + call_gadget.is_success.expr()
+                    * (precompile_return_length.expr() + return_data_copy_size.min()));
 
         // Verify transfer only for CALL opcode in the successful case.  If value == 0,
         // skip the transfer (this is necessary for non-existing accounts, which
         // will not be crated when value is 0 and so the callee balance lookup
         // would be invalid).
-        let transfer = cb.condition(and::expr(&[is_call.expr(), is_precheck_ok.expr()]), |cb| {
+        let is_transfer = and::expr([is_call.expr(), is_precheck_ok.expr()]);
+        let transfer = cb.condition(is_transfer.expr(), |cb| {
             TransferGadget::construct(
                 cb,
                 caller_address.expr(),
@@ -207,6 +246,8 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
                 &mut callee_reversion_info,
             )
         });
+        let transfer_rwc_delta = is_transfer.expr() * transfer.rw_delta();
+        let transfer_reversible_w_delta = is_transfer * transfer.reversible_w_delta();
 
         // For CALLCODE opcode, verify caller balance is greater than or equal to stack
         // `value` in successful case. that is `is_insufficient_balance` is false.
@@ -227,32 +268,230 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
         // Sum up and verify gas cost.
         // Only CALL opcode could invoke transfer to make empty account into non-empty.
         let gas_cost = call_gadget.gas_cost_expr(is_warm_prev.expr(), is_call.expr());
-        // Apply EIP 150
+        // Apply EIP 150, the requested gas is an i64 so it is always capped
         let gas_available = cb.curr.state.gas_left.expr() - gas_cost.clone();
         let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
         let all_but_one_64th_gas = gas_available - one_64th_gas.quotient();
         let capped_callee_gas_left =
-            MinMaxGadget::construct(cb, call_gadget.gas_expr(), all_but_one_64th_gas.clone());
-        let callee_gas_left = select::expr(
-            call_gadget.gas_is_u64.expr(),
-            capped_callee_gas_left.min(),
-            all_but_one_64th_gas,
-        );
-
-        let return_data_len = cb.query_cell();
-        let return_data_copy_size = MinMaxGadget::<F, N_BYTES_GAS>::construct(
-            cb,
-            return_data_len.expr(),
-            call_gadget.rd_address.length(),
-        );
-        let precompile_memory_writes =
-            is_precompile.expr() * (return_data_len.expr() + return_data_copy_size.min());
+            MinMaxGadget::construct(cb, call_gadget.gas_expr(), all_but_one_64th_gas);
+        let callee_gas_left = capped_callee_gas_left.min();
 
+        // The host function pops the gas, the callee and the value pointers, the
+        // call and return data ranges and the status pointer.
         let stack_pointer_delta =
-            select::expr(is_call.expr() + is_callcode.expr(), 6.expr(), 5.expr());
+            select::expr(is_call.expr() + is_callcode.expr(), 8.expr(), 7.expr());
         let step_gas_cost = cb.query_cell();
         let memory_expansion = call_gadget.memory_expansion.clone();
 
+        // Call context reads (+6), the arguments popped from the stack with the
+        // callee address read from memory (+27), the status write (+1), the callee
+        // code hash (+1) and access list (+1), the callee's reversion info (+2) and
+        // the caller balance (+1).
+        //
+        // CALL and CALLCODE pop the value pointer and read the value from memory
+        // (+33), DELEGATECALL reads the current caller address and value (+2).
+        let common_rwc_delta = 39.expr()
+            + (is_call.expr() + is_callcode.expr()) * 33.expr()
+            + is_delegatecall.expr() * 2.expr()
+            + transfer_rwc_delta.clone();
+
+        // handle precompile calls.
+        let (precompile_gadget, input_bytes_rlc, output_bytes_rlc, return_bytes_rlc) = cb
+            .condition(
+                and::expr([is_precompile.expr(), is_precheck_ok.expr()]),
+                |cb| {
+                    cb.require_equal(
+                        "Callee has no code for precompile",
+                        no_callee_code.expr(),
+                        true.expr(),
+                    );
+
+                    // Write to callee's context.
+                    for (field_tag, value) in [
+                        (
+                            CallContextFieldTag::IsSuccess,
+                            call_gadget.is_success.expr(),
+                        ),
+                        (
+                            CallContextFieldTag::CalleeAddress,
+                            call_gadget.callee_address_expr(),
+                        ),
+                        (CallContextFieldTag::CallerId, cb.curr.state.call_id.expr()),
+                        (
+                            CallContextFieldTag::CallDataOffset,
+                            call_gadget.cd_address.offset(),
+                        ),
+                        (
+                            CallContextFieldTag::CallDataLength,
+                            call_gadget.cd_address.length(),
+                        ),
+                        (
+                            CallContextFieldTag::ReturnDataOffset,
+                            call_gadget.rd_address.offset(),
+                        ),
+                        (
+                            CallContextFieldTag::ReturnDataLength,
+                            call_gadget.rd_address.length(),
+                        ),
+                    ] {
+                        cb.call_context_lookup(
+                            true.expr(),
+                            Some(callee_call_id.expr()),
+                            field_tag,
+                            value,
+                        );
+                    } // rwc_delta += 7 for precompile
+
+                    // Save caller's call state
+                    for (field_tag, value) in [
+                        (
+                            CallContextFieldTag::ProgramCounter,
+                            cb.curr.state.program_counter.expr() + 1.expr(),
+                        ),
+                        (
+                            CallContextFieldTag::StackPointer,
+                            cb.curr.state.stack_pointer.expr() + stack_pointer_delta.expr(),
+                        ),
+                        (
+                            CallContextFieldTag::GasLeft,
+                            cb.curr.state.gas_left.expr() - step_gas_cost.expr(),
+                        ),
+                        (
+                            CallContextFieldTag::MemorySize,
+                            memory_expansion.next_memory_word_size(),
+                        ),
+                        (
+                            CallContextFieldTag::ReversibleWriteCounter,
+                            cb.curr.state.reversible_write_counter.expr() + 1.expr(),
+                        ),
+                        (CallContextFieldTag::LastCalleeId, callee_call_id.expr()),
+                        (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
+                        (
+                            CallContextFieldTag::LastCalleeReturnDataLength,
+                            precompile_return_length.expr(),
+                        ),
+                    ] {
+                        cb.call_context_lookup(true.expr(), None, field_tag, value);
+                    } // rwc_delta += 8 for precompile
+
+                    // copy table lookup to verify the copying of bytes:
+                    // - from caller's memory (`call_data_length` bytes starting at
+                    //   `call_data_offset`)
+                    // - to the precompile input.
+                    let input_bytes_rlc = cb.condition(call_gadget.cd_address.has_length(), |cb| {
+                        let input_bytes_rlc = cb.query_cell_phase2();
+                        cb.copy_table_lookup(
+                            cb.curr.state.call_id.expr(),
+                            CopyDataType::Memory.expr(),
+                            callee_call_id.expr(),
+                            5.expr() + call_gadget.callee_address_expr(), // refer u64::from(CopyDataType)
+                            call_gadget.cd_address.offset(),
+                            call_gadget.cd_address.address(),
+                            0.expr(),
+                            call_gadget.cd_address.length(),
+                            input_bytes_rlc.expr(),
+                            call_gadget.cd_address.length(), // reads
+                        ); // rwc_delta += `call_gadget.cd_address.length()` for precompile
+                        input_bytes_rlc
+                    });
+
+                    // copy table lookup to verify the precompile result.
+                    // - from precompiled contract.
+                    // - to the callee's memory (starting at `0`).
+                    let output_bytes_rlc = cb.condition(
+                        and::expr([
+                            call_gadget.is_success.expr(),
+                            call_gadget.cd_address.has_length(),
+                            not::expr(precompile_return_length_zero.expr()),
+                        ]),
+                        |cb| {
+                            let output_bytes_rlc = cb.query_cell_phase2();
+                            cb.copy_table_lookup(
+                                callee_call_id.expr(),
+                                5.expr() + call_gadget.callee_address_expr(), // refer u64::from(CopyDataType)
+                                callee_call_id.expr(),
+                                CopyDataType::Memory.expr(),
+                                0.expr(),
+                                precompile_return_length.expr(),
+                                0.expr(),
+                                precompile_return_length.expr(),
+                                output_bytes_rlc.expr(),
+                                precompile_return_length.expr(), // writes.
+                            ); // rwc_delta += `precompile_return_length` for precompile
+                            output_bytes_rlc
+                        },
+                    );
+
+                    // copy table lookup to verify the copying of bytes if the precompile call was
+                    // successful.
+                    // - from precompile (min(rd_length, precompile_return_length) bytes)
+                    // - to caller's memory (min(rd_length, precompile_return_length) bytes starting
+                    //   at `return_data_offset`).
+                    let return_bytes_rlc = cb.condition(
+                        and::expr([
+                            call_gadget.is_success.expr(),
+                            call_gadget.cd_address.has_length(),
+                            call_gadget.rd_address.has_length(),
+                            not::expr(precompile_return_length_zero.expr()),
+                        ]),
+                        |cb| {
+                            let return_bytes_rlc = cb.query_cell_phase2();
+                            cb.copy_table_lookup(
+                                callee_call_id.expr(),
+                                5.expr() + call_gadget.callee_address_expr(), // refer u64::from(CopyDataType)
+                                cb.curr.state.call_id.expr(),
+                                CopyDataType::Memory.expr(),
+                                0.expr(),
+                                return_data_copy_size.min(),
+                                call_gadget.rd_address.offset(),
+                                return_data_copy_size.min(),
+                                return_bytes_rlc.expr(),
+                                return_data_copy_size.min(), // writes
+                            ); // rwc_delta += `return_data_copy_size.min()` for precompile
+                            return_bytes_rlc
+                        },
+                    );
+
+                    // +15 call context lookups for precompile.
+                    cb.require_step_state_transition(StepStateTransition {
+                        rw_counter: Delta(
+                            common_rwc_delta.clone() + 15.expr() + precompile_memory_rws,
+                        ),
+                        call_id: To(callee_call_id.expr()),
+                        is_root: To(false.expr()),
+                        is_create: To(false.expr()),
+                        code_hash: To(cb.empty_code_hash_rlc()),
+                        program_counter: Delta(1.expr()),
+                        stack_pointer: Delta(stack_pointer_delta.expr()),
+                        gas_left: To(callee_gas_left.expr()),
+                        memory_word_size: To(0.expr()),
+                        reversible_write_counter: To(0.expr()),
+                        ..StepStateTransition::default()
+                    });
+
+                    (
+                        PrecompileGadget::construct(
+                            cb,
+                            call_gadget.is_success.expr(),
+                            call_gadget.callee_address_expr(),
+                            cb.curr.state.call_id.expr(),
+                            call_gadget.cd_address.offset(),
+                            call_gadget.cd_address.length(),
+                            call_gadget.rd_address.offset(),
+                            call_gadget.rd_address.length(),
+                            precompile_return_length.expr(),
+                            input_bytes_rlc.expr(),
+                            output_bytes_rlc.expr(),
+                            return_bytes_rlc.expr(),
+                        ),
+                        input_bytes_rlc,
+                        output_bytes_rlc,
+                        return_bytes_rlc,
+                    )
+                },
+            );
+
+        // handle calls to accounts with no code.
         cb.condition(
             and::expr([
                 no_callee_code.expr(),
@@ -268,53 +507,18 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
                 ] {
                     cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
                 }
-            },
-        );
-        cb.condition(
-            and::expr([is_precompile.expr(), is_precheck_ok.expr()]),
-            |cb| {
-                // Save caller's call state
-                for (field_tag, value) in [
-                    (CallContextFieldTag::LastCalleeId, callee_call_id.expr()),
-                    (CallContextFieldTag::LastCalleeReturnDataOffset, 0.expr()),
-                    (
-                        CallContextFieldTag::LastCalleeReturnDataLength,
-                        return_data_len.expr(),
-                    ),
-                ] {
-                    cb.call_context_lookup(true.expr(), None, field_tag, value);
-                }
-            },
-        );
 
-        cb.condition(
-            and::expr([call_gadget.is_empty_code_hash.expr(), is_precheck_ok.expr()]),
-            |cb| {
-                // For CALLCODE opcode, it has an extra stack pop `value` and one account read
-                // for caller balance (+2).
-                //
-                // For DELEGATECALL opcode, it has two extra call context lookups for current
-                // caller address and value (+2).
-                //
-                // No extra lookups for STATICCALL opcode.
-                let transfer_rwc_delta =
-                    is_call.expr() * not::expr(transfer.value_is_zero.expr()) * 2.expr();
-                let rw_counter_delta = 21.expr()
-                    + is_call.expr() * 1.expr()
-                    + transfer_rwc_delta.clone()
-                    + is_callcode.expr()
-                    + is_delegatecall.expr() * 2.expr()
-                    + precompile_memory_writes;
+                // +3 call context lookups for empty accounts.
                 cb.require_step_state_transition(StepStateTransition {
-                    rw_counter: Delta(rw_counter_delta),
+                    rw_counter: Delta(common_rwc_delta.clone() + 3.expr()),
                     program_counter: Delta(1.expr()),
                     stack_pointer: Delta(stack_pointer_delta.expr()),
                     gas_left: Delta(-step_gas_cost.expr()),
-
                     memory_word_size: To(memory_expansion.next_memory_word_size()),
-                    // For CALL opcode, `transfer` invocation has two account write if value is not
-                    // zero.
-                    reversible_write_counter: Delta(1.expr() + transfer_rwc_delta),
+                    // The access list write and the `transfer` writes are reversible.
+                    reversible_write_counter: Delta(
+                        1.expr() + transfer_reversible_w_delta.clone(),
+                    ),
                     ..StepStateTransition::default()
                 });
             },
@@ -331,8 +535,9 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
                 cb.call_context_lookup(true.expr(), None, field_tag, 0.expr());
             }
 
+            // No transfer is made when the precheck fails.
             cb.require_step_state_transition(StepStateTransition {
-                rw_counter: Delta(22.expr()),
+                rw_counter: Delta(common_rwc_delta.clone() + 3.expr()),
                 program_counter: Delta(1.expr()),
                 stack_pointer: Delta(stack_pointer_delta.expr()),
                 gas_left: Delta(
@@ -345,8 +550,13 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
             });
         });
 
+        // handle all other calls.
         cb.condition(
-            and::expr(&[not::expr(no_callee_code.expr()), is_precheck_ok]),
+            and::expr(&[
+                not::expr(no_callee_code.expr()),
+                is_precheck_ok,
+                not::expr(is_precompile.expr()),
+            ]),
             |cb| {
                 // Save caller's call state
                 for (field_tag, value) in [
@@ -425,33 +635,16 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
                 let callee_gas_left = callee_gas_left
                     + call_gadget.has_value.clone() * GAS_STIPEND_CALL_WITH_VALUE.expr();
 
-                // For CALL opcode, it has an extra stack pop `value` (+1) and if the value is
-                // not zero, two account write for `transfer` call (+2).
-                //
-                // For CALLCODE opcode, it has an extra stack pop `value` and one account read
-                // for caller balance (+2).
-                //
-                // For DELEGATECALL opcode, it has two extra call context lookups for current
-                // caller address and value (+2).
-                //
-                // No extra lookups for STATICCALL opcode.
-                let transfer_rwc_delta =
-                    is_call.expr() * not::expr(transfer.value_is_zero.expr()) * 2.expr();
-                let rw_counter_delta = 41.expr()
-                    + is_call.expr() * 1.expr()
-                    + transfer_rwc_delta.clone()
-                    + is_callcode.expr()
-                    + is_delegatecall.expr() * 2.expr();
+                // +5 caller and +18 callee call context writes.
                 cb.require_step_state_transition(StepStateTransition {
-                    rw_counter: Delta(rw_counter_delta),
+                    rw_counter: Delta(common_rwc_delta + 23.expr()),
                     call_id: To(callee_call_id.expr()),
                     is_root: To(false.expr()),
                     is_create: To(false.expr()),
                     code_hash: To(call_gadget.phase2_callee_code_hash.expr()),
                     gas_left: To(callee_gas_left),
-                    // For CALL opcode, `transfer` invocation has two account write if value is not
-                    // zero.
-                    reversible_write_counter: To(transfer_rwc_delta),
+                    // The `transfer` writes are reverted together with the callee.
+                    reversible_write_counter: To(transfer_reversible_w_delta),
                     ..StepStateTransition::new_context()
                 });
             },
@@ -480,11 +673,17 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
             is_depth_ok,
             one_64th_gas,
             capped_callee_gas_left,
+            step_gas_cost,
+            // precompile related fields.
             is_code_address_zero,
             is_precompile_lt,
-            return_data_len,
+            precompile_gadget,
+            precompile_return_length,
+            precompile_return_length_zero,
             return_data_copy_size,
-            step_gas_cost,
+            input_bytes_rlc,
+            output_bytes_rlc,
+            return_bytes_rlc,
         }
     }
 
@@ -497,9 +696,6 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
-        for (_i, _rw) in step.rw_indices.iter().enumerate() {
-            // log::trace!("rw {}th, {:?}", i, block.rws[*rw]);
-        }
         let opcode = step.opcode.unwrap();
         let is_call = opcode == OpcodeId::CALL;
         let is_callcode = opcode == OpcodeId::CALLCODE;
@@ -511,72 +707,72 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
             step.rw_indices[5],
         ]
             .map(|idx| block.rws[idx].call_context_value());
-
-        let value = U256::zero();
-        let callee_address = U256::zero();
+        let read_memory = |index: usize, n: usize| {
+            let bytes = step.rw_indices[index..index + n]
+                .iter()
+                .map(|&idx| block.rws[idx].memory_value())
+                .collect::<Vec<u8>>();
+            U256::from_big_endian(&bytes)
+        };
 
         self.is_depth_ok
             .assign(region, offset, F::from(depth.low_u64()), F::from(1025))?;
 
-        let stack_index = 6;
-
         // This offset is used to change the index offset of `step.rw_indices`.
-        // Since both CALL and CALLCODE have an extra stack pop `value`, and
-        // opcode DELEGATECALL has two extra call context lookups - current
-        // caller address and current value.
-        let mut rw_offset = 0;
+        // Opcode DELEGATECALL has two extra call context lookups - current
+        // caller address and current value, both CALL and CALLCODE have an
+        // extra `value` pointer followed by 32 memory reads.
+        let mut rw_offset = 6;
         let [current_caller_address, current_value] = if is_delegatecall {
             rw_offset += 2;
             [step.rw_indices[6], step.rw_indices[7]].map(|idx| block.rws[idx].call_context_value())
         } else {
             [U256::zero(), U256::zero()]
         };
-        let [gas, callee_address_offset] = [
-            step.rw_indices[stack_index + rw_offset],
-            step.rw_indices[stack_index + 1 + rw_offset],
-        ]
-            .map(|idx| block.rws[idx].stack_value());
-        let is_precompile = is_precompiled(&callee_address.to_address());
-        let value_offset = if is_call || is_callcode {
-            rw_offset += 1;
-            block.rws[step.rw_indices[7 + rw_offset]].stack_value()
+        let [status_offset, rd_length, rd_offset, cd_length, cd_offset] = [0, 1, 2, 3, 4]
+            .map(|i| block.rws[step.rw_indices[rw_offset + i]].stack_value());
+        rw_offset += 5;
+        let (value_offset, value) = if is_call || is_callcode {
+            let value_offset = block.rws[step.rw_indices[rw_offset]].stack_value();
+            let value = read_memory(rw_offset + 1, N_BYTES_WORD);
+            rw_offset += 1 + N_BYTES_WORD;
+            (value_offset, value)
         } else {
-            StackWord::zero()
+            (U64::zero(), U256::zero())
         };
-        let [cd_offset, cd_length, rd_offset, rd_length, status_offset] = [
-            step.rw_indices[stack_index + 2 + rw_offset],
-            step.rw_indices[stack_index + 3 + rw_offset],
-            step.rw_indices[stack_index + 4 + rw_offset],
-            step.rw_indices[stack_index + 5 + rw_offset],
-            step.rw_indices[stack_index + 6 + rw_offset],
-        ]
-            .map(|idx| block.rws[idx].stack_value());
-        // log::trace!("rw_offset {:?}", rw_offset);
-        let callee_code_hash = block.rws[step.rw_indices[13 + rw_offset]]
+        let callee_address_offset = block.rws[step.rw_indices[rw_offset]].stack_value();
+        let callee_address = read_memory(rw_offset + 1, N_BYTES_ACCOUNT_ADDRESS);
+        rw_offset += 1 + N_BYTES_ACCOUNT_ADDRESS;
+        let gas = block.rws[step.rw_indices[rw_offset]].stack_value();
+        let is_success = block.rws[step.rw_indices[rw_offset + 1]].memory_value() as u64;
+        let is_precompile = is_precompiled(&callee_address.to_address());
+
+        let callee_code_hash = block.rws[step.rw_indices[rw_offset + 2]]
             .account_codehash_pair()
             .0;
         let callee_exists = !callee_code_hash.is_zero() || is_precompile;
 
         let (is_warm, is_warm_prev) =
-            block.rws[step.rw_indices[14 + rw_offset]].tx_access_list_value_pair();
+            block.rws[step.rw_indices[rw_offset + 3]].tx_access_list_value_pair();
 
         let [callee_rw_counter_end_of_reversion, callee_is_persistent] = [
-            step.rw_indices[15 + rw_offset],
-            step.rw_indices[16 + rw_offset],
+            step.rw_indices[rw_offset + 4],
+            step.rw_indices[rw_offset + 5],
         ]
             .map(|idx| block.rws[idx].call_context_value());
 
         // check if it is insufficient balance case.
         // get caller balance
-        let (caller_balance, _) = block.rws[step.rw_indices[17 + rw_offset]].account_balance_pair();
+        let (caller_balance, _) = block.rws[step.rw_indices[rw_offset + 6]].account_balance_pair();
+        rw_offset += 7;
         self.caller_balance_word
             .assign(region, offset, Some(caller_balance.to_le_bytes()))?;
 
         self.is_insufficient_balance
-            .assign(region, offset, caller_balance, value.to_u256())?;
+            .assign(region, offset, caller_balance, value)?;
 
         let is_precheck_ok =
-            depth.low_u64() < 1025 && (!(is_call || is_callcode) || caller_balance >= value.to_u256());
+            depth.low_u64() < 1025 && (!(is_call || is_callcode) || caller_balance >= value);
 
         // only call opcode do transfer in sucessful case.
         let (caller_balance_pair, callee_balance_pair) =
@@ -585,37 +781,15 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
                     rw_offset += 1;
                 }
                 let caller_balance_pair =
-                    block.rws[step.rw_indices[18 + rw_offset]].account_balance_pair();
+                    block.rws[step.rw_indices[rw_offset]].account_balance_pair();
                 let callee_balance_pair =
-                    block.rws[step.rw_indices[19 + rw_offset]].account_balance_pair();
+                    block.rws[step.rw_indices[rw_offset + 1]].account_balance_pair();
                 rw_offset += 2;
                 (caller_balance_pair, callee_balance_pair)
             } else {
                 ((U256::zero(), U256::zero()), (U256::zero(), U256::zero()))
             };
 
-        let return_data_len = if is_precompile {
-            let return_data_len_rw = block.rws[step.rw_indices[20 + rw_offset]];
-            assert_eq!(
-                return_data_len_rw.field_tag().unwrap(),
-                CallContextFieldTag::LastCalleeReturnDataLength as u64
-            );
-            return_data_len_rw.call_context_value()
-        } else {
-            0.into()
-        };
-        self.return_data_len.assign(
-            region,
-            offset,
-            Value::known(F::from(return_data_len.as_u64())),
-        )?;
-        self.return_data_copy_size.assign(
-            region,
-            offset,
-            F::from(return_data_len.as_u64()),
-            F::from(rd_length.as_u64()),
-        )?;
-
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
         self.is_call.assign(
@@ -676,16 +850,17 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
             offset,
             gas,
             callee_address_offset,
-            U256::zero(), // todo
+            callee_address,
             value_offset,
-            U256::zero(), // todo
+            value,
             status_offset,
-            U64::zero(), // todo
+            U64::from(is_success),
             cd_offset,
             cd_length,
             rd_offset,
             rd_length,
             region.code_hash(callee_code_hash),
+            step.memory_word_size(),
         )?;
         self.is_warm
             .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
@@ -697,8 +872,8 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
             callee_rw_counter_end_of_reversion.low_u64() as usize,
             callee_is_persistent.low_u64() != 0,
         )?;
-        // conditionally assign
-        if is_precheck_ok && !value.is_zero() {
+        // conditionally assign, `value_is_zero` is needed by every branch
+        if is_call && is_precheck_ok {
             self.transfer.assign(
                 region,
                 offset,
@@ -706,23 +881,19 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
                 callee_balance_pair,
                 value,
             )?;
+        } else {
+            self.transfer
+                .value_is_zero
+                .assign_value(region, offset, region.word_rlc(value))?;
         }
 
-        let mut code_address_bytes = [0; 32];
-        code_address_bytes[0..N_BYTES_ACCOUNT_ADDRESS]
-            .copy_from_slice(&callee_address.to_le_bytes()[0..N_BYTES_ACCOUNT_ADDRESS]);
-        let code_address_bytes = F::from_repr(code_address_bytes).unwrap();
-        self.is_code_address_zero
-            .assign(region, offset, code_address_bytes)?;
-        self.is_precompile_lt
-            .assign(region, offset, code_address_bytes, F::from(0xA))?;
         let has_value = !value.is_zero() && !is_delegatecall;
         let gas_cost = self.call.cal_gas_cost_for_assignment(
             memory_expansion_gas_cost,
             is_warm_prev,
             is_call,
             has_value,
-            !callee_exists,
+            callee_code_hash.is_zero(),
         )?;
         let gas_available = step.gas_left - gas_cost;
         self.step_gas_cost
@@ -735,6 +906,98 @@ impl<F: Field> ExecutionGadget<F> for EvmCallOpGadget<F> {
             F::from(gas.low_u64()),
             F::from(gas_available - gas_available / 64),
         )?;
+
+        // precompile related assignment.
+        let code_address: F = callee_address.to_address().to_scalar().unwrap();
+        self.is_code_address_zero
+            .assign(region, offset, code_address)?;
+        self.is_precompile_lt
+            .assign(region, offset, code_address, 0x0Au64.into())?;
+        let is_precompile_call = is_precompile && is_precheck_ok;
+        if is_precompile_call {
+            self.precompile_gadget.assign(
+                region,
+                offset,
+                callee_address.to_address().0[19].into(),
+            )?;
+        }
+        // the precompile branch starts with 7 callee and 8 caller call context
+        // writes, the last one being the return data length
+        let precompile_return_length = if is_precompile_call {
+            let value_rw = block.rws[step.rw_indices[rw_offset + 14]];
+            assert_eq!(
+                value_rw.field_tag(),
+                Some(CallContextFieldTag::LastCalleeReturnDataLength as u64),
+            );
+            value_rw.call_context_value().low_u64()
+        } else {
+            0
+        };
+        self.precompile_return_length.assign(
+            region,
+            offset,
+            Value::known(F::from(precompile_return_length)),
+        )?;
+        self.precompile_return_length_zero.assign(
+            region,
+            offset,
+            F::from(precompile_return_length),
+        )?;
+        let (_, return_data_copy_size) = self.return_data_copy_size.assign(
+            region,
+            offset,
+            F::from(precompile_return_length),
+            F::from(rd_length.as_u64()),
+        )?;
+
+        let (input_bytes_rlc, output_bytes_rlc, return_bytes_rlc) = if is_precompile_call {
+            let input_length = cd_length.as_usize();
+            let input_bytes_start = rw_offset + 15;
+            let input_bytes_end = input_bytes_start + input_length;
+            let (output_length, return_length) = if input_length > 0 && is_success == 1 {
+                (
+                    precompile_return_length as usize,
+                    return_data_copy_size.get_lower_128() as usize,
+                )
+            } else {
+                (0, 0)
+            };
+            let output_bytes_end = input_bytes_end + output_length;
+            let return_bytes_end = output_bytes_end + return_length;
+            let [input_bytes, output_bytes, return_bytes] = [
+                (input_bytes_start, input_bytes_end),
+                (input_bytes_end, output_bytes_end),
+                (output_bytes_end, return_bytes_end),
+            ]
+                .map(|(start, end)| {
+                    (start..end)
+                        .map(|i| block.rws[step.rw_indices[i]].memory_value())
+                        .collect::<Vec<u8>>()
+                });
+
+            let [input_bytes_rlc, output_bytes_rlc, return_bytes_rlc] =
+                [input_bytes, output_bytes, return_bytes].map(|bytes| {
+                    region
+                        .challenges()
+                        .keccak_input()
+                        .map(|randomness| rlc::value(bytes.iter().rev(), randomness))
+                });
+            (input_bytes_rlc, output_bytes_rlc, return_bytes_rlc)
+        } else {
+            (
+                Value::known(F::zero()),
+                Value::known(F::zero()),
+                Value::known(F::zero()),
+            )
+        };
+
+        self.input_bytes_rlc
+            .assign(region, offset, input_bytes_rlc)?;
+        self.output_bytes_rlc
+            .assign(region, offset, output_bytes_rlc)?;
+        self.return_bytes_rlc
+            .assign(region, offset, return_bytes_rlc)?;
+
         Ok(())
     }
 }
@@ -783,6 +1046,18 @@ mod test {
         }
     }
 
+    #[test]
+    fn callop_with_value() {
+        let stack = Stack {
+            value: Word::from(10).pow(18.into()),
+            ..Default::default()
+        };
+
+        for opcode in [OpcodeId::CALL, OpcodeId::CALLCODE] {
+            test_ok(caller(&opcode, stack, true), callee(bytecode! {}));
+        }
+    }
+
     #[test]
     fn callop_nested() {
         for opcode in TEST_CALL_OPCODES {
@@ -909,7 +1184,7 @@ mod test {
 
         // Call twice for testing both cold and warm access
         let mut bytecode = Bytecode::default();
-        let address_offset = bytecode.fill_default_global_data(Address::repeat_byte(0xff).to_u256().to_be_bytes().to_vec());
+        let address_offset = bytecode.fill_default_global_data(Address::repeat_byte(0xff).to_fixed_bytes().to_vec());
         let value_offset = bytecode.fill_default_global_data(stack.value.to_be_bytes().to_vec());
         let status_offset = bytecode.alloc_default_global_data(1);
         // first call
@@ -961,30 +1236,9 @@ mod test {
     }
 
     fn caller_for_insufficient_balance(opcode: &OpcodeId, stack: Stack) -> Account {
-        let is_call_or_callcode = opcode == &OpcodeId::CALL || opcode == &OpcodeId::CALLCODE;
-        let terminator = OpcodeId::STOP;
-
-        let mut bytecode = bytecode! {
-            PUSH32(Word::from(stack.rd_length))
-            PUSH32(stack.rd_offset)
-            PUSH32(Word::from(stack.cd_length))
-            PUSH32(stack.cd_offset)
-        };
-        if is_call_or_callcode {
-            bytecode.push(32, stack.value);
-        }
-        bytecode.append(&bytecode! {
-            PUSH32(Address::repeat_byte(0xff).to_word())
-            PUSH32(Word::from(stack.gas))
-            .write_op(*opcode)
-            .write_op(terminator)
-        });
-
         Account {
-            address: Address::repeat_byte(0xfe),
             balance: Word::from(10).pow(18.into()),
-            code: bytecode.to_vec().into(),
-            ..Default::default()
+            ..caller(opcode, stack, true)
         }
     }
 
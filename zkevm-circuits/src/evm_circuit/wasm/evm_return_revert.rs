@@ -134,6 +134,8 @@ impl<F: Field> ExecutionGadget<F> for EvmReturnRevertGadget<F> {
                 init_code_rlc.expr(),
                 copy_rw_increase.expr(),
             );
+            // the deployed code must be a module the wasm circuit validated
+            cb.wasm_module_validated_lookup(code_hash.expr());
 
             let [caller_id, address] = [
                 CallContextFieldTag::CallerId,
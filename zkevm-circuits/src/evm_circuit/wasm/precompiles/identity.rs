@@ -0,0 +1,191 @@
+use eth_types::{Field, ToScalar};
+use gadgets::util::Expr;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            common_gadget::RestoreContextGadget, constraint_builder::EVMConstraintBuilder,
+            CachedRegion, Cell,
+        },
+    },
+    table::CallContextFieldTag,
+    witness::{Block, Call, ExecStep, Transaction},
+};
+
+/// Precompile step of the identity contract. The calling gadget constrains the
+/// output bytes to equal the input bytes, so identity is fully proven.
+#[derive(Clone, Debug)]
+pub(crate) struct IdentityGadget<F> {
+    is_success: Cell<F>,
+    callee_address: Cell<F>,
+    caller_id: Cell<F>,
+    call_data_offset: Cell<F>,
+    call_data_length: Cell<F>,
+    return_data_offset: Cell<F>,
+    return_data_length: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for IdentityGadget<F> {
+    const EXECUTION_STATE: ExecutionState = ExecutionState::PrecompileIdentity;
+
+    const NAME: &'static str = "IDENTITY";
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let [is_success, callee_address, caller_id, call_data_offset, call_data_length, return_data_offset, return_data_length] =
+            [
+                CallContextFieldTag::IsSuccess,
+                CallContextFieldTag::CalleeAddress,
+                CallContextFieldTag::CallerId,
+                CallContextFieldTag::CallDataOffset,
+                CallContextFieldTag::CallDataLength,
+                CallContextFieldTag::ReturnDataOffset,
+                CallContextFieldTag::ReturnDataLength,
+            ]
+            .map(|tag| cb.call_context(None, tag));
+
+        cb.precompile_info_lookup(
+            cb.execution_state().as_u64().expr(),
+            callee_address.expr(),
+            cb.execution_state().precompile_base_gas_cost().expr(),
+        );
+
+        let restore_context = RestoreContextGadget::construct(
+            cb,
+            is_success.expr(),
+            0.expr(),
+            0.expr(),
+            0.expr(),
+            0.expr(),
+            0.expr(),
+        );
+
+        Self {
+            is_success,
+            callee_address,
+            caller_id,
+            call_data_offset,
+            call_data_length,
+            return_data_offset,
+            return_data_length,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.is_success.assign(
+            region,
+            offset,
+            Value::known(F::from(u64::from(call.is_success))),
+        )?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(call.code_address.unwrap().to_scalar().unwrap()),
+        )?;
+        self.caller_id
+            .assign(region, offset, Value::known(F::from(call.caller_id as u64)))?;
+        self.call_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_offset)),
+        )?;
+        self.call_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.call_data_length)),
+        )?;
+        self.return_data_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_offset)),
+        )?;
+        self.return_data_length.assign(
+            region,
+            offset,
+            Value::known(F::from(call.return_data_length)),
+        )?;
+
+        self.restore_context
+            .assign(region, offset, block, call, step, 7)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
+    use eth_types::{bytecode_internal, Address, Bytecode};
+    use itertools::Itertools;
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn identity_call(opcode: OpcodeId, input: &[u8], rd_length: u32) -> Bytecode {
+        let mut code = Bytecode::default();
+        let address = Address::from_low_u64_be(PrecompileCalls::Identity.address());
+        let address_offset = code.fill_default_global_data(address.to_fixed_bytes().to_vec());
+        let value_offset = code.fill_default_global_data(vec![0; 32]);
+        let input_offset = code.fill_default_global_data(input.to_vec());
+        let output_offset = code.alloc_default_global_data(rd_length.max(1));
+        let status_offset = code.alloc_default_global_data(1);
+        bytecode_internal! {code,
+            I32Const[0xFFFFFF]
+            I32Const[address_offset]
+        }
+        if opcode == OpcodeId::CALL || opcode == OpcodeId::CALLCODE {
+            bytecode_internal! {code,
+                I32Const[value_offset]
+            }
+        }
+        bytecode_internal! {code,
+            I32Const[input_offset]
+            I32Const[input.len()]
+            I32Const[output_offset]
+            I32Const[rd_length]
+            I32Const[status_offset]
+            .write_op(opcode)
+        }
+        code
+    }
+
+    #[test]
+    fn precompile_identity_test() {
+        let call_kinds = [
+            OpcodeId::CALL,
+            OpcodeId::STATICCALL,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::CALLCODE,
+        ];
+        let test_vector: [(&[u8], u32); 3] = [
+            // single byte
+            (&[0xff], 1),
+            // less than 32 bytes
+            (&[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef], 8),
+            // more than 32 bytes, returning only part of them
+            (&[0xaa; 63], 35),
+        ];
+
+        for ((input, rd_length), &call_kind) in test_vector.iter().cartesian_product(&call_kinds) {
+            CircuitTestBuilder::new_from_test_ctx(
+                TestContext::<2, 1>::simple_ctx_with_bytecode(identity_call(
+                    call_kind,
+                    input,
+                    *rd_length,
+                ))
+                .unwrap(),
+            )
+            .run();
+        }
+    }
+}
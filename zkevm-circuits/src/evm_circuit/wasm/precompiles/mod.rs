@@ -0,0 +1,2 @@
+mod identity;
+pub(crate) use identity::IdentityGadget;
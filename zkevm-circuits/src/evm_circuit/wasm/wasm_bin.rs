@@ -11,7 +11,7 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{ConstrainBuilderCommon, StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -24,6 +24,7 @@ use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 #[derive(Clone, Debug)]
 pub(crate) struct WasmBinGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
     lhs: Cell<F>,
     lhs_neg: Cell<F>,
     rhs: Cell<F>,
@@ -194,17 +195,19 @@ impl<F: Field> ExecutionGadget<F> for WasmBinGadget<F> {
         ].into_iter().map(nn_case).collect());
 
         // State transition
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(3.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(1.expr()),
-            gas_left: Delta(-OpcodeId::I32Add.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
+            fuel,
             lhs,
             lhs_neg,
             rhs,
@@ -240,6 +243,8 @@ impl<F: Field> ExecutionGadget<F> for WasmBinGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         let opcode = step.opcode.unwrap();
 
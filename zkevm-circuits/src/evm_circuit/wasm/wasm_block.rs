@@ -0,0 +1,464 @@
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
+
+use bus_mapping::evm::OpcodeId;
+use eth_types::bytecode::{wasm_blocks, wasm_instructions};
+use eth_types::{Field, ToScalar};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            CachedRegion,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
+            constraint_builder::{StepStateTransition, Transition::{Any, Delta}},
+            math_gadget::IsZeroGadget,
+            not,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+    wasm_circuit::types::WasmModuleTag,
+};
+use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder};
+
+/// Structured control instructions. A frame is opened by `block`, `loop` and `if`,
+/// which write the stack pointer under its params to the control frame of their
+/// pc, and closed by `end`, which is only reached with exactly the frame results
+/// above it. `else` and `end` find their opener by the pc their instruction is
+/// tagged with, the params and results come from the tag of the opener and the
+/// `else` and `end` pcs from its blocktype. `if` with a zero condition jumps
+/// past the `else` or to the `end`, `else` jumps to the `end`.
+#[derive(Clone, Debug)]
+pub(crate) struct WasmBlockGadget<F> {
+    same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
+
+    is_nop: Cell<F>,
+    is_block: Cell<F>,
+    is_loop: Cell<F>,
+    is_if: Cell<F>,
+    is_else: Cell<F>,
+    is_end: Cell<F>,
+
+    arg: Cell<F>,
+    opener_opcode: Cell<F>,
+    opener_arg: Cell<F>,
+    num_params: Cell<F>,
+    num_results: Cell<F>,
+    end_pc: Cell<F>,
+    else_pc: Cell<F>,
+    else_pc_is_zero: IsZeroGadget<F>,
+
+    cond: Cell<F>,
+    cond_is_zero: IsZeroGadget<F>,
+
+    locals_frame: Cell<F>,
+    frame_stack_pointer: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for WasmBlockGadget<F> {
+    const NAME: &'static str = "WASM_BLOCK";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_BLOCK;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let is_nop = cb.query_bool();
+        let is_block = cb.query_bool();
+        let is_loop = cb.query_bool();
+        let is_if = cb.query_bool();
+        let is_else = cb.query_bool();
+        let is_end = cb.query_bool();
+
+        let arg = cb.query_cell();
+        let opener_opcode = cb.query_cell();
+        let opener_arg = cb.query_cell();
+        let num_params = cb.query_cell();
+        let num_results = cb.query_cell();
+        let end_pc = cb.query_cell();
+        let else_pc = cb.query_cell();
+        let cond = cb.query_cell();
+        let locals_frame = cb.query_cell();
+        let frame_stack_pointer = cb.query_cell();
+
+        cb.require_equal(
+            "op_block: exactly one instruction is selected",
+            is_nop.expr()
+                + is_block.expr()
+                + is_loop.expr()
+                + is_if.expr()
+                + is_else.expr()
+                + is_end.expr(),
+            1.expr(),
+        );
+        cb.require_equal(
+            "op_block: opcode matches the selected instruction",
+            opcode.expr(),
+            is_nop.expr() * OpcodeId::Nop.expr()
+                + is_block.expr() * OpcodeId::Block.expr()
+                + is_loop.expr() * OpcodeId::Loop.expr()
+                + is_if.expr() * OpcodeId::If.expr()
+                + is_else.expr() * OpcodeId::Else.expr()
+                + is_end.expr() * OpcodeId::End.expr(),
+        );
+
+        let pc = cb.curr.state.program_counter.expr();
+        let is_opener = is_block.expr() + is_loop.expr() + is_if.expr();
+        let is_closer = is_else.expr() + is_end.expr();
+        let is_frame = not::expr(is_nop.expr());
+
+        // an opener is tagged with its params and results, `else` and `end` with the
+        // pc plus one of their opener
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            pc.clone(),
+            opcode.expr(),
+            arg.expr(),
+        );
+        let opener_pc =
+            is_opener.clone() * pc.clone() + is_closer.clone() * (arg.expr() - 1.expr());
+        cb.condition(is_opener.clone(), |cb| {
+            cb.require_equal(
+                "op_block: an opener opens its own frame",
+                opener_opcode.expr(),
+                opcode.expr(),
+            );
+            cb.require_equal(
+                "op_block: an opener is tagged with its arity",
+                opener_arg.expr(),
+                arg.expr(),
+            );
+        });
+        cb.condition(is_closer.clone(), |cb| {
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::Instruction,
+                opener_pc.clone(),
+                opener_opcode.expr(),
+                opener_arg.expr(),
+            );
+        });
+        cb.require_zero(
+            "op_block: else is opened by an if",
+            is_else.expr() * (opener_opcode.expr() - OpcodeId::If.expr()),
+        );
+        cb.condition(is_frame.clone(), |cb| {
+            // the stack holds at most 1024 values, so the tag splits uniquely
+            cb.range_lookup(num_params.expr(), 1024);
+            cb.range_lookup(num_results.expr(), 1024);
+            cb.require_equal(
+                "op_block: the tag of the opener packs its params and results",
+                opener_arg.expr(),
+                num_params.expr() + num_results.expr() * 0x10000.expr(),
+            );
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::Block,
+                opener_pc.clone(),
+                end_pc.expr(),
+                else_pc.expr(),
+            );
+        });
+        cb.require_zero(
+            "op_block: end closes the frame of its opener",
+            is_end.expr() * (end_pc.expr() - pc.clone()),
+        );
+        cb.require_zero(
+            "op_block: else is the else of its if",
+            is_else.expr() * (else_pc.expr() - pc.clone()),
+        );
+
+        cb.condition(is_if.expr(), |cb| {
+            cb.stack_pop(cond.expr());
+        });
+        let cond_is_zero = IsZeroGadget::construct(cb, cond.expr());
+        let else_pc_is_zero = IsZeroGadget::construct(cb, else_pc.expr());
+
+        // the control frames of a function frame are keyed by its locals frame
+        let stack_pointer = cb.curr.state.stack_pointer.expr();
+        cb.condition(is_frame.clone(), |cb| {
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::LocalsFrame,
+                locals_frame.expr(),
+            );
+            cb.control_frame_lookup(
+                is_opener.clone(),
+                cb.curr.state.call_id.expr() + locals_frame.expr(),
+                opener_pc,
+                frame_stack_pointer.expr(),
+            );
+        });
+        cb.require_zero(
+            "op_block: frame is entered under its params",
+            is_opener
+                * (frame_stack_pointer.expr()
+                    - stack_pointer.clone()
+                    - is_if.expr()
+                    - num_params.expr()),
+        );
+        cb.require_zero(
+            "op_block: frame is left with exactly its results on the stack",
+            is_closer * (stack_pointer + num_results.expr() - frame_stack_pointer.expr()),
+        );
+
+        // `if` with a zero condition jumps past its `else` or to its `end`, `else` skips
+        // the `else` arm to the `end`
+        let next_pc = cb.next.state.program_counter.expr();
+        let is_skip = is_if.expr() * cond_is_zero.expr();
+        cb.condition(is_skip.clone(), |cb| {
+            cb.require_equal(
+                "op_block: if with a zero condition jumps past its else or to its end",
+                next_pc.clone(),
+                else_pc_is_zero.expr() * end_pc.expr()
+                    + not::expr(else_pc_is_zero.expr()) * (else_pc.expr() + 1.expr()),
+            );
+        });
+        cb.condition(is_else.expr(), |cb| {
+            cb.require_equal(
+                "op_block: else jumps to the end",
+                next_pc.clone(),
+                end_pc.expr(),
+            );
+        });
+        cb.condition(1.expr() - is_skip - is_else.expr(), |cb| {
+            cb.require_equal(
+                "op_block: falls through to the next instruction",
+                next_pc.clone(),
+                pc.clone() + 1.expr(),
+            );
+        });
+
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(is_if.expr() + is_frame * 2.expr()),
+            program_counter: Any,
+            stack_pointer: Delta(is_if.expr()),
+            gas_left: Delta(-fuel.expr()),
+            ..StepStateTransition::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            fuel,
+            is_nop,
+            is_block,
+            is_loop,
+            is_if,
+            is_else,
+            is_end,
+            arg,
+            opener_opcode,
+            opener_arg,
+            num_params,
+            num_results,
+            end_pc,
+            else_pc,
+            else_pc_is_zero,
+            cond,
+            cond_is_zero,
+            locals_frame,
+            frame_stack_pointer,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
+
+        let opcode = step.opcode.unwrap();
+        for (cell, op) in [
+            (&self.is_nop, OpcodeId::Nop),
+            (&self.is_block, OpcodeId::Block),
+            (&self.is_loop, OpcodeId::Loop),
+            (&self.is_if, OpcodeId::If),
+            (&self.is_else, OpcodeId::Else),
+            (&self.is_end, OpcodeId::End),
+        ] {
+            cell.assign(region, offset, Value::known(F::from((opcode == op) as u64)))?;
+        }
+
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let instructions = wasm_instructions(code).unwrap_or_default();
+        let instruction = |pc: u64| instructions.get(pc as usize).copied().unwrap_or_default();
+        let (_, _, arg) = instruction(step.program_counter);
+        self.arg
+            .assign(region, offset, Value::known(F::from(arg)))?;
+
+        let mut rws = step.rw_indices.iter().map(|idx| &block.rws[*idx]);
+        let cond = if opcode == OpcodeId::If {
+            rws.next().unwrap().stack_value().to_scalar().unwrap()
+        } else {
+            F::zero()
+        };
+        self.cond.assign(region, offset, Value::known(cond))?;
+        self.cond_is_zero.assign(region, offset, cond)?;
+
+        let (opener_opcode, opener_arg, wasm_block, locals_frame, frame_stack_pointer) =
+            match step.control_frame {
+                Some(frame) => {
+                    let (_, opener_opcode, opener_arg) = instruction(frame.pc);
+                    let wasm_block = wasm_blocks(code)
+                        .and_then(|blocks| blocks.into_iter().find(|block| block.pc == frame.pc))
+                        .unwrap_or_default();
+                    let locals_frame = rws.next().unwrap().call_context_value().low_u64();
+                    (
+                        opener_opcode,
+                        opener_arg,
+                        wasm_block,
+                        locals_frame,
+                        frame.stack_pointer(),
+                    )
+                }
+                None => Default::default(),
+            };
+        for (cell, value) in [
+            (&self.opener_opcode, opener_opcode),
+            (&self.opener_arg, opener_arg),
+            (&self.num_params, wasm_block.num_params),
+            (&self.num_results, wasm_block.num_results),
+            (&self.end_pc, wasm_block.end_pc),
+            (&self.else_pc, wasm_block.else_pc),
+            (&self.locals_frame, locals_frame),
+            (&self.frame_stack_pointer, frame_stack_pointer as u64),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(value)))?;
+        }
+        self.else_pc_is_zero
+            .assign(region, offset, F::from(wasm_block.else_pc))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use wasm_encoder::ValType;
+
+    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_nop() {
+        run_test(bytecode! {
+            Nop
+            Nop
+        });
+    }
+
+    #[test]
+    fn test_nested_blocks() {
+        run_test(bytecode! {
+            Block
+                Loop
+                    I32Const[1]
+                    Drop
+                End
+                Nop
+            End
+        });
+    }
+
+    #[test]
+    fn test_if_taken() {
+        run_test(bytecode! {
+            I32Const[1]
+            If
+                I32Const[2]
+                Drop
+            Else
+                I32Const[3]
+                Drop
+            End
+        });
+    }
+
+    #[test]
+    fn test_if_not_taken() {
+        run_test(bytecode! {
+            I32Const[0]
+            If
+                I32Const[2]
+                Drop
+            Else
+                I32Const[3]
+                Drop
+            End
+            I32Const[0]
+            If
+                Nop
+            End
+        });
+    }
+
+    #[test]
+    fn test_block_with_params_and_results() {
+        run_test(bytecode! {
+            I32Const[1]
+            I32Const[2]
+            .write_block(OpcodeId::Block, vec![ValType::I32, ValType::I32], vec![ValType::I32])
+                I32Add
+            End
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_if_with_results() {
+        run_test(bytecode! {
+            I32Const[0]
+            .write_block(OpcodeId::If, vec![], vec![ValType::I32, ValType::I64])
+                I32Const[1]
+                I64Const[2]
+            Else
+                I32Const[3]
+                I64Const[4]
+            End
+            Drop
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_loop_with_param() {
+        let mut code = bytecode! {
+            I32Const[3]
+            .write_block(OpcodeId::Loop, vec![ValType::I32], vec![ValType::I32])
+                I32Const[1]
+                I32Sub
+                TeeLocal[0]
+                GetLocal[0]
+                BrIf[0]
+            End
+            Drop
+        };
+        code.with_main_locals(vec![(1, ValType::I32)]);
+        run_test(code);
+    }
+}
@@ -1,7 +1,10 @@
+use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
 use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use eth_types::bytecode::{wasm_blocks, wasm_branches, wasm_instructions};
+use eth_types::evm_types::MAX_WASM_ARITY;
+use eth_types::{Field, ToScalar};
 
 use crate::{
     evm_circuit::{
@@ -9,20 +12,62 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
-            constraint_builder::{StepStateTransition, Transition::To, Transition::Delta},
+            common_gadget::{SameContextGadget, WasmFuelGadget},
+            constraint_builder::{StepStateTransition, Transition::{Any, Delta, To}},
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            not, sum,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
+    table::CallContextFieldTag,
     util::Expr,
+    wasm_circuit::types::WasmModuleTag,
 };
 use crate::evm_circuit::util::Cell;
-use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+use crate::evm_circuit::util::constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder};
 
+const N_VALUES: usize = MAX_WASM_ARITY as usize;
+
+/// `return`, `br` and `br_if` staying in the running function. The label of a
+/// branch is tagged with the pc plus one of the opener of its target frame, 0
+/// for the function body. A taken branch to a frame reads the stack pointer
+/// under its params and moves the values it carries on top of it, the params
+/// of a loop or the results of a block or `if`, at most [`MAX_WASM_ARITY`] of
+/// them. It continues at the loop header or the block `end`, or right after
+/// them. A `return` or a branch to the function body only stays here in the
+/// entry function, where it ends the call.
 #[derive(Clone, Debug)]
 pub(crate) struct WasmBreakGadget<F> {
     same_context: SameContextGadget<F>,
-    program_counter: Cell<F>,
+    fuel: WasmFuelGadget<F>,
+
+    is_return: Cell<F>,
+    is_br: Cell<F>,
+    is_br_if: Cell<F>,
+
+    label: Cell<F>,
+    target: Cell<F>,
+    target_is_zero: IsZeroGadget<F>,
+
+    cond: Cell<F>,
+    cond_is_zero: IsZeroGadget<F>,
+
+    is_exit: Cell<F>,
+    is_jump: Cell<F>,
+
+    opener_opcode: Cell<F>,
+    is_loop: IsEqualGadget<F>,
+    opener_arg: Cell<F>,
+    num_params: Cell<F>,
+    num_results: Cell<F>,
+    end_pc: Cell<F>,
+    else_pc: Cell<F>,
+    target_pc: Cell<F>,
+
+    arity: [Cell<F>; N_VALUES + 1],
+    locals_frame: Cell<F>,
+    frame_stack_pointer: Cell<F>,
+    values: [Cell<F>; N_VALUES],
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmBreakGadget<F> {
@@ -31,22 +76,223 @@ impl<F: Field> ExecutionGadget<F> for WasmBreakGadget<F> {
     const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_BREAK;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
-        let program_counter = cb.query_cell();
+        let opcode = cb.query_cell();
+
+        let is_return = cb.query_bool();
+        let is_br = cb.query_bool();
+        let is_br_if = cb.query_bool();
+
+        let label = cb.query_cell();
+        let target = cb.query_cell();
+        let cond = cb.query_cell();
+        let is_exit = cb.query_bool();
+        let is_jump = cb.query_bool();
+        let opener_opcode = cb.query_cell();
+        let opener_arg = cb.query_cell();
+        let num_params = cb.query_cell();
+        let num_results = cb.query_cell();
+        let end_pc = cb.query_cell();
+        let else_pc = cb.query_cell();
+        let target_pc = cb.query_cell();
+        let arity = [(); N_VALUES + 1].map(|_| cb.query_bool());
+        let locals_frame = cb.query_cell();
+        let frame_stack_pointer = cb.query_cell();
+        let values = [(); N_VALUES].map(|_| cb.query_cell());
+
+        cb.require_equal(
+            "op_break: exactly one instruction is selected",
+            is_return.expr() + is_br.expr() + is_br_if.expr(),
+            1.expr(),
+        );
+        cb.require_equal(
+            "op_break: opcode matches the selected instruction",
+            opcode.expr(),
+            is_return.expr() * OpcodeId::Return.expr()
+                + is_br.expr() * OpcodeId::Br.expr()
+                + is_br_if.expr() * OpcodeId::BrIf.expr(),
+        );
+
+        // the label is the immediate of a branch, its own row tags it with its target
+        let pc = cb.curr.state.program_counter.expr();
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            pc.clone(),
+            opcode.expr(),
+            label.expr(),
+        );
+        cb.condition(is_br.expr() + is_br_if.expr(), |cb| {
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::Label,
+                pc.clone(),
+                target.expr(),
+                label.expr(),
+            );
+        });
+        let target_is_zero = IsZeroGadget::construct(cb, target.expr());
+
+        // `br_if` pops its condition
+        cb.condition(is_br_if.expr(), |cb| {
+            cb.stack_pop(cond.expr());
+        });
+        let cond_is_zero = IsZeroGadget::construct(cb, cond.expr());
+        let is_taken_branch = is_br.expr() + is_br_if.expr() * not::expr(cond_is_zero.expr());
+        cb.require_equal(
+            "op_break: return and a taken branch to the function body exit",
+            is_exit.expr(),
+            is_return.expr() + is_taken_branch.clone() * target_is_zero.expr(),
+        );
+        cb.require_equal(
+            "op_break: a taken branch to a frame jumps",
+            is_jump.expr(),
+            is_taken_branch * not::expr(target_is_zero.expr()),
+        );
+
+        // a called function returns to its caller in the return gadget
+        cb.condition(is_exit.expr(), |cb| {
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::InternalFunctionId,
+                0.expr(),
+            );
+        });
+
+        let opener_pc = target.expr() - 1.expr();
+        let is_loop = IsEqualGadget::construct(cb, opener_opcode.expr(), OpcodeId::Loop.expr());
+        let n = sum::expr(
+            arity
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| i.expr() * cell.expr()),
+        );
+        cb.require_equal(
+            "op_break: one arity is selected",
+            sum::expr(arity.iter().map(|cell| cell.expr())),
+            1.expr(),
+        );
+        cb.condition(is_jump.expr(), |cb| {
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::Instruction,
+                opener_pc.clone(),
+                opener_opcode.expr(),
+                opener_arg.expr(),
+            );
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::Block,
+                opener_pc.clone(),
+                end_pc.expr(),
+                else_pc.expr(),
+            );
+            // the stack holds at most 1024 values, so the tag splits uniquely
+            cb.range_lookup(num_params.expr(), 1024);
+            cb.range_lookup(num_results.expr(), 1024);
+            cb.require_equal(
+                "op_break: the tag of the opener packs its params and results",
+                opener_arg.expr(),
+                num_params.expr() + num_results.expr() * 0x10000.expr(),
+            );
+            cb.require_equal(
+                "op_break: a loop carries its params, a block or if its results",
+                n.clone(),
+                is_loop.expr() * num_params.expr() + not::expr(is_loop.expr()) * num_results.expr(),
+            );
+            cb.require_equal(
+                "op_break: a loop is re-entered at its header, a block left at its end",
+                target_pc.expr(),
+                is_loop.expr() * opener_pc.clone() + not::expr(is_loop.expr()) * end_pc.expr(),
+            );
+            // the header or `end` may be skipped, they leave the state as it is
+            let next_pc = cb.next.state.program_counter.expr();
+            cb.require_zero(
+                "op_break: the branch lands on or right after the header or end",
+                (next_pc.clone() - target_pc.expr()) * (next_pc - target_pc.expr() - 1.expr()),
+            );
+
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::LocalsFrame,
+                locals_frame.expr(),
+            );
+            cb.control_frame_lookup(
+                false.expr(),
+                cb.curr.state.call_id.expr() + locals_frame.expr(),
+                opener_pc.clone(),
+                frame_stack_pointer.expr(),
+            );
+        });
+
+        // every value is read before the first write, the ranges may overlap
+        let stack_pointer = cb.curr.state.stack_pointer.expr();
+        let carries = |i: usize| sum::expr(arity[i + 1..].iter().map(|cell| cell.expr()));
+        for (i, value) in values.iter().enumerate() {
+            cb.condition(is_jump.expr() * carries(i), |cb| {
+                cb.stack_lookup(false.expr(), is_br_if.expr() + i.expr(), value.expr());
+            });
+        }
+        for (i, value) in values.iter().enumerate() {
+            cb.condition(is_jump.expr() * carries(i), |cb| {
+                cb.stack_lookup(
+                    true.expr(),
+                    frame_stack_pointer.expr() - n.clone() + i.expr() - stack_pointer.clone(),
+                    value.expr(),
+                );
+            });
+        }
+
+        cb.condition(is_br_if.expr() * cond_is_zero.expr(), |cb| {
+            cb.require_equal(
+                "op_break: not taken br_if falls through to the next instruction",
+                cb.next.state.program_counter.expr(),
+                pc.clone() + 1.expr(),
+            );
+        });
+
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
 
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(2.expr()),
-            program_counter: To(program_counter.expr()),
-            stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::Call.constant_gas_cost().expr()),
-            ..Default::default()
+            rw_counter: Delta(
+                is_br_if.expr()
+                    + is_exit.expr()
+                    + is_jump.expr() * (2.expr() + n.clone() * 2.expr()),
+            ),
+            program_counter: Any,
+            stack_pointer: To(is_jump.expr() * (frame_stack_pointer.expr() - n)
+                + not::expr(is_jump.expr()) * (stack_pointer + is_br_if.expr())),
+            gas_left: Delta(-fuel.expr()),
+            ..StepStateTransition::default()
         };
-
-        let opcode = cb.query_cell();
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
-            program_counter,
+            fuel,
+            is_return,
+            is_br,
+            is_br_if,
+            label,
+            target,
+            target_is_zero,
+            cond,
+            cond_is_zero,
+            is_exit,
+            is_jump,
+            opener_opcode,
+            is_loop,
+            opener_arg,
+            num_params,
+            num_results,
+            end_pc,
+            else_pc,
+            target_pc,
+            arity,
+            locals_frame,
+            frame_stack_pointer,
+            values,
         }
     }
 
@@ -54,12 +300,130 @@ impl<F: Field> ExecutionGadget<F> for WasmBreakGadget<F> {
         &self,
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
-        _block: &Block<F>,
+        block: &Block<F>,
         _: &Transaction,
-        _call: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
+
+        let opcode = step.opcode.unwrap();
+        for (cell, op) in [
+            (&self.is_return, OpcodeId::Return),
+            (&self.is_br, OpcodeId::Br),
+            (&self.is_br_if, OpcodeId::BrIf),
+        ] {
+            cell.assign(region, offset, Value::known(F::from((opcode == op) as u64)))?;
+        }
+
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let instructions = wasm_instructions(code).unwrap_or_default();
+        let instruction = |pc: u64| instructions.get(pc as usize).copied().unwrap_or_default();
+        let (_, _, label) = instruction(step.program_counter);
+        let target = wasm_branches(code)
+            .and_then(|branches| {
+                branches
+                    .into_iter()
+                    .find(|(_, pc, _)| *pc == step.program_counter)
+            })
+            .map(|(_, _, target)| target)
+            .unwrap_or_default();
+        self.label
+            .assign(region, offset, Value::known(F::from(label)))?;
+        self.target
+            .assign(region, offset, Value::known(F::from(target)))?;
+        self.target_is_zero
+            .assign(region, offset, F::from(target))?;
+
+        let mut rws = step.rw_indices.iter().map(|idx| &block.rws[*idx]);
+        let cond = if opcode == OpcodeId::BrIf {
+            rws.next().unwrap().stack_value().to_scalar().unwrap()
+        } else {
+            F::zero()
+        };
+        self.cond.assign(region, offset, Value::known(cond))?;
+        self.cond_is_zero.assign(region, offset, cond)?;
+
+        let is_taken = opcode != OpcodeId::BrIf || cond != F::zero();
+        let is_exit = is_taken && (opcode == OpcodeId::Return || target == 0);
+        let is_jump = is_taken && opcode != OpcodeId::Return && target != 0;
+        self.is_exit
+            .assign(region, offset, Value::known(F::from(is_exit as u64)))?;
+        self.is_jump
+            .assign(region, offset, Value::known(F::from(is_jump as u64)))?;
+
+        let (opener_opcode, opener_arg) = if target != 0 {
+            let (_, opener_opcode, opener_arg) = instruction(target - 1);
+            (opener_opcode, opener_arg)
+        } else {
+            (0, 0)
+        };
+        let wasm_block = wasm_blocks(code)
+            .and_then(|blocks| blocks.into_iter().find(|block| block.pc + 1 == target))
+            .unwrap_or_default();
+        let is_loop = opener_opcode == OpcodeId::Loop.as_u64();
+        let n = if is_loop {
+            wasm_block.num_params
+        } else {
+            wasm_block.num_results
+        };
+        let target_pc = if is_loop {
+            wasm_block.pc
+        } else {
+            wasm_block.end_pc
+        };
+        self.opener_opcode
+            .assign(region, offset, Value::known(F::from(opener_opcode)))?;
+        self.is_loop.assign(
+            region,
+            offset,
+            F::from(opener_opcode),
+            F::from(OpcodeId::Loop.as_u64()),
+        )?;
+        for (cell, value) in [
+            (&self.opener_arg, opener_arg),
+            (&self.num_params, wasm_block.num_params),
+            (&self.num_results, wasm_block.num_results),
+            (&self.end_pc, wasm_block.end_pc),
+            (&self.else_pc, wasm_block.else_pc),
+            (&self.target_pc, target_pc),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(value)))?;
+        }
+        for (i, cell) in self.arity.iter().enumerate() {
+            cell.assign(
+                region,
+                offset,
+                Value::known(F::from((i as u64 == n) as u64)),
+            )?;
+        }
+
+        let (locals_frame, frame_stack_pointer) = match step.control_frame {
+            Some(frame) if is_jump => {
+                let locals_frame = rws.next().unwrap().call_context_value().low_u64();
+                rws.next();
+                (locals_frame, frame.stack_pointer() as u64)
+            }
+            _ => (0, 0),
+        };
+        self.locals_frame
+            .assign(region, offset, Value::known(F::from(locals_frame)))?;
+        self.frame_stack_pointer.assign(
+            region,
+            offset,
+            Value::known(F::from(frame_stack_pointer)),
+        )?;
+
+        for (i, cell) in self.values.iter().enumerate() {
+            let value = if is_jump && (i as u64) < n {
+                rws.next().unwrap().stack_value().to_scalar().unwrap()
+            } else {
+                F::zero()
+            };
+            cell.assign(region, offset, Value::known(value))?;
+        }
 
         Ok(())
     }
@@ -68,7 +432,7 @@ impl<F: Field> ExecutionGadget<F> for WasmBreakGadget<F> {
 #[cfg(test)]
 mod test {
     use wasm_encoder::ValType;
-    use eth_types::{bytecode, Bytecode};
+    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode};
     use mock::test_ctx::TestContext;
 
     use crate::test_util::CircuitTestBuilder;
@@ -198,4 +562,51 @@ mod test {
         };
         run_test(code);
     }
+
+    #[test]
+    fn test_wasm_br_carries_results() {
+        let code = bytecode! {
+            .write_block(OpcodeId::Block, vec![], vec![ValType::I32, ValType::I64])
+                I32Const[1]
+                I32Const[2]
+                I64Const[3]
+                Br[0]
+            End
+            Drop
+            Drop
+        };
+        run_test(code);
+    }
+
+    #[test]
+    fn test_wasm_br_if_carries_results_to_lv2() {
+        let code = bytecode! {
+            .write_block(OpcodeId::Block, vec![], vec![ValType::I32])
+                Block
+                    I32Const[1]
+                    I32Const[2]
+                    I32Const[1]
+                    BrIf[1]
+                    Drop
+                    Drop
+                End
+                I32Const[3]
+            End
+            Drop
+        };
+        run_test(code);
+    }
+
+    #[test]
+    fn test_wasm_br_to_function_label() {
+        let code = bytecode! {
+            Block
+                I32Const[1]
+                BrIf[1]
+            End
+            I32Const[100]
+            Drop
+        };
+        run_test(code);
+    }
 }
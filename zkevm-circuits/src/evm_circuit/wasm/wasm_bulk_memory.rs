@@ -0,0 +1,430 @@
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
+
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::bytecode::{wasm_data_segment, wasm_memory_limits, WASM_PAGE_SIZE};
+use eth_types::Field;
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            CachedRegion,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
+            constraint_builder::{StepStateTransition, Transition::Delta},
+            math_gadget::{IsZeroGadget, LtGadget},
+            not,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+    wasm_circuit::types::WasmModuleTag,
+};
+use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder};
+
+/// The operands are i32, so the end of the accessed ranges and the memory size
+/// fit five bytes.
+const N_BYTES_MEMORY_END: usize = 5;
+
+/// Handles the bulk memory instructions. `memory.copy`, `memory.fill` and
+/// `memory.init` are proven by a lookup into the copy table, reading from
+/// memory, from a constant byte or from the data segment inside the bytecode.
+/// The data index is the immediate of the instruction at the program counter,
+/// the segment bounds come from the data section. `data.drop` only writes the
+/// dropped flag of the segment, which `memory.init` reads to see an empty
+/// segment.
+///
+/// The copy circuit interleaves reads and writes, so `memory.copy` to a higher
+/// address copies from the highest byte down not to read the bytes it has
+/// already overwritten.
+#[derive(Clone, Debug)]
+pub(crate) struct WasmBulkMemoryGadget<F> {
+    same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
+
+    is_copy: Cell<F>,
+    is_fill: Cell<F>,
+    is_init: Cell<F>,
+    is_drop: Cell<F>,
+
+    length_bytes: [Cell<F>; 4],
+    src_bytes: [Cell<F>; 4],
+    dst_bytes: [Cell<F>; 4],
+    data_index: Cell<F>,
+    segment_start: Cell<F>,
+    segment_end: Cell<F>,
+    is_dropped: Cell<F>,
+    initial_pages: Cell<F>,
+    grown_pages: Cell<F>,
+    copy_rwc_inc: Cell<F>,
+
+    is_dst_out_of_bounds: LtGadget<F, N_BYTES_MEMORY_END>,
+    is_src_out_of_bounds: LtGadget<F, N_BYTES_MEMORY_END>,
+    is_src_lower: LtGadget<F, 4>,
+    is_empty: IsZeroGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for WasmBulkMemoryGadget<F> {
+    const NAME: &'static str = "WASM_BULK_MEMORY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_BULK_MEMORY;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let is_copy = cb.query_bool();
+        let is_fill = cb.query_bool();
+        let is_init = cb.query_bool();
+        let is_drop = cb.query_bool();
+
+        let length_bytes = cb.query_bytes();
+        let src_bytes = cb.query_bytes();
+        let dst_bytes = cb.query_bytes();
+        let data_index = cb.query_cell();
+        let segment_start = cb.query_cell();
+        let segment_end = cb.query_cell();
+        let is_dropped = cb.query_bool();
+        let initial_pages = cb.query_cell();
+        let grown_pages = cb.query_cell();
+        let copy_rwc_inc = cb.query_cell();
+
+        cb.require_equal(
+            "op_bulk_memory: exactly one instruction is selected",
+            is_copy.expr() + is_fill.expr() + is_init.expr() + is_drop.expr(),
+            1.expr(),
+        );
+        cb.require_equal(
+            "op_bulk_memory: opcode matches the selected instruction",
+            opcode.expr(),
+            is_copy.expr() * OpcodeId::MemoryCopy.expr()
+                + is_fill.expr() * OpcodeId::MemoryFill.expr()
+                + is_init.expr() * OpcodeId::MemoryInit.expr()
+                + is_drop.expr() * OpcodeId::DataDrop.expr(),
+        );
+
+        // the data index is the first immediate, the memory index 0 for memory.copy and
+        // memory.fill
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            cb.curr.state.program_counter.expr(),
+            opcode.expr(),
+            data_index.expr(),
+        );
+
+        // the operands are i32, only the low byte of the source is used as the fill value
+        let [length, src, dst] = [&length_bytes, &src_bytes, &dst_bytes]
+            .map(|bytes| bytes.iter().rev().fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr()));
+        cb.condition(not::expr(is_drop.expr()), |cb| {
+            cb.stack_pop(length.clone());
+            cb.stack_pop(src.clone());
+            cb.stack_pop(dst.clone());
+            cb.call_context_lookup(
+                false.expr(),
+                None,
+                CallContextFieldTag::GrownMemoryPages,
+                grown_pages.expr(),
+            );
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::MemoryPages,
+                0.expr(),
+                initial_pages.expr(),
+                0.expr(),
+            );
+        });
+        cb.require_zero("op_bulk_memory: data.drop copies nothing", is_drop.expr() * length.clone());
+        cb.condition(is_init.expr(), |cb| {
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::DataSegment,
+                data_index.expr(),
+                segment_start.expr(),
+                segment_end.expr(),
+            );
+            cb.data_drop_lookup(false.expr(), data_index.expr(), is_dropped.expr());
+        });
+        cb.condition(is_drop.expr(), |cb| {
+            cb.data_drop_lookup(true.expr(), data_index.expr(), 1.expr());
+        });
+
+        let memory_size = (initial_pages.expr() + grown_pages.expr()) * WASM_PAGE_SIZE.expr();
+        let is_dst_out_of_bounds = LtGadget::construct(cb, memory_size.clone(), dst.clone() + length.clone());
+        // a dropped segment is empty, the fill value has no range
+        let src_size = is_copy.expr() * memory_size
+            + is_init.expr() * (segment_end.expr() - segment_start.expr()) * not::expr(is_dropped.expr());
+        let is_src_out_of_bounds = LtGadget::construct(cb, src_size, src.clone() + length.clone());
+        cb.require_zero(
+            "op_bulk_memory: destination range ends within the memory size",
+            not::expr(is_drop.expr()) * is_dst_out_of_bounds.expr(),
+        );
+        cb.require_zero(
+            "op_bulk_memory: source range ends within the memory or the data segment",
+            (is_copy.expr() + is_init.expr()) * is_src_out_of_bounds.expr(),
+        );
+
+        let is_src_lower = LtGadget::construct(cb, src.clone(), dst.clone());
+        let is_reverse = is_copy.expr() * is_src_lower.expr();
+        let last_offset = is_reverse.clone() * (length.clone() - 1.expr());
+
+        let is_empty = IsZeroGadget::construct(cb, length.clone());
+        cb.condition(not::expr(is_empty.expr()), |cb| {
+            cb.require_equal(
+                "op_bulk_memory: memory.copy reads and writes every byte, the others only write",
+                copy_rwc_inc.expr(),
+                length.clone() * (1.expr() + is_copy.expr()),
+            );
+            cb.copy_table_lookup_with_direction(
+                is_copy.expr() * cb.curr.state.call_id.expr()
+                    + is_fill.expr() * src_bytes[0].expr()
+                    + is_init.expr() * cb.curr.state.code_hash.expr(),
+                is_copy.expr() * CopyDataType::Memory.expr()
+                    + is_fill.expr() * CopyDataType::Constant.expr()
+                    + is_init.expr() * CopyDataType::Bytecode.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                is_copy.expr() * (src.clone() + last_offset.clone())
+                    + is_init.expr() * (segment_start.expr() + src.clone()),
+                is_copy.expr() * (src.clone() + length.clone())
+                    + is_fill.expr() * length.clone()
+                    + is_init.expr() * segment_end.expr(),
+                dst.clone() + last_offset,
+                length.clone(),
+                0.expr(),
+                copy_rwc_inc.expr(),
+                is_reverse,
+            );
+        });
+        cb.condition(is_empty.expr(), |cb| {
+            cb.require_zero(
+                "op_bulk_memory: if no bytes to copy, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+        });
+
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr() * not::expr(is_drop.expr())),
+            gas_left: Delta(-fuel.expr()),
+            ..StepStateTransition::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            fuel,
+            is_copy,
+            is_fill,
+            is_init,
+            is_drop,
+            length_bytes,
+            src_bytes,
+            dst_bytes,
+            data_index,
+            segment_start,
+            segment_end,
+            is_dropped,
+            initial_pages,
+            grown_pages,
+            copy_rwc_inc,
+            is_dst_out_of_bounds,
+            is_src_out_of_bounds,
+            is_src_lower,
+            is_empty,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
+
+        let opcode = step.opcode.unwrap();
+        for (cell, op) in [
+            (&self.is_copy, OpcodeId::MemoryCopy),
+            (&self.is_fill, OpcodeId::MemoryFill),
+            (&self.is_init, OpcodeId::MemoryInit),
+            (&self.is_drop, OpcodeId::DataDrop),
+        ] {
+            cell.assign(region, offset, Value::known(F::from((opcode == op) as u64)))?;
+        }
+
+        let [length, src, dst] = if opcode == OpcodeId::DataDrop {
+            [0; 3]
+        } else {
+            [0, 1, 2].map(|i| block.rws[step.rw_indices[i]].stack_value().as_u64())
+        };
+        for (bytes, val) in [(&self.length_bytes, length), (&self.src_bytes, src), (&self.dst_bytes, dst)] {
+            for (cell, byte) in bytes.iter().zip((val as u32).to_le_bytes()) {
+                cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+            }
+        }
+
+        let code = &block
+            .bytecodes
+            .get(&call.code_hash)
+            .expect("could not find current environment's bytecode")
+            .bytes;
+        let data_index = if matches!(opcode, OpcodeId::MemoryInit | OpcodeId::DataDrop) {
+            step.params[0]
+        } else {
+            0
+        };
+        self.data_index.assign(region, offset, Value::known(F::from(data_index)))?;
+
+        let (segment_start, segment_end, is_dropped) = if opcode == OpcodeId::MemoryInit {
+            let (segment_start, segment_end) = wasm_data_segment(code, data_index).unwrap_or_default();
+            let is_dropped = block.rws[step.rw_indices[4]].data_drop_value();
+            (segment_start, segment_end, is_dropped)
+        } else {
+            (0, 0, false)
+        };
+        self.segment_start.assign(region, offset, Value::known(F::from(segment_start)))?;
+        self.segment_end.assign(region, offset, Value::known(F::from(segment_end)))?;
+        self.is_dropped.assign(region, offset, Value::known(F::from(is_dropped as u64)))?;
+
+        let (initial_pages, grown_pages) = if opcode == OpcodeId::DataDrop {
+            (0, 0)
+        } else {
+            (
+                wasm_memory_limits(code).unwrap_or_default().0,
+                block.rws[step.rw_indices[3]].call_context_value().as_u64(),
+            )
+        };
+        self.initial_pages.assign(region, offset, Value::known(F::from(initial_pages)))?;
+        self.grown_pages.assign(region, offset, Value::known(F::from(grown_pages)))?;
+
+        let memory_size = (initial_pages + grown_pages) * WASM_PAGE_SIZE;
+        let src_size = match opcode {
+            OpcodeId::MemoryCopy => memory_size,
+            OpcodeId::MemoryInit if !is_dropped => segment_end - segment_start,
+            _ => 0,
+        };
+        self.is_dst_out_of_bounds.assign(region, offset, F::from(memory_size), F::from(dst + length))?;
+        self.is_src_out_of_bounds.assign(region, offset, F::from(src_size), F::from(src + length))?;
+        self.is_src_lower.assign(region, offset, F::from(src), F::from(dst))?;
+
+        let copy_rwc_inc = if opcode == OpcodeId::MemoryCopy { 2 * length } else { length };
+        self.copy_rwc_inc.assign(region, offset, Value::known(F::from(copy_rwc_inc)))?;
+        self.is_empty.assign(region, offset, F::from(length))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eth_types::{bytecode, bytecode::WASM_PAGE_SIZE, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_memory_copy() {
+        let mut code = bytecode! {
+            I32Const[0x20]
+            I32Const[0]
+            I32Const[5]
+            MemoryCopy
+        };
+        code.fill_default_global_data(vec![1, 2, 3, 4, 5]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_memory_copy_overlapping_to_lower_range() {
+        let mut code = bytecode! {
+            I32Const[0]
+            I32Const[2]
+            I32Const[4]
+            MemoryCopy
+        };
+        code.fill_default_global_data(vec![1, 2, 3, 4, 5, 6]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_memory_copy_overlapping_to_higher_range() {
+        let mut code = bytecode! {
+            I32Const[2]
+            I32Const[0]
+            I32Const[4]
+            MemoryCopy
+        };
+        code.fill_default_global_data(vec![1, 2, 3, 4, 5, 6]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_memory_init_at_segment_end() {
+        let mut code = bytecode! {
+            I32Const[WASM_PAGE_SIZE - 2]
+            I32Const[1]
+            I32Const[2]
+            MemoryInit[0]
+            I32Const[0]
+            I32Const[3]
+            I32Const[0]
+            MemoryInit[0]
+        };
+        code.with_passive_data(vec![7, 8, 9]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_memory_fill() {
+        run_test(bytecode! {
+            I32Const[3]
+            I32Const[0x1ab]
+            I32Const[7]
+            MemoryFill
+        });
+    }
+
+    #[test]
+    fn test_memory_init_and_data_drop() {
+        let mut code = bytecode! {
+            I32Const[0x10]
+            I32Const[1]
+            I32Const[2]
+            MemoryInit[0]
+            DataDrop[0]
+        };
+        code.with_passive_data(vec![7, 8, 9]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_empty_bulk_memory() {
+        run_test(bytecode! {
+            I32Const[0]
+            I32Const[0]
+            I32Const[0]
+            MemoryCopy
+            I32Const[0]
+            I32Const[0]
+            I32Const[0]
+            MemoryFill
+        });
+    }
+}
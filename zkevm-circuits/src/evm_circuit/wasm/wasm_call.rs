@@ -2,29 +2,54 @@ use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
 use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use eth_types::bytecode::wasm_function_locals;
+use eth_types::{evm_types::MAX_WASM_CALL_DEPTH, Field};
 
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
+        param::STACK_CAPACITY,
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{StepStateTransition, Transition::Delta, Transition::To},
+            math_gadget::LtGadget,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
+    table::{CallContextFieldTag, WasmFunctionTableTag},
     util::Expr,
+    wasm_circuit::types::WasmModuleTag,
 };
 use crate::evm_circuit::util::Cell;
-use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
+use crate::evm_circuit::util::constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder};
 
+/// The call depth never exceeds `MAX_WASM_CALL_DEPTH`.
+const N_BYTES_CALL_DEPTH: usize = 2;
+
+/// Direct and indirect calls. The callee of `call` is its immediate,
+/// `call_indirect` pops a table slot which must hold a function of the type
+/// of its immediate, otherwise the step is an `ErrorInvalidIndirectCall`. The
+/// callee runs from the first instruction of its body and the call context
+/// then describes its frame, the caller frame and the return address are
+/// saved in call context rows keyed by the rw counter of the call.
 #[derive(Clone, Debug)]
 pub(crate) struct WasmCallGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
+    is_call_indirect: Cell<F>,
+    type_index: Cell<F>,
+    elem_index: Cell<F>,
     program_counter: Cell<F>,
     function_index: Cell<F>,
+    num_params: Cell<F>,
+    num_locals: Cell<F>,
+    caller_function_id: Cell<F>,
+    caller_locals_base: Cell<F>,
+    caller_locals_frame: Cell<F>,
+    depth: Cell<F>,
+    is_depth_available: LtGadget<F, N_BYTES_CALL_DEPTH>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmCallGadget<F> {
@@ -33,37 +58,148 @@ impl<F: Field> ExecutionGadget<F> for WasmCallGadget<F> {
     const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_CALL;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let is_call_indirect = cb.query_bool();
+        let type_index = cb.query_cell();
+        let elem_index = cb.query_cell();
         let function_index = cb.query_cell();
         let program_counter = cb.query_cell();
 
-        // cb.call_context_lookup(
-        //     1.expr(),
-        //     None,
-        //     CallContextFieldTag::InternalFunctionId,
-        //     function_index.expr(),
-        // );
-        // cb.call_context_lookup(
-        //     1.expr(),
-        //     None,
-        //     CallContextFieldTag::ProgramCounter,
-        //     program_counter.expr(),
-        // );
+        let opcode = cb.query_cell();
+        cb.require_equal(
+            "op_call: opcode is call or call_indirect",
+            opcode.expr(),
+            OpcodeId::Call.expr() + is_call_indirect.expr(),
+        );
+        // the immediate is the callee of `call` and the expected type of `call_indirect`
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            cb.curr.state.program_counter.expr(),
+            opcode.expr(),
+            is_call_indirect.expr() * type_index.expr()
+                + (1.expr() - is_call_indirect.expr()) * function_index.expr(),
+        );
+
+        cb.condition(is_call_indirect.expr(), |cb| {
+            cb.stack_pop(elem_index.expr());
+            // only slots inside the table have a row, a null slot stores zero
+            cb.wasm_function_table_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmFunctionTableTag::Element,
+                elem_index.expr(),
+                function_index.expr() + 1.expr(),
+            );
+            cb.wasm_function_table_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmFunctionTableTag::FunctionType,
+                function_index.expr(),
+                type_index.expr(),
+            );
+        });
+
+        // the callee starts with its params and its zeroed declared locals on
+        // top of the stack, its declared locals get a frame of their own
+        let num_params = cb.query_cell();
+        let num_locals = cb.query_cell();
+        for (tag, cell) in [
+            (WasmFunctionTableTag::ParamCount, &num_params),
+            (WasmFunctionTableTag::LocalCount, &num_locals),
+        ] {
+            cb.wasm_function_table_lookup(
+                cb.curr.state.code_hash.expr(),
+                tag,
+                function_index.expr() + 1.expr(),
+                cell.expr(),
+            );
+        }
+        // the callee runs from the first instruction of its body
+        cb.wasm_function_table_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmFunctionTableTag::FunctionBody,
+            function_index.expr() + 1.expr(),
+            program_counter.expr(),
+        );
+
+        // the caller frame is saved in call context rows keyed by the callee
+        // frame, which the return reads back
+        let caller_function_id = cb.query_cell();
+        let caller_locals_base = cb.query_cell();
+        let caller_locals_frame = cb.query_cell();
+        let depth = cb.query_cell();
+        for (field_tag, cell) in [
+            (CallContextFieldTag::InternalFunctionId, &caller_function_id),
+            (CallContextFieldTag::LocalsBase, &caller_locals_base),
+            (CallContextFieldTag::LocalsFrame, &caller_locals_frame),
+            (CallContextFieldTag::FunctionDepth, &depth),
+        ] {
+            cb.call_context_lookup(false.expr(), None, field_tag, cell.expr());
+        }
+        let is_depth_available =
+            LtGadget::construct(cb, depth.expr(), MAX_WASM_CALL_DEPTH.expr());
+        cb.require_equal(
+            "op_call: the callee frame stays within the maximum call depth",
+            is_depth_available.expr(),
+            1.expr(),
+        );
+        let frame_id = cb.curr.state.rw_counter.expr();
+        for (field_tag, value) in [
+            (CallContextFieldTag::InternalFunctionId, caller_function_id.expr()),
+            (CallContextFieldTag::LocalsBase, caller_locals_base.expr()),
+            (CallContextFieldTag::LocalsFrame, caller_locals_frame.expr()),
+            (
+                CallContextFieldTag::ProgramCounter,
+                cb.curr.state.program_counter.expr() + 1.expr(),
+            ),
+        ] {
+            cb.call_context_lookup(true.expr(), Some(frame_id.clone()), field_tag, value);
+        }
+
+        let stack_height = STACK_CAPACITY.expr() - cb.curr.state.stack_pointer.expr();
+        for (field_tag, value) in [
+            (CallContextFieldTag::InternalFunctionId, function_index.expr() + 1.expr()),
+            (CallContextFieldTag::ProgramCounter, program_counter.expr()),
+            (
+                CallContextFieldTag::LocalsBase,
+                stack_height - is_call_indirect.expr() - num_params.expr(),
+            ),
+            (
+                CallContextFieldTag::LocalsFrame,
+                frame_id - cb.curr.state.call_id.expr(),
+            ),
+            (CallContextFieldTag::FunctionDepth, depth.expr() + 1.expr()),
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, value);
+        }
+
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
 
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(2.expr()),
+            rw_counter: Delta(13.expr() + is_call_indirect.expr()),
             program_counter: To(program_counter.expr()),
-            stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::Call.constant_gas_cost().expr()),
+            stack_pointer: Delta(
+                is_call_indirect.expr() + num_params.expr() - num_locals.expr(),
+            ),
+            gas_left: Delta(-fuel.expr()),
             ..Default::default()
         };
 
-        let opcode = cb.query_cell();
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
+            fuel,
+            is_call_indirect,
+            type_index,
+            elem_index,
             program_counter,
             function_index,
+            num_params,
+            num_locals,
+            caller_function_id,
+            caller_locals_base,
+            caller_locals_frame,
+            depth,
+            is_depth_available,
         }
     }
 
@@ -73,16 +209,60 @@ impl<F: Field> ExecutionGadget<F> for WasmCallGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _call: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
+
+        let is_call_indirect = step.opcode.unwrap() == OpcodeId::CallIndirect;
+        self.is_call_indirect.assign(region, offset, Value::known(F::from(is_call_indirect as u64)))?;
 
-        let function_index = block.rws[step.rw_indices[0]].call_context_value();
-        self.function_index.assign(region, offset, Value::known(F::from(function_index.low_u64())))?;
-        let program_counter = block.rws[step.rw_indices[1]].call_context_value();
+        let mut rw_indices = step.rw_indices.iter().map(|idx| &block.rws[*idx]);
+        let (type_index, elem_index) = if is_call_indirect {
+            let elem_index = rw_indices.next().unwrap().stack_value();
+            (step.params[0], elem_index.as_u64())
+        } else {
+            (0, 0)
+        };
+        self.type_index.assign(region, offset, Value::known(F::from(type_index)))?;
+        self.elem_index.assign(region, offset, Value::known(F::from(elem_index)))?;
+
+        let mut caller_values = [0; 4];
+        for value in caller_values.iter_mut() {
+            *value = rw_indices.next().unwrap().call_context_value().low_u64();
+        }
+        let [caller_function_id, caller_locals_base, caller_locals_frame, depth] = caller_values;
+        for (cell, value) in [
+            (&self.caller_function_id, caller_function_id),
+            (&self.caller_locals_base, caller_locals_base),
+            (&self.caller_locals_frame, caller_locals_frame),
+            (&self.depth, depth),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(value)))?;
+        }
+        self.is_depth_available.assign(
+            region,
+            offset,
+            F::from(depth),
+            F::from(MAX_WASM_CALL_DEPTH),
+        )?;
+        // skip the saved caller frame
+        let mut rw_indices = rw_indices.skip(4);
+
+        let function_index = rw_indices.next().unwrap().call_context_value().low_u64() - 1;
+        self.function_index.assign(region, offset, Value::known(F::from(function_index)))?;
+        let program_counter = rw_indices.next().unwrap().call_context_value();
         self.program_counter.assign(region, offset, Value::known(F::from(program_counter.low_u64())))?;
 
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let (num_params, num_locals) = wasm_function_locals(code)
+            .and_then(|locals| locals.get(function_index as usize).copied())
+            .unwrap_or_default();
+        self.num_params.assign(region, offset, Value::known(F::from(num_params)))?;
+        self.num_locals.assign(region, offset, Value::known(F::from(num_locals)))?;
+
         Ok(())
     }
 }
@@ -94,7 +274,9 @@ mod test {
     use eth_types::{bytecode, Bytecode};
     use mock::test_ctx::TestContext;
 
-    use crate::test_util::CircuitTestBuilder;
+    use bus_mapping::evm::OpcodeId;
+
+    use crate::{table::CallContextFieldTag, test_util::CircuitTestBuilder, witness::Rw};
 
     fn run_test(bytecode: Bytecode) {
         CircuitTestBuilder::new_from_test_ctx(
@@ -132,4 +314,101 @@ mod test {
         }, vec![(1, ValType::I32)]);
         run_test(code);
     }
+
+    #[test]
+    fn test_call_indirect() {
+        let mut code = bytecode! {
+            I32Const[1]
+            CallIndirect[0]
+            I32Const[7]
+            I32Const[0]
+            CallIndirect[1]
+        };
+        code.new_function(vec![], vec![], bytecode! {
+            Return
+        }, vec![]);
+        code.new_function(vec![ValType::I32], vec![], bytecode! {
+            Return
+        }, vec![]);
+        code.with_function_table(vec![1, 0]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_callee_not_entered_at_its_body_start_must_fail() {
+        let mut code = bytecode! {
+            Call[0]
+        };
+        code.new_function(vec![], vec![], bytecode! {
+            Nop
+            Nop
+            Return
+        }, vec![]);
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .block_modifier(Box::new(|block| {
+            // enter the callee at its second instruction
+            let call_id = block.txs[0].calls[0].id;
+            let steps = &mut block.txs[0].steps;
+            let call_index = steps
+                .iter()
+                .position(|step| step.opcode == Some(OpcodeId::Call))
+                .unwrap();
+            for (tag, index) in steps[call_index].rw_indices.iter() {
+                if let Rw::CallContext {
+                    call_id: rw_call_id,
+                    field_tag: CallContextFieldTag::ProgramCounter,
+                    value,
+                    ..
+                } = &mut block.rws.0.get_mut(tag).unwrap()[*index]
+                {
+                    // the return address is keyed by the callee frame
+                    if *rw_call_id == call_id {
+                        *value = *value + 1;
+                    }
+                }
+            }
+            steps[call_index + 1].program_counter += 1;
+        }))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        .run();
+    }
+
+    #[test]
+    fn test_call_indirect_with_other_type_than_the_immediate_must_fail() {
+        let mut code = bytecode! {
+            I32Const[0]
+            CallIndirect[0]
+        };
+        code.new_function(vec![], vec![], bytecode! {
+            Return
+        }, vec![]);
+        code.new_function(vec![ValType::I32], vec![], bytecode! {
+            Return
+        }, vec![]);
+        code.with_function_table(vec![0, 1]);
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .block_modifier(Box::new(|block| {
+            // expect the type of the other function
+            let step = block.txs[0]
+                .steps
+                .iter_mut()
+                .find(|step| step.opcode == Some(OpcodeId::CallIndirect))
+                .unwrap();
+            step.params[0] += 1;
+        }))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        .run();
+    }
 }
@@ -1,7 +1,7 @@
 use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
-use eth_types::{evm_types::OpcodeId, Field, ToScalar};
+use eth_types::{Field, ToScalar};
 
 use crate::{
     evm_circuit::{
@@ -9,7 +9,7 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -22,6 +22,7 @@ use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 #[derive(Clone, Debug)]
 pub(crate) struct WasmConstGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
     value: Cell<F>,
 }
 
@@ -38,17 +39,19 @@ impl<F: Field> ExecutionGadget<F> for WasmConstGadget<F> {
         cb.stack_push(value.expr());
 
         // State transition
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(1.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta((-1).expr()),
-            gas_left: Delta(-OpcodeId::I32Const.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..Default::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
+            fuel,
             value,
         }
     }
@@ -63,6 +66,8 @@ impl<F: Field> ExecutionGadget<F> for WasmConstGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
         self.value
@@ -11,7 +11,7 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -24,6 +24,7 @@ use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 #[derive(Clone, Debug)]
 pub(crate) struct WasmConversionGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
     value: Cell<F>,
     value_limbs: [Cell<F>; 8],
     res: Cell<F>,
@@ -31,6 +32,12 @@ pub(crate) struct WasmConversionGadget<F> {
     is_i32_wrap_i64: Cell<F>,
     is_i64_extend_i32_u: Cell<F>,
     is_i64_extend_i32_s: Cell<F>,
+    is_i32_extend8_s: Cell<F>,
+    is_i32_extend16_s: Cell<F>,
+    is_i64_extend8_s: Cell<F>,
+    is_i64_extend16_s: Cell<F>,
+    is_i64_extend32_s: Cell<F>,
+    sign_byte: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmConversionGadget<F> {
@@ -48,6 +55,12 @@ impl<F: Field> ExecutionGadget<F> for WasmConversionGadget<F> {
         let is_i32_wrap_i64 = cb.alloc_bit_value();
         let is_i64_extend_i32_u = cb.alloc_bit_value();
         let is_i64_extend_i32_s = cb.alloc_bit_value();
+        let is_i32_extend8_s = cb.alloc_bit_value();
+        let is_i32_extend16_s = cb.alloc_bit_value();
+        let is_i64_extend8_s = cb.alloc_bit_value();
+        let is_i64_extend16_s = cb.alloc_bit_value();
+        let is_i64_extend32_s = cb.alloc_bit_value();
+        let sign_byte = cb.alloc_u64();
 
         cb.stack_pop(value.expr());
         cb.stack_push(res.expr());
@@ -63,6 +76,11 @@ impl<F: Field> ExecutionGadget<F> for WasmConversionGadget<F> {
               is_i32_wrap_i64.expr()
             + is_i64_extend_i32_u.expr()
             + is_i64_extend_i32_s.expr()
+            + is_i32_extend8_s.expr()
+            + is_i32_extend16_s.expr()
+            + is_i64_extend8_s.expr()
+            + is_i64_extend16_s.expr()
+            + is_i64_extend32_s.expr()
             - 1.expr()
         ]);
 
@@ -108,20 +126,72 @@ impl<F: Field> ExecutionGadget<F> for WasmConversionGadget<F> {
             },
         );
 
+        // Sign extension keeps the low `width` bytes and fills the rest of the
+        // result with the sign byte of the highest kept one.
+        let sign_extensions = [
+            (&is_i32_extend8_s, 1, 4),
+            (&is_i32_extend16_s, 2, 4),
+            (&is_i64_extend8_s, 1, 8),
+            (&is_i64_extend16_s, 2, 8),
+            (&is_i64_extend32_s, 4, 8),
+        ];
+        let is_sign_extension = sign_extensions
+            .iter()
+            .fold(0.expr(), |acc, (is_op, _, _)| acc + is_op.expr());
+        let sign_limb = sign_extensions
+            .iter()
+            .fold(0.expr(), |acc, (is_op, width, _)| acc + is_op.expr() * value_limbs[width - 1].expr());
+        cb.condition(is_sign_extension, |cb| {
+            cb.add_lookup("op_conversion: sign byte of the extended limb", Lookup::Fixed {
+                tag: FixedTableTag::SignByte.expr(),
+                values: [sign_limb, sign_byte.expr(), 0.expr()],
+            });
+        });
+        cb.require_zeros(
+            "op_conversion: result case of sign extension",
+            sign_extensions
+                .iter()
+                .map(|(is_op, width, size)| {
+                    let low = (0..*width)
+                        .fold(0.expr(), |acc, i| acc + value_limbs[i].expr() * (1_u64 << i*8).expr());
+                    let fill = (*width..*size)
+                        .fold(0.expr(), |acc, i| acc + sign_byte.expr() * (1_u64 << i*8).expr());
+                    (low + fill - res.expr()) * is_op.expr()
+                })
+                .collect(),
+        );
+
         let opcode = cb.query_cell();
 
+        cb.require_zeros(
+            "op_conversion: opcode matches the selected conversion",
+            vec![
+                opcode.expr()
+                    - is_i32_wrap_i64.expr() * OpcodeId::I32WrapI64.expr()
+                    - is_i64_extend_i32_u.expr() * OpcodeId::I64ExtendUI32.expr()
+                    - is_i64_extend_i32_s.expr() * OpcodeId::I64ExtendSI32.expr()
+                    - is_i32_extend8_s.expr() * OpcodeId::I32Extend8S.expr()
+                    - is_i32_extend16_s.expr() * OpcodeId::I32Extend16S.expr()
+                    - is_i64_extend8_s.expr() * OpcodeId::I64Extend8S.expr()
+                    - is_i64_extend16_s.expr() * OpcodeId::I64Extend16S.expr()
+                    - is_i64_extend32_s.expr() * OpcodeId::I64Extend32S.expr(),
+            ],
+        );
+
         // State transition
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(2.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::I32WrapI64.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
+            fuel,
             value,
             value_limbs,
             res,
@@ -129,6 +199,12 @@ impl<F: Field> ExecutionGadget<F> for WasmConversionGadget<F> {
             is_i32_wrap_i64,
             is_i64_extend_i32_u,
             is_i64_extend_i32_s,
+            is_i32_extend8_s,
+            is_i32_extend16_s,
+            is_i64_extend8_s,
+            is_i64_extend16_s,
+            is_i64_extend32_s,
+            sign_byte,
         }
     }
 
@@ -142,6 +218,8 @@ impl<F: Field> ExecutionGadget<F> for WasmConversionGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         let opcode = step.opcode.unwrap();
 
@@ -168,6 +246,23 @@ impl<F: Field> ExecutionGadget<F> for WasmConversionGadget<F> {
                 self.is_value_pos.assign(region, offset, Value::<F>::known(F::from(is_value_pos)))?;
                 self.is_i64_extend_i32_s.assign(region, offset, Value::known(true.to_scalar().unwrap()))?;
             }
+            OpcodeId::I32Extend8S |
+            OpcodeId::I32Extend16S |
+            OpcodeId::I64Extend8S |
+            OpcodeId::I64Extend16S |
+            OpcodeId::I64Extend32S => {
+                let (is_op, width) = match opcode {
+                    OpcodeId::I32Extend8S => (&self.is_i32_extend8_s, 1),
+                    OpcodeId::I32Extend16S => (&self.is_i32_extend16_s, 2),
+                    OpcodeId::I64Extend8S => (&self.is_i64_extend8_s, 1),
+                    OpcodeId::I64Extend16S => (&self.is_i64_extend16_s, 2),
+                    _ => (&self.is_i64_extend32_s, 4),
+                };
+                let sign_limb = (value.0[0] >> ((width - 1) * 8)) & 0xff;
+                let sign_byte = (sign_limb >> 7) * 0xff;
+                self.sign_byte.assign(region, offset, Value::<F>::known(F::from(sign_byte)))?;
+                is_op.assign(region, offset, Value::known(true.to_scalar().unwrap()))?;
+            }
             _ => unreachable!("not supported opcode: {:?}", opcode),
         };
  
@@ -218,6 +313,42 @@ mod test {
         });
     }
 
+    #[test]
+    fn test_i32_sign_extension() {
+        run_test(bytecode! {
+            I32Const[0x7f]
+            I32Extend8S
+            Drop
+            I32Const[0x80]
+            I32Extend8S
+            Drop
+            I32Const[0x12348000]
+            I32Extend16S
+            Drop
+            I32Const[0x7fff]
+            I32Extend16S
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_i64_sign_extension() {
+        run_test(bytecode! {
+            I64Const[0xff]
+            I64Extend8S
+            Drop
+            I64Const[0x8000]
+            I64Extend16S
+            Drop
+            I64Const[0x1_7fffffff]
+            I64Extend32S
+            Drop
+            I64Const[0x80000000]
+            I64Extend32S
+            Drop
+        });
+    }
+
     #[test]
     fn test_i64_extend_s_i32() {
         run_test(bytecode! {
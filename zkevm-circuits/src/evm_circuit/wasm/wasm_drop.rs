@@ -1,7 +1,6 @@
 use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
-use bus_mapping::evm::OpcodeId;
 use eth_types::{Field, ToScalar};
 
 use crate::{
@@ -11,7 +10,7 @@ use crate::{
         util::{
             CachedRegion,
             Cell,
-            common_gadget::SameContextGadget, constraint_builder::{StepStateTransition, Transition::Delta},
+            common_gadget::{SameContextGadget, WasmFuelGadget}, constraint_builder::{StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
@@ -22,6 +21,7 @@ use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 #[derive(Clone, Debug)]
 pub(crate) struct WasmDropGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
     phase2_value: Cell<F>,
 }
 
@@ -37,18 +37,20 @@ impl<F: Field> ExecutionGadget<F> for WasmDropGadget<F> {
         cb.stack_pop(phase2_value.expr());
 
         // State transition
+        let opcode = cb.query_cell();
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(1.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(1.expr()),
-            gas_left: Delta(-OpcodeId::POP.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..Default::default()
         };
-        let opcode = cb.query_cell();
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
+            fuel,
             phase2_value,
         }
     }
@@ -63,6 +65,8 @@ impl<F: Field> ExecutionGadget<F> for WasmDropGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         let value = block.rws[step.rw_indices[0]].stack_value();
         self.phase2_value.assign(region, offset, Value::known(value.to_scalar().unwrap()))?;
@@ -3,6 +3,7 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
+            common_gadget::WasmFuelGadget,
             constraint_builder::{
                 ConstrainBuilderCommon, StepStateTransition,
                 Transition::{Delta, Same},
@@ -25,6 +26,7 @@ pub(crate) struct WasmEndGadget<F> {
     code_length: Cell<F>,
     is_out_of_range: IsZeroGadget<F>,
     opcode: Cell<F>,
+    fuel: WasmFuelGadget<F>,
     // restore_context: RestoreContextGadget<F>,
 }
 
@@ -52,6 +54,7 @@ impl<F: Field> ExecutionGadget<F> for WasmEndGadget<F> {
             opcode.expr(),
             OpcodeId::End.expr(),
         );
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
 
         // Call ends with STOP must be successful
         cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 1.expr());
@@ -69,6 +72,7 @@ impl<F: Field> ExecutionGadget<F> for WasmEndGadget<F> {
             cb.require_step_state_transition(StepStateTransition {
                 call_id: Same,
                 rw_counter: Delta(1.expr()),
+                gas_left: Delta(-fuel.expr()),
                 ..StepStateTransition::any()
             });
         });
@@ -90,6 +94,7 @@ impl<F: Field> ExecutionGadget<F> for WasmEndGadget<F> {
             code_length,
             is_out_of_range,
             opcode,
+            fuel,
             // restore_context,
         }
     }
@@ -122,6 +127,7 @@ impl<F: Field> ExecutionGadget<F> for WasmEndGadget<F> {
         let opcode = step.opcode.unwrap();
         self.opcode
             .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        self.fuel.assign(region, offset, block, opcode)?;
 
         // if !call.is_root {
         //     self.restore_context
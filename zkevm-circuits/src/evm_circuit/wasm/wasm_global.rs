@@ -10,7 +10,7 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{ConstrainBuilderCommon, StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -23,6 +23,7 @@ use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 #[derive(Clone, Debug)]
 pub(crate) struct WasmGlobalGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
     is_get_global: Cell<F>,
     is_set_global: Cell<F>,
     index: Cell<F>,
@@ -61,11 +62,13 @@ impl<F: Field> ExecutionGadget<F> for WasmGlobalGadget<F> {
 
         let sp = is_get_global.expr() * (-1).expr() + is_set_global.expr() * (1).expr();
 
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
+
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(2.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(sp),
-            gas_left: Delta(-OpcodeId::GetGlobal.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..Default::default()
         };
 
@@ -73,6 +76,7 @@ impl<F: Field> ExecutionGadget<F> for WasmGlobalGadget<F> {
 
         Self {
             same_context,
+            fuel,
             is_set_global,
             is_get_global,
             index,
@@ -90,6 +94,8 @@ impl<F: Field> ExecutionGadget<F> for WasmGlobalGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         match step.opcode.unwrap() {
             OpcodeId::SetGlobal => {
@@ -1,7 +1,8 @@
 use halo2_proofs::circuit::Value;
-use halo2_proofs::plonk::{Error, Expression};
+use halo2_proofs::plonk::Error;
 
 use bus_mapping::evm::OpcodeId;
+use eth_types::bytecode::{wasm_memory_limits, WASM_PAGE_SIZE};
 use eth_types::{Field, ToScalar};
 
 use crate::{
@@ -10,39 +11,40 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
-            constraint_builder::{ConstrainBuilderCommon, StepStateTransition, Transition::Delta},
+            common_gadget::{SameContextGadget, WasmFuelGadget},
+            constraint_builder::{StepStateTransition, Transition::Delta},
+            math_gadget::LtGadget,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
+    table::CallContextFieldTag,
     util::Expr,
+    wasm_circuit::types::WasmModuleTag,
 };
 use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder};
 
+/// The address and the memarg offset fit four bytes each, so the end of the
+/// accessed range and the memory size fit five bytes.
+const N_BYTES_MEMORY_END: usize = 5;
+
+/// Loads are byte granular: every loaded byte is a separate memory read, so an
+/// unaligned access that crosses a word boundary needs no special handling.
 #[derive(Clone, Debug)]
 pub(crate) struct WasmLoadGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
+
+    raw_address: Cell<F>,
+    raw_address_bytes: [Cell<F>; 4],
+    memarg_offset: Cell<F>,
+    memarg_offset_bytes: [Cell<F>; 4],
+    initial_pages: Cell<F>,
+    grown_pages: Cell<F>,
+    is_out_of_bounds: LtGadget<F, N_BYTES_MEMORY_END>,
+    value: Cell<F>,
+    bytes: [Cell<F>; 8],
 
-    opcode_load_offset: Cell<F>,
-
-    load_start_block_index: Cell<F>,
-    load_start_block_inner_offset: Cell<F>,
-    load_start_block_inner_offset_helper: Cell<F>,
-
-    load_end_block_index: Cell<F>,
-    load_end_block_inner_offset: Cell<F>,
-    load_end_block_inner_offset_helper: Cell<F>,
-
-    load_value1: Cell<F>,
-    load_value2: Cell<F>,
-
-    mask_bits: [Cell<F>; 16],
-    offset_modulus: Cell<F>,
-    res: Cell<F>,
-    value_in_heap: Cell<F>,
-    load_base: Cell<F>,
-
-    vtype: Cell<F>,
     is_one_byte: Cell<F>,
     is_two_bytes: Cell<F>,
     is_four_bytes: Cell<F>,
@@ -50,12 +52,8 @@ pub(crate) struct WasmLoadGadget<F> {
     is_sign: Cell<F>,
     is_i64: Cell<F>,
 
-    highest_u4: [Cell<F>; 4],
-
-    //lookup_offset_len_bits: OffsetLenBitsTableLookupCell,
-    //lookup_pow: PowTableLookupCell,
-
-    address_within_allocated_pages_helper: Cell<F>,
+    sign_bit: Cell<F>,
+    sign_rest: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmLoadGadget<F> {
@@ -63,215 +61,180 @@ impl<F: Field> ExecutionGadget<F> for WasmLoadGadget<F> {
 
     const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_LOAD;
 
-    fn configure(cb: &mut ConstrainBuilderCommon<F>) -> Self {
-
-        let opcode_load_offset = cb.alloc_common_range_value();
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
 
-        let load_start_block_index = cb.alloc_common_range_value();
-        let load_start_block_inner_offset = cb.alloc_common_range_value();
-        let load_start_block_inner_offset_helper = cb.alloc_common_range_value();
+        let raw_address = cb.query_cell();
+        let raw_address_bytes = cb.query_bytes();
+        let memarg_offset = cb.query_cell();
+        let memarg_offset_bytes = cb.query_bytes();
+        let initial_pages = cb.query_cell();
+        let grown_pages = cb.query_cell();
+        let value = cb.query_cell();
+        let bytes = cb.query_bytes();
+
+        let is_one_byte = cb.query_bool();
+        let is_two_bytes = cb.query_bool();
+        let is_four_bytes = cb.query_bool();
+        let is_eight_bytes = cb.query_bool();
+        let is_sign = cb.query_bool();
+        let is_i64 = cb.query_bool();
+
+        let sign_bit = cb.query_bool();
+        let sign_rest = cb.query_cell();
+
+        cb.require_equal(
+            "op_load: exactly one width is selected",
+            is_one_byte.expr() + is_two_bytes.expr() + is_four_bytes.expr() + is_eight_bytes.expr(),
+            1.expr(),
+        );
+        cb.require_zero(
+            "op_load: eight bytes are loaded only into i64",
+            is_eight_bytes.expr() * (1.expr() - is_i64.expr()),
+        );
 
-        let load_end_block_index = cb.alloc_common_range_value();
-        let load_end_block_inner_offset = cb.alloc_common_range_value();
-        let load_end_block_inner_offset_helper = cb.alloc_common_range_value();
+        // i32.load and i64.load take the whole width, all the others extend the value.
+        let is_full_width = is_eight_bytes.expr() + is_four_bytes.expr() * (1.expr() - is_i64.expr());
+        cb.require_zero(
+            "op_load: full width loads are not sign extended",
+            is_full_width.clone() * is_sign.expr(),
+        );
+        cb.require_equal(
+            "op_load: opcode matches width, sign and type",
+            opcode.expr(),
+            is_full_width.clone() * (OpcodeId::I32Load.expr() + is_i64.expr())
+                + (1.expr() - is_full_width)
+                    * (OpcodeId::I32Load8S.expr()
+                        + is_i64.expr() * 4.expr()
+                        + is_two_bytes.expr() * 2.expr()
+                        + is_four_bytes.expr() * 4.expr()
+                        + 1.expr()
+                        - is_sign.expr()),
+        );
 
-        let load_value1 = cb.alloc_u64_on_u8();
-        let load_value2 = cb.alloc_u64_on_u8();
-        let offset_modulus = cb.alloc_u64_on_u8();
-        let res = cb.alloc_u64();
-        let value_in_heap = cb.alloc_u64();
-        let load_base = cb.alloc_u64();
+        let is_byte_loaded = |i: usize| match i {
+            0 => 1.expr(),
+            1 => is_two_bytes.expr() + is_four_bytes.expr() + is_eight_bytes.expr(),
+            2 | 3 => is_four_bytes.expr() + is_eight_bytes.expr(),
+            _ => is_eight_bytes.expr(),
+        };
+        let len = is_one_byte.expr()
+            + is_two_bytes.expr() * 2.expr()
+            + is_four_bytes.expr() * 4.expr()
+            + is_eight_bytes.expr() * 8.expr();
 
-        let mask_bits = [0; 16].map(|_| cb.alloc_bit_value());
-        let is_one_byte = cb.alloc_bit_value();
-        let is_two_bytes = cb.alloc_bit_value();
-        let is_four_bytes = cb.alloc_bit_value();
-        let is_eight_bytes = cb.alloc_bit_value();
-        let is_sign = cb.alloc_bit_value();
-        let is_i64 = cb.alloc_bit_value();
-        let vtype = cb.alloc_common_range_value();
+        cb.stack_pop(raw_address.expr());
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::GrownMemoryPages,
+            grown_pages.expr(),
+        );
 
-        let highest_u4 = [0; 4].map(|_| cb.alloc_bit_value());
+        // the memarg offset is the immediate of the instruction at the program counter
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            cb.curr.state.program_counter.expr(),
+            opcode.expr(),
+            memarg_offset.expr(),
+        );
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::MemoryPages,
+            0.expr(),
+            initial_pages.expr(),
+            0.expr(),
+        );
+        for (name, cell, bytes) in [
+            ("op_load: address fits four bytes", &raw_address, &raw_address_bytes),
+            ("op_load: memarg offset fits four bytes", &memarg_offset, &memarg_offset_bytes),
+        ] {
+            cb.require_equal(
+                name,
+                cell.expr(),
+                bytes.iter().rev().fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr()),
+            );
+        }
 
-        //let lookup_offset_len_bits = common.alloc_offset_len_bits_table_lookup();
-        //let lookup_pow = common.alloc_pow_table_lookup();
+        let address = raw_address.expr() + memarg_offset.expr();
+        let is_out_of_bounds = LtGadget::construct(
+            cb,
+            (initial_pages.expr() + grown_pages.expr()) * WASM_PAGE_SIZE.expr(),
+            address.clone() + len.clone(),
+        );
+        cb.require_zero(
+            "op_load: accessed range ends within the memory size",
+            is_out_of_bounds.expr(),
+        );
+        for (i, byte) in bytes.iter().enumerate() {
+            cb.condition(is_byte_loaded(i), |cb| {
+                cb.memory_lookup(0.expr(), address.clone() + i.expr(), byte.expr(), None);
+            });
+            cb.require_zero(
+                "op_load: bytes beyond the width are zero",
+                (1.expr() - is_byte_loaded(i)) * byte.expr(),
+            );
+        }
 
-        let current_memory_page_size = cb.allocated_memory_pages_cell();
-        let address_within_allocated_pages_helper = cb.alloc_common_range_value();
+        // The sign bit is the highest bit of the last loaded byte.
+        let highest_byte = is_one_byte.expr() * bytes[0].expr()
+            + is_two_bytes.expr() * bytes[1].expr()
+            + is_four_bytes.expr() * bytes[3].expr()
+            + is_eight_bytes.expr() * bytes[7].expr();
+        cb.require_equal(
+            "op_load: highest byte = sign_bit * 128 + sign_rest",
+            highest_byte,
+            sign_bit.expr() * 128.expr() + sign_rest.expr(),
+        );
+        cb.range_lookup(sign_rest.expr(), 128);
+
+        let unsigned_value = bytes
+            .iter()
+            .rev()
+            .fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr());
+        let padding = is_one_byte.expr() * 0xffffff00_u64.expr()
+            + is_two_bytes.expr() * 0xffff0000_u64.expr()
+            + is_i64.expr() * (1.expr() - is_eight_bytes.expr()) * 0xffffffff_00000000_u64.expr();
+        cb.require_equal(
+            "op_load: value = bytes + sign extension padding",
+            value.expr(),
+            unsigned_value + is_sign.expr() * sign_bit.expr() * padding,
+        );
 
-        cb.stack_pop(raw_address.expr());
-        cb.stack_pop(block_value1.expr());
-        cb.stack_pop(block_value2.expr());
         cb.stack_push(value.expr());
 
-        cb.require_zeros("op_load: start end offset <= 7", vec![
-            load_start_block_inner_offset.expr()
-                + load_start_block_inner_offset_helper.expr()
-                - 7.expr(),
-            load_end_block_inner_offset.expr()
-                + load_end_block_inner_offset_helper.expr()
-                - 7.expr(),
-        ]);
-
-        cb.require_zeros("op_load: start end equation, start_index * 8 + start_offset + len = stop_index * 8 + stop_offset + 1", {
-            let len = 1.expr()
-                + is_two_bytes.expr() * 1.expr()
-                + is_four_bytes.expr() * 3.expr()
-                + is_eight_bytes.expr() * 7.expr();
-            vec![
-                load_start_block_index.expr() * 8.expr()
-                    + load_start_block_inner_offset.expr()
-                    + len
-                    - 1.expr()
-                    - load_end_block_index.expr() * 8.expr()
-                    - load_end_block_inner_offset.expr(),
-            ]
-        });
-
-        cb.require_zeros("op_load: start load_base", vec![
-            load_base.expr() + opcode_load_offset.expr()
-                - load_start_block_index.expr() * 8.expr()
-                - load_start_block_inner_offset.expr(),
-        ]);
-
-        cb.require_zeros("op_load: length", vec![
-            is_one_byte.expr()
-                + is_two_bytes.expr()
-                + is_four_bytes.expr()
-                + is_eight_bytes.expr()
-                - 1.expr(),
-        ]);
-
-        cb.require_zeros("op_load: mask_bits offset len", {
-            let len = 1.expr()
-                + is_two_bytes.expr() * 1.expr()
-                + is_four_bytes.expr() * 3.expr()
-                + is_eight_bytes.expr() * 7.expr();
-            let (_, bits_encode) = mask_bits
-                .map(|c| c.expr(meta))
-                .into_iter()
-                .enumerate()
-                .reduce(|(_, acc), (i, e)| (i, acc + e * (1u64 << i).expr()))
-                .unwrap();
-            vec![
-                lookup_offset_len_bits.expr()
-                    - offset_len_bits_encode_expr(
-                        load_start_block_inner_offset.expr(),
-                        len,
-                        bits_encode,
-                    ),
-            ]
-        });
-
-        cb.require_zeros("op_load: pow table lookup", vec![
-            lookup_pow.expr(meta)
-                - pow_table_encode(
-                    offset_modulus.expr(),
-                    load_start_block_inner_offset.expr() * 8.expr(),
-                ),
-        ]);
-
-        /*constraint_builder.push(
-            "op_load value_in_heap",
-            Box::new(move |meta| {
-                let mut acc = value_in_heap.expr(meta) * offset_modulus.expr(meta);
-                for i in 0..8 {
-                    acc = acc
-                        - load_value1.u8_expr(meta, i)
-                            * constant!(bn_to_field(&(BigUint::from(1u64) << (i * 8))))
-                            * mask_bits[i as usize].expr(meta);
-                    acc = acc
-                        - load_value2.u8_expr(meta, i)
-                            * constant!(bn_to_field(&(BigUint::from(1u64) << (i * 8 + 64))))
-                            * mask_bits[i as usize + 8].expr(meta);
-                }
-                vec![acc]
-            }),
-        );*/
-
-        /*constraint_builder.push(
-            "op_load value: value = padding + value_in_heap",
-            Box::new(move |meta| {
-                let mut acc = is_one_byte.expr(meta) * value_in_heap.u4_expr(meta, 1)
-                    + is_two_bytes.expr(meta) * value_in_heap.u4_expr(meta, 3)
-                    + is_four_bytes.expr(meta) * value_in_heap.u4_expr(meta, 7)
-                    + is_eight_bytes.expr(meta) * value_in_heap.u4_expr(meta, 15);
-                for i in 0..4 {
-                    acc = acc - highest_u4[i].expr(meta) * constant_from!(1u64 << 3 - i as u64)
-                }
-                let padding = is_one_byte.expr(meta) * constant_from!(0xffffff00)
-                    + is_two_bytes.expr(meta) * constant_from!(0xffff0000)
-                    + (constant_from!(1) - is_eight_bytes.expr(meta))
-                        * is_i64.expr(meta)
-                        * constant_from!(0xffffffff00000000);
-                vec![
-                    res.expr(meta)
-                        - value_in_heap.expr(meta)
-                        - highest_u4[0].expr(meta) * is_sign.expr(meta) * padding,
-                    acc,
-                ]
-            }),
-        );*/
-
-        cb.require_zeros("op_load: is_i64 = 1 when vtype = 2", vec![
-            is_i64.expr() + 1.expr() - vtype.expr()
-        ]);
-
-        cb.require_zeros("op_load: allocated address", {
-            let len = 1.expr()
-                + is_two_bytes.expr(meta) * 1.expr()
-                + is_four_bytes.expr(meta) * 3.expr()
-                + is_eight_bytes.expr(meta) * 7.expr();
-            vec![
-                load_base.expr()
-                    + opcode_load_offset.expr()
-                    + len
-                    + address_within_allocated_pages_helper.expr()
-                    - current_memory_page_size.expr() * WASM_PAGE_SIZE.expr(),
-            ]
-        });
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
 
-        let opcode = cb.query_cell();
-
-        // State transition
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(2.expr()),
+            rw_counter: Delta(3.expr() + len),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(0.expr()),
-            // TODO: Change opcode.
-            gas_left: Delta(-OpcodeId::I32Eqz.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
-            opcode_load_offset,
-            load_start_block_index,
-            load_start_block_inner_offset,
-            load_start_block_inner_offset_helper,
-            load_end_block_index,
-            load_end_block_inner_offset,
-            load_end_block_inner_offset_helper,
-            load_value1,
-            load_value2,
-            mask_bits,
-            offset_modulus,
-            load_base,
-            res,
-            value_in_heap,
+            fuel,
+            raw_address,
+            raw_address_bytes,
+            memarg_offset,
+            memarg_offset_bytes,
+            initial_pages,
+            grown_pages,
+            is_out_of_bounds,
+            value,
+            bytes,
             is_one_byte,
             is_two_bytes,
             is_four_bytes,
             is_eight_bytes,
             is_sign,
             is_i64,
-            highest_u4,
-            vtype,
-            //lookup_stack_write,
-            //lookup_offset_len_bits,
-            lookup_pow,
-            address_within_allocated_pages_helper,
+            sign_bit,
+            sign_rest,
         }
     }
 
@@ -281,38 +244,79 @@ impl<F: Field> ExecutionGadget<F> for WasmLoadGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _call: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         let opcode = step.opcode.unwrap();
+        let (len, is_sign, is_i64) = match opcode {
+            OpcodeId::I32Load => (4, false, false),
+            OpcodeId::I64Load => (8, false, true),
+            OpcodeId::I32Load8S => (1, true, false),
+            OpcodeId::I32Load8U => (1, false, false),
+            OpcodeId::I32Load16S => (2, true, false),
+            OpcodeId::I32Load16U => (2, false, false),
+            OpcodeId::I64Load8S => (1, true, true),
+            OpcodeId::I64Load8U => (1, false, true),
+            OpcodeId::I64Load16S => (2, true, true),
+            OpcodeId::I64Load16U => (2, false, true),
+            OpcodeId::I64Load32S => (4, true, true),
+            OpcodeId::I64Load32U => (4, false, true),
+            _ => unreachable!("not supported opcode: {:?}", opcode),
+        };
 
-        let [rhs, lhs, value] = [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2]]
-            .map(|idx| block.rws[idx].stack_value());
-
-/*
+        let raw_address = block.rws[step.rw_indices[0]].stack_value().as_u64();
+        let grown_pages = block.rws[step.rw_indices[1]].call_context_value().as_u64();
+        let value = block.rws[step.rw_indices[2 + len]].stack_value();
+        let memarg_offset = step.params[0];
+
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let (initial_pages, _) = wasm_memory_limits(code).unwrap_or_default();
+
+        for (cell, bytes, val) in [
+            (&self.raw_address, &self.raw_address_bytes, raw_address),
+            (&self.memarg_offset, &self.memarg_offset_bytes, memarg_offset),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(val)))?;
+            for (byte_cell, byte) in bytes.iter().zip((val as u32).to_le_bytes()) {
+                byte_cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+            }
+        }
+        self.initial_pages.assign(region, offset, Value::known(F::from(initial_pages)))?;
+        self.grown_pages.assign(region, offset, Value::known(F::from(grown_pages)))?;
+        self.is_out_of_bounds.assign(
+            region,
+            offset,
+            F::from((initial_pages + grown_pages) * WASM_PAGE_SIZE),
+            F::from(raw_address + memarg_offset + len as u64),
+        )?;
         self.value.assign(region, offset, Value::known(value.to_scalar().unwrap()))?;
-        self.value_inv.assign(region, offset, Value::known(F::from(value.as_u64()).invert().unwrap_or(F::zero())))?;
-        self.res.assign(region, offset, Value::known(res.to_scalar().unwrap()))?;
 
-        match opcode {
-            OpcodeId::I64Eqz => {
-                let zero_or_one = (value.as_u64() == 0) as u64;
-                self.res.assign(region, offset, Value::known(F::from(zero_or_one)))?;
-            }
-            OpcodeId::I32Eqz => {
-                let zero_or_one = (value.as_u32() == 0) as u64;
-                self.res.assign(region, offset, Value::known(F::from(zero_or_one)))?;
-            }
-            _ => unreachable!("not supported opcode: {:?}", opcode),
-        };
- 
-        let is_i64 = matches!(opcode,
-            OpcodeId::I64Eqz
-        );
+        let mut bytes = [0u8; 8];
+        for (i, byte) in bytes.iter_mut().take(len).enumerate() {
+            *byte = block.rws[step.rw_indices[2 + i]].memory_value();
+        }
+        for (cell, byte) in self.bytes.iter().zip(bytes.iter()) {
+            cell.assign(region, offset, Value::known(F::from(*byte as u64)))?;
+        }
+
+        for (cell, width) in [
+            (&self.is_one_byte, 1),
+            (&self.is_two_bytes, 2),
+            (&self.is_four_bytes, 4),
+            (&self.is_eight_bytes, 8),
+        ] {
+            cell.assign(region, offset, Value::known(F::from((len == width) as u64)))?;
+        }
+        self.is_sign.assign(region, offset, Value::known(F::from(is_sign as u64)))?;
         self.is_i64.assign(region, offset, Value::known(F::from(is_i64 as u64)))?;
-*/
+
+        let highest_byte = bytes[len - 1] as u64;
+        self.sign_bit.assign(region, offset, Value::known(F::from(highest_byte >> 7)))?;
+        self.sign_rest.assign(region, offset, Value::known(F::from(highest_byte & 0x7f)))?;
 
         Ok(())
     }
@@ -320,7 +324,8 @@ impl<F: Field> ExecutionGadget<F> for WasmLoadGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use eth_types::{bytecode, Bytecode};
+    use eth_types::{bytecode, bytecode::WASM_PAGE_SIZE, bytecode_internal, Bytecode};
+    use eth_types::evm_types::OpcodeId;
     use mock::TestContext;
 
     use crate::test_util::CircuitTestBuilder;
@@ -331,29 +336,69 @@ mod test {
         ).run()
     }
 
-/*
+    fn load_from_data(op: OpcodeId, offset: u64) -> Bytecode {
+        let mut code = Bytecode::default();
+        let dest = code.fill_default_global_data(vec![0x81, 0xff, 0x7f, 0x80, 0x01, 0x02, 0x03, 0xf4, 0x55]);
+        bytecode_internal! {code,
+            I32Const[dest]
+            .write_memarg(op, offset, 0, 0)
+            Drop
+        }
+        code
+    }
+
     #[test]
-    fn test_i32_eqz() {
+    fn test_i32_load() {
+        run_test(load_from_data(OpcodeId::I32Load, 0));
+        run_test(load_from_data(OpcodeId::I32Load8S, 0));
+        run_test(load_from_data(OpcodeId::I32Load8U, 0));
+        run_test(load_from_data(OpcodeId::I32Load16S, 0));
+        run_test(load_from_data(OpcodeId::I32Load16U, 0));
+    }
+
+    #[test]
+    fn test_i64_load() {
+        run_test(load_from_data(OpcodeId::I64Load, 0));
+        run_test(load_from_data(OpcodeId::I64Load8S, 0));
+        run_test(load_from_data(OpcodeId::I64Load8U, 0));
+        run_test(load_from_data(OpcodeId::I64Load16S, 0));
+        run_test(load_from_data(OpcodeId::I64Load16U, 0));
+        run_test(load_from_data(OpcodeId::I64Load32S, 0));
+        run_test(load_from_data(OpcodeId::I64Load32U, 0));
+    }
+
+    #[test]
+    fn test_load_with_memarg_offset_unaligned() {
+        run_test(load_from_data(OpcodeId::I32Load, 3));
+        run_test(load_from_data(OpcodeId::I64Load, 1));
+        run_test(load_from_data(OpcodeId::I64Load32S, 5));
+        run_test(load_from_data(OpcodeId::I32Load16S, 7));
+    }
+
+    #[test]
+    fn test_load_at_memory_end() {
         run_test(bytecode! {
-            I32Const[0]
-            I32Eqz
+            I32Const[WASM_PAGE_SIZE - 8]
+            .write_memarg(OpcodeId::I64Load, 0, 3, 0)
             Drop
             I32Const[1]
-            I32Eqz
+            GrowMemory
+            Drop
+            I32Const[WASM_PAGE_SIZE]
+            .write_memarg(OpcodeId::I32Load16U, WASM_PAGE_SIZE - 2, 1, 0)
             Drop
         });
     }
 
     #[test]
-    fn test_i64_eqz() {
+    fn test_load_after_store() {
         run_test(bytecode! {
-            I64Const[0]
-            I64Eqz
-            Drop
-            I64Const[1]
-            I64Eqz
+            I32Const[0x20]
+            I64Const[0x7f80_1122_3344_55ff_u64 as i64]
+            .write_memarg(OpcodeId::I64Store, 0, 3, 0)
+            I32Const[0x21]
+            .write_memarg(OpcodeId::I64Load8S, 6, 0, 0)
             Drop
         });
     }
-*/
 }
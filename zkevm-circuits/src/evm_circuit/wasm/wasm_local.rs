@@ -2,32 +2,54 @@ use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::Error;
 
 use bus_mapping::evm::OpcodeId;
+use eth_types::bytecode::{wasm_entry_function, wasm_function_locals};
 use eth_types::{Field, ToScalar};
 
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
+        param::STACK_CAPACITY,
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{ConstrainBuilderCommon, StepStateTransition, Transition::Delta},
+            math_gadget::LtGadget,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
+    table::{CallContextFieldTag, WasmFunctionTableTag},
     util::Expr,
+    wasm_circuit::types::WasmModuleTag,
 };
 use crate::evm_circuit::util::Cell;
 use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 
+/// Local indices fit four bytes.
+const N_BYTES_LOCAL_INDEX: usize = 4;
+
+/// `local.get`, `local.set` and `local.tee`. The trace gives the depth of the
+/// local in the stack, its index is the height of that slot above the frame
+/// base, must be the immediate of the instruction and be below the local count
+/// of the running function. Params are
+/// the stack slots the caller pushed, declared locals live in the `Local` rw
+/// target keyed by the frame.
 #[derive(Clone, Debug)]
 pub(crate) struct WasmLocalGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
     is_get_local: Cell<F>,
     is_set_local: Cell<F>,
     is_tee_local: Cell<F>,
-    index: Cell<F>,
+    depth: Cell<F>,
     value: Cell<F>,
+    function_id: Cell<F>,
+    locals_base: Cell<F>,
+    locals_frame: Cell<F>,
+    num_params: Cell<F>,
+    num_locals: Cell<F>,
+    is_param: LtGadget<F, N_BYTES_LOCAL_INDEX>,
+    in_bounds: LtGadget<F, N_BYTES_LOCAL_INDEX>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmLocalGadget<F> {
@@ -38,11 +60,11 @@ impl<F: Field> ExecutionGadget<F> for WasmLocalGadget<F> {
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
 
-        let is_get_local = cb.query_cell();
-        let is_set_local = cb.query_cell();
-        let is_tee_local = cb.query_cell();
+        let is_get_local = cb.query_bool();
+        let is_set_local = cb.query_bool();
+        let is_tee_local = cb.query_bool();
 
-        let index = cb.query_cell();
+        let depth = cb.query_cell();
         let value = cb.query_cell();
 
         cb.require_equal(
@@ -50,29 +72,91 @@ impl<F: Field> ExecutionGadget<F> for WasmLocalGadget<F> {
             is_get_local.expr() + is_set_local.expr() + is_tee_local.expr(),
             1.expr(),
         );
+        cb.require_equal(
+            "op_local: opcode matches the selector",
+            opcode.expr(),
+            is_get_local.expr() * OpcodeId::GetLocal.expr()
+                + is_set_local.expr() * OpcodeId::SetLocal.expr()
+                + is_tee_local.expr() * OpcodeId::TeeLocal.expr(),
+        );
+
+        let function_id = cb.query_cell();
+        let locals_base = cb.query_cell();
+        let locals_frame = cb.query_cell();
+        for (field_tag, cell) in [
+            (CallContextFieldTag::InternalFunctionId, &function_id),
+            (CallContextFieldTag::LocalsBase, &locals_base),
+            (CallContextFieldTag::LocalsFrame, &locals_frame),
+        ] {
+            cb.call_context_lookup(false.expr(), None, field_tag, cell.expr());
+        }
+
+        let num_params = cb.query_cell();
+        let num_locals = cb.query_cell();
+        cb.wasm_function_table_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmFunctionTableTag::ParamCount,
+            function_id.expr(),
+            num_params.expr(),
+        );
+        cb.wasm_function_table_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmFunctionTableTag::LocalCount,
+            function_id.expr(),
+            num_locals.expr(),
+        );
+
+        // `local.set` addresses the stack after its pop
+        let stack_height = STACK_CAPACITY.expr() - cb.curr.state.stack_pointer.expr();
+        let local_index = stack_height - is_set_local.expr() - depth.expr() - locals_base.expr();
+        // the index is the immediate of the instruction at the program counter
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            cb.curr.state.program_counter.expr(),
+            opcode.expr(),
+            local_index.clone(),
+        );
+        let in_bounds = LtGadget::construct(cb, local_index.clone(), num_locals.expr());
+        cb.require_equal(
+            "op_local: index is below the local count",
+            in_bounds.expr(),
+            1.expr(),
+        );
+        let is_param = LtGadget::construct(cb, local_index.clone(), num_params.expr());
 
-        cb.condition(is_set_local.expr(), |cb| {
+        cb.condition(1.expr() - is_get_local.expr(), |cb| {
             cb.stack_pop(value.expr());
-            cb.stack_lookup(1.expr(), cb.stack_pointer_offset() + index.expr(), value.expr());
         });
 
-        cb.condition(is_get_local.expr(), |cb| {
-            cb.stack_lookup(0.expr(), cb.stack_pointer_offset() + index.expr(), value.expr());
-            cb.stack_push(value.expr());
+        let is_write = 1.expr() - is_get_local.expr();
+        cb.condition(is_param.expr(), |cb| {
+            cb.stack_lookup(
+                is_write.clone(),
+                depth.expr() - 1.expr() + is_set_local.expr(),
+                value.expr(),
+            );
+        });
+        cb.condition(1.expr() - is_param.expr(), |cb| {
+            cb.local_lookup(
+                is_write,
+                cb.curr.state.call_id.expr() + locals_frame.expr(),
+                local_index,
+                value.expr(),
+            );
         });
 
-        cb.condition(is_tee_local.expr(), |cb| {
-            cb.stack_pop(value.expr());
-            cb.stack_lookup(1.expr(), cb.stack_pointer_offset() + index.expr() - 1.expr(), value.expr());
+        cb.condition(1.expr() - is_set_local.expr(), |cb| {
             cb.stack_push(value.expr());
         });
 
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
+
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(2.expr()),
+            rw_counter: Delta(5.expr() + is_tee_local.expr()),
             program_counter: Delta(1.expr()),
-            stack_pointer: Delta((-1).expr()),
-            // stack_pointer: Delta(is_tee_local.expr() * 2.expr() + (1.expr() - is_tee_local.expr()) * 1.expr()),
-            gas_left: Delta(-OpcodeId::GetLocal.constant_gas_cost().expr()),
+            stack_pointer: Delta(is_set_local.expr() - is_get_local.expr()),
+            gas_left: Delta(-fuel.expr()),
             ..Default::default()
         };
 
@@ -80,11 +164,19 @@ impl<F: Field> ExecutionGadget<F> for WasmLocalGadget<F> {
 
         Self {
             same_context,
+            fuel,
             is_set_local,
             is_get_local,
             is_tee_local,
-            index,
+            depth,
             value,
+            function_id,
+            locals_base,
+            locals_frame,
+            num_params,
+            num_locals,
+            is_param,
+            in_bounds,
         }
     }
 
@@ -94,32 +186,51 @@ impl<F: Field> ExecutionGadget<F> for WasmLocalGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _call: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
-        match step.opcode.unwrap() {
-            OpcodeId::SetLocal => {
-                self.is_set_local.assign(region, offset, Value::known(F::one()))?;
-                let (value, index) = block.rws[step.rw_indices[1]].local_value();
-                self.value.assign(region, offset, Value::<F>::known(value.to_scalar().unwrap()))?;
-                self.index.assign(region, offset, Value::<F>::known(index.to_scalar().unwrap()))?;
-            }
-            OpcodeId::GetLocal => {
-                self.is_get_local.assign(region, offset, Value::known(F::one()))?;
-                let (value, index) = block.rws[step.rw_indices[0]].local_value();
-                self.value.assign(region, offset, Value::<F>::known(value.to_scalar().unwrap()))?;
-                self.index.assign(region, offset, Value::<F>::known(index.to_scalar().unwrap()))?;
-            }
-            OpcodeId::TeeLocal => {
-                self.is_tee_local.assign(region, offset, Value::known(F::one()))?;
-                let (value, index) = block.rws[step.rw_indices[1]].local_value();
-                self.value.assign(region, offset, Value::<F>::known(value.to_scalar().unwrap()))?;
-                self.index.assign(region, offset, Value::<F>::known(index.to_scalar().unwrap()))?;
-            }
-            _ => unreachable!("not supported opcode: {:?}", step.opcode),
+        let opcode = step.opcode.unwrap();
+        for (cell, selector) in [
+            (&self.is_get_local, OpcodeId::GetLocal),
+            (&self.is_set_local, OpcodeId::SetLocal),
+            (&self.is_tee_local, OpcodeId::TeeLocal),
+        ] {
+            cell.assign(region, offset, Value::known(F::from((opcode == selector) as u64)))?;
+        }
+        self.depth.assign(region, offset, Value::known(F::from(step.params[0])))?;
+
+        let mut rw_indices = step.rw_indices.iter().map(|idx| &block.rws[*idx]);
+        for cell in [&self.function_id, &self.locals_base, &self.locals_frame] {
+            let value = rw_indices.next().unwrap().call_context_value();
+            cell.assign(region, offset, Value::known(value.to_scalar().unwrap()))?;
+        }
+
+        // the local access follows the pop of `local.set` and `local.tee`
+        if opcode != OpcodeId::GetLocal {
+            rw_indices.next();
+        }
+        let (value, local_index) = rw_indices.next().unwrap().local_value();
+        self.value.assign(region, offset, Value::<F>::known(value.to_scalar().unwrap()))?;
+
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let function_id = block.rws[step.rw_indices[0]].call_context_value().as_u64();
+        let function_index = match function_id {
+            0 => wasm_entry_function(code),
+            function_id => Some(function_id - 1),
         };
+        let (num_params, num_locals) = function_index
+            .and_then(|idx| wasm_function_locals(code)?.get(idx as usize).copied())
+            .unwrap_or_default();
+        self.num_params.assign(region, offset, Value::known(F::from(num_params)))?;
+        self.num_locals.assign(region, offset, Value::known(F::from(num_locals)))?;
+
+        let local_index = F::from(local_index as u64);
+        self.in_bounds.assign(region, offset, local_index, F::from(num_locals))?;
+        self.is_param.assign(region, offset, local_index, F::from(num_params))?;
 
         Ok(())
     }
@@ -129,10 +240,11 @@ impl<F: Field> ExecutionGadget<F> for WasmLocalGadget<F> {
 mod test {
     use wasm_encoder::ValType;
 
+    use bus_mapping::evm::OpcodeId;
     use eth_types::{bytecode, Bytecode};
     use mock::TestContext;
 
-    use crate::test_util::CircuitTestBuilder;
+    use crate::{table::RwTableTag, test_util::CircuitTestBuilder, witness::Rw};
 
     fn run_test(bytecode: Bytecode) {
         CircuitTestBuilder::new_from_test_ctx(
@@ -197,4 +309,58 @@ mod test {
         code.with_main_locals(vec![(3, ValType::I32)]);
         run_test(code);
     }
+
+    #[test]
+    fn test_locals_are_zero_on_every_entry() {
+        let mut code = bytecode! {
+            I32Const[5]
+            Call[0]
+            Drop
+            I32Const[6]
+            Call[0]
+            Drop
+        };
+        // the second call must not see the declared local set by the first one
+        code.new_function(vec![ValType::I32], vec![ValType::I32], bytecode! {
+            GetLocal[1]
+            GetLocal[0]
+            SetLocal[1]
+        }, vec![(1, ValType::I32)]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_local_index_other_than_the_immediate_must_fail() {
+        let mut code = bytecode! {
+            GetLocal[1]
+            Drop
+        };
+        code.with_main_locals(vec![(2, ValType::I32)]);
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .block_modifier(Box::new(|block| {
+            // read the other zero local, with a depth and a local rw consistent with it
+            let step = block.txs[0]
+                .steps
+                .iter_mut()
+                .find(|step| step.opcode == Some(OpcodeId::GetLocal))
+                .unwrap();
+            step.params[0] += 1;
+            let (tag, index) = *step
+                .rw_indices
+                .iter()
+                .find(|(tag, _)| *tag == RwTableTag::Local)
+                .unwrap();
+            if let Rw::Local { local_index, .. } = &mut block.rws.0.get_mut(&tag).unwrap()[index] {
+                *local_index -= 1;
+            }
+        }))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        .run();
+    }
 }
@@ -0,0 +1,231 @@
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
+
+use bus_mapping::evm::OpcodeId;
+use eth_types::bytecode::wasm_memory_limits;
+use eth_types::{Field, ToScalar};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            CachedRegion,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
+            constraint_builder::{StepStateTransition, Transition::Delta},
+            math_gadget::LtGadget,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::CallContextFieldTag,
+    util::Expr,
+};
+use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder};
+
+/// Page count plus an i32 delta always fits five bytes.
+const N_BYTES_PAGES: usize = 5;
+
+/// Handles both `memory.size` and `memory.grow`. The call context tracks the
+/// pages grown on top of the initial memory, because a call context field starts
+/// at zero in the rw table.
+#[derive(Clone, Debug)]
+pub(crate) struct WasmMemoryGrowGadget<F> {
+    same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
+
+    is_grow: Cell<F>,
+    delta: Cell<F>,
+    grown_pages: Cell<F>,
+    initial_pages: Cell<F>,
+    max_pages: Cell<F>,
+    result: Cell<F>,
+
+    exceeds_max: LtGadget<F, N_BYTES_PAGES>,
+}
+
+impl<F: Field> ExecutionGadget<F> for WasmMemoryGrowGadget<F> {
+    const NAME: &'static str = "WASM_MEMORY_GROW";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_MEMORY_GROW;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let is_grow = cb.query_bool();
+        let delta = cb.query_cell();
+        let grown_pages = cb.query_cell();
+        let initial_pages = cb.query_cell();
+        let max_pages = cb.query_cell();
+        let result = cb.query_cell();
+
+        cb.require_equal(
+            "op_memory_grow: opcode is memory.size or memory.grow",
+            opcode.expr(),
+            OpcodeId::CurrentMemory.expr() + is_grow.expr(),
+        );
+        cb.require_zero(
+            "op_memory_grow: memory.size has no delta",
+            (1.expr() - is_grow.expr()) * delta.expr(),
+        );
+
+        cb.condition(is_grow.expr(), |cb| {
+            cb.stack_pop(delta.expr());
+        });
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::GrownMemoryPages,
+            grown_pages.expr(),
+        );
+
+        let old_pages = initial_pages.expr() + grown_pages.expr();
+        let exceeds_max = LtGadget::construct(cb, max_pages.expr(), old_pages.clone() + delta.expr());
+        let is_success = 1.expr() - exceeds_max.expr();
+
+        cb.condition(is_grow.expr() * is_success.clone(), |cb| {
+            cb.call_context_lookup(
+                true.expr(),
+                None,
+                CallContextFieldTag::GrownMemoryPages,
+                grown_pages.expr() + delta.expr(),
+            );
+        });
+
+        cb.require_equal(
+            "op_memory_grow: result is the old page count or -1 on failure",
+            result.expr(),
+            is_success.clone() * old_pages + exceeds_max.expr() * 0xffffffff_u64.expr(),
+        );
+        cb.stack_push(result.expr());
+
+        // Only the pages actually grown are charged, `memory.size` has no delta.
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
+        let grow_fuel = is_success.clone() * delta.expr() * fuel.per_page_expr();
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(2.expr() + is_grow.expr() * (1.expr() + is_success)),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(is_grow.expr() - 1.expr()),
+            gas_left: Delta(-fuel.expr() - grow_fuel),
+            ..StepStateTransition::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            fuel,
+            is_grow,
+            delta,
+            grown_pages,
+            initial_pages,
+            max_pages,
+            result,
+            exceeds_max,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
+
+        let is_grow = step.opcode.unwrap() == OpcodeId::GrowMemory;
+        let mut rw_indices = step.rw_indices.iter().map(|idx| &block.rws[*idx]);
+        let delta = if is_grow {
+            rw_indices.next().unwrap().stack_value().as_u64()
+        } else {
+            0
+        };
+        let grown_pages = rw_indices.next().unwrap().call_context_value().as_u64();
+        let result = rw_indices.last().unwrap().stack_value();
+
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let (initial_pages, max_pages) = wasm_memory_limits(code).unwrap_or_default();
+
+        self.is_grow.assign(region, offset, Value::known(F::from(is_grow as u64)))?;
+        self.delta.assign(region, offset, Value::known(F::from(delta)))?;
+        self.grown_pages.assign(region, offset, Value::known(F::from(grown_pages)))?;
+        self.initial_pages.assign(region, offset, Value::known(F::from(initial_pages)))?;
+        self.max_pages.assign(region, offset, Value::known(F::from(max_pages)))?;
+        self.result.assign(region, offset, Value::known(result.to_scalar().unwrap()))?;
+
+        self.exceeds_max.assign(
+            region,
+            offset,
+            F::from(max_pages),
+            F::from(initial_pages + grown_pages + delta),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use eth_types::{bytecode, Bytecode};
+    use mock::TestContext;
+
+    use crate::test_util::CircuitTestBuilder;
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_memory_size() {
+        run_test(bytecode! {
+            CurrentMemory
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_memory_grow() {
+        run_test(bytecode! {
+            I32Const[2]
+            GrowMemory
+            Drop
+            I32Const[0]
+            GrowMemory
+            Drop
+            CurrentMemory
+            Drop
+        });
+    }
+
+    #[test]
+    fn test_memory_grow_beyond_max() {
+        let mut code = bytecode! {
+            I32Const[1]
+            GrowMemory
+            Drop
+            I32Const[2]
+            GrowMemory
+            Drop
+            CurrentMemory
+            Drop
+        };
+        code.with_max_memory_pages(2);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_memory_grow_beyond_address_space() {
+        run_test(bytecode! {
+            I32Const[0x10000]
+            GrowMemory
+            Drop
+        });
+    }
+}
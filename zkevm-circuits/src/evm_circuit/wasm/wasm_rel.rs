@@ -11,7 +11,7 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{ConstrainBuilderCommon, StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -29,6 +29,7 @@ const I32_REM_SHIFT: usize = 28usize;
 #[derive(Clone, Debug)]
 pub(crate) struct WasmRelGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
 
     // Neg version of aruments is used to reconstruct it from limbs than `is_neg` makes sense.
     lhs: Cell<F>,
@@ -224,18 +225,20 @@ impl<F: Field> ExecutionGadget<F> for WasmRelGadget<F> {
         let opcode = cb.query_cell();
 
         // State transition
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(2.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(0.expr()),
             // TODO: Change opcode.
-            gas_left: Delta(-OpcodeId::I32Eqz.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
+            fuel,
             lhs,
             is_neg_lhs,
             neg_lhs,
@@ -269,6 +272,8 @@ impl<F: Field> ExecutionGadget<F> for WasmRelGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         let opcode = step.opcode.unwrap();
 
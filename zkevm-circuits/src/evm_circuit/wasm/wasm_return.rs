@@ -0,0 +1,522 @@
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::Error;
+
+use bus_mapping::evm::OpcodeId;
+use eth_types::bytecode::{
+    wasm_function_locals, wasm_function_types, wasm_instructions, wasm_type_arities,
+};
+use eth_types::evm_types::MAX_WASM_ARITY;
+use eth_types::{Field, ToScalar};
+
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::STACK_CAPACITY,
+        step::ExecutionState,
+        util::{
+            CachedRegion,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
+            constraint_builder::{StepStateTransition, Transition::{Delta, To}},
+            math_gadget::IsZeroGadget,
+            sum,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{CallContextFieldTag, WasmFunctionTableTag},
+    util::Expr,
+    wasm_circuit::types::WasmModuleTag,
+};
+use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder};
+
+const N_VALUES: usize = MAX_WASM_ARITY as usize;
+
+/// `return`, the `end` of the function body or a taken `br`/`br_if` to its label,
+/// leaving a function entered by `call` or `call_indirect`. The results declared
+/// by the function type are moved right above the caller stack, and the caller
+/// frame saved by the call is restored. Functions with more than
+/// [`MAX_WASM_ARITY`] results have no arity to select and can't return.
+#[derive(Clone, Debug)]
+pub(crate) struct WasmReturnGadget<F> {
+    same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
+
+    is_end: Cell<F>,
+    is_br: Cell<F>,
+    is_br_if: Cell<F>,
+    label: Cell<F>,
+    cond: Cell<F>,
+    cond_is_zero: IsZeroGadget<F>,
+    function_id: Cell<F>,
+    is_entry_function: IsZeroGadget<F>,
+    locals_base: Cell<F>,
+    locals_frame: Cell<F>,
+    depth: Cell<F>,
+    num_locals: Cell<F>,
+    num_results: Cell<F>,
+    arity: [Cell<F>; N_VALUES + 1],
+    values: [Cell<F>; N_VALUES],
+
+    caller_function_id: Cell<F>,
+    caller_locals_base: Cell<F>,
+    caller_locals_frame: Cell<F>,
+    return_pc: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for WasmReturnGadget<F> {
+    const NAME: &'static str = "WASM_RETURN";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_RETURN;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let is_end = cb.query_bool();
+        let is_br = cb.query_bool();
+        let is_br_if = cb.query_bool();
+        let is_return = 1.expr() - is_end.expr() - is_br.expr() - is_br_if.expr();
+        cb.require_boolean("op_return: at most one instruction is selected", is_return.clone());
+        cb.require_equal(
+            "op_return: opcode is return, end, br or br_if",
+            opcode.expr(),
+            is_end.expr() * OpcodeId::End.expr()
+                + is_br.expr() * OpcodeId::Br.expr()
+                + is_br_if.expr() * OpcodeId::BrIf.expr()
+                + is_return * OpcodeId::Return.expr(),
+        );
+
+        // the `end` of the function body is tagged with 0 and a branch to the
+        // function body with the target 0
+        let label = cb.query_cell();
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            cb.curr.state.program_counter.expr(),
+            opcode.expr(),
+            label.expr(),
+        );
+        // an `end` closing a nested frame is tagged with the pc plus one of its opener
+        cb.condition(is_end.expr(), |cb| {
+            cb.require_zero("op_return: the function body end has label 0", label.expr());
+        });
+        cb.condition(is_br.expr() + is_br_if.expr(), |cb| {
+            cb.wasm_module_lookup(
+                cb.curr.state.code_hash.expr(),
+                WasmModuleTag::Label,
+                cb.curr.state.program_counter.expr(),
+                0.expr(),
+                label.expr(),
+            );
+        });
+
+        // `br_if` pops its condition and only returns when it is taken
+        let cond = cb.query_cell();
+        cb.condition(is_br_if.expr(), |cb| {
+            cb.stack_pop(cond.expr());
+        });
+        let cond_is_zero = IsZeroGadget::construct(cb, cond.expr());
+        cb.require_zero(
+            "op_return: br_if returns when its condition is not zero",
+            is_br_if.expr() * cond_is_zero.expr(),
+        );
+
+        let function_id = cb.query_cell();
+        let locals_base = cb.query_cell();
+        let locals_frame = cb.query_cell();
+        let depth = cb.query_cell();
+        for (field_tag, cell) in [
+            (CallContextFieldTag::InternalFunctionId, &function_id),
+            (CallContextFieldTag::LocalsBase, &locals_base),
+            (CallContextFieldTag::LocalsFrame, &locals_frame),
+            (CallContextFieldTag::FunctionDepth, &depth),
+        ] {
+            cb.call_context_lookup(false.expr(), None, field_tag, cell.expr());
+        }
+        let is_entry_function = IsZeroGadget::construct(cb, function_id.expr());
+        cb.require_zero(
+            "op_return: only a called function returns to a caller",
+            is_entry_function.expr(),
+        );
+
+        let num_locals = cb.query_cell();
+        let num_results = cb.query_cell();
+        for (tag, cell) in [
+            (WasmFunctionTableTag::LocalCount, &num_locals),
+            (WasmFunctionTableTag::ResultCount, &num_results),
+        ] {
+            cb.wasm_function_table_lookup(
+                cb.curr.state.code_hash.expr(),
+                tag,
+                function_id.expr(),
+                cell.expr(),
+            );
+        }
+
+        // a validated body ends with exactly its results above its locals,
+        // `return` may leave more values under them
+        let stack_pointer = cb.curr.state.stack_pointer.expr();
+        cb.condition(is_end.expr(), |cb| {
+            cb.require_equal(
+                "op_return: the function body ends with the results of its type",
+                stack_pointer.clone(),
+                STACK_CAPACITY.expr()
+                    - locals_base.expr()
+                    - num_locals.expr()
+                    - num_results.expr(),
+            );
+        });
+
+        let arity = [(); N_VALUES + 1].map(|_| cb.query_bool());
+        cb.require_equal(
+            "op_return: one arity is selected",
+            sum::expr(arity.iter().map(|cell| cell.expr())),
+            1.expr(),
+        );
+        cb.require_equal(
+            "op_return: the arity is the result count of the function type",
+            sum::expr(
+                arity
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| i.expr() * cell.expr()),
+            ),
+            num_results.expr(),
+        );
+
+        // every result is read before the first write, the ranges may overlap
+        let values = [(); N_VALUES].map(|_| cb.query_cell());
+        let carries = |i: usize| sum::expr(arity[i + 1..].iter().map(|cell| cell.expr()));
+        for (i, value) in values.iter().enumerate() {
+            cb.condition(carries(i), |cb| {
+                cb.stack_lookup(false.expr(), is_br_if.expr() + i.expr(), value.expr());
+            });
+        }
+        for (i, value) in values.iter().enumerate() {
+            cb.condition(carries(i), |cb| {
+                cb.stack_lookup(
+                    true.expr(),
+                    STACK_CAPACITY.expr() - locals_base.expr() - num_results.expr() + i.expr()
+                        - stack_pointer.clone(),
+                    value.expr(),
+                );
+            });
+        }
+
+        let caller_function_id = cb.query_cell();
+        let caller_locals_base = cb.query_cell();
+        let caller_locals_frame = cb.query_cell();
+        let return_pc = cb.query_cell();
+        let frame_id = cb.curr.state.call_id.expr() + locals_frame.expr();
+        for (field_tag, cell) in [
+            (CallContextFieldTag::InternalFunctionId, &caller_function_id),
+            (CallContextFieldTag::LocalsBase, &caller_locals_base),
+            (CallContextFieldTag::LocalsFrame, &caller_locals_frame),
+            (CallContextFieldTag::ProgramCounter, &return_pc),
+        ] {
+            cb.call_context_lookup(false.expr(), Some(frame_id.clone()), field_tag, cell.expr());
+        }
+        for (field_tag, value) in [
+            (CallContextFieldTag::InternalFunctionId, caller_function_id.expr()),
+            (CallContextFieldTag::LocalsBase, caller_locals_base.expr()),
+            (CallContextFieldTag::LocalsFrame, caller_locals_frame.expr()),
+            (CallContextFieldTag::FunctionDepth, depth.expr() - 1.expr()),
+        ] {
+            cb.call_context_lookup(true.expr(), None, field_tag, value);
+        }
+
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
+
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(12.expr() + is_br_if.expr() + num_results.expr() * 2.expr()),
+            program_counter: To(return_pc.expr()),
+            stack_pointer: To(STACK_CAPACITY.expr() - locals_base.expr() - num_results.expr()),
+            gas_left: Delta(-fuel.expr()),
+            ..StepStateTransition::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            fuel,
+            is_end,
+            is_br,
+            is_br_if,
+            label,
+            cond,
+            cond_is_zero,
+            function_id,
+            is_entry_function,
+            locals_base,
+            locals_frame,
+            depth,
+            num_locals,
+            num_results,
+            arity,
+            values,
+            caller_function_id,
+            caller_locals_base,
+            caller_locals_frame,
+            return_pc,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
+
+        let opcode = step.opcode.unwrap();
+        for (cell, op) in [
+            (&self.is_end, OpcodeId::End),
+            (&self.is_br, OpcodeId::Br),
+            (&self.is_br_if, OpcodeId::BrIf),
+        ] {
+            cell.assign(region, offset, Value::known(F::from((opcode == op) as u64)))?;
+        }
+
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let (_, _, label) = wasm_instructions(code)
+            .and_then(|instructions| instructions.get(step.program_counter as usize).copied())
+            .unwrap_or_default();
+        self.label.assign(region, offset, Value::known(F::from(label)))?;
+
+        let mut rws = step.rw_indices.iter().map(|idx| &block.rws[*idx]);
+        let cond = if opcode == OpcodeId::BrIf {
+            rws.next().unwrap().stack_value().to_scalar().unwrap()
+        } else {
+            F::zero()
+        };
+        self.cond.assign(region, offset, Value::known(cond))?;
+        self.cond_is_zero.assign(region, offset, cond)?;
+
+        let mut next_call_context_value = || rws.next().unwrap().call_context_value().low_u64();
+        let function_id = next_call_context_value();
+        for (cell, value) in [
+            (&self.function_id, function_id),
+            (&self.locals_base, next_call_context_value()),
+            (&self.locals_frame, next_call_context_value()),
+            (&self.depth, next_call_context_value()),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(value)))?;
+        }
+        self.is_entry_function.assign(region, offset, F::from(function_id))?;
+
+        let function_index = function_id as usize - 1;
+        let (_, num_locals) = wasm_function_locals(code)
+            .and_then(|locals| locals.get(function_index).copied())
+            .unwrap_or_default();
+        let (_, num_results) = wasm_function_types(code)
+            .and_then(|types| types.get(function_index).copied())
+            .and_then(|type_index| wasm_type_arities(code)?.get(type_index as usize).copied())
+            .unwrap_or_default();
+        self.num_locals.assign(region, offset, Value::known(F::from(num_locals)))?;
+        self.num_results.assign(region, offset, Value::known(F::from(num_results)))?;
+        for (i, cell) in self.arity.iter().enumerate() {
+            cell.assign(
+                region,
+                offset,
+                Value::known(F::from((i as u64 == num_results) as u64)),
+            )?;
+        }
+
+        for (i, cell) in self.values.iter().enumerate() {
+            let value = if (i as u64) < num_results {
+                rws.next().unwrap().stack_value().to_scalar().unwrap()
+            } else {
+                F::zero()
+            };
+            cell.assign(region, offset, Value::known(value))?;
+        }
+        // skip the writes of the results
+        let mut rws = rws.skip(num_results as usize);
+
+        let mut next_call_context_value = || rws.next().unwrap().call_context_value().low_u64();
+        for cell in [
+            &self.caller_function_id,
+            &self.caller_locals_base,
+            &self.caller_locals_frame,
+            &self.return_pc,
+        ] {
+            cell.assign(region, offset, Value::known(F::from(next_call_context_value())))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use wasm_encoder::ValType;
+
+    use bus_mapping::evm::OpcodeId;
+    use eth_types::{bytecode, Bytecode};
+    use halo2_proofs::dev::VerifyFailure;
+    use mock::test_ctx::TestContext;
+
+    use crate::{evm_circuit::step::ExecutionState, test_util::CircuitTestBuilder};
+
+    fn run_test(bytecode: Bytecode) {
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        ).run()
+    }
+
+    #[test]
+    fn test_end_of_function_body() {
+        let mut code = bytecode! {
+            I32Const[5]
+            Call[0]
+            Drop
+        };
+        code.new_function(vec![ValType::I32], vec![ValType::I32], bytecode! {
+            GetLocal[0]
+            I32Const[1]
+            I32Add
+        }, vec![]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_return_drops_the_callee_stack() {
+        let mut code = bytecode! {
+            I32Const[7]
+            Call[0]
+            Drop
+        };
+        code.new_function(vec![ValType::I32], vec![ValType::I32], bytecode! {
+            I32Const[1]
+            I32Const[2]
+            Block
+                GetLocal[0]
+                Return
+            End
+            Drop
+        }, vec![(1, ValType::I64)]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_recursive_factorial() {
+        let mut code = bytecode! {
+            I32Const[5]
+            Call[0]
+            Drop
+        };
+        code.new_function(vec![ValType::I32], vec![ValType::I32], bytecode! {
+            GetLocal[0]
+            I32Eqz
+            If
+                I32Const[1]
+                Return
+            End
+            GetLocal[0]
+            GetLocal[0]
+            I32Const[1]
+            I32Sub
+            Call[0]
+            I32Mul
+        }, vec![]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_br_if_to_function_label() {
+        let mut code = bytecode! {
+            I32Const[5]
+            Call[0]
+            Drop
+        };
+        code.new_function(vec![ValType::I32], vec![ValType::I32], bytecode! {
+            GetLocal[0]
+            Block
+                GetLocal[0]
+                GetLocal[0]
+                BrIf[1]
+                Drop
+            End
+        }, vec![]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_return_multiple_results() {
+        let mut code = bytecode! {
+            I32Const[3]
+            Call[0]
+            Drop
+            Drop
+            Drop
+        };
+        code.new_function(vec![ValType::I32], vec![ValType::I32, ValType::I64, ValType::I32], bytecode! {
+            I32Const[1]
+            GetLocal[0]
+            I64Const[2]
+            GetLocal[0]
+            Return
+        }, vec![]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_br_to_function_label_with_results() {
+        let mut code = bytecode! {
+            Call[0]
+            Drop
+            Drop
+        };
+        code.new_function(vec![], vec![ValType::I64, ValType::I32], bytecode! {
+            Block
+                I64Const[4]
+                I32Const[5]
+                Br[1]
+            End
+            I64Const[6]
+            I32Const[7]
+        }, vec![(1, ValType::I32)]);
+        run_test(code);
+    }
+
+    #[test]
+    fn test_end_of_nested_block_as_return_must_fail() {
+        let mut code = bytecode! {
+            Call[0]
+        };
+        code.new_function(vec![], vec![], bytecode! {
+            Block
+            End
+        }, vec![]);
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .block_modifier(Box::new(|block| {
+            // return at the `end` of the block instead of the one of the function body
+            let step = block.txs[0]
+                .steps
+                .iter_mut()
+                .find(|step| {
+                    step.execution_state == ExecutionState::WASM_RETURN
+                        && step.opcode == Some(OpcodeId::End)
+                })
+                .unwrap();
+            step.program_counter -= 1;
+        }))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            let errors = prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .expect_err("the end of a block is proven as a return");
+            assert!(errors.iter().any(|error| matches!(
+                error,
+                VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                    if format!("{}", constraint).contains("the function body end has label 0")
+            )));
+        }))
+        .run();
+    }
+}
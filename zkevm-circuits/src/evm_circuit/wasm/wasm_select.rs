@@ -9,7 +9,7 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -22,6 +22,7 @@ use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 #[derive(Clone, Debug)]
 pub(crate) struct WasmSelectGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
     cond: Cell<F>,
     cond_inv: Cell<F>,
     val1: Cell<F>,
@@ -60,17 +61,19 @@ impl<F: Field> ExecutionGadget<F> for WasmSelectGadget<F> {
         let opcode = cb.query_cell();
 
         // State transition
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(4.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::Select.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
+            fuel,
             cond,
             cond_inv,
             val1,
@@ -94,6 +97,9 @@ impl<F: Field> ExecutionGadget<F> for WasmSelectGadget<F> {
 
         self.same_context.assign_exec_step(region, offset, step)?;
 
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
+
         let opcode = step.opcode.unwrap();
 
         let [cond, val2, val1, res] = [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2], step.rw_indices[3]]
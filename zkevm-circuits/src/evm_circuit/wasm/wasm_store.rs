@@ -1,7 +1,8 @@
 use halo2_proofs::circuit::Value;
-use halo2_proofs::plonk::{Error, Expression};
+use halo2_proofs::plonk::Error;
 
 use bus_mapping::evm::OpcodeId;
+use eth_types::bytecode::{wasm_memory_limits, WASM_PAGE_SIZE};
 use eth_types::{Field, ToScalar};
 
 use crate::{
@@ -10,50 +11,45 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
-            constraint_builder::{ConstrainBuilderCommon, StepStateTransition, Transition::Delta},
+            common_gadget::{SameContextGadget, WasmFuelGadget},
+            constraint_builder::{StepStateTransition, Transition::Delta},
+            math_gadget::LtGadget,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
+    table::CallContextFieldTag,
     util::Expr,
+    wasm_circuit::types::WasmModuleTag,
 };
 use crate::evm_circuit::util::Cell;
+use crate::evm_circuit::util::constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder};
 
+/// The address and the memarg offset fit four bytes each, so the end of the
+/// accessed range and the memory size fit five bytes.
+const N_BYTES_MEMORY_END: usize = 5;
+
+/// Stores are byte granular: every stored byte is a separate memory write, so an
+/// unaligned access that crosses a word boundary needs no special handling.
 #[derive(Clone, Debug)]
 pub(crate) struct WasmStoreGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
+
+    raw_address: Cell<F>,
+    raw_address_bytes: [Cell<F>; 4],
+    memarg_offset: Cell<F>,
+    memarg_offset_bytes: [Cell<F>; 4],
+    initial_pages: Cell<F>,
+    grown_pages: Cell<F>,
+    is_out_of_bounds: LtGadget<F, N_BYTES_MEMORY_END>,
+    value: Cell<F>,
+    value_bytes: [Cell<F>; 8],
 
-    opcode_store_offset: Cell<F>,
-
-    store_start_block_index: Cell<F>,
-    store_start_block_inner_offset: Cell<F>,
-    store_start_block_inner_offset_helper: Cell<F>,
-
-    store_end_block_index: Cell<F>,
-    store_end_block_inner_offset: Cell<F>,
-    store_end_block_inner_offset_helper: Cell<F>,
-
-    load_value1: Cell<F>,
-    load_value2: Cell<F>,
-    store_value1: Cell<F>,
-    store_value2: Cell<F>,
-
-    mask_bits: [Cell<F>; 16],
-    offset_modulus: Cell<F>,
-    store_raw_value: Cell<F>,
-    store_base: Cell<F>,
-    store_wrapped_value: Cell<F>,
-
-    vtype: Cell<F>,
     is_one_byte: Cell<F>,
     is_two_bytes: Cell<F>,
     is_four_bytes: Cell<F>,
     is_eight_bytes: Cell<F>,
-
-    //lookup_offset_len_bits: OffsetLenBitsTableLookupCell,
-    //lookup_pow: PowTableLookupCell,
-
-    address_within_allocated_pages_helper: Cell<F>,
+    is_i64: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for WasmStoreGadget<F> {
@@ -61,239 +57,154 @@ impl<F: Field> ExecutionGadget<F> for WasmStoreGadget<F> {
 
     const EXECUTION_STATE: ExecutionState = ExecutionState::WASM_STORE;
 
-    fn configure(cb: &mut ConstrainBuilderCommon<F>) -> Self {
-        let opcode_store_offset = cb.alloc_common_range_value();
-
-        let store_start_block_index = cb.alloc_common_range_value();
-        let store_start_block_inner_offset = cb.alloc_common_range_value();
-        let store_start_block_inner_offset_helper = cb.alloc_common_range_value();
-
-        let store_end_block_index = cb.alloc_common_range_value();
-        let store_end_block_inner_offset = cb.alloc_common_range_value();
-        let store_end_block_inner_offset_helper = cb.alloc_common_range_value();
-
-        let load_value1 = cb.alloc_u64_on_u8();
-        let load_value2 = cb.alloc_u64_on_u8();
-        let store_value1 = cb.alloc_u64_on_u8();
-        let store_value2 = cb.alloc_u64_on_u8();
-        let offset_modulus = cb.alloc_u64();
-        let store_raw_value = cb.alloc_u64();
-        let store_base = cb.alloc_u64();
-
-        let store_wrapped_value = cb.alloc_unlimited_value();
-
-        let mask_bits = [0; 16].map(|_| cb.alloc_bit_value());
-        let is_one_byte = cb.alloc_bit_value();
-        let is_two_bytes = cb.alloc_bit_value();
-        let is_four_bytes = cb.alloc_bit_value();
-        let is_eight_bytes = cb.alloc_bit_value();
-        let vtype = cb.alloc_common_range_value();
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
 
-        let lookup_offset_len_bits = cb.alloc_offset_len_bits_table_lookup();
-        let lookup_pow = cb.alloc_pow_table_lookup();
+        let raw_address = cb.query_cell();
+        let raw_address_bytes = cb.query_bytes();
+        let memarg_offset = cb.query_cell();
+        let memarg_offset_bytes = cb.query_bytes();
+        let initial_pages = cb.query_cell();
+        let grown_pages = cb.query_cell();
+        let value = cb.query_cell();
+        let value_bytes = cb.query_bytes();
+
+        let is_one_byte = cb.query_bool();
+        let is_two_bytes = cb.query_bool();
+        let is_four_bytes = cb.query_bool();
+        let is_eight_bytes = cb.query_bool();
+        let is_i64 = cb.query_bool();
+
+        cb.require_equal(
+            "op_store: exactly one width is selected",
+            is_one_byte.expr() + is_two_bytes.expr() + is_four_bytes.expr() + is_eight_bytes.expr(),
+            1.expr(),
+        );
+        cb.require_zero(
+            "op_store: eight bytes are stored only from i64",
+            is_eight_bytes.expr() * (1.expr() - is_i64.expr()),
+        );
 
-        let current_memory_page_size = cb.allocated_memory_pages_cell();
-        let address_within_allocated_pages_helper = cb.alloc_common_range_value();
+        // i32.store and i64.store take the whole width, all the others wrap the value.
+        let is_full_width = is_eight_bytes.expr() + is_four_bytes.expr() * (1.expr() - is_i64.expr());
+        cb.require_equal(
+            "op_store: opcode matches width and type",
+            opcode.expr(),
+            is_full_width.clone() * (OpcodeId::I32Store.expr() + is_i64.expr())
+                + (1.expr() - is_full_width)
+                    * (OpcodeId::I32Store8.expr()
+                        + is_i64.expr() * 2.expr()
+                        + is_two_bytes.expr()
+                        + is_four_bytes.expr() * 2.expr()),
+        );
 
         cb.stack_pop(value.expr());
         cb.stack_pop(raw_address.expr());
-        cb.stack_pop(pre_block_value.expr());
-        cb.stack_push(update_block_value1.expr());
-
-        cb.require_zeros("op_store: start end offset range", vec![
-            store_start_block_inner_offset.expr()
-                + store_start_block_inner_offset_helper.expr()
-                - 7.expr(),
-            store_end_block_inner_offset.expr()
-                + store_end_block_inner_offset_helper.expr()
-                - 7.expr(),
-        ]);
-
-        cb.require_zeros("op_store: start end equation", {
-            let len = 1.expr()
-                + is_two_bytes.expr() * 1.expr()
-                + is_four_bytes.expr() * 3.expr()
-                + is_eight_bytes.expr() * 7.expr();
-            vec![
-                store_start_block_index.expr() * 8.expr()
-                    + store_start_block_inner_offset.expr()
-                    + len
-                    - 1.expr()
-                    - store_end_block_index.expr() * 8.expr()
-                    - store_end_block_inner_offset.expr(),
-            ]
-        });
+        cb.call_context_lookup(
+            false.expr(),
+            None,
+            CallContextFieldTag::GrownMemoryPages,
+            grown_pages.expr(),
+        );
 
-        cb.require_zeros("op_store: start store_base", vec![
-            store_base.expr() + opcode_store_offset.expr()
-                - store_start_block_index.expr() * 8.expr()
-                - store_start_block_inner_offset.expr(),
-        ]);
-
-        cb.require_zeros("op_store: length", vec![
-            is_one_byte.expr()
-                + is_two_bytes.expr()
-                + is_four_bytes.expr()
-                + is_eight_bytes.expr()
-                - 1.expr(),
-        ]);
-
-        cb.require_zeros("op_store: mask_bits offset len", {
-            let len = 1.expr()
-                + is_two_bytes.expr() * 1.expr()
-                + is_four_bytes.expr() * 3.expr()
-                + is_eight_bytes.expr() * 7.expr();
-            let (_, bits_encode) = mask_bits
-                .map(|c| c.expr())
-                .into_iter()
-                .enumerate()
-                .reduce(|(_, acc), (i, e)| (i, acc + e * (1u64 << i).expr()))
-                .unwrap();
-            vec![
-                lookup_offset_len_bits.expr()
-                    - offset_len_bits_encode_expr(
-                        store_start_block_inner_offset.expr(),
-                        len,
-                        bits_encode,
-                    ),
-            ]
-        });
+        // the memarg offset is the immediate of the instruction at the program counter
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::Instruction,
+            cb.curr.state.program_counter.expr(),
+            opcode.expr(),
+            memarg_offset.expr(),
+        );
+        cb.wasm_module_lookup(
+            cb.curr.state.code_hash.expr(),
+            WasmModuleTag::MemoryPages,
+            0.expr(),
+            initial_pages.expr(),
+            0.expr(),
+        );
+        for (name, cell, bytes) in [
+            ("op_store: address fits four bytes", &raw_address, &raw_address_bytes),
+            ("op_store: memarg offset fits four bytes", &memarg_offset, &memarg_offset_bytes),
+        ] {
+            cb.require_equal(
+                name,
+                cell.expr(),
+                bytes.iter().rev().fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr()),
+            );
+        }
 
-        cb.require_zeros("op_store: pow table lookup", vec![
-            lookup_pow.expr()
-                - pow_table_encode(
-                    offset_modulus.expr(),
-                    store_start_block_inner_offset.expr() * 8.expr(),
-                ),
-        ]);
-
-        /*constraint_builder.push(
-            "op_store wrap value",
-            Box::new(move |meta| {
-                let has_two_bytes =
-                    is_two_bytes.expr(meta) + is_four_bytes.expr(meta) + is_eight_bytes.expr(meta);
-                let has_four_bytes = is_four_bytes.expr(meta) + is_eight_bytes.expr(meta);
-                let has_eight_bytes = is_eight_bytes.expr(meta);
-                let byte_value = (0..8)
-                    .map(|i| {
-                        store_raw_value.u4_expr(meta, i * 2) * constant_from!(1u64 << (8 * i))
-                            + store_raw_value.u4_expr(meta, i * 2 + 1)
-                                * constant_from!(1u64 << (8 * i + 4))
-                    })
-                    .collect::<Vec<_>>();
-                vec![
-                    byte_value[0].clone()
-                        + byte_value[1].clone() * has_two_bytes
-                        + (byte_value[2].clone() + byte_value[3].clone()) * has_four_bytes
-                        + (byte_value[4].clone()
-                            + byte_value[5].clone()
-                            + byte_value[6].clone()
-                            + byte_value[7].clone())
-                            * has_eight_bytes
-                        - store_wrapped_value.expr(meta),
-                ]
-            }),
-        );*/
-
-        /*constraint_builder.push(
-            "op_store write value",
-            Box::new(move |meta| {
-                let mut acc = store_wrapped_value.expr(meta) * offset_modulus.expr(meta);
-
-                for i in 0..8 {
-                    acc = acc
-                        - store_value1.u8_expr(meta, i)
-                            * constant!(bn_to_field(&(BigUint::from(1u64) << (i * 8))))
-                            * mask_bits[i as usize].expr(meta);
-
-                    acc = acc
-                        - store_value2.u8_expr(meta, i)
-                            * constant!(bn_to_field(&(BigUint::from(1u64) << (i * 8 + 64))))
-                            * mask_bits[i as usize + 8].expr(meta);
-                }
-
-                vec![acc]
-            }),
-        );*/
-
-        /*constraint_builder.push(
-            "op_store unchanged value",
-            Box::new(move |meta| {
-                let mut acc = constant_from!(0);
-
-                for i in 0..8 {
-                    acc = acc
-                        + load_value1.u8_expr(meta, i)
-                            * constant!(bn_to_field(&(BigUint::from(1u64) << (i * 8))))
-                            * (constant_from!(1) - mask_bits[i as usize].expr(meta))
-                        - store_value1.u8_expr(meta, i)
-                            * constant!(bn_to_field(&(BigUint::from(1u64) << (i * 8))))
-                            * (constant_from!(1) - mask_bits[i as usize].expr(meta));
-
-                    acc = acc
-                        + load_value2.u8_expr(meta, i)
-                            * constant!(bn_to_field(&(BigUint::from(1u64) << (i * 8 + 64))))
-                            * (constant_from!(1) - mask_bits[i as usize + 8].expr(meta))
-                        - store_value2.u8_expr(meta, i)
-                            * constant!(bn_to_field(&(BigUint::from(1u64) << (i * 8 + 64))))
-                            * (constant_from!(1) - mask_bits[i as usize + 8].expr(meta));
-                }
-
-                vec![acc]
-            }),
-        );*/
-
-        cb.require_zeros("op_store: allocated address", {
-            let len = 1.expr()
-                + is_two_bytes.expr() * 1.expr()
-                + is_four_bytes.expr() * 3.expr()
-                + is_eight_bytes.expr() * 7.expr();
-            vec![
-                (store_base.expr()
-                    + opcode_store_offset.expr()
-                    + len
-                    + address_within_allocated_pages_helper.expr()
-                    - current_memory_page_size.expr() * WASM_PAGE_SIZE.expr()),
-            ]
-        });
+        // The whole value is decomposed so the stored low bytes are unique, the high
+        // bytes are dropped by the wrapping stores.
+        cb.require_equal(
+            "op_store: value from bytes",
+            value.expr(),
+            value_bytes
+                .iter()
+                .rev()
+                .fold(0.expr(), |acc, byte| acc * 256.expr() + byte.expr()),
+        );
+        for byte in value_bytes.iter().skip(4) {
+            cb.require_zero(
+                "op_store: i32 value fits four bytes",
+                (1.expr() - is_i64.expr()) * byte.expr(),
+            );
+        }
 
-        let opcode = cb.query_cell();
+        let is_byte_stored = |i: usize| match i {
+            0 => 1.expr(),
+            1 => is_two_bytes.expr() + is_four_bytes.expr() + is_eight_bytes.expr(),
+            2 | 3 => is_four_bytes.expr() + is_eight_bytes.expr(),
+            _ => is_eight_bytes.expr(),
+        };
+        let len = is_one_byte.expr()
+            + is_two_bytes.expr() * 2.expr()
+            + is_four_bytes.expr() * 4.expr()
+            + is_eight_bytes.expr() * 8.expr();
+
+        let address = raw_address.expr() + memarg_offset.expr();
+        let is_out_of_bounds = LtGadget::construct(
+            cb,
+            (initial_pages.expr() + grown_pages.expr()) * WASM_PAGE_SIZE.expr(),
+            address.clone() + len.clone(),
+        );
+        cb.require_zero(
+            "op_store: accessed range ends within the memory size",
+            is_out_of_bounds.expr(),
+        );
+        for (i, byte) in value_bytes.iter().enumerate() {
+            cb.condition(is_byte_stored(i), |cb| {
+                cb.memory_lookup(1.expr(), address.clone() + i.expr(), byte.expr(), None);
+            });
+        }
+
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
 
-        // State transition
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(4.expr()),
+            rw_counter: Delta(3.expr() + len),
             program_counter: Delta(1.expr()),
-            stack_pointer: Delta(0.expr()),
-            // TODO: change op.
-            gas_left: Delta(-OpcodeId::I32Eqz.constant_gas_cost().expr()),
+            stack_pointer: Delta(2.expr()),
+            gas_left: Delta(-fuel.expr()),
             ..StepStateTransition::default()
         };
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
-            opcode_store_offset,
-            store_start_block_index,
-            store_start_block_inner_offset,
-            store_start_block_inner_offset_helper,
-            store_end_block_index,
-            store_end_block_inner_offset,
-            store_end_block_inner_offset_helper,
-            store_value1,
-            store_value2,
-            mask_bits,
-            offset_modulus,
-            store_base,
-            store_raw_value,
-            store_wrapped_value,
+            fuel,
+            raw_address,
+            raw_address_bytes,
+            memarg_offset,
+            memarg_offset_bytes,
+            initial_pages,
+            grown_pages,
+            is_out_of_bounds,
+            value,
+            value_bytes,
             is_one_byte,
             is_two_bytes,
             is_four_bytes,
             is_eight_bytes,
-            vtype,
-            load_value1,
-            load_value2,
-            address_within_allocated_pages_helper,
+            is_i64,
         }
     }
 
@@ -303,44 +214,65 @@ impl<F: Field> ExecutionGadget<F> for WasmStoreGadget<F> {
         offset: usize,
         block: &Block<F>,
         _: &Transaction,
-        _call: &Call,
+        call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         let opcode = step.opcode.unwrap();
+        let (len, is_i64) = match opcode {
+            OpcodeId::I32Store => (4, false),
+            OpcodeId::I64Store => (8, true),
+            OpcodeId::I32Store8 => (1, false),
+            OpcodeId::I32Store16 => (2, false),
+            OpcodeId::I64Store8 => (1, true),
+            OpcodeId::I64Store16 => (2, true),
+            OpcodeId::I64Store32 => (4, true),
+            _ => unreachable!("not supported opcode: {:?}", opcode),
+        };
 
-        cb.stack_pop(value.expr());
-        cb.stack_pop(raw_address.expr());
-        cb.stack_pop(pre_block_value.expr());
-        cb.stack_push(update_block_value1.expr());
+        let value = block.rws[step.rw_indices[0]].stack_value();
+        let raw_address = block.rws[step.rw_indices[1]].stack_value().as_u64();
+        let grown_pages = block.rws[step.rw_indices[2]].call_context_value().as_u64();
+        let memarg_offset = step.params[0];
 
-        let [value, raw_address, pre_block_value, update_block_value1] =
-            [step.rw_indices[0], step.rw_indices[1], step.rw_indices[2], step.rw_indices[3]]
-            .map(|idx| block.rws[idx].stack_value());
+        let code = &block.bytecodes.get(&call.code_hash).unwrap().bytes;
+        let (initial_pages, _) = wasm_memory_limits(code).unwrap_or_default();
 
-/*
         self.value.assign(region, offset, Value::known(value.to_scalar().unwrap()))?;
-        self.value_inv.assign(region, offset, Value::known(F::from(value.as_u64()).invert().unwrap_or(F::zero())))?;
-        self.res.assign(region, offset, Value::known(res.to_scalar().unwrap()))?;
-
-        match opcode {
-            OpcodeId::I64Eqz => {
-                let zero_or_one = (value.as_u64() == 0) as u64;
-                self.res.assign(region, offset, Value::known(F::from(zero_or_one)))?;
+        for (cell, bytes, val) in [
+            (&self.raw_address, &self.raw_address_bytes, raw_address),
+            (&self.memarg_offset, &self.memarg_offset_bytes, memarg_offset),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(val)))?;
+            for (byte_cell, byte) in bytes.iter().zip((val as u32).to_le_bytes()) {
+                byte_cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
             }
-            OpcodeId::I32Eqz => {
-                let zero_or_one = (value.as_u32() == 0) as u64;
-                self.res.assign(region, offset, Value::known(F::from(zero_or_one)))?;
-            }
-            _ => unreachable!("not supported opcode: {:?}", opcode),
-        };
- 
-        let is_i64 = matches!(opcode,
-            OpcodeId::I64Eqz
-        );
+        }
+        self.initial_pages.assign(region, offset, Value::known(F::from(initial_pages)))?;
+        self.grown_pages.assign(region, offset, Value::known(F::from(grown_pages)))?;
+        self.is_out_of_bounds.assign(
+            region,
+            offset,
+            F::from((initial_pages + grown_pages) * WASM_PAGE_SIZE),
+            F::from(raw_address + memarg_offset + len as u64),
+        )?;
+
+        for (cell, byte) in self.value_bytes.iter().zip(value.as_u64().to_le_bytes()) {
+            cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+        }
+
+        for (cell, width) in [
+            (&self.is_one_byte, 1),
+            (&self.is_two_bytes, 2),
+            (&self.is_four_bytes, 4),
+            (&self.is_eight_bytes, 8),
+        ] {
+            cell.assign(region, offset, Value::known(F::from((len == width) as u64)))?;
+        }
         self.is_i64.assign(region, offset, Value::known(F::from(is_i64 as u64)))?;
-*/
 
         Ok(())
     }
@@ -348,7 +280,8 @@ impl<F: Field> ExecutionGadget<F> for WasmStoreGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use eth_types::{bytecode, Bytecode};
+    use eth_types::{bytecode, bytecode::WASM_PAGE_SIZE, Bytecode};
+    use eth_types::evm_types::OpcodeId;
     use mock::TestContext;
 
     use crate::test_util::CircuitTestBuilder;
@@ -359,29 +292,54 @@ mod test {
         ).run()
     }
 
-/*
     #[test]
-    fn test_i32_eqz() {
+    fn test_i32_store() {
+        for op in [OpcodeId::I32Store, OpcodeId::I32Store8, OpcodeId::I32Store16] {
+            run_test(bytecode! {
+                I32Const[0]
+                I32Const[-2]
+                .write_memarg(op, 0, 0, 0)
+            });
+        }
+    }
+
+    #[test]
+    fn test_i64_store() {
+        for op in [
+            OpcodeId::I64Store,
+            OpcodeId::I64Store8,
+            OpcodeId::I64Store16,
+            OpcodeId::I64Store32,
+        ] {
+            run_test(bytecode! {
+                I32Const[0]
+                I64Const[0x0102_0304_0506_0708]
+                .write_memarg(op, 0, 0, 0)
+            });
+        }
+    }
+
+    #[test]
+    fn test_store_at_memory_end() {
         run_test(bytecode! {
-            I32Const[0]
-            I32Eqz
-            Drop
-            I32Const[1]
-            I32Eqz
-            Drop
+            I32Const[WASM_PAGE_SIZE - 4]
+            I32Const[-1]
+            .write_memarg(OpcodeId::I32Store, 0, 2, 0)
+            I32Const[WASM_PAGE_SIZE - 0x10]
+            I64Const[0x55]
+            .write_memarg(OpcodeId::I64Store8, 0xf, 0, 0)
         });
     }
 
     #[test]
-    fn test_i64_eqz() {
+    fn test_store_with_memarg_offset_unaligned() {
         run_test(bytecode! {
-            I64Const[0]
-            I64Eqz
-            Drop
-            I64Const[1]
-            I64Eqz
-            Drop
+            I32Const[5]
+            I64Const[-1]
+            .write_memarg(OpcodeId::I64Store, 3, 0, 0)
+            I32Const[1]
+            I32Const[0x7fff]
+            .write_memarg(OpcodeId::I32Store16, 0x1f, 0, 0)
         });
     }
-*/
 }
@@ -11,7 +11,7 @@ use crate::{
         step::ExecutionState,
         util::{
             CachedRegion,
-            common_gadget::SameContextGadget,
+            common_gadget::{SameContextGadget, WasmFuelGadget},
             constraint_builder::{StepStateTransition, Transition::Delta},
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -24,6 +24,7 @@ use crate::evm_circuit::util::constraint_builder::EVMConstraintBuilder;
 #[derive(Clone, Debug)]
 pub(crate) struct WasmUnaryGadget<F> {
     same_context: SameContextGadget<F>,
+    fuel: WasmFuelGadget<F>,
     operand: Cell<F>,
     result: Cell<F>,
     is_ctz: Cell<F>,
@@ -117,19 +118,22 @@ impl<F: Field> ExecutionGadget<F> for WasmUnaryGadget<F> {
             ],
         );
 
+        let opcode = cb.query_cell();
+        let fuel = WasmFuelGadget::construct(cb, opcode.expr());
+
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(2.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(0.expr()),
-            gas_left: Delta(-OpcodeId::I32Ctz.constant_gas_cost().expr()),
+            gas_left: Delta(-fuel.expr()),
             ..Default::default()
         };
 
-        let opcode = cb.query_cell();
         let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
 
         Self {
             same_context,
+            fuel,
             operand,
             result,
             is_ctz,
@@ -151,6 +155,8 @@ impl<F: Field> ExecutionGadget<F> for WasmUnaryGadget<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
+        self.fuel
+            .assign(region, offset, block, step.opcode.unwrap())?;
 
         let opcode = step.opcode.unwrap();
 
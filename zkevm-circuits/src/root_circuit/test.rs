@@ -2,6 +2,7 @@
 pub use super::*;
 use crate::super_circuit::{test::block_1tx, SuperCircuit};
 use bus_mapping::circuit_input_builder::CircuitsParams;
+use eth_types::evm_types::WasmFuelSchedule;
 use halo2_proofs::{
     circuit::Value,
     dev::MockProver,
@@ -32,6 +33,7 @@ fn test_root_circuit() {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
         };
         let (k, circuit, instance, _) =
             SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, TEST_MOCK_RANDOMNESS>::build(
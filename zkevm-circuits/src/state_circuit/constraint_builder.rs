@@ -111,6 +111,15 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(RwTableTag::Global), |cb| {
             cb.build_memory_constraints(q)
         });
+        self.condition(q.tag_matches(RwTableTag::Local), |cb| {
+            cb.build_local_constraints(q)
+        });
+        self.condition(q.tag_matches(RwTableTag::DataDrop), |cb| {
+            cb.build_data_drop_constraints(q)
+        });
+        self.condition(q.tag_matches(RwTableTag::ControlFrame), |cb| {
+            cb.build_control_frame_constraints(q)
+        });
         self.condition(q.tag_matches(RwTableTag::AccountStorage), |cb| {
             cb.build_account_storage_constraints(q)
         });
@@ -290,6 +299,79 @@ impl<F: Field> ConstraintBuilder<F> {
         );
     }
 
+    fn build_local_constraints(&mut self, q: &Queries<F>) {
+        // Unused keys are 0
+        self.require_zero("field_tag is 0 for Local", q.field_tag());
+        self.require_zero("storage_key is 0 for Local", q.rw_table.storage_key.clone());
+        for limb in &q.address.limbs[2..] {
+            self.require_zero("local index fits into 2 limbs", limb.clone());
+        }
+        // The id is unique to a function frame, so a declared local which is
+        // read before any write still holds the zero it starts with.
+        self.require_zero("initial Local value is 0", q.initial_value());
+        self.require_equal(
+            "state_root is unchanged for Local",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+        self.require_equal(
+            "value_prev column equals initial_value for Local",
+            q.value_prev_column(),
+            q.initial_value(),
+        );
+    }
+
+    fn build_data_drop_constraints(&mut self, q: &Queries<F>) {
+        // Unused keys are 0
+        self.require_zero("field_tag is 0 for DataDrop", q.field_tag());
+        self.require_zero(
+            "storage_key is 0 for DataDrop",
+            q.rw_table.storage_key.clone(),
+        );
+        for limb in &q.address.limbs[2..] {
+            self.require_zero("data index fits into 2 limbs", limb.clone());
+        }
+        // A segment of the module instance of the call is not dropped until
+        // data.drop writes its flag.
+        self.require_zero("initial DataDrop value is 0", q.initial_value());
+        self.require_boolean("DataDrop value is boolean", q.value());
+        self.require_equal(
+            "state_root is unchanged for DataDrop",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+        self.require_equal(
+            "value_prev column equals initial_value for DataDrop",
+            q.value_prev_column(),
+            q.initial_value(),
+        );
+    }
+
+    fn build_control_frame_constraints(&mut self, q: &Queries<F>) {
+        // Unused keys are 0
+        self.require_zero("field_tag is 0 for ControlFrame", q.field_tag());
+        self.require_zero(
+            "storage_key is 0 for ControlFrame",
+            q.rw_table.storage_key.clone(),
+        );
+        for limb in &q.address.limbs[2..] {
+            self.require_zero("opener pc fits into 2 limbs", limb.clone());
+        }
+        // The id is unique to a function frame and the opener writes the frame
+        // before the instructions inside it run.
+        self.require_zero("initial ControlFrame value is 0", q.initial_value());
+        self.require_equal(
+            "state_root is unchanged for ControlFrame",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+        self.require_equal(
+            "value_prev column equals initial_value for ControlFrame",
+            q.value_prev_column(),
+            q.initial_value(),
+        );
+    }
+
     fn build_account_storage_constraints(&mut self, q: &Queries<F>) {
         // TODO: cold VS warm
         // ref. spec 4.0. Unused keys are 0
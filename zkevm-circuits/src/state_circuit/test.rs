@@ -783,6 +783,28 @@ fn stack_read_before_write() {
     assert_error_matches(verify(rows), "first access to new stack address is a write");
 }
 
+#[test]
+fn local_read_before_write_is_zero() {
+    let rows = vec![
+        Rw::Local {
+            rw_counter: 9,
+            is_write: false,
+            frame_id: 3,
+            local_index: 2,
+            value: U64::zero(),
+        },
+        Rw::Local {
+            rw_counter: 10,
+            is_write: false,
+            frame_id: 4,
+            local_index: 2,
+            value: U64::from(10),
+        },
+    ];
+
+    assert_error_matches(verify(rows), "first access reads don't change value");
+}
+
 #[test]
 fn invalid_stack_address() {
     let rows = vec![Rw::Stack {
@@ -16,6 +16,7 @@
 //! - [ ] Keccak Circuit
 //! - [ ] MPT Circuit
 //! - [x] PublicInputs Circuit
+//! - [x] Wasm Circuit
 //!
 //! And the following shared tables, with the circuits that use them:
 //!
@@ -37,6 +38,7 @@
 //!   - [x] Bytecode Circuit
 //!   - [x] EVM Circuit
 //!   - [x] Copy Circuit
+//!   - [x] Wasm Circuit
 //! - [ ] Block Table
 //!   - [ ] EVM Circuit
 //!   - [x] PublicInputs Circuit
@@ -68,6 +70,10 @@ use crate::{
     poseidon_circuit::{PoseidonCircuit, PoseidonCircuitConfig, PoseidonCircuitConfigArgs},
     tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
     util::{log2_ceil, SubCircuit, SubCircuitConfig},
+    wasm_circuit::{
+        bytecode::bytecode_table::WasmBytecodeTable,
+        circuit::{WasmCircuit, WasmCircuitConfig, WasmCircuitConfigArgs},
+    },
     witness::{block_convert, Block},
 };
 
@@ -83,7 +89,7 @@ use crate::{
     state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
     table::{
         BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable, PoseidonTable,
-        RlpFsmRlpTable as RlpTable, RwTable, TxTable,
+        RlpFsmRlpTable as RlpTable, RwTable, TxTable, WasmFunctionTable,
     },
 };
 
@@ -127,6 +133,7 @@ pub struct SuperCircuitConfig<F: Field> {
     pi_circuit: PiCircuitConfig<F>,
     exp_circuit: ExpCircuitConfig<F>,
     rlp_circuit: RlpCircuitConfig<F>,
+    wasm_circuit: WasmCircuitConfig<F>,
     /// Mpt Circuit
     #[cfg(feature = "zktrie")]
     mpt_circuit: MptCircuitConfig,
@@ -273,6 +280,18 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         );
         log_circuit_info(meta, "copy circuit");
 
+        let wasm_function_table = WasmFunctionTable::construct(meta);
+        let wasm_bytecode_table = WasmBytecodeTable::construct(meta, true);
+        let wasm_circuit = WasmCircuitConfig::new(
+            meta,
+            WasmCircuitConfigArgs {
+                bytecode_table: bytecode_table.clone(),
+                wasm_function_table: wasm_function_table.clone(),
+                wasm_bytecode_table: wasm_bytecode_table.clone(),
+            },
+        );
+        log_circuit_info(meta, "wasm circuit");
+
         #[cfg(feature = "zktrie")]
         let mpt_circuit = MptCircuitConfig::new(
             meta,
@@ -309,6 +328,8 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 copy_table,
                 keccak_table,
                 exp_table,
+                wasm_function_table,
+                wasm_bytecode_table,
             },
         );
         log_circuit_info(meta, "evm circuit");
@@ -334,6 +355,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             rlp_circuit,
             tx_circuit,
             exp_circuit,
+            wasm_circuit,
             #[cfg(feature = "zktrie")]
             mpt_circuit,
         }
@@ -369,6 +391,8 @@ pub struct SuperCircuit<
     pub poseidon_circuit: PoseidonCircuit<F>,
     /// Rlp Circuit
     pub rlp_circuit: RlpCircuit<F, Transaction>,
+    /// Wasm Circuit
+    pub wasm_circuit: WasmCircuit<F>,
     /// Mpt Circuit
     #[cfg(feature = "zktrie")]
     pub mpt_circuit: MptCircuit<F>,
@@ -401,6 +425,7 @@ impl<
         let rlp = RlpCircuit::min_num_rows_block(block);
         let exp = ExpCircuit::min_num_rows_block(block);
         let pi = PiCircuit::min_num_rows_block(block);
+        let wasm = WasmCircuit::min_num_rows_block(block);
         let poseidon = (0, 0); //PoseidonCircuit::min_num_rows_block(block);
         #[cfg(feature = "zktrie")]
         let mpt = (0, 0); //MptCircuit::min_num_rows_block(block);
@@ -416,6 +441,7 @@ impl<
             exp,
             pi,
             poseidon,
+            wasm,
             #[cfg(feature = "zktrie")]
             mpt,
         ];
@@ -453,6 +479,7 @@ impl<
             CopyCircuit::<F>::unusable_rows(),
             ExpCircuit::<F>::unusable_rows(),
             KeccakCircuit::<F>::unusable_rows(),
+            WasmCircuit::<F>::unusable_rows(),
         ])
         .unwrap()
     }
@@ -468,6 +495,7 @@ impl<
         let keccak_circuit = KeccakCircuit::new_from_block(block);
         let poseidon_circuit = PoseidonCircuit::new_from_block(block);
         let rlp_circuit = RlpCircuit::new_from_block(block);
+        let wasm_circuit = WasmCircuit::new_from_block(block);
         #[cfg(feature = "zktrie")]
         let mpt_circuit = MptCircuit::new_from_block(block);
         SuperCircuit::<_, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS> {
@@ -481,6 +509,7 @@ impl<
             keccak_circuit,
             poseidon_circuit,
             rlp_circuit,
+            wasm_circuit,
             #[cfg(feature = "zktrie")]
             mpt_circuit,
         }
@@ -523,6 +552,8 @@ impl<
             .synthesize_sub(&config.poseidon_circuit, challenges, layouter)?;
         self.bytecode_circuit
             .synthesize_sub(&config.bytecode_circuit, challenges, layouter)?;
+        self.wasm_circuit
+            .synthesize_sub(&config.wasm_circuit, challenges, layouter)?;
         self.tx_circuit
             .synthesize_sub(&config.tx_circuit, challenges, layouter)?;
         self.state_circuit
@@ -8,7 +8,9 @@ use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use std::{collections::HashMap, env::set_var};
 
-use eth_types::{address, bytecode, geth_types::GethData, Bytecode, Word};
+use eth_types::{
+    address, bytecode, evm_types::WasmFuelSchedule, geth_types::GethData, Bytecode, Word,
+};
 
 #[test]
 fn super_circuit_degree() {
@@ -183,6 +185,53 @@ fn block_2tx() -> GethData {
     block
 }
 
+/// A call into a module that stores to and loads from its memory, so every byte of the callee
+/// has to be found in the wasm circuit for the bytecode table lookup to hold
+fn block_1tx_memory() -> GethData {
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+
+    let chain_id = (*MOCK_CHAIN_ID).as_u64();
+
+    let bytecode = bytecode! {
+        I32Const[0]
+        I32Const[7]
+        .write_memarg(OpcodeId::I32Store, 4, 0, 0)
+        I32Const[0]
+        .write_memarg(OpcodeId::I32Load, 4, 0, 0)
+        Drop
+    };
+
+    let wallet_a = LocalWallet::new(&mut rng).with_chain_id(chain_id);
+
+    let addr_a = wallet_a.address();
+    let addr_b = address!("0x000000000000000000000000000000000000BBBB");
+
+    let mut wallets = HashMap::new();
+    wallets.insert(wallet_a.address(), wallet_a);
+
+    let mut block: GethData = TestContext::<2, 1>::new(
+        Some(vec![Word::zero()]),
+        |accs| {
+            accs[0]
+                .address(addr_b)
+                .balance(Word::from(1u64 << 20))
+                .code(bytecode);
+            accs[1].address(addr_a).balance(Word::from(1u64 << 20));
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .gas(Word::from(1_000_000u64));
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+    block.sign(&wallets);
+    block
+}
+
 const TEST_MOCK_RANDOMNESS: u64 = 0x100;
 
 // High memory usage test.  Run in serial with:
@@ -207,6 +256,7 @@ fn serial_test_super_circuit_1tx_1max_tx() {
         max_keccak_rows: 0,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_rlp_rows: 500,
+        wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
     };
     test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
         block,
@@ -236,6 +286,7 @@ fn serial_test_super_circuit_1tx_deploy_2max_tx() {
         max_exp_steps: 256,
         max_evm_rows: 0,
         max_rlp_rows: 500,
+        wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
     };
     test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
         block,
@@ -263,6 +314,7 @@ fn serial_test_super_circuit_1tx_2max_tx() {
         max_keccak_rows: 0,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_rlp_rows: 500,
+        wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
     };
     test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
         block,
@@ -292,6 +344,7 @@ fn serial_test_super_circuit_2tx_4max_tx() {
         max_exp_steps: 256,
         max_evm_rows: 0,
         max_rlp_rows: 800,
+        wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
     };
     test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
         block,
@@ -319,9 +372,78 @@ fn serial_test_super_circuit_2tx_2max_tx() {
         max_keccak_rows: 0,
         max_inner_blocks: MAX_INNER_BLOCKS,
         max_rlp_rows: 500,
+        wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
     };
     test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
         block,
         circuits_params,
     );
 }
+
+#[ignore]
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_super_circuit_1tx_memory_1max_tx() {
+    let block = block_1tx_memory();
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 256;
+    const MAX_INNER_BLOCKS: usize = 1;
+    let circuits_params = CircuitsParams {
+        max_txs: MAX_TXS,
+        max_calldata: MAX_CALLDATA,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_mpt_rows: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        max_inner_blocks: MAX_INNER_BLOCKS,
+        max_rlp_rows: 500,
+        wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
+    };
+    test_super_circuit::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
+        block,
+        circuits_params,
+    );
+}
+
+#[ignore]
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_super_circuit_without_wasm_bytecodes_must_fail() {
+    let mut difficulty_be_bytes = [0u8; 32];
+    let mut chain_id_be_bytes = [0u8; 32];
+    MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+    MOCK_CHAIN_ID.to_big_endian(&mut chain_id_be_bytes);
+    set_var("CHAIN_ID", hex::encode(chain_id_be_bytes));
+    set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 256;
+    const MAX_INNER_BLOCKS: usize = 1;
+    let circuits_params = CircuitsParams {
+        max_txs: MAX_TXS,
+        max_calldata: MAX_CALLDATA,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_mpt_rows: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        max_inner_blocks: MAX_INNER_BLOCKS,
+        max_rlp_rows: 500,
+        wasm_fuel_schedule: WasmFuelSchedule::DEFAULT,
+    };
+    let (k, mut circuit, instance, _) =
+        SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>::build(
+            block_1tx_memory(),
+            circuits_params,
+        )
+        .unwrap();
+    // the bytecode table still holds the callee, which the wasm circuit no longer parses
+    circuit.wasm_circuit.wbs.clear();
+    let prover = MockProver::run(k, &circuit, instance).unwrap();
+    assert!(prover.verify_par().is_err());
+}
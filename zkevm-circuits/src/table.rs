@@ -370,6 +370,12 @@ pub enum RwTableTag {
     Stack,
     /// Global operation
     Global,
+    /// Local operation
+    Local,
+    /// Data segment dropped flag operation
+    DataDrop,
+    /// WASM control frame operation
+    ControlFrame,
     /// Memory operation
     Memory,
     /// Account Storage operation
@@ -512,6 +518,14 @@ pub enum CallContextFieldTag {
     MemorySize,
     /// ReversibleWriteCounter
     ReversibleWriteCounter,
+    /// GrownMemoryPages
+    GrownMemoryPages,
+    /// LocalsBase
+    LocalsBase,
+    /// LocalsFrame
+    LocalsFrame,
+    /// FunctionDepth
+    FunctionDepth,
 }
 impl_expr!(CallContextFieldTag);
 
@@ -1290,6 +1304,206 @@ impl<F: Field> LookupTable<F> for BytecodeTable {
     }
 }
 
+/// Tag to identify the field in a Wasm Function Table row
+#[derive(Clone, Copy, Debug)]
+pub enum WasmFunctionTableTag {
+    /// Number of slots in the table
+    TableSize = 1,
+    /// Function referenced by a table slot, stored as the function index plus
+    /// one so that zero stands for a null reference
+    Element,
+    /// Type index of a function
+    FunctionType,
+    /// Number of params of a function, indexed by the function index plus one
+    /// and by zero for the entry function
+    ParamCount,
+    /// Number of locals of a function, params included, indexed like
+    /// `ParamCount`
+    LocalCount,
+    /// Number of results of a function, indexed like `ParamCount`
+    ResultCount,
+    /// Program counter of the first instruction of a function body, indexed by
+    /// the function index plus one
+    FunctionBody,
+}
+impl_expr!(WasmFunctionTableTag);
+
+/// How the wasm circuit binds a row of the Wasm Function Table to its module
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasmFunctionTableBinding {
+    /// Not decoded from any section
+    Unbound,
+    /// Looked up by tag, index and value into the module view
+    Module,
+    /// Entry function row, looked up like a `Module` row of the function it aliases
+    Entry,
+    /// Function body row, looked up by index and value into the code section
+    Body,
+}
+
+/// Table with the function table, the function signatures and the local
+/// declarations of every wasm module indexed by its Code Hash, used to resolve
+/// `call_indirect` and to bound local indices
+#[derive(Clone, Debug)]
+pub struct WasmFunctionTable {
+    /// Is Enabled
+    pub q_enable: Column<Fixed>,
+    /// Whether the row is bound by the wasm circuit to the value decoded from the module
+    pub q_bound: Column<Fixed>,
+    /// Whether the row is an entry function row, bound through the function it aliases
+    pub q_entry: Column<Fixed>,
+    /// Whether the row is a function body row, bound to the code section
+    pub q_body: Column<Fixed>,
+    /// Code Hash
+    pub code_hash: Column<Advice>,
+    /// Tag
+    pub tag: Column<Advice>,
+    /// Table slot or function index, zero for the table size
+    pub index: Column<Advice>,
+    /// Value
+    pub value: Column<Advice>,
+}
+
+impl WasmFunctionTable {
+    /// Construct a new WasmFunctionTable
+    pub fn construct<F: Field>(meta: &mut ConstraintSystem<F>) -> Self {
+        let [tag, index, value] = array::from_fn(|_| meta.advice_column());
+        let code_hash = meta.advice_column_in(SecondPhase);
+        Self {
+            q_enable: meta.fixed_column(),
+            q_bound: meta.fixed_column(),
+            q_entry: meta.fixed_column(),
+            q_body: meta.fixed_column(),
+            code_hash,
+            tag,
+            index,
+            value,
+        }
+    }
+
+    /// Assign the `WasmFunctionTable` from a list of bytecodes.
+    pub fn load<'a, F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        bytecodes: impl IntoIterator<Item = &'a Bytecode> + Clone,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "wasm function table",
+            |mut region| {
+                region.assign_fixed(
+                    || "wasm function table all-zero row",
+                    self.q_enable,
+                    0,
+                    || Value::known(F::one()),
+                )?;
+                let table_columns = <WasmFunctionTable as LookupTable<F>>::advice_columns(self);
+                for &column in table_columns.iter() {
+                    region.assign_advice(
+                        || "wasm function table all-zero row",
+                        column,
+                        0,
+                        || Value::known(F::zero()),
+                    )?;
+                }
+
+                for (offset, (binding, row)) in bytecodes
+                    .clone()
+                    .into_iter()
+                    .flat_map(|bytecode| bytecode.function_table_assignments(challenges))
+                    .enumerate()
+                    .map(|(idx, row)| (idx + 1, row))
+                {
+                    region.assign_fixed(
+                        || format!("wasm function table row {}", offset),
+                        self.q_enable,
+                        offset,
+                        || Value::known(F::one()),
+                    )?;
+                    region.assign_fixed(
+                        || format!("wasm function table row {}", offset),
+                        self.q_bound,
+                        offset,
+                        || {
+                            Value::known(F::from(
+                                (binding == WasmFunctionTableBinding::Module) as u64,
+                            ))
+                        },
+                    )?;
+                    region.assign_fixed(
+                        || format!("wasm function table row {}", offset),
+                        self.q_entry,
+                        offset,
+                        || {
+                            Value::known(F::from(
+                                (binding == WasmFunctionTableBinding::Entry) as u64,
+                            ))
+                        },
+                    )?;
+                    region.assign_fixed(
+                        || format!("wasm function table row {}", offset),
+                        self.q_body,
+                        offset,
+                        || {
+                            Value::known(F::from(
+                                (binding == WasmFunctionTableBinding::Body) as u64,
+                            ))
+                        },
+                    )?;
+                    for (&column, value) in table_columns.iter().zip_eq(row) {
+                        region.assign_advice(
+                            || format!("wasm function table row {}", offset),
+                            column,
+                            offset,
+                            || value,
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: Field> LookupTable<F> for WasmFunctionTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.q_enable.into(),
+            self.q_bound.into(),
+            self.q_entry.into(),
+            self.q_body.into(),
+            self.code_hash.into(),
+            self.tag.into(),
+            self.index.into(),
+            self.value.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("q_enable"),
+            String::from("q_bound"),
+            String::from("q_entry"),
+            String::from("q_body"),
+            String::from("code_hash"),
+            String::from("tag"),
+            String::from("index"),
+            String::from("value"),
+        ]
+    }
+
+    /// The rows are looked up whether the wasm circuit binds them or not
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        vec![
+            meta.query_fixed(self.q_enable, Rotation::cur()),
+            meta.query_advice(self.code_hash, Rotation::cur()),
+            meta.query_advice(self.tag, Rotation::cur()),
+            meta.query_advice(self.index, Rotation::cur()),
+            meta.query_advice(self.value, Rotation::cur()),
+        ]
+    }
+}
+
 /// Tag to identify the field in a Block Table row
 // Keep the sequence consistent with OpcodeId for scalar
 #[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
@@ -1601,6 +1815,7 @@ pub struct CopyTable {
     /// 1. Call ID/Caller ID for CopyDataType::Memory
     /// 2. RLC encoding of bytecode hash for CopyDataType::Bytecode
     /// 3. Transaction ID for CopyDataType::TxCalldata, CopyDataType::TxLog
+    /// 4. The repeated byte for CopyDataType::Constant
     pub id: Column<Advice>,
     /// The source/destination address for this copy step.  Can be memory
     /// address, byte index in the bytecode, tx call data, and tx log data.
@@ -1619,13 +1834,15 @@ pub struct CopyTable {
     pub rw_counter: Column<Advice>,
     /// Decrementing counter denoting reverse read-write counter.
     pub rwc_inc_left: Column<Advice>,
+    /// Whether the copy event steps from the highest address down.
+    pub is_reverse: Column<Advice>,
     /// Binary chip to constrain the copy table conditionally depending on the
     /// current row's tag, whether it is Bytecode, Memory, TxCalldata or
     /// TxLog. This also now includes various precompile calls, hence will take up more cells.
     pub tag: BinaryNumberConfig<CopyDataType, 4>,
 }
 
-type CopyTableRow<F> = [(Value<F>, &'static str); 8];
+type CopyTableRow<F> = [(Value<F>, &'static str); 9];
 type CopyCircuitRow<F> = [(Value<F>, &'static str); 5];
 
 impl CopyTable {
@@ -1642,6 +1859,7 @@ impl CopyTable {
             rlc_acc: meta.advice_column_in(SecondPhase),
             rw_counter: meta.advice_column(),
             rwc_inc_left: meta.advice_column(),
+            is_reverse: meta.advice_column(),
         }
     }
 
@@ -1663,9 +1881,12 @@ impl CopyTable {
                 .map(|keccak_input| rlc::value(values.iter().rev(), keccak_input))
         };
         let mut value_acc = Value::known(F::zero());
-        for (step_idx, (is_read_step, copy_step)) in copy_event
-            .bytes
-            .iter()
+        let bytes: Box<dyn Iterator<Item = &(u8, bool)>> = if copy_event.is_reverse {
+            Box::new(copy_event.bytes.iter().rev())
+        } else {
+            Box::new(copy_event.bytes.iter())
+        };
+        for (step_idx, (is_read_step, copy_step)) in bytes
             .flat_map(|(value, is_code)| {
                 let read_step = CopyStep {
                     value: *value,
@@ -1711,12 +1932,11 @@ impl CopyTable {
             };
 
             // addr
-            let copy_step_addr: u64 =
-                if is_read_step {
-                    copy_event.src_addr
-                } else {
-                    copy_event.dst_addr
-                } + (u64::try_from(step_idx).unwrap() - if is_read_step { 0 } else { 1 }) / 2u64;
+            let copy_step_addr: u64 = if is_read_step {
+                copy_event.src_addr
+            } else {
+                copy_event.dst_addr
+            } + copy_event.addr_offset(step_idx);
 
             let addr = if tag == CopyDataType::TxLog {
                 Value::known(
@@ -1777,6 +1997,7 @@ impl CopyTable {
                         Value::known(F::from(copy_event.rw_counter_increase_left(step_idx))),
                         "rwc_inc_left",
                     ),
+                    (Value::known(F::from(copy_event.is_reverse)), "is_reverse"),
                 ],
                 [
                     (is_last, "is_last"),
@@ -1858,6 +2079,7 @@ impl<F: Field> LookupTable<F> for CopyTable {
             self.rlc_acc.into(),
             self.rw_counter.into(),
             self.rwc_inc_left.into(),
+            self.is_reverse.into(),
         ]
     }
 
@@ -1872,6 +2094,7 @@ impl<F: Field> LookupTable<F> for CopyTable {
             String::from("rlc_acc"),
             String::from("rw_counter"),
             String::from("rwc_inc_left"),
+            String::from("is_reverse"),
         ]
     }
 
@@ -1890,6 +2113,7 @@ impl<F: Field> LookupTable<F> for CopyTable {
             meta.query_advice(self.rlc_acc, Rotation::cur()), // rlc_acc
             meta.query_advice(self.rw_counter, Rotation::cur()), // rw_counter
             meta.query_advice(self.rwc_inc_left, Rotation::cur()), // rwc_inc_left
+            meta.query_advice(self.is_reverse, Rotation::cur()), // is_reverse
         ]
     }
 }
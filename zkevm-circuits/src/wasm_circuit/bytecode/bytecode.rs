@@ -43,3 +43,12 @@ impl From<&eth_types::bytecode::Bytecode> for WasmBytecode {
         WasmBytecode::new(b.to_vec())
     }
 }
+
+impl From<&crate::witness::Bytecode> for WasmBytecode {
+    fn from(b: &crate::witness::Bytecode) -> Self {
+        Self {
+            bytes: b.bytes.clone(),
+            code_hash: b.hash,
+        }
+    }
+}
@@ -1,17 +1,28 @@
 use std::array;
 
 use halo2_proofs::{
-    circuit::{Region, Value},
+    circuit::{Layouter, Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, *},
+    poly::Rotation,
 };
 use itertools::Itertools;
 use log::debug;
 
-use eth_types::Field;
+use eth_types::{
+    bytecode::{
+        wasm_blocks, wasm_branches, wasm_data_segment, wasm_initial_memory_pages,
+        wasm_instructions,
+    },
+    Field,
+};
 
 use crate::{
     table::LookupTable,
-    wasm_circuit::{bytecode::bytecode::WasmBytecode, types::AssignDeltaType},
+    wasm_circuit::{
+        bytecode::bytecode::WasmBytecode,
+        consts::WASM_MAGIC_PREFIX,
+        types::{AssignDeltaType, ErrorCode, WasmModuleTag},
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -19,21 +30,98 @@ pub struct WasmBytecodeTable {
     pub index: Column<Advice>,
     pub value: Column<Advice>,
     pub code_hash: Column<Advice>,
+    /// Last byte of a bytecode, assigned by the wasm circuit
+    pub q_last: Column<Fixed>,
+    /// Error code the wasm circuit assigned to the bytecode, 0 at the last byte means the
+    /// bytecode is a valid module
+    pub error_code: Column<Advice>,
+    /// Module view: what the row decodes, 0 if it is not looked up
+    pub module_tag: Column<Fixed>,
+    /// Module view: index of the decoded value, the rows between two tagged rows carry the
+    /// counter the next index is derived from
+    pub module_index: Column<Advice>,
+    /// Module view: decoded value
+    pub module_value: Column<Advice>,
+    /// Module view: second decoded value of the tags which have one
+    pub module_arg: Column<Advice>,
 
     pub zero_row_enabled: bool,
 }
 
+/// Module view of the wasm bytecode table, the facts the wasm circuit decoded from the modules
+#[derive(Clone, Debug)]
+pub struct WasmModuleTable {
+    pub code_hash: Column<Advice>,
+    pub module_tag: Column<Fixed>,
+    pub module_index: Column<Advice>,
+    pub module_value: Column<Advice>,
+    pub module_arg: Column<Advice>,
+}
+
 impl WasmBytecodeTable {
     pub fn construct<F: Field>(cs: &mut ConstraintSystem<F>, zero_row_enabled: bool) -> Self {
-        let [index, value, code_hash] = array::from_fn(|_| cs.advice_column());
+        let [index, value, code_hash, error_code, module_index, module_value, module_arg] =
+            array::from_fn(|_| cs.advice_column());
         Self {
             index,
             value,
             code_hash,
+            q_last: cs.fixed_column(),
+            error_code,
+            module_tag: cs.fixed_column(),
+            module_index,
+            module_value,
+            module_arg,
             zero_row_enabled,
         }
     }
 
+    pub fn module_table(&self) -> WasmModuleTable {
+        WasmModuleTable {
+            code_hash: self.code_hash,
+            module_tag: self.module_tag,
+            module_index: self.module_index,
+            module_value: self.module_value,
+            module_arg: self.module_arg,
+        }
+    }
+
+    /// Assigns the module view of a row. Rows without a tag only carry `index` to the next
+    /// tagged row.
+    pub fn assign_module_view<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        tag: Option<WasmModuleTag>,
+        index: u64,
+        value: u64,
+        arg: u64,
+    ) -> Result<(), Error> {
+        debug!(
+            "assign at {} module view tag {:?} index {} value {} arg {}",
+            assign_offset, tag, index, value, arg
+        );
+        region.assign_fixed(
+            || format!("assign 'module_tag' val {:?} at {}", tag, assign_offset),
+            self.module_tag,
+            assign_offset,
+            || Value::known(F::from(tag.map_or(0, |tag| tag as u64))),
+        )?;
+        for (column, value) in [
+            (self.module_index, index),
+            (self.module_value, value),
+            (self.module_arg, arg),
+        ] {
+            region.assign_advice(
+                || format!("assign module view val {} at {}", value, assign_offset),
+                column,
+                assign_offset,
+                || Value::known(F::from(value)),
+            )?;
+        }
+        Ok(())
+    }
+
     pub fn load<'a, F: Field>(
         &self,
         region: &mut Region<F>,
@@ -43,11 +131,13 @@ impl WasmBytecodeTable {
         let mut assign_offset = 0;
         assign_offset = assign_delta;
         debug!("wasm bytecode table start assign at {}", assign_offset);
-        let bytecode_table_columns = <WasmBytecodeTable as LookupTable<F>>::advice_columns(self);
+        let bytecode_table_columns = [self.index, self.value, self.code_hash];
+        // the module view is overwritten by the section chips at the rows they decode
+        let module_view_columns = [self.module_index, self.module_value, self.module_arg];
 
         if self.zero_row_enabled {
             let value = 0;
-            for &column in bytecode_table_columns.iter() {
+            for &column in bytecode_table_columns.iter().chain(module_view_columns.iter()) {
                 debug!(
                     "assign at {} column.index {} wasm_bytecode_table val {:?}",
                     assign_offset, column.index, value
@@ -85,15 +175,143 @@ impl WasmBytecodeTable {
                     || value,
                 )?;
             }
+            for &column in module_view_columns.iter() {
+                region.assign_advice(
+                    || format!("assign at {} module view val 0", assign_offset),
+                    column,
+                    assign_offset,
+                    || Value::known(F::zero()),
+                )?;
+            }
             assign_offset += 1;
         }
         Ok(assign_offset)
     }
+
+    /// Assign the wasm bytecodes into their own region, used by circuits that
+    /// only look the table up. Without the wasm circuit to parse them, only the bytecodes
+    /// lacking the wasm magic prefix are marked as invalid.
+    pub fn dev_load<F: Field>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        wbs: &[WasmBytecode],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "wasm bytecode table",
+            |mut region| {
+                let mut assign_delta = 0;
+                for wb in wbs {
+                    let start = assign_delta + if self.zero_row_enabled { 1 } else { 0 };
+                    assign_delta = self.load(&mut region, wb, assign_delta)?;
+                    let error_code = if wb.bytes.starts_with(WASM_MAGIC_PREFIX.as_bytes()) {
+                        ErrorCode::Ok
+                    } else {
+                        ErrorCode::Error
+                    };
+                    for offset in start..assign_delta {
+                        region.assign_advice(
+                            || format!("assign 'error_code' val {:?} at {}", error_code, offset),
+                            self.error_code,
+                            offset,
+                            || Value::known(F::from(error_code as u64)),
+                        )?;
+                    }
+                    if assign_delta > start {
+                        region.assign_fixed(
+                            || format!("assign 'q_last' val 1 at {}", assign_delta - 1),
+                            self.q_last,
+                            assign_delta - 1,
+                            || Value::known(F::one()),
+                        )?;
+                    }
+                    if matches!(error_code, ErrorCode::Ok) {
+                        self.dev_load_module_view(&mut region, &wb.bytes, start)?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assigns the facts of the module view the evm circuit looks up, the section chips that
+    /// decode them do not run with `dev_load`
+    fn dev_load_module_view<F: Field>(
+        &self,
+        region: &mut Region<F>,
+        code: &[u8],
+        start: usize,
+    ) -> Result<(), Error> {
+        let instructions = wasm_instructions(code).unwrap_or_default();
+        for (pc, &(pos, opcode, arg)) in instructions.iter().enumerate() {
+            self.assign_module_view(
+                region,
+                start + pos,
+                Some(WasmModuleTag::Instruction),
+                pc as u64,
+                opcode,
+                arg,
+            )?;
+        }
+        for block in wasm_blocks(code).unwrap_or_default() {
+            self.assign_module_view(
+                region,
+                start + block.blocktype_pos,
+                Some(WasmModuleTag::Block),
+                block.pc,
+                block.end_pc,
+                block.else_pc,
+            )?;
+        }
+        for (pos, pc, target) in wasm_branches(code).unwrap_or_default() {
+            let (_, _, label) = instructions[pc as usize];
+            self.assign_module_view(
+                region,
+                start + pos,
+                Some(WasmModuleTag::Label),
+                pc,
+                target,
+                label,
+            )?;
+        }
+        if let Some((pos, pages)) = wasm_initial_memory_pages(code) {
+            self.assign_module_view(
+                region,
+                start + pos,
+                Some(WasmModuleTag::MemoryPages),
+                0,
+                pages,
+                0,
+            )?;
+        }
+        let mut data_index = 0;
+        while let Some((segment_start, segment_end)) = wasm_data_segment(code, data_index) {
+            self.assign_module_view(
+                region,
+                start + segment_start as usize - 1,
+                Some(WasmModuleTag::DataSegment),
+                data_index,
+                segment_start,
+                segment_end,
+            )?;
+            data_index += 1;
+        }
+        Ok(())
+    }
 }
 
 impl<F: Field> LookupTable<F> for WasmBytecodeTable {
     fn columns(&self) -> Vec<Column<Any>> {
-        vec![self.index.into(), self.value.into(), self.code_hash.into()]
+        vec![
+            self.index.into(),
+            self.value.into(),
+            self.code_hash.into(),
+            self.q_last.into(),
+            self.error_code.into(),
+            self.module_tag.into(),
+            self.module_index.into(),
+            self.module_value.into(),
+            self.module_arg.into(),
+        ]
     }
 
     fn annotations(&self) -> Vec<String> {
@@ -101,6 +319,44 @@ impl<F: Field> LookupTable<F> for WasmBytecodeTable {
             String::from("index"),
             String::from("value"),
             String::from("code_hash"),
+            String::from("q_last"),
+            String::from("error_code"),
+            String::from("module_tag"),
+            String::from("module_index"),
+            String::from("module_value"),
+            String::from("module_arg"),
+        ]
+    }
+
+    /// The evm circuit only asks whether the module behind a code hash was validated, which is
+    /// the error code at its last byte
+    fn table_exprs(&self, meta: &mut VirtualCells<F>) -> Vec<Expression<F>> {
+        vec![
+            meta.query_advice(self.code_hash, Rotation::cur()),
+            meta.query_fixed(self.q_last, Rotation::cur()),
+            meta.query_advice(self.error_code, Rotation::cur()),
+        ]
+    }
+}
+
+impl<F: Field> LookupTable<F> for WasmModuleTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.code_hash.into(),
+            self.module_tag.into(),
+            self.module_index.into(),
+            self.module_value.into(),
+            self.module_arg.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("code_hash"),
+            String::from("module_tag"),
+            String::from("module_index"),
+            String::from("module_value"),
+            String::from("module_arg"),
         ]
     }
 }
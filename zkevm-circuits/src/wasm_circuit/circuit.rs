@@ -2,7 +2,7 @@ use std::{cell::RefCell, marker::PhantomData, rc::Rc};
 
 use halo2_proofs::{
     circuit::{Chip, Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Fixed},
+    plonk::{Advice, Column, ConstraintSystem, Error as Halo2Error, Fixed},
     poly::Rotation,
 };
 use log::debug;
@@ -16,7 +16,8 @@ use gadgets::{
 
 use crate::{
     evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
-    table::PoseidonTable,
+    table::{BytecodeTable, PoseidonTable, WasmFunctionTable, WasmFunctionTableTag},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
     wasm_circuit::{
         bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
         common::{
@@ -36,11 +37,16 @@ use crate::{
             error_index_out_of_bounds, is_recoverable_error, remap_error_to_assign_at,
             remap_error_to_compute_value_at, remap_error_to_invalid_enum_value_at, Error,
         },
-        leb128::{circuit::LEB128Chip, helpers::leb128_compute_last_byte_offset},
+        leb128::{
+            circuit::LEB128Chip,
+            helpers::{leb128_compute_last_byte_offset, leb128_compute_sn},
+        },
         sections::{
             code::body::circuit::WasmCodeSectionBodyChip,
             consts::LebParams,
+            custom::body::circuit::WasmCustomSectionBodyChip,
             data::body::circuit::WasmDataSectionBodyChip,
+            datacount::body::circuit::WasmDataCountSectionBodyChip,
             element::body::circuit::WasmElementSectionBodyChip,
             export::body::circuit::WasmExportSectionBodyChip,
             function::body::circuit::WasmFunctionSectionBodyChip,
@@ -59,6 +65,10 @@ use crate::{
                 types::{LookupArgsParams, Tag},
             },
             fixed_range::config::RangeTableConfig,
+            host_functions::config::HostFunctionsTableConfig,
+            module_types::{circuit::ModuleTypesChip, helpers::module_types_parse},
+            section_rank::config::SectionRankTableConfig,
+            type_stack::config::TypeStackTableConfig,
         },
         types::{
             AssignDeltaType, AssignType, AssignValueType, ControlInstruction, ErrorCode,
@@ -67,6 +77,7 @@ use crate::{
         },
         utf8::circuit::UTF8Chip,
     },
+    witness,
 };
 
 pub struct WasmSectionConfig<F: Field> {
@@ -89,6 +100,9 @@ pub struct WasmConfig<F: Field> {
     is_section_body: Column<Fixed>,
 
     section_id: Column<Advice>,
+    /// Id of the last non custom section, the section order is checked on it
+    section_rank: Column<Advice>,
+    section_id_is_custom_chip: IsZeroChip<F>,
 
     leb128_chip: Rc<LEB128Chip<F>>,
     utf8_chip: Rc<UTF8Chip<F>>,
@@ -104,13 +118,19 @@ pub struct WasmConfig<F: Field> {
     wasm_start_section_body_chip: Rc<WasmStartSectionBodyChip<F>>,
     wasm_table_section_body_chip: Rc<WasmTableSectionBodyChip<F>>,
     wasm_element_section_body_chip: Rc<WasmElementSectionBodyChip<F>>,
+    wasm_custom_section_body_chip: Rc<WasmCustomSectionBodyChip<F>>,
+    wasm_datacount_section_body_chip: Rc<WasmDataCountSectionBodyChip<F>>,
     section_id_lt_chip: LtChip<F, 1>,
     dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
+    module_types_chip: Rc<ModuleTypesChip<F>>,
+    type_stack_table_config: TypeStackTableConfig<F>,
+    host_functions_table_config: HostFunctionsTableConfig<F>,
     magic_prefix_count: usize,
     index_at_magic_prefix: Vec<IsZeroChip<F>>,
     poseidon_table: PoseidonTable,
     range_table_config_0_256: RangeTableConfig<F, 0, 256>,
     section_id_range_table_config: RangeTableConfig<F, 0, { WASM_SECTION_ID_MAX + 1 }>,
+    section_rank_table_config: SectionRankTableConfig<F>,
     range_table_config_0_128: Rc<RangeTableConfig<F, 0, 128>>,
 
     func_count: Column<Advice>,
@@ -119,13 +139,133 @@ pub struct WasmConfig<F: Field> {
     body_byte_rev_index_l2: Column<Advice>,
     body_item_rev_count_l1: Column<Advice>,
     body_item_rev_count_l2: Column<Advice>,
+    /// Data segments declared by the data count section and not yet parsed
+    data_count: Column<Advice>,
+    data_count_declared: Column<Advice>,
 
     error_code: Column<Advice>,
 
     _marker: PhantomData<F>,
 }
 
-impl<F: Field> WasmConfig<F> {}
+impl<F: Field> WasmConfig<F> {
+    /// Binds every byte of the bytecode table the evm circuit reads code from to a row parsed
+    /// by the wasm circuit, so no bytecode reaches execution without passing through the parser.
+    /// Code hashes are kept as scalars, which is how the bytecode table keeps poseidon code hashes
+    pub fn configure_bytecode_table_lookup(
+        &self,
+        cs: &mut ConstraintSystem<F>,
+        bytecode_table: &BytecodeTable,
+    ) {
+        let q_enable = self.q_enable;
+        let q_first = self.q_first;
+        let wb_table = self.wb_table.clone();
+        cs.lookup_any("bytecode table byte exists in wasm bytecode table", |vc| {
+            let cond = vc.query_fixed(bytecode_table.q_enable, Rotation::cur())
+                * vc.query_advice(bytecode_table.tag, Rotation::cur());
+            let wb_cond = vc.query_fixed(q_enable, Rotation::cur())
+                * not::expr(vc.query_fixed(q_first, Rotation::cur()));
+            vec![
+                (
+                    cond.clone() * vc.query_advice(bytecode_table.code_hash, Rotation::cur()),
+                    wb_cond.clone() * vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    cond.clone() * vc.query_advice(bytecode_table.index, Rotation::cur()),
+                    wb_cond.clone() * vc.query_advice(wb_table.index, Rotation::cur()),
+                ),
+                (
+                    cond * vc.query_advice(bytecode_table.value, Rotation::cur()),
+                    wb_cond * vc.query_advice(wb_table.value, Rotation::cur()),
+                ),
+            ]
+        });
+    }
+
+    /// Binds the rows of the function table to the values the section chips decode into the
+    /// module view of the wasm bytecode table
+    pub fn configure_function_table_lookup(
+        &self,
+        cs: &mut ConstraintSystem<F>,
+        function_table: &WasmFunctionTable,
+    ) {
+        let q_enable = self.q_enable;
+        let wb_table = self.wb_table.clone();
+        cs.lookup_any("function table row is decoded from its module", |vc| {
+            let cond = vc.query_fixed(function_table.q_enable, Rotation::cur())
+                * vc.query_fixed(function_table.q_bound, Rotation::cur());
+            let wb_cond = vc.query_fixed(q_enable, Rotation::cur());
+            vec![
+                (
+                    cond.clone() * vc.query_advice(function_table.code_hash, Rotation::cur()),
+                    wb_cond.clone() * vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    cond.clone() * vc.query_advice(function_table.tag, Rotation::cur()),
+                    wb_cond.clone() * vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                ),
+                (
+                    cond.clone() * vc.query_advice(function_table.index, Rotation::cur()),
+                    wb_cond.clone() * vc.query_advice(wb_table.module_index, Rotation::cur()),
+                ),
+                (
+                    cond * vc.query_advice(function_table.value, Rotation::cur()),
+                    wb_cond * vc.query_advice(wb_table.module_value, Rotation::cur()),
+                ),
+            ]
+        });
+        // the entry function rows are indexed by zero, the alias the module view keeps in `arg`
+        cs.lookup_any("function table entry row is decoded from its module", |vc| {
+            let cond = vc.query_fixed(function_table.q_enable, Rotation::cur())
+                * vc.query_fixed(function_table.q_entry, Rotation::cur());
+            let wb_cond = vc.query_fixed(q_enable, Rotation::cur());
+            vec![
+                (
+                    cond.clone() * vc.query_advice(function_table.code_hash, Rotation::cur()),
+                    wb_cond.clone() * vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    cond.clone() * vc.query_advice(function_table.tag, Rotation::cur()),
+                    wb_cond.clone() * vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                ),
+                (
+                    cond.clone() * vc.query_advice(function_table.index, Rotation::cur()),
+                    wb_cond.clone() * vc.query_advice(wb_table.module_arg, Rotation::cur()),
+                ),
+                (
+                    cond * vc.query_advice(function_table.value, Rotation::cur()),
+                    wb_cond * vc.query_advice(wb_table.module_value, Rotation::cur()),
+                ),
+            ]
+        });
+        // the program counter a function body starts at is kept by the code section rows only
+        let code_body_config = self.wasm_code_section_body_chip.config.clone();
+        cs.lookup_any("function table body row is the start of a code section body", |vc| {
+            let cond = vc.query_fixed(function_table.q_enable, Rotation::cur())
+                * vc.query_fixed(function_table.q_body, Rotation::cur());
+            let body_cond = vc.query_fixed(code_body_config.q_enable, Rotation::cur())
+                * code_body_config.is_body_start_expr(vc);
+            vec![
+                (
+                    cond.clone() * vc.query_advice(function_table.code_hash, Rotation::cur()),
+                    body_cond.clone() * vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    cond.clone() * vc.query_advice(function_table.tag, Rotation::cur()),
+                    body_cond.clone() * WasmFunctionTableTag::FunctionBody.expr(),
+                ),
+                (
+                    cond.clone() * vc.query_advice(function_table.index, Rotation::cur()),
+                    body_cond.clone() * code_body_config.func_index_plus_one_expr(vc),
+                ),
+                (
+                    cond * vc.query_advice(function_table.value, Rotation::cur()),
+                    body_cond * code_body_config.pc_expr(vc),
+                ),
+            ]
+        });
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct WasmChip<F: Field> {
@@ -337,7 +477,13 @@ impl<F: Field> WasmChip<F> {
             .section_id_range_table_config
             .load(layouter)
             .unwrap();
+        self.config.section_rank_table_config.load(layouter).unwrap();
         self.config.range_table_config_0_128.load(layouter).unwrap();
+        self.config.type_stack_table_config.load(layouter).unwrap();
+        self.config
+            .host_functions_table_config
+            .load(layouter)
+            .unwrap();
 
         Ok(())
     }
@@ -353,23 +499,29 @@ impl<F: Field> WasmChip<F> {
 
         let q_enable = cs.fixed_column();
         let q_first = cs.fixed_column();
-        let q_last = cs.fixed_column();
+        // the last row and the error code are kept in the table so the evm circuit can ask
+        // whether a module was validated
+        let q_last = wb_table.q_last;
         let is_section_id = cs.fixed_column();
         let is_section_len = cs.fixed_column();
         let is_section_body = cs.fixed_column();
 
         let section_id = cs.advice_column();
+        let section_rank = cs.advice_column();
         let func_count = cs.advice_column();
         let block_depth_level = cs.advice_column();
         let body_byte_rev_index_l1 = cs.advice_column();
         let body_byte_rev_index_l2 = cs.advice_column();
         let body_item_rev_count_l1 = cs.advice_column();
         let body_item_rev_count_l2 = cs.advice_column();
+        let data_count = cs.advice_column();
+        let data_count_declared = cs.advice_column();
 
-        let error_code = cs.advice_column();
+        let error_code = wb_table.error_code;
 
         let range_table_config_0_256 = RangeTableConfig::configure(cs);
         let section_id_range_table_config = RangeTableConfig::configure(cs);
+        let section_rank_table_config = SectionRankTableConfig::configure(cs);
         let range_table_config_0_128 = Rc::new(RangeTableConfig::configure(cs));
         let poseidon_table = PoseidonTable::dev_construct(cs);
 
@@ -383,6 +535,11 @@ impl<F: Field> WasmChip<F> {
         let config = DynamicIndexesChip::configure(cs, shared_state.clone());
         let dynamic_indexes_chip = Rc::new(DynamicIndexesChip::construct(config));
 
+        let config = ModuleTypesChip::configure(cs, shared_state.clone());
+        let module_types_chip = Rc::new(ModuleTypesChip::construct(config));
+        let type_stack_table_config = TypeStackTableConfig::configure(cs);
+        let host_functions_table_config = HostFunctionsTableConfig::configure(cs);
+
         let config = WasmTypeSectionItemChip::configure(
             cs,
             wb_table.clone(),
@@ -417,6 +574,9 @@ impl<F: Field> WasmChip<F> {
             body_byte_rev_index_l2,
             body_item_rev_count_l1,
             error_code,
+            bytecode_number,
+            module_types_chip.clone(),
+            &host_functions_table_config,
         );
         let wasm_import_section_body_chip = Rc::new(WasmImportSectionBodyChip::construct(config));
 
@@ -495,6 +655,10 @@ impl<F: Field> WasmChip<F> {
             body_item_rev_count_l1,
             error_code,
             bytecode_number,
+            module_types_chip.clone(),
+            &type_stack_table_config,
+            range_table_config_0_128.as_ref(),
+            &range_table_config_0_256,
         );
         let wasm_code_section_body_chip = Rc::new(WasmCodeSectionBodyChip::construct(config));
 
@@ -530,6 +694,42 @@ impl<F: Field> WasmChip<F> {
         );
         let wasm_table_section_body_chip = Rc::new(WasmTableSectionBodyChip::construct(config));
 
+        let config = WasmCustomSectionBodyChip::configure(
+            cs,
+            wb_table.clone(),
+            leb128_chip.clone(),
+            utf8_chip.clone(),
+            func_count,
+            shared_state.clone(),
+            body_byte_rev_index_l2,
+            error_code,
+        );
+        let wasm_custom_section_body_chip = Rc::new(WasmCustomSectionBodyChip::construct(config));
+
+        let config = WasmDataCountSectionBodyChip::configure(
+            cs,
+            wb_table.clone(),
+            leb128_chip.clone(),
+            func_count,
+            shared_state.clone(),
+            error_code,
+        );
+        let wasm_datacount_section_body_chip =
+            Rc::new(WasmDataCountSectionBodyChip::construct(config));
+
+        let value_inv = cs.advice_column();
+        let section_id_is_custom_config = IsZeroChip::configure(
+            cs,
+            |vc| {
+                vc.query_fixed(is_section_id, Rotation::cur())
+                    + vc.query_fixed(is_section_len, Rotation::cur())
+                    + vc.query_fixed(is_section_body, Rotation::cur())
+            },
+            |vc| vc.query_advice(section_id, Rotation::cur()),
+            value_inv,
+        );
+        let section_id_is_custom_chip = IsZeroChip::construct(section_id_is_custom_config);
+
         let mut index_at_magic_prefix: Vec<IsZeroChip<F>> = Vec::new();
         for index in 0..magic_prefix_count {
             let value_inv = cs.advice_column();
@@ -667,8 +867,8 @@ impl<F: Field> WasmChip<F> {
 
                 and::expr([not_q_first_expr.clone(), q_enable_expr.clone()])
             },
-            |vc| vc.query_advice(section_id, Rotation::prev()),
-            |vc| vc.query_advice(section_id, Rotation::cur()),
+            |vc| vc.query_advice(section_rank, Rotation::prev()),
+            |vc| vc.query_advice(section_rank, Rotation::cur()),
         );
         let section_id_lt_chip = LtChip::construct(section_id_lt_chip_config);
 
@@ -702,6 +902,9 @@ impl<F: Field> WasmChip<F> {
 
             let section_id_expr = vc.query_advice(section_id, Rotation::cur());
             let section_id_prev_expr = vc.query_advice(section_id, Rotation::prev());
+            let section_rank_expr = vc.query_advice(section_rank, Rotation::cur());
+            let section_rank_prev_expr = vc.query_advice(section_rank, Rotation::prev());
+            let section_id_is_custom_expr = section_id_is_custom_chip.config().expr();
 
             let leb128_is_last_byte_expr =
                 vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
@@ -828,11 +1031,24 @@ impl<F: Field> WasmChip<F> {
                         + vc.query_fixed(
                         wasm_element_section_body_chip.config.q_enable,
                         Rotation::cur(),
+                    )
+                        + vc.query_fixed(
+                        wasm_custom_section_body_chip.config.q_enable,
+                        Rotation::cur(),
+                    )
+                        + vc.query_fixed(
+                        wasm_datacount_section_body_chip.config.q_enable,
+                        Rotation::cur(),
                     )
                         + is_section_id_expr.clone()
                         + is_section_len_expr.clone(),
                     1.expr(),
                 );
+                cb.require_equal(
+                    "is_section_body -> custom section chip is enabled for custom sections only",
+                    vc.query_fixed(wasm_custom_section_body_chip.config.q_enable, Rotation::cur()),
+                    section_id_is_custom_expr.clone(),
+                );
             });
             // func_count constraints
             cb.condition(q_first_expr.clone(), |cb| {
@@ -943,6 +1159,125 @@ impl<F: Field> WasmChip<F> {
                     byte_val_expr.clone(),
                 )
             });
+            cb.condition(
+                and::expr([
+                    not_q_first_expr.clone(),
+                    or::expr([is_section_len_expr.clone(), is_section_body_expr.clone()]),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_section_len || is_section_body -> section_id=prev.section_id",
+                        section_id_expr.clone(),
+                        section_id_prev_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "is_section_len || is_section_body -> section_rank=prev.section_rank",
+                        section_rank_expr.clone(),
+                        section_rank_prev_expr.clone(),
+                    );
+                },
+            );
+            // custom sections may appear anywhere, so they keep the rank of the section before them
+            cb.condition(
+                and::expr([is_section_id_expr.clone(), section_id_is_custom_expr.clone()]),
+                |cb| {
+                    cb.require_equal(
+                        "is_section_id && is_custom -> section_rank=prev.section_rank",
+                        section_rank_expr.clone(),
+                        section_rank_prev_expr.clone(),
+                    )
+                },
+            );
+
+            // data count constraints
+            let data_count_expr = vc.query_advice(data_count, Rotation::cur());
+            let data_count_prev_expr = vc.query_advice(data_count, Rotation::prev());
+            let data_count_declared_expr = vc.query_advice(data_count_declared, Rotation::cur());
+            let data_count_declared_prev_expr =
+                vc.query_advice(data_count_declared, Rotation::prev());
+            let wasm_datacount_section_q_first_expr = vc.query_fixed(
+                wasm_datacount_section_body_chip.config.q_first,
+                Rotation::cur(),
+            );
+            let wasm_data_section_q_first_expr =
+                vc.query_fixed(wasm_data_section_body_chip.config.q_first, Rotation::cur());
+            cb.condition(q_first_expr.clone(), |cb| {
+                cb.require_zero("q_first => data_count=0", data_count_expr.clone());
+                cb.require_zero(
+                    "q_first => data_count_declared=0",
+                    data_count_declared_expr.clone(),
+                );
+            });
+            cb.condition(
+                and::expr([
+                    not_q_first_expr.clone(),
+                    not::expr(wasm_datacount_section_q_first_expr.clone()),
+                    not::expr(wasm_data_section_q_first_expr.clone()),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "not_q_first && !wasm_datacount_section_q_first && !wasm_data_section_q_first => prev.data_count=data_count",
+                        data_count_prev_expr.clone(),
+                        data_count_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "not_q_first && !wasm_datacount_section_q_first && !wasm_data_section_q_first => prev.data_count_declared=data_count_declared",
+                        data_count_declared_prev_expr.clone(),
+                        data_count_declared_expr.clone(),
+                    );
+                },
+            );
+            cb.condition(wasm_datacount_section_q_first_expr.clone(), |cb| {
+                let wasm_datacount_section_leb128_sn_expr = vc.query_advice(
+                    wasm_datacount_section_body_chip.config.leb128_chip.config.sn,
+                    Rotation::cur(),
+                );
+                cb.require_equal(
+                    "wasm_datacount_section_q_first => data_count is the declared count",
+                    data_count_expr.clone(),
+                    wasm_datacount_section_leb128_sn_expr,
+                );
+                cb.require_equal(
+                    "wasm_datacount_section_q_first => data_count_declared=1",
+                    data_count_declared_expr.clone(),
+                    1.expr(),
+                );
+            });
+            cb.condition(wasm_data_section_q_first_expr.clone(), |cb| {
+                let wasm_data_section_leb128_sn_expr = vc.query_advice(
+                    wasm_data_section_body_chip.config.leb128_chip.config.sn,
+                    Rotation::cur(),
+                );
+                cb.require_equal(
+                    "wasm_data_section_q_first => declared data segments are parsed",
+                    data_count_expr.clone(),
+                    data_count_prev_expr.clone()
+                        - data_count_declared_prev_expr.clone() * wasm_data_section_leb128_sn_expr,
+                );
+                cb.require_equal(
+                    "wasm_data_section_q_first => prev.data_count_declared=data_count_declared",
+                    data_count_declared_prev_expr.clone(),
+                    data_count_declared_expr.clone(),
+                );
+            });
+            cb.condition(
+                wasm_code_section_body_chip
+                    .config
+                    .is_data_index_instruction_expr(vc),
+                |cb| {
+                    cb.require_equal(
+                        "memory.init or data.drop => data_count_declared=1",
+                        data_count_declared_expr.clone(),
+                        1.expr(),
+                    )
+                },
+            );
+            cb.condition(q_last_expr.clone(), |cb| {
+                cb.require_zero(
+                    "q_last => declared data count matches the data section",
+                    data_count_expr.clone(),
+                );
+            });
             cb.condition(and::expr([
                 not_q_first_expr.clone(),
                 not_q_first_prev_expr.clone(),
@@ -960,13 +1295,17 @@ impl<F: Field> WasmChip<F> {
                     "id of section equals to default at magic prefix indexes",
                     index_at_magic_prefix[i].config().expr() * (section_id_expr.clone() - SECTION_ID_DEFAULT.expr()),
                 );
+                cb.require_zero(
+                    "rank of section equals to default at magic prefix indexes",
+                    index_at_magic_prefix[i].config().expr() * (section_rank_expr.clone() - SECTION_ID_DEFAULT.expr()),
+                );
             }
 
             cb.condition(not_q_first_expr.clone(), |cb| {
                 cb.require_zero(
-                    "prev.section_id <= cur.section_id",
+                    "prev.section_rank <= cur.section_rank",
                     (section_id_lt_chip.config().is_lt(vc, None) - 1.expr())
-                        * (section_id_expr.clone() - section_id_prev_expr.clone()),
+                        * (section_rank_expr.clone() - section_rank_prev_expr.clone()),
                 );
             });
 
@@ -981,6 +1320,26 @@ impl<F: Field> WasmChip<F> {
             cb.gate(q_enable_expr)
         });
 
+        cs.lookup("section_rank is the rank of section_id", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                vc,
+                q_enable,
+                &shared_state.borrow(),
+                error_code,
+            );
+            let is_section_id_expr = vc.query_fixed(is_section_id, Rotation::cur());
+            let not_custom_expr = not::expr(section_id_is_custom_chip.config().expr());
+            let cond = q_enable_expr * is_section_id_expr * not_custom_expr;
+
+            let section_id_expr = vc.query_advice(section_id, Rotation::cur());
+            let section_rank_expr = vc.query_advice(section_rank, Rotation::cur());
+
+            vec![
+                (cond.clone() * section_id_expr, section_rank_table_config.section_id),
+                (cond * section_rank_expr, section_rank_table_config.rank),
+            ]
+        });
+
         cs.lookup("section_id is a valid number", |vc| {
             let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
                 vc,
@@ -1281,9 +1640,12 @@ impl<F: Field> WasmChip<F> {
             q_last,
             range_table_config_0_256,
             section_id_range_table_config,
+            section_rank_table_config,
             index_at_magic_prefix,
             magic_prefix_count,
             section_id,
+            section_rank,
+            section_id_is_custom_chip,
             is_section_id,
             is_section_len,
             is_section_body,
@@ -1301,9 +1663,14 @@ impl<F: Field> WasmChip<F> {
             wasm_start_section_body_chip,
             wasm_table_section_body_chip,
             wasm_element_section_body_chip,
+            wasm_custom_section_body_chip,
+            wasm_datacount_section_body_chip,
             section_id_lt_chip,
             range_table_config_0_128,
             dynamic_indexes_chip,
+            module_types_chip,
+            type_stack_table_config,
+            host_functions_table_config,
             shared_state,
             func_count,
             block_depth_level,
@@ -1311,6 +1678,8 @@ impl<F: Field> WasmChip<F> {
             body_byte_rev_index_l2,
             body_item_rev_count_l1,
             body_item_rev_count_l2,
+            data_count,
+            data_count_declared,
             error_code,
         };
 
@@ -1332,6 +1701,9 @@ impl<F: Field> WasmChip<F> {
         wb_offset: usize,
         assign_delta: AssignDeltaType,
     ) -> Result<NewWbOffsetType, Error> {
+        // a rejected bytecode must not leave its state to the next one
+        self.config.shared_state.borrow_mut().error_code_reset();
+        self.config.shared_state.borrow_mut().block_level_reset();
         let result = self.assign_auto_internal(region, wb, wb_offset, assign_delta);
         let assign_delta = assign_delta
             + if self.config.wb_table.zero_row_enabled {
@@ -1351,7 +1723,10 @@ impl<F: Field> WasmChip<F> {
                     Error::ParseOpcodeFailedAt(offset) |
                     Error::InvalidByteValueAt(offset) |
                     Error::InvalidEnumValueAt(offset) |
-                    Error::ComputeValueAt(offset) => {
+                    Error::ComputeValueAt(offset) |
+                    Error::FloatUnsupportedAt(offset) |
+                    Error::ImportForbiddenAt(offset) |
+                    Error::TypeMismatchAt(offset) => {
                         debug!("recoverable error offset: {}", offset);
                         self.shared_state().borrow_mut().error_code = ErrorCode::Error as u64;
                         // cannot use offset received from error because of forward checks 
@@ -1375,6 +1750,7 @@ impl<F: Field> WasmChip<F> {
                     _ => return Err(e)
                 }
 
+                self.config.shared_state.borrow_mut().bytecode_number_inc();
                 Ok(wb.bytes.len() + assign_delta)
             } else {
                 Err(e)
@@ -1451,8 +1827,17 @@ impl<F: Field> WasmChip<F> {
             }
         }
 
+        // function bodies are type checked against the types of the whole module
+        let module_types = module_types_parse(&wb.bytes).map_err(remap_error_to_compute_value_at(
+            WASM_SECTIONS_START_INDEX + assign_delta,
+        ))?;
+        *self.config.module_types_chip.config.module_types.borrow_mut() = module_types;
+
         let mut wb_offset = WASM_SECTIONS_START_INDEX;
-        let mut section_id_prev: i64 = SECTION_ID_DEFAULT as i64;
+        let mut section_rank_prev: u64 = SECTION_ID_DEFAULT as u64;
+        let mut data_count_prev: u64 = 0;
+        let mut data_count_declared_prev = false;
+        let mut data_count_body_offset = 0;
         while wb_offset < wb.bytes.len() {
             let section_start_offset = wb_offset;
             let section_len_start_offset = section_start_offset + 1;
@@ -1473,6 +1858,34 @@ impl<F: Field> WasmChip<F> {
                 section_start_offset + section_len_leb_bytes_count as usize + section_len;
             let section_end_offset = section_body_end_offset;
 
+            let section_rank = match WasmSection::try_from(section_id as i32) {
+                Ok(WasmSection::Custom) => section_rank_prev,
+                Ok(wasm_section) => wasm_section.rank(),
+                Err(_) => section_id,
+            };
+            let (mut data_count, mut data_count_declared) =
+                (data_count_prev, data_count_declared_prev);
+            if section_id == WasmSection::DataCount as u64 {
+                let (count, _) = leb128_compute_sn(&wb.bytes, false, section_body_start_offset)
+                    .map_err(remap_error_to_compute_value_at(
+                        section_body_start_offset + assign_delta,
+                    ))?;
+                data_count = count;
+                data_count_declared = true;
+                data_count_body_offset = section_body_start_offset;
+            }
+            if section_id == WasmSection::Data as u64 && data_count_declared {
+                let (items_count, _) =
+                    leb128_compute_sn(&wb.bytes, false, section_body_start_offset).map_err(
+                        remap_error_to_compute_value_at(section_body_start_offset + assign_delta),
+                    )?;
+                if items_count != data_count {
+                    return Err(Error::InvalidByteValueAt(
+                        section_body_start_offset + assign_delta,
+                    ));
+                }
+                data_count = 0;
+            }
             for wb_offset in section_start_offset..=section_end_offset {
                 if wb_offset == section_start_offset {
                     let wasm_section: WasmSection = (section_id as i32).try_into().map_err(
@@ -1578,11 +1991,20 @@ impl<F: Field> WasmChip<F> {
                                 .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
                         }
                         WasmSection::Code => {
+                            self.config.shared_state.borrow_mut().data_index_used = false;
                             next_section_offset = self
                                 .config
                                 .wasm_code_section_body_chip
                                 .assign_auto(region, wb, section_body_offset, assign_delta)
                                 .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
+                            // memory.init and data.drop are only valid with the data count section
+                            if self.config.shared_state.borrow().data_index_used
+                                && !data_count_declared
+                            {
+                                return Err(Error::InvalidByteValueAt(
+                                    section_body_offset + assign_delta,
+                                ));
+                            }
                         }
                         WasmSection::Data => {
                             next_section_offset = self
@@ -1591,6 +2013,26 @@ impl<F: Field> WasmChip<F> {
                                 .assign_auto(region, wb, section_body_offset, assign_delta)
                                 .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
                         }
+                        WasmSection::DataCount => {
+                            next_section_offset = self
+                                .config
+                                .wasm_datacount_section_body_chip
+                                .assign_auto(region, wb, section_body_offset, assign_delta)
+                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
+                        }
+                        WasmSection::Custom => {
+                            next_section_offset = self
+                                .config
+                                .wasm_custom_section_body_chip
+                                .assign_auto(
+                                    region,
+                                    wb,
+                                    section_body_offset,
+                                    assign_delta,
+                                    section_len,
+                                )
+                                .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
+                        }
                         _ => {
                             return Err(Error::FatalUnsupportedValue(format!(
                                 "unsupported section value '{:x?}'",
@@ -1613,17 +2055,49 @@ impl<F: Field> WasmChip<F> {
                         || Value::known(F::from(section_id)),
                     )
                     .map_err(remap_error_to_assign_at(wb_offset))?;
+                self.config
+                    .section_id_is_custom_chip
+                    .assign(region, wb_offset + assign_delta, Value::known(F::from(section_id)))
+                    .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
+                region
+                    .assign_advice(
+                        || format!("assign at {} section_rank val {}", wb_offset, section_rank),
+                        self.config.section_rank,
+                        wb_offset + assign_delta,
+                        || Value::known(F::from(section_rank)),
+                    )
+                    .map_err(remap_error_to_assign_at(wb_offset))?;
                 self.config
                     .section_id_lt_chip
                     .assign(
                         region,
                         wb_offset + assign_delta,
-                        F::from(section_id_prev as u64),
-                        F::from(section_id),
+                        F::from(section_rank_prev),
+                        F::from(section_rank),
                     )
                     .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
-                section_id_prev = section_id as i64;
+                section_rank_prev = section_rank;
+                let (data_count, data_count_declared) = if wb_offset < section_body_start_offset {
+                    (data_count_prev, data_count_declared_prev)
+                } else {
+                    (data_count, data_count_declared)
+                };
+                for (column, value) in [
+                    (self.config.data_count, data_count),
+                    (self.config.data_count_declared, data_count_declared as u64),
+                ] {
+                    region
+                        .assign_advice(
+                            || format!("assign at {} data count val {}", wb_offset, value),
+                            column,
+                            wb_offset + assign_delta,
+                            || Value::known(F::from(value)),
+                        )
+                        .map_err(remap_error_to_assign_at(wb_offset))?;
+                }
             }
+            data_count_prev = data_count;
+            data_count_declared_prev = data_count_declared;
 
             self.assign(
                 region,
@@ -1657,6 +2131,10 @@ impl<F: Field> WasmChip<F> {
             }
         }
 
+        if data_count_prev != 0 {
+            return Err(Error::InvalidByteValueAt(data_count_body_offset + assign_delta));
+        }
+
         let dynamic_indexes_offset = self.config.dynamic_indexes_chip.assign_auto(
             region,
             self.config.shared_state.borrow().dynamic_indexes_offset,
@@ -1666,6 +2144,142 @@ impl<F: Field> WasmChip<F> {
         )?;
         self.config.shared_state.borrow_mut().dynamic_indexes_offset = dynamic_indexes_offset;
 
+        let module_types_offset = self.config.module_types_chip.assign_auto(
+            region,
+            self.config.shared_state.borrow().module_types_offset,
+            assign_delta,
+            &self.config.module_types_chip.config.module_types.borrow(),
+        )?;
+        self.config.shared_state.borrow_mut().module_types_offset = module_types_offset;
+
         Ok(wb_offset + assign_delta)
     }
 }
+
+/// Wasm circuit configuration
+#[derive(Debug, Clone)]
+pub struct WasmCircuitConfig<F: Field> {
+    wasm_config: WasmConfig<F>,
+}
+
+/// Wasm circuit configuration arguments
+pub struct WasmCircuitConfigArgs {
+    /// BytecodeTable the evm circuit reads code from
+    pub bytecode_table: BytecodeTable,
+    /// WasmFunctionTable the evm circuit resolves calls and locals with
+    pub wasm_function_table: WasmFunctionTable,
+    /// WasmBytecodeTable with the validated modules, shared with the evm circuit
+    pub wasm_bytecode_table: WasmBytecodeTable,
+}
+
+impl<F: Field> SubCircuitConfig<F> for WasmCircuitConfig<F> {
+    type ConfigArgs = WasmCircuitConfigArgs;
+
+    fn new(
+        cs: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            bytecode_table,
+            wasm_function_table,
+            wasm_bytecode_table,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        // every bytecode of the block is parsed, the ones which are not valid modules get
+        // their error flag raised instead of failing the proof
+        shared_state.borrow_mut().error_processing_enabled = true;
+        shared_state.borrow_mut().host_imports_restricted = true;
+        let wb_table = Rc::new(wasm_bytecode_table);
+        let wasm_config = WasmChip::<F>::configure(cs, wb_table, shared_state);
+        // with keccak code hashes the bytecode table keeps the rlc of the hash while the wasm
+        // bytecode table keeps its scalar, so the binding only holds for poseidon code hashes
+        #[cfg(feature = "poseidon-codehash")]
+        wasm_config.configure_bytecode_table_lookup(cs, &bytecode_table);
+        #[cfg(not(feature = "poseidon-codehash"))]
+        let _ = bytecode_table;
+        wasm_config.configure_function_table_lookup(cs, &wasm_function_table);
+
+        Self { wasm_config }
+    }
+}
+
+/// Wasm circuit validates the structure of every wasm module executed in the block
+#[derive(Clone, Default, Debug)]
+pub struct WasmCircuit<F: Field> {
+    /// Wasm bytecodes
+    pub wbs: Vec<WasmBytecode>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> WasmCircuit<F> {
+    /// Creates a new WasmCircuit
+    pub fn new(wbs: Vec<WasmBytecode>) -> Self {
+        Self {
+            wbs,
+            _marker: PhantomData,
+        }
+    }
+
+    fn rows_required(wbs: &[WasmBytecode]) -> usize {
+        // every bytecode is prefixed with the zero row of the wasm bytecode table
+        let bytecode_rows: usize = wbs.iter().map(|wb| wb.bytes.len() + 1).sum();
+        // the module types table shares the region and starts with an empty row
+        let module_types_rows: usize = 1 + wbs
+            .iter()
+            .map(|wb| module_types_parse(&wb.bytes).map_or(0, |m| m.rows_count()))
+            .sum::<usize>();
+        bytecode_rows.max(module_types_rows)
+    }
+}
+
+impl<F: Field> SubCircuit<F> for WasmCircuit<F> {
+    type Config = WasmCircuitConfig<F>;
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let wbs = block
+            .bytecodes
+            .values()
+            // empty code has no bytes in the bytecode table to bind
+            .filter(|bytecode| !bytecode.bytes.is_empty())
+            .map(WasmBytecode::from)
+            .collect();
+        Self::new(wbs)
+    }
+
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let rows = Self::rows_required(&Self::new_from_block(block).wbs);
+        (rows, rows.max(block.circuits_params.max_bytecode))
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        _challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Halo2Error> {
+        let mut wasm_chip = WasmChip::construct(config.wasm_config.clone());
+
+        wasm_chip
+            .load_once(layouter)
+            .map_err(|_| Halo2Error::Synthesis)?;
+        layouter.assign_region(
+            || "wasm circuit",
+            |mut region| {
+                wasm_chip.config.shared_state.borrow_mut().reset();
+                let mut assign_delta = 0;
+                for wb in &self.wbs {
+                    wasm_chip
+                        .load(&mut region, wb, assign_delta)
+                        .map_err(|_| Halo2Error::Synthesis)?;
+                    assign_delta = wasm_chip
+                        .assign_auto(&mut region, wb, 0, assign_delta)
+                        .map_err(|e| {
+                            debug!("wasm circuit failed to assign bytecode: {:?}", e);
+                            Halo2Error::Synthesis
+                        })?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
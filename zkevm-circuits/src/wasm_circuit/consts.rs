@@ -13,6 +13,9 @@ pub static WASM_VERSION_PREFIX_END_INDEX: usize =
 pub static WASM_SECTIONS_START_INDEX: usize = WASM_VERSION_PREFIX_END_INDEX + 1;
 pub static WASM_BLOCK_END: u8 = 0xB;
 pub static WASM_BLOCKTYPE_DELIMITER: i32 = 0x40;
+pub static WASM_INSTRUCTION_PREFIX_FC: u8 = 0xFC;
+/// Name of the exported function a wasm contract runs first
+pub static ENTRY_FUNCTION_NAME: &[u8] = b"main";
 pub const WASM_SECTION_ID_MAX: usize = WasmSection::DataCount as usize;
 
 // TODO make it differ from custom section id (which is 0 too)
@@ -9,6 +9,9 @@ pub enum Error {
     ParseOpcodeFailedAt(AssignOffsetType),
     InvalidEnumValueAt(AssignOffsetType),
     ComputeValueAt(AssignOffsetType),
+    FloatUnsupportedAt(AssignOffsetType),
+    ImportForbiddenAt(AssignOffsetType),
+    TypeMismatchAt(AssignOffsetType),
 
     InvalidEnumValue,
     IndexOutOfBoundsSimple,
@@ -41,6 +44,9 @@ pub fn is_recoverable_error(e: &Error) -> bool {
         | Error::InvalidByteValueAt(_)
         | Error::InvalidEnumValueAt(_)
         | Error::ComputeValueAt(_)
+        | Error::FloatUnsupportedAt(_)
+        | Error::ImportForbiddenAt(_)
+        | Error::TypeMismatchAt(_)
         | Error::IndexOutOfBoundsSimple
         | Error::Leb128Encode
         | Error::Leb128EncodeSigned
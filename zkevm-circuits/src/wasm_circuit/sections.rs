@@ -10,3 +10,5 @@ pub mod consts;
 pub mod start;
 pub mod table;
 pub mod element;
+pub mod custom;
+pub mod datacount;
@@ -2,15 +2,16 @@ use std::{cell::RefCell, marker::PhantomData, rc::Rc};
 
 use halo2_proofs::{
     circuit::{Chip, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Fixed},
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
     poly::Rotation,
 };
 use itertools::Itertools;
 use log::debug;
 
-use eth_types::Field;
+use eth_types::{bytecode::wasm_entry_function, Field};
 use gadgets::{
     binary_number::BinaryNumberChip,
+    is_zero::{IsZeroChip, IsZeroInstruction},
     less_than::{LtChip, LtInstruction},
     util::{and, not, or, Expr},
 };
@@ -25,22 +26,40 @@ use crate::{
             WasmErrorAwareChip, WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
             WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
         },
-        consts::{WASM_BLOCKTYPE_DELIMITER, WASM_BLOCK_END},
+        consts::{WASM_BLOCKTYPE_DELIMITER, WASM_BLOCK_END, WASM_INSTRUCTION_PREFIX_FC},
         error::{
-            remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
+            remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at,
+            validate_wb_offset, Error,
         },
         leb128::circuit::LEB128Chip,
         sections::{code::body::types::AssignType, consts::LebParams},
         tables::{
             code_blocks, code_blocks::circuit::CodeBlocksChip,
             dynamic_indexes::circuit::DynamicIndexesChip,
+            fixed_range::config::RangeTableConfig,
+            module_types::{
+                circuit::ModuleTypesChip,
+                types::{AssignType as ModuleTypesAssignType, LookupArgsParams},
+            },
+            type_stack::{
+                config::TypeStackTableConfig,
+                types::{
+                    num_type_bit, prefixed_instruction_key, BlockType, TypeSeq,
+                    TypeStackEffect, TypeStackFrame, TypeStackState, TYPE_STACK_MAX_LEN,
+                },
+            },
         },
         types::{
-            AssignDeltaType, AssignValueType, ControlInstruction, NumericInstruction,
-            ParametricInstruction, SharedState, VariableInstruction, CONTROL_INSTRUCTION_BLOCK,
+            AssignDeltaType, AssignValueType, ControlInstruction, MemoryInstruction, NumType,
+            NumericInstruction, ParametricInstruction, PrefixedInstruction, SharedState,
+            VariableInstruction, WasmModuleTag, CONTROL_INSTRUCTION_BLOCK,
             CONTROL_INSTRUCTION_WITHOUT_ARGS, CONTROL_INSTRUCTION_WITH_LEB_ARG,
-            NUMERIC_INSTRUCTIONS_WITHOUT_ARGS, NUMERIC_INSTRUCTION_WITH_LEB_ARG,
-            PARAMETRIC_INSTRUCTIONS_WITHOUT_ARGS, VARIABLE_INSTRUCTION_WITH_LEB_ARG,
+            FLOAT_PREFIXED_INSTRUCTIONS,
+            MEMORY_INSTRUCTION_WITH_MEMARG, MEMORY_INSTRUCTION_WITH_MEMORY_INDEX,
+            NUMERIC_INSTRUCTIONS_WITHOUT_ARGS, NUM_TYPE_VALUES,
+            NUMERIC_INSTRUCTION_WITH_LEB_ARG, PARAMETRIC_INSTRUCTIONS_WITHOUT_ARGS,
+            PREFIXED_INSTRUCTIONS_WITHOUT_ARGS, PREFIXED_INSTRUCTION_WITH_LEB_ARG,
+            PREFIXED_INSTRUCTION_WITH_TWO_LEB_ARGS, VARIABLE_INSTRUCTION_WITH_LEB_ARG,
         },
     },
 };
@@ -60,22 +79,83 @@ pub struct WasmCodeSectionBodyConfig<F: Field> {
     pub is_numeric_instruction_leb_arg: Column<Fixed>,
     pub is_variable_instruction: Column<Fixed>,
     pub is_variable_instruction_leb_arg: Column<Fixed>,
+    pub is_memory_instruction: Column<Fixed>,
+    /// memarg of a load or store, or the memory index of `memory.size`/`memory.grow`
+    pub is_memory_instruction_leb_arg: Column<Fixed>,
     pub is_control_instruction: Column<Fixed>,
     pub is_control_instruction_leb_arg: Column<Fixed>,
     pub is_parametric_instruction: Column<Fixed>,
+    pub is_prefixed_instruction: Column<Fixed>,
+    pub is_prefixed_instruction_sub_opcode: Column<Fixed>,
+    pub is_prefixed_instruction_leb_arg: Column<Fixed>,
     pub is_blocktype_delimiter: Column<Fixed>,
+    /// single byte blocktype of one result
+    pub is_blocktype_valtype: Column<Fixed>,
+    /// blocktype referring to a function type, a non-negative s33
+    pub is_blocktype_type_index: Column<Fixed>,
     pub is_block_end: Column<Fixed>,
 
     pub leb128_chip: Rc<LEB128Chip<F>>,
     pub numeric_instructions_chip: Rc<BinaryNumberChip<F, NumericInstruction, 8>>,
     pub variable_instruction_chip: Rc<BinaryNumberChip<F, VariableInstruction, 8>>,
+    pub memory_instruction_chip: Rc<BinaryNumberChip<F, MemoryInstruction, 8>>,
     pub control_instruction_chip: Rc<BinaryNumberChip<F, ControlInstruction, 8>>,
     pub parametric_instruction_chip: Rc<BinaryNumberChip<F, ParametricInstruction, 8>>,
+    pub prefixed_instruction_chip: Rc<BinaryNumberChip<F, PrefixedInstruction, 8>>,
     pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
 
     pub code_blocks_chip: Rc<CodeBlocksChip<F>>,
     block_opcode_number: Column<Advice>,
 
+    pub module_types_chip: Rc<ModuleTypesChip<F>>,
+    /// operand types left after the instruction, packed as `TypeSeq`
+    type_stack: Column<Advice>,
+    type_stack_len: Column<Advice>,
+    type_stack_shift: Column<Advice>,
+    /// `block_opcode_number` of the innermost open frame, 0 for the function frame
+    frame_id: Column<Advice>,
+    frame_base: Column<Advice>,
+    is_unreachable: Column<Advice>,
+    pops: Column<Advice>,
+    pops_count: Column<Advice>,
+    pops_shift: Column<Advice>,
+    pushes: Column<Advice>,
+    pushes_count: Column<Advice>,
+    pushes_shift: Column<Advice>,
+    /// operands under the popped ones
+    rest: Column<Advice>,
+    rest_shift: Column<Advice>,
+    operand_type: Column<Advice>,
+    pub rest_lt_chip: Rc<LtChip<F, 16>>,
+    pub is_func_label_chip: Rc<IsZeroChip<F>>,
+    /// results of a `block`, `loop` or `if`, its params are the pushes
+    block_results: Column<Advice>,
+    block_results_count: Column<Advice>,
+    block_results_shift: Column<Advice>,
+    /// set on an `if` followed by an `else`
+    has_else: Column<Advice>,
+    /// `block_opcode_number` of the `end` closing a `block`, `loop` or `if`
+    frame_end: Column<Advice>,
+    /// pc of the `end` closing a `block`, `loop` or `if`
+    frame_end_pc: Column<Advice>,
+    /// pc of the `else` of an `if`, 0 without one
+    else_pc: Column<Advice>,
+    /// frame a `br`/`br_if` to a block label targets, it encloses the instruction
+    label_frame_id: Column<Advice>,
+    label_frame_end: Column<Advice>,
+    /// pc of the opener of the frame a `br`/`br_if` to a block label targets
+    label_frame_pc: Column<Advice>,
+    pub label_frame_id_lt_chip: Rc<LtChip<F, 3>>,
+    pub label_frame_end_lt_chip: Rc<LtChip<F, 3>>,
+
+    wb_table: Rc<WasmBytecodeTable>,
+    /// locals the function body declared so far, params excluded
+    declared_locals: Column<Advice>,
+    /// set on the params and locals count of the function exported as `main`
+    is_entry_func: Column<Advice>,
+    /// instructions of the code section before the row, the program counter of an instruction
+    pc: Column<Advice>,
+
     pub func_count: Column<Advice>,
     pub block_level: Column<Advice>,
     pub block_level_lt_chip: Rc<LtChip<F, 2>>,
@@ -89,7 +169,52 @@ pub struct WasmCodeSectionBodyConfig<F: Field> {
     _marker: PhantomData<F>,
 }
 
-impl<'a, F: Field> WasmCodeSectionBodyConfig<F> {}
+impl<'a, F: Field> WasmCodeSectionBodyConfig<F> {
+    /// 1 at the sub opcode of `memory.init` and `data.drop`, the instructions that are only valid
+    /// when the module declares the data count section
+    pub fn is_data_index_instruction_expr(&self, vc: &mut VirtualCells<F>) -> Expression<F> {
+        vc.query_fixed(self.is_prefixed_instruction_sub_opcode, Rotation::cur())
+            * (self
+                .prefixed_instruction_chip
+                .config
+                .value_equals(PrefixedInstruction::MemoryInit, Rotation::cur())(vc)
+                + self
+                    .prefixed_instruction_chip
+                    .config
+                    .value_equals(PrefixedInstruction::DataDrop, Rotation::cur())(vc))
+    }
+
+    /// 1 at the last local declaration of a function body, the row before its first instruction,
+    /// where `pc` is already the program counter of that instruction
+    pub fn is_body_start_expr(&self, vc: &mut VirtualCells<F>) -> Expression<F> {
+        let is_instruction_next_expr = [
+            self.is_numeric_instruction,
+            self.is_variable_instruction,
+            self.is_memory_instruction,
+            self.is_control_instruction,
+            self.is_parametric_instruction,
+            self.is_prefixed_instruction,
+            self.is_block_end,
+        ]
+        .iter()
+        .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, Rotation::next()));
+
+        (vc.query_fixed(self.is_local_type_transitions_count, Rotation::cur())
+            + vc.query_fixed(self.is_local_type, Rotation::cur()))
+            * is_instruction_next_expr
+    }
+
+    /// Index of the function the row belongs to plus one
+    pub fn func_index_plus_one_expr(&self, vc: &mut VirtualCells<F>) -> Expression<F> {
+        vc.query_advice(self.func_count, Rotation::cur())
+            - vc.query_advice(self.body_item_rev_count, Rotation::cur())
+    }
+
+    /// Instructions of the code section before the row
+    pub fn pc_expr(&self, vc: &mut VirtualCells<F>) -> Expression<F> {
+        vc.query_advice(self.pc, Rotation::cur())
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct WasmCodeSectionBodyChip<F: Field> {
@@ -165,7 +290,11 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                 AssignType::IsLocalRepetitionCount,
                 AssignType::IsNumericInstructionLebArg,
                 AssignType::IsVariableInstructionLebArg,
+                AssignType::IsMemoryInstructionLebArg,
                 AssignType::IsControlInstructionLebArg,
+                AssignType::IsPrefixedInstructionSubOpcode,
+                AssignType::IsPrefixedInstructionLebArg,
+                AssignType::IsBlocktypeTypeIndex,
             ]
             .contains(&assign_type)
             {
@@ -358,6 +487,45 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::IsMemoryInstruction => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_memory_instruction' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_memory_instruction,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    if assign_value == 1 {
+                        let opcode = wb.bytes[wb_offset]
+                            .try_into()
+                            .map_err(remap_error_to_invalid_enum_value_at(assign_offset))?;
+                        self.config
+                            .memory_instruction_chip
+                            .assign(region, assign_offset, &opcode)
+                            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+                    }
+                }
+                AssignType::IsMemoryInstructionLebArg => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_memory_instruction_leb_arg' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_memory_instruction_leb_arg,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::IsControlInstruction => {
                     region
                         .assign_fixed(
@@ -421,6 +589,62 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                             .map_err(remap_error(Error::FatalAssignExternalChip))?;
                     }
                 }
+                AssignType::IsPrefixedInstruction => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_prefixed_instruction' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_prefixed_instruction,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::IsPrefixedInstructionSubOpcode => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_prefixed_instruction_sub_opcode' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_prefixed_instruction_sub_opcode,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                    if assign_value == 1 {
+                        let sub_opcode = leb_params.unwrap().sn;
+                        let opcode: PrefixedInstruction = u8::try_from(sub_opcode)
+                            .map_err(remap_error_to_invalid_enum_value_at(assign_offset))?
+                            .try_into()
+                            .map_err(remap_error_to_invalid_enum_value_at(assign_offset))?;
+                        self.config
+                            .prefixed_instruction_chip
+                            .assign(region, assign_offset, &opcode)
+                            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+                    }
+                }
+                AssignType::IsPrefixedInstructionLebArg => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_prefixed_instruction_leb_arg' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_prefixed_instruction_leb_arg,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::IsBlocktypeDelimiter => {
                     region
                         .assign_fixed(
@@ -436,6 +660,36 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::IsBlocktypeValtype => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_blocktype_valtype' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_blocktype_valtype,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::IsBlocktypeTypeIndex => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_blocktype_type_index' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_blocktype_type_index,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::IsBlockEnd => {
                     region
                         .assign_fixed(
@@ -481,6 +735,31 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmCodeSectionBodyChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::DeclaredLocals => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'declared_locals' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.declared_locals,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::Pc => {
+                    region
+                        .assign_advice(
+                            || format!("assign 'pc' val {} at {}", assign_value, assign_offset),
+                            self.config.pc,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::BlockOpcodeIndex => {
                     region
                         .assign_advice(
@@ -525,6 +804,10 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         body_item_rev_count: Column<Advice>,
         error_code: Column<Advice>,
         bytecode_number: Column<Advice>,
+        module_types_chip: Rc<ModuleTypesChip<F>>,
+        type_stack_table_config: &TypeStackTableConfig<F>,
+        range_table_config_0_128: &RangeTableConfig<F, 0, 128>,
+        range_table_config_0_256: &RangeTableConfig<F, 0, 256>,
     ) -> WasmCodeSectionBodyConfig<F> {
         let q_enable = cs.fixed_column();
         let q_first = cs.fixed_column();
@@ -542,12 +825,48 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         let is_numeric_instruction_leb_arg = cs.fixed_column();
         let is_variable_instruction = cs.fixed_column();
         let is_variable_instruction_leb_arg = cs.fixed_column();
+        let is_memory_instruction = cs.fixed_column();
+        let is_memory_instruction_leb_arg = cs.fixed_column();
         let is_control_instruction = cs.fixed_column();
         let is_control_instruction_leb_arg = cs.fixed_column();
         let is_parametric_instruction = cs.fixed_column();
+        let is_prefixed_instruction = cs.fixed_column();
+        let is_prefixed_instruction_sub_opcode = cs.fixed_column();
+        let is_prefixed_instruction_leb_arg = cs.fixed_column();
         let is_blocktype_delimiter = cs.fixed_column();
+        let is_blocktype_valtype = cs.fixed_column();
+        let is_blocktype_type_index = cs.fixed_column();
         let is_block_end = cs.fixed_column();
 
+        let type_stack = cs.advice_column();
+        let type_stack_len = cs.advice_column();
+        let type_stack_shift = cs.advice_column();
+        let frame_id = cs.advice_column();
+        let frame_base = cs.advice_column();
+        let is_unreachable = cs.advice_column();
+        let pops = cs.advice_column();
+        let pops_count = cs.advice_column();
+        let pops_shift = cs.advice_column();
+        let pushes = cs.advice_column();
+        let pushes_count = cs.advice_column();
+        let pushes_shift = cs.advice_column();
+        let rest = cs.advice_column();
+        let rest_shift = cs.advice_column();
+        let operand_type = cs.advice_column();
+        let block_results = cs.advice_column();
+        let block_results_count = cs.advice_column();
+        let block_results_shift = cs.advice_column();
+        let has_else = cs.advice_column();
+        let frame_end = cs.advice_column();
+        let frame_end_pc = cs.advice_column();
+        let else_pc = cs.advice_column();
+        let label_frame_id = cs.advice_column();
+        let label_frame_end = cs.advice_column();
+        let label_frame_pc = cs.advice_column();
+        let declared_locals = cs.advice_column();
+        let is_entry_func = cs.advice_column();
+        let pc = cs.advice_column();
+
         let config = CodeBlocksChip::configure(cs, shared_state.clone());
         let code_blocks_chip = Rc::new(CodeBlocksChip::construct(config));
 
@@ -567,6 +886,18 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             BinaryNumberChip::configure(cs, is_variable_instruction, Some(wb_table.value.into()));
         let variable_instruction_chip = Rc::new(BinaryNumberChip::construct(config));
 
+        let config =
+            BinaryNumberChip::configure(cs, is_memory_instruction, Some(wb_table.value.into()));
+        let memory_instruction_chip = Rc::new(BinaryNumberChip::construct(config));
+
+        // the sub-opcode is LEB128 encoded, so it is checked against the decoded value
+        let config = BinaryNumberChip::configure(
+            cs,
+            is_prefixed_instruction_sub_opcode,
+            Some(leb128_chip.config.sn.into()),
+        );
+        let prefixed_instruction_chip = Rc::new(BinaryNumberChip::construct(config));
+
         let config = LtChip::configure(
             cs,
             |vc| {
@@ -600,6 +931,116 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         );
         let block_level_lt_chip = Rc::new(LtChip::construct(config));
 
+        let instruction_flags = [
+            is_numeric_instruction,
+            is_variable_instruction,
+            is_memory_instruction,
+            is_control_instruction,
+            is_parametric_instruction,
+            is_prefixed_instruction,
+            is_block_end,
+        ];
+
+        // rest < 2^(prev.type_stack_len - pops_count) makes the split of the stack unique
+        let config = LtChip::configure(
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.borrow(),
+                    error_code,
+                );
+                let is_instruction_expr = instruction_flags
+                    .iter()
+                    .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, Rotation::cur()));
+                let is_unreachable_prev_expr = vc.query_advice(is_unreachable, Rotation::prev());
+
+                and::expr([
+                    q_enable_expr,
+                    is_instruction_expr,
+                    not::expr(is_unreachable_prev_expr),
+                ])
+            },
+            |vc| vc.query_advice(rest, Rotation::cur()),
+            |vc| vc.query_advice(rest_shift, Rotation::cur()),
+        );
+        let rest_lt_chip = Rc::new(LtChip::construct(config));
+
+        // the label of a `br`/`br_if` is the function itself when it skips every open block
+        let value_inv = cs.advice_column();
+        let config = IsZeroChip::configure(
+            cs,
+            |vc| {
+                let is_control_instruction_expr =
+                    vc.query_fixed(is_control_instruction, Rotation::cur());
+                let is_br_expr = control_instruction_chip
+                    .config
+                    .value_equals(ControlInstruction::Br, Rotation::cur())(
+                    vc
+                );
+                let is_br_if_expr = control_instruction_chip
+                    .config
+                    .value_equals(ControlInstruction::BrIf, Rotation::cur())(
+                    vc
+                );
+
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.borrow(),
+                    error_code,
+                );
+
+                q_enable_expr * is_control_instruction_expr * (is_br_expr + is_br_if_expr)
+            },
+            |vc| {
+                let block_level_expr = vc.query_advice(block_level, Rotation::cur());
+                let label_expr = vc.query_advice(leb128_chip.config.sn, Rotation::next());
+
+                block_level_expr - 1.expr() - label_expr
+            },
+            value_inv,
+        );
+        let is_func_label_chip = Rc::new(IsZeroChip::construct(config));
+
+        // the frame a `br`/`br_if` to a block label targets opens before it and ends after it
+        let is_block_label_expr = |vc: &mut VirtualCells<F>| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                vc,
+                q_enable,
+                &shared_state.borrow(),
+                error_code,
+            );
+            let is_control_instruction_expr =
+                vc.query_fixed(is_control_instruction, Rotation::cur());
+            let is_br_expr = control_instruction_chip
+                .config
+                .value_equals(ControlInstruction::Br, Rotation::cur())(vc);
+            let is_br_if_expr = control_instruction_chip
+                .config
+                .value_equals(ControlInstruction::BrIf, Rotation::cur())(vc);
+
+            q_enable_expr
+                * is_control_instruction_expr
+                * (is_br_expr + is_br_if_expr)
+                * not::expr(is_func_label_chip.config().expr())
+        };
+        let config = LtChip::configure(
+            cs,
+            is_block_label_expr,
+            |vc| vc.query_advice(label_frame_id, Rotation::cur()),
+            |vc| vc.query_advice(block_opcode_number, Rotation::cur()) + 1.expr(),
+        );
+        let label_frame_id_lt_chip = Rc::new(LtChip::construct(config));
+        let config = LtChip::configure(
+            cs,
+            is_block_label_expr,
+            |vc| vc.query_advice(block_opcode_number, Rotation::cur()),
+            |vc| vc.query_advice(label_frame_end, Rotation::cur()),
+        );
+        let label_frame_end_lt_chip = Rc::new(LtChip::construct(config));
+
         Self::configure_len_prefixed_bytes_span_checks(
             cs,
             leb128_chip.as_ref(),
@@ -613,10 +1054,17 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                         is_numeric_instruction_leb_arg,
                         is_variable_instruction,
                         is_variable_instruction_leb_arg,
+                        is_memory_instruction,
+                        is_memory_instruction_leb_arg,
                         is_control_instruction,
                         is_control_instruction_leb_arg,
                         is_parametric_instruction,
+                        is_prefixed_instruction,
+                        is_prefixed_instruction_sub_opcode,
+                        is_prefixed_instruction_leb_arg,
                         is_blocktype_delimiter,
+                        is_blocktype_valtype,
+                        is_blocktype_type_index,
                         is_block_end,
                     ]
                     .map(|c| vc.query_fixed(c, Rotation::cur()))
@@ -720,6 +1168,9 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 + control_opcode_is_if_expr.clone()
                 + control_opcode_is_else_expr.clone()
                 + is_block_end_expr.clone();
+            let is_opener_expr = control_opcode_is_block_expr.clone()
+                + control_opcode_is_loop_expr.clone()
+                + control_opcode_is_if_expr.clone();
 
             let c = &code_blocks_chip.config;
             vec![
@@ -739,9 +1190,33 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     block_opcode_number_increased_expr.clone() * q_last_expr.clone(),
                     vc.query_fixed(c.q_last, Rotation::cur()),
                 ),
+                (
+                    is_opener_expr.clone() * vc.query_advice(pushes_count, Rotation::cur()),
+                    vc.query_advice(c.params_count, Rotation::cur()),
+                ),
+                (
+                    is_opener_expr.clone() * vc.query_advice(block_results_count, Rotation::cur()),
+                    vc.query_advice(c.results_count, Rotation::cur()),
+                ),
             ]
         });
 
+        // the params count is at the body size of a function and the locals count at its last
+        // local declaration, the row before its first instruction
+        let is_param_count_fact_expr = |vc: &mut VirtualCells<F>| {
+            vc.query_fixed(is_func_body_len, Rotation::cur())
+                * vc.query_fixed(leb128_chip.config.is_first_byte, Rotation::cur())
+        };
+        let is_local_count_fact_expr = |vc: &mut VirtualCells<F>| {
+            let is_instruction_next_expr = instruction_flags
+                .iter()
+                .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, Rotation::next()));
+
+            (vc.query_fixed(is_local_type_transitions_count, Rotation::cur())
+                + vc.query_fixed(is_local_type, Rotation::cur()))
+                * is_instruction_next_expr
+        };
+
         cs.create_gate("WasmCodeSectionBody gate", |vc| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -759,10 +1234,17 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             let is_numeric_instruction_leb_arg_expr = vc.query_fixed(is_numeric_instruction_leb_arg, Rotation::cur());
             let is_variable_instruction_expr = vc.query_fixed(is_variable_instruction, Rotation::cur());
             let is_variable_instruction_leb_arg_expr = vc.query_fixed(is_variable_instruction_leb_arg, Rotation::cur());
+            let is_memory_instruction_expr = vc.query_fixed(is_memory_instruction, Rotation::cur());
+            let is_memory_instruction_leb_arg_expr = vc.query_fixed(is_memory_instruction_leb_arg, Rotation::cur());
             let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
             let is_control_instruction_leb_arg_expr = vc.query_fixed(is_control_instruction_leb_arg, Rotation::cur());
             let is_parametric_instruction_expr = vc.query_fixed(is_parametric_instruction, Rotation::cur());
+            let is_prefixed_instruction_expr = vc.query_fixed(is_prefixed_instruction, Rotation::cur());
+            let is_prefixed_instruction_sub_opcode_expr = vc.query_fixed(is_prefixed_instruction_sub_opcode, Rotation::cur());
+            let is_prefixed_instruction_leb_arg_expr = vc.query_fixed(is_prefixed_instruction_leb_arg, Rotation::cur());
             let is_blocktype_delimiter_expr = vc.query_fixed(is_blocktype_delimiter, Rotation::cur());
+            let is_blocktype_valtype_expr = vc.query_fixed(is_blocktype_valtype, Rotation::cur());
+            let is_blocktype_type_index_expr = vc.query_fixed(is_blocktype_type_index, Rotation::cur());
             let is_block_end_prev_expr = vc.query_fixed(is_block_end, Rotation::prev());
             let is_block_end_expr = vc.query_fixed(is_block_end, Rotation::cur());
 
@@ -790,9 +1272,16 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             cb.require_boolean("is_numeric_instruction_leb_arg is boolean", is_numeric_instruction_leb_arg_expr.clone());
             cb.require_boolean("is_variable_instruction is boolean", is_variable_instruction_expr.clone());
             cb.require_boolean("is_variable_instruction_leb_arg is boolean", is_variable_instruction_leb_arg_expr.clone());
+            cb.require_boolean("is_memory_instruction is boolean", is_memory_instruction_expr.clone());
+            cb.require_boolean("is_memory_instruction_leb_arg is boolean", is_memory_instruction_leb_arg_expr.clone());
             cb.require_boolean("is_control_instruction is boolean", is_control_instruction_expr.clone());
             cb.require_boolean("is_control_instruction_leb_arg is boolean", is_control_instruction_leb_arg_expr.clone());
             cb.require_boolean("is_parametric_instruction is boolean", is_parametric_instruction_expr.clone());
+            cb.require_boolean("is_prefixed_instruction is boolean", is_prefixed_instruction_expr.clone());
+            cb.require_boolean("is_prefixed_instruction_sub_opcode is boolean", is_prefixed_instruction_sub_opcode_expr.clone());
+            cb.require_boolean("is_prefixed_instruction_leb_arg is boolean", is_prefixed_instruction_leb_arg_expr.clone());
+            cb.require_boolean("is_blocktype_valtype is boolean", is_blocktype_valtype_expr.clone());
+            cb.require_boolean("is_blocktype_type_index is boolean", is_blocktype_type_index_expr.clone());
 
             configure_constraints_for_q_first_and_q_last(
                 &mut cb,
@@ -884,6 +1373,18 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                         variable_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
                     }).collect_vec()
             );
+            let is_memory_opcode_with_memarg_expr = or::expr(
+                MEMORY_INSTRUCTION_WITH_MEMARG.iter()
+                    .map(|v| {
+                        memory_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
+                    }).collect_vec()
+            );
+            let is_memory_opcode_with_memory_index_expr = or::expr(
+                MEMORY_INSTRUCTION_WITH_MEMORY_INDEX.iter()
+                    .map(|v| {
+                        memory_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
+                    }).collect_vec()
+            );
             let is_control_opcode_without_params_expr = or::expr(
                 CONTROL_INSTRUCTION_WITHOUT_ARGS.iter()
                     .map(|v| {
@@ -909,10 +1410,28 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     }).collect_vec()
             );
 
+            let is_prefixed_opcode_without_params_expr = or::expr(
+                PREFIXED_INSTRUCTIONS_WITHOUT_ARGS.iter()
+                    .map(|v| {
+                        prefixed_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
+                    }).collect_vec()
+            );
+            let is_prefixed_opcode_with_leb_params_expr = or::expr(
+                PREFIXED_INSTRUCTION_WITH_LEB_ARG.iter()
+                    .chain(PREFIXED_INSTRUCTION_WITH_TWO_LEB_ARGS)
+                    .map(|v| {
+                        prefixed_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
+                    }).collect_vec()
+            );
+
             let is_instruction_leb_arg_expr = or::expr([
                 is_numeric_instruction_leb_arg_expr.clone(),
                 is_variable_instruction_leb_arg_expr.clone(),
+                is_memory_instruction_leb_arg_expr.clone(),
                 is_control_instruction_leb_arg_expr.clone(),
+                is_prefixed_instruction_sub_opcode_expr.clone(),
+                is_prefixed_instruction_leb_arg_expr.clone(),
+                is_blocktype_type_index_expr.clone(),
             ]);
 
             // block_level constraints
@@ -1005,10 +1524,17 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     + is_numeric_instruction_leb_arg_expr.clone()
                     + is_variable_instruction_expr.clone()
                     + is_variable_instruction_leb_arg_expr.clone()
+                    + is_memory_instruction_expr.clone()
+                    + is_memory_instruction_leb_arg_expr.clone()
                     + is_control_instruction_expr.clone()
                     + is_control_instruction_leb_arg_expr.clone()
                     + is_parametric_instruction_expr.clone()
+                    + is_prefixed_instruction_expr.clone()
+                    + is_prefixed_instruction_sub_opcode_expr.clone()
+                    + is_prefixed_instruction_leb_arg_expr.clone()
                     + is_blocktype_delimiter_expr.clone()
+                    + is_blocktype_valtype_expr.clone()
+                    + is_blocktype_type_index_expr.clone()
                     + is_block_end_expr.clone(),
                 1.expr(),
             );
@@ -1038,7 +1564,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 true,
                 &[
                     is_local_type_transitions_count, is_local_repetition_count,
-                    is_numeric_instruction, is_variable_instruction, is_control_instruction, is_parametric_instruction, is_block_end,
+                    is_numeric_instruction, is_variable_instruction, is_memory_instruction, is_control_instruction, is_parametric_instruction, is_prefixed_instruction, is_block_end,
                 ],
             );
             configure_transition_check(
@@ -1055,7 +1581,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 "check next: ... is_local_type(1))) -> is_func_body_code+",
                 is_local_type_expr.clone(),
                 true,
-                &[is_local_repetition_count, is_numeric_instruction, is_variable_instruction, is_control_instruction, is_parametric_instruction, ],
+                &[is_local_repetition_count, is_numeric_instruction, is_variable_instruction, is_memory_instruction, is_control_instruction, is_parametric_instruction, is_prefixed_instruction, ],
             );
 
             // BASIC CONSTRAINTS:
@@ -1090,6 +1616,20 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 }
             );
 
+            cb.condition(
+                is_memory_instruction_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_memory_instruction(1) => opcode is valid",
+                        or::expr([
+                            is_memory_opcode_with_memarg_expr.clone(),
+                            is_memory_opcode_with_memory_index_expr.clone(),
+                        ]),
+                        1.expr(),
+                    );
+                }
+            );
+
             cb.condition(
                 is_control_instruction_expr.clone(),
                 |cb| {
@@ -1134,6 +1674,43 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     )
                 }
             );
+            // is_prefixed_instruction{1} => WASM_INSTRUCTION_PREFIX_FC
+            cb.condition(
+                is_prefixed_instruction_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_prefixed_instruction(1) => WASM_INSTRUCTION_PREFIX_FC",
+                        byte_val_expr.clone(),
+                        WASM_INSTRUCTION_PREFIX_FC.expr(),
+                    );
+                }
+            );
+            // is_prefixed_instruction_sub_opcode => not a float instruction
+            cb.condition(
+                is_prefixed_instruction_sub_opcode_expr.clone(),
+                |cb| {
+                    cb.require_zero(
+                        "is_prefixed_instruction_sub_opcode => not a float instruction",
+                        or::expr(
+                            FLOAT_PREFIXED_INSTRUCTIONS.iter()
+                                .map(|v| {
+                                    prefixed_instruction_chip.config.value_equals(*v, Rotation::cur())(vc)
+                                }).collect_vec()
+                        ),
+                    );
+                }
+            );
+            // is_local_type{1} => integer type
+            cb.condition(
+                is_local_type_expr.clone(),
+                |cb| {
+                    cb.require_in_set(
+                        "is_local_type(1) => integer type",
+                        byte_val_expr.clone(),
+                        NUM_TYPE_VALUES.iter().map(|v| v.expr()).collect_vec(),
+                    );
+                }
+            );
             // is_blocktype_delimiter{1} => WASM_BLOCKTYPE_DELIMITER
             cb.condition(
                 is_blocktype_delimiter_expr.clone(),
@@ -1145,6 +1722,17 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     );
                 }
             );
+            // is_blocktype_valtype{1} => integer type
+            cb.condition(
+                is_blocktype_valtype_expr.clone(),
+                |cb| {
+                    cb.require_in_set(
+                        "is_blocktype_valtype(1) => integer type",
+                        byte_val_expr.clone(),
+                        NUM_TYPE_VALUES.iter().map(|v| v.expr()).collect_vec(),
+                    );
+                }
+            );
             // is_block_end{1} => WASM_BLOCK_END
             cb.condition(
                 is_block_end_expr.clone(),
@@ -1185,9 +1773,32 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 }
             );
 
-            // is_control_opcode_with_leb_param{1} -> is_control_instruction_leb_arg+
-            cb.condition(
-                is_control_opcode_with_leb_param_expr.clone(),
+            // is_memory_instruction{1} -> is_memory_instruction_leb_arg+
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "is_memory_instruction(1) -> is_memory_instruction_leb_arg+",
+                is_memory_instruction_expr.clone(),
+                true,
+                &[is_memory_instruction_leb_arg],
+            );
+            // memory.size and memory.grow take the index of the only memory
+            cb.condition(
+                and::expr([
+                    is_memory_instruction_expr.clone(),
+                    is_memory_opcode_with_memory_index_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_zero(
+                        "memory.size/memory.grow => memory index is 0",
+                        vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                    );
+                }
+            );
+
+            // is_control_opcode_with_leb_param{1} -> is_control_instruction_leb_arg+
+            cb.condition(
+                is_control_opcode_with_leb_param_expr.clone(),
                 |cb| {
                     let is_control_instruction_leb_arg_next_expr = vc.query_fixed(is_control_instruction_leb_arg, Rotation::next());
                     cb.require_equal(
@@ -1197,16 +1808,38 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     );
                 }
             );
-            // is_control_opcode_block{1} -> is_blocktype_delimiter{1}
+            // is_prefixed_instruction{1} -> is_prefixed_instruction_sub_opcode+
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "is_prefixed_instruction(1) -> is_prefixed_instruction_sub_opcode+",
+                is_prefixed_instruction_expr.clone(),
+                true,
+                &[is_prefixed_instruction_sub_opcode],
+            );
+            // is_prefixed_opcode_with_leb_params{1} -> is_prefixed_instruction_leb_arg+
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "is_prefixed_opcode_with_leb_params(1) -> is_prefixed_instruction_leb_arg+",
+                and::expr([
+                    is_prefixed_instruction_sub_opcode_expr.clone(),
+                    leb128_is_last_byte_expr.clone(),
+                    is_prefixed_opcode_with_leb_params_expr.clone(),
+                ]),
+                true,
+                &[is_prefixed_instruction_leb_arg],
+            );
+            // is_control_opcode_block{1} -> is_blocktype_delimiter{1} || is_blocktype_valtype{1} || is_blocktype_type_index+
             configure_transition_check(
                 &mut cb,
                 vc,
-                "is_control_opcode_block(1) -> is_blocktype_delimiter(1)",
+                "is_control_opcode_block(1) -> is_blocktype_delimiter(1) || is_blocktype_valtype(1) || is_blocktype_type_index+",
                 and::expr([
                     is_control_opcode_block_expr.clone(),
                 ]),
                 true,
-                &[is_blocktype_delimiter],
+                &[is_blocktype_delimiter, is_blocktype_valtype, is_blocktype_type_index],
             );
 
             // COMPLEX RELATIONS CONSTRAINTS:
@@ -1221,6 +1854,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1232,6 +1867,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                             + is_variable_instruction_next_expr
                             + is_control_instruction_next_expr
                             + is_parametric_instruction_next_expr
+                            + is_prefixed_instruction_next_expr
+                            + is_memory_instruction_next_expr
 
                             + is_block_end_next_expr
                         ,
@@ -1250,6 +1887,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1261,6 +1900,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                             + is_variable_instruction_next_expr
                             + is_control_instruction_next_expr
                             + is_parametric_instruction_next_expr
+                            + is_prefixed_instruction_next_expr
+                            + is_memory_instruction_next_expr
 
                             + is_block_end_next_expr
                         ,
@@ -1282,6 +1923,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1293,6 +1936,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                             + is_variable_instruction_next_expr
                             + is_control_instruction_next_expr
                             + is_parametric_instruction_next_expr
+                            + is_prefixed_instruction_next_expr
+                            + is_memory_instruction_next_expr
 
                             + is_block_end_next_expr
                         ,
@@ -1314,11 +1959,15 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
 
                     let is_instruction_next_expr = is_numeric_instruction_next_expr
                         + is_variable_instruction_next_expr
                         + is_control_instruction_next_expr
-                        + is_parametric_instruction_next_expr;
+                        + is_parametric_instruction_next_expr
+                        + is_prefixed_instruction_next_expr
+                        + is_memory_instruction_next_expr;
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1348,11 +1997,15 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
 
                     let is_instruction_next_expr = is_numeric_instruction_next_expr
                         + is_variable_instruction_next_expr
                         + is_control_instruction_next_expr
-                        + is_parametric_instruction_next_expr;
+                        + is_parametric_instruction_next_expr
+                        + is_prefixed_instruction_next_expr
+                        + is_memory_instruction_next_expr;
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1382,11 +2035,15 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
 
                     let is_instruction_next_expr = is_numeric_instruction_next_expr
                         + is_variable_instruction_next_expr
                         + is_control_instruction_next_expr
-                        + is_parametric_instruction_next_expr;
+                        + is_parametric_instruction_next_expr
+                        + is_prefixed_instruction_next_expr
+                        + is_memory_instruction_next_expr;
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1403,6 +2060,166 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 }
             );
 
+            // is_prefixed_instruction_sub_opcode && is_prefixed_opcode_without_params -> is_instruction || is_block_end
+            cb.condition(
+                and::expr([
+                    is_prefixed_instruction_sub_opcode_expr.clone(),
+                    is_prefixed_opcode_without_params_expr.clone(),
+                    leb128_is_last_byte_expr.clone(),
+                ]),
+                |cb| {
+                    let is_numeric_instruction_next_expr = vc.query_fixed(is_numeric_instruction, Rotation::next());
+                    let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
+                    let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
+                    let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
+
+                    let is_instruction_next_expr = is_numeric_instruction_next_expr
+                        + is_variable_instruction_next_expr
+                        + is_control_instruction_next_expr
+                        + is_parametric_instruction_next_expr
+                        + is_prefixed_instruction_next_expr
+                        + is_memory_instruction_next_expr;
+
+                    let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
+
+                    cb.require_equal(
+                        "check next: is_prefixed_instruction_sub_opcode && is_prefixed_opcode_without_params -> is_instruction || is_block_end",
+                        is_instruction_next_expr
+
+                            + is_block_end_next_expr
+                        ,
+                        1.expr(),
+                    );
+                }
+            );
+
+            // is_memory_instruction_leb_arg -> is_memory_instruction_leb_arg || is_instruction || is_block_end
+            cb.condition(
+                and::expr([
+                    is_memory_instruction_leb_arg_expr.clone(),
+                    leb128_is_last_byte_expr.clone(),
+                ]),
+                |cb| {
+                    let is_memory_instruction_leb_arg_next_expr = vc.query_fixed(is_memory_instruction_leb_arg, Rotation::next());
+
+                    let is_numeric_instruction_next_expr = vc.query_fixed(is_numeric_instruction, Rotation::next());
+                    let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
+                    let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
+                    let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
+
+                    let is_instruction_next_expr = is_numeric_instruction_next_expr
+                        + is_variable_instruction_next_expr
+                        + is_control_instruction_next_expr
+                        + is_parametric_instruction_next_expr
+                        + is_prefixed_instruction_next_expr
+                        + is_memory_instruction_next_expr;
+
+                    let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
+
+                    cb.require_equal(
+                        "check next: is_memory_instruction_leb_arg -> is_memory_instruction_leb_arg || is_instruction || is_block_end",
+                        is_memory_instruction_leb_arg_next_expr
+
+                            + is_instruction_next_expr
+
+                            + is_block_end_next_expr
+                        ,
+                        1.expr(),
+                    );
+                }
+            );
+
+            // is_prefixed_instruction_leb_arg -> is_instruction || is_block_end
+            cb.condition(
+                and::expr([
+                    is_prefixed_instruction_leb_arg_expr.clone(),
+                    leb128_is_last_byte_expr.clone(),
+                ]),
+                |cb| {
+                    let is_prefixed_instruction_leb_arg_next_expr = vc.query_fixed(is_prefixed_instruction_leb_arg, Rotation::next());
+
+                    let is_numeric_instruction_next_expr = vc.query_fixed(is_numeric_instruction, Rotation::next());
+                    let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
+                    let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
+                    let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
+
+                    let is_instruction_next_expr = is_numeric_instruction_next_expr
+                        + is_variable_instruction_next_expr
+                        + is_control_instruction_next_expr
+                        + is_parametric_instruction_next_expr
+                        + is_prefixed_instruction_next_expr
+                        + is_memory_instruction_next_expr;
+
+                    let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
+
+                    cb.require_equal(
+                        "check next: is_prefixed_instruction_leb_arg -> is_instruction || is_block_end",
+                        is_prefixed_instruction_leb_arg_next_expr
+
+                            + is_instruction_next_expr
+
+                            + is_block_end_next_expr
+                        ,
+                        1.expr(),
+                    );
+                }
+            );
+
+            // is_blocktype_delimiter{1} || is_blocktype_valtype{1} || is_blocktype_type_index+ -> is_instruction || is_block_end
+            cb.condition(
+                or::expr([
+                    is_blocktype_delimiter_expr.clone(),
+                    is_blocktype_valtype_expr.clone(),
+                    and::expr([
+                        is_blocktype_type_index_expr.clone(),
+                        leb128_is_last_byte_expr.clone(),
+                    ]),
+                ]),
+                |cb| {
+                    let is_numeric_instruction_next_expr = vc.query_fixed(is_numeric_instruction, Rotation::next());
+                    let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
+                    let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
+                    let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
+
+                    let is_instruction_next_expr = is_numeric_instruction_next_expr
+                        + is_variable_instruction_next_expr
+                        + is_control_instruction_next_expr
+                        + is_parametric_instruction_next_expr
+                        + is_prefixed_instruction_next_expr
+                        + is_memory_instruction_next_expr;
+
+                    let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
+
+                    cb.require_equal(
+                        "check next: blocktype -> is_instruction || is_block_end",
+                        is_instruction_next_expr
+
+                            + is_block_end_next_expr
+                        ,
+                        1.expr(),
+                    );
+                }
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "is_blocktype_type_index && !last_byte -> is_blocktype_type_index",
+                and::expr([
+                    is_blocktype_type_index_expr.clone(),
+                    not::expr(leb128_is_last_byte_expr.clone()),
+                ]),
+                true,
+                &[is_blocktype_type_index],
+            );
+
             // is_block_end && !not_q_last -> is_instruction || is_block_end
             cb.condition(
                 and::expr([
@@ -1416,11 +2233,15 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     let is_variable_instruction_next_expr = vc.query_fixed(is_variable_instruction, Rotation::next());
                     let is_control_instruction_next_expr = vc.query_fixed(is_control_instruction, Rotation::next());
                     let is_parametric_instruction_next_expr = vc.query_fixed(is_parametric_instruction, Rotation::next());
+                    let is_prefixed_instruction_next_expr = vc.query_fixed(is_prefixed_instruction, Rotation::next());
+                    let is_memory_instruction_next_expr = vc.query_fixed(is_memory_instruction, Rotation::next());
 
                     let is_instruction_next_expr = is_numeric_instruction_next_expr
                         + is_variable_instruction_next_expr
                         + is_control_instruction_next_expr
-                        + is_parametric_instruction_next_expr;
+                        + is_parametric_instruction_next_expr
+                        + is_prefixed_instruction_next_expr
+                        + is_memory_instruction_next_expr;
 
                     let is_block_end_next_expr = vc.query_fixed(is_block_end, Rotation::next());
 
@@ -1428,34 +2249,1185 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                         "check next: is_block_end && !not_q_last -> is_instruction || is_block_end",
                         is_func_body_len_next_expr
 
-                            + is_instruction_next_expr
+                            + is_instruction_next_expr
+
+                            + is_block_end_next_expr
+                        ,
+                        1.expr(),
+                    );
+                }
+            );
+
+            cb.condition(
+                and::expr([
+                    q_enable_expr.clone(),
+                    not_q_first_expr,
+                    or::expr([
+                        is_br_prev_expr,
+                        is_br_if_prev_expr,
+                    ])
+                ]),
+                |cb| {
+                    cb.require_zero(
+                        "br/br_if arg is valid",
+                        block_level_lt_chip.config().is_lt(vc, None).expr() - 1.expr(),
+                    );
+                }
+            );
+
+            // locals count: the repetition counts of the local declarations add up
+            let declared_locals_prev_expr = vc.query_advice(declared_locals, Rotation::prev());
+            let declared_locals_expr = vc.query_advice(declared_locals, Rotation::cur());
+            cb.condition(
+                is_func_body_len_expr.clone(),
+                |cb| {
+                    cb.require_zero(
+                        "is_func_body_len => declared_locals=0",
+                        declared_locals_expr.clone(),
+                    );
+                }
+            );
+            cb.condition(
+                is_local_type_transitions_count_expr.clone() + is_local_type_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_local_type_transitions_count || is_local_type => declared_locals=prev.declared_locals",
+                        declared_locals_expr.clone(),
+                        declared_locals_prev_expr.clone(),
+                    );
+                }
+            );
+            cb.condition(
+                is_local_repetition_count_expr.clone(),
+                |cb| {
+                    let leb128_is_first_byte_expr = vc.query_fixed(leb128_chip.config.is_first_byte, Rotation::cur());
+                    let leb128_sn_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
+                    cb.require_equal(
+                        "is_local_repetition_count => declared_locals=prev.declared_locals+repetition count",
+                        declared_locals_expr.clone(),
+                        declared_locals_prev_expr.clone() + leb128_is_first_byte_expr * leb128_sn_expr,
+                    );
+                }
+            );
+
+            // module view: params and locals count of the function, the entry function is also
+            // indexed by zero through `arg`
+            let is_param_count_fact_expr = is_param_count_fact_expr(vc);
+            let is_local_count_fact_expr = is_local_count_fact_expr(vc);
+            let func_index_expr = vc.query_advice(func_count, Rotation::cur())
+                - 1.expr()
+                - vc.query_advice(body_item_rev_count, Rotation::cur());
+            let is_entry_func_expr = vc.query_advice(is_entry_func, Rotation::cur());
+            for (name, cond_expr, tag) in [
+                ("params count fact => module_tag=ParamCount", is_param_count_fact_expr, WasmModuleTag::ParamCount),
+                ("locals count fact => module_tag=LocalCount", is_local_count_fact_expr, WasmModuleTag::LocalCount),
+            ] {
+                cb.condition(
+                    cond_expr,
+                    |cb| {
+                        cb.require_equal(
+                            name,
+                            vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                            tag.expr(),
+                        );
+                        cb.require_equal(
+                            "function count fact => module_index=func_index+1",
+                            vc.query_advice(wb_table.module_index, Rotation::cur()),
+                            func_index_expr.clone() + 1.expr(),
+                        );
+                        cb.require_boolean(
+                            "function count fact => is_entry_func is boolean",
+                            is_entry_func_expr.clone(),
+                        );
+                        cb.require_equal(
+                            "function count fact => module_arg=0 for the entry function, func_index+1 otherwise",
+                            vc.query_advice(wb_table.module_arg, Rotation::cur()),
+                            not::expr(is_entry_func_expr.clone()) * (func_index_expr.clone() + 1.expr()),
+                        );
+                    }
+                );
+            }
+
+            cb.gate(q_enable_expr.clone())
+        });
+
+        module_types_chip.lookup_args(
+            "params count of the function is the params count of its type",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let func_index_expr = vc.query_advice(func_count, Rotation::cur())
+                    - 1.expr()
+                    - vc.query_advice(body_item_rev_count, Rotation::cur());
+
+                LookupArgsParams {
+                    cond: q_enable_expr * is_param_count_fact_expr(vc),
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsFunc, 1.expr()),
+                        (ModuleTypesAssignType::Index, func_index_expr),
+                        (ModuleTypesAssignType::ParamsCount, vc.query_advice(wb_table.module_value, Rotation::cur())),
+                    ],
+                }
+            },
+        );
+
+        module_types_chip.lookup_args(
+            "locals count of the function is its params count and the declared locals",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let func_index_expr = vc.query_advice(func_count, Rotation::cur())
+                    - 1.expr()
+                    - vc.query_advice(body_item_rev_count, Rotation::cur());
+
+                LookupArgsParams {
+                    cond: q_enable_expr * is_local_count_fact_expr(vc),
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsFunc, 1.expr()),
+                        (ModuleTypesAssignType::Index, func_index_expr),
+                        (
+                            ModuleTypesAssignType::ParamsCount,
+                            vc.query_advice(wb_table.module_value, Rotation::cur())
+                                - vc.query_advice(declared_locals, Rotation::cur()),
+                        ),
+                    ],
+                }
+            },
+        );
+
+        cs.lookup_any("entry function is the function exported as main", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let cond_expr = q_enable_expr
+                * (is_param_count_fact_expr(vc) + is_local_count_fact_expr(vc))
+                * vc.query_advice(is_entry_func, Rotation::cur());
+            let func_index_expr = vc.query_advice(func_count, Rotation::cur())
+                - 1.expr()
+                - vc.query_advice(body_item_rev_count, Rotation::cur());
+
+            vec![
+                (
+                    cond_expr.clone() * vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                    vc.query_advice(wb_table.code_hash, Rotation::cur()),
+                ),
+                (
+                    cond_expr.clone() * WasmModuleTag::EntryFunction.expr(),
+                    vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                ),
+                (0.expr(), vc.query_advice(wb_table.module_index, Rotation::cur())),
+                (
+                    cond_expr * func_index_expr,
+                    vc.query_advice(wb_table.module_value, Rotation::cur()),
+                ),
+            ]
+        });
+
+        // module view: every instruction is tagged with its pc and opcode, the immediate it is
+        // looked up with is carried from the row it starts at to the opcode
+        cs.create_gate("WasmCodeSectionBody instruction view gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let is_funcs_count_expr = vc.query_fixed(is_funcs_count, Rotation::cur());
+            let is_instruction_prev_expr = instruction_flags
+                .iter()
+                .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, Rotation::prev()));
+            let is_instruction_expr = instruction_flags
+                .iter()
+                .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, Rotation::cur()));
+            let is_arg_carry_expr = |vc: &mut VirtualCells<F>, rotation: Rotation| {
+                [
+                    is_variable_instruction_leb_arg,
+                    is_memory_instruction_leb_arg,
+                    is_control_instruction_leb_arg,
+                    is_prefixed_instruction_sub_opcode,
+                    is_prefixed_instruction_leb_arg,
+                ]
+                .iter()
+                .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, rotation))
+            };
+            let is_arg_carry_next_expr = is_arg_carry_expr(vc, Rotation::next());
+            let is_arg_carry_expr = is_arg_carry_expr(vc, Rotation::cur());
+            let is_prefixed_instruction_expr = vc.query_fixed(is_prefixed_instruction, Rotation::cur());
+            let is_prefixed_instruction_sub_opcode_expr = vc.query_fixed(is_prefixed_instruction_sub_opcode, Rotation::cur());
+            let is_memory_instruction_prev_expr = vc.query_fixed(is_memory_instruction, Rotation::prev());
+            let is_memory_size_or_grow_prev_expr = memory_instruction_chip.config.value_equals(MemoryInstruction::MemorySize, Rotation::prev())(vc)
+                + memory_instruction_chip.config.value_equals(MemoryInstruction::MemoryGrow, Rotation::prev())(vc);
+            let leb128_is_first_byte_expr = vc.query_fixed(leb128_chip.config.is_first_byte, Rotation::cur());
+            let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
+            let leb128_sn_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
+            let pc_expr = vc.query_advice(pc, Rotation::cur());
+            let module_arg_expr = vc.query_advice(wb_table.module_arg, Rotation::cur());
+            let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+            let is_block_end_expr = vc.query_fixed(is_block_end, Rotation::cur());
+            let is_func_end_expr = is_block_end_expr.clone()
+                * (vc.query_fixed(q_last, Rotation::cur()) + vc.query_fixed(is_func_body_len, Rotation::next()));
+            let is_block_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Block, Rotation::cur())(vc);
+            let is_loop_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Loop, Rotation::cur())(vc);
+            let is_if_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::If, Rotation::cur())(vc);
+            let is_else_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Else, Rotation::cur())(vc);
+            let is_br_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Br, Rotation::cur())(vc);
+            let is_br_if_expr = is_control_instruction_expr * control_instruction_chip.config.value_equals(ControlInstruction::BrIf, Rotation::cur())(vc);
+            let is_opener_expr = is_block_expr.clone() + is_loop_expr.clone() + is_if_expr.clone();
+            let has_else_expr = vc.query_advice(has_else, Rotation::cur());
+            let module_tag_next_expr = vc.query_fixed(wb_table.module_tag, Rotation::next());
+            let module_index_next_expr = vc.query_advice(wb_table.module_index, Rotation::next());
+            let module_value_next_expr = vc.query_advice(wb_table.module_value, Rotation::next());
+
+            cb.condition(
+                is_funcs_count_expr.clone(),
+                |cb| {
+                    cb.require_zero("is_funcs_count => pc=0", pc_expr.clone());
+                }
+            );
+            cb.condition(
+                not::expr(is_funcs_count_expr),
+                |cb| {
+                    cb.require_equal(
+                        "pc=prev.pc+1 after an instruction, prev.pc otherwise",
+                        pc_expr.clone(),
+                        vc.query_advice(pc, Rotation::prev()) + is_instruction_prev_expr,
+                    );
+                }
+            );
+
+            cb.condition(
+                is_instruction_expr.clone(),
+                |cb| {
+                    // a prefixed opcode is packed with its sub-opcode like `OpcodeId::as_u64`
+                    let opcode_expr = is_prefixed_instruction_expr.clone()
+                        * (((WASM_INSTRUCTION_PREFIX_FC as u64) << 32).expr() + vc.query_advice(leb128_chip.config.sn, Rotation::next()))
+                        + not::expr(is_prefixed_instruction_expr.clone()) * vc.query_advice(wb_table.value, Rotation::cur());
+                    cb.require_equal(
+                        "instruction => module_tag=Instruction",
+                        vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                        WasmModuleTag::Instruction.expr(),
+                    );
+                    cb.require_equal(
+                        "instruction => module_index=pc",
+                        vc.query_advice(wb_table.module_index, Rotation::cur()),
+                        pc_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "instruction => module_value=opcode",
+                        vc.query_advice(wb_table.module_value, Rotation::cur()),
+                        opcode_expr,
+                    );
+                }
+            );
+            // the args of the control frame instructions are bound by the frame lookups
+            cb.condition(
+                (is_instruction_expr - is_opener_expr.clone() - is_else_expr - is_block_end_expr.clone() + is_func_end_expr.clone())
+                    * not::expr(is_arg_carry_next_expr.clone()),
+                |cb| {
+                    cb.require_zero("instruction without a bound immediate => module_arg=0", module_arg_expr.clone());
+                }
+            );
+            // the blocktype of an opener is tagged with the pcs its frame jumps to
+            cb.condition(
+                is_opener_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "opener => module_arg=params count+results count*2^16",
+                        module_arg_expr.clone(),
+                        vc.query_advice(pushes_count, Rotation::cur())
+                            + vc.query_advice(block_results_count, Rotation::cur()) * 0x10000.expr(),
+                    );
+                    cb.require_equal(
+                        "opener => next.module_tag=Block",
+                        module_tag_next_expr.clone(),
+                        WasmModuleTag::Block.expr(),
+                    );
+                    cb.require_equal(
+                        "opener => next.module_index=pc",
+                        module_index_next_expr.clone(),
+                        pc_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "opener => next.module_value=frame_end_pc",
+                        module_value_next_expr.clone(),
+                        vc.query_advice(frame_end_pc, Rotation::cur()),
+                    );
+                    cb.require_equal(
+                        "opener => next.module_arg=else_pc",
+                        vc.query_advice(wb_table.module_arg, Rotation::next()),
+                        vc.query_advice(else_pc, Rotation::cur()),
+                    );
+                }
+            );
+            cb.condition(
+                is_opener_expr - is_if_expr * has_else_expr,
+                |cb| {
+                    cb.require_zero("opener without an else => else_pc=0", vc.query_advice(else_pc, Rotation::cur()));
+                }
+            );
+            // the label of a branch is tagged with the pc of the opener of its frame
+            cb.condition(
+                is_br_expr + is_br_if_expr,
+                |cb| {
+                    cb.require_equal(
+                        "br/br_if => next.module_tag=Label",
+                        module_tag_next_expr,
+                        WasmModuleTag::Label.expr(),
+                    );
+                    cb.require_equal(
+                        "br/br_if => next.module_index=pc",
+                        module_index_next_expr,
+                        pc_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "br/br_if => next.module_value=label_frame_pc+1, 0 for the function label",
+                        module_value_next_expr,
+                        not::expr(is_func_label_chip.config().expr())
+                            * (vc.query_advice(label_frame_pc, Rotation::cur()) + 1.expr()),
+                    );
+                }
+            );
+            cb.condition(
+                is_prefixed_instruction_sub_opcode_expr.clone()
+                    * leb128_is_last_byte_expr
+                    * not::expr(vc.query_fixed(is_prefixed_instruction_leb_arg, Rotation::next())),
+                |cb| {
+                    cb.require_zero("prefixed instruction without args => module_arg=0", module_arg_expr.clone());
+                }
+            );
+            cb.condition(
+                is_arg_carry_expr,
+                |cb| {
+                    cb.require_equal(
+                        "instruction arg => module_arg=prev.module_arg",
+                        module_arg_expr.clone(),
+                        vc.query_advice(wb_table.module_arg, Rotation::prev()),
+                    );
+                }
+            );
+            // the first immediate of a variable, control or prefixed instruction and of
+            // `memory.size`/`memory.grow`, the offset of a memarg
+            let is_arg_start_expr = leb128_is_first_byte_expr
+                * (vc.query_fixed(is_variable_instruction_leb_arg, Rotation::cur()) * vc.query_fixed(is_variable_instruction, Rotation::prev())
+                    + vc.query_fixed(is_control_instruction_leb_arg, Rotation::cur()) * vc.query_fixed(is_control_instruction, Rotation::prev())
+                    + vc.query_fixed(is_memory_instruction_leb_arg, Rotation::cur())
+                        * (vc.query_fixed(is_memory_instruction_leb_arg, Rotation::prev())
+                            + is_memory_instruction_prev_expr * is_memory_size_or_grow_prev_expr)
+                    + vc.query_fixed(is_prefixed_instruction_leb_arg, Rotation::cur()) * vc.query_fixed(is_prefixed_instruction_sub_opcode, Rotation::prev()));
+            cb.condition(
+                is_arg_start_expr,
+                |cb| {
+                    cb.require_equal(
+                        "first immediate => module_arg=immediate",
+                        module_arg_expr.clone(),
+                        leb128_sn_expr.clone(),
+                    );
+                }
+            );
+
+            cb.gate(q_enable_expr)
+        });
+
+        cs.create_gate("WasmCodeSectionBody type stack gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
+            let is_func_body_len_expr = vc.query_fixed(is_func_body_len, Rotation::cur());
+            let is_func_body_len_next_expr = vc.query_fixed(is_func_body_len, Rotation::next());
+            let is_variable_instruction_expr = vc.query_fixed(is_variable_instruction, Rotation::cur());
+            let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+            let is_parametric_instruction_expr = vc.query_fixed(is_parametric_instruction, Rotation::cur());
+            let is_block_end_expr = vc.query_fixed(is_block_end, Rotation::cur());
+            let is_instruction_expr = instruction_flags
+                .iter()
+                .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, Rotation::cur()));
+            let is_state_carried_expr = [
+                is_local_type_transitions_count,
+                is_local_repetition_count,
+                is_local_type,
+                is_numeric_instruction_leb_arg,
+                is_variable_instruction_leb_arg,
+                is_memory_instruction_leb_arg,
+                is_control_instruction_leb_arg,
+                is_prefixed_instruction_sub_opcode,
+                is_prefixed_instruction_leb_arg,
+                is_blocktype_delimiter,
+                is_blocktype_valtype,
+                is_blocktype_type_index,
+            ]
+            .iter()
+            .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, Rotation::cur()));
+
+            let is_local_get_expr = is_variable_instruction_expr.clone() * variable_instruction_chip.config.value_equals(VariableInstruction::LocalGet, Rotation::cur())(vc);
+            let is_local_set_expr = is_variable_instruction_expr.clone() * variable_instruction_chip.config.value_equals(VariableInstruction::LocalSet, Rotation::cur())(vc);
+            let is_local_tee_expr = is_variable_instruction_expr.clone() * variable_instruction_chip.config.value_equals(VariableInstruction::LocalTee, Rotation::cur())(vc);
+            let is_global_get_expr = is_variable_instruction_expr.clone() * variable_instruction_chip.config.value_equals(VariableInstruction::GlobalGet, Rotation::cur())(vc);
+            let is_global_set_expr = is_variable_instruction_expr.clone() * variable_instruction_chip.config.value_equals(VariableInstruction::GlobalSet, Rotation::cur())(vc);
+            let is_drop_expr = is_parametric_instruction_expr.clone() * parametric_instruction_chip.config.value_equals(ParametricInstruction::Drop, Rotation::cur())(vc);
+            let is_select_expr = is_parametric_instruction_expr.clone() * parametric_instruction_chip.config.value_equals(ParametricInstruction::Select, Rotation::cur())(vc);
+            let is_unreachable_instruction_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Unreachable, Rotation::cur())(vc);
+            let is_block_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Block, Rotation::cur())(vc);
+            let is_loop_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Loop, Rotation::cur())(vc);
+            let is_if_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::If, Rotation::cur())(vc);
+            let is_else_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Else, Rotation::cur())(vc);
+            let is_br_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::Br, Rotation::cur())(vc);
+            let is_br_if_expr = is_control_instruction_expr.clone() * control_instruction_chip.config.value_equals(ControlInstruction::BrIf, Rotation::cur())(vc);
+            let is_opener_expr = is_block_expr.clone() + is_loop_expr.clone() + is_if_expr.clone();
+            let is_func_label_expr = is_func_label_chip.config().expr();
+            let is_blocktype_delimiter_next_expr = vc.query_fixed(is_blocktype_delimiter, Rotation::next());
+            let is_blocktype_valtype_next_expr = vc.query_fixed(is_blocktype_valtype, Rotation::next());
+            let byte_val_next_expr = vc.query_advice(wb_table.value, Rotation::next());
+            let is_func_end_expr = is_block_end_expr.clone() * (q_last_expr + is_func_body_len_next_expr);
+            let is_inner_end_expr = is_block_end_expr.clone() - is_func_end_expr.clone();
+
+            let block_opcode_number_expr = vc.query_advice(block_opcode_number, Rotation::cur());
+            let type_stack_expr = vc.query_advice(type_stack, Rotation::cur());
+            let type_stack_prev_expr = vc.query_advice(type_stack, Rotation::prev());
+            let type_stack_len_expr = vc.query_advice(type_stack_len, Rotation::cur());
+            let type_stack_len_prev_expr = vc.query_advice(type_stack_len, Rotation::prev());
+            let type_stack_shift_expr = vc.query_advice(type_stack_shift, Rotation::cur());
+            let type_stack_shift_prev_expr = vc.query_advice(type_stack_shift, Rotation::prev());
+            let frame_id_expr = vc.query_advice(frame_id, Rotation::cur());
+            let frame_id_prev_expr = vc.query_advice(frame_id, Rotation::prev());
+            let frame_base_expr = vc.query_advice(frame_base, Rotation::cur());
+            let frame_base_prev_expr = vc.query_advice(frame_base, Rotation::prev());
+            let is_unreachable_expr = vc.query_advice(is_unreachable, Rotation::cur());
+            let is_unreachable_prev_expr = vc.query_advice(is_unreachable, Rotation::prev());
+            let pops_expr = vc.query_advice(pops, Rotation::cur());
+            let pops_count_expr = vc.query_advice(pops_count, Rotation::cur());
+            let pops_shift_expr = vc.query_advice(pops_shift, Rotation::cur());
+            let pushes_expr = vc.query_advice(pushes, Rotation::cur());
+            let pushes_count_expr = vc.query_advice(pushes_count, Rotation::cur());
+            let pushes_shift_expr = vc.query_advice(pushes_shift, Rotation::cur());
+            let rest_expr = vc.query_advice(rest, Rotation::cur());
+            let rest_shift_expr = vc.query_advice(rest_shift, Rotation::cur());
+            let operand_type_expr = vc.query_advice(operand_type, Rotation::cur());
+            let rest_is_lt_expr = rest_lt_chip.config().is_lt(vc, None);
+            let block_results_expr = vc.query_advice(block_results, Rotation::cur());
+            let block_results_count_expr = vc.query_advice(block_results_count, Rotation::cur());
+            let block_results_shift_expr = vc.query_advice(block_results_shift, Rotation::cur());
+            let has_else_expr = vc.query_advice(has_else, Rotation::cur());
+
+            let is_checked_expr = is_instruction_expr.clone() * not::expr(is_unreachable_prev_expr.clone());
+            let is_applied_expr = is_checked_expr.clone() * (1.expr() - is_opener_expr.clone() - is_else_expr.clone() - is_block_end_expr.clone());
+
+            cb.require_boolean("is_unreachable is boolean", is_unreachable_expr.clone());
+
+            cb.condition(
+                is_func_body_len_expr.clone(),
+                |cb| {
+                    cb.require_zero("is_func_body_len => type_stack=0", type_stack_expr.clone());
+                    cb.require_zero("is_func_body_len => type_stack_len=0", type_stack_len_expr.clone());
+                    cb.require_equal("is_func_body_len => type_stack_shift=1", type_stack_shift_expr.clone(), 1.expr());
+                    cb.require_zero("is_func_body_len => frame_id=0", frame_id_expr.clone());
+                    cb.require_zero("is_func_body_len => frame_base=0", frame_base_expr.clone());
+                    cb.require_zero("is_func_body_len => is_unreachable=0", is_unreachable_expr.clone());
+                }
+            );
+
+            cb.condition(
+                is_state_carried_expr.clone(),
+                |cb| {
+                    cb.require_equal("is_state_carried => type_stack=prev.type_stack", type_stack_expr.clone(), type_stack_prev_expr.clone());
+                    cb.require_equal("is_state_carried => type_stack_len=prev.type_stack_len", type_stack_len_expr.clone(), type_stack_len_prev_expr.clone());
+                    cb.require_equal("is_state_carried => type_stack_shift=prev.type_stack_shift", type_stack_shift_expr.clone(), type_stack_shift_prev_expr.clone());
+                    cb.require_equal("is_state_carried => frame_id=prev.frame_id", frame_id_expr.clone(), frame_id_prev_expr.clone());
+                    cb.require_equal("is_state_carried => frame_base=prev.frame_base", frame_base_expr.clone(), frame_base_prev_expr.clone());
+                    cb.require_equal("is_state_carried => is_unreachable=prev.is_unreachable", is_unreachable_expr.clone(), is_unreachable_prev_expr.clone());
+                }
+            );
+
+            cb.condition(
+                is_instruction_expr.clone(),
+                |cb| {
+                    cb.require_boolean("is_instruction => operand_type is boolean", operand_type_expr.clone());
+                }
+            );
+
+            // effects of the instructions generic over their operand type, the rest come from lookups
+            let no_operands = || [0.expr(), 0.expr(), 1.expr()];
+            let one_operand = [operand_type_expr.clone(), 1.expr(), 2.expr()];
+            for (cond_expr, pops_values, pushes_values) in [
+                (is_local_get_expr.clone() + is_global_get_expr.clone(), no_operands(), one_operand.clone()),
+                (is_local_set_expr.clone() + is_global_set_expr.clone() + is_drop_expr.clone(), one_operand.clone(), no_operands()),
+                (is_local_tee_expr.clone(), one_operand.clone(), one_operand.clone()),
+                (is_select_expr.clone(), [6.expr() * operand_type_expr.clone(), 3.expr(), 8.expr()], one_operand.clone()),
+                (is_unreachable_instruction_expr.clone(), no_operands(), no_operands()),
+            ] {
+                cb.condition(
+                    cond_expr,
+                    |cb| {
+                        let [pops_value, pops_count_value, pops_shift_value] = pops_values;
+                        let [pushes_value, pushes_count_value, pushes_shift_value] = pushes_values;
+                        cb.require_equal("generic effect => pops", pops_expr.clone(), pops_value);
+                        cb.require_equal("generic effect => pops_count", pops_count_expr.clone(), pops_count_value);
+                        cb.require_equal("generic effect => pops_shift", pops_shift_expr.clone(), pops_shift_value);
+                        cb.require_equal("generic effect => pushes", pushes_expr.clone(), pushes_value);
+                        cb.require_equal("generic effect => pushes_count", pushes_count_expr.clone(), pushes_count_value);
+                        cb.require_equal("generic effect => pushes_shift", pushes_shift_expr.clone(), pushes_shift_value);
+                    }
+                );
+            }
+
+            cb.condition(
+                is_else_expr.clone() + is_block_end_expr.clone(),
+                |cb| {
+                    cb.require_zero("else/end => pushes=0", pushes_expr.clone());
+                    cb.require_zero("else/end => pushes_count=0", pushes_count_expr.clone());
+                    cb.require_equal("else/end => pushes_shift=1", pushes_shift_expr.clone(), 1.expr());
+                }
+            );
+            cb.condition(
+                is_func_end_expr.clone(),
+                |cb| {
+                    cb.require_zero("function end => pops=0", pops_expr.clone());
+                    cb.require_zero("function end => pops_count=0", pops_count_expr.clone());
+                    cb.require_equal("function end => pops_shift=1", pops_shift_expr.clone(), 1.expr());
+                }
+            );
+            cb.condition(
+                is_br_expr.clone(),
+                |cb| {
+                    cb.require_zero("br => pushes=0", pushes_expr.clone());
+                    cb.require_zero("br => pushes_count=0", pushes_count_expr.clone());
+                    cb.require_equal("br => pushes_shift=1", pushes_shift_expr.clone(), 1.expr());
+                }
+            );
+            cb.condition(
+                is_br_if_expr.clone(),
+                |cb| {
+                    cb.require_equal("br_if => pops=label types and i32", pops_expr.clone(), 2.expr() * pushes_expr.clone());
+                    cb.require_equal("br_if => pops_count=pushes_count+1", pops_count_expr.clone(), pushes_count_expr.clone() + 1.expr());
+                    cb.require_equal("br_if => pops_shift=2*pushes_shift", pops_shift_expr.clone(), 2.expr() * pushes_shift_expr.clone());
+                }
+            );
+            cb.condition(
+                (is_br_expr.clone() + is_br_if_expr.clone()) * not::expr(is_func_label_expr.clone()),
+                |cb| {
+                    cb.require_equal(
+                        "br/br_if to a block => the label frame opens before the instruction",
+                        label_frame_id_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
+                    );
+                    cb.require_equal(
+                        "br/br_if to a block => the label frame ends after the instruction",
+                        label_frame_end_lt_chip.config().is_lt(vc, None),
+                        1.expr(),
+                    );
+                }
+            );
+
+            // a blocktype is the empty type, a single result or a function type from the lookup
+            cb.condition(
+                is_opener_expr.clone() * is_blocktype_delimiter_next_expr.clone(),
+                |cb| {
+                    cb.require_zero("empty blocktype => pushes=0", pushes_expr.clone());
+                    cb.require_zero("empty blocktype => pushes_count=0", pushes_count_expr.clone());
+                    cb.require_equal("empty blocktype => pushes_shift=1", pushes_shift_expr.clone(), 1.expr());
+                    cb.require_zero("empty blocktype => block_results=0", block_results_expr.clone());
+                    cb.require_zero("empty blocktype => block_results_count=0", block_results_count_expr.clone());
+                    cb.require_equal("empty blocktype => block_results_shift=1", block_results_shift_expr.clone(), 1.expr());
+                }
+            );
+            cb.condition(
+                is_opener_expr.clone() * is_blocktype_valtype_next_expr.clone(),
+                |cb| {
+                    cb.require_zero("valtype blocktype => pushes=0", pushes_expr.clone());
+                    cb.require_zero("valtype blocktype => pushes_count=0", pushes_count_expr.clone());
+                    cb.require_equal("valtype blocktype => pushes_shift=1", pushes_shift_expr.clone(), 1.expr());
+                    cb.require_equal(
+                        "valtype blocktype => block_results=type bit of the valtype",
+                        block_results_expr.clone(),
+                        (NumType::I32 as u64).expr() - byte_val_next_expr.clone(),
+                    );
+                    cb.require_equal("valtype blocktype => block_results_count=1", block_results_count_expr.clone(), 1.expr());
+                    cb.require_equal("valtype blocktype => block_results_shift=2", block_results_shift_expr.clone(), 2.expr());
+                }
+            );
+            // the params are popped and pushed back as the operands of the new frame
+            cb.condition(
+                is_block_expr.clone() + is_loop_expr.clone(),
+                |cb| {
+                    cb.require_equal("block/loop => pops=pushes", pops_expr.clone(), pushes_expr.clone());
+                    cb.require_equal("block/loop => pops_count=pushes_count", pops_count_expr.clone(), pushes_count_expr.clone());
+                    cb.require_equal("block/loop => pops_shift=pushes_shift", pops_shift_expr.clone(), pushes_shift_expr.clone());
+                }
+            );
+            cb.condition(
+                is_if_expr.clone(),
+                |cb| {
+                    cb.require_equal("if => pops=params and i32", pops_expr.clone(), 2.expr() * pushes_expr.clone());
+                    cb.require_equal("if => pops_count=pushes_count+1", pops_count_expr.clone(), pushes_count_expr.clone() + 1.expr());
+                    cb.require_equal("if => pops_shift=2*pushes_shift", pops_shift_expr.clone(), 2.expr() * pushes_shift_expr.clone());
+                    cb.require_boolean("if => has_else is boolean", has_else_expr.clone());
+                }
+            );
+            // the missing else branch passes the params through
+            cb.condition(
+                is_if_expr.clone() * not::expr(has_else_expr.clone()),
+                |cb| {
+                    cb.require_equal("if without else => block_results=params", block_results_expr.clone(), pushes_expr.clone());
+                    cb.require_equal("if without else => block_results_count=params_count", block_results_count_expr.clone(), pushes_count_expr.clone());
+                    cb.require_equal("if without else => block_results_shift=params_shift", block_results_shift_expr.clone(), pushes_shift_expr.clone());
+                }
+            );
+
+            cb.condition(
+                is_checked_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_checked => prev.type_stack=rest*pops_shift+pops",
+                        type_stack_prev_expr.clone(),
+                        rest_expr.clone() * pops_shift_expr.clone() + pops_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "is_checked => rest_shift*pops_shift=prev.type_stack_shift",
+                        rest_shift_expr.clone() * pops_shift_expr.clone(),
+                        type_stack_shift_prev_expr.clone(),
+                    );
+                    cb.require_equal("is_checked => rest<rest_shift", rest_is_lt_expr.clone(), 1.expr());
+                }
+            );
+            cb.condition(
+                is_applied_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_applied => type_stack=rest*pushes_shift+pushes",
+                        type_stack_expr.clone(),
+                        rest_expr.clone() * pushes_shift_expr.clone() + pushes_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "is_applied => type_stack_len=prev.type_stack_len-pops_count+pushes_count",
+                        type_stack_len_expr.clone(),
+                        type_stack_len_prev_expr.clone() - pops_count_expr.clone() + pushes_count_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "is_applied => type_stack_shift=rest_shift*pushes_shift",
+                        type_stack_shift_expr.clone(),
+                        rest_shift_expr.clone() * pushes_shift_expr.clone(),
+                    );
+                }
+            );
+            cb.condition(
+                is_instruction_expr.clone() * is_unreachable_prev_expr.clone() * (1.expr() - is_opener_expr.clone() - is_else_expr.clone() - is_block_end_expr.clone()),
+                |cb| {
+                    cb.require_equal("unreachable instruction => type_stack=prev.type_stack", type_stack_expr.clone(), type_stack_prev_expr.clone());
+                    cb.require_equal("unreachable instruction => type_stack_len=prev.type_stack_len", type_stack_len_expr.clone(), type_stack_len_prev_expr.clone());
+                    cb.require_equal("unreachable instruction => type_stack_shift=prev.type_stack_shift", type_stack_shift_expr.clone(), type_stack_shift_prev_expr.clone());
+                }
+            );
+
+            cb.condition(
+                is_opener_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "block/loop/if => type_stack=rest*pushes_shift+pushes",
+                        type_stack_expr.clone(),
+                        rest_expr.clone() * pushes_shift_expr.clone() + pushes_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "block/loop/if => type_stack_shift=rest_shift*pushes_shift",
+                        type_stack_shift_expr.clone(),
+                        rest_shift_expr.clone() * pushes_shift_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "block/loop/if => type_stack_len-pushes_count=rest len",
+                        type_stack_len_expr.clone() - pushes_count_expr.clone(),
+                        not::expr(is_unreachable_prev_expr.clone()) * (type_stack_len_prev_expr.clone() - pops_count_expr.clone()),
+                    );
+                    cb.require_equal("block/loop/if => frame_id=block_opcode_number", frame_id_expr.clone(), block_opcode_number_expr.clone());
+                    cb.require_equal("block/loop/if => frame_base=type_stack_len-pushes_count", frame_base_expr.clone(), type_stack_len_expr.clone() - pushes_count_expr.clone());
+                    cb.require_zero("block/loop/if => is_unreachable=0", is_unreachable_expr.clone());
+                }
+            );
+            // an unreachable frame has no known operands, the new frame starts with its params only
+            cb.condition(
+                is_opener_expr.clone() * is_unreachable_prev_expr.clone(),
+                |cb| {
+                    cb.require_zero("unreachable block/loop/if => rest=0", rest_expr.clone());
+                    cb.require_equal("unreachable block/loop/if => rest_shift=1", rest_shift_expr.clone(), 1.expr());
+                }
+            );
+            cb.condition(
+                is_unreachable_instruction_expr.clone() + is_br_expr.clone(),
+                |cb| {
+                    cb.require_equal("unreachable/br => frame_id=prev.frame_id", frame_id_expr.clone(), frame_id_prev_expr.clone());
+                    cb.require_equal("unreachable/br => frame_base=prev.frame_base", frame_base_expr.clone(), frame_base_prev_expr.clone());
+                    cb.require_equal("unreachable/br => is_unreachable=1", is_unreachable_expr.clone(), 1.expr());
+                }
+            );
+            cb.condition(
+                is_instruction_expr.clone()
+                    - is_opener_expr.clone()
+                    - is_unreachable_instruction_expr.clone()
+                    - is_br_expr.clone()
+                    - is_else_expr.clone()
+                    - is_block_end_expr.clone(),
+                |cb| {
+                    cb.require_equal("regular instruction => frame_id=prev.frame_id", frame_id_expr.clone(), frame_id_prev_expr.clone());
+                    cb.require_equal("regular instruction => frame_base=prev.frame_base", frame_base_expr.clone(), frame_base_prev_expr.clone());
+                    cb.require_equal("regular instruction => is_unreachable=prev.is_unreachable", is_unreachable_expr.clone(), is_unreachable_prev_expr.clone());
+                }
+            );
+            cb.condition(
+                is_else_expr.clone(),
+                |cb| {
+                    cb.require_equal("else => frame_id=prev.frame_id", frame_id_expr.clone(), frame_id_prev_expr.clone());
+                    cb.require_equal("else => frame_base=prev.frame_base", frame_base_expr.clone(), frame_base_prev_expr.clone());
+                    cb.require_zero("else => is_unreachable=0", is_unreachable_expr.clone());
+                }
+            );
+            cb.condition(
+                (is_else_expr.clone() + is_inner_end_expr.clone()) * not::expr(is_unreachable_prev_expr.clone()),
+                |cb| {
+                    cb.require_equal(
+                        "reachable else/end => frame is left with exactly its results",
+                        type_stack_len_prev_expr.clone() - pops_count_expr.clone(),
+                        frame_base_prev_expr.clone(),
+                    );
+                }
+            );
+
+            cb.gate(q_enable_expr.clone())
+        });
+
+        cs.lookup("type stack effect of an instruction with a fixed effect", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let is_numeric_instruction_expr = vc.query_fixed(is_numeric_instruction, Rotation::cur());
+            let is_memory_instruction_expr = vc.query_fixed(is_memory_instruction, Rotation::cur());
+            let is_prefixed_instruction_expr = vc.query_fixed(is_prefixed_instruction, Rotation::cur());
+            let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
+            let sub_opcode_expr = vc.query_advice(leb128_chip.config.sn, Rotation::next());
+
+            let cond_expr = q_enable_expr * (is_numeric_instruction_expr + is_memory_instruction_expr + is_prefixed_instruction_expr.clone());
+            let key_expr = is_prefixed_instruction_expr.clone() * (prefixed_instruction_key(0).expr() + sub_opcode_expr)
+                + not::expr(is_prefixed_instruction_expr) * byte_val_expr;
+
+            let t = type_stack_table_config;
+            vec![
+                (cond_expr.clone() * key_expr, t.key),
+                (cond_expr.clone() * vc.query_advice(pops, Rotation::cur()), t.pops),
+                (cond_expr.clone() * vc.query_advice(pops_count, Rotation::cur()), t.pops_count),
+                (cond_expr.clone() * vc.query_advice(pops_shift, Rotation::cur()), t.pops_shift),
+                (cond_expr.clone() * vc.query_advice(pushes, Rotation::cur()), t.pushes),
+                (cond_expr.clone() * vc.query_advice(pushes_count, Rotation::cur()), t.pushes_count),
+                (cond_expr.clone() * vc.query_advice(pushes_shift, Rotation::cur()), t.pushes_shift),
+            ]
+        });
+
+        module_types_chip.lookup_args(
+            "local index is in range and the local has the operand type",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let is_variable_instruction_expr = vc.query_fixed(is_variable_instruction, Rotation::cur());
+                let is_local_get_expr = variable_instruction_chip.config.value_equals(VariableInstruction::LocalGet, Rotation::cur())(vc);
+                let is_local_set_expr = variable_instruction_chip.config.value_equals(VariableInstruction::LocalSet, Rotation::cur())(vc);
+                let is_local_tee_expr = variable_instruction_chip.config.value_equals(VariableInstruction::LocalTee, Rotation::cur())(vc);
+                let func_index_expr = vc.query_advice(func_count, Rotation::cur())
+                    - 1.expr()
+                    - vc.query_advice(body_item_rev_count, Rotation::cur());
+
+                LookupArgsParams {
+                    cond: q_enable_expr * is_variable_instruction_expr * (is_local_get_expr + is_local_set_expr + is_local_tee_expr),
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsLocal, 1.expr()),
+                        (ModuleTypesAssignType::FuncIndex, func_index_expr),
+                        (ModuleTypesAssignType::Index, vc.query_advice(leb128_chip.config.sn, Rotation::next())),
+                        (ModuleTypesAssignType::ValueType, vc.query_advice(operand_type, Rotation::cur())),
+                    ],
+                }
+            },
+        );
+
+        module_types_chip.lookup_args(
+            "global index is in range and the global has the operand type",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let is_variable_instruction_expr = vc.query_fixed(is_variable_instruction, Rotation::cur());
+                let is_global_get_expr = variable_instruction_chip.config.value_equals(VariableInstruction::GlobalGet, Rotation::cur())(vc);
+                let is_global_set_expr = variable_instruction_chip.config.value_equals(VariableInstruction::GlobalSet, Rotation::cur())(vc);
+
+                LookupArgsParams {
+                    cond: q_enable_expr * is_variable_instruction_expr * (is_global_get_expr + is_global_set_expr),
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsGlobal, 1.expr()),
+                        (ModuleTypesAssignType::Index, vc.query_advice(leb128_chip.config.sn, Rotation::next())),
+                        (ModuleTypesAssignType::ValueType, vc.query_advice(operand_type, Rotation::cur())),
+                    ],
+                }
+            },
+        );
+
+        module_types_chip.lookup_args(
+            "type index blocktype takes the params and results of the type",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+                let is_block_expr = control_instruction_chip.config.value_equals(ControlInstruction::Block, Rotation::cur())(vc);
+                let is_loop_expr = control_instruction_chip.config.value_equals(ControlInstruction::Loop, Rotation::cur())(vc);
+                let is_if_expr = control_instruction_chip.config.value_equals(ControlInstruction::If, Rotation::cur())(vc);
+                let is_blocktype_type_index_next_expr = vc.query_fixed(is_blocktype_type_index, Rotation::next());
+
+                LookupArgsParams {
+                    cond: q_enable_expr
+                        * is_control_instruction_expr
+                        * (is_block_expr + is_loop_expr + is_if_expr)
+                        * is_blocktype_type_index_next_expr,
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsType, 1.expr()),
+                        (ModuleTypesAssignType::Index, vc.query_advice(leb128_chip.config.sn, Rotation::next())),
+                        (ModuleTypesAssignType::Params, vc.query_advice(pushes, Rotation::cur())),
+                        (ModuleTypesAssignType::ParamsCount, vc.query_advice(pushes_count, Rotation::cur())),
+                        (ModuleTypesAssignType::ParamsShift, vc.query_advice(pushes_shift, Rotation::cur())),
+                        (ModuleTypesAssignType::Results, vc.query_advice(block_results, Rotation::cur())),
+                        (ModuleTypesAssignType::ResultsCount, vc.query_advice(block_results_count, Rotation::cur())),
+                        (ModuleTypesAssignType::ResultsShift, vc.query_advice(block_results_shift, Rotation::cur())),
+                    ],
+                }
+            },
+        );
+
+        module_types_chip.lookup_args(
+            "call pops the callee params and pushes its results",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+                let is_call_expr = control_instruction_chip.config.value_equals(ControlInstruction::Call, Rotation::cur())(vc);
+
+                LookupArgsParams {
+                    cond: q_enable_expr * is_control_instruction_expr * is_call_expr,
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsFunc, 1.expr()),
+                        (ModuleTypesAssignType::Index, vc.query_advice(leb128_chip.config.sn, Rotation::next())),
+                        (ModuleTypesAssignType::Params, vc.query_advice(pops, Rotation::cur())),
+                        (ModuleTypesAssignType::ParamsCount, vc.query_advice(pops_count, Rotation::cur())),
+                        (ModuleTypesAssignType::ParamsShift, vc.query_advice(pops_shift, Rotation::cur())),
+                        (ModuleTypesAssignType::Results, vc.query_advice(pushes, Rotation::cur())),
+                        (ModuleTypesAssignType::ResultsCount, vc.query_advice(pushes_count, Rotation::cur())),
+                        (ModuleTypesAssignType::ResultsShift, vc.query_advice(pushes_shift, Rotation::cur())),
+                    ],
+                }
+            },
+        );
+
+        module_types_chip.lookup_args(
+            "br to the function label pops the function results",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+                let is_br_expr = control_instruction_chip.config.value_equals(ControlInstruction::Br, Rotation::cur())(vc);
+                let func_index_expr = vc.query_advice(func_count, Rotation::cur())
+                    - 1.expr()
+                    - vc.query_advice(body_item_rev_count, Rotation::cur());
+
+                LookupArgsParams {
+                    cond: q_enable_expr * is_control_instruction_expr * is_br_expr * is_func_label_chip.config().expr(),
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsFunc, 1.expr()),
+                        (ModuleTypesAssignType::Index, func_index_expr),
+                        (ModuleTypesAssignType::Results, vc.query_advice(pops, Rotation::cur())),
+                        (ModuleTypesAssignType::ResultsCount, vc.query_advice(pops_count, Rotation::cur())),
+                        (ModuleTypesAssignType::ResultsShift, vc.query_advice(pops_shift, Rotation::cur())),
+                    ],
+                }
+            },
+        );
+
+        module_types_chip.lookup_args(
+            "br_if to the function label keeps the function results",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+                let is_br_if_expr = control_instruction_chip.config.value_equals(ControlInstruction::BrIf, Rotation::cur())(vc);
+                let func_index_expr = vc.query_advice(func_count, Rotation::cur())
+                    - 1.expr()
+                    - vc.query_advice(body_item_rev_count, Rotation::cur());
+
+                LookupArgsParams {
+                    cond: q_enable_expr * is_control_instruction_expr * is_br_if_expr * is_func_label_chip.config().expr(),
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsFunc, 1.expr()),
+                        (ModuleTypesAssignType::Index, func_index_expr),
+                        (ModuleTypesAssignType::Results, vc.query_advice(pushes, Rotation::cur())),
+                        (ModuleTypesAssignType::ResultsCount, vc.query_advice(pushes_count, Rotation::cur())),
+                        (ModuleTypesAssignType::ResultsShift, vc.query_advice(pushes_shift, Rotation::cur())),
+                    ],
+                }
+            },
+        );
+
+        module_types_chip.lookup_args(
+            "reachable function end leaves exactly the function results",
+            cs,
+            |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+                let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
+                let is_block_end_expr = vc.query_fixed(is_block_end, Rotation::cur());
+                let is_func_body_len_next_expr = vc.query_fixed(is_func_body_len, Rotation::next());
+                let is_unreachable_prev_expr = vc.query_advice(is_unreachable, Rotation::prev());
+                let func_index_expr = vc.query_advice(func_count, Rotation::cur())
+                    - 1.expr()
+                    - vc.query_advice(body_item_rev_count, Rotation::cur());
+
+                LookupArgsParams {
+                    cond: q_enable_expr
+                        * is_block_end_expr
+                        * (q_last_expr + is_func_body_len_next_expr)
+                        * not::expr(is_unreachable_prev_expr),
+                    bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                    args: vec![
+                        (ModuleTypesAssignType::IsFunc, 1.expr()),
+                        (ModuleTypesAssignType::Index, func_index_expr),
+                        (ModuleTypesAssignType::Results, vc.query_advice(type_stack, Rotation::prev())),
+                        (ModuleTypesAssignType::ResultsCount, vc.query_advice(type_stack_len, Rotation::prev())),
+                        (ModuleTypesAssignType::ResultsShift, vc.query_advice(type_stack_shift, Rotation::prev())),
+                    ],
+                }
+            },
+        );
+
+        cs.lookup_any("end of a block restores the state its opener left", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
+            let is_block_end_expr = vc.query_fixed(is_block_end, Rotation::cur());
+            let is_func_body_len_next_expr = vc.query_fixed(is_func_body_len, Rotation::next());
+            let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+            let is_block_expr = control_instruction_chip.config.value_equals(ControlInstruction::Block, Rotation::cur())(vc);
+            let is_loop_expr = control_instruction_chip.config.value_equals(ControlInstruction::Loop, Rotation::cur())(vc);
+            let is_if_expr = control_instruction_chip.config.value_equals(ControlInstruction::If, Rotation::cur())(vc);
+
+            let cond_expr = q_enable_expr * is_block_end_expr.clone() * not::expr(q_last_expr + is_func_body_len_next_expr);
+            let is_opener_expr = is_control_instruction_expr * (is_block_expr + is_loop_expr + is_if_expr);
+
+            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+            let frame_id_expr = vc.query_advice(frame_id, Rotation::cur());
+            let frame_id_prev_expr = vc.query_advice(frame_id, Rotation::prev());
+            let frame_base_expr = vc.query_advice(frame_base, Rotation::cur());
+            let frame_base_prev_expr = vc.query_advice(frame_base, Rotation::prev());
+            let is_unreachable_expr = vc.query_advice(is_unreachable, Rotation::cur());
+            let is_unreachable_prev_expr = vc.query_advice(is_unreachable, Rotation::prev());
+            let type_stack_expr = vc.query_advice(type_stack, Rotation::cur());
+            let type_stack_len_expr = vc.query_advice(type_stack_len, Rotation::cur());
+            let type_stack_shift_expr = vc.query_advice(type_stack_shift, Rotation::cur());
+            let rest_expr = vc.query_advice(rest, Rotation::cur());
+            let rest_shift_expr = vc.query_advice(rest_shift, Rotation::cur());
+            let block_results_expr = vc.query_advice(block_results, Rotation::cur());
+            let block_results_count_expr = vc.query_advice(block_results_count, Rotation::cur());
+            let block_results_shift_expr = vc.query_advice(block_results_shift, Rotation::cur());
+            let block_opcode_number_expr = vc.query_advice(block_opcode_number, Rotation::cur());
+            let pc_expr = vc.query_advice(pc, Rotation::cur());
+            let module_arg_expr = vc.query_advice(wb_table.module_arg, Rotation::cur());
+
+            // the results are pushed onto the operands the opener left under its params, the
+            // end is looked up by the pc of its opener
+            vec![
+                (cond_expr.clone() * bytecode_number_expr.clone(), is_opener_expr.clone() * bytecode_number_expr),
+                (cond_expr.clone() * frame_id_prev_expr.clone(), is_opener_expr.clone() * frame_id_expr.clone()),
+                (cond_expr.clone() * frame_id_expr, is_opener_expr.clone() * frame_id_prev_expr),
+                (cond_expr.clone() * frame_base_expr.clone(), is_opener_expr.clone() * frame_base_prev_expr),
+                (cond_expr.clone() * is_unreachable_expr, is_opener_expr.clone() * is_unreachable_prev_expr),
+                (
+                    cond_expr.clone() * type_stack_expr,
+                    is_opener_expr.clone() * (rest_expr * block_results_shift_expr.clone() + block_results_expr.clone()),
+                ),
+                (
+                    cond_expr.clone() * type_stack_len_expr,
+                    is_opener_expr.clone() * (frame_base_expr + block_results_count_expr.clone()),
+                ),
+                (
+                    cond_expr.clone() * type_stack_shift_expr,
+                    is_opener_expr.clone() * rest_shift_expr * block_results_shift_expr.clone(),
+                ),
+                (cond_expr.clone() * vc.query_advice(pops, Rotation::cur()), is_opener_expr.clone() * block_results_expr),
+                (cond_expr.clone() * vc.query_advice(pops_count, Rotation::cur()), is_opener_expr.clone() * block_results_count_expr),
+                (cond_expr.clone() * vc.query_advice(pops_shift, Rotation::cur()), is_opener_expr.clone() * block_results_shift_expr),
+                (cond_expr.clone() * block_opcode_number_expr, is_opener_expr.clone() * vc.query_advice(frame_end, Rotation::cur())),
+                (cond_expr.clone() * pc_expr.clone(), is_opener_expr.clone() * vc.query_advice(frame_end_pc, Rotation::cur())),
+                (cond_expr.clone() * module_arg_expr, is_opener_expr.clone() * (pc_expr + 1.expr())),
+            ]
+        });
+
+        cs.lookup_any("else restores the state its if left", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+            let is_else_expr = control_instruction_chip.config.value_equals(ControlInstruction::Else, Rotation::cur())(vc);
+            let is_if_expr = control_instruction_chip.config.value_equals(ControlInstruction::If, Rotation::cur())(vc);
+
+            let cond_expr = q_enable_expr * is_control_instruction_expr.clone() * is_else_expr;
+            let is_if_expr = is_control_instruction_expr * is_if_expr;
+
+            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+            let frame_id_expr = vc.query_advice(frame_id, Rotation::cur());
+            let type_stack_expr = vc.query_advice(type_stack, Rotation::cur());
+            let type_stack_len_expr = vc.query_advice(type_stack_len, Rotation::cur());
+            let type_stack_shift_expr = vc.query_advice(type_stack_shift, Rotation::cur());
+            let pc_expr = vc.query_advice(pc, Rotation::cur());
+
+            // the else is looked up by the pc of its if, which knows it has an else
+            vec![
+                (cond_expr.clone() * bytecode_number_expr.clone(), is_if_expr.clone() * bytecode_number_expr),
+                (cond_expr.clone() * frame_id_expr.clone(), is_if_expr.clone() * frame_id_expr),
+                (cond_expr.clone() * vc.query_advice(wb_table.module_arg, Rotation::cur()), is_if_expr.clone() * (pc_expr + 1.expr())),
+                (cond_expr.clone(), is_if_expr.clone() * vc.query_advice(has_else, Rotation::cur())),
+                (cond_expr.clone() * type_stack_expr.clone(), is_if_expr.clone() * type_stack_expr),
+                (cond_expr.clone() * type_stack_len_expr.clone(), is_if_expr.clone() * type_stack_len_expr),
+                (cond_expr.clone() * type_stack_shift_expr.clone(), is_if_expr.clone() * type_stack_shift_expr),
+                (cond_expr.clone() * vc.query_advice(pops, Rotation::cur()), is_if_expr.clone() * vc.query_advice(block_results, Rotation::cur())),
+                (cond_expr.clone() * vc.query_advice(pops_count, Rotation::cur()), is_if_expr.clone() * vc.query_advice(block_results_count, Rotation::cur())),
+                (cond_expr.clone() * vc.query_advice(pops_shift, Rotation::cur()), is_if_expr.clone() * vc.query_advice(block_results_shift, Rotation::cur())),
+            ]
+        });
+
+        cs.lookup_any("if with an else has it in its frame", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+            let is_if_expr = control_instruction_chip.config.value_equals(ControlInstruction::If, Rotation::cur())(vc);
+            let is_else_expr = control_instruction_chip.config.value_equals(ControlInstruction::Else, Rotation::cur())(vc);
+
+            let cond_expr = q_enable_expr * is_control_instruction_expr.clone() * is_if_expr * vc.query_advice(has_else, Rotation::cur());
+            let is_else_expr = is_control_instruction_expr * is_else_expr;
+
+            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+            let frame_id_expr = vc.query_advice(frame_id, Rotation::cur());
+
+            let pc_expr = vc.query_advice(pc, Rotation::cur());
+
+            vec![
+                (cond_expr.clone() * bytecode_number_expr.clone(), is_else_expr.clone() * bytecode_number_expr),
+                (cond_expr.clone() * frame_id_expr.clone(), is_else_expr.clone() * frame_id_expr),
+                (cond_expr.clone() * vc.query_advice(else_pc, Rotation::cur()), is_else_expr.clone() * pc_expr),
+            ]
+        });
+
+        cs.lookup_any("br/br_if to a block takes the operand types of its label", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let is_control_instruction_expr = vc.query_fixed(is_control_instruction, Rotation::cur());
+            let is_br_expr = control_instruction_chip.config.value_equals(ControlInstruction::Br, Rotation::cur())(vc);
+            let is_br_if_expr = control_instruction_chip.config.value_equals(ControlInstruction::BrIf, Rotation::cur())(vc);
+            let is_block_expr = control_instruction_chip.config.value_equals(ControlInstruction::Block, Rotation::cur())(vc);
+            let is_loop_expr = control_instruction_chip.config.value_equals(ControlInstruction::Loop, Rotation::cur())(vc);
+            let is_if_expr = control_instruction_chip.config.value_equals(ControlInstruction::If, Rotation::cur())(vc);
+
+            let cond_expr = q_enable_expr
+                * is_control_instruction_expr.clone()
+                * (is_br_expr.clone() + is_br_if_expr.clone())
+                * not::expr(is_func_label_chip.config().expr());
+            let is_opener_expr = is_control_instruction_expr.clone() * (is_block_expr + is_loop_expr.clone() + is_if_expr);
+            let is_loop_expr = is_control_instruction_expr * is_loop_expr;
+
+            // a branch to a loop re-enters it with its params, to a block or if it leaves with its results
+            let [label_types_expr, label_types_count_expr, label_types_shift_expr] = [
+                (pops, pushes),
+                (pops_count, pushes_count),
+                (pops_shift, pushes_shift),
+            ]
+            .map(|(br_column, br_if_column)| {
+                is_br_expr.clone() * vc.query_advice(br_column, Rotation::cur())
+                    + is_br_if_expr.clone() * vc.query_advice(br_if_column, Rotation::cur())
+            });
+            let [frame_label_types_expr, frame_label_types_count_expr, frame_label_types_shift_expr] = [
+                (pushes, block_results),
+                (pushes_count, block_results_count),
+                (pushes_shift, block_results_shift),
+            ]
+            .map(|(params_column, results_column)| {
+                is_loop_expr.clone() * vc.query_advice(params_column, Rotation::cur())
+                    + not::expr(is_loop_expr.clone()) * vc.query_advice(results_column, Rotation::cur())
+            });
+
+            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+            let block_level_expr = vc.query_advice(block_level, Rotation::cur());
+            let label_expr = vc.query_advice(leb128_chip.config.sn, Rotation::next());
+
+            // levels of the enclosing frames are distinct, so the level picks the labelled one
+            vec![
+                (cond_expr.clone() * bytecode_number_expr.clone(), is_opener_expr.clone() * bytecode_number_expr),
+                (cond_expr.clone() * vc.query_advice(label_frame_id, Rotation::cur()), is_opener_expr.clone() * vc.query_advice(frame_id, Rotation::cur())),
+                (cond_expr.clone() * vc.query_advice(label_frame_end, Rotation::cur()), is_opener_expr.clone() * vc.query_advice(frame_end, Rotation::cur())),
+                (cond_expr.clone() * vc.query_advice(label_frame_pc, Rotation::cur()), is_opener_expr.clone() * vc.query_advice(pc, Rotation::cur())),
+                (cond_expr.clone() * (block_level_expr.clone() - label_expr), is_opener_expr.clone() * block_level_expr),
+                (cond_expr.clone() * label_types_expr, is_opener_expr.clone() * frame_label_types_expr),
+                (cond_expr.clone() * label_types_count_expr, is_opener_expr.clone() * frame_label_types_count_expr),
+                (cond_expr.clone() * label_types_shift_expr, is_opener_expr.clone() * frame_label_types_shift_expr),
+            ]
+        });
+
+        cs.lookup("popped operands stay inside the frame", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let is_instruction_expr = instruction_flags
+                .iter()
+                .fold(0.expr(), |acc, c| acc + vc.query_fixed(*c, Rotation::cur()));
+            let is_unreachable_prev_expr = vc.query_advice(is_unreachable, Rotation::prev());
+            let type_stack_len_prev_expr = vc.query_advice(type_stack_len, Rotation::prev());
+            let frame_base_prev_expr = vc.query_advice(frame_base, Rotation::prev());
+            let pops_count_expr = vc.query_advice(pops_count, Rotation::cur());
+
+            let cond_expr = q_enable_expr * is_instruction_expr * not::expr(is_unreachable_prev_expr);
+
+            vec![(
+                cond_expr * (type_stack_len_prev_expr - pops_count_expr - frame_base_prev_expr),
+                range_table_config_0_128.value,
+            )]
+        });
+
+        cs.lookup("type stack stays under its max len", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+
+            vec![(
+                q_enable_expr * vc.query_advice(type_stack_len, Rotation::cur()),
+                range_table_config_0_128.value,
+            )]
+        });
+
+        cs.lookup("blocktype type index is a non-negative s33", |vc| {
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+            let is_blocktype_type_index_expr = vc.query_fixed(is_blocktype_type_index, Rotation::cur());
+            let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
+            let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
+
+            // the sign bit of the last byte is clear, so the unsigned value is the s33 one
+            vec![(
+                q_enable_expr * is_blocktype_type_index_expr * leb128_is_last_byte_expr * (byte_val_expr + 0x40.expr()),
+                range_table_config_0_128.value,
+            )]
+        });
 
-                            + is_block_end_next_expr
-                        ,
-                        1.expr(),
-                    );
-                }
-            );
+        for diff in label_frame_id_lt_chip
+            .config()
+            .diff
+            .into_iter()
+            .chain(label_frame_end_lt_chip.config().diff)
+        {
+            cs.lookup("label frame lt chips diff bytes are bytes", |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
+
+                vec![(
+                    q_enable_expr * vc.query_advice(diff, Rotation::cur()),
+                    range_table_config_0_256.value,
+                )]
+            });
+        }
 
-            cb.condition(
-                and::expr([
-                    q_enable_expr.clone(),
-                    not_q_first_expr,
-                    or::expr([
-                        is_br_prev_expr,
-                        is_br_if_prev_expr,
-                    ])
-                ]),
-                |cb| {
-                    cb.require_zero(
-                        "br/br_if arg is valid",
-                        block_level_lt_chip.config().is_lt(vc, None).expr() - 1.expr(),
-                    );
-                }
-            );
+        for diff in rest_lt_chip.config().diff {
+            cs.lookup("rest_lt_chip diff bytes are bytes", |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(vc, q_enable, &shared_state.borrow(), error_code);
 
-            cb.gate(q_enable_expr.clone())
-        });
+                vec![(
+                    q_enable_expr * vc.query_advice(diff, Rotation::cur()),
+                    range_table_config_0_256.value,
+                )]
+            });
+        }
 
         let config = WasmCodeSectionBodyConfig::<F> {
             _marker: PhantomData,
@@ -1472,19 +3444,62 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             is_numeric_instruction_leb_arg,
             is_variable_instruction,
             is_variable_instruction_leb_arg,
+            is_memory_instruction,
+            is_memory_instruction_leb_arg,
             is_control_instruction,
             is_control_instruction_leb_arg,
             is_parametric_instruction,
+            is_prefixed_instruction,
+            is_prefixed_instruction_sub_opcode,
+            is_prefixed_instruction_leb_arg,
             is_blocktype_delimiter,
+            is_blocktype_valtype,
+            is_blocktype_type_index,
             is_block_end,
             leb128_chip,
             numeric_instructions_chip,
             variable_instruction_chip,
+            memory_instruction_chip,
             control_instruction_chip,
             parametric_instruction_chip,
+            prefixed_instruction_chip,
             dynamic_indexes_chip,
             code_blocks_chip,
             block_opcode_number,
+            module_types_chip,
+            type_stack,
+            type_stack_len,
+            type_stack_shift,
+            frame_id,
+            frame_base,
+            is_unreachable,
+            pops,
+            pops_count,
+            pops_shift,
+            pushes,
+            pushes_count,
+            pushes_shift,
+            rest,
+            rest_shift,
+            operand_type,
+            rest_lt_chip,
+            is_func_label_chip,
+            block_results,
+            block_results_count,
+            block_results_shift,
+            has_else,
+            frame_end,
+            frame_end_pc,
+            else_pc,
+            label_frame_id,
+            label_frame_end,
+            label_frame_pc,
+            label_frame_id_lt_chip,
+            label_frame_end_lt_chip,
+            wb_table,
+            declared_locals,
+            is_entry_func,
+            pc,
             func_count,
             block_level,
             block_level_lt_chip,
@@ -1505,13 +3520,25 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         wb_offset: usize,
         assign_delta: AssignDeltaType,
         block_opcode_number: &mut u64,
+        pc: &mut u64,
+        func_index: u64,
+        type_stack_state: &mut TypeStackState,
+        type_stack_frames: &mut Vec<TypeStackFrame>,
     ) -> Result<usize, Error> {
         let mut offset = wb_offset;
+        // sub-opcode of a prefixed instruction or the first immediate of the rest
+        let mut immediate: u64 = 0;
+        // immediate the instruction is tagged with in the module view
+        let mut module_arg: u64 = 0;
 
         let opcode = wb.bytes[offset];
+        if NumericInstruction::is_float_value(opcode) || MemoryInstruction::is_float_value(opcode) {
+            return Err(Error::FloatUnsupportedAt(offset + assign_delta));
+        }
 
         let mut assign_type = AssignType::Unknown;
         let mut assign_type_argument = AssignType::Unknown;
+        let mut block_type: Option<BlockType> = None;
 
         if let Ok(opcode) = <u8 as TryInto<NumericInstruction>>::try_into(opcode) {
             assign_type = AssignType::IsNumericInstruction;
@@ -1527,10 +3554,28 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             }
         }
 
+        if let Ok(_opcode) = <u8 as TryInto<MemoryInstruction>>::try_into(opcode) {
+            assign_type = AssignType::IsMemoryInstruction;
+            assign_type_argument = AssignType::IsMemoryInstructionLebArg;
+        }
+
         if let Ok(opcode) = <u8 as TryInto<ControlInstruction>>::try_into(opcode) {
             assign_type = AssignType::IsControlInstruction;
             if CONTROL_INSTRUCTION_BLOCK.contains(&opcode) {
-                assign_type_argument = AssignType::IsBlocktypeDelimiter;
+                validate_wb_offset(wb, offset + 1)?;
+                let blocktype = wb.bytes[offset + 1];
+                if NumType::is_float_value(blocktype) {
+                    return Err(Error::FloatUnsupportedAt(offset + 1 + assign_delta));
+                }
+                assign_type_argument = if blocktype as i32 == WASM_BLOCKTYPE_DELIMITER {
+                    block_type = Some(BlockType::default());
+                    AssignType::IsBlocktypeDelimiter
+                } else if let Ok(num_type) = <u8 as TryInto<NumType>>::try_into(blocktype) {
+                    block_type = Some(BlockType::new(&[], &[num_type]));
+                    AssignType::IsBlocktypeValtype
+                } else {
+                    AssignType::IsBlocktypeTypeIndex
+                };
                 self.shared_state().borrow_mut().block_level_inc();
             }
             if CONTROL_INSTRUCTION_WITH_LEB_ARG.contains(&opcode) {
@@ -1594,6 +3639,10 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             assign_type = AssignType::IsParametricInstruction;
         }
 
+        if opcode == WASM_INSTRUCTION_PREFIX_FC {
+            assign_type = AssignType::IsPrefixedInstruction;
+        }
+
         if opcode == WASM_BLOCK_END {
             assign_type = AssignType::IsBlockEnd;
             self.shared_state().borrow_mut().block_level_dec();
@@ -1613,8 +3662,10 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         if [
             AssignType::IsNumericInstruction,
             AssignType::IsVariableInstruction,
+            AssignType::IsMemoryInstruction,
             AssignType::IsControlInstruction,
             AssignType::IsParametricInstruction,
+            AssignType::IsPrefixedInstruction,
             AssignType::IsBlockEnd,
         ]
         .contains(&assign_type)
@@ -1632,7 +3683,95 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             offset += 1;
         }
 
-        if assign_type_argument == AssignType::IsBlocktypeDelimiter {
+        if assign_type == AssignType::IsPrefixedInstruction {
+            let (sub_opcode, sub_opcode_leb_len) = self.markup_leb_section(
+                region,
+                wb,
+                offset,
+                assign_delta,
+                &[AssignType::IsPrefixedInstructionSubOpcode],
+            )?;
+            immediate = sub_opcode;
+            self.markup_code_blocks(
+                region,
+                &wb,
+                offset,
+                assign_delta,
+                sub_opcode_leb_len,
+                *block_opcode_number,
+                None,
+            )?;
+            let prefixed_instruction: PrefixedInstruction = u8::try_from(sub_opcode)
+                .map_err(remap_error_to_invalid_enum_value_at(offset + assign_delta))?
+                .try_into()
+                .map_err(remap_error_to_invalid_enum_value_at(offset + assign_delta))?;
+            if prefixed_instruction.is_float() {
+                return Err(Error::FloatUnsupportedAt(offset + assign_delta));
+            }
+            if [PrefixedInstruction::MemoryInit, PrefixedInstruction::DataDrop]
+                .contains(&prefixed_instruction)
+            {
+                self.config.shared_state.borrow_mut().data_index_used = true;
+            }
+            offset += sub_opcode_leb_len;
+
+            for arg_index in 0..prefixed_instruction.leb_args_count() {
+                let (arg_val, arg_leb_len) = self.markup_leb_section(
+                    region,
+                    wb,
+                    offset,
+                    assign_delta,
+                    &[AssignType::IsPrefixedInstructionLebArg],
+                )?;
+                if arg_index == 0 {
+                    module_arg = arg_val;
+                }
+                self.markup_code_blocks(
+                    region,
+                    &wb,
+                    offset,
+                    assign_delta,
+                    arg_leb_len,
+                    *block_opcode_number,
+                    None,
+                )?;
+                offset += arg_leb_len;
+            }
+        }
+
+        if assign_type == AssignType::IsMemoryInstruction {
+            let memory_instruction: MemoryInstruction = opcode
+                .try_into()
+                .map_err(remap_error_to_invalid_enum_value_at(wb_offset + assign_delta))?;
+            for arg_index in 0..memory_instruction.leb_args_count() {
+                let (arg_val, arg_leb_len) =
+                    self.markup_leb_section(region, wb, offset, assign_delta, &[assign_type_argument])?;
+                if MEMORY_INSTRUCTION_WITH_MEMORY_INDEX.contains(&memory_instruction) && arg_val != 0 {
+                    return Err(Error::InvalidByteValueAt(offset + assign_delta));
+                }
+                // the offset of a memarg follows its alignment
+                if arg_index + 1 == memory_instruction.leb_args_count() {
+                    module_arg = arg_val;
+                }
+                self.markup_code_blocks(
+                    region,
+                    &wb,
+                    offset,
+                    assign_delta,
+                    arg_leb_len,
+                    *block_opcode_number,
+                    None,
+                )?;
+                offset += arg_leb_len;
+            }
+        }
+
+        if [
+            AssignType::IsBlocktypeDelimiter,
+            AssignType::IsBlocktypeValtype,
+        ]
+        .contains(&assign_type_argument)
+        {
             self.assign(
                 region,
                 wb,
@@ -1654,6 +3793,51 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             offset += 1;
         }
 
+        if assign_type_argument == AssignType::IsBlocktypeTypeIndex {
+            let (type_index, type_index_leb_len) =
+                self.markup_leb_section(region, wb, offset, assign_delta, &[assign_type_argument])?;
+            // a negative s33 has the sign bit of its last byte set
+            if wb.bytes[offset + type_index_leb_len - 1] >= WASM_BLOCKTYPE_DELIMITER as u8 {
+                return Err(Error::InvalidByteValueAt(offset + assign_delta));
+            }
+            self.markup_code_blocks(
+                region,
+                &wb,
+                offset,
+                assign_delta,
+                type_index_leb_len,
+                *block_opcode_number,
+                None,
+            )?;
+            let module_types = self.config.module_types_chip.config.module_types.borrow();
+            let func_type = module_types
+                .types
+                .get(type_index as usize)
+                .ok_or(Error::TypeMismatchAt(offset + assign_delta))?;
+            block_type = Some(BlockType::new(&func_type.params, &func_type.results));
+            offset += type_index_leb_len;
+        }
+        if let Some(block_type) = block_type {
+            for (assign_type, assign_value) in [
+                (
+                    code_blocks::types::AssignType::ParamsCount,
+                    block_type.params.count,
+                ),
+                (
+                    code_blocks::types::AssignType::ResultsCount,
+                    block_type.results.count,
+                ),
+            ] {
+                self.config.code_blocks_chip.assign(
+                    region,
+                    *block_opcode_number as usize - 1,
+                    assign_delta,
+                    &[assign_type],
+                    assign_value,
+                )?;
+            }
+        }
+
         if [
             AssignType::IsNumericInstructionLebArg,
             AssignType::IsVariableInstructionLebArg,
@@ -1663,6 +3847,8 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         {
             let (instr_arg_val, inst_arg_leb_len) =
                 self.markup_leb_section(region, wb, offset, assign_delta, &[assign_type_argument])?;
+            immediate = instr_arg_val;
+            module_arg = instr_arg_val;
             self.markup_code_blocks(
                 region,
                 &wb,
@@ -1695,9 +3881,547 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             return Err(Error::ParseOpcodeFailedAt(offset));
         }
 
+        // an opener is tagged with its arity, its `else` and `end` with the pc of the opener
+        let control_instruction: Option<ControlInstruction> = opcode.try_into().ok();
+        if let Some(block_type) = block_type {
+            module_arg = block_type.params.count + (block_type.results.count << 16);
+        } else if control_instruction == Some(ControlInstruction::Else) || opcode == WASM_BLOCK_END {
+            module_arg = type_stack_frames.last().map_or(0, |frame| frame.opener_pc + 1);
+        }
+        // the label of a branch is tagged with the pc plus one of the opener it targets
+        let label_target = match control_instruction {
+            Some(ControlInstruction::Br) | Some(ControlInstruction::BrIf) => Some(
+                type_stack_frames
+                    .len()
+                    .checked_sub(1 + immediate as usize)
+                    .map_or(0, |frame_index| type_stack_frames[frame_index].opener_pc + 1),
+            ),
+            _ => None,
+        };
+
+        let opcode = if assign_type == AssignType::IsPrefixedInstruction {
+            (opcode as u64) << 32 | immediate
+        } else {
+            opcode as u64
+        };
+        let has_bound_arg = [
+            AssignType::IsVariableInstruction,
+            AssignType::IsMemoryInstruction,
+            AssignType::IsPrefixedInstruction,
+        ]
+        .contains(&assign_type)
+            || assign_type_argument == AssignType::IsControlInstructionLebArg;
+        self.config
+            .wb_table
+            .assign_module_view(
+                region,
+                wb_offset + assign_delta,
+                Some(WasmModuleTag::Instruction),
+                *pc,
+                opcode,
+                module_arg,
+            )
+            .map_err(remap_error_to_assign_at(wb_offset + assign_delta))?;
+        self.assign(region, wb, wb_offset, assign_delta, &[AssignType::Pc], *pc, None)?;
+        let instruction_pc = *pc;
+        *pc += 1;
+        for offset in wb_offset + 1..offset {
+            if let (Some(target), true) = (label_target, offset == wb_offset + 1) {
+                self.config
+                    .wb_table
+                    .assign_module_view(
+                        region,
+                        offset + assign_delta,
+                        Some(WasmModuleTag::Label),
+                        instruction_pc,
+                        target,
+                        module_arg,
+                    )
+                    .map_err(remap_error_to_assign_at(offset + assign_delta))?;
+            } else if has_bound_arg {
+                self.config
+                    .wb_table
+                    .assign_module_view(region, offset + assign_delta, None, 0, 0, module_arg)
+                    .map_err(remap_error_to_assign_at(offset + assign_delta))?;
+            }
+            self.assign(region, wb, offset, assign_delta, &[AssignType::Pc], *pc, None)?;
+        }
+
+        self.markup_type_stack(
+            region,
+            wb,
+            wb_offset,
+            offset,
+            assign_delta,
+            instruction_pc,
+            immediate,
+            block_type,
+            *block_opcode_number,
+            func_index,
+            type_stack_state,
+            type_stack_frames,
+        )?;
+
         Ok(offset)
     }
 
+    /// Checks the instruction at `wb_offset..end_offset` against the operand type stack, assigns
+    /// its effect and the stack state left after it
+    fn markup_type_stack(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        end_offset: usize,
+        assign_delta: AssignDeltaType,
+        pc: u64,
+        immediate: u64,
+        block_type: Option<BlockType>,
+        block_opcode_number: u64,
+        func_index: u64,
+        state: &mut TypeStackState,
+        frames: &mut Vec<TypeStackFrame>,
+    ) -> Result<(), Error> {
+        let assign_offset = wb_offset + assign_delta;
+        let opcode = wb.bytes[wb_offset];
+        let block_level = self.config.shared_state.borrow().block_level as u64;
+        let module_types = self.config.module_types_chip.config.module_types.borrow();
+        let is_reachable = !state.is_unreachable;
+        let stack_type_bit = |depth: u64| (state.stack.packed >> depth) as u64 & 1;
+
+        let effect = if opcode == WASM_INSTRUCTION_PREFIX_FC {
+            TypeStackEffect::fixed(prefixed_instruction_key(immediate))
+        } else if opcode == WASM_BLOCK_END {
+            // the function end is checked against the function results by a lookup
+            Some(TypeStackEffect {
+                pops: frames
+                    .last()
+                    .map(|frame| frame.block_type.results)
+                    .unwrap_or_default(),
+                ..Default::default()
+            })
+        } else if let Ok(instr) = <u8 as TryInto<VariableInstruction>>::try_into(opcode) {
+            let num_type = match instr {
+                VariableInstruction::LocalGet
+                | VariableInstruction::LocalSet
+                | VariableInstruction::LocalTee => module_types.local_type(func_index, immediate),
+                VariableInstruction::GlobalGet | VariableInstruction::GlobalSet => {
+                    module_types.global_type(immediate)
+                }
+            };
+            num_type.map(|num_type| {
+                let effect = match instr {
+                    VariableInstruction::LocalGet | VariableInstruction::GlobalGet => {
+                        TypeStackEffect::new(&[], &[num_type])
+                    }
+                    VariableInstruction::LocalSet | VariableInstruction::GlobalSet => {
+                        TypeStackEffect::new(&[num_type], &[])
+                    }
+                    VariableInstruction::LocalTee => {
+                        TypeStackEffect::new(&[num_type], &[num_type])
+                    }
+                };
+                TypeStackEffect {
+                    operand_type: num_type_bit(num_type),
+                    ..effect
+                }
+            })
+        } else if let Ok(instr) = <u8 as TryInto<ParametricInstruction>>::try_into(opcode) {
+            // the operand type is read from the stack, the effect fails to apply if it is wrong
+            let operand_type = match instr {
+                ParametricInstruction::Drop => stack_type_bit(0),
+                ParametricInstruction::Select => stack_type_bit(1),
+            };
+            let num_type = if operand_type == 0 {
+                NumType::I32
+            } else {
+                NumType::I64
+            };
+            let effect = match instr {
+                ParametricInstruction::Drop => TypeStackEffect::new(&[num_type], &[]),
+                ParametricInstruction::Select => {
+                    TypeStackEffect::new(&[num_type, num_type, NumType::I32], &[num_type])
+                }
+            };
+            Some(TypeStackEffect {
+                operand_type,
+                ..effect
+            })
+        } else if let Ok(instr) = <u8 as TryInto<ControlInstruction>>::try_into(opcode) {
+            let condition = TypeSeq::new(&[NumType::I32]);
+            match instr {
+                // the params are popped and pushed back as the operands of the new frame
+                ControlInstruction::Block | ControlInstruction::Loop => {
+                    block_type.map(|block_type| TypeStackEffect {
+                        pops: block_type.params,
+                        pushes: block_type.params,
+                        ..Default::default()
+                    })
+                }
+                ControlInstruction::If => block_type.map(|block_type| TypeStackEffect {
+                    pops: block_type.params.concat(&condition),
+                    pushes: block_type.params,
+                    ..Default::default()
+                }),
+                ControlInstruction::Unreachable => Some(TypeStackEffect::default()),
+                ControlInstruction::Else => frames.last().map(|frame| TypeStackEffect {
+                    pops: frame.block_type.results,
+                    ..Default::default()
+                }),
+                ControlInstruction::Call => module_types
+                    .func_type(immediate)
+                    .map(|func_type| TypeStackEffect::new(&func_type.params, &func_type.results)),
+                ControlInstruction::Br | ControlInstruction::BrIf => {
+                    let label_types = if immediate + 1 == block_level {
+                        module_types
+                            .func_type(func_index)
+                            .map(|func_type| TypeSeq::new(&func_type.results))
+                    } else {
+                        // a label out of range is rejected by `block_level_lt_chip`
+                        Some(
+                            frames
+                                .len()
+                                .checked_sub(1 + immediate as usize)
+                                .map(|frame_index| frames[frame_index].label_types())
+                                .unwrap_or_default(),
+                        )
+                    };
+                    label_types.map(|label_types| {
+                        if instr == ControlInstruction::Br {
+                            TypeStackEffect {
+                                pops: label_types,
+                                ..Default::default()
+                            }
+                        } else {
+                            TypeStackEffect {
+                                pops: label_types.concat(&condition),
+                                pushes: label_types,
+                                ..Default::default()
+                            }
+                        }
+                    })
+                }
+                _ => None,
+            }
+        } else {
+            TypeStackEffect::fixed(opcode as u64)
+        }
+        .ok_or(Error::TypeMismatchAt(assign_offset))?;
+
+        let control_instruction: Option<ControlInstruction> = opcode.try_into().ok();
+        let is_else = control_instruction == Some(ControlInstruction::Else);
+        let is_block_end = opcode == WASM_BLOCK_END;
+
+        let prev_state = *state;
+        let mut rest = TypeSeq::default();
+        if is_reachable {
+            let (effect_rest, applied_state) =
+                state.apply(&effect).ok_or(Error::TypeMismatchAt(assign_offset))?;
+            rest = effect_rest;
+            if !is_else && !is_block_end {
+                *state = applied_state;
+            }
+        }
+
+        match control_instruction {
+            Some(opener @ ControlInstruction::Block)
+            | Some(opener @ ControlInstruction::Loop)
+            | Some(opener @ ControlInstruction::If) => {
+                let block_type = block_type.ok_or(Error::TypeMismatchAt(assign_offset))?;
+                // an unreachable frame has no known operands, the new frame starts with its params
+                let opened = TypeStackState {
+                    stack: rest.concat(&block_type.params),
+                    frame_id: block_opcode_number,
+                    frame_base: rest.count,
+                    is_unreachable: false,
+                };
+                frames.push(TypeStackFrame {
+                    opcode: opener,
+                    block_type,
+                    parent: prev_state,
+                    opened,
+                    has_else: false,
+                    opener_offset: assign_offset,
+                    opener_pc: pc,
+                    else_pc: 0,
+                    labels: vec![],
+                });
+                *state = opened;
+                let c = &self.config;
+                for (column, name, value) in [
+                    (c.block_results, "block_results", block_type.results.packed),
+                    (
+                        c.block_results_count,
+                        "block_results_count",
+                        block_type.results.count as u128,
+                    ),
+                    (
+                        c.block_results_shift,
+                        "block_results_shift",
+                        block_type.results.shift(),
+                    ),
+                ] {
+                    self.assign_type_stack_value(region, assign_offset, column, name, value)?;
+                }
+            }
+            Some(ControlInstruction::Unreachable) | Some(ControlInstruction::Br) => {
+                state.is_unreachable = true;
+            }
+            Some(ControlInstruction::Else) => {
+                let frame = frames
+                    .last_mut()
+                    .filter(|frame| frame.opcode == ControlInstruction::If)
+                    .ok_or(Error::TypeMismatchAt(assign_offset))?;
+                if is_reachable
+                    && state.stack.count != state.frame_base + frame.block_type.results.count
+                {
+                    return Err(Error::TypeMismatchAt(assign_offset));
+                }
+                frame.has_else = true;
+                frame.else_pc = pc;
+                self.assign_type_stack_value(
+                    region,
+                    frame.opener_offset,
+                    self.config.has_else,
+                    "has_else",
+                    1,
+                )?;
+                self.assign_type_stack_value(
+                    region,
+                    frame.opener_offset,
+                    self.config.else_pc,
+                    "else_pc",
+                    pc as u128,
+                )?;
+                *state = frame.opened;
+            }
+            _ => {}
+        }
+        if control_instruction == Some(ControlInstruction::Br)
+            || control_instruction == Some(ControlInstruction::BrIf)
+        {
+            if let Some(frame_index) = frames.len().checked_sub(1 + immediate as usize) {
+                let frame = &mut frames[frame_index];
+                frame.labels.push((assign_offset, block_opcode_number));
+                self.assign_type_stack_value(
+                    region,
+                    assign_offset,
+                    self.config.label_frame_id,
+                    "label_frame_id",
+                    frame.opened.frame_id as u128,
+                )?;
+                self.assign_type_stack_value(
+                    region,
+                    assign_offset,
+                    self.config.label_frame_pc,
+                    "label_frame_pc",
+                    frame.opener_pc as u128,
+                )?;
+                self.config
+                    .label_frame_id_lt_chip
+                    .assign(
+                        region,
+                        assign_offset,
+                        F::from(frame.opened.frame_id),
+                        F::from(block_opcode_number + 1),
+                    )
+                    .map_err(remap_error(Error::FatalAssignExternalChip))?;
+            }
+        }
+        if is_block_end {
+            if let Some(frame) = frames.pop() {
+                let block_type = frame.block_type;
+                if is_reachable && state.stack.count != state.frame_base + block_type.results.count
+                {
+                    return Err(Error::TypeMismatchAt(assign_offset));
+                }
+                // the missing else branch passes the params through
+                if frame.opcode == ControlInstruction::If
+                    && !frame.has_else
+                    && block_type.params != block_type.results
+                {
+                    return Err(Error::TypeMismatchAt(assign_offset));
+                }
+                *state = TypeStackState {
+                    stack: frame.opened_rest().concat(&block_type.results),
+                    ..frame.parent
+                };
+                if state.stack.count as usize >= TYPE_STACK_MAX_LEN {
+                    return Err(Error::TypeMismatchAt(assign_offset));
+                }
+                self.assign_type_stack_value(
+                    region,
+                    frame.opener_offset,
+                    self.config.frame_end,
+                    "frame_end",
+                    block_opcode_number as u128,
+                )?;
+                self.assign_type_stack_value(
+                    region,
+                    frame.opener_offset,
+                    self.config.frame_end_pc,
+                    "frame_end_pc",
+                    pc as u128,
+                )?;
+                // the blocktype of the opener is tagged with the pcs its frame jumps to
+                self.config
+                    .wb_table
+                    .assign_module_view(
+                        region,
+                        frame.opener_offset + 1,
+                        Some(WasmModuleTag::Block),
+                        frame.opener_pc,
+                        pc,
+                        frame.else_pc,
+                    )
+                    .map_err(remap_error_to_assign_at(frame.opener_offset + 1))?;
+                for (label_offset, label_block_opcode_number) in frame.labels {
+                    self.assign_type_stack_value(
+                        region,
+                        label_offset,
+                        self.config.label_frame_end,
+                        "label_frame_end",
+                        block_opcode_number as u128,
+                    )?;
+                    self.config
+                        .label_frame_end_lt_chip
+                        .assign(
+                            region,
+                            label_offset,
+                            F::from(label_block_opcode_number),
+                            F::from(block_opcode_number),
+                        )
+                        .map_err(remap_error(Error::FatalAssignExternalChip))?;
+                }
+            } else if is_reachable {
+                let results = module_types
+                    .func_type(func_index)
+                    .map(|func_type| TypeSeq::new(&func_type.results));
+                if results != Some(state.stack) {
+                    return Err(Error::TypeMismatchAt(assign_offset));
+                }
+            }
+        }
+
+        self.assign_type_stack_effect(region, assign_offset, &effect, &rest)?;
+        if control_instruction == Some(ControlInstruction::Br)
+            || control_instruction == Some(ControlInstruction::BrIf)
+        {
+            self.config
+                .is_func_label_chip
+                .assign(
+                    region,
+                    assign_offset,
+                    Value::known(F::from(block_level) - F::one() - F::from(immediate)),
+                )
+                .map_err(remap_error(Error::FatalAssignExternalChip))?;
+        }
+        for offset in wb_offset..end_offset {
+            self.assign_type_stack_state(region, offset + assign_delta, state)?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_type_stack_value(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        column: Column<Advice>,
+        name: &str,
+        value: u128,
+    ) -> Result<(), Error> {
+        debug!("assign at {} '{}' val {}", assign_offset, name, value);
+        region
+            .assign_advice(
+                || format!("assign '{}' val {} at {}", name, value, assign_offset),
+                column,
+                assign_offset,
+                || Value::known(F::from_u128(value)),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))?;
+
+        Ok(())
+    }
+
+    /// assigns the params or locals count of a function to the module view
+    fn assign_func_count_fact(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        tag: WasmModuleTag,
+        func_index: u64,
+        value: u64,
+        is_entry_func: bool,
+    ) -> Result<(), Error> {
+        let arg = if is_entry_func { 0 } else { func_index + 1 };
+        self.config
+            .wb_table
+            .assign_module_view(region, assign_offset, Some(tag), func_index + 1, value, arg)
+            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.assign_type_stack_value(
+            region,
+            assign_offset,
+            self.config.is_entry_func,
+            "is_entry_func",
+            is_entry_func as u128,
+        )
+    }
+
+    fn assign_type_stack_state(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        state: &TypeStackState,
+    ) -> Result<(), Error> {
+        let c = &self.config;
+        for (column, name, value) in [
+            (c.type_stack, "type_stack", state.stack.packed),
+            (c.type_stack_len, "type_stack_len", state.stack.count as u128),
+            (c.type_stack_shift, "type_stack_shift", state.stack.shift()),
+            (c.frame_id, "frame_id", state.frame_id as u128),
+            (c.frame_base, "frame_base", state.frame_base as u128),
+            (c.is_unreachable, "is_unreachable", state.is_unreachable as u128),
+        ] {
+            self.assign_type_stack_value(region, assign_offset, column, name, value)?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_type_stack_effect(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        effect: &TypeStackEffect,
+        rest: &TypeSeq,
+    ) -> Result<(), Error> {
+        let c = &self.config;
+        for (column, name, value) in [
+            (c.pops, "pops", effect.pops.packed),
+            (c.pops_count, "pops_count", effect.pops.count as u128),
+            (c.pops_shift, "pops_shift", effect.pops.shift()),
+            (c.pushes, "pushes", effect.pushes.packed),
+            (c.pushes_count, "pushes_count", effect.pushes.count as u128),
+            (c.pushes_shift, "pushes_shift", effect.pushes.shift()),
+            (c.rest, "rest", rest.packed),
+            (c.rest_shift, "rest_shift", rest.shift()),
+            (c.operand_type, "operand_type", effect.operand_type as u128),
+        ] {
+            self.assign_type_stack_value(region, assign_offset, column, name, value)?;
+        }
+        c.rest_lt_chip
+            .assign(
+                region,
+                assign_offset,
+                F::from_u128(rest.packed),
+                F::from_u128(rest.shift()),
+            )
+            .map_err(remap_error(Error::FatalAssignExternalChip))?;
+
+        Ok(())
+    }
+
     fn markup_code_blocks(
         &self,
         region: &mut Region<F>,
@@ -1766,6 +4490,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
     ) -> Result<usize, Error> {
         let mut offset = wb_offset;
         let mut block_opcode_number: u64 = 0;
+        let mut pc: u64 = 0;
 
         // is_funcs_count+
         let (funcs_count, funcs_count_leb_len) = self.markup_leb_section(
@@ -1785,6 +4510,9 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
             None,
         )?;
         let mut body_item_rev_count = funcs_count;
+        for offset in offset..offset + funcs_count_leb_len {
+            self.assign(region, wb, offset, assign_delta, &[AssignType::Pc], pc, None)?;
+        }
         let funcs_count_last_byte_offset = offset + funcs_count_leb_len - 1;
         self.assign(
             region,
@@ -1807,8 +4535,24 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
         )?;
         offset += funcs_count_leb_len;
 
-        for _func_index in 0..funcs_count {
+        let defined_funcs_start = self.config.shared_state.borrow().func_count as u64 - funcs_count;
+        let entry_func_index = wasm_entry_function(&wb.bytes);
+        for defined_func_index in 0..funcs_count {
             body_item_rev_count -= 1;
+            let func_index = defined_funcs_start + defined_func_index;
+            let is_entry_func = entry_func_index == Some(func_index);
+            let params_count = self
+                .config
+                .module_types_chip
+                .config
+                .module_types
+                .borrow()
+                .func_type(func_index)
+                .map_or(0, |func_type| func_type.params.len() as u64);
+            let mut declared_locals: u64 = 0;
+            let func_body_start_offset = offset;
+            let mut type_stack_state = TypeStackState::default();
+            let mut type_stack_frames: Vec<TypeStackFrame> = vec![];
             // is_func_body_len+
             self.config.shared_state.borrow_mut().block_level_inc();
             let (func_body_len, func_body_len_leb_len) = self.markup_leb_section(
@@ -1840,6 +4584,14 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     None,
                 )?;
             }
+            self.assign_func_count_fact(
+                region,
+                offset + assign_delta,
+                WasmModuleTag::ParamCount,
+                func_index,
+                params_count,
+                is_entry_func,
+            )?;
             let func_body_len_last_byte_offset = offset + func_body_len_leb_len - 1;
             for offset in func_body_len_last_byte_offset..=func_body_end_offset {
                 self.assign(
@@ -1876,7 +4628,7 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
 
             for _is_valtype_transition_index in 0..is_local_type_transitions_count {
                 // -> local_var_descriptor+(is_local_repetition_count+ ...
-                let (_is_local_repetition_count, is_local_repetition_count_leb_len) = self
+                let (is_local_repetition_count, is_local_repetition_count_leb_len) = self
                     .markup_leb_section(
                         region,
                         wb,
@@ -1893,9 +4645,25 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     block_opcode_number,
                     None,
                 )?;
+                declared_locals += is_local_repetition_count;
+                for offset in offset..offset + is_local_repetition_count_leb_len + 1 {
+                    self.assign(
+                        region,
+                        &wb,
+                        offset,
+                        assign_delta,
+                        &[AssignType::DeclaredLocals],
+                        declared_locals,
+                        None,
+                    )?;
+                }
                 offset += is_local_repetition_count_leb_len;
 
                 // is_local_type{1}
+                validate_wb_offset(wb, offset)?;
+                if NumType::is_float_value(wb.bytes[offset]) {
+                    return Err(Error::FloatUnsupportedAt(offset + assign_delta));
+                }
                 self.assign(
                     region,
                     wb,
@@ -1917,6 +4685,20 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                 offset += 1;
             }
 
+            self.assign_func_count_fact(
+                region,
+                offset - 1 + assign_delta,
+                WasmModuleTag::LocalCount,
+                func_index,
+                params_count + declared_locals,
+                is_entry_func,
+            )?;
+
+            for offset in func_body_start_offset..offset {
+                self.assign_type_stack_state(region, offset + assign_delta, &type_stack_state)?;
+                self.assign(region, wb, offset, assign_delta, &[AssignType::Pc], pc, None)?;
+            }
+
             while offset <= func_body_end_offset {
                 offset = self.markup_instruction_section(
                     region,
@@ -1924,6 +4706,10 @@ impl<F: Field> WasmCodeSectionBodyChip<F> {
                     offset,
                     assign_delta,
                     &mut block_opcode_number,
+                    &mut pc,
+                    func_index,
+                    &mut type_stack_state,
+                    &mut type_stack_frames,
                 )?;
             }
         }
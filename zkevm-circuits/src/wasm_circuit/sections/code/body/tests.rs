@@ -11,7 +11,12 @@ use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
     leb128::circuit::LEB128Chip,
     sections::code::body::circuit::WasmCodeSectionBodyChip,
-    tables::dynamic_indexes::circuit::DynamicIndexesChip,
+    tables::{
+        dynamic_indexes::circuit::DynamicIndexesChip,
+        fixed_range::config::RangeTableConfig,
+        module_types::{circuit::ModuleTypesChip, types::ModuleTypes},
+        type_stack::config::TypeStackTableConfig,
+    },
     types::SharedState,
 };
 
@@ -20,6 +25,8 @@ struct TestCircuit<'a, F> {
     code_hash: Hash,
     bytecode: &'a [u8],
     offset_start: usize,
+    /// types of the module the code section is taken from
+    module_types: ModuleTypes,
     _marker: PhantomData<F>,
 }
 
@@ -27,6 +34,9 @@ struct TestCircuit<'a, F> {
 struct TestCircuitConfig<F: Field> {
     body_chip: Rc<WasmCodeSectionBodyChip<F>>,
     wb_table: Rc<WasmBytecodeTable>,
+    type_stack_table_config: TypeStackTableConfig<F>,
+    range_table_config_0_128: RangeTableConfig<F, 0, 128>,
+    range_table_config_0_256: RangeTableConfig<F, 0, 256>,
     _marker: PhantomData<F>,
 }
 
@@ -54,6 +64,12 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
         let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
         let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
 
+        let module_types_config = ModuleTypesChip::configure(cs, shared_state.clone());
+        let module_types_chip = Rc::new(ModuleTypesChip::construct(module_types_config));
+        let type_stack_table_config = TypeStackTableConfig::configure(cs);
+        let range_table_config_0_128 = RangeTableConfig::configure(cs);
+        let range_table_config_0_256 = RangeTableConfig::configure(cs);
+
         let wasm_code_section_body_config = WasmCodeSectionBodyChip::configure(
             cs,
             wb_table.clone(),
@@ -65,12 +81,19 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             body_item_rev_count,
             error_code,
             bytecode_number,
+            module_types_chip,
+            &type_stack_table_config,
+            &range_table_config_0_128,
+            &range_table_config_0_256,
         );
         let wasm_code_section_body_chip =
             WasmCodeSectionBodyChip::construct(wasm_code_section_body_config);
         let test_circuit_config = TestCircuitConfig {
             body_chip: Rc::new(wasm_code_section_body_chip),
             wb_table: wb_table.clone(),
+            type_stack_table_config,
+            range_table_config_0_128,
+            range_table_config_0_256,
             _marker: Default::default(),
         };
 
@@ -84,6 +107,21 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
     ) -> Result<(), Error> {
         let wb = WasmBytecode::new(self.bytecode.to_vec().clone());
         let assign_delta = 0;
+        config.type_stack_table_config.load(&mut layouter)?;
+        config.range_table_config_0_128.load(&mut layouter)?;
+        config.range_table_config_0_256.load(&mut layouter)?;
+        let module_types_chip = config.body_chip.config.module_types_chip.clone();
+        *module_types_chip.config.module_types.borrow_mut() = self.module_types.clone();
+        layouter.assign_region(
+            || "module types region",
+            |mut region| {
+                module_types_chip
+                    .assign_auto(&mut region, 0, assign_delta, &self.module_types)
+                    .unwrap();
+
+                Ok(())
+            },
+        )?;
         layouter
             .assign_region(
                 || format!("wasm bytecode table at {}", assign_delta),
@@ -96,6 +134,8 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
         layouter.assign_region(
             || "wasm_code_section_body region",
             |mut region| {
+                config.body_chip.config.shared_state.borrow_mut().func_count =
+                    self.module_types.imported_funcs_count;
                 let mut offset_start = self.offset_start;
                 while offset_start < wb.bytes.len() {
                     offset_start = config
@@ -116,17 +156,20 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
 mod wasm_code_section_body_tests {
     use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
     use log::debug;
+    use wabt::wat2wasm;
     use wasmbin::sections::Kind;
 
     use bus_mapping::state_db::CodeDB;
     use eth_types::Field;
 
     use crate::wasm_circuit::{
-        common::wat_extract_section_body_bytecode, sections::code::body::tests::TestCircuit,
+        common::wat_extract_section_body_bytecode,
+        sections::code::body::tests::TestCircuit,
+        tables::module_types::{helpers::module_types_parse, types::ModuleTypes},
     };
 
     fn test<'a, F: Field>(test_circuit: TestCircuit<'_, F>, is_ok: bool) {
-        let k = 8;
+        let k = 9;
         let prover = MockProver::run(k, &test_circuit, vec![]).unwrap();
         if is_ok {
             prover.assert_satisfied();
@@ -135,6 +178,11 @@ mod wasm_code_section_body_tests {
         }
     }
 
+    fn wat_module_types(path_to_file: &str) -> ModuleTypes {
+        let wat = std::fs::read(path_to_file).unwrap();
+        module_types_parse(&wat2wasm(wat).unwrap()).unwrap()
+    }
+
     #[test]
     pub fn file1_ok() {
         let bytecode = wat_extract_section_body_bytecode("./test_files/cc1.wat", Kind::Code);
@@ -149,6 +197,7 @@ mod wasm_code_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
+            module_types: wat_module_types("./test_files/cc1.wat"),
             _marker: Default::default(),
         };
         test(test_circuit, true);
@@ -168,6 +217,7 @@ mod wasm_code_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
+            module_types: wat_module_types("./test_files/cc2.wat"),
             _marker: Default::default(),
         };
         test(test_circuit, true);
@@ -187,6 +237,67 @@ mod wasm_code_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
+            module_types: wat_module_types("./test_files/cc3.wat"),
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn file4_ok() {
+        let bytecode = wat_extract_section_body_bytecode("./test_files/cc4.wat", Kind::Code);
+        debug!(
+            "bytecode (len {}) hex {:x?} bin {:?}",
+            bytecode.len(),
+            bytecode,
+            bytecode
+        );
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: wat_module_types("./test_files/cc4.wat"),
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn file5_ok() {
+        let bytecode = wat_extract_section_body_bytecode("./test_files/cc5.wat", Kind::Code);
+        debug!(
+            "bytecode (len {}) hex {:x?} bin {:?}",
+            bytecode.len(),
+            bytecode,
+            bytecode
+        );
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: wat_module_types("./test_files/cc5.wat"),
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn file7_ok() {
+        let bytecode = wat_extract_section_body_bytecode("./test_files/cc7.wat", Kind::Code);
+        debug!(
+            "bytecode (len {}) hex {:x?} bin {:?}",
+            bytecode.len(),
+            bytecode,
+            bytecode
+        );
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: wat_module_types("./test_files/cc7.wat"),
             _marker: Default::default(),
         };
         test(test_circuit, true);
@@ -13,10 +13,17 @@ pub enum AssignType {
     IsNumericInstructionLebArg,
     IsVariableInstruction,
     IsVariableInstructionLebArg,
+    IsMemoryInstruction,
+    IsMemoryInstructionLebArg,
     IsControlInstruction,
     IsControlInstructionLebArg,
     IsParametricInstruction,
+    IsPrefixedInstruction,
+    IsPrefixedInstructionSubOpcode,
+    IsPrefixedInstructionLebArg,
     IsBlocktypeDelimiter,
+    IsBlocktypeValtype,
+    IsBlocktypeTypeIndex,
     IsBlockEnd,
 
     BodyByteRevIndex,
@@ -24,5 +31,8 @@ pub enum AssignType {
 
     BlockOpcodeIndex,
 
+    DeclaredLocals,
+    Pc,
+
     ErrorCode,
 }
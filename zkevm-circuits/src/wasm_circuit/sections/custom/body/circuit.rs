@@ -0,0 +1,473 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use halo2_proofs::{
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Fixed},
+    poly::Rotation,
+};
+use log::debug;
+
+use eth_types::Field;
+use gadgets::util::{and, not, or, Expr};
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    wasm_circuit::{
+        bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+        common::{
+            configure_constraints_for_q_first_and_q_last, configure_transition_check,
+            WasmAssignAwareChip, WasmErrorAwareChip, WasmFuncCountAwareChip,
+            WasmLenPrefixedBytesSpanAwareChip, WasmMarkupLeb128SectionAwareChip,
+            WasmSharedStateAwareChip,
+        },
+        error::{error_index_out_of_bounds, remap_error_to_assign_at, Error},
+        leb128::circuit::LEB128Chip,
+        sections::{consts::LebParams, custom::body::types::AssignType},
+        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState},
+        utf8::circuit::UTF8Chip,
+    },
+};
+
+/// Custom section body: a UTF-8 name prefixed by its length, then payload bytes
+/// up to the end of the section. The payload has no meaning for execution and is
+/// only bound to the section length.
+#[derive(Debug, Clone)]
+pub struct WasmCustomSectionBodyConfig<F: Field> {
+    pub q_enable: Column<Fixed>,
+    pub q_first: Column<Fixed>,
+    pub q_last: Column<Fixed>,
+    pub is_name_len: Column<Fixed>,
+    pub is_name: Column<Fixed>,
+    pub is_payload: Column<Fixed>,
+
+    pub leb128_chip: Rc<LEB128Chip<F>>,
+    pub utf8_chip: Rc<UTF8Chip<F>>,
+
+    func_count: Column<Advice>,
+    body_byte_rev_index: Column<Advice>,
+
+    error_code: Column<Advice>,
+
+    shared_state: Rc<RefCell<SharedState>>,
+
+    _marker: PhantomData<F>,
+}
+
+impl<'a, F: Field> WasmCustomSectionBodyConfig<F> {}
+
+#[derive(Debug, Clone)]
+pub struct WasmCustomSectionBodyChip<F: Field> {
+    pub config: WasmCustomSectionBodyConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> WasmMarkupLeb128SectionAwareChip<F> for WasmCustomSectionBodyChip<F> {}
+
+impl<F: Field> WasmLenPrefixedBytesSpanAwareChip<F> for WasmCustomSectionBodyChip<F> {}
+
+impl<F: Field> WasmErrorAwareChip<F> for WasmCustomSectionBodyChip<F> {
+    fn error_code_col(&self) -> Column<Advice> {
+        self.config.error_code
+    }
+}
+
+impl<F: Field> WasmSharedStateAwareChip<F> for WasmCustomSectionBodyChip<F> {
+    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+        self.config.shared_state.clone()
+    }
+}
+
+impl<F: Field> WasmFuncCountAwareChip<F> for WasmCustomSectionBodyChip<F> {
+    fn func_count_col(&self) -> Column<Advice> {
+        self.config.func_count
+    }
+}
+
+impl<F: Field> WasmAssignAwareChip<F> for WasmCustomSectionBodyChip<F> {
+    type AssignType = AssignType;
+
+    fn assign_internal(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+        assign_types: &[Self::AssignType],
+        assign_value: AssignValueType,
+        leb_params: Option<LebParams>,
+    ) -> Result<(), Error> {
+        let q_enable = true;
+        let assign_offset = wb_offset + assign_delta;
+        debug!(
+            "assign at {} q_enable {} assign_types {:?} assign_value {} byte_val {:x?}",
+            assign_offset, q_enable, assign_types, assign_value, wb.bytes[wb_offset],
+        );
+        region
+            .assign_fixed(
+                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
+                self.config.q_enable,
+                assign_offset,
+                || Value::known(F::from(q_enable as u64)),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))?;
+        self.assign_func_count(region, assign_offset)?;
+
+        for assign_type in assign_types {
+            if *assign_type == AssignType::IsNameLen {
+                let p = leb_params.unwrap();
+                self.config
+                    .leb128_chip
+                    .assign(region, assign_offset, q_enable, p)?;
+            }
+            if *assign_type == AssignType::IsName {
+                let byte_val = wb.bytes[wb_offset];
+                self.config
+                    .utf8_chip
+                    .assign(region, assign_offset, true, byte_val)?;
+            }
+            match assign_type {
+                AssignType::QFirst => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'q_first' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.q_first,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::QLast => {
+                    region
+                        .assign_fixed(
+                            || format!("assign 'q_last' val {} at {}", assign_value, assign_offset),
+                            self.config.q_last,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::IsNameLen => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_name_len' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_name_len,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::IsName => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_name' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_name,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::IsPayload => {
+                    region
+                        .assign_fixed(
+                            || {
+                                format!(
+                                    "assign 'is_payload' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.is_payload,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::BodyByteRevIndex => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'body_byte_rev_index' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.body_byte_rev_index,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::ErrorCode => {
+                    self.assign_error_code(region, assign_offset, None)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field> WasmCustomSectionBodyChip<F> {
+    pub fn construct(config: WasmCustomSectionBodyConfig<F>) -> Self {
+        let instance = Self {
+            config,
+            _marker: PhantomData,
+        };
+        instance
+    }
+
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        wb_table: Rc<WasmBytecodeTable>,
+        leb128_chip: Rc<LEB128Chip<F>>,
+        utf8_chip: Rc<UTF8Chip<F>>,
+        func_count: Column<Advice>,
+        shared_state: Rc<RefCell<SharedState>>,
+        body_byte_rev_index: Column<Advice>,
+        error_code: Column<Advice>,
+    ) -> WasmCustomSectionBodyConfig<F> {
+        let q_enable = cs.fixed_column();
+        let q_first = cs.fixed_column();
+        let q_last = cs.fixed_column();
+        let is_name_len = cs.fixed_column();
+        let is_name = cs.fixed_column();
+        let is_payload = cs.fixed_column();
+
+        Self::configure_len_prefixed_bytes_span_checks(
+            cs,
+            leb128_chip.as_ref(),
+            |vc| vc.query_fixed(is_name, Rotation::cur()),
+            body_byte_rev_index,
+            |vc| {
+                and::expr([
+                    vc.query_fixed(is_name_len, Rotation::cur()),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur()),
+                ])
+            },
+            |vc| {
+                let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
+                let is_name_len_expr = vc.query_fixed(is_name_len, Rotation::cur());
+                let is_name_expr = vc.query_fixed(is_name, Rotation::cur());
+                let is_payload_next_expr = vc.query_fixed(is_payload, Rotation::next());
+                let leb128_is_last_byte_expr =
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
+
+                // an empty name ends right at its length
+                and::expr([
+                    or::expr([
+                        is_name_expr,
+                        and::expr([is_name_len_expr, leb128_is_last_byte_expr]),
+                    ]),
+                    or::expr([q_last_expr, is_payload_next_expr]),
+                ])
+            },
+        );
+
+        cs.create_gate("WasmCustomSectionBody gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                vc,
+                q_enable,
+                &shared_state.borrow(),
+                error_code,
+            );
+            let q_last_expr = vc.query_fixed(q_last, Rotation::cur());
+            let not_q_last_expr = not::expr(q_last_expr.clone());
+            let is_name_len_expr = vc.query_fixed(is_name_len, Rotation::cur());
+            let is_name_expr = vc.query_fixed(is_name, Rotation::cur());
+            let is_payload_expr = vc.query_fixed(is_payload, Rotation::cur());
+
+            let _byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
+
+            let leb128_q_enable_expr = vc.query_fixed(leb128_chip.config.q_enable, Rotation::cur());
+            let leb128_is_last_byte_expr =
+                vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
+            let utf8_chip_q_enabled_expr =
+                vc.query_fixed(utf8_chip.config.q_enable, Rotation::cur());
+
+            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
+            cb.require_boolean("is_name_len is boolean", is_name_len_expr.clone());
+            cb.require_boolean("is_name is boolean", is_name_expr.clone());
+            cb.require_boolean("is_payload is boolean", is_payload_expr.clone());
+
+            configure_constraints_for_q_first_and_q_last(
+                &mut cb,
+                vc,
+                &q_enable,
+                &q_first,
+                &[is_name_len],
+                &q_last,
+                &[is_name_len, is_name, is_payload],
+            );
+
+            cb.require_equal(
+                "exactly one mark flag active at the same time",
+                is_name_len_expr.clone() + is_name_expr.clone() + is_payload_expr.clone(),
+                1.expr(),
+            );
+
+            cb.condition(is_name_len_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_name_len => leb128",
+                    leb128_q_enable_expr.clone(),
+                    1.expr(),
+                )
+            });
+            cb.require_equal(
+                "is_name -> utf8",
+                is_name_expr.clone(),
+                utf8_chip_q_enabled_expr.clone(),
+            );
+
+            // is_name_len+ -> is_name* -> is_payload*
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_name_len+ -> is_name* -> is_payload*",
+                and::expr([not_q_last_expr.clone(), is_name_len_expr.clone()]),
+                true,
+                &[is_name_len, is_name, is_payload],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next (last leb byte): is_name_len+ -> is_name* -> is_payload*",
+                and::expr([
+                    not_q_last_expr.clone(),
+                    leb128_is_last_byte_expr.clone(),
+                    is_name_len_expr.clone(),
+                ]),
+                true,
+                &[is_name, is_payload],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_name* -> is_payload*",
+                and::expr([not_q_last_expr.clone(), is_name_expr.clone()]),
+                true,
+                &[is_name, is_payload],
+            );
+            configure_transition_check(
+                &mut cb,
+                vc,
+                "check next: is_payload*",
+                and::expr([not_q_last_expr.clone(), is_payload_expr.clone()]),
+                true,
+                &[is_payload],
+            );
+
+            cb.gate(q_enable_expr.clone())
+        });
+
+        let config = WasmCustomSectionBodyConfig::<F> {
+            _marker: PhantomData,
+
+            q_enable,
+            q_first,
+            q_last,
+            is_name_len,
+            is_name,
+            is_payload,
+            leb128_chip,
+            utf8_chip,
+            func_count,
+            body_byte_rev_index,
+            error_code,
+            shared_state,
+        };
+
+        config
+    }
+
+    /// Unlike other section bodies a custom section can not be parsed to its
+    /// end, so the caller passes the body length
+    pub fn assign_auto(
+        &self,
+        region: &mut Region<F>,
+        wb: &WasmBytecode,
+        wb_offset: usize,
+        assign_delta: AssignDeltaType,
+        body_len: usize,
+    ) -> Result<NewWbOffsetType, Error> {
+        let mut offset = wb_offset;
+        let body_end_offset = wb_offset + body_len;
+        if body_len == 0 || body_end_offset > wb.bytes.len() {
+            return Err(error_index_out_of_bounds(wb_offset + assign_delta));
+        }
+
+        self.assign(
+            region,
+            &wb,
+            offset,
+            assign_delta,
+            &[AssignType::QFirst],
+            1,
+            None,
+        )?;
+
+        // is_name_len+
+        let (name_len, name_leb_len) = self.markup_leb_section(
+            region,
+            &wb,
+            offset,
+            assign_delta,
+            &[AssignType::IsNameLen],
+        )?;
+        let name_len_last_byte_offset = offset + name_leb_len - 1;
+        let name_last_byte_offset = name_len_last_byte_offset + name_len as usize;
+        if name_last_byte_offset >= body_end_offset {
+            return Err(error_index_out_of_bounds(name_len_last_byte_offset + assign_delta));
+        }
+        for offset in name_len_last_byte_offset..=name_last_byte_offset {
+            self.assign(
+                region,
+                &wb,
+                offset,
+                assign_delta,
+                &[AssignType::BodyByteRevIndex],
+                (name_last_byte_offset - offset) as u64,
+                None,
+            )?;
+        }
+        offset += name_leb_len;
+
+        // is_name*
+        for _ in 0..name_len {
+            self.assign(region, &wb, offset, assign_delta, &[AssignType::IsName], 1, None)?;
+            offset += 1;
+        }
+
+        // is_payload*
+        while offset < body_end_offset {
+            self.assign(region, &wb, offset, assign_delta, &[AssignType::IsPayload], 1, None)?;
+            offset += 1;
+        }
+
+        self.assign(
+            region,
+            &wb,
+            offset - 1,
+            assign_delta,
+            &[AssignType::QLast],
+            1,
+            None,
+        )?;
+
+        Ok(offset)
+    }
+}
@@ -0,0 +1,220 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use eth_types::{Field, Hash};
+
+use crate::wasm_circuit::{
+    bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    common::WasmSharedStateAwareChip,
+    leb128::circuit::LEB128Chip,
+    sections::custom::body::circuit::WasmCustomSectionBodyChip,
+    tables::fixed_range::config::RangeTableConfig,
+    types::SharedState,
+    utf8::circuit::UTF8Chip,
+};
+
+#[derive(Default)]
+struct TestCircuit<'a, F> {
+    code_hash: Hash,
+    bytecode: &'a [u8],
+    offset_start: usize,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Clone)]
+struct TestCircuitConfig<F: Field> {
+    body_chip: Rc<WasmCustomSectionBodyChip<F>>,
+    wb_table: Rc<WasmBytecodeTable>,
+    range_table_config_0_128: Rc<RangeTableConfig<F, 0, 128>>,
+    _marker: PhantomData<F>,
+}
+
+impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
+    type Config = TestCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let func_count = cs.advice_column();
+        let error_code = cs.advice_column();
+        let body_byte_rev_index = cs.advice_column();
+
+        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+
+        let range_table_config_0_128 = Rc::new(RangeTableConfig::configure(cs));
+
+        let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
+        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+
+        let utf8_config =
+            UTF8Chip::<F>::configure(cs, range_table_config_0_128.clone(), &wb_table.value);
+        let utf8_chip = Rc::new(UTF8Chip::construct(utf8_config));
+
+        let config = WasmCustomSectionBodyChip::configure(
+            cs,
+            wb_table.clone(),
+            leb128_chip.clone(),
+            utf8_chip.clone(),
+            func_count,
+            shared_state.clone(),
+            body_byte_rev_index,
+            error_code,
+        );
+        let wasm_custom_section_body_chip = WasmCustomSectionBodyChip::construct(config);
+        let test_circuit_config = TestCircuitConfig {
+            body_chip: Rc::new(wasm_custom_section_body_chip),
+            wb_table: wb_table.clone(),
+            range_table_config_0_128: range_table_config_0_128.clone(),
+            _marker: Default::default(),
+        };
+
+        test_circuit_config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let wb = WasmBytecode::new(self.bytecode.to_vec().clone());
+        let assign_delta = 0;
+        layouter
+            .assign_region(
+                || format!("wasm bytecode table at {}", assign_delta),
+                |mut region| {
+                    config.wb_table.load(&mut region, &wb, assign_delta)?;
+                    Ok(())
+                },
+            )
+            .unwrap();
+        config.range_table_config_0_128.load(&mut layouter)?;
+        layouter.assign_region(
+            || "wasm_custom_section_body region",
+            |mut region| {
+                config.body_chip.shared_state().borrow_mut().reset();
+                config
+                    .body_chip
+                    .assign_auto(
+                        &mut region,
+                        &wb,
+                        self.offset_start,
+                        assign_delta,
+                        wb.bytes.len() - self.offset_start,
+                    )
+                    .unwrap();
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod wasm_custom_section_body_tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use log::debug;
+
+    use bus_mapping::state_db::CodeDB;
+    use eth_types::Field;
+
+    use crate::wasm_circuit::{
+        sections::custom::body::tests::TestCircuit, tests_helpers::custom_section_bytecode,
+    };
+
+    fn test<'a, F: Field>(test_circuit: TestCircuit<'_, F>, is_ok: bool) {
+        let k = 8;
+        let prover = MockProver::run(k, &test_circuit, vec![]).unwrap();
+        if is_ok {
+            prover.assert_satisfied();
+        } else {
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    fn custom_section_body_bytecode(name: &[u8], payload: &[u8]) -> Vec<u8> {
+        let section = custom_section_bytecode(name, payload);
+        // skip the section id and its single byte length
+        section[2..].to_vec()
+    }
+
+    #[test]
+    pub fn producers_section_ok() {
+        let bytecode = custom_section_body_bytecode(
+            b"producers",
+            b"\x01\x0cprocessed-by\x01\x05rustc\x061.70.0",
+        );
+        debug!("bytecode (len {}) hex {:x?}", bytecode.len(), bytecode);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn empty_payload_ok() {
+        let bytecode = custom_section_body_bytecode(b"target_features", &[]);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn empty_name_ok() {
+        let bytecode = custom_section_body_bytecode(b"", &[0xff, 0x00, 0x80]);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn name_with_invalid_bytes_fails() {
+        let bytecode = custom_section_body_bytecode(b"na\xffme", &[1, 2, 3]);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, false);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn name_beyond_section_end_fails() {
+        let mut bytecode = custom_section_body_bytecode(b"name", &[]);
+        bytecode[0] += 1;
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+}
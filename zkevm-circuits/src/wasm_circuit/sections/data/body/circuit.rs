@@ -36,7 +36,7 @@ use crate::{
         },
         types::{
             AssignDeltaType, AssignValueType, MemSegmentType, NewWbOffsetType, NumericInstruction,
-            SharedState,
+            SharedState, WasmModuleTag,
         },
     },
 };
@@ -62,6 +62,7 @@ pub struct WasmDataSectionBodyConfig<F: Field> {
     pub mem_segment_type: Column<Advice>,
     pub mem_segment_type_chip: Rc<BinaryNumberChip<F, MemSegmentType, 8>>,
 
+    wb_table: Rc<WasmBytecodeTable>,
     func_count: Column<Advice>,
     body_byte_rev_index: Column<Advice>,
     body_item_rev_count: Column<Advice>,
@@ -1011,6 +1012,52 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                 }
             );
 
+            // module view: a data segment is indexed by the segments before it and spans the
+            // bytes following its length
+            let module_index_expr = vc.query_advice(wb_table.module_index, Rotation::cur());
+            cb.condition(is_items_count_expr.clone(), |cb| {
+                cb.require_zero("is_items_count => module_index=0", module_index_expr.clone())
+            });
+            cb.condition(not::expr(is_items_count_expr.clone()), |cb| {
+                let is_mem_segment_len_last_byte_prev_expr = and::expr([
+                    vc.query_fixed(is_mem_segment_len, Rotation::prev()),
+                    vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::prev()),
+                ]);
+                cb.require_equal(
+                    "!is_items_count => module_index grows past every data segment",
+                    module_index_expr.clone(),
+                    vc.query_advice(wb_table.module_index, Rotation::prev())
+                        + is_mem_segment_len_last_byte_prev_expr,
+                )
+            });
+            cb.condition(
+                vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                |cb| {
+                    cb.require_equal(
+                        "module_tag => is_mem_segment_len",
+                        is_mem_segment_len_expr.clone(),
+                        1.expr(),
+                    );
+                    cb.require_equal(
+                        "module_tag => leb128_is_last_byte",
+                        leb128_is_last_byte_expr.clone(),
+                        1.expr(),
+                    );
+                    let segment_start_expr =
+                        vc.query_advice(wb_table.index, Rotation::cur()) + 1.expr();
+                    cb.require_equal(
+                        "module_tag => module_value=segment start",
+                        vc.query_advice(wb_table.module_value, Rotation::cur()),
+                        segment_start_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "module_tag => module_arg=segment start+segment len",
+                        vc.query_advice(wb_table.module_arg, Rotation::cur()),
+                        segment_start_expr + vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+                    );
+                },
+            );
+
             cb.gate(q_enable_expr.clone())
         });
 
@@ -1033,6 +1080,7 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
             dynamic_indexes_chip,
             mem_segment_type,
             mem_segment_type_chip,
+            wb_table,
             func_count,
             body_byte_rev_index,
             body_item_rev_count,
@@ -1091,6 +1139,8 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
         self.config.shared_state.borrow_mut().dynamic_indexes_offset = dynamic_indexes_offset;
         offset += items_count_leb_len;
 
+        // (last byte of the segment length, segment start, segment end) of every segment
+        let mut data_segments = Vec::with_capacity(items_count as usize);
         for _item_index in 0..items_count {
             body_item_rev_count -= 1;
             let item_start_offset = offset;
@@ -1205,6 +1255,11 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                     let mem_segment_len_last_byte_offset = offset + mem_segment_len_leb_len - 1;
                     let mem_segment_last_byte_offset =
                         mem_segment_len_last_byte_offset + mem_segment_len as usize;
+                    data_segments.push((
+                        mem_segment_len_last_byte_offset,
+                        mem_segment_len_last_byte_offset + 1,
+                        mem_segment_last_byte_offset + 1,
+                    ));
                     for offset in mem_segment_len_last_byte_offset..=mem_segment_last_byte_offset {
                         self.assign(
                             region,
@@ -1269,6 +1324,11 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                     let mem_segment_len_last_byte_offset = offset + mem_segment_len_leb_len - 1;
                     let mem_segment_last_byte_offset =
                         mem_segment_len_last_byte_offset + mem_segment_len as usize;
+                    data_segments.push((
+                        mem_segment_len_last_byte_offset,
+                        mem_segment_len_last_byte_offset + 1,
+                        mem_segment_last_byte_offset + 1,
+                    ));
                     for offset in mem_segment_len_last_byte_offset..=mem_segment_last_byte_offset {
                         self.assign(
                             region,
@@ -1418,6 +1478,12 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
                         assign_delta,
                         &[AssignType::IsMemSegmentLen, AssignType::IsMemSegmentTypeCtx],
                     )?;
+                    let mem_segment_len_last_byte_offset = offset + mem_segment_len_leb_len - 1;
+                    data_segments.push((
+                        mem_segment_len_last_byte_offset,
+                        mem_segment_len_last_byte_offset + 1,
+                        mem_segment_len_last_byte_offset + 1 + mem_segment_len as usize,
+                    ));
                     for offset in offset..offset + mem_segment_len_leb_len {
                         self.assign(
                             region,
@@ -1474,6 +1540,25 @@ impl<F: Field> WasmDataSectionBodyChip<F> {
             }
         }
 
+        let mut data_index = 0;
+        for wb_offset in wb_offset..offset {
+            let assign_offset = wb_offset + assign_delta;
+            let data_segment = data_segments.get(data_index);
+            let (tag, value, arg) = match data_segment {
+                Some(&(fact_offset, start, end)) if fact_offset == wb_offset => {
+                    (Some(WasmModuleTag::DataSegment), start as u64, end as u64)
+                }
+                _ => (None, 0, 0),
+            };
+            self.config
+                .wb_table
+                .assign_module_view(region, assign_offset, tag, data_index as u64, value, arg)
+                .map_err(remap_error_to_assign_at(assign_offset))?;
+            if tag.is_some() {
+                data_index += 1;
+            }
+        }
+
         if offset != wb_offset {
             self.assign(
                 region,
@@ -0,0 +1,162 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use eth_types::{Field, Hash};
+
+use crate::wasm_circuit::{
+    bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
+    leb128::circuit::LEB128Chip,
+    sections::datacount::body::circuit::WasmDataCountSectionBodyChip,
+    types::SharedState,
+};
+
+#[derive(Default)]
+struct TestCircuit<'a, F> {
+    code_hash: Hash,
+    bytecode: &'a [u8],
+    offset_start: usize,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Clone)]
+struct TestCircuitConfig<F: Field> {
+    datacount_section_body_chip: Rc<WasmDataCountSectionBodyChip<F>>,
+    wb_table: Rc<WasmBytecodeTable>,
+    _marker: PhantomData<F>,
+}
+
+impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
+    type Config = TestCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
+        let func_count = cs.advice_column();
+        let error_code = cs.advice_column();
+
+        let shared_state = Rc::new(RefCell::new(SharedState::default()));
+
+        let leb128_config = LEB128Chip::<F>::configure(cs, &wb_table.value);
+        let leb128_chip = Rc::new(LEB128Chip::construct(leb128_config));
+
+        let config = WasmDataCountSectionBodyChip::configure(
+            cs,
+            wb_table.clone(),
+            leb128_chip.clone(),
+            func_count,
+            shared_state.clone(),
+            error_code,
+        );
+        let wasm_datacount_section_body_chip =
+            Rc::new(WasmDataCountSectionBodyChip::construct(config));
+
+        let test_circuit_config = TestCircuitConfig {
+            datacount_section_body_chip: wasm_datacount_section_body_chip,
+            wb_table,
+            _marker: Default::default(),
+        };
+
+        test_circuit_config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let wb = WasmBytecode::new(self.bytecode.to_vec().clone());
+        let assign_delta = 0;
+        layouter
+            .assign_region(
+                || format!("wasm bytecode table at {}", assign_delta),
+                |mut region| {
+                    config.wb_table.load(&mut region, &wb, assign_delta)?;
+                    Ok(())
+                },
+            )
+            .unwrap();
+        layouter.assign_region(
+            || "wasm_datacount_section_body region",
+            |mut region| {
+                let mut offset_start = self.offset_start;
+                while offset_start < wb.bytes.len() {
+                    offset_start = config
+                        .datacount_section_body_chip
+                        .assign_auto(&mut region, &wb, offset_start, assign_delta)
+                        .unwrap();
+                }
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod wasm_datacount_section_body_tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use bus_mapping::state_db::CodeDB;
+    use eth_types::Field;
+
+    use crate::wasm_circuit::sections::datacount::body::tests::TestCircuit;
+
+    fn test<'a, F: Field>(test_circuit: TestCircuit<'_, F>, is_ok: bool) {
+        let k = 8;
+        let prover = MockProver::run(k, &test_circuit, vec![]).unwrap();
+        if is_ok {
+            prover.assert_satisfied();
+        } else {
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    pub fn single_byte_count_ok() {
+        let bytecode = [3];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn multi_byte_count_ok() {
+        let bytecode = [0x80, 0x01];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn dup_fails() {
+        let bytecode = [3, 3];
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            _marker: Default::default(),
+        };
+        test(test_circuit, false);
+    }
+}
@@ -0,0 +1,9 @@
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AssignType {
+    QFirst,
+    QLast,
+
+    IsCount,
+
+    ErrorCode,
+}
@@ -28,7 +28,7 @@ use crate::{
             consts::LebParams,
             element::body::{consts::ElementType, types::AssignType},
         },
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState},
+        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState, WasmModuleTag},
     },
 };
 
@@ -53,6 +53,7 @@ pub struct WasmElementSectionBodyConfig<F: Field> {
 
     pub leb128_chip: Rc<LEB128Chip<F>>,
 
+    wb_table: Rc<WasmBytecodeTable>,
     pub func_count: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
@@ -739,6 +740,64 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                 &[is_func_idx, is_elem_type],
             );
 
+            // module view: the function indexes of an active segment fill the table slots from
+            // the offset of the segment on
+            let module_tag_expr = vc.query_fixed(wb_table.module_tag, Rotation::cur());
+            let module_index_expr = vc.query_advice(wb_table.module_index, Rotation::cur());
+            let module_index_prev_expr = vc.query_advice(wb_table.module_index, Rotation::prev());
+            let module_value_expr = vc.query_advice(wb_table.module_value, Rotation::cur());
+            cb.condition(
+                and::expr([elem_type_is_0_expr.clone(), is_numeric_instruction_leb_arg_expr.clone()]),
+                |cb| {
+                    cb.require_equal(
+                        "elem_type_is_0 && is_numeric_instruction_leb_arg => module_index=offset",
+                        module_index_expr.clone(),
+                        leb128_sn_expr.clone(),
+                    );
+                }
+            );
+            cb.condition(
+                and::expr([
+                    elem_type_is_0_expr.clone(),
+                    or::expr([is_block_end_expr.clone(), is_funcs_idx_count_expr.clone()]),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "elem_type_is_0 && (is_block_end || is_funcs_idx_count) => module_index=prev.module_index",
+                        module_index_expr.clone(),
+                        module_index_prev_expr.clone(),
+                    );
+                }
+            );
+            cb.condition(
+                and::expr([elem_type_is_0_expr.clone(), is_func_idx_expr.clone()]),
+                |cb| {
+                    let is_func_idx_prev_expr = vc.query_fixed(is_func_idx, Rotation::prev());
+                    let leb128_is_first_byte_prev_expr = vc.query_fixed(leb128_chip.config.is_first_byte, Rotation::prev());
+                    cb.require_equal(
+                        "elem_type_is_0 && is_func_idx => module_index grows past every func_idx",
+                        module_index_expr.clone(),
+                        module_index_prev_expr.clone()
+                            + and::expr([is_func_idx_prev_expr, leb128_is_first_byte_prev_expr]),
+                    );
+                }
+            );
+            cb.condition(
+                module_tag_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "module_tag => elem_type_is_0 && is_func_idx",
+                        and::expr([elem_type_is_0_expr.clone(), is_func_idx_expr.clone()]),
+                        1.expr(),
+                    );
+                    cb.require_equal(
+                        "module_tag => module_value=func_idx+1",
+                        module_value_expr.clone(),
+                        leb128_sn_expr.clone() + 1.expr(),
+                    );
+                }
+            );
+
             cb.gate(q_enable_expr.clone())
         });
 
@@ -760,6 +819,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
             elem_type,
             elem_type_chip,
             leb128_chip,
+            wb_table,
             func_count,
             body_item_rev_count,
             error_code,
@@ -769,6 +829,20 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
         config
     }
 
+    fn assign_module_view(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        tag: Option<WasmModuleTag>,
+        index: u64,
+        value: u64,
+    ) -> Result<(), Error> {
+        self.config
+            .wb_table
+            .assign_module_view(region, assign_offset, tag, index, value, 0)
+            .map_err(remap_error_to_assign_at(assign_offset))
+    }
+
     pub fn assign_auto(
         &self,
         region: &mut Region<F>,
@@ -863,7 +937,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                     offset += 1;
 
                     // numeric_instruction_leb_arg+
-                    let (_numeric_instruction_leb_arg, numeric_instruction_leb_arg_leb_len) = self
+                    let (numeric_instruction_leb_arg, numeric_instruction_leb_arg_leb_len) = self
                         .markup_leb_section(
                             region,
                             wb,
@@ -874,6 +948,8 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                                 AssignType::IsElemTypeCtx,
                             ],
                         )?;
+                    // the slot the next function index fills, carried over to it
+                    let mut slot = numeric_instruction_leb_arg;
                     for offset in offset..offset + numeric_instruction_leb_arg_leb_len {
                         self.assign(
                             region,
@@ -884,6 +960,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                             elem_type_val,
                             None,
                         )?;
+                        self.assign_module_view(region, offset + assign_delta, None, slot, 0)?;
                     }
                     offset += numeric_instruction_leb_arg_leb_len;
 
@@ -906,6 +983,7 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                         elem_type_val,
                         None,
                     )?;
+                    self.assign_module_view(region, offset + assign_delta, None, slot, 0)?;
                     offset += 1;
 
                     // funcs_idx_count+
@@ -926,12 +1004,13 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                             elem_type_val,
                             None,
                         )?;
+                        self.assign_module_view(region, offset + assign_delta, None, slot, 0)?;
                     }
                     offset += funcs_idx_count_leb_len;
 
                     for _funcs_idx_index in 0..funcs_idx_count {
                         // func_idx+
-                        let (_func_idx, func_idx_leb_len) = self.markup_leb_section(
+                        let (func_idx, func_idx_leb_len) = self.markup_leb_section(
                             region,
                             wb,
                             offset,
@@ -949,6 +1028,17 @@ impl<F: Field> WasmElementSectionBodyChip<F> {
                                 None,
                             )?;
                         }
+                        self.assign_module_view(
+                            region,
+                            offset + assign_delta,
+                            Some(WasmModuleTag::Element),
+                            slot,
+                            func_idx + 1,
+                        )?;
+                        slot += 1;
+                        for offset in offset + 1..offset + func_idx_leb_len {
+                            self.assign_module_view(region, offset + assign_delta, None, slot, 0)?;
+                        }
                         offset += func_idx_leb_len;
                     }
                 }
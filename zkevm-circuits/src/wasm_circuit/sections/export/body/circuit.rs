@@ -23,12 +23,16 @@ use crate::{
             WasmFuncCountAwareChip, WasmLenPrefixedBytesSpanAwareChip,
             WasmMarkupLeb128SectionAwareChip, WasmNameAwareChip, WasmSharedStateAwareChip,
         },
+        consts::ENTRY_FUNCTION_NAME,
         error::{
             remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
         },
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, export::body::types::AssignType},
-        types::{AssignDeltaType, AssignValueType, ExportDescType, NewWbOffsetType, SharedState},
+        types::{
+            AssignDeltaType, AssignValueType, ExportDescType, NewWbOffsetType, SharedState,
+            WasmModuleTag,
+        },
     },
 };
 
@@ -45,6 +49,7 @@ pub struct WasmExportSectionBodyConfig<F: Field> {
     pub is_exportdesc_val: Column<Fixed>,
 
     pub leb128_chip: Rc<LEB128Chip<F>>,
+    wb_table: Rc<WasmBytecodeTable>,
     pub exportdesc_type: Column<Advice>,
     pub exportdesc_type_chip: Rc<BinaryNumberChip<F, ExportDescType, 8>>,
 
@@ -421,6 +426,8 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
 
             let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
 
+            let module_tag_expr = vc.query_fixed(wb_table.module_tag, Rotation::cur());
+
             cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
             cb.require_boolean("is_export_name_len is boolean", is_export_name_len_expr.clone());
@@ -599,6 +606,63 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
                 }
             );
 
+            // module view: the function exported as `main`, its name is the 4 bytes before the
+            // exportdesc type
+            cb.condition(
+                module_tag_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "module_tag => is_exportdesc_val",
+                        is_exportdesc_val_expr.clone(),
+                        1.expr(),
+                    );
+                    cb.require_equal(
+                        "module_tag => leb128.is_first_byte",
+                        vc.query_fixed(leb128_chip.config.is_first_byte, Rotation::cur()),
+                        1.expr(),
+                    );
+                    cb.require_equal(
+                        "module_tag => exportdesc_type=Funcidx",
+                        exportdesc_type_expr.clone(),
+                        ExportDescType::Funcidx.expr(),
+                    );
+                    cb.require_zero(
+                        "module_tag => module_index=0",
+                        vc.query_advice(wb_table.module_index, Rotation::cur()),
+                    );
+                    cb.require_equal(
+                        "module_tag => module_value=funcidx",
+                        vc.query_advice(wb_table.module_value, Rotation::cur()),
+                        vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+                    );
+                    let name_len_rotation = Rotation(-2 - ENTRY_FUNCTION_NAME.len() as i32);
+                    cb.require_equal(
+                        "module_tag => name len is a single leb byte",
+                        vc.query_fixed(is_export_name_len, name_len_rotation)
+                            * vc.query_fixed(leb128_chip.config.is_first_byte, name_len_rotation),
+                        1.expr(),
+                    );
+                    cb.require_equal(
+                        "module_tag => name len is the entry function name len",
+                        vc.query_advice(wb_table.value, name_len_rotation),
+                        ENTRY_FUNCTION_NAME.len().expr(),
+                    );
+                    for (i, byte) in ENTRY_FUNCTION_NAME.iter().enumerate() {
+                        let rotation = Rotation(name_len_rotation.0 + 1 + i as i32);
+                        cb.require_equal(
+                            "module_tag => is_export_name",
+                            vc.query_fixed(is_export_name, rotation),
+                            1.expr(),
+                        );
+                        cb.require_equal(
+                            "module_tag => name is the entry function name",
+                            vc.query_advice(wb_table.value, rotation),
+                            byte.expr(),
+                        );
+                    }
+                }
+            );
+
             cb.gate(q_enable_expr.clone())
         });
 
@@ -615,6 +679,7 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
             is_exportdesc_type_ctx,
             is_exportdesc_val,
             leb128_chip,
+            wb_table,
             exportdesc_type,
             exportdesc_type_chip,
             func_count,
@@ -740,13 +805,30 @@ impl<F: Field> WasmExportSectionBodyChip<F> {
                 | ExportDescType::Tableidx
                 | ExportDescType::Memidx
                 | ExportDescType::Globalidx => {
-                    let (_exportdesc_val, exportdesc_val_leb_len) = self.markup_leb_section(
+                    let (exportdesc_val, exportdesc_val_leb_len) = self.markup_leb_section(
                         region,
                         wb,
                         offset,
                         assign_delta,
                         &[AssignType::IsExportdescVal, AssignType::IsExportdescTypeCtx],
                     )?;
+                    if exportdesc_type == ExportDescType::Funcidx
+                        && &wb.bytes[export_name_new_offset - export_name_len as usize
+                            ..export_name_new_offset]
+                            == ENTRY_FUNCTION_NAME
+                    {
+                        self.config
+                            .wb_table
+                            .assign_module_view(
+                                region,
+                                offset + assign_delta,
+                                Some(WasmModuleTag::EntryFunction),
+                                0,
+                                exportdesc_val,
+                                0,
+                            )
+                            .map_err(remap_error_to_assign_at(offset + assign_delta))?;
+                    }
                     for offset in offset..offset + exportdesc_val_leb_len {
                         self.assign(
                             region,
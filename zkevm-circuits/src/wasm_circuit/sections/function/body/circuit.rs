@@ -22,7 +22,7 @@ use crate::{
         error::{remap_error_to_assign_at, Error},
         leb128::circuit::LEB128Chip,
         sections::{consts::LebParams, function::body::types::AssignType},
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState},
+        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, SharedState, WasmModuleTag},
     },
 };
 
@@ -36,6 +36,7 @@ pub struct WasmFunctionSectionBodyConfig<F: Field> {
 
     pub leb128_chip: Rc<LEB128Chip<F>>,
 
+    wb_table: Rc<WasmBytecodeTable>,
     func_count: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
@@ -203,7 +204,7 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
 
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
-        _wb_table: Rc<WasmBytecodeTable>,
+        wb_table: Rc<WasmBytecodeTable>,
         leb128_chip: Rc<LEB128Chip<F>>,
         func_count: Column<Advice>,
         shared_state: Rc<RefCell<SharedState>>,
@@ -258,6 +259,11 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
 
             let leb128_is_last_byte_expr =
                 vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
+            let leb128_sn_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
+            let func_count_expr = vc.query_advice(func_count, Rotation::cur());
+            let module_tag_expr = vc.query_fixed(wb_table.module_tag, Rotation::cur());
+            let module_index_expr = vc.query_advice(wb_table.module_index, Rotation::cur());
+            let module_value_expr = vc.query_advice(wb_table.module_value, Rotation::cur());
 
             cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
@@ -290,6 +296,35 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
                 },
             );
 
+            // module view: declared functions are indexed after the imported ones
+            cb.condition(is_items_count_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_items_count => module_index=func_count",
+                    module_index_expr.clone(),
+                    func_count_expr.clone(),
+                )
+            });
+            cb.condition(is_typeidx_expr.clone(), |cb| {
+                let module_index_prev_expr =
+                    vc.query_advice(wb_table.module_index, Rotation::prev());
+                let is_typeidx_prev_expr = vc.query_fixed(is_typeidx, Rotation::prev());
+                let leb128_is_first_byte_prev_expr =
+                    vc.query_fixed(leb128_chip.config.is_first_byte, Rotation::prev());
+                cb.require_equal(
+                    "is_typeidx => module_index grows past every type index",
+                    module_index_expr.clone(),
+                    module_index_prev_expr
+                        + and::expr([is_typeidx_prev_expr, leb128_is_first_byte_prev_expr]),
+                )
+            });
+            cb.condition(module_tag_expr.clone(), |cb| {
+                cb.require_equal(
+                    "module_tag => module_value=typeidx",
+                    module_value_expr.clone(),
+                    leb128_sn_expr.clone(),
+                )
+            });
+
             // is_items_count+ -> is_typeidx+
             configure_transition_check(
                 &mut cb,
@@ -344,6 +379,7 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
             is_items_count,
             is_typeidx,
             leb128_chip,
+            wb_table,
             func_count,
             body_item_rev_count,
             error_code,
@@ -353,6 +389,20 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
         config
     }
 
+    fn assign_module_view(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        tag: Option<WasmModuleTag>,
+        index: u64,
+        value: u64,
+    ) -> Result<(), Error> {
+        self.config
+            .wb_table
+            .assign_module_view(region, assign_offset, tag, index, value, 0)
+            .map_err(remap_error_to_assign_at(assign_offset))
+    }
+
     pub fn assign_auto(
         &self,
         region: &mut Region<F>,
@@ -370,6 +420,7 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
             &[AssignType::IsItemsCount],
         )?;
         let mut body_item_rev_count = items_count;
+        let mut func_index = self.config.shared_state.borrow().func_count as u64;
         for offset in offset..offset + items_count_leb_len {
             self.assign(
                 region,
@@ -380,6 +431,7 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
                 body_item_rev_count,
                 None,
             )?;
+            self.assign_module_view(region, offset + assign_delta, None, func_index, 0)?;
         }
         self.assign(
             region,
@@ -396,13 +448,24 @@ impl<F: Field> WasmFunctionSectionBodyChip<F> {
             body_item_rev_count -= 1;
             let item_start_offset = offset;
 
-            let (_typeidx_val, typeidx_val_leb_len) = self.markup_leb_section(
+            let (typeidx_val, typeidx_val_leb_len) = self.markup_leb_section(
                 region,
                 wb,
                 offset,
                 assign_delta,
                 &[AssignType::IsTypeidx],
             )?;
+            self.assign_module_view(
+                region,
+                offset + assign_delta,
+                Some(WasmModuleTag::FunctionType),
+                func_index,
+                typeidx_val,
+            )?;
+            func_index += 1;
+            for offset in offset + 1..offset + typeidx_val_leb_len {
+                self.assign_module_view(region, offset + assign_delta, None, func_index, 0)?;
+            }
             offset += typeidx_val_leb_len;
 
             for offset in item_start_offset..offset {
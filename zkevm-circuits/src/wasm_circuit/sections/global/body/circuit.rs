@@ -263,6 +263,9 @@ impl<F: Field> WasmAssignAwareChip<F> for WasmGlobalSectionBodyChip<F> {
                             || Value::known(F::from(assign_value)),
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
+                    if NumType::is_float_value(assign_value as u8) {
+                        return Err(Error::FloatUnsupportedAt(assign_offset));
+                    }
                     let global_type: NumType = (assign_value as u8)
                         .try_into()
                         .map_err(remap_error_to_invalid_enum_value_at(assign_offset))?;
@@ -29,12 +29,24 @@ use crate::{
         error::{
             remap_error, remap_error_to_assign_at, remap_error_to_invalid_enum_value_at, Error,
         },
-        leb128::circuit::LEB128Chip,
+        leb128::{circuit::LEB128Chip, helpers::leb128_compute_sn},
         sections::{consts::LebParams, import::body::types::AssignType},
-        tables::dynamic_indexes::circuit::DynamicIndexesChip,
+        tables::{
+            dynamic_indexes::circuit::DynamicIndexesChip,
+            host_functions::{
+                config::HostFunctionsTableConfig,
+                types::{is_host_function, name_acc_value},
+            },
+            module_types::{
+                circuit::ModuleTypesChip,
+                types::{AssignType as ModuleTypesAssignType, LookupArgsParams},
+            },
+            type_stack::types::TypeSeq,
+        },
         types::{
             AssignDeltaType, AssignValueType, ImportDescType, LimitType, NewWbOffsetType, RefType,
-            SharedState, IMPORT_DESC_TYPE_VALUES, MUTABILITY_VALUES, REF_TYPE_VALUES,
+            SharedState, WasmModuleTag, IMPORT_DESC_TYPE_VALUES, MUTABILITY_VALUES,
+            REF_TYPE_VALUES,
         },
         utf8::circuit::UTF8Chip,
     },
@@ -64,7 +76,17 @@ pub struct WasmImportSectionBodyConfig<F: Field> {
     pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
     pub importdesc_type: Column<Advice>,
     pub importdesc_type_chip: Rc<BinaryNumberChip<F, ImportDescType, 8>>,
-
+    pub mod_name_len: Column<Advice>,
+    pub mod_name_acc: Column<Advice>,
+    pub import_name_len: Column<Advice>,
+    pub import_name_acc: Column<Advice>,
+    pub func_params: Column<Advice>,
+    pub func_params_count: Column<Advice>,
+    pub func_results: Column<Advice>,
+    pub func_results_count: Column<Advice>,
+    pub module_types_chip: Rc<ModuleTypesChip<F>>,
+
+    wb_table: Rc<WasmBytecodeTable>,
     func_count: Column<Advice>,
     body_byte_rev_index: Column<Advice>,
     body_item_rev_count: Column<Advice>,
@@ -498,7 +520,11 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
         body_byte_rev_index: Column<Advice>,
         body_item_rev_count: Column<Advice>,
         error_code: Column<Advice>,
+        bytecode_number: Column<Advice>,
+        module_types_chip: Rc<ModuleTypesChip<F>>,
+        host_functions_table_config: &HostFunctionsTableConfig<F>,
     ) -> WasmImportSectionBodyConfig<F> {
+        let host_imports_restricted = shared_state.borrow().host_imports_restricted;
         let q_enable = cs.fixed_column();
         let q_first = cs.fixed_column();
         let q_last = cs.fixed_column();
@@ -520,6 +546,18 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             BinaryNumberChip::configure(cs, is_importdesc_type_ctx, Some(importdesc_type.into()));
         let importdesc_type_chip = Rc::new(BinaryNumberChip::construct(config));
 
+        // module and import names with their byte lengths and their bytes packed big-endian,
+        // carried to the importdesc type where the import is looked up among the host functions
+        let mod_name_len = cs.advice_column();
+        let mod_name_acc = cs.advice_column();
+        let import_name_len = cs.advice_column();
+        let import_name_acc = cs.advice_column();
+        // signature of the type the imported function refers to
+        let func_params = cs.advice_column();
+        let func_params_count = cs.advice_column();
+        let func_results = cs.advice_column();
+        let func_results_count = cs.advice_column();
+
         let limit_type_fields =
             Self::construct_limit_type_fields(cs, q_enable, leb128_chip.as_ref());
         Self::configure_limit_type_constraints(
@@ -650,9 +688,18 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             let byte_val_expr = vc.query_advice(wb_table.value, Rotation::cur());
             let importdesc_type_prev_expr = vc.query_advice(importdesc_type, Rotation::prev());
             let importdesc_type_expr = vc.query_advice(importdesc_type, Rotation::cur());
+            let mod_name_len_prev_expr = vc.query_advice(mod_name_len, Rotation::prev());
+            let mod_name_len_expr = vc.query_advice(mod_name_len, Rotation::cur());
+            let mod_name_acc_prev_expr = vc.query_advice(mod_name_acc, Rotation::prev());
+            let mod_name_acc_expr = vc.query_advice(mod_name_acc, Rotation::cur());
+            let import_name_len_prev_expr = vc.query_advice(import_name_len, Rotation::prev());
+            let import_name_len_expr = vc.query_advice(import_name_len, Rotation::cur());
+            let import_name_acc_prev_expr = vc.query_advice(import_name_acc, Rotation::prev());
+            let import_name_acc_expr = vc.query_advice(import_name_acc, Rotation::cur());
 
             let utf8_chip_q_enabled_expr = vc.query_fixed(utf8_chip.config.q_enable, Rotation::cur());
             let leb128_is_last_byte_expr = vc.query_fixed(leb128_chip.config.is_last_byte, Rotation::cur());
+            let leb128_sn_expr = vc.query_advice(leb128_chip.config.sn, Rotation::cur());
 
             cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
             cb.require_boolean("is_items_count is boolean", is_items_count_expr.clone());
@@ -760,6 +807,107 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 }
             );
 
+            cb.condition(
+                is_mod_name_len_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_mod_name_len => mod_name_len=sn",
+                        mod_name_len_expr.clone(),
+                        leb128_sn_expr.clone(),
+                    );
+                    cb.require_zero(
+                        "is_mod_name_len => mod_name_acc=0",
+                        mod_name_acc_expr.clone(),
+                    );
+                }
+            );
+            cb.condition(
+                is_mod_name_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_mod_name => mod_name_acc=prev.mod_name_acc*256+byte_val",
+                        mod_name_acc_expr.clone(),
+                        mod_name_acc_prev_expr.clone() * 256.expr() + byte_val_expr.clone(),
+                    )
+                }
+            );
+            cb.condition(
+                or::expr([
+                    is_mod_name_expr.clone(),
+                    is_import_name_len_expr.clone(),
+                    is_import_name_expr.clone(),
+                    is_importdesc_type_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_mod_name || is_import_name_len || is_import_name || is_importdesc_type => mod_name_len=prev.mod_name_len",
+                        mod_name_len_expr.clone(),
+                        mod_name_len_prev_expr.clone(),
+                    )
+                }
+            );
+            cb.condition(
+                or::expr([
+                    is_import_name_len_expr.clone(),
+                    is_import_name_expr.clone(),
+                    is_importdesc_type_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_import_name_len || is_import_name || is_importdesc_type => mod_name_acc=prev.mod_name_acc",
+                        mod_name_acc_expr.clone(),
+                        mod_name_acc_prev_expr.clone(),
+                    )
+                }
+            );
+            cb.condition(
+                is_import_name_len_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_import_name_len => import_name_len=sn",
+                        import_name_len_expr.clone(),
+                        leb128_sn_expr.clone(),
+                    );
+                    cb.require_zero(
+                        "is_import_name_len => import_name_acc=0",
+                        import_name_acc_expr.clone(),
+                    );
+                }
+            );
+            cb.condition(
+                is_import_name_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_import_name => import_name_acc=prev.import_name_acc*256+byte_val",
+                        import_name_acc_expr.clone(),
+                        import_name_acc_prev_expr.clone() * 256.expr() + byte_val_expr.clone(),
+                    )
+                }
+            );
+            cb.condition(
+                or::expr([
+                    is_import_name_expr.clone(),
+                    is_importdesc_type_expr.clone(),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "is_import_name || is_importdesc_type => import_name_len=prev.import_name_len",
+                        import_name_len_expr.clone(),
+                        import_name_len_prev_expr.clone(),
+                    )
+                }
+            );
+            cb.condition(
+                is_importdesc_type_expr.clone(),
+                |cb| {
+                    cb.require_equal(
+                        "is_importdesc_type => import_name_acc=prev.import_name_acc",
+                        import_name_acc_expr.clone(),
+                        import_name_acc_prev_expr.clone(),
+                    )
+                }
+            );
+
             cb.require_equal(
                 "is_mod_name || is_import_name -> utf8",
                 or::expr([
@@ -1507,12 +1655,127 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                         importdesc_type_expr.clone(),
                         byte_val_expr.clone(),
                     );
+                    if host_imports_restricted {
+                        cb.require_equal(
+                            "is_importdesc_type => only functions are imported",
+                            byte_val_expr.clone(),
+                            (ImportDescType::Typeidx as u64).expr(),
+                        );
+                    }
+                }
+            );
+
+            // module view: an imported function takes the index func_count was raised to at
+            // its importdesc type
+            cb.condition(
+                vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                |cb| {
+                    cb.require_equal(
+                        "module_tag => module_index+1=func_count",
+                        vc.query_advice(wb_table.module_index, Rotation::cur()) + 1.expr(),
+                        vc.query_advice(func_count, Rotation::cur()),
+                    );
+                    cb.require_equal(
+                        "module_tag => module_value=importdesc_val",
+                        vc.query_advice(wb_table.module_value, Rotation::cur()),
+                        leb128_sn_expr.clone(),
+                    );
                 }
             );
 
             cb.gate(q_enable_expr.clone())
         });
 
+        // every import must be a host function with the signature the host provides
+        if host_imports_restricted {
+            cs.lookup("import section: every import is a host function", |vc| {
+                let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                    vc,
+                    q_enable,
+                    &shared_state.borrow(),
+                    error_code,
+                );
+                let cond_expr = q_enable_expr * vc.query_fixed(is_importdesc_type, Rotation::cur());
+                let t = host_functions_table_config;
+
+                vec![
+                    (
+                        cond_expr.clone() * vc.query_advice(mod_name_len, Rotation::cur()),
+                        t.mod_name_len,
+                    ),
+                    (
+                        cond_expr.clone() * vc.query_advice(mod_name_acc, Rotation::cur()),
+                        t.mod_name_acc,
+                    ),
+                    (
+                        cond_expr.clone() * vc.query_advice(import_name_len, Rotation::cur()),
+                        t.import_name_len,
+                    ),
+                    (
+                        cond_expr.clone() * vc.query_advice(import_name_acc, Rotation::cur()),
+                        t.import_name_acc,
+                    ),
+                    (
+                        cond_expr.clone() * vc.query_advice(func_params, Rotation::cur()),
+                        t.params,
+                    ),
+                    (
+                        cond_expr.clone() * vc.query_advice(func_params_count, Rotation::cur()),
+                        t.params_count,
+                    ),
+                    (
+                        cond_expr.clone() * vc.query_advice(func_results, Rotation::cur()),
+                        t.results,
+                    ),
+                    (
+                        cond_expr.clone() * vc.query_advice(func_results_count, Rotation::cur()),
+                        t.results_count,
+                    ),
+                ]
+            });
+
+            module_types_chip.lookup_args(
+                "import section: imported func signature is the signature of its type",
+                cs,
+                |vc| {
+                    let q_enable_expr = Self::get_selector_expr_enriched_with_error_processing(
+                        vc,
+                        q_enable,
+                        &shared_state.borrow(),
+                        error_code,
+                    );
+
+                    LookupArgsParams {
+                        cond: q_enable_expr * vc.query_fixed(is_importdesc_type, Rotation::cur()),
+                        bytecode_number: vc.query_advice(bytecode_number, Rotation::cur()),
+                        args: vec![
+                            (ModuleTypesAssignType::IsType, 1.expr()),
+                            (
+                                ModuleTypesAssignType::Index,
+                                vc.query_advice(leb128_chip.config.sn, Rotation::next()),
+                            ),
+                            (
+                                ModuleTypesAssignType::Params,
+                                vc.query_advice(func_params, Rotation::cur()),
+                            ),
+                            (
+                                ModuleTypesAssignType::ParamsCount,
+                                vc.query_advice(func_params_count, Rotation::cur()),
+                            ),
+                            (
+                                ModuleTypesAssignType::Results,
+                                vc.query_advice(func_results, Rotation::cur()),
+                            ),
+                            (
+                                ModuleTypesAssignType::ResultsCount,
+                                vc.query_advice(func_results_count, Rotation::cur()),
+                            ),
+                        ],
+                    }
+                },
+            );
+        }
+
         let config = WasmImportSectionBodyConfig::<F> {
             _marker: PhantomData,
 
@@ -1535,6 +1798,16 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
             dynamic_indexes_chip,
             importdesc_type,
             importdesc_type_chip,
+            mod_name_len,
+            mod_name_acc,
+            import_name_len,
+            import_name_acc,
+            func_params,
+            func_params_count,
+            func_results,
+            func_results_count,
+            module_types_chip,
+            wb_table,
             func_count,
             body_byte_rev_index,
             body_item_rev_count,
@@ -1545,6 +1818,32 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
         config
     }
 
+    /// Assigns the name length and the packed name prefix seen so far.
+    fn assign_name_acc(
+        &self,
+        region: &mut Region<F>,
+        assign_offset: usize,
+        (len_col, acc_col): (Column<Advice>, Column<Advice>),
+        name_len: usize,
+        name_prefix: &[u8],
+    ) -> Result<(), Error> {
+        for (column, name, value) in [
+            (len_col, "name_len", F::from(name_len as u64)),
+            (acc_col, "name_acc", name_acc_value(name_prefix)),
+        ] {
+            region
+                .assign_advice(
+                    || format!("assign '{}' at {}", name, assign_offset),
+                    column,
+                    assign_offset,
+                    || Value::known(value),
+                )
+                .map_err(remap_error_to_assign_at(assign_offset))?;
+        }
+
+        Ok(())
+    }
+
     pub fn assign_auto(
         &self,
         region: &mut Region<F>,
@@ -1610,6 +1909,18 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                     None,
                 )?;
             }
+            let mod_name_cols = (self.config.mod_name_len, self.config.mod_name_acc);
+            let import_name_cols = (self.config.import_name_len, self.config.import_name_acc);
+            let mod_name = &wb.bytes[offset + mod_name_leb_len..mod_name_last_byte_offset + 1];
+            for offset in offset..offset + mod_name_leb_len {
+                self.assign_name_acc(
+                    region,
+                    offset + assign_delta,
+                    mod_name_cols,
+                    mod_name.len(),
+                    &[],
+                )?;
+            }
             offset += mod_name_leb_len;
 
             // is_mod_name*
@@ -1622,6 +1933,16 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 mod_name_len as usize,
                 1,
             )?;
+            for prefix_len in 1..=mod_name.len() {
+                let assign_offset = offset + prefix_len - 1 + assign_delta;
+                self.assign_name_acc(
+                    region,
+                    assign_offset,
+                    mod_name_cols,
+                    mod_name.len(),
+                    &mod_name[..prefix_len],
+                )?;
+            }
             offset += mod_name_len as usize;
 
             // is_import_name_len+
@@ -1646,6 +1967,24 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                     None,
                 )?;
             }
+            let import_name =
+                &wb.bytes[offset + import_name_leb_len..import_name_last_byte_offset + 1];
+            for offset in offset..offset + import_name_leb_len {
+                self.assign_name_acc(
+                    region,
+                    offset + assign_delta,
+                    mod_name_cols,
+                    mod_name.len(),
+                    mod_name,
+                )?;
+                self.assign_name_acc(
+                    region,
+                    offset + assign_delta,
+                    import_name_cols,
+                    import_name.len(),
+                    &[],
+                )?;
+            }
             offset += import_name_leb_len;
 
             // is_import_name*
@@ -1658,6 +1997,23 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 import_name_len as usize,
                 1,
             )?;
+            for prefix_len in 1..=import_name.len() {
+                let assign_offset = offset + prefix_len - 1 + assign_delta;
+                self.assign_name_acc(
+                    region,
+                    assign_offset,
+                    mod_name_cols,
+                    mod_name.len(),
+                    mod_name,
+                )?;
+                self.assign_name_acc(
+                    region,
+                    assign_offset,
+                    import_name_cols,
+                    import_name.len(),
+                    &import_name[..prefix_len],
+                )?;
+            }
             offset += import_name_len as usize;
 
             // is_importdesc_type{1}
@@ -1695,12 +2051,81 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                 .importdesc_type_chip
                 .assign(region, offset + assign_delta, &importdesc_type)
                 .map_err(remap_error(Error::FatalAssignExternalChip))?;
+            self.assign_name_acc(
+                region,
+                offset + assign_delta,
+                mod_name_cols,
+                mod_name.len(),
+                mod_name,
+            )?;
+            self.assign_name_acc(
+                region,
+                offset + assign_delta,
+                import_name_cols,
+                import_name.len(),
+                import_name,
+            )?;
+            let func_type = if importdesc_type == ImportDescType::Typeidx {
+                let (type_index, _) = leb128_compute_sn(&wb.bytes, false, offset + 1)?;
+                self.config
+                    .module_types_chip
+                    .config
+                    .module_types
+                    .borrow()
+                    .types
+                    .get(type_index as usize)
+                    .cloned()
+            } else {
+                None
+            };
+            if let Some(func_type) = &func_type {
+                let params = TypeSeq::new(&func_type.params);
+                let results = TypeSeq::new(&func_type.results);
+                let assign_offset = offset + assign_delta;
+                for (column, name, value) in [
+                    (
+                        self.config.func_params,
+                        "func_params",
+                        F::from_u128(params.packed),
+                    ),
+                    (
+                        self.config.func_params_count,
+                        "func_params_count",
+                        F::from(params.count),
+                    ),
+                    (
+                        self.config.func_results,
+                        "func_results",
+                        F::from_u128(results.packed),
+                    ),
+                    (
+                        self.config.func_results_count,
+                        "func_results_count",
+                        F::from(results.count),
+                    ),
+                ] {
+                    region
+                        .assign_advice(
+                            || format!("assign '{}' at {}", name, assign_offset),
+                            column,
+                            assign_offset,
+                            || Value::known(value),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+            }
+            let is_allowed = func_type.map_or(false, |func_type| {
+                is_host_function(mod_name, import_name, &func_type)
+            });
+            if self.config.shared_state.borrow().host_imports_restricted && !is_allowed {
+                return Err(Error::ImportForbiddenAt(offset + assign_delta));
+            }
             offset += 1;
 
             // is_importdesc_val+
             match importdesc_type {
                 ImportDescType::Typeidx => {
-                    let (_importdesc_val, importdesc_val_leb_len) = self.markup_leb_section(
+                    let (importdesc_val, importdesc_val_leb_len) = self.markup_leb_section(
                         region,
                         wb,
                         offset,
@@ -1711,6 +2136,18 @@ impl<F: Field> WasmImportSectionBodyChip<F> {
                             AssignType::FuncCount,
                         ],
                     )?;
+                    let func_index = self.config.shared_state.borrow().func_count as u64 - 1;
+                    self.config
+                        .wb_table
+                        .assign_module_view(
+                            region,
+                            offset + assign_delta,
+                            Some(WasmModuleTag::FunctionType),
+                            func_index,
+                            importdesc_val,
+                            0,
+                        )
+                        .map_err(remap_error_to_assign_at(offset + assign_delta))?;
                     for offset in offset..offset + importdesc_val_leb_len {
                         self.assign(
                             region,
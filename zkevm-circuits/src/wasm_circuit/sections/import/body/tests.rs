@@ -12,16 +12,24 @@ use crate::wasm_circuit::{
     common::WasmSharedStateAwareChip,
     leb128::circuit::LEB128Chip,
     sections::import::body::circuit::WasmImportSectionBodyChip,
-    tables::{dynamic_indexes::circuit::DynamicIndexesChip, fixed_range::config::RangeTableConfig},
+    tables::{
+        dynamic_indexes::circuit::DynamicIndexesChip,
+        fixed_range::config::RangeTableConfig,
+        host_functions::config::HostFunctionsTableConfig,
+        module_types::{circuit::ModuleTypesChip, types::ModuleTypes},
+    },
     types::SharedState,
     utf8::circuit::UTF8Chip,
 };
 
+/// Host imports are checked only if `HOST_IMPORTS_RESTRICTED`
 #[derive(Default)]
-struct TestCircuit<'a, F> {
+struct TestCircuit<'a, F, const HOST_IMPORTS_RESTRICTED: bool = false> {
     code_hash: Hash,
     bytecode: &'a [u8],
     offset_start: usize,
+    /// types of the module the import section is taken from
+    module_types: ModuleTypes,
     _marker: PhantomData<F>,
 }
 
@@ -30,10 +38,13 @@ struct TestCircuitConfig<F: Field> {
     body_chip: Rc<WasmImportSectionBodyChip<F>>,
     wb_table: Rc<WasmBytecodeTable>,
     range_table_config_0_128: Rc<RangeTableConfig<F, 0, 128>>,
+    host_functions_table_config: HostFunctionsTableConfig<F>,
     _marker: PhantomData<F>,
 }
 
-impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
+impl<'a, F: Field, const HOST_IMPORTS_RESTRICTED: bool> Circuit<F>
+    for TestCircuit<'a, F, HOST_IMPORTS_RESTRICTED>
+{
     type Config = TestCircuitConfig<F>;
     type FloorPlanner = SimpleFloorPlanner;
 
@@ -45,10 +56,12 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
         let wb_table = Rc::new(WasmBytecodeTable::construct(cs, false));
         let func_count = cs.advice_column();
         let error_code = cs.advice_column();
+        let bytecode_number = cs.advice_column();
         let body_byte_rev_index = cs.advice_column();
         let body_item_rev_count = cs.advice_column();
 
         let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        shared_state.borrow_mut().host_imports_restricted = HOST_IMPORTS_RESTRICTED;
 
         let range_table_config_0_128 = Rc::new(RangeTableConfig::configure(cs));
 
@@ -62,6 +75,10 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             UTF8Chip::<F>::configure(cs, range_table_config_0_128.clone(), &wb_table.value);
         let utf8_chip = Rc::new(UTF8Chip::construct(utf8_config));
 
+        let module_types_config = ModuleTypesChip::configure(cs, shared_state.clone());
+        let module_types_chip = Rc::new(ModuleTypesChip::construct(module_types_config));
+        let host_functions_table_config = HostFunctionsTableConfig::configure(cs);
+
         let wasm_import_section_body_config = WasmImportSectionBodyChip::configure(
             cs,
             wb_table.clone(),
@@ -73,6 +90,9 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             body_byte_rev_index,
             body_item_rev_count,
             error_code,
+            bytecode_number,
+            module_types_chip,
+            &host_functions_table_config,
         );
         let wasm_import_section_body_chip =
             WasmImportSectionBodyChip::construct(wasm_import_section_body_config);
@@ -80,6 +100,7 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             body_chip: Rc::new(wasm_import_section_body_chip),
             wb_table: wb_table.clone(),
             range_table_config_0_128: range_table_config_0_128.clone(),
+            host_functions_table_config,
             _marker: Default::default(),
         };
 
@@ -103,6 +124,19 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
             )
             .unwrap();
         config.range_table_config_0_128.load(&mut layouter)?;
+        config.host_functions_table_config.load(&mut layouter)?;
+        let module_types_chip = config.body_chip.config.module_types_chip.clone();
+        *module_types_chip.config.module_types.borrow_mut() = self.module_types.clone();
+        layouter.assign_region(
+            || "module types region",
+            |mut region| {
+                module_types_chip
+                    .assign_auto(&mut region, 0, assign_delta, &self.module_types)
+                    .unwrap();
+
+                Ok(())
+            },
+        )?;
         layouter.assign_region(
             || "wasm_import_section_body region",
             |mut region| {
@@ -127,16 +161,26 @@ impl<'a, F: Field> Circuit<F> for TestCircuit<'a, F> {
 mod wasm_import_section_body_tests {
     use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
     use log::debug;
+    use wabt::wat2wasm;
     use wasmbin::sections::Kind;
 
     use bus_mapping::state_db::CodeDB;
     use eth_types::Field;
 
     use crate::wasm_circuit::{
-        common::wat_extract_section_body_bytecode, sections::import::body::tests::TestCircuit,
+        common::wat_extract_section_body_bytecode,
+        sections::import::body::tests::TestCircuit,
+        tables::module_types::{
+            helpers::module_types_parse,
+            types::{FuncType, ModuleTypes},
+        },
+        types::{ImportDescType, NumType},
     };
 
-    fn test<'a, F: Field>(test_circuit: TestCircuit<'_, F>, is_ok: bool) {
+    fn test<'a, F: Field, const HOST_IMPORTS_RESTRICTED: bool>(
+        test_circuit: TestCircuit<'_, F, HOST_IMPORTS_RESTRICTED>,
+        is_ok: bool,
+    ) {
         let k = 8;
         let prover = MockProver::run(k, &test_circuit, vec![]).unwrap();
         if is_ok {
@@ -146,6 +190,39 @@ mod wasm_import_section_body_tests {
         }
     }
 
+    fn wat_module_types(path_to_file: &str) -> ModuleTypes {
+        let wat = std::fs::read(path_to_file).unwrap();
+        module_types_parse(&wat2wasm(wat).unwrap()).unwrap()
+    }
+
+    /// Body of an import section importing every `(module, name, typeidx)` as a function
+    fn func_imports_bytecode(imports: &[(&str, &str, u8)]) -> Vec<u8> {
+        let mut bytecode = vec![imports.len() as u8];
+        for (mod_name, import_name, typeidx) in imports {
+            bytecode.push(mod_name.len() as u8);
+            bytecode.extend_from_slice(mod_name.as_bytes());
+            bytecode.push(import_name.len() as u8);
+            bytecode.extend_from_slice(import_name.as_bytes());
+            bytecode.push(ImportDescType::Typeidx as u8);
+            bytecode.push(*typeidx);
+        }
+        bytecode
+    }
+
+    fn module_types(types: &[FuncType]) -> ModuleTypes {
+        ModuleTypes {
+            types: types.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn i32_params(count: usize) -> FuncType {
+        FuncType {
+            params: vec![NumType::I32; count],
+            results: vec![],
+        }
+    }
+
     #[test]
     pub fn file1_ok() {
         let bytecode = wat_extract_section_body_bytecode("./test_files/cc1.wat", Kind::Import);
@@ -160,6 +237,7 @@ mod wasm_import_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
+            module_types: wat_module_types("./test_files/cc1.wat"),
             ..Default::default()
         };
         test(test_circuit, true);
@@ -179,7 +257,128 @@ mod wasm_import_section_body_tests {
             code_hash,
             bytecode: &bytecode,
             offset_start: 0,
-            _marker: Default::default(),
+            module_types: wat_module_types("./test_files/cc2.wat"),
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn file1_host_imports_restricted_fails() {
+        let bytecode = wat_extract_section_body_bytecode("./test_files/cc1.wat", Kind::Import);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr, true> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: wat_module_types("./test_files/cc1.wat"),
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn host_functions_ok() {
+        let bytecode = func_imports_bytecode(&[
+            ("env", "_evm_stop", 0),
+            ("env", "_evm_address", 1),
+            ("env", "_evm_sload", 2),
+            ("env", "_evm_sstore", 2),
+        ]);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr, true> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: module_types(&[i32_params(0), i32_params(1), i32_params(2)]),
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn unknown_import_name_fails() {
+        let bytecode = func_imports_bytecode(&[("env", "_evm_sloadx", 0)]);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr, true> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: module_types(&[i32_params(2)]),
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn unknown_module_name_fails() {
+        let bytecode = func_imports_bytecode(&[("envx", "_evm_sload", 0)]);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr, true> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: module_types(&[i32_params(2)]),
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn wrong_params_fails() {
+        let bytecode = func_imports_bytecode(&[("env", "_evm_sload", 0)]);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr, true> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: module_types(&[FuncType {
+                params: vec![NumType::I32, NumType::I64],
+                results: vec![],
+            }]),
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn unexpected_results_fails() {
+        let bytecode = func_imports_bytecode(&[("env", "_evm_address", 0)]);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr, true> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: module_types(&[FuncType {
+                params: vec![NumType::I32],
+                results: vec![NumType::I32],
+            }]),
+            ..Default::default()
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn global_import_fails() {
+        // "env" "_evm_sload" (global i32)
+        let mut bytecode = func_imports_bytecode(&[("env", "_evm_sload", 0)]);
+        let len = bytecode.len();
+        bytecode[len - 2] = ImportDescType::GlobalType as u8;
+        bytecode[len - 1] = NumType::I32 as u8;
+        bytecode.push(0);
+        let code_hash = CodeDB::hash(&bytecode);
+        let test_circuit = TestCircuit::<Fr, true> {
+            code_hash,
+            bytecode: &bytecode,
+            offset_start: 0,
+            module_types: module_types(&[i32_params(2)]),
+            ..Default::default()
         };
         test(test_circuit, true);
     }
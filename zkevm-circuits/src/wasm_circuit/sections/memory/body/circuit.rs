@@ -35,7 +35,7 @@ use crate::{
         },
         types::{
             AssignDeltaType, AssignValueType, LimitType, NewWbOffsetType, SharedState,
-            LIMIT_TYPE_VALUES,
+            WasmModuleTag, LIMIT_TYPE_VALUES,
         },
     },
 };
@@ -52,6 +52,7 @@ pub struct WasmMemorySectionBodyConfig<F: Field> {
     pub leb128_chip: Rc<LEB128Chip<F>>,
     pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
 
+    wb_table: Rc<WasmBytecodeTable>,
     func_count: Column<Advice>,
     body_item_rev_count: Column<Advice>,
 
@@ -565,6 +566,27 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                 );
             });
 
+            // module view: the initial pages are the minimum of the limits
+            cb.condition(
+                vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                |cb| {
+                    cb.require_equal(
+                        "module_tag => is_limit_min",
+                        is_limit_min_expr.clone(),
+                        1.expr(),
+                    );
+                    cb.require_zero(
+                        "module_tag => module_index=0",
+                        vc.query_advice(wb_table.module_index, Rotation::cur()),
+                    );
+                    cb.require_equal(
+                        "module_tag => module_value=limit_min",
+                        vc.query_advice(wb_table.module_value, Rotation::cur()),
+                        vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+                    );
+                },
+            );
+
             cb.gate(q_enable_expr.clone())
         });
 
@@ -578,6 +600,7 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
             limit_type_fields,
             leb128_chip,
             dynamic_indexes_chip,
+            wb_table,
             func_count,
             body_item_rev_count,
             error_code,
@@ -683,6 +706,17 @@ impl<F: Field> WasmMemorySectionBodyChip<F> {
                     None,
                 )?;
             }
+            self.config
+                .wb_table
+                .assign_module_view(
+                    region,
+                    offset + assign_delta,
+                    Some(WasmModuleTag::MemoryPages),
+                    0,
+                    limit_min,
+                    0,
+                )
+                .map_err(remap_error_to_assign_at(offset + assign_delta))?;
             offset += limit_min_leb_len;
 
             // limit_max*
@@ -31,7 +31,7 @@ use crate::{
         tables::dynamic_indexes::{circuit::DynamicIndexesChip, types::Tag},
         types::{
             AssignDeltaType, AssignValueType, LimitType, NewWbOffsetType, SharedState,
-            REF_TYPE_VALUES,
+            WasmModuleTag, REF_TYPE_VALUES,
         },
     },
 };
@@ -49,6 +49,7 @@ pub struct WasmTableSectionBodyConfig<F: Field> {
     pub leb128_chip: Rc<LEB128Chip<F>>,
     pub dynamic_indexes_chip: Rc<DynamicIndexesChip<F>>,
 
+    wb_table: Rc<WasmBytecodeTable>,
     pub func_count: Column<Advice>,
     pub error_code: Column<Advice>,
     shared_state: Rc<RefCell<SharedState>>,
@@ -561,6 +562,27 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
                 },
             );
 
+            // module view: the table size is the minimum of the limits
+            cb.condition(
+                vc.query_fixed(wb_table.module_tag, Rotation::cur()),
+                |cb| {
+                    cb.require_equal(
+                        "module_tag => is_limit_min",
+                        is_limit_min_expr.clone(),
+                        1.expr(),
+                    );
+                    cb.require_zero(
+                        "module_tag => module_index=0",
+                        vc.query_advice(wb_table.module_index, Rotation::cur()),
+                    );
+                    cb.require_equal(
+                        "module_tag => module_value=limit_min",
+                        vc.query_advice(wb_table.module_value, Rotation::cur()),
+                        vc.query_advice(leb128_chip.config.sn, Rotation::cur()),
+                    );
+                },
+            );
+
             cb.gate(q_enable_expr.clone())
         });
 
@@ -575,6 +597,7 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
             limit_type_fields,
             leb128_chip,
             dynamic_indexes_chip,
+            wb_table,
             func_count,
             error_code,
             shared_state,
@@ -676,6 +699,17 @@ impl<F: Field> WasmTableSectionBodyChip<F> {
                 None,
             )?;
         }
+        self.config
+            .wb_table
+            .assign_module_view(
+                region,
+                offset + assign_delta,
+                Some(WasmModuleTag::TableSize),
+                0,
+                limit_min,
+                0,
+            )
+            .map_err(remap_error_to_assign_at(offset + assign_delta))?;
         offset += limit_min_leb_len;
 
         // limit_max*
@@ -20,13 +20,15 @@ use crate::{
             WasmAssignAwareChip, WasmCountPrefixedItemsAwareChip, WasmErrorAwareChip,
             WasmFuncCountAwareChip, WasmMarkupLeb128SectionAwareChip, WasmSharedStateAwareChip,
         },
-        error::{remap_error_to_assign_at, Error},
+        error::{remap_error_to_assign_at, validate_wb_offset, Error},
         leb128::circuit::LEB128Chip,
         sections::{
             consts::LebParams,
             r#type::item::{consts::Type::FuncType, types::AssignType},
         },
-        types::{AssignDeltaType, AssignValueType, NewWbOffsetType, NumType, SharedState},
+        types::{
+            AssignDeltaType, AssignValueType, NewWbOffsetType, NumType, SharedState, NUM_TYPE_VALUES,
+        },
     },
 };
 
@@ -370,7 +372,7 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
                     cb.require_in_set(
                         "type_section_item input/output type has valid value",
                         byte_value_expr.clone(),
-                        vec![NumType::I32.expr(), NumType::I64.expr()],
+                        NUM_TYPE_VALUES.iter().map(|v| v.expr()).collect_vec(),
                     )
                 },
             );
@@ -531,6 +533,10 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
         offset += input_count_leb_len;
         // is_input_type*
         for offset in offset..(offset + input_count as usize) {
+            validate_wb_offset(wb, offset)?;
+            if NumType::is_float_value(wb.bytes[offset]) {
+                return Err(Error::FloatUnsupportedAt(offset + assign_delta));
+            }
             self.assign(
                 region,
                 wb,
@@ -576,6 +582,10 @@ impl<F: Field> WasmTypeSectionItemChip<F> {
         offset += output_count_leb_len;
         // is_output_type*
         for offset in offset..(offset + output_count as usize) {
+            validate_wb_offset(wb, offset)?;
+            if NumType::is_float_value(wb.bytes[offset]) {
+                return Err(Error::FloatUnsupportedAt(offset + assign_delta));
+            }
             self.assign(
                 region,
                 wb,
@@ -1,3 +1,7 @@
 pub mod fixed_range;
 pub mod dynamic_indexes;
 pub mod code_blocks;
+pub mod section_rank;
+pub mod type_stack;
+pub mod module_types;
+pub mod host_functions;
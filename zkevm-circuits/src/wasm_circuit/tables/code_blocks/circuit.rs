@@ -34,6 +34,11 @@ pub struct CodeBlocksConfig<F> {
     pub bytecode_number: Column<Advice>,
     pub index: Column<Advice>,
     pub opcode: Column<Advice>,
+    /// params and results count of the blocktype, set on `block`, `loop` and `if` rows only. The
+    /// code body packs the same counts into the `arg` of the opener in the module view, which is
+    /// where the execution of the frame reads them from
+    pub params_count: Column<Advice>,
+    pub results_count: Column<Advice>,
 
     pub opcode_chip: Rc<BinaryNumberChip<F, Opcode, 8>>,
 
@@ -82,6 +87,8 @@ impl<F: Field> CodeBlocksChip<F> {
         let opcode = cs.advice_column();
 
         let index = cs.advice_column();
+        let params_count = cs.advice_column();
+        let results_count = cs.advice_column();
 
         let config = BinaryNumberChip::configure(cs, q_enable, Some(opcode.into()));
         let opcode_chip = Rc::new(BinaryNumberChip::construct(config));
@@ -253,6 +260,8 @@ impl<F: Field> CodeBlocksChip<F> {
             q_last,
             opcode,
             index,
+            params_count,
+            results_count,
             opcode_chip,
             bytecode_number,
             shared_state,
@@ -321,6 +330,36 @@ impl<F: Field> CodeBlocksChip<F> {
                         )
                         .map_err(remap_error_to_assign_at(assign_offset))?;
                 }
+                AssignType::ParamsCount => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'params_count' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.params_count,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
+                AssignType::ResultsCount => {
+                    region
+                        .assign_advice(
+                            || {
+                                format!(
+                                    "assign 'results_count' val {} at {}",
+                                    assign_value, assign_offset
+                                )
+                            },
+                            self.config.results_count,
+                            assign_offset,
+                            || Value::known(F::from(assign_value)),
+                        )
+                        .map_err(remap_error_to_assign_at(assign_offset))?;
+                }
                 AssignType::Opcode => {
                     region
                         .assign_advice(
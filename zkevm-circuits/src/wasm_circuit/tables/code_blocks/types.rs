@@ -11,6 +11,8 @@ pub enum AssignType {
     QLast,
     Index,
     Opcode,
+    ParamsCount,
+    ResultsCount,
 }
 
 #[derive(Default, Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
@@ -0,0 +1,92 @@
+use std::marker::PhantomData;
+
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use crate::wasm_circuit::tables::{
+    host_functions::types::{host_functions, name_acc_value},
+    type_stack::types::TypeSeq,
+};
+
+/// Functions a module may import: the module and import names, each with its byte length and its
+/// bytes packed big-endian, and the params and results of the function packed with their counts.
+/// The lengths bound the names to the short host names so no longer name packs to the same value.
+/// The first row is all zeros so disabled lookups match it.
+#[derive(Debug, Clone)]
+pub struct HostFunctionsTableConfig<F: Field> {
+    pub mod_name_len: TableColumn,
+    pub mod_name_acc: TableColumn,
+    pub import_name_len: TableColumn,
+    pub import_name_acc: TableColumn,
+    pub params: TableColumn,
+    pub params_count: TableColumn,
+    pub results: TableColumn,
+    pub results_count: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> HostFunctionsTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            mod_name_len: cs.lookup_table_column(),
+            mod_name_acc: cs.lookup_table_column(),
+            import_name_len: cs.lookup_table_column(),
+            import_name_acc: cs.lookup_table_column(),
+            params: cs.lookup_table_column(),
+            params_count: cs.lookup_table_column(),
+            results: cs.lookup_table_column(),
+            results_count: cs.lookup_table_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load host functions table",
+            |mut table| {
+                let mut rows = vec![[F::zero(); 8]];
+                for (mod_name, import_name, func_type) in host_functions() {
+                    let params = TypeSeq::new(&func_type.params);
+                    let results = TypeSeq::new(&func_type.results);
+                    rows.push([
+                        F::from(mod_name.len() as u64),
+                        name_acc_value(mod_name.as_bytes()),
+                        F::from(import_name.len() as u64),
+                        name_acc_value(import_name.as_bytes()),
+                        F::from_u128(params.packed),
+                        F::from(params.count),
+                        F::from_u128(results.packed),
+                        F::from(results.count),
+                    ]);
+                }
+                for (offset, row) in rows.iter().enumerate() {
+                    for (column, value) in [
+                        self.mod_name_len,
+                        self.mod_name_acc,
+                        self.import_name_len,
+                        self.import_name_acc,
+                        self.params,
+                        self.params_count,
+                        self.results,
+                        self.results_count,
+                    ]
+                    .iter()
+                    .zip(row)
+                    {
+                        table.assign_cell(
+                            || "host functions table cell",
+                            *column,
+                            offset,
+                            || Value::known(*value),
+                        )?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
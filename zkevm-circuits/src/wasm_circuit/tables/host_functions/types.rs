@@ -0,0 +1,37 @@
+use eth_types::{
+    bytecode::{WASM_HOST_FUNCTIONS, WASM_HOST_MODULE},
+    Field,
+};
+
+use crate::wasm_circuit::{tables::module_types::types::FuncType, types::NumType};
+
+/// Every function the host provides as `(module name, import name, signature)`
+pub fn host_functions() -> Vec<(&'static str, &'static str, FuncType)> {
+    WASM_HOST_FUNCTIONS
+        .iter()
+        .map(|&(_, name, params_count)| {
+            let func_type = FuncType {
+                params: vec![NumType::I32; params_count],
+                results: vec![],
+            };
+            (WASM_HOST_MODULE, name, func_type)
+        })
+        .collect()
+}
+
+pub fn is_host_function(mod_name: &[u8], import_name: &[u8], func_type: &FuncType) -> bool {
+    host_functions()
+        .iter()
+        .any(|(host_mod_name, host_name, host_func_type)| {
+            host_mod_name.as_bytes() == mod_name
+                && host_name.as_bytes() == import_name
+                && host_func_type == func_type
+        })
+}
+
+/// Packs the name bytes into a field element, big-endian
+pub fn name_acc_value<F: Field>(name: &[u8]) -> F {
+    name.iter().fold(F::zero(), |acc, &byte| {
+        acc * F::from(256) + F::from(byte as u64)
+    })
+}
@@ -0,0 +1,4 @@
+pub mod circuit;
+pub mod helpers;
+pub mod tests;
+pub mod types;
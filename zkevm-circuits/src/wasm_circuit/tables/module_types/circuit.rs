@@ -0,0 +1,641 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use halo2_proofs::{
+    circuit::{Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Expression, Fixed, VirtualCells},
+    poly::Rotation,
+};
+use log::debug;
+
+use eth_types::Field;
+use gadgets::util::{and, not, Expr};
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    wasm_circuit::{
+        common::{WasmBytecodeNumberAwareChip, WasmSharedStateAwareChip},
+        error::{remap_error_to_assign_at, Error},
+        tables::{
+            module_types::types::{AssignType, LookupArgsParams, ModuleTypes},
+            type_stack::types::{num_type_bit, TypeSeq},
+        },
+        types::{AssignDeltaType, NewWbOffsetType, SharedState},
+    },
+};
+
+/// Per bytecode table of function signatures, local types and global types. Type and function
+/// rows carry the packed params and results of the signature, local rows are either a param (one
+/// row per param) or a run of declared locals (one row per local, `run_start..run_end` is the
+/// run).
+#[derive(Debug, Clone)]
+pub struct ModuleTypesConfig<F> {
+    pub q_enable: Column<Fixed>,
+    pub is_type: Column<Fixed>,
+    pub is_func: Column<Fixed>,
+    pub is_local: Column<Fixed>,
+    pub is_global: Column<Fixed>,
+    pub is_param: Column<Fixed>,
+    pub is_run_first: Column<Fixed>,
+    pub is_run_last: Column<Fixed>,
+    bytecode_number: Column<Advice>,
+    pub index: Column<Advice>,
+    pub func_index: Column<Advice>,
+    pub type_index: Column<Advice>,
+    pub params: Column<Advice>,
+    pub params_count: Column<Advice>,
+    pub params_shift: Column<Advice>,
+    pub results: Column<Advice>,
+    pub results_count: Column<Advice>,
+    pub results_shift: Column<Advice>,
+    pub value_type: Column<Advice>,
+    pub run_start: Column<Advice>,
+    pub run_end: Column<Advice>,
+
+    pub module_types: Rc<RefCell<ModuleTypes>>,
+    pub shared_state: Rc<RefCell<SharedState>>,
+
+    _marker: PhantomData<F>,
+}
+
+impl<'a, F: Field> ModuleTypesConfig<F> {}
+
+#[derive(Debug, Clone)]
+pub struct ModuleTypesChip<F> {
+    pub config: ModuleTypesConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> WasmSharedStateAwareChip<F> for ModuleTypesChip<F> {
+    fn shared_state(&self) -> Rc<RefCell<SharedState>> {
+        self.config.shared_state.clone()
+    }
+}
+
+impl<F: Field> WasmBytecodeNumberAwareChip<F> for ModuleTypesChip<F> {
+    fn bytecode_number_col(&self) -> Column<Advice> {
+        self.config.bytecode_number
+    }
+}
+
+impl<F: Field> ModuleTypesChip<F> {
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        shared_state: Rc<RefCell<SharedState>>,
+    ) -> ModuleTypesConfig<F> {
+        let q_enable = cs.fixed_column();
+        let is_type = cs.fixed_column();
+        let is_func = cs.fixed_column();
+        let is_local = cs.fixed_column();
+        let is_global = cs.fixed_column();
+        let is_param = cs.fixed_column();
+        let is_run_first = cs.fixed_column();
+        let is_run_last = cs.fixed_column();
+
+        let bytecode_number = cs.advice_column();
+        let index = cs.advice_column();
+        let func_index = cs.advice_column();
+        let type_index = cs.advice_column();
+        let params = cs.advice_column();
+        let params_count = cs.advice_column();
+        let params_shift = cs.advice_column();
+        let results = cs.advice_column();
+        let results_count = cs.advice_column();
+        let results_shift = cs.advice_column();
+        let value_type = cs.advice_column();
+        let run_start = cs.advice_column();
+        let run_end = cs.advice_column();
+
+        cs.create_gate("ModuleTypes gate", |vc| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_enable_expr = vc.query_fixed(q_enable, Rotation::cur());
+            let is_type_expr = vc.query_fixed(is_type, Rotation::cur());
+            let is_func_expr = vc.query_fixed(is_func, Rotation::cur());
+            let is_local_expr = vc.query_fixed(is_local, Rotation::cur());
+            let is_global_expr = vc.query_fixed(is_global, Rotation::cur());
+            let is_param_expr = vc.query_fixed(is_param, Rotation::cur());
+            let is_run_first_expr = vc.query_fixed(is_run_first, Rotation::cur());
+            let is_run_last_expr = vc.query_fixed(is_run_last, Rotation::cur());
+            let is_local_prev_expr = vc.query_fixed(is_local, Rotation::prev());
+            let is_param_prev_expr = vc.query_fixed(is_param, Rotation::prev());
+            let is_run_last_prev_expr = vc.query_fixed(is_run_last, Rotation::prev());
+            let is_local_next_expr = vc.query_fixed(is_local, Rotation::next());
+            let is_param_next_expr = vc.query_fixed(is_param, Rotation::next());
+            let is_run_first_next_expr = vc.query_fixed(is_run_first, Rotation::next());
+
+            let bytecode_number_expr = vc.query_advice(bytecode_number, Rotation::cur());
+            let bytecode_number_next_expr = vc.query_advice(bytecode_number, Rotation::next());
+            let index_expr = vc.query_advice(index, Rotation::cur());
+            let index_next_expr = vc.query_advice(index, Rotation::next());
+            let func_index_expr = vc.query_advice(func_index, Rotation::cur());
+            let func_index_next_expr = vc.query_advice(func_index, Rotation::next());
+            let type_index_expr = vc.query_advice(type_index, Rotation::cur());
+            let value_type_expr = vc.query_advice(value_type, Rotation::cur());
+            let value_type_next_expr = vc.query_advice(value_type, Rotation::next());
+            let run_start_expr = vc.query_advice(run_start, Rotation::cur());
+            let run_start_next_expr = vc.query_advice(run_start, Rotation::next());
+            let run_end_expr = vc.query_advice(run_end, Rotation::cur());
+            let run_end_next_expr = vc.query_advice(run_end, Rotation::next());
+
+            let is_declared_expr = is_local_expr.clone() * not::expr(is_param_expr.clone());
+            let is_declared_prev_expr = is_local_prev_expr * not::expr(is_param_prev_expr);
+            let is_declared_next_expr = is_local_next_expr * not::expr(is_param_next_expr);
+
+            cb.require_boolean("q_enable is boolean", q_enable_expr.clone());
+            for (name, flag_expr) in [
+                ("is_type is boolean", is_type_expr.clone()),
+                ("is_func is boolean", is_func_expr.clone()),
+                ("is_local is boolean", is_local_expr.clone()),
+                ("is_global is boolean", is_global_expr.clone()),
+                ("is_param is boolean", is_param_expr.clone()),
+                ("is_run_first is boolean", is_run_first_expr.clone()),
+                ("is_run_last is boolean", is_run_last_expr.clone()),
+            ] {
+                cb.require_boolean(name, flag_expr);
+            }
+            cb.require_equal(
+                "exactly one of is_type, is_func, is_local, is_global",
+                is_type_expr.clone()
+                    + is_func_expr.clone()
+                    + is_local_expr.clone()
+                    + is_global_expr.clone(),
+                1.expr(),
+            );
+            for (name, flag_expr) in [
+                ("is_param => is_local", is_param_expr.clone()),
+                ("is_run_first => is_local", is_run_first_expr.clone()),
+                ("is_run_last => is_local", is_run_last_expr.clone()),
+            ] {
+                cb.require_zero(name, flag_expr * not::expr(is_local_expr.clone()));
+            }
+            cb.require_zero(
+                "run flags are only set on declared locals",
+                is_param_expr.clone() * (is_run_first_expr.clone() + is_run_last_expr.clone()),
+            );
+
+            cb.condition(is_type_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_type => type_index=index",
+                    type_index_expr.clone(),
+                    index_expr.clone(),
+                );
+            });
+            cb.condition(is_func_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_func => func_index=index",
+                    func_index_expr.clone(),
+                    index_expr.clone(),
+                );
+            });
+            cb.condition(is_local_expr.clone() + is_global_expr.clone(), |cb| {
+                cb.require_boolean(
+                    "is_local || is_global => value_type is boolean",
+                    value_type_expr.clone(),
+                );
+            });
+
+            cb.condition(is_run_first_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_run_first => index=run_start",
+                    index_expr.clone(),
+                    run_start_expr.clone(),
+                );
+            });
+            cb.condition(is_run_last_expr.clone(), |cb| {
+                cb.require_equal(
+                    "is_run_last => index+1=run_end",
+                    index_expr.clone() + 1.expr(),
+                    run_end_expr.clone(),
+                );
+            });
+            cb.condition(
+                and::expr([
+                    is_declared_expr.clone(),
+                    not::expr(is_run_last_expr.clone()),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "declared local inside a run => next is a declared local",
+                        is_declared_next_expr.clone(),
+                        1.expr(),
+                    );
+                    cb.require_zero(
+                        "declared local inside a run => next is not a run start",
+                        is_run_first_next_expr.clone(),
+                    );
+                    cb.require_equal(
+                        "declared local inside a run => index grows 1 by 1",
+                        index_expr.clone() + 1.expr(),
+                        index_next_expr.clone(),
+                    );
+                    for (name, cur_expr, next_expr) in [
+                        (
+                            "declared local inside a run => bytecode_number=next.bytecode_number",
+                            bytecode_number_expr.clone(),
+                            bytecode_number_next_expr.clone(),
+                        ),
+                        (
+                            "declared local inside a run => func_index=next.func_index",
+                            func_index_expr.clone(),
+                            func_index_next_expr.clone(),
+                        ),
+                        (
+                            "declared local inside a run => run_start=next.run_start",
+                            run_start_expr.clone(),
+                            run_start_next_expr.clone(),
+                        ),
+                        (
+                            "declared local inside a run => run_end=next.run_end",
+                            run_end_expr.clone(),
+                            run_end_next_expr.clone(),
+                        ),
+                        (
+                            "declared local inside a run => value_type=next.value_type",
+                            value_type_expr.clone(),
+                            value_type_next_expr.clone(),
+                        ),
+                    ] {
+                        cb.require_equal(name, cur_expr, next_expr);
+                    }
+                },
+            );
+            cb.condition(
+                and::expr([
+                    is_declared_expr.clone(),
+                    not::expr(is_run_first_expr.clone()),
+                ]),
+                |cb| {
+                    cb.require_equal(
+                        "declared local inside a run => prev is a declared local",
+                        is_declared_prev_expr.clone(),
+                        1.expr(),
+                    );
+                    cb.require_zero(
+                        "declared local inside a run => prev is not a run end",
+                        is_run_last_prev_expr.clone(),
+                    );
+                },
+            );
+
+            cb.gate(q_enable_expr.clone())
+        });
+
+        cs.lookup_any("param local type index matches its function", |vc| {
+            let cond_expr = vc.query_fixed(is_param, Rotation::cur());
+            vec![
+                (
+                    cond_expr.clone(),
+                    vc.query_fixed(is_func, Rotation::cur()),
+                ),
+                (
+                    cond_expr.clone() * vc.query_advice(bytecode_number, Rotation::cur()),
+                    vc.query_advice(bytecode_number, Rotation::cur()),
+                ),
+                (
+                    cond_expr.clone() * vc.query_advice(func_index, Rotation::cur()),
+                    vc.query_advice(index, Rotation::cur()),
+                ),
+                (
+                    cond_expr.clone() * vc.query_advice(type_index, Rotation::cur()),
+                    vc.query_advice(type_index, Rotation::cur()),
+                ),
+            ]
+        });
+
+        cs.lookup_any("func signature matches its type", |vc| {
+            let cond_expr = vc.query_fixed(is_func, Rotation::cur());
+            let is_type_expr = vc.query_fixed(is_type, Rotation::cur());
+
+            let mut lookup = vec![
+                (cond_expr.clone(), is_type_expr.clone()),
+                (
+                    cond_expr.clone() * vc.query_advice(bytecode_number, Rotation::cur()),
+                    vc.query_advice(bytecode_number, Rotation::cur()),
+                ),
+            ];
+            for column in [
+                type_index,
+                params,
+                params_count,
+                params_shift,
+                results,
+                results_count,
+                results_shift,
+            ] {
+                lookup.push((
+                    cond_expr.clone() * vc.query_advice(column, Rotation::cur()),
+                    vc.query_advice(column, Rotation::cur()),
+                ));
+            }
+
+            lookup
+        });
+
+        let config = ModuleTypesConfig::<F> {
+            _marker: Default::default(),
+
+            q_enable,
+            is_type,
+            is_func,
+            is_local,
+            is_global,
+            is_param,
+            is_run_first,
+            is_run_last,
+            bytecode_number,
+            index,
+            func_index,
+            type_index,
+            params,
+            params_count,
+            params_shift,
+            results,
+            results_count,
+            results_shift,
+            value_type,
+            run_start,
+            run_end,
+            module_types: Default::default(),
+            shared_state,
+        };
+
+        config
+    }
+
+    pub fn construct(config: ModuleTypesConfig<F>) -> Self {
+        let instance = Self {
+            config,
+            _marker: PhantomData,
+        };
+        instance
+    }
+
+    fn query(&self, vc: &mut VirtualCells<'_, F>, assign_type: AssignType) -> Expression<F> {
+        let c = &self.config;
+        match assign_type {
+            AssignType::IsType => vc.query_fixed(c.is_type, Rotation::cur()),
+            AssignType::IsFunc => vc.query_fixed(c.is_func, Rotation::cur()),
+            AssignType::IsLocal => vc.query_fixed(c.is_local, Rotation::cur()),
+            AssignType::IsGlobal => vc.query_fixed(c.is_global, Rotation::cur()),
+            AssignType::IsParam => vc.query_fixed(c.is_param, Rotation::cur()),
+            AssignType::IsRunFirst => vc.query_fixed(c.is_run_first, Rotation::cur()),
+            AssignType::IsRunLast => vc.query_fixed(c.is_run_last, Rotation::cur()),
+            AssignType::Index => vc.query_advice(c.index, Rotation::cur()),
+            AssignType::FuncIndex => vc.query_advice(c.func_index, Rotation::cur()),
+            AssignType::TypeIndex => vc.query_advice(c.type_index, Rotation::cur()),
+            AssignType::Params => vc.query_advice(c.params, Rotation::cur()),
+            AssignType::ParamsCount => vc.query_advice(c.params_count, Rotation::cur()),
+            AssignType::ParamsShift => vc.query_advice(c.params_shift, Rotation::cur()),
+            AssignType::Results => vc.query_advice(c.results, Rotation::cur()),
+            AssignType::ResultsCount => vc.query_advice(c.results_count, Rotation::cur()),
+            AssignType::ResultsShift => vc.query_advice(c.results_shift, Rotation::cur()),
+            AssignType::ValueType => vc.query_advice(c.value_type, Rotation::cur()),
+            AssignType::RunStart => vc.query_advice(c.run_start, Rotation::cur()),
+            AssignType::RunEnd => vc.query_advice(c.run_end, Rotation::cur()),
+        }
+    }
+
+    pub fn lookup_args(
+        &self,
+        name: &'static str,
+        cs: &mut ConstraintSystem<F>,
+        p: impl FnOnce(&mut VirtualCells<'_, F>) -> LookupArgsParams<F>,
+    ) {
+        cs.lookup_any(name, |vc| {
+            let p = p(vc);
+
+            let mut lookup = vec![(
+                p.cond.clone() * p.bytecode_number,
+                vc.query_advice(self.config.bytecode_number, Rotation::cur()),
+            )];
+            for (assign_type, arg) in p.args {
+                lookup.push((p.cond.clone() * arg, self.query(vc, assign_type)));
+            }
+
+            lookup
+        });
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        assign_type: AssignType,
+        assign_value: u128,
+    ) -> Result<(), Error> {
+        let q_enable = true;
+        let assign_offset = offset + assign_delta;
+        debug!(
+            "assign at {} q_enable {} assign_type {:?} assign_value {:?}",
+            assign_offset, q_enable, assign_type, assign_value,
+        );
+        region
+            .assign_fixed(
+                || format!("assign 'q_enable' val {} at {}", q_enable, assign_offset),
+                self.config.q_enable,
+                assign_offset,
+                || Value::known(F::from(q_enable as u64)),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))?;
+        let c = &self.config;
+        let fixed_column = match assign_type {
+            AssignType::IsType => Some(c.is_type),
+            AssignType::IsFunc => Some(c.is_func),
+            AssignType::IsLocal => Some(c.is_local),
+            AssignType::IsGlobal => Some(c.is_global),
+            AssignType::IsParam => Some(c.is_param),
+            AssignType::IsRunFirst => Some(c.is_run_first),
+            AssignType::IsRunLast => Some(c.is_run_last),
+            _ => None,
+        };
+        if let Some(column) = fixed_column {
+            region
+                .assign_fixed(
+                    || {
+                        format!(
+                            "assign '{:?}' val {} at {}",
+                            assign_type, assign_value, assign_offset
+                        )
+                    },
+                    column,
+                    assign_offset,
+                    || Value::known(F::from_u128(assign_value)),
+                )
+                .map_err(remap_error_to_assign_at(assign_offset))?;
+            return Ok(());
+        }
+        let advice_column = match assign_type {
+            AssignType::Index => c.index,
+            AssignType::FuncIndex => c.func_index,
+            AssignType::TypeIndex => c.type_index,
+            AssignType::Params => c.params,
+            AssignType::ParamsCount => c.params_count,
+            AssignType::ParamsShift => c.params_shift,
+            AssignType::Results => c.results,
+            AssignType::ResultsCount => c.results_count,
+            AssignType::ResultsShift => c.results_shift,
+            AssignType::ValueType => c.value_type,
+            AssignType::RunStart => c.run_start,
+            AssignType::RunEnd => c.run_end,
+            _ => unreachable!(),
+        };
+        region
+            .assign_advice(
+                || {
+                    format!(
+                        "assign '{:?}' val {} at {}",
+                        assign_type, assign_value, assign_offset
+                    )
+                },
+                advice_column,
+                assign_offset,
+                || Value::known(F::from_u128(assign_value)),
+            )
+            .map_err(remap_error_to_assign_at(assign_offset))?;
+
+        Ok(())
+    }
+
+    fn assign_row(
+        &self,
+        region: &mut Region<F>,
+        offset: usize,
+        assign_delta: AssignDeltaType,
+        values: &[(AssignType, u128)],
+    ) -> Result<(), Error> {
+        self.assign_bytecode_number(region, offset + assign_delta, None)?;
+        for (assign_type, assign_value) in values {
+            self.assign(region, offset, assign_delta, *assign_type, *assign_value)?;
+        }
+        Ok(())
+    }
+
+    /// Assigns the rows of `module_types` right after `start_offset`, returns the last assigned
+    /// offset
+    pub fn assign_auto(
+        &self,
+        region: &mut Region<F>,
+        start_offset: usize,
+        assign_delta: AssignDeltaType,
+        module_types: &ModuleTypes,
+    ) -> Result<NewWbOffsetType, Error> {
+        let mut offset = start_offset;
+        for (type_index, func_type) in module_types.types.iter().enumerate() {
+            let (params, results) = (
+                TypeSeq::new(&func_type.params),
+                TypeSeq::new(&func_type.results),
+            );
+            offset += 1;
+            self.assign_row(
+                region,
+                offset,
+                assign_delta,
+                &[
+                    (AssignType::IsType, 1),
+                    (AssignType::Index, type_index as u128),
+                    (AssignType::TypeIndex, type_index as u128),
+                    (AssignType::Params, params.packed),
+                    (AssignType::ParamsCount, params.count as u128),
+                    (AssignType::ParamsShift, params.shift()),
+                    (AssignType::Results, results.packed),
+                    (AssignType::ResultsCount, results.count as u128),
+                    (AssignType::ResultsShift, results.shift()),
+                ],
+            )?;
+        }
+        for (func_index, type_index) in module_types.funcs.iter().enumerate() {
+            let func_type = module_types
+                .types
+                .get(*type_index as usize)
+                .ok_or(Error::IndexOutOfBoundsSimple)?;
+            let (params, results) = (
+                TypeSeq::new(&func_type.params),
+                TypeSeq::new(&func_type.results),
+            );
+            offset += 1;
+            self.assign_row(
+                region,
+                offset,
+                assign_delta,
+                &[
+                    (AssignType::IsFunc, 1),
+                    (AssignType::Index, func_index as u128),
+                    (AssignType::FuncIndex, func_index as u128),
+                    (AssignType::TypeIndex, *type_index as u128),
+                    (AssignType::Params, params.packed),
+                    (AssignType::ParamsCount, params.count as u128),
+                    (AssignType::ParamsShift, params.shift()),
+                    (AssignType::Results, results.packed),
+                    (AssignType::ResultsCount, results.count as u128),
+                    (AssignType::ResultsShift, results.shift()),
+                ],
+            )?;
+        }
+        for (defined_index, locals) in module_types.locals.iter().enumerate() {
+            let func_index = module_types.imported_funcs_count + defined_index;
+            let type_index = *module_types
+                .funcs
+                .get(func_index)
+                .ok_or(Error::IndexOutOfBoundsSimple)?;
+            let func_type = module_types
+                .types
+                .get(type_index as usize)
+                .ok_or(Error::IndexOutOfBoundsSimple)?;
+            for (local_index, num_type) in func_type.params.iter().enumerate() {
+                offset += 1;
+                self.assign_row(
+                    region,
+                    offset,
+                    assign_delta,
+                    &[
+                        (AssignType::IsLocal, 1),
+                        (AssignType::IsParam, 1),
+                        (AssignType::Index, local_index as u128),
+                        (AssignType::FuncIndex, func_index as u128),
+                        (AssignType::TypeIndex, type_index as u128),
+                        (AssignType::ValueType, num_type_bit(*num_type) as u128),
+                    ],
+                )?;
+            }
+            let mut run_start = func_type.params.len() as u64;
+            for (count, num_type) in locals {
+                let run_end = run_start + count;
+                for local_index in run_start..run_end {
+                    offset += 1;
+                    self.assign_row(
+                        region,
+                        offset,
+                        assign_delta,
+                        &[
+                            (AssignType::IsLocal, 1),
+                            (AssignType::IsRunFirst, (local_index == run_start) as u128),
+                            (AssignType::IsRunLast, (local_index + 1 == run_end) as u128),
+                            (AssignType::Index, local_index as u128),
+                            (AssignType::FuncIndex, func_index as u128),
+                            (AssignType::ValueType, num_type_bit(*num_type) as u128),
+                            (AssignType::RunStart, run_start as u128),
+                            (AssignType::RunEnd, run_end as u128),
+                        ],
+                    )?;
+                }
+                run_start = run_end;
+            }
+        }
+        for (global_index, num_type) in module_types.globals.iter().enumerate() {
+            offset += 1;
+            self.assign_row(
+                region,
+                offset,
+                assign_delta,
+                &[
+                    (AssignType::IsGlobal, 1),
+                    (AssignType::Index, global_index as u128),
+                    (AssignType::ValueType, num_type_bit(*num_type) as u128),
+                ],
+            )?;
+        }
+
+        Ok(offset)
+    }
+}
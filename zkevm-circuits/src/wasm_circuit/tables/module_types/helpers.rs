@@ -0,0 +1,135 @@
+use crate::wasm_circuit::{
+    common::wasm_compute_section_len,
+    consts::{WASM_BLOCK_END, WASM_SECTIONS_START_INDEX},
+    error::Error,
+    leb128::helpers::leb128_compute_sn,
+    tables::module_types::types::{FuncType, ModuleTypes},
+    types::{ImportDescType, NumType, NumericInstruction, VariableInstruction, WasmSection},
+};
+
+fn read_byte(bytes: &[u8], offset: &mut usize) -> Result<u8, Error> {
+    let byte = *bytes.get(*offset).ok_or(Error::IndexOutOfBoundsSimple)?;
+    *offset += 1;
+    Ok(byte)
+}
+
+fn read_leb(bytes: &[u8], offset: &mut usize, is_signed: bool) -> Result<u64, Error> {
+    let (sn, last_byte_offset) = leb128_compute_sn(bytes, is_signed, *offset)?;
+    *offset = last_byte_offset + 1;
+    Ok(sn)
+}
+
+fn read_num_type(bytes: &[u8], offset: &mut usize) -> Result<NumType, Error> {
+    read_byte(bytes, offset)?.try_into()
+}
+
+fn read_num_types(bytes: &[u8], offset: &mut usize) -> Result<Vec<NumType>, Error> {
+    let count = read_leb(bytes, offset, false)?;
+    (0..count).map(|_| read_num_type(bytes, offset)).collect()
+}
+
+fn skip_limits(bytes: &[u8], offset: &mut usize) -> Result<(), Error> {
+    let flags = read_byte(bytes, offset)?;
+    read_leb(bytes, offset, false)?;
+    if flags & 1 != 0 {
+        read_leb(bytes, offset, false)?;
+    }
+    Ok(())
+}
+
+/// Collects the function types, the function to type mapping, the local declarations and the
+/// global types of a module
+pub fn module_types_parse(bytes: &[u8]) -> Result<ModuleTypes, Error> {
+    let mut module_types = ModuleTypes::default();
+    let mut offset = WASM_SECTIONS_START_INDEX;
+    while offset < bytes.len() {
+        let section_id = read_byte(bytes, &mut offset)?;
+        let (section_len, section_len_leb_len) = wasm_compute_section_len(bytes, offset)?;
+        offset += section_len_leb_len as usize;
+        let section_end = offset + section_len;
+        let mut offset = offset;
+        match section_id {
+            id if id == WasmSection::Type as u8 => {
+                for _ in 0..read_leb(bytes, &mut offset, false)? {
+                    // the func type marker
+                    read_byte(bytes, &mut offset)?;
+                    let params = read_num_types(bytes, &mut offset)?;
+                    let results = read_num_types(bytes, &mut offset)?;
+                    module_types.types.push(FuncType { params, results });
+                }
+            }
+            id if id == WasmSection::Import as u8 => {
+                for _ in 0..read_leb(bytes, &mut offset, false)? {
+                    // module and import names
+                    for _ in 0..2 {
+                        offset += read_leb(bytes, &mut offset, false)? as usize;
+                    }
+                    let importdesc_type: ImportDescType =
+                        read_byte(bytes, &mut offset)?.try_into()?;
+                    match importdesc_type {
+                        ImportDescType::Typeidx => {
+                            let type_index = read_leb(bytes, &mut offset, false)?;
+                            module_types.funcs.push(type_index);
+                            module_types.imported_funcs_count += 1;
+                        }
+                        ImportDescType::TableType => {
+                            read_byte(bytes, &mut offset)?;
+                            skip_limits(bytes, &mut offset)?;
+                        }
+                        ImportDescType::MemType => skip_limits(bytes, &mut offset)?,
+                        ImportDescType::GlobalType => {
+                            let num_type = read_num_type(bytes, &mut offset)?;
+                            module_types.globals.push(num_type);
+                            // mutability
+                            read_byte(bytes, &mut offset)?;
+                        }
+                    }
+                }
+            }
+            id if id == WasmSection::Function as u8 => {
+                for _ in 0..read_leb(bytes, &mut offset, false)? {
+                    let type_index = read_leb(bytes, &mut offset, false)?;
+                    module_types.funcs.push(type_index);
+                }
+            }
+            id if id == WasmSection::Global as u8 => {
+                for _ in 0..read_leb(bytes, &mut offset, false)? {
+                    let num_type = read_num_type(bytes, &mut offset)?;
+                    module_types.globals.push(num_type);
+                    // mutability
+                    read_byte(bytes, &mut offset)?;
+                    let init_opcode = read_byte(bytes, &mut offset)?;
+                    if init_opcode == VariableInstruction::GlobalGet as u8 {
+                        read_leb(bytes, &mut offset, false)?;
+                    } else if init_opcode == NumericInstruction::I32Const as u8
+                        || init_opcode == NumericInstruction::I64Const as u8
+                    {
+                        read_leb(bytes, &mut offset, true)?;
+                    } else {
+                        return Err(Error::InvalidEnumValue);
+                    }
+                    if read_byte(bytes, &mut offset)? != WASM_BLOCK_END {
+                        return Err(Error::InvalidEnumValue);
+                    }
+                }
+            }
+            id if id == WasmSection::Code as u8 => {
+                for _ in 0..read_leb(bytes, &mut offset, false)? {
+                    let body_len = read_leb(bytes, &mut offset, false)? as usize;
+                    let body_end = offset + body_len;
+                    let mut locals = vec![];
+                    for _ in 0..read_leb(bytes, &mut offset, false)? {
+                        let count = read_leb(bytes, &mut offset, false)?;
+                        locals.push((count, read_num_type(bytes, &mut offset)?));
+                    }
+                    module_types.locals.push(locals);
+                    offset = body_end;
+                }
+            }
+            _ => {}
+        }
+        offset = section_end;
+    }
+
+    Ok(module_types)
+}
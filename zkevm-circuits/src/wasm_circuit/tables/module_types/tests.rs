@@ -0,0 +1,175 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use eth_types::Field;
+use gadgets::util::Expr;
+
+use crate::wasm_circuit::{
+    tables::{
+        module_types::{
+            circuit::ModuleTypesChip,
+            types::{AssignType, LookupArgsParams, ModuleTypes},
+        },
+        type_stack::types::TypeSeq,
+    },
+    types::NumType,
+};
+
+#[derive(Default)]
+struct TestCircuit<F> {
+    module_types: ModuleTypes,
+    _marker: PhantomData<F>,
+}
+
+#[derive(Clone)]
+struct TestCircuitConfig<F: Field> {
+    chip: Rc<ModuleTypesChip<F>>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> Circuit<F> for TestCircuit<F> {
+    type Config = TestCircuitConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+        let shared_state = Rc::new(RefCell::new(Default::default()));
+        let config = ModuleTypesChip::configure(cs, shared_state.clone());
+        let chip = ModuleTypesChip::construct(config);
+
+        let test_circuit_config = TestCircuitConfig {
+            chip: Rc::new(chip),
+            _marker: Default::default(),
+        };
+
+        // the defined function takes a single i64
+        let params = TypeSeq::new(&[NumType::I64]);
+        test_circuit_config.chip.lookup_args(
+            "module types func params lookup test",
+            cs,
+            |_vc| LookupArgsParams {
+                cond: 1.expr(),
+                bytecode_number: 1.expr(),
+                args: vec![
+                    (AssignType::IsFunc, 1.expr()),
+                    (AssignType::Index, 1.expr()),
+                    (AssignType::Params, (params.packed as u64).expr()),
+                    (AssignType::ParamsCount, params.count.expr()),
+                    (AssignType::ParamsShift, (params.shift() as u64).expr()),
+                ],
+            },
+        );
+
+        test_circuit_config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "module types region",
+            |mut region| {
+                config.chip.config.shared_state.borrow_mut().reset();
+                config
+                    .chip
+                    .assign_auto(&mut region, 0, 0, &self.module_types)
+                    .unwrap();
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod module_types_tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use wabt::wat2wasm;
+
+    use eth_types::Field;
+
+    use crate::wasm_circuit::{
+        tables::module_types::{
+            helpers::module_types_parse,
+            tests::TestCircuit,
+            types::{FuncType, ModuleTypes},
+        },
+        types::NumType,
+    };
+
+    fn test<'a, F: Field>(test_circuit: TestCircuit<F>, is_ok: bool) {
+        let k = 8;
+        let prover = MockProver::run(k, &test_circuit, vec![]).unwrap();
+        if is_ok {
+            prover.assert_satisfied();
+        } else {
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    fn module_types() -> ModuleTypes {
+        let wat = r#"
+            (module
+              (import "env" "f" (func (param i32) (result i64)))
+              (import "env" "g" (global i64))
+              (global (mut i32) (i32.const 0))
+              (func (param i64) (local i32 i32) (local i64)
+                local.get 0
+                drop)
+            )
+        "#;
+        module_types_parse(&wat2wasm(wat).unwrap()).unwrap()
+    }
+
+    #[test]
+    pub fn parse_ok() {
+        let module_types = module_types();
+        assert_eq!(
+            module_types.func_type(0),
+            Some(&FuncType {
+                params: vec![NumType::I32],
+                results: vec![NumType::I64],
+            })
+        );
+        assert_eq!(module_types.imported_funcs_count, 1);
+        assert_eq!(module_types.local_type(1, 0), Some(NumType::I64));
+        assert_eq!(module_types.local_type(1, 2), Some(NumType::I32));
+        assert_eq!(module_types.local_type(1, 3), Some(NumType::I64));
+        assert_eq!(module_types.local_type(1, 4), None);
+        assert_eq!(module_types.local_type(0, 1), None);
+        assert_eq!(module_types.global_type(0), Some(NumType::I64));
+        assert_eq!(module_types.global_type(1), Some(NumType::I32));
+        assert_eq!(module_types.rows_count(), 2 + 2 + 4 + 2);
+    }
+
+    #[test]
+    pub fn ok() {
+        let test_circuit = TestCircuit::<Fr> {
+            module_types: module_types(),
+            _marker: Default::default(),
+        };
+        test(test_circuit, true);
+    }
+
+    #[test]
+    pub fn wrong_params_fail() {
+        let mut module_types = module_types();
+        module_types.types[1].params = vec![NumType::I32];
+        let test_circuit = TestCircuit::<Fr> {
+            module_types,
+            _marker: Default::default(),
+        };
+        test(test_circuit, false);
+    }
+}
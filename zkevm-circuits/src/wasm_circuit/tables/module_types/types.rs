@@ -0,0 +1,100 @@
+use halo2_proofs::plonk::Expression;
+
+use eth_types::Field;
+
+use crate::wasm_circuit::types::NumType;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AssignType {
+    IsType,
+    IsFunc,
+    IsLocal,
+    IsGlobal,
+    IsParam,
+    IsRunFirst,
+    IsRunLast,
+    Index,
+    FuncIndex,
+    TypeIndex,
+    Params,
+    ParamsCount,
+    ParamsShift,
+    Results,
+    ResultsCount,
+    ResultsShift,
+    ValueType,
+    RunStart,
+    RunEnd,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FuncType {
+    pub params: Vec<NumType>,
+    pub results: Vec<NumType>,
+}
+
+/// Types a function body is checked against, parsed from the whole module
+#[derive(Clone, Debug, Default)]
+pub struct ModuleTypes {
+    pub types: Vec<FuncType>,
+    /// type index of every function, imported functions come first
+    pub funcs: Vec<u64>,
+    pub imported_funcs_count: usize,
+    /// local declarations of every defined function as (repetition count, type) pairs
+    pub locals: Vec<Vec<(u64, NumType)>>,
+    /// imported globals come first
+    pub globals: Vec<NumType>,
+}
+
+impl ModuleTypes {
+    pub fn func_type(&self, func_index: u64) -> Option<&FuncType> {
+        let type_index = *self.funcs.get(func_index as usize)?;
+        self.types.get(type_index as usize)
+    }
+
+    pub fn local_type(&self, func_index: u64, local_index: u64) -> Option<NumType> {
+        let params = &self.func_type(func_index)?.params;
+        if let Some(num_type) = params.get(local_index as usize) {
+            return Some(*num_type);
+        }
+        let defined_index = (func_index as usize).checked_sub(self.imported_funcs_count)?;
+        let mut first_index = params.len() as u64;
+        for (count, num_type) in self.locals.get(defined_index)? {
+            if local_index < first_index + count {
+                return Some(*num_type);
+            }
+            first_index += count;
+        }
+        None
+    }
+
+    pub fn global_type(&self, global_index: u64) -> Option<NumType> {
+        self.globals.get(global_index as usize).copied()
+    }
+
+    /// Number of rows the module types table takes: one per type, per function, per param and
+    /// declared local of the defined functions and per global
+    pub fn rows_count(&self) -> usize {
+        let locals_count: usize = self
+            .locals
+            .iter()
+            .enumerate()
+            .map(|(defined_index, locals)| {
+                let params_count = self
+                    .func_type((self.imported_funcs_count + defined_index) as u64)
+                    .map_or(0, |func_type| func_type.params.len());
+                let declared_count: u64 = locals.iter().map(|(count, _)| count).sum();
+                params_count + declared_count as usize
+            })
+            .sum();
+        self.types.len() + self.funcs.len() + locals_count + self.globals.len()
+    }
+}
+
+/// Lookup into the module types table, every `(column, arg)` pair is matched against the row
+/// together with the bytecode number
+pub struct LookupArgsParams<F: Field> {
+    pub cond: Expression<F>,
+    pub bytecode_number: Expression<F>,
+    pub args: Vec<(AssignType, Expression<F>)>,
+}
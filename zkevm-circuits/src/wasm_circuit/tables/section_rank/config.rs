@@ -0,0 +1,54 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+use eth_types::Field;
+
+use crate::wasm_circuit::types::WASM_SECTION_VALUES;
+
+/// Maps every section id to the rank the section order is checked on
+#[derive(Debug, Clone)]
+pub struct SectionRankTableConfig<F: Field> {
+    pub section_id: TableColumn,
+    pub rank: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> SectionRankTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        let section_id = cs.lookup_table_column();
+        let rank = cs.lookup_table_column();
+
+        Self {
+            section_id,
+            rank,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load section rank table",
+            |mut table| {
+                for (offset, section) in WASM_SECTION_VALUES.iter().enumerate() {
+                    table.assign_cell(
+                        || "section_id",
+                        self.section_id,
+                        offset,
+                        || Value::known(F::from(*section as u64)),
+                    )?;
+                    table.assign_cell(
+                        || "rank",
+                        self.rank,
+                        offset,
+                        || Value::known(F::from(section.rank())),
+                    )?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,83 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+use eth_types::Field;
+
+use crate::wasm_circuit::tables::type_stack::types::{type_stack_effects, TypeSeq};
+
+/// Maps every instruction with a fixed stack effect to the operand types it pops and the result
+/// types it pushes, each packed with its count and `2^count`. The first row is all zeros so
+/// disabled lookups match it.
+#[derive(Debug, Clone)]
+pub struct TypeStackTableConfig<F: Field> {
+    pub key: TableColumn,
+    pub pops: TableColumn,
+    pub pops_count: TableColumn,
+    pub pops_shift: TableColumn,
+    pub pushes: TableColumn,
+    pub pushes_count: TableColumn,
+    pub pushes_shift: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> TypeStackTableConfig<F> {
+    pub fn configure(cs: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            key: cs.lookup_table_column(),
+            pops: cs.lookup_table_column(),
+            pops_count: cs.lookup_table_column(),
+            pops_shift: cs.lookup_table_column(),
+            pushes: cs.lookup_table_column(),
+            pushes_count: cs.lookup_table_column(),
+            pushes_shift: cs.lookup_table_column(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load type stack table",
+            |mut table| {
+                let mut rows = vec![[F::zero(); 7]];
+                for (key, pops, pushes) in type_stack_effects() {
+                    let (pops, pushes) = (TypeSeq::new(&pops), TypeSeq::new(&pushes));
+                    rows.push([
+                        F::from(key),
+                        F::from_u128(pops.packed),
+                        F::from(pops.count),
+                        F::from_u128(pops.shift()),
+                        F::from_u128(pushes.packed),
+                        F::from(pushes.count),
+                        F::from_u128(pushes.shift()),
+                    ]);
+                }
+                for (offset, row) in rows.iter().enumerate() {
+                    for (column, value) in [
+                        self.key,
+                        self.pops,
+                        self.pops_count,
+                        self.pops_shift,
+                        self.pushes,
+                        self.pushes_count,
+                        self.pushes_shift,
+                    ]
+                    .iter()
+                    .zip(row)
+                    {
+                        table.assign_cell(
+                            || "type stack table cell",
+                            *column,
+                            offset,
+                            || Value::known(*value),
+                        )?;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,238 @@
+use crate::wasm_circuit::{
+    consts::WASM_INSTRUCTION_PREFIX_FC,
+    types::{
+        ControlInstruction, MemoryInstruction, NumType, NumericInstruction, PrefixedInstruction,
+    },
+};
+
+/// Max number of operands a function frame may hold, the packed stack must stay far below the
+/// field modulus
+pub const TYPE_STACK_MAX_LEN: usize = 128;
+
+/// Bit an operand type takes in a packed type sequence, `I32` packs to 0 and `I64` to 1
+pub fn num_type_bit(num_type: NumType) -> u64 {
+    NumType::I32 as u64 - num_type as u64
+}
+
+/// Operand types packed one bit per operand, the last operand (the top of the stack) takes the
+/// lowest bit
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeSeq {
+    pub packed: u128,
+    pub count: u64,
+}
+
+impl TypeSeq {
+    pub fn new(types: &[NumType]) -> Self {
+        let mut seq = Self::default();
+        for num_type in types {
+            seq.push(*num_type);
+        }
+        seq
+    }
+
+    pub fn push(&mut self, num_type: NumType) {
+        self.packed = self.packed * 2 + num_type_bit(num_type) as u128;
+        self.count += 1;
+    }
+
+    pub fn shift(&self) -> u128 {
+        1 << self.count
+    }
+
+    /// `other` placed on top of `self`
+    pub fn concat(&self, other: &TypeSeq) -> Self {
+        Self {
+            packed: self.packed * other.shift() + other.packed,
+            count: self.count + other.count,
+        }
+    }
+
+    /// splits off the `count` topmost operands, returns the rest and the split part
+    pub fn split_top(&self, count: u64) -> Option<(Self, Self)> {
+        if count > self.count {
+            return None;
+        }
+        let top = Self {
+            packed: self.packed % (1 << count),
+            count,
+        };
+        let rest = Self {
+            packed: self.packed >> count,
+            count: self.count - count,
+        };
+        Some((rest, top))
+    }
+}
+
+/// Key of a prefixed instruction in the type stack table, one byte opcodes are keyed by the
+/// opcode itself
+pub fn prefixed_instruction_key(sub_opcode: u64) -> u64 {
+    WASM_INSTRUCTION_PREFIX_FC as u64 * 256 + sub_opcode
+}
+
+/// Instructions with a fixed stack effect: key, popped operand types and pushed result types
+pub fn type_stack_effects() -> Vec<(u64, Vec<NumType>, Vec<NumType>)> {
+    use NumType::{I32, I64};
+
+    let mut effects = vec![
+        (NumericInstruction::I32Const as u64, vec![], vec![I32]),
+        (NumericInstruction::I64Const as u64, vec![], vec![I64]),
+        (NumericInstruction::I32Add as u64, vec![I32, I32], vec![I32]),
+        (NumericInstruction::I64Add as u64, vec![I64, I64], vec![I64]),
+        (NumericInstruction::I32extend8S as u64, vec![I32], vec![I32]),
+        (NumericInstruction::I32extend16S as u64, vec![I32], vec![I32]),
+        (NumericInstruction::I64extend8S as u64, vec![I64], vec![I64]),
+        (NumericInstruction::I64extend16S as u64, vec![I64], vec![I64]),
+        (NumericInstruction::I64extend32S as u64, vec![I64], vec![I64]),
+    ];
+    // loads and stores take the address below the stored value
+    for (instr, pops, pushes) in [
+        (MemoryInstruction::I32Load, vec![I32], vec![I32]),
+        (MemoryInstruction::I64Load, vec![I32], vec![I64]),
+        (MemoryInstruction::I32Load8S, vec![I32], vec![I32]),
+        (MemoryInstruction::I32Load8U, vec![I32], vec![I32]),
+        (MemoryInstruction::I32Load16S, vec![I32], vec![I32]),
+        (MemoryInstruction::I32Load16U, vec![I32], vec![I32]),
+        (MemoryInstruction::I64Load8S, vec![I32], vec![I64]),
+        (MemoryInstruction::I64Load8U, vec![I32], vec![I64]),
+        (MemoryInstruction::I64Load16S, vec![I32], vec![I64]),
+        (MemoryInstruction::I64Load16U, vec![I32], vec![I64]),
+        (MemoryInstruction::I64Load32S, vec![I32], vec![I64]),
+        (MemoryInstruction::I64Load32U, vec![I32], vec![I64]),
+        (MemoryInstruction::I32Store, vec![I32, I32], vec![]),
+        (MemoryInstruction::I64Store, vec![I32, I64], vec![]),
+        (MemoryInstruction::I32Store8, vec![I32, I32], vec![]),
+        (MemoryInstruction::I32Store16, vec![I32, I32], vec![]),
+        (MemoryInstruction::I64Store8, vec![I32, I64], vec![]),
+        (MemoryInstruction::I64Store16, vec![I32, I64], vec![]),
+        (MemoryInstruction::I64Store32, vec![I32, I64], vec![]),
+        (MemoryInstruction::MemorySize, vec![], vec![I32]),
+        (MemoryInstruction::MemoryGrow, vec![I32], vec![I32]),
+    ] {
+        effects.push((instr as u64, pops, pushes));
+    }
+    // `table.grow` and `table.fill` take a reference operand which has no numeric type
+    for (instr, pops, pushes) in [
+        (PrefixedInstruction::MemoryInit, vec![I32, I32, I32], vec![]),
+        (PrefixedInstruction::DataDrop, vec![], vec![]),
+        (PrefixedInstruction::MemoryCopy, vec![I32, I32, I32], vec![]),
+        (PrefixedInstruction::MemoryFill, vec![I32, I32, I32], vec![]),
+        (PrefixedInstruction::TableInit, vec![I32, I32, I32], vec![]),
+        (PrefixedInstruction::ElemDrop, vec![], vec![]),
+        (PrefixedInstruction::TableCopy, vec![I32, I32, I32], vec![]),
+        (PrefixedInstruction::TableSize, vec![], vec![I32]),
+    ] {
+        effects.push((prefixed_instruction_key(instr as u64), pops, pushes));
+    }
+
+    effects
+}
+
+/// Popped and pushed operand types of a single instruction, `operand_type` is the type bit of the
+/// operand the instruction is generic over (locals, globals, `drop` and `select`)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeStackEffect {
+    pub pops: TypeSeq,
+    pub pushes: TypeSeq,
+    pub operand_type: u64,
+}
+
+impl TypeStackEffect {
+    pub fn new(pops: &[NumType], pushes: &[NumType]) -> Self {
+        Self {
+            pops: TypeSeq::new(pops),
+            pushes: TypeSeq::new(pushes),
+            operand_type: 0,
+        }
+    }
+
+    /// effect of an instruction from `type_stack_effects`
+    pub fn fixed(key: u64) -> Option<Self> {
+        type_stack_effects()
+            .into_iter()
+            .find(|(k, _, _)| *k == key)
+            .map(|(_, pops, pushes)| Self::new(&pops, &pushes))
+    }
+}
+
+/// Params and results of a `block`, `loop` or `if`, taken from its blocktype
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockType {
+    pub params: TypeSeq,
+    pub results: TypeSeq,
+}
+
+impl BlockType {
+    pub fn new(params: &[NumType], results: &[NumType]) -> Self {
+        Self {
+            params: TypeSeq::new(params),
+            results: TypeSeq::new(results),
+        }
+    }
+}
+
+/// Operand stack of a function body at some instruction. Values below `frame_base` belong to the
+/// enclosing frames and cannot be popped, an unreachable frame skips stack checks until its
+/// `else` or `end`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeStackState {
+    pub stack: TypeSeq,
+    pub frame_id: u64,
+    pub frame_base: u64,
+    pub is_unreachable: bool,
+}
+
+impl TypeStackState {
+    /// returns the operands left under the popped ones and the new state, `None` when the popped
+    /// types do not match
+    pub fn apply(&self, effect: &TypeStackEffect) -> Option<(TypeSeq, Self)> {
+        let (rest, top) = self.stack.split_top(effect.pops.count)?;
+        if top != effect.pops || rest.count < self.frame_base {
+            return None;
+        }
+        let stack = rest.concat(&effect.pushes);
+        if stack.count as usize >= TYPE_STACK_MAX_LEN {
+            return None;
+        }
+        Some((rest, Self { stack, ..*self }))
+    }
+}
+
+/// Frame opened by `block`, `loop` or `if`, keeps the state before the opcode and right after it
+#[derive(Clone, Debug)]
+pub struct TypeStackFrame {
+    pub opcode: ControlInstruction,
+    pub block_type: BlockType,
+    pub parent: TypeStackState,
+    pub opened: TypeStackState,
+    pub has_else: bool,
+    /// assign offset of the opcode that opened the frame
+    pub opener_offset: usize,
+    /// pc of the opcode that opened the frame
+    pub opener_pc: u64,
+    /// pc of the `else` of an `if`, 0 until it is reached
+    pub else_pc: u64,
+    /// assign offsets and block opcode numbers of the `br`/`br_if` targeting the frame, their
+    /// frame end is only known at the `end`
+    pub labels: Vec<(usize, u64)>,
+}
+
+impl TypeStackFrame {
+    /// operand types a branch to the frame takes, a loop is re-entered with its params
+    pub fn label_types(&self) -> TypeSeq {
+        if self.opcode == ControlInstruction::Loop {
+            self.block_type.params
+        } else {
+            self.block_type.results
+        }
+    }
+
+    /// operands of the enclosing frame left under the params
+    pub fn opened_rest(&self) -> TypeSeq {
+        TypeSeq {
+            packed: self.opened.stack.packed >> self.block_type.params.count,
+            count: self.opened.frame_base,
+        }
+    }
+}
@@ -11,7 +11,7 @@ use eth_types::{Field, ToWord};
 use crate::wasm_circuit::{
     bytecode::{bytecode::WasmBytecode, bytecode_table::WasmBytecodeTable},
     circuit::{WasmChip, WasmConfig},
-    types::SharedState,
+    types::{ErrorCode, SharedState},
 };
 
 #[derive(Default)]
@@ -19,6 +19,8 @@ struct TestCircuitWithErrorProcessing<F> {
     wbs: Vec<WasmBytecode>,
     wb_offset: usize,
     assign_delta_base: usize,
+    /// every bytecode must end up with the error flag raised
+    assert_rejected: bool,
     _marker: PhantomData<F>,
 }
 
@@ -33,6 +35,7 @@ impl<F: Field> Circuit<F> for TestCircuitWithErrorProcessing<F> {
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
         let mut shared_state = Rc::new(RefCell::new(SharedState::default()));
         shared_state.borrow_mut().error_processing_enabled = true;
+        shared_state.borrow_mut().host_imports_restricted = true;
         let wb_table = Rc::new(WasmBytecodeTable::construct(cs, true));
         let config = WasmChip::<F>::configure(cs, wb_table, shared_state);
 
@@ -58,6 +61,12 @@ impl<F: Field> Circuit<F> for TestCircuitWithErrorProcessing<F> {
                     assign_delta = wasm_chip
                         .assign_auto(&mut region, wb, self.wb_offset, assign_delta)
                         .unwrap();
+                    if self.assert_rejected {
+                        assert_eq!(
+                            wasm_chip.config.shared_state.borrow().error_code,
+                            ErrorCode::Error as u64
+                        );
+                    }
                     // debug!(
                     //     "RESULT error_code {}",
                     //     wasm_chip.config.shared_state.borrow().error_code
@@ -72,15 +81,19 @@ impl<F: Field> Circuit<F> for TestCircuitWithErrorProcessing<F> {
     }
 }
 
+/// Parses modules without error processing, host imports are checked only if
+/// `HOST_IMPORTS_RESTRICTED`
 #[derive(Default)]
-struct TestCircuit<F> {
+struct TestCircuit<F, const HOST_IMPORTS_RESTRICTED: bool = false> {
     wbs: Vec<WasmBytecode>,
     wb_offset: usize,
     assign_delta_base: usize,
     _marker: PhantomData<F>,
 }
 
-impl<F: Field> Circuit<F> for TestCircuit<F> {
+impl<F: Field, const HOST_IMPORTS_RESTRICTED: bool> Circuit<F>
+    for TestCircuit<F, HOST_IMPORTS_RESTRICTED>
+{
     type Config = WasmConfig<F>;
     type FloorPlanner = SimpleFloorPlanner;
 
@@ -90,6 +103,7 @@ impl<F: Field> Circuit<F> for TestCircuit<F> {
 
     fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
         let shared_state = Rc::new(RefCell::new(SharedState::default()));
+        shared_state.borrow_mut().host_imports_restricted = HOST_IMPORTS_RESTRICTED;
         let wb_table = Rc::new(WasmBytecodeTable::construct(cs, true));
         let config = WasmChip::<F>::configure(cs, wb_table, shared_state);
 
@@ -134,7 +148,7 @@ mod wasm_circuit_tests {
     use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
     use log::debug;
     use rand::{random, thread_rng, Rng};
-    use wabt::wat2wasm;
+    use wabt::{wat2wasm, wat2wasm_with_features, Features};
 
     use eth_types::Field;
 
@@ -145,12 +159,20 @@ mod wasm_circuit_tests {
             WASM_VERSION_PREFIX_END_INDEX, WASM_VERSION_PREFIX_LEN,
             WASM_VERSION_PREFIX_START_INDEX,
         },
+        common::wasm_compute_section_len,
         tests::{TestCircuit, TestCircuitWithErrorProcessing},
-        tests_helpers::mutate_byte,
-        types::WasmSection,
+        tests_helpers::{custom_section_bytecode, mutate_byte},
+        types::{
+            MemoryInstruction, NumType, NumericInstruction, ParametricInstruction,
+            VariableInstruction, WasmSection,
+        },
     };
 
-    fn test<'a, F: Field>(test_circuit: &TestCircuit<F>, is_ok: bool, k: u32) {
+    fn test<'a, F: Field, const HOST_IMPORTS_RESTRICTED: bool>(
+        test_circuit: &TestCircuit<F, HOST_IMPORTS_RESTRICTED>,
+        is_ok: bool,
+        k: u32,
+    ) {
         let prover = MockProver::run(k, test_circuit, vec![]).unwrap();
         if is_ok {
             prover.assert_satisfied();
@@ -167,6 +189,19 @@ mod wasm_circuit_tests {
         debug!("wb: {:x?}", wb.bytes);
     }
 
+    /// Splits a module into its magic prefix with version and its sections
+    fn split_sections(bytes: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
+        let mut sections = vec![];
+        let mut offset = WASM_VERSION_PREFIX_END_INDEX + 1;
+        while offset < bytes.len() {
+            let (len, leb_len) = wasm_compute_section_len(bytes, offset + 1).unwrap();
+            let end = offset + 1 + leb_len as usize + len;
+            sections.push(bytes[offset..end].to_vec());
+            offset = end;
+        }
+        (bytes[..=WASM_VERSION_PREFIX_END_INDEX].to_vec(), sections)
+    }
+
     fn test_with_error_processing<'a, F: Field>(
         test_circuit: &TestCircuitWithErrorProcessing<F>,
         is_ok: bool,
@@ -379,6 +414,162 @@ mod wasm_circuit_tests {
         test_with_error_processing(&circuit, true, 9);
     }
 
+    #[test]
+    pub fn float_module_rejected_ok() {
+        let codes = [
+            std::fs::read("./test_files/cc6.wat").unwrap(),
+            br#"(module (func (local f32)))"#.to_vec(),
+            br#"(module (func f32.const 0 drop))"#.to_vec(),
+            br#"(module (global f64 (f64.const 0)))"#.to_vec(),
+        ];
+        for data in codes {
+            let bytes = wat2wasm(data).unwrap();
+            let wb = WasmBytecode::new(bytes);
+            debug_wb(&wb);
+            let circuit = TestCircuitWithErrorProcessing::<Fr> {
+                wbs: vec![wb],
+                ..Default::default()
+            };
+            test_with_error_processing(&circuit, true, 9);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn float_module_without_error_processing_must_fail() {
+        let path = "./test_files/cc6.wat";
+        let data: Vec<u8> = std::fs::read(path).unwrap();
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn selfdestruct_import_rejected_ok() {
+        let data = br#"(module
+            (import "env" "_evm_address" (func (param i32)))
+            (import "env" "_evm_selfdestruct" (func (param i32)))
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        debug_wb(&wb);
+        let circuit = TestCircuitWithErrorProcessing::<Fr> {
+            wbs: vec![wb],
+            assert_rejected: true,
+            ..Default::default()
+        };
+        test_with_error_processing(&circuit, true, 9);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn selfdestruct_import_without_error_processing_must_fail() {
+        let data = br#"(module (import "env" "_evm_selfdestruct" (func (param i32))))"#;
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        let circuit = TestCircuit::<Fr, true> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn host_imports_ok() {
+        let data = br#"(module
+            (import "env" "_evm_address" (func $address (param i32)))
+            (import "env" "_evm_sload" (func $sload (param i32 i32)))
+            (import "env" "_evm_stop" (func $stop))
+            (memory 1)
+            (func (export "main")
+                i32.const 0
+                call $address
+                i32.const 0
+                i32.const 32
+                call $sload
+                call $stop)
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        debug_wb(&wb);
+        let circuit = TestCircuit::<Fr, true> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    fn forbidden_import_modules() -> Vec<Vec<u8>> {
+        [
+            // not a host function
+            r#"(module (import "env" "_evm_address_" (func (param i32))))"#,
+            // host function from another module
+            r#"(module (import "evm" "_evm_address" (func (param i32))))"#,
+            // host function with a different signature
+            r#"(module (import "env" "_evm_address" (func (param i64))))"#,
+            r#"(module (import "env" "_evm_address" (func (param i32) (result i32))))"#,
+            r#"(module (import "env" "_evm_sload" (func (param i32))))"#,
+            // only functions are provided by the host
+            r#"(module (import "env" "_evm_address" (global i32)))"#,
+            r#"(module (import "env" "memory" (memory 1)))"#,
+        ]
+        .iter()
+        .map(|wat| wat2wasm(wat).unwrap())
+        .collect()
+    }
+
+    #[test]
+    pub fn forbidden_import_rejected_ok() {
+        for bytes in forbidden_import_modules() {
+            let wb = WasmBytecode::new(bytes);
+            debug_wb(&wb);
+            let circuit = TestCircuitWithErrorProcessing::<Fr> {
+                wbs: vec![wb],
+                assert_rejected: true,
+                ..Default::default()
+            };
+            test_with_error_processing(&circuit, true, 9);
+        }
+    }
+
+    #[test]
+    pub fn fixture_imports_rejected_ok() {
+        // the fixtures import a table, a memory and globals from other modules, and a function
+        // with a long name that is no host function
+        let paths = [
+            "./test_files/cc1.wat",
+            "./test_files/cc2.wat",
+            "./test_files/cc3.wat",
+        ];
+        for path in paths {
+            debug!("processing file '{}'", path);
+            let data: Vec<u8> = std::fs::read(path).unwrap();
+            let bytes = wat2wasm(data).unwrap();
+            let wb = WasmBytecode::new(bytes);
+            let circuit = TestCircuitWithErrorProcessing::<Fr> {
+                wbs: vec![wb],
+                assert_rejected: true,
+                ..Default::default()
+            };
+            test_with_error_processing(&circuit, true, 9);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn forbidden_import_without_error_processing_must_fail() {
+        let wb = WasmBytecode::new(forbidden_import_modules().remove(0));
+        let circuit = TestCircuit::<Fr, true> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
     #[test]
     pub fn file1_invalid_section_id_parse_error_ok() {
         let path = "./test_files/cc1.wat";
@@ -397,4 +588,399 @@ mod wasm_circuit_tests {
         };
         test_with_error_processing(&circuit, true, 9);
     }
+
+    #[test]
+    pub fn custom_sections_ok() {
+        let data = br#"(module
+            (memory 1)
+            (func (export "main") (local i32)
+                i32.const 1
+                local.set 0
+            )
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let (mut bytes, sections) = split_sections(&bytes);
+        bytes.extend(custom_section_bytecode(b"target_features", b"\x01+\x0bbulk-memory"));
+        for (i, section) in sections.iter().enumerate() {
+            bytes.extend(section);
+            if i == 0 {
+                bytes.extend(custom_section_bytecode(b"", &[0xff; 200]));
+            }
+        }
+        bytes.extend(custom_section_bytecode(b"name", b"\x01\x07\x01\x00\x04main"));
+        bytes.extend(custom_section_bytecode(
+            b"producers",
+            b"\x01\x0cprocessed-by\x01\x05rustc\x061.70.0",
+        ));
+        bytes.extend(custom_section_bytecode(b"target_features", &[]));
+        let wb = WasmBytecode::new(bytes);
+        debug_wb(&wb);
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 10);
+    }
+
+    #[test]
+    pub fn custom_section_does_not_reset_section_order_must_fail() {
+        let data = br#"(module
+            (memory 1)
+            (func (export "main"))
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let (mut bytes, mut sections) = split_sections(&bytes);
+        let memory_section_index = sections
+            .iter()
+            .position(|s| s[0] == WasmSection::Memory as u8)
+            .unwrap();
+        let memory_section = sections.remove(memory_section_index);
+        let function_section_index = sections
+            .iter()
+            .position(|s| s[0] == WasmSection::Function as u8)
+            .unwrap();
+        sections.insert(function_section_index, custom_section_bytecode(b"name", &[]));
+        sections.insert(function_section_index, memory_section);
+        for section in sections {
+            bytes.extend(section);
+        }
+        let wb = WasmBytecode::new(bytes);
+        debug_wb(&wb);
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, false, 9);
+    }
+
+    /// Inserts a data count section with the given count before the code section
+    fn with_data_count_section(bytes: &[u8], count: u8) -> Vec<u8> {
+        let (mut bytes, mut sections) = split_sections(bytes);
+        let code_section_index = sections
+            .iter()
+            .position(|s| s[0] == WasmSection::Code as u8)
+            .unwrap();
+        sections.insert(
+            code_section_index,
+            vec![WasmSection::DataCount as u8, 1, count],
+        );
+        for section in sections {
+            bytes.extend(section);
+        }
+        bytes
+    }
+
+    #[test]
+    pub fn data_count_section_ok() {
+        let data = br#"(module
+            (memory 1)
+            (func (export "main"))
+            (data (i32.const 0) "abc")
+            (data (i32.const 8) "def")
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(with_data_count_section(&bytes, 2));
+        debug_wb(&wb);
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn data_count_mismatch_parse_error_ok() {
+        let data = br#"(module
+            (memory 1)
+            (func (export "main"))
+            (data (i32.const 0) "abc")
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(with_data_count_section(&bytes, 2));
+        debug_wb(&wb);
+        let circuit = TestCircuitWithErrorProcessing::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test_with_error_processing(&circuit, true, 9);
+    }
+
+    /// Module using `memory.init` and `data.drop`, stripped of its data count section
+    fn data_index_module() -> Vec<u8> {
+        let data = br#"(module
+            (memory 1)
+            (func (export "main")
+                i32.const 0
+                i32.const 0
+                i32.const 3
+                memory.init 0
+                data.drop 0)
+            (data "abc")
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let (mut bytes, sections) = split_sections(&bytes);
+        for section in sections {
+            if section[0] != WasmSection::DataCount as u8 {
+                bytes.extend(section);
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    pub fn data_index_with_data_count_section_ok() {
+        let wb = WasmBytecode::new(with_data_count_section(&data_index_module(), 1));
+        debug_wb(&wb);
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn data_index_without_data_count_section_rejected_ok() {
+        let wb = WasmBytecode::new(data_index_module());
+        debug_wb(&wb);
+        let circuit = TestCircuitWithErrorProcessing::<Fr> {
+            wbs: vec![wb],
+            assert_rejected: true,
+            ..Default::default()
+        };
+        test_with_error_processing(&circuit, true, 9);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn data_index_without_data_count_section_without_error_processing_must_fail() {
+        let wb = WasmBytecode::new(data_index_module());
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn type_checked_module_ok() {
+        let data = br#"(module
+            (global $g (mut i64) (i64.const 0))
+            (func $add (param i32 i32) (result i32)
+                local.get 0
+                local.get 1
+                i32.add)
+            (func (export "main") (param i32) (result i32) (local i64)
+                block
+                    local.get 0
+                    br_if 0
+                    i64.const 1
+                    global.get $g
+                    i64.add
+                    local.tee 1
+                    global.set $g
+                end
+                local.get 0
+                if
+                    i32.const 7
+                    local.set 0
+                else
+                    unreachable
+                end
+                local.get 0
+                i32.const 1
+                call $add
+                i32.const 2
+                local.get 0
+                select
+                br 0)
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        debug_wb(&wb);
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn multi_value_blocks_ok() {
+        let data = br#"(module
+            (func (export "main") (param i32) (result i32)
+                local.get 0
+                block (param i32) (result i32 i64)
+                    i64.const 1
+                end
+                drop
+                loop (param i32) (result i32)
+                    i32.const 1
+                    i32.add
+                    local.get 0
+                    br_if 0
+                end
+                block (param i32) (result i32)
+                    br 0
+                end
+                if (result i32)
+                    i32.const 1
+                else
+                    i32.const 2
+                end
+                local.get 0
+                if (param i32) (result i32)
+                    i32.const 1
+                    i32.add
+                end
+                drop
+                block (result i32)
+                    block
+                        i32.const 5
+                        br 1
+                    end
+                    unreachable
+                end)
+        )"#;
+        let mut features = Features::new();
+        features.enable_multi_value();
+        let bytes = wat2wasm_with_features(data, features).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        debug_wb(&wb);
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 10);
+    }
+
+    /// Modules made ill-typed by patching a single byte of their code
+    fn ill_typed_modules() -> Vec<Vec<u8>> {
+        let mut modules = vec![];
+
+        // the result type becomes i64
+        let mut bytes = wat2wasm(br#"(module (func (result i32) i32.const 0))"#).unwrap();
+        let idx = bytes
+            .iter()
+            .rposition(|b| *b == NumericInstruction::I32Const as u8)
+            .unwrap();
+        bytes[idx] = NumericInstruction::I64Const as u8;
+        modules.push(bytes);
+
+        // the local index becomes out of range
+        let mut bytes = wat2wasm(br#"(module (func (local i32) local.get 0 drop))"#).unwrap();
+        let idx = bytes
+            .iter()
+            .rposition(|b| *b == VariableInstruction::LocalGet as u8)
+            .unwrap();
+        bytes[idx + 1] = 1;
+        modules.push(bytes);
+
+        // `i32.const 0` becomes `drop unreachable` which drops from an empty stack
+        let mut bytes = wat2wasm(br#"(module (func i32.const 0 drop))"#).unwrap();
+        let idx = bytes
+            .iter()
+            .rposition(|b| *b == NumericInstruction::I32Const as u8)
+            .unwrap();
+        bytes[idx] = ParametricInstruction::Drop as u8;
+        modules.push(bytes);
+
+        // the block result type becomes i64
+        let mut bytes =
+            wat2wasm(br#"(module (func block (result i32) i32.const 0 end drop))"#).unwrap();
+        let idx = bytes
+            .iter()
+            .rposition(|b| *b == NumericInstruction::I32Const as u8)
+            .unwrap();
+        bytes[idx - 1] = NumType::I64 as u8;
+        modules.push(bytes);
+
+        modules
+    }
+
+    #[test]
+    pub fn type_mismatch_parse_error_ok() {
+        for bytes in ill_typed_modules() {
+            let wb = WasmBytecode::new(bytes);
+            debug_wb(&wb);
+            let circuit = TestCircuitWithErrorProcessing::<Fr> {
+                wbs: vec![wb],
+                ..Default::default()
+            };
+            test_with_error_processing(&circuit, true, 9);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn type_mismatch_without_error_processing_must_fail() {
+        let wb = WasmBytecode::new(ill_typed_modules().remove(0));
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn memory_instructions_ok() {
+        let data = br#"(module
+            (memory 1)
+            (func (export "main") (param i32) (result i64)
+                local.get 0
+                i32.const 7
+                i32.store offset=4
+                local.get 0
+                i64.const 9
+                i64.store32 align=2
+                local.get 0
+                i32.load8_u offset=1
+                drop
+                memory.size
+                memory.grow
+                drop
+                local.get 0
+                i64.load16_s offset=300))
+        )"#;
+        let bytes = wat2wasm(data).unwrap();
+        let wb = WasmBytecode::new(bytes);
+        debug_wb(&wb);
+        let circuit = TestCircuit::<Fr> {
+            wbs: vec![wb],
+            ..Default::default()
+        };
+        test(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn float_memory_instruction_rejected_ok() {
+        let mut bytes =
+            wat2wasm(br#"(module (memory 1) (func i32.const 0 i32.load drop))"#).unwrap();
+        let idx = bytes
+            .iter()
+            .rposition(|b| *b == MemoryInstruction::I32Load as u8)
+            .unwrap();
+        bytes[idx] = MemoryInstruction::F32Load as u8;
+        let wb = WasmBytecode::new(bytes);
+        debug_wb(&wb);
+        let circuit = TestCircuitWithErrorProcessing::<Fr> {
+            wbs: vec![wb],
+            assert_rejected: true,
+            ..Default::default()
+        };
+        test_with_error_processing(&circuit, true, 9);
+    }
+
+    #[test]
+    pub fn evm_bytecode_next_to_module_ok() {
+        // PUSH1 0 PUSH1 0 RETURN is parsed like any other code and gets its error flag raised,
+        // the module after it is still accepted
+        let evm_wb = WasmBytecode::new(vec![0x60, 0x00, 0x60, 0x00, 0xf3]);
+        let data: Vec<u8> = std::fs::read("./test_files/cc1.wat").unwrap();
+        let wb = WasmBytecode::new(wat2wasm(data).unwrap());
+        let circuit = TestCircuitWithErrorProcessing::<Fr> {
+            wbs: vec![evm_wb, wb],
+            ..Default::default()
+        };
+        test_with_error_processing(&circuit, true, 10);
+    }
 }
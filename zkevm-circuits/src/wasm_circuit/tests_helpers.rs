@@ -1,5 +1,7 @@
 use rand::{random, Rng, thread_rng};
 
+use crate::wasm_circuit::{leb128::helpers::leb128_encode, types::WasmSection};
+
 pub fn break_bit_by_mask(byte_to_break: &mut u8, break_mask: u8) {
     *byte_to_break = (!*byte_to_break & break_mask) | (*byte_to_break & !break_mask);
 }
@@ -7,4 +9,14 @@ pub fn break_bit_by_mask(byte_to_break: &mut u8, break_mask: u8) {
 pub fn mutate_byte(byte_to_mutate: &mut u8) {
     let mut byte_old_val = *byte_to_mutate;
     while byte_old_val == *byte_to_mutate { *byte_to_mutate = random(); }
-}
\ No newline at end of file
+}
+
+pub fn custom_section_bytecode(name: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut body = leb128_encode(false, name.len() as i128).unwrap();
+    body.extend(name);
+    body.extend(payload);
+    let mut section = vec![WasmSection::Custom as u8];
+    section.extend(leb128_encode(false, body.len() as i128).unwrap());
+    section.extend(body);
+    section
+}
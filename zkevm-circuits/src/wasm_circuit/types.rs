@@ -37,6 +37,53 @@ pub enum ErrorCode {
     Error = 1,
 }
 
+/// Tag of a row of the module view of the wasm bytecode table. The tags the wasm function table
+/// also has share its values so that function table rows are looked up into the view as they are.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WasmModuleTag {
+    /// Number of slots in the table, at the minimum of its limits
+    TableSize = 1,
+    /// Function index plus one referenced by a table slot, at the function index of an active
+    /// element segment
+    Element = 2,
+    /// Type index of a function, at the type index of an imported or a declared function
+    FunctionType = 3,
+    /// Number of params of a function, at the body size of a declared function. Indexed by the
+    /// function index plus one, `arg` is zero for the entry function and the index otherwise.
+    ParamCount = 4,
+    /// Number of locals of a function, params included, at the last local declaration of a
+    /// declared function. Indexed like `ParamCount`.
+    LocalCount = 5,
+    /// Index of the function exported as `main`, at the index of its export
+    EntryFunction = 7,
+    /// Opcode of an instruction of the code section, at its opcode byte. Indexed by the position
+    /// of the instruction in the code section, the program counter of the execution, prefixed
+    /// opcodes are packed like `OpcodeId::as_u64`. `arg` is the offset of a memarg and the first
+    /// immediate of the variable, control and prefixed instructions and of `memory.size` and
+    /// `memory.grow`. It is the number of params plus the number of results times 2^16 for a
+    /// `block`, `loop` or `if`, the pc of the opener plus one for an `else` and an `end` closing
+    /// a frame.
+    Instruction = 8,
+    /// Initial number of pages of the memory, at the minimum of its limits
+    MemoryPages = 9,
+    /// Position of the first byte of a data segment in the bytecode, at the last byte of the
+    /// segment length. Indexed by the data index, `arg` is the position past the last byte.
+    DataSegment = 10,
+    /// Pc of the `end` of a `block`, `loop` or `if`, at its blocktype. Indexed by the pc of the
+    /// opener, `arg` is the pc of the `else` of an `if` and 0 without one.
+    Block = 11,
+    /// Pc plus one of the `block`, `loop` or `if` a `br` or `br_if` targets, 0 for the function
+    /// body, at the label. Indexed by the pc of the branch, `arg` is the label.
+    Label = 12,
+}
+
+impl<F: FieldExt> Expr<F> for WasmModuleTag {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        Expression::Constant(F::from(*self as u64))
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum WasmSection {
     Custom = 0,
@@ -83,6 +130,20 @@ impl TryFrom<i32> for WasmSection {
     }
 }
 
+impl WasmSection {
+    /// Position of the section in a module. The data count section goes right
+    /// before the code section, custom sections may appear anywhere and have no
+    /// rank of their own.
+    pub fn rank(&self) -> u64 {
+        match self {
+            WasmSection::Custom => 0,
+            WasmSection::DataCount => WasmSection::Element as u64 + 1,
+            WasmSection::Code | WasmSection::Data => *self as u64 + 1,
+            _ => *self as u64,
+        }
+    }
+}
+
 impl<F: FieldExt> Expr<F> for WasmSection {
     #[inline]
     fn expr(&self) -> Expression<F> {
@@ -95,17 +156,26 @@ impl<F: FieldExt> Expr<F> for WasmSection {
 pub enum NumType {
     I32 = 0x7F,
     I64 = 0x7E,
-    // not supported yet
-    // F32 = 0x7D,
-    // F64 = 0x7C,
+    // known only to reject them, see `FLOAT_NUM_TYPE_VALUES`
+    F32 = 0x7D,
+    F64 = 0x7C,
 }
 
 pub const NUM_TYPE_VALUES: &[NumType] = &[
     NumType::I32,
     NumType::I64,
-    // NumType::F32,
-    // NumType::F64,
 ];
+/// Float types are not supported, a module using them is rejected by the parser
+pub const FLOAT_NUM_TYPE_VALUES: &[NumType] = &[
+    NumType::F32,
+    NumType::F64,
+];
+
+impl NumType {
+    pub fn is_float_value(v: u8) -> bool {
+        FLOAT_NUM_TYPE_VALUES.iter().any(|t| *t as u8 == v)
+    }
+}
 
 impl TryFrom<u8> for NumType {
     type Error = Error;
@@ -490,10 +560,90 @@ pub enum NumericInstruction {
     I64extend32S = 0xc4,
 }
 
-pub const NUMERIC_INSTRUCTIONS_WITHOUT_ARGS: &[NumericInstruction] =
-    &[NumericInstruction::I32Add, NumericInstruction::I64Add];
+pub const NUMERIC_INSTRUCTIONS_WITHOUT_ARGS: &[NumericInstruction] = &[
+    NumericInstruction::I32Add,
+    NumericInstruction::I64Add,
+    NumericInstruction::I32extend8S,
+    NumericInstruction::I32extend16S,
+    NumericInstruction::I64extend8S,
+    NumericInstruction::I64extend16S,
+    NumericInstruction::I64extend32S,
+];
 pub const NUMERIC_INSTRUCTION_WITH_LEB_ARG: &[NumericInstruction] =
     &[NumericInstruction::I32Const, NumericInstruction::I64Const];
+/// Float instructions are not supported, a module using them is rejected by the parser
+pub const FLOAT_NUMERIC_INSTRUCTIONS: &[NumericInstruction] = &[
+    NumericInstruction::F32Const,
+    NumericInstruction::F64Const,
+    NumericInstruction::F32Eq,
+    NumericInstruction::F32Ne,
+    NumericInstruction::F32Lt,
+    NumericInstruction::F32Gt,
+    NumericInstruction::F32Le,
+    NumericInstruction::F32Ge,
+    NumericInstruction::F64Eq,
+    NumericInstruction::F64Ne,
+    NumericInstruction::F64Lt,
+    NumericInstruction::F64Gt,
+    NumericInstruction::F64Le,
+    NumericInstruction::F64Ge,
+    NumericInstruction::F32Abs,
+    NumericInstruction::F32Neg,
+    NumericInstruction::F32Ceil,
+    NumericInstruction::F32Floor,
+    NumericInstruction::F32Trunc,
+    NumericInstruction::F32Nearest,
+    NumericInstruction::F32Sqrt,
+    NumericInstruction::F32Add,
+    NumericInstruction::F32Sub,
+    NumericInstruction::F32Mul,
+    NumericInstruction::F32Div,
+    NumericInstruction::F32Min,
+    NumericInstruction::F32Max,
+    NumericInstruction::F32Copysign,
+    NumericInstruction::F64Abs,
+    NumericInstruction::F64Neg,
+    NumericInstruction::F64Ceil,
+    NumericInstruction::F64Floor,
+    NumericInstruction::F64Trunc,
+    NumericInstruction::F64Nearest,
+    NumericInstruction::F64Sqrt,
+    NumericInstruction::F64Add,
+    NumericInstruction::F64Sub,
+    NumericInstruction::F64Mul,
+    NumericInstruction::F64Div,
+    NumericInstruction::F64Min,
+    NumericInstruction::F64Max,
+    NumericInstruction::F64Copysign,
+    NumericInstruction::I32TruncSF32,
+    NumericInstruction::I32TruncUF32,
+    NumericInstruction::I32TruncSF64,
+    NumericInstruction::I32TruncUF64,
+    NumericInstruction::I64TruncSF32,
+    NumericInstruction::I64TruncUF32,
+    NumericInstruction::I64TruncSF64,
+    NumericInstruction::I64TruncUF64,
+    NumericInstruction::F32ConvertSI32,
+    NumericInstruction::F32ConvertUI32,
+    NumericInstruction::F32ConvertSI64,
+    NumericInstruction::F32ConvertUI64,
+    NumericInstruction::F32DemoteF64,
+    NumericInstruction::F64ConvertSI32,
+    NumericInstruction::F64ConvertUI32,
+    NumericInstruction::F64ConvertSI64,
+    NumericInstruction::F64ConvertUI64,
+    NumericInstruction::F64PromoteF32,
+    NumericInstruction::I32ReinterpretF32,
+    NumericInstruction::I64ReinterpretF64,
+    NumericInstruction::F32ReinterpretI32,
+    NumericInstruction::F64ReinterpretI64,
+];
+
+impl NumericInstruction {
+    pub fn is_float_value(v: u8) -> bool {
+        FLOAT_NUMERIC_INSTRUCTIONS.iter().any(|instr| *instr as u8 == v)
+    }
+}
 
 impl TryFrom<u8> for NumericInstruction {
     type Error = Error;
@@ -569,6 +719,111 @@ impl<F: FieldExt> Expr<F> for VariableInstruction {
     }
 }
 
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryInstruction {
+    I32Load = 0x28,
+    I64Load = 0x29,
+    F32Load = 0x2A,
+    F64Load = 0x2B,
+    I32Load8S = 0x2C,
+    I32Load8U = 0x2D,
+    I32Load16S = 0x2E,
+    I32Load16U = 0x2F,
+    I64Load8S = 0x30,
+    I64Load8U = 0x31,
+    I64Load16S = 0x32,
+    I64Load16U = 0x33,
+    I64Load32S = 0x34,
+    I64Load32U = 0x35,
+    I32Store = 0x36,
+    I64Store = 0x37,
+    F32Store = 0x38,
+    F64Store = 0x39,
+    I32Store8 = 0x3A,
+    I32Store16 = 0x3B,
+    I64Store8 = 0x3C,
+    I64Store16 = 0x3D,
+    I64Store32 = 0x3E,
+    MemorySize = 0x3F,
+    MemoryGrow = 0x40,
+}
+
+/// Loads and stores, followed by a memarg of two LEB128 immediates: the alignment and the offset
+pub const MEMORY_INSTRUCTION_WITH_MEMARG: &[MemoryInstruction] = &[
+    MemoryInstruction::I32Load,
+    MemoryInstruction::I64Load,
+    MemoryInstruction::I32Load8S,
+    MemoryInstruction::I32Load8U,
+    MemoryInstruction::I32Load16S,
+    MemoryInstruction::I32Load16U,
+    MemoryInstruction::I64Load8S,
+    MemoryInstruction::I64Load8U,
+    MemoryInstruction::I64Load16S,
+    MemoryInstruction::I64Load16U,
+    MemoryInstruction::I64Load32S,
+    MemoryInstruction::I64Load32U,
+    MemoryInstruction::I32Store,
+    MemoryInstruction::I64Store,
+    MemoryInstruction::I32Store8,
+    MemoryInstruction::I32Store16,
+    MemoryInstruction::I64Store8,
+    MemoryInstruction::I64Store16,
+    MemoryInstruction::I64Store32,
+];
+/// Followed by the index of the memory, which is always 0
+pub const MEMORY_INSTRUCTION_WITH_MEMORY_INDEX: &[MemoryInstruction] =
+    &[MemoryInstruction::MemorySize, MemoryInstruction::MemoryGrow];
+pub const FLOAT_MEMORY_INSTRUCTIONS: &[MemoryInstruction] = &[
+    MemoryInstruction::F32Load,
+    MemoryInstruction::F64Load,
+    MemoryInstruction::F32Store,
+    MemoryInstruction::F64Store,
+];
+
+impl MemoryInstruction {
+    pub fn is_float_value(v: u8) -> bool {
+        FLOAT_MEMORY_INSTRUCTIONS.iter().any(|instr| *instr as u8 == v)
+    }
+
+    /// Number of LEB128 immediates following the opcode
+    pub fn leb_args_count(&self) -> usize {
+        if MEMORY_INSTRUCTION_WITH_MEMARG.contains(self) {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+impl TryFrom<u8> for MemoryInstruction {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in MEMORY_INSTRUCTION_WITH_MEMARG
+            .iter()
+            .chain(MEMORY_INSTRUCTION_WITH_MEMORY_INDEX)
+        {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<MemoryInstruction> for usize {
+    fn from(t: MemoryInstruction) -> Self {
+        t as usize
+    }
+}
+
+impl<F: FieldExt> Expr<F> for MemoryInstruction {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        Expression::Constant(F::from(*self as u64))
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ControlInstruction {
     Unreachable = 0x00,
@@ -670,23 +925,138 @@ impl<F: FieldExt> Expr<F> for ParametricInstruction {
     }
 }
 
+/// Sub-opcodes of the instructions prefixed with `WASM_INSTRUCTION_PREFIX_FC`
+#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PrefixedInstruction {
+    I32TruncSatSF32 = 0x00,
+    I32TruncSatUF32 = 0x01,
+    I32TruncSatSF64 = 0x02,
+    I32TruncSatUF64 = 0x03,
+    I64TruncSatSF32 = 0x04,
+    I64TruncSatUF32 = 0x05,
+    I64TruncSatSF64 = 0x06,
+    I64TruncSatUF64 = 0x07,
+    MemoryInit = 0x08,
+    DataDrop = 0x09,
+    MemoryCopy = 0x0A,
+    MemoryFill = 0x0B,
+    TableInit = 0x0C,
+    ElemDrop = 0x0D,
+    TableCopy = 0x0E,
+    TableGrow = 0x0F,
+    TableSize = 0x10,
+    TableFill = 0x11,
+}
+
+pub const PREFIXED_INSTRUCTIONS_WITHOUT_ARGS: &[PrefixedInstruction] = &[
+    PrefixedInstruction::I32TruncSatSF32,
+    PrefixedInstruction::I32TruncSatUF32,
+    PrefixedInstruction::I32TruncSatSF64,
+    PrefixedInstruction::I32TruncSatUF64,
+    PrefixedInstruction::I64TruncSatSF32,
+    PrefixedInstruction::I64TruncSatUF32,
+    PrefixedInstruction::I64TruncSatSF64,
+    PrefixedInstruction::I64TruncSatUF64,
+];
+/// The saturating truncations read a float operand, so they are rejected like
+/// the rest of `FLOAT_NUMERIC_INSTRUCTIONS`
+pub const FLOAT_PREFIXED_INSTRUCTIONS: &[PrefixedInstruction] = &[
+    PrefixedInstruction::I32TruncSatSF32,
+    PrefixedInstruction::I32TruncSatUF32,
+    PrefixedInstruction::I32TruncSatSF64,
+    PrefixedInstruction::I32TruncSatUF64,
+    PrefixedInstruction::I64TruncSatSF32,
+    PrefixedInstruction::I64TruncSatUF32,
+    PrefixedInstruction::I64TruncSatSF64,
+    PrefixedInstruction::I64TruncSatUF64,
+];
+pub const PREFIXED_INSTRUCTION_WITH_LEB_ARG: &[PrefixedInstruction] = &[
+    PrefixedInstruction::DataDrop,
+    PrefixedInstruction::MemoryFill,
+    PrefixedInstruction::ElemDrop,
+    PrefixedInstruction::TableGrow,
+    PrefixedInstruction::TableSize,
+    PrefixedInstruction::TableFill,
+];
+pub const PREFIXED_INSTRUCTION_WITH_TWO_LEB_ARGS: &[PrefixedInstruction] = &[
+    PrefixedInstruction::MemoryInit,
+    PrefixedInstruction::MemoryCopy,
+    PrefixedInstruction::TableInit,
+    PrefixedInstruction::TableCopy,
+];
+
+impl PrefixedInstruction {
+    pub fn is_float(&self) -> bool {
+        FLOAT_PREFIXED_INSTRUCTIONS.contains(self)
+    }
+
+    /// Number of LEB128 immediates following the sub-opcode
+    pub fn leb_args_count(&self) -> usize {
+        if PREFIXED_INSTRUCTION_WITH_TWO_LEB_ARGS.contains(self) {
+            2
+        } else if PREFIXED_INSTRUCTION_WITH_LEB_ARG.contains(self) {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl TryFrom<u8> for PrefixedInstruction {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        for instr in PREFIXED_INSTRUCTIONS_WITHOUT_ARGS
+            .iter()
+            .chain(PREFIXED_INSTRUCTION_WITH_LEB_ARG)
+            .chain(PREFIXED_INSTRUCTION_WITH_TWO_LEB_ARGS)
+        {
+            if v == *instr as u8 {
+                return Ok(*instr);
+            }
+        }
+        Err(Error::InvalidEnumValue)
+    }
+}
+
+impl From<PrefixedInstruction> for usize {
+    fn from(t: PrefixedInstruction) -> Self {
+        t as usize
+    }
+}
+
+impl<F: FieldExt> Expr<F> for PrefixedInstruction {
+    #[inline]
+    fn expr(&self) -> Expression<F> {
+        Expression::Constant(F::from(*self as u64))
+    }
+}
+
 #[derive(Copy, Clone, Debug, Default)]
 pub struct SharedState {
     pub bytecode_number: u64,
     pub dynamic_indexes_offset: usize,
+    pub module_types_offset: usize,
     pub func_count: usize,
     pub block_level: usize,
+    /// set once the code section uses `memory.init` or `data.drop`
+    pub data_index_used: bool,
 
     pub error_processing_enabled: bool,
     pub error_code: u64,
+    /// only host functions may be imported, see `tables::host_functions`. Off for circuits that
+    /// just parse modules
+    pub host_imports_restricted: bool,
 }
 
 impl SharedState {
     pub fn reset(&mut self) {
         self.bytecode_number = 1;
         self.dynamic_indexes_offset = 0;
+        self.module_types_offset = 0;
         self.func_count = 0;
         self.block_level = 0;
+        self.data_index_used = false;
 
         // self.error_processing_enabled = true;
         self.error_code = 0;
@@ -1,8 +1,16 @@
 use bus_mapping::evm::OpcodeId;
+use eth_types::bytecode::{
+    wasm_entry_function, wasm_function_bodies, wasm_function_locals, wasm_function_types,
+    wasm_imported_function_count, wasm_table_elements, wasm_type_arities,
+};
 use eth_types::{Field, ToLittleEndian, Word};
 use halo2_proofs::circuit::Value;
 
-use crate::{evm_circuit::util::rlc, table::BytecodeFieldTag, util::Challenges};
+use crate::{
+    evm_circuit::util::rlc,
+    table::{BytecodeFieldTag, WasmFunctionTableBinding, WasmFunctionTableTag},
+    util::Challenges,
+};
 
 /// Bytecode
 #[derive(Clone, Debug)]
@@ -14,14 +22,8 @@ pub struct Bytecode {
 }
 
 impl Bytecode {
-    /// Assignments for bytecode table
-    pub fn table_assignments<F: Field>(
-        &self,
-        challenges: &Challenges<Value<F>>,
-    ) -> Vec<[Value<F>; 5]> {
-        let n = 1 + self.bytes.len();
-        let mut rows = Vec::with_capacity(n);
-        let hash = if cfg!(feature = "poseidon-codehash") {
+    fn hash_value<F: Field>(&self, challenges: &Challenges<Value<F>>) -> Value<F> {
+        if cfg!(feature = "poseidon-codehash") {
             challenges
                 .evm_word()
                 .map(|_challenge| rlc::value(&self.hash.to_le_bytes(), F::from(256u64)))
@@ -30,7 +32,17 @@ impl Bytecode {
             challenges
                 .evm_word()
                 .map(|challenge| rlc::value(&self.hash.to_le_bytes(), challenge))
-        };
+        }
+    }
+
+    /// Assignments for bytecode table
+    pub fn table_assignments<F: Field>(
+        &self,
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<[Value<F>; 5]> {
+        let n = 1 + self.bytes.len();
+        let mut rows = Vec::with_capacity(n);
+        let hash = self.hash_value(challenges);
 
         rows.push([
             hash,
@@ -62,6 +74,120 @@ impl Bytecode {
         rows
     }
 
+    /// Assignments for wasm function table, each row comes with how the wasm circuit binds it to
+    /// the module. Null table slots and the locals of imported functions are not decoded from any
+    /// section, the function bodies are bound to the code section itself.
+    pub fn function_table_assignments<F: Field>(
+        &self,
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<(WasmFunctionTableBinding, [Value<F>; 4])> {
+        let hash = self.hash_value(challenges);
+        let elements = wasm_table_elements(&self.bytes).unwrap_or_default();
+        let function_types = wasm_function_types(&self.bytes).unwrap_or_default();
+
+        let mut rows = Vec::with_capacity(1 + elements.len() + function_types.len());
+        rows.push((
+            WasmFunctionTableBinding::Module,
+            [
+                hash,
+                Value::known(F::from(WasmFunctionTableTag::TableSize as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(elements.len() as u64)),
+            ],
+        ));
+        for (idx, element) in elements.iter().enumerate() {
+            let binding = if element.is_some() {
+                WasmFunctionTableBinding::Module
+            } else {
+                WasmFunctionTableBinding::Unbound
+            };
+            rows.push((
+                binding,
+                [
+                    hash,
+                    Value::known(F::from(WasmFunctionTableTag::Element as u64)),
+                    Value::known(F::from(idx as u64)),
+                    Value::known(F::from(element.map_or(0, |func_idx| func_idx + 1))),
+                ],
+            ));
+        }
+        for (idx, type_idx) in function_types.iter().enumerate() {
+            rows.push((
+                WasmFunctionTableBinding::Module,
+                [
+                    hash,
+                    Value::known(F::from(WasmFunctionTableTag::FunctionType as u64)),
+                    Value::known(F::from(idx as u64)),
+                    Value::known(F::from(*type_idx)),
+                ],
+            ));
+        }
+        let function_locals = wasm_function_locals(&self.bytes).unwrap_or_default();
+        let imported_count = wasm_imported_function_count(&self.bytes).unwrap_or_default();
+        let type_arities = wasm_type_arities(&self.bytes).unwrap_or_default();
+        let num_results = |func_idx: usize| {
+            function_types
+                .get(func_idx)
+                .and_then(|type_idx| type_arities.get(*type_idx as usize))
+                .map_or(0, |(_, num_results)| *num_results)
+        };
+        let entry_is_declared =
+            wasm_entry_function(&self.bytes).map_or(false, |idx| idx as usize >= imported_count);
+        let entry_function = wasm_entry_function(&self.bytes).and_then(|idx| {
+            let locals = function_locals.get(idx as usize).copied()?;
+            Some((locals, num_results(idx as usize)))
+        });
+        for (idx, ((num_params, num_locals), num_results)) in entry_function
+            .into_iter()
+            .map(|function| (0, function))
+            .chain(
+                function_locals
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, locals)| (idx + 1, (*locals, num_results(idx)))),
+            )
+        {
+            let binding = match idx {
+                0 if entry_is_declared => WasmFunctionTableBinding::Entry,
+                idx if idx > imported_count => WasmFunctionTableBinding::Module,
+                _ => WasmFunctionTableBinding::Unbound,
+            };
+            for (tag, binding, value) in [
+                (WasmFunctionTableTag::ParamCount, binding, num_params),
+                (WasmFunctionTableTag::LocalCount, binding, num_locals),
+                (
+                    WasmFunctionTableTag::ResultCount,
+                    WasmFunctionTableBinding::Unbound,
+                    num_results,
+                ),
+            ] {
+                rows.push((
+                    binding,
+                    [
+                        hash,
+                        Value::known(F::from(tag as u64)),
+                        Value::known(F::from(idx as u64)),
+                        Value::known(F::from(value)),
+                    ],
+                ));
+            }
+        }
+        // the bodies of the defined functions, the imported ones come first in the index space
+        let bodies = wasm_function_bodies(&self.bytes).unwrap_or_default();
+        for (idx, pc) in bodies.iter().enumerate() {
+            rows.push((
+                WasmFunctionTableBinding::Body,
+                [
+                    hash,
+                    Value::known(F::from(WasmFunctionTableTag::FunctionBody as u64)),
+                    Value::known(F::from((imported_count + idx + 1) as u64)),
+                    Value::known(F::from(*pc)),
+                ],
+            ));
+        }
+        rows
+    }
+
     /// get byte value and is_code pair
     pub fn get(&self, dest: usize) -> [u8; 2] {
         let mut push_data_left = 0;
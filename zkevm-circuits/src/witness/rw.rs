@@ -217,6 +217,30 @@ pub enum Rw {
         global_index: usize,
         value: StackWord,
     },
+    /// Local
+    Local {
+        rw_counter: usize,
+        is_write: bool,
+        frame_id: usize,
+        local_index: usize,
+        value: StackWord,
+    },
+    /// DataDrop
+    DataDrop {
+        rw_counter: usize,
+        is_write: bool,
+        call_id: usize,
+        data_index: usize,
+        is_dropped: bool,
+    },
+    /// ControlFrame
+    ControlFrame {
+        rw_counter: usize,
+        is_write: bool,
+        frame_id: usize,
+        pc: u64,
+        stack_pointer: usize,
+    },
     /// Memory
     Memory {
         rw_counter: usize,
@@ -408,7 +432,9 @@ impl Rw {
 
     pub(crate) fn local_value(&self) -> (StackWord, usize) {
         match self {
-            Self::Stack { value, local_index, .. } => (*value, *local_index),
+            Self::Stack { value, local_index, .. } | Self::Local { value, local_index, .. } => {
+                (*value, *local_index)
+            }
             _ => unreachable!(),
         }
     }
@@ -441,6 +467,27 @@ impl Rw {
         }
     }
 
+    pub fn memory_address(&self) -> u64 {
+        match self {
+            Self::Memory { memory_address, .. } => *memory_address,
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
+    pub fn data_drop_value(&self) -> bool {
+        match self {
+            Self::DataDrop { is_dropped, .. } => *is_dropped,
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
+    pub fn control_frame_value(&self) -> usize {
+        match self {
+            Self::ControlFrame { stack_pointer, .. } => *stack_pointer,
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
     // At this moment is a helper for the EVM circuit until EVM challange API is
     // applied
     pub(crate) fn table_assignment_aux<F: Field>(&self, randomness: F) -> RwRow<F> {
@@ -496,6 +543,9 @@ impl Rw {
             | Self::Memory { rw_counter, .. }
             | Self::Stack { rw_counter, .. }
             | Self::Global { rw_counter, .. }
+            | Self::Local { rw_counter, .. }
+            | Self::DataDrop { rw_counter, .. }
+            | Self::ControlFrame { rw_counter, .. }
             | Self::AccountStorage { rw_counter, .. }
             | Self::TxAccessListAccount { rw_counter, .. }
             | Self::TxAccessListAccountStorage { rw_counter, .. }
@@ -513,6 +563,9 @@ impl Rw {
             Self::Memory { is_write, .. }
             | Self::Stack { is_write, .. }
             | Self::Global { is_write, .. }
+            | Self::Local { is_write, .. }
+            | Self::DataDrop { is_write, .. }
+            | Self::ControlFrame { is_write, .. }
             | Self::AccountStorage { is_write, .. }
             | Self::TxAccessListAccount { is_write, .. }
             | Self::TxAccessListAccountStorage { is_write, .. }
@@ -530,6 +583,9 @@ impl Rw {
             Self::Memory { .. } => RwTableTag::Memory,
             Self::Stack { .. } => RwTableTag::Stack,
             Self::Global { .. } => RwTableTag::Global,
+            Self::Local { .. } => RwTableTag::Local,
+            Self::DataDrop { .. } => RwTableTag::DataDrop,
+            Self::ControlFrame { .. } => RwTableTag::ControlFrame,
             Self::AccountStorage { .. } => RwTableTag::AccountStorage,
             Self::TxAccessListAccount { .. } => RwTableTag::TxAccessListAccount,
             Self::TxAccessListAccountStorage { .. } => RwTableTag::TxAccessListAccountStorage,
@@ -552,7 +608,11 @@ impl Rw {
             Self::CallContext { call_id, .. }
             | Self::Stack { call_id, .. }
             | Self::Global { call_id, .. }
+            | Self::DataDrop { call_id, .. }
             | Self::Memory { call_id, .. } => Some(*call_id),
+            Self::Local { frame_id, .. } | Self::ControlFrame { frame_id, .. } => {
+                Some(*frame_id)
+            }
             Self::Start { .. } | Self::Account { .. } => None,
         }
     }
@@ -578,6 +638,13 @@ impl Rw {
             Self::Global { global_index, .. } => {
                 Some(Address::from_low_u64_be(*global_index as u64))
             }
+            Self::Local { local_index, .. } => {
+                Some(Address::from_low_u64_be(*local_index as u64))
+            }
+            Self::DataDrop { data_index, .. } => {
+                Some(Address::from_low_u64_be(*data_index as u64))
+            }
+            Self::ControlFrame { pc, .. } => Some(Address::from_low_u64_be(*pc)),
             Self::TxLog {
                 log_id,
                 field_tag,
@@ -603,6 +670,9 @@ impl Rw {
             | Self::Memory { .. }
             | Self::Stack { .. }
             | Self::Global { .. }
+            | Self::Local { .. }
+            | Self::DataDrop { .. }
+            | Self::ControlFrame { .. }
             | Self::AccountStorage { .. }
             | Self::TxAccessListAccount { .. }
             | Self::TxAccessListAccountStorage { .. }
@@ -619,6 +689,9 @@ impl Rw {
             | Self::CallContext { .. }
             | Self::Stack { .. }
             | Self::Global { .. }
+            | Self::Local { .. }
+            | Self::DataDrop { .. }
+            | Self::ControlFrame { .. }
             | Self::Memory { .. }
             | Self::TxRefund { .. }
             | Self::Account { .. }
@@ -671,7 +744,7 @@ impl Rw {
             Self::Stack { value, .. } => {
                 value.to_scalar().unwrap()
             }
-            Self::Global { value, .. } => {
+            Self::Global { value, .. } | Self::Local { value, .. } => {
                 value.to_scalar().unwrap()
             }
 
@@ -684,6 +757,8 @@ impl Rw {
 
             Self::TxAccessListAccount { is_warm, .. }
             | Self::TxAccessListAccountStorage { is_warm, .. } => F::from(*is_warm as u64),
+            Self::DataDrop { is_dropped, .. } => F::from(*is_dropped as u64),
+            Self::ControlFrame { stack_pointer, .. } => F::from(*stack_pointer as u64),
             Self::Memory { byte, .. } => F::from(u64::from(*byte)),
             Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => F::from(*value),
         }
@@ -721,6 +796,9 @@ impl Rw {
             Self::Start { .. }
             | Self::Stack { .. }
             | Self::Global { .. }
+            | Self::Local { .. }
+            | Self::DataDrop { .. }
+            | Self::ControlFrame { .. }
             | Self::Memory { .. }
             | Self::CallContext { .. }
             | Self::TxLog { .. }
@@ -876,7 +954,11 @@ impl From<&operation::OperationContainer> for RwMap {
                         CallContextField::GasLeft => CallContextFieldTag::GasLeft,
                         CallContextField::MemorySize => CallContextFieldTag::MemorySize,
                         CallContextField::ReversibleWriteCounter => CallContextFieldTag::ReversibleWriteCounter,
+                        CallContextField::GrownMemoryPages => CallContextFieldTag::GrownMemoryPages,
                         CallContextField::InternalFunctionId => CallContextFieldTag::InternalFunctionId,
+                        CallContextField::LocalsBase => CallContextFieldTag::LocalsBase,
+                        CallContextField::LocalsFrame => CallContextFieldTag::LocalsFrame,
+                        CallContextField::FunctionDepth => CallContextFieldTag::FunctionDepth,
                     },
                     value: op.op().value,
                 })
@@ -911,6 +993,48 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            RwTableTag::Local,
+            container
+                .locals
+                .iter()
+                .map(|op| Rw::Local {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    frame_id: op.op().frame_id(),
+                    local_index: op.op().address() as usize,
+                    value: *op.op().value(),
+                })
+                .collect(),
+        );
+        rws.insert(
+            RwTableTag::DataDrop,
+            container
+                .data_drops
+                .iter()
+                .map(|op| Rw::DataDrop {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    call_id: op.op().call_id(),
+                    data_index: op.op().address() as usize,
+                    is_dropped: op.op().is_dropped(),
+                })
+                .collect(),
+        );
+        rws.insert(
+            RwTableTag::ControlFrame,
+            container
+                .control_frames
+                .iter()
+                .map(|op| Rw::ControlFrame {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    frame_id: op.op().frame_id(),
+                    pc: op.op().address(),
+                    stack_pointer: op.op().stack_pointer(),
+                })
+                .collect(),
+        );
         rws.insert(
             RwTableTag::Memory,
             container
@@ -59,6 +59,12 @@ pub struct ExecStep {
     pub max_stack_height: u32,
     /// Num locals
     pub num_locals: u32,
+    /// Control frame opened, closed or targeted by the step
+    pub control_frame: Option<circuit_input_builder::ControlFrame>,
+    /// Function frame left by a return to its caller
+    pub function_frame: Option<circuit_input_builder::FunctionFrame>,
+    /// Immediate arguments of the instruction
+    pub params: Vec<u64>,
 }
 
 impl fmt::Debug for ExecStep {
@@ -115,6 +121,13 @@ impl From<&ExecError> for ExecutionState {
             },
             ExecError::InvalidCreationCode => ExecutionState::ErrorInvalidCreationCode,
             ExecError::InvalidJump => ExecutionState::ErrorInvalidJump,
+            ExecError::InvalidIndirectCall => ExecutionState::ErrorInvalidIndirectCall,
+            ExecError::CallStackExhausted => ExecutionState::ErrorCallStackExhausted,
+            ExecError::Unreachable => ExecutionState::ErrorUnreachable,
+            ExecError::IntegerDivideByZero | ExecError::IntegerOverflow => {
+                ExecutionState::ErrorIntegerDivision
+            }
+            ExecError::MemoryOutOfBounds => ExecutionState::ErrorMemoryOutOfBounds,
             ExecError::ReturnDataOutOfBounds => ExecutionState::ErrorReturnDataOutOfBound,
             ExecError::CodeStoreOutOfGas | ExecError::MaxCodeSizeExceeded => {
                 ExecutionState::ErrorCodeStore
@@ -196,7 +209,12 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
 
                     OpcodeId::I32WrapI64 |
                     OpcodeId::I64ExtendSI32 |
-                    OpcodeId::I64ExtendUI32 => ExecutionState::WASM_CONVERSION,
+                    OpcodeId::I64ExtendUI32 |
+                    OpcodeId::I32Extend8S |
+                    OpcodeId::I32Extend16S |
+                    OpcodeId::I64Extend8S |
+                    OpcodeId::I64Extend16S |
+                    OpcodeId::I64Extend32S => ExecutionState::WASM_CONVERSION,
 
                     OpcodeId::GetGlobal |
                     OpcodeId::SetGlobal => ExecutionState::WASM_GLOBAL,
@@ -205,13 +223,53 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::SetLocal |
                     OpcodeId::TeeLocal => ExecutionState::WASM_LOCAL,
 
+                    OpcodeId::I32Load |
+                    OpcodeId::I64Load |
+                    OpcodeId::I32Load8S |
+                    OpcodeId::I32Load8U |
+                    OpcodeId::I32Load16S |
+                    OpcodeId::I32Load16U |
+                    OpcodeId::I64Load8S |
+                    OpcodeId::I64Load8U |
+                    OpcodeId::I64Load16S |
+                    OpcodeId::I64Load16U |
+                    OpcodeId::I64Load32S |
+                    OpcodeId::I64Load32U => ExecutionState::WASM_LOAD,
+
+                    OpcodeId::I32Store |
+                    OpcodeId::I64Store |
+                    OpcodeId::I32Store8 |
+                    OpcodeId::I32Store16 |
+                    OpcodeId::I64Store8 |
+                    OpcodeId::I64Store16 |
+                    OpcodeId::I64Store32 => ExecutionState::WASM_STORE,
+
+                    OpcodeId::CurrentMemory |
+                    OpcodeId::GrowMemory => ExecutionState::WASM_MEMORY_GROW,
+
+                    OpcodeId::MemoryCopy |
+                    OpcodeId::MemoryFill |
+                    OpcodeId::MemoryInit |
+                    OpcodeId::DataDrop => ExecutionState::WASM_BULK_MEMORY,
+
                     OpcodeId::Call |
                     OpcodeId::CallIndirect => ExecutionState::WASM_CALL,
 
                     OpcodeId::Return |
+                    OpcodeId::End |
                     OpcodeId::Br |
-                    OpcodeId::BrIf |
-                    OpcodeId::BrTable => ExecutionState::WASM_BREAK,
+                    OpcodeId::BrIf if step.function_frame.is_some() => ExecutionState::WASM_RETURN,
+
+                    OpcodeId::Return |
+                    OpcodeId::Br |
+                    OpcodeId::BrIf => ExecutionState::WASM_BREAK,
+
+                    OpcodeId::Nop |
+                    OpcodeId::Block |
+                    OpcodeId::Loop |
+                    OpcodeId::If |
+                    OpcodeId::Else => ExecutionState::WASM_BLOCK,
+                    OpcodeId::End if step.control_frame.is_some() => ExecutionState::WASM_BLOCK,
 
                     OpcodeId::End => ExecutionState::WASM_END,
 
@@ -319,6 +377,9 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
                     operation::Target::Memory => RwTableTag::Memory,
                     operation::Target::Stack => RwTableTag::Stack,
                     operation::Target::Global => RwTableTag::Global,
+                    operation::Target::Local => RwTableTag::Local,
+                    operation::Target::DataDrop => RwTableTag::DataDrop,
+                    operation::Target::ControlFrame => RwTableTag::ControlFrame,
                     operation::Target::Storage => RwTableTag::AccountStorage,
                     operation::Target::TxAccessListAccount => RwTableTag::TxAccessListAccount,
                     operation::Target::TxAccessListAccountStorage => {
@@ -353,5 +414,8 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
         function_index: step.function_index,
         max_stack_height: step.function_index,
         num_locals: step.num_locals,
+        control_frame: step.control_frame,
+        function_frame: step.function_frame,
+        params: step.params.clone(),
     }
 }
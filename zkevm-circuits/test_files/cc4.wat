@@ -0,0 +1,16 @@
+(module
+  (type (;0;) (func))
+  (memory (;0;) 1)
+  (func (;0;) (type 0)
+    i32.const 0
+    i32.const 16
+    i32.const 8
+    memory.copy
+    i32.const 0
+    i32.const 255
+    i32.const 8
+    memory.fill
+    data.drop 0)
+  (export "main" (func 0))
+  (data (;0;) "\01\02\03\04\05\06\07\08")
+)
@@ -0,0 +1,14 @@
+(module
+  (type (;0;) (func))
+  (func (;0;) (type 0)
+    i32.const 128
+    i32.extend8_s
+    i32.extend16_s
+    drop
+    i64.const 32768
+    i64.extend8_s
+    i64.extend16_s
+    i64.extend32_s
+    drop)
+  (export "main" (func 0))
+)
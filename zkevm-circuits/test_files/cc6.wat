@@ -0,0 +1,10 @@
+(module
+  (type (;0;) (func (param f32) (result f64)))
+  (func (;0;) (type 0) (param f32) (result f64)
+    (local f64)
+    f64.const 1.5
+    local.get 0
+    f64.promote_f32
+    f64.add)
+  (export "main" (func 0))
+)
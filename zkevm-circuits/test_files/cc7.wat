@@ -0,0 +1,26 @@
+(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0) (param i32) (result i32)
+    block (result i32)
+      loop
+        local.get 0
+        i32.const 1
+        i32.sub
+        local.tee 0
+        br_if 0
+      end
+      i32.const 2
+      br 0
+    end
+    local.get 0
+    if (result i32)
+      i32.const 3
+    else
+      block (result i32)
+        i32.const 4
+        br 1
+      end
+    end
+    i32.add)
+  (export "main" (func 0))
+)